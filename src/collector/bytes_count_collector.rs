@@ -0,0 +1,144 @@
+use collector::Collector;
+use fastfield::BytesFastFieldReader;
+use schema::Field;
+use std::collections::HashMap;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector counting the number of matching documents
+/// for each distinct value of a bytes fast field.
+///
+/// When the bytes fast field holds a term, this gives
+/// the document frequency of each term among the matching documents.
+pub struct BytesCountCollector {
+    field: Field,
+    counts: HashMap<Vec<u8>, u64>,
+    ff_reader: Option<BytesFastFieldReader>,
+}
+
+impl BytesCountCollector {
+    /// Creates a collector counting the values of the given bytes fast field.
+    pub fn for_field(field: Field) -> BytesCountCollector {
+        BytesCountCollector {
+            field,
+            counts: HashMap::new(),
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the number of matching documents having the given value.
+    pub fn count(&self, value: &[u8]) -> u64 {
+        self.counts.get(value).cloned().unwrap_or(0u64)
+    }
+
+    /// Returns the counts of each of the values.
+    pub fn counts(&self) -> &HashMap<Vec<u8>, u64> {
+        &self.counts
+    }
+
+    /// Adds the counts of `other` to the counts of this collector.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    pub fn merge(&mut self, other: BytesCountCollector) {
+        for (value, count) in other.counts {
+            *self.counts.entry(value).or_insert(0) += count;
+        }
+    }
+
+    /// Returns the counts of each of the values.
+    pub fn harvest(self) -> HashMap<Vec<u8>, u64> {
+        self.counts
+    }
+}
+
+impl Collector for BytesCountCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(reader.bytes_fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let value = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get_val(doc);
+        if let Some(count) = self.counts.get_mut(value) {
+            *count += 1;
+            return;
+        }
+        self.counts.insert(value.to_vec(), 1);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::BytesCountCollector;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use Index;
+
+    #[test]
+    fn test_bytes_count_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let term_field = schema_builder.add_bytes_field("term");
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", term_field => b"apple".to_vec()))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "b", term_field => b"pear".to_vec()))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", term_field => b"apple".to_vec()))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", term_field => b"pear".to_vec()))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", term_field => b"apple".to_vec()))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", term_field => b"".to_vec()))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = BytesCountCollector::for_field(term_field);
+        searcher.search(&query, &mut collector).unwrap();
+        assert_eq!(collector.counts().len(), 3);
+        assert_eq!(collector.count(b"apple"), 3);
+        assert_eq!(collector.count(b"pear"), 1);
+        assert_eq!(collector.count(b""), 1);
+        assert_eq!(collector.count(b"banana"), 0);
+
+        let mut all_collector = BytesCountCollector::for_field(term_field);
+        searcher.search(&AllQuery, &mut all_collector).unwrap();
+        collector.merge(all_collector);
+        let counts = collector.harvest();
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&b"apple"[..]], 6);
+        assert_eq!(counts[&b"pear"[..]], 3);
+        assert_eq!(counts[&b""[..]], 2);
+    }
+}
@@ -0,0 +1,268 @@
+use collector::Collector;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector wrapper that stops forwarding documents to the
+/// underlying collector as soon as a shared cancellation flag is set.
+///
+/// The flag is checked on every call to `collect`, and reported through
+/// `Collector::is_done`: the search then stops going through the documents,
+/// even in the middle of a large segment, and skips the remaining segments.
+///
+/// Once the search is done, `is_cancelled()` tells whether the results
+/// of the underlying collector are partial.
+pub struct CancellableCollector<C: Collector> {
+    inner: C,
+    cancel: Arc<AtomicBool>,
+    cancelled: bool,
+}
+
+impl<C: Collector> CancellableCollector<C> {
+    /// Wraps `inner`. Setting `cancel` to `true` cancels the collection.
+    pub fn new(inner: C, cancel: Arc<AtomicBool>) -> CancellableCollector<C> {
+        CancellableCollector {
+            inner,
+            cancel,
+            cancelled: false,
+        }
+    }
+
+    /// Returns true iff the cancellation flag was observed during the
+    /// collection, in which case some matching documents were not
+    /// passed to the underlying collector.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Returns a reference to the underlying collector.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the underlying collector.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    // Records the cancellation, and returns true iff the collection
+    // was cancelled.
+    fn check_cancelled(&mut self) -> bool {
+        if !self.cancelled && self.cancel.load(Ordering::Relaxed) {
+            self.cancelled = true;
+        }
+        self.cancelled
+    }
+}
+
+impl<C: Collector> Collector for CancellableCollector<C> {
+    fn set_segment(
+        &mut self,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<()> {
+        if self.cancelled {
+            return Ok(());
+        }
+        self.inner.set_segment(segment_local_id, segment)
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.check_cancelled() {
+            return;
+        }
+        self.inner.collect(doc, score);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.inner.requires_scoring()
+    }
+
+    fn score_threshold(&self) -> Option<Score> {
+        self.inner.score_threshold()
+    }
+
+    fn accepts_blocks(&self) -> bool {
+        self.inner.accepts_blocks()
+    }
+
+    fn collect_block(&mut self, docs: &[DocId]) {
+        if self.check_cancelled() {
+            return;
+        }
+        self.inner.collect_block(docs);
+    }
+
+    fn is_done(&self) -> bool {
+        self.cancelled || self.cancel.load(Ordering::Relaxed) || self.inner.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::CancellableCollector;
+    use collector::{Collector, CountCollector};
+    use query::AllQuery;
+    use schema::SchemaBuilder;
+    use schema::INT_INDEXED;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use DocId;
+    use Index;
+    use Result;
+    use Score;
+    use SegmentLocalId;
+    use SegmentReader;
+
+    #[test]
+    fn test_cancellable_collector() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut collector = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        assert!(collector.accepts_blocks());
+        collector.collect(1, 0.2);
+        collector.collect_block(&[2, 3]);
+        assert!(!collector.is_cancelled());
+        assert!(!collector.is_done());
+        cancel.store(true, Ordering::SeqCst);
+        assert!(collector.is_done());
+        collector.collect(4, 0.5);
+        collector.collect_block(&[5, 6]);
+        assert!(collector.is_cancelled());
+        assert_eq!(collector.into_inner().count(), 3);
+    }
+
+    /// Counts the calls made by the search, before the
+    /// `CancellableCollector` filters them out.
+    struct CallCountingCollector<C: Collector> {
+        inner: C,
+        num_docs: usize,
+        num_segments: usize,
+    }
+
+    impl<C: Collector> Collector for CallCountingCollector<C> {
+        fn set_segment(
+            &mut self,
+            segment_ord: SegmentLocalId,
+            reader: &SegmentReader,
+        ) -> Result<()> {
+            self.num_segments += 1;
+            self.inner.set_segment(segment_ord, reader)
+        }
+
+        fn collect(&mut self, doc: DocId, score: Score) {
+            self.num_docs += 1;
+            self.inner.collect(doc, score);
+        }
+
+        fn requires_scoring(&self) -> bool {
+            self.inner.requires_scoring()
+        }
+
+        fn accepts_blocks(&self) -> bool {
+            self.inner.accepts_blocks()
+        }
+
+        fn collect_block(&mut self, docs: &[DocId]) {
+            self.num_docs += docs.len();
+            self.inner.collect_block(docs);
+        }
+
+        fn is_done(&self) -> bool {
+            self.inner.is_done()
+        }
+    }
+
+    /// Counts documents and flips the cancellation flag
+    /// after a given number of documents.
+    struct FlipFlagCollector {
+        count: usize,
+        flip_after: usize,
+        cancel: Arc<AtomicBool>,
+        accepts_blocks: bool,
+    }
+
+    impl Collector for FlipFlagCollector {
+        fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+            Ok(())
+        }
+
+        fn collect(&mut self, _: DocId, _: Score) {
+            self.count += 1;
+            if self.count == self.flip_after {
+                self.cancel.store(true, Ordering::SeqCst);
+            }
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+
+        fn accepts_blocks(&self) -> bool {
+            self.accepts_blocks
+        }
+
+        fn collect_block(&mut self, docs: &[DocId]) {
+            for &doc in docs {
+                self.collect(doc, 1f32);
+            }
+        }
+    }
+
+    fn search_until_flipped(flip_after: usize, accepts_blocks: bool) {
+        let mut schema_builder = SchemaBuilder::default();
+        let val_field = schema_builder.add_u64_field("val", INT_INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for val in 0u64..10_000u64 {
+                index_writer.add_document(doc!(val_field => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            for val in 0u64..100u64 {
+                index_writer.add_document(doc!(val_field => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flip_flag_collector = FlipFlagCollector {
+            count: 0,
+            flip_after,
+            cancel: cancel.clone(),
+            accepts_blocks,
+        };
+        let mut collector = CallCountingCollector {
+            inner: CancellableCollector::new(flip_flag_collector, cancel),
+            num_docs: 0,
+            num_segments: 0,
+        };
+        assert_eq!(collector.accepts_blocks(), accepts_blocks);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        assert!(collector.inner.is_cancelled());
+        assert!(collector.inner.is_done());
+        // The search stopped going through the documents
+        // shortly after the cancellation, and skipped the second segment.
+        assert!(collector.num_docs < 2_000);
+        assert_eq!(collector.num_segments, 1);
+        assert!(collector.inner.inner().count >= flip_after);
+        assert!(collector.inner.inner().count < 2_000);
+    }
+
+    #[test]
+    fn test_cancellable_collector_mid_search() {
+        search_until_flipped(10, false);
+    }
+
+    #[test]
+    fn test_cancellable_collector_mid_search_blocks() {
+        search_until_flipped(10, true);
+    }
+}
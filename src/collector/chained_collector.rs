@@ -20,6 +20,12 @@ impl Collector for DoNothingCollector {
     fn requires_scoring(&self) -> bool {
         false
     }
+    // It never needs any document, so that it does not keep
+    // a chain going once the other collectors are done.
+    #[inline]
+    fn is_done(&self) -> bool {
+        true
+    }
 }
 
 /// Zero-cost abstraction used to collect on multiple collectors.
@@ -44,16 +50,16 @@ impl Collector for DoNothingCollector {
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -110,6 +116,10 @@ impl<Left: Collector, Right: Collector> Collector for ChainedCollector<Left, Rig
     fn requires_scoring(&self) -> bool {
         self.left.requires_scoring() || self.right.requires_scoring()
     }
+
+    fn is_done(&self) -> bool {
+        self.left.is_done() && self.right.is_done()
+    }
 }
 
 /// Creates a `ChainedCollector`
@@ -124,7 +134,12 @@ pub fn chain() -> ChainedCollector<DoNothingCollector, DoNothingCollector> {
 mod tests {
 
     use super::*;
-    use collector::{Collector, CountCollector, TopCollector};
+    use collector::{CancellableCollector, Collector, CountCollector, TopCollector};
+    use query::AllQuery;
+    use schema::{SchemaBuilder, INT_INDEXED};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use Index;
 
     #[test]
     fn test_chained_collector() {
@@ -139,4 +154,49 @@ mod tests {
         assert_eq!(count_collector.count(), 3);
         assert!(top_collector.at_capacity());
     }
+
+    #[test]
+    fn test_chained_collector_is_done() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut left = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        let mut right = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        let mut count_collector = CountCollector::default();
+        assert!(chain().is_done());
+        {
+            let collectors = chain().push(&mut left).push(&mut right);
+            assert!(!collectors.is_done());
+            cancel.store(true, Ordering::SeqCst);
+            assert!(collectors.is_done());
+        }
+        // The count collector still needs documents.
+        let collectors = chain().push(&mut left).push(&mut count_collector);
+        assert!(!collectors.is_done());
+    }
+
+    #[test]
+    fn test_chained_collector_cancelled_search() {
+        let mut schema_builder = SchemaBuilder::default();
+        let val_field = schema_builder.add_u64_field("val", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for val in 0u64..100u64 {
+                index_writer.add_document(doc!(val_field => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let cancel = Arc::new(AtomicBool::new(true));
+        let mut left = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        let mut right = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        {
+            let mut collectors = chain().push(&mut left).push(&mut right);
+            index.searcher().search(&AllQuery, &mut collectors).unwrap();
+        }
+        // The search stopped before pushing any document to the chain.
+        assert!(!left.is_cancelled());
+        assert!(!right.is_cancelled());
+        assert_eq!(left.inner().count(), 0);
+        assert_eq!(right.inner().count(), 0);
+    }
 }
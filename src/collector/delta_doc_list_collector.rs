@@ -0,0 +1,181 @@
+use collector::Collector;
+use common::VInt;
+use std::io;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Decodes a doc id list encoded by the `DeltaDocListCollector`.
+pub fn decode_doc_list(mut encoded: &[u8]) -> io::Result<Vec<DocId>> {
+    let mut docs = Vec::new();
+    let mut doc = 0u64;
+    while !encoded.is_empty() {
+        doc += VInt::deserialize_u64(&mut encoded)?;
+        docs.push(doc as DocId);
+    }
+    Ok(docs)
+}
+
+/// Collector encoding the doc ids of the matching documents
+/// of each segment in a compact form.
+///
+/// Within a segment, documents are collected by increasing doc id.
+/// Each doc id is encoded as its difference with the previous doc id
+/// of the segment (the first doc id is kept as is), and differences are
+/// written as `VInt`s.
+///
+/// The result is smaller than a `Vec<DocAddress>` by a large factor,
+/// which is useful to ship large match sets over the wire.
+/// The encoded lists can be decoded with `decode_doc_list`.
+///
+/// # Panics
+/// Collecting a doc id that is not greater than the previous doc id
+/// of the segment panics, as it cannot be delta-encoded.
+pub struct DeltaDocListCollector {
+    segment_doc_lists: Vec<(SegmentLocalId, Vec<u8>)>,
+    previous_doc: DocId,
+}
+
+impl Default for DeltaDocListCollector {
+    fn default() -> DeltaDocListCollector {
+        DeltaDocListCollector {
+            segment_doc_lists: Vec::new(),
+            previous_doc: 0,
+        }
+    }
+}
+
+impl DeltaDocListCollector {
+    /// Returns the encoded doc id lists of the segments
+    /// containing at least one matching document,
+    /// ordered by segment ordinal.
+    pub fn harvest(self) -> Vec<(SegmentLocalId, Vec<u8>)> {
+        self.segment_doc_lists
+            .into_iter()
+            .filter(|&(_, ref encoded)| !encoded.is_empty())
+            .collect()
+    }
+}
+
+impl Collector for DeltaDocListCollector {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_doc_lists.push((segment_local_id, Vec::new()));
+        self.previous_doc = 0;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let previous_doc = self.previous_doc;
+        let encoded = &mut self
+            .segment_doc_lists
+            .last_mut()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .1;
+        assert!(
+            encoded.is_empty() || doc > previous_doc,
+            "Doc ids must be collected in increasing order: got {} after {}.",
+            doc,
+            previous_doc
+        );
+        VInt(u64::from(doc - previous_doc)).serialize_into_vec(encoded);
+        self.previous_doc = doc;
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{decode_doc_list, DeltaDocListCollector};
+    use collector::tests::TestCollector;
+    use collector::Collector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use Index;
+
+    #[test]
+    fn test_decode_doc_list_empty() {
+        assert!(decode_doc_list(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Doc ids must be collected in increasing order")]
+    fn test_delta_doc_list_collector_unordered_docs() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut collector = DeltaDocListCollector::default();
+        collector
+            .set_segment(0, searcher.segment_reader(0))
+            .unwrap();
+        collector.collect(3, 1f32);
+        collector.collect(2, 1f32);
+    }
+
+    #[test]
+    fn test_delta_doc_list_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1_000 {
+                if i % 3 == 0 {
+                    index_writer.add_document(doc!(text_field => "a")).unwrap();
+                } else {
+                    index_writer.add_document(doc!(text_field => "b")).unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("a").unwrap();
+
+        let mut collector = DeltaDocListCollector::default();
+        searcher.search(&*query, &mut collector).unwrap();
+        let segment_doc_lists = collector.harvest();
+        assert_eq!(segment_doc_lists.len(), 2);
+
+        let mut decoded_docs = Vec::new();
+        for &(segment_local_id, ref encoded) in &segment_doc_lists {
+            let segment_reader = searcher.segment_reader(segment_local_id);
+            let offset: u32 = searcher.segment_readers()[..segment_local_id as usize]
+                .iter()
+                .map(|segment_reader| segment_reader.max_doc())
+                .sum();
+            let docs = decode_doc_list(encoded).unwrap();
+            assert!(docs.iter().all(|&doc| doc < segment_reader.max_doc()));
+            decoded_docs.extend(docs.into_iter().map(|doc| doc + offset));
+        }
+        let mut test_collector = TestCollector::default();
+        searcher.search(&*query, &mut test_collector).unwrap();
+        assert_eq!(decoded_docs, test_collector.docs());
+        let num_encoded_bytes: usize = segment_doc_lists
+            .iter()
+            .map(|&(_, ref encoded)| encoded.len())
+            .sum();
+        assert_eq!(decoded_docs.len(), 335);
+        assert_eq!(num_encoded_bytes, 335);
+    }
+}
@@ -0,0 +1,146 @@
+use collector::Collector;
+use std::collections::HashSet;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector comparing the set of matching documents
+/// with the set of documents matched by a previous search.
+///
+/// This is typically used to display the results that are new
+/// since the last time a query was run.
+///
+/// `DocAddress`es are only meaningful for a given `Searcher`,
+/// so the previous set of documents must have been computed against
+/// the same `Searcher`.
+pub struct DiffCollector {
+    previous: HashSet<DocAddress>,
+    current: HashSet<DocAddress>,
+    segment_local_id: SegmentLocalId,
+}
+
+impl DiffCollector {
+    /// Creates a collector comparing the matching documents with `previous`.
+    pub fn new(previous: HashSet<DocAddress>) -> DiffCollector {
+        DiffCollector {
+            previous,
+            current: HashSet::new(),
+            segment_local_id: 0,
+        }
+    }
+
+    /// Returns the set of documents matched by the current search.
+    pub fn current(&self) -> &HashSet<DocAddress> {
+        &self.current
+    }
+
+    /// Returns the documents that match the current search,
+    /// but were not in the previous set, sorted by `DocAddress`.
+    pub fn added(&self) -> Vec<DocAddress> {
+        let mut added: Vec<DocAddress> = self
+            .current
+            .difference(&self.previous)
+            .cloned()
+            .collect();
+        added.sort();
+        added
+    }
+
+    /// Returns the documents of the previous set that do not
+    /// match the current search anymore, sorted by `DocAddress`.
+    pub fn removed(&self) -> Vec<DocAddress> {
+        let mut removed: Vec<DocAddress> = self
+            .previous
+            .difference(&self.current)
+            .cloned()
+            .collect();
+        removed.sort();
+        removed
+    }
+}
+
+impl Collector for DiffCollector {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_local_id = segment_local_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        self.current.insert(DocAddress(self.segment_local_id, doc));
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::DiffCollector;
+    use query::TermQuery;
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use std::collections::HashSet;
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_diff_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "b c"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let search = |text: &str, previous: HashSet<DocAddress>| {
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            let mut diff_collector = DiffCollector::new(previous);
+            searcher.search(&query, &mut diff_collector).unwrap();
+            diff_collector
+        };
+        let a_docs = search("a", HashSet::new());
+        assert_eq!(
+            a_docs.added(),
+            vec![DocAddress(0, 0), DocAddress(0, 1), DocAddress(1, 1)]
+        );
+        assert!(a_docs.removed().is_empty());
+
+        // overlapping sets
+        let b_docs = search("b", a_docs.current().clone());
+        assert_eq!(b_docs.added(), vec![DocAddress(0, 2), DocAddress(1, 0)]);
+        assert_eq!(b_docs.removed(), vec![DocAddress(0, 0), DocAddress(1, 1)]);
+
+        // distinct sets
+        let c_docs = search("c", a_docs.current().clone());
+        assert_eq!(c_docs.added(), vec![DocAddress(1, 0)]);
+        assert_eq!(
+            c_docs.removed(),
+            vec![DocAddress(0, 0), DocAddress(0, 1), DocAddress(1, 1)]
+        );
+
+        // identical sets
+        let same_docs = search("a", a_docs.current().clone());
+        assert!(same_docs.added().is_empty());
+        assert!(same_docs.removed().is_empty());
+    }
+}
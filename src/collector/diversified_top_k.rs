@@ -0,0 +1,317 @@
+use collector::top_collector::TopCollector;
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+// Best remaining candidate of a value, along with its diversified score.
+//
+// The ordering is the one of the diversified score, ties being broken
+// in favor of the lowest `DocAddress`.
+struct ValueHead {
+    diversified_score: Score,
+    score: Score,
+    doc_address: DocAddress,
+    value: u64,
+}
+
+impl Ord for ValueHead {
+    fn cmp(&self, other: &ValueHead) -> Ordering {
+        self.diversified_score
+            .partial_cmp(&other.diversified_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.doc_address.cmp(&self.doc_address))
+    }
+}
+
+impl PartialOrd for ValueHead {
+    fn partial_cmp(&self, other: &ValueHead) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for ValueHead {
+    fn eq(&self, other: &ValueHead) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ValueHead {}
+
+/// Collector selecting `k` documents balancing their score against the
+/// diversity of the values of a `u64` fast field, in the manner of
+/// maximal marginal relevance.
+///
+/// The documents are selected greedily. At each step, the collector
+/// picks the document maximizing
+///
+/// ```text
+/// score_weight * score - lambda * (number of selected documents sharing its value)
+/// ```
+///
+/// so that a document whose value was already selected needs a higher
+/// score to be picked. With a `lambda` of `0`, the selection is the plain
+/// top `k` by score.
+///
+/// Since documents sharing a value are always picked by decreasing score,
+/// only the `k` best documents of each value can ever be selected:
+/// this is the candidate pool retained by the collector, and merged by
+/// `.merge(...)`. Its size is therefore bounded by `k` times the number
+/// of distinct values.
+///
+/// ```rust
+/// use tantivy::collector::DiversifiedTopK;
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let author_field = schema_builder.add_u64_field("author", FAST);
+///
+/// let collector = DiversifiedTopK::new(1.0, author_field, 0.5, 10);
+/// ```
+pub struct DiversifiedTopK {
+    score_weight: Score,
+    diversity_field: Field,
+    lambda: Score,
+    k: usize,
+    candidates: HashMap<u64, TopCollector<Score>>,
+    segment_id: SegmentLocalId,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl DiversifiedTopK {
+    /// Creates a collector selecting `k` documents, with a penalty of
+    /// `lambda` for each selected document sharing their value of
+    /// `diversity_field`.
+    ///
+    /// # Panics
+    /// Panics if `score_weight` is not strictly positive,
+    /// if `lambda` is negative, or if `k` is `0`.
+    pub fn new(
+        score_weight: Score,
+        diversity_field: Field,
+        lambda: Score,
+        k: usize,
+    ) -> DiversifiedTopK {
+        assert!(
+            score_weight > 0.0,
+            "The score weight must be strictly positive."
+        );
+        assert!(lambda >= 0.0, "Lambda must not be negative.");
+        assert!(k > 0, "k must be strictly positive.");
+        DiversifiedTopK {
+            score_weight,
+            diversity_field,
+            lambda,
+            k,
+            candidates: HashMap::new(),
+            segment_id: 0,
+            ff_reader: None,
+        }
+    }
+
+    fn push_candidate(&mut self, value: u64, score: Score, doc_address: DocAddress) {
+        let k = self.k;
+        let candidates = self
+            .candidates
+            .entry(value)
+            .or_insert_with(|| TopCollector::with_limit(k));
+        candidates.set_segment_id(doc_address.segment_ord());
+        candidates.collect(doc_address.doc(), score);
+    }
+
+    /// Adds the candidates retained by `other` to this collector.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    ///
+    /// # Panics
+    /// Panics if the two collectors do not select the same number of documents.
+    pub fn merge(&mut self, other: DiversifiedTopK) {
+        assert_eq!(
+            self.k, other.k,
+            "Cannot merge collectors selecting a different number of documents."
+        );
+        for (value, candidates) in other.candidates {
+            for (score, doc_address) in candidates.top_docs() {
+                self.push_candidate(value, score, doc_address);
+            }
+        }
+    }
+
+    /// Returns the (at most) `k` selected documents, in the order
+    /// of their selection, along with their original score.
+    pub fn top_docs(&self) -> Vec<(Score, DocAddress)> {
+        let mut remaining: HashMap<u64, ::std::vec::IntoIter<(Score, DocAddress)>> = self
+            .candidates
+            .iter()
+            .map(|(&value, candidates)| (value, candidates.top_docs().into_iter()))
+            .collect();
+        let mut num_selected: HashMap<u64, usize> = HashMap::new();
+        let mut heads: BinaryHeap<ValueHead> = BinaryHeap::new();
+        for (&value, value_candidates) in &mut remaining {
+            if let Some((score, doc_address)) = value_candidates.next() {
+                heads.push(ValueHead {
+                    diversified_score: self.score_weight * score,
+                    score,
+                    doc_address,
+                    value,
+                });
+            }
+        }
+        let mut selected = Vec::with_capacity(self.k);
+        while selected.len() < self.k {
+            let head = match heads.pop() {
+                Some(head) => head,
+                None => break,
+            };
+            selected.push((head.score, head.doc_address));
+            let num_selected_for_value = {
+                let count = num_selected.entry(head.value).or_insert(0);
+                *count += 1;
+                *count
+            };
+            if let Some((score, doc_address)) = remaining
+                .get_mut(&head.value)
+                .and_then(|value_candidates| value_candidates.next())
+            {
+                heads.push(ValueHead {
+                    diversified_score: self.score_weight * score
+                        - self.lambda * num_selected_for_value as Score,
+                    score,
+                    doc_address,
+                    value: head.value,
+                });
+            }
+        }
+        selected
+    }
+
+    /// Returns the selected documents, as in `.top_docs()`.
+    pub fn harvest(self) -> Vec<(Score, DocAddress)> {
+        self.top_docs()
+    }
+}
+
+impl Collector for DiversifiedTopK {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        self.ff_reader = Some(reader.fast_field_reader(self.diversity_field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let value = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        let doc_address = DocAddress(self.segment_id, doc);
+        self.push_candidate(value, score, doc_address);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::DiversifiedTopK;
+    use collector::TopCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, TEXT};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_diversified_top_k() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let author_field = schema_builder.add_u64_field("author", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // The author 1 wrote the most relevant documents.
+            index_writer
+                .add_document(doc!(text_field => "a a a a", author_field => 1u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a a", author_field => 2u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a a a", author_field => 1u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a", author_field => 3u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a a a", author_field => 1u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "b", author_field => 4u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("a")
+            .unwrap();
+        let author = |doc_address: DocAddress| {
+            searcher
+                .segment_reader(doc_address.segment_ord())
+                .fast_field_reader::<u64>(author_field)
+                .unwrap()
+                .get(doc_address.doc())
+        };
+
+        // The pure top 3 only has documents of the author 1.
+        let mut top_collector = TopCollector::with_limit(3);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let mut top_docs = top_collector.docs();
+        top_docs.sort();
+        assert!(top_docs.iter().all(|&doc_address| author(doc_address) == 1));
+
+        // Without a penalty, the selection is the same.
+        let mut collector = DiversifiedTopK::new(1.0, author_field, 0.0, 3);
+        searcher.search(&*query, &mut collector).unwrap();
+        let mut selected_docs: Vec<DocAddress> = collector
+            .top_docs()
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect();
+        selected_docs.sort();
+        assert_eq!(selected_docs, top_docs);
+
+        // With a penalty, each author gets a document.
+        let mut collector = DiversifiedTopK::new(1.0, author_field, 1.0, 3);
+        searcher.search(&*query, &mut collector).unwrap();
+        let diversified_docs = collector.top_docs();
+        let authors: Vec<u64> = diversified_docs
+            .iter()
+            .map(|&(_, doc_address)| author(doc_address))
+            .collect();
+        assert_eq!(authors, vec![1u64, 2u64, 3u64]);
+
+        // Merging the partitions, in any order, gives the same selection.
+        let mut merged = DiversifiedTopK::new(1.0, author_field, 1.0, 3);
+        for partition_ord in (0..2).rev() {
+            let mut partition_collector = DiversifiedTopK::new(1.0, author_field, 1.0, 3);
+            searcher
+                .search_partition(&*query, &mut partition_collector, (partition_ord, 2))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), diversified_docs);
+    }
+}
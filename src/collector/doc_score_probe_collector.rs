@@ -0,0 +1,111 @@
+use collector::Collector;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector recording the score of a single target document.
+///
+/// All of the other documents are ignored. This is helpful
+/// to debug relevance, e.g. to understand why a given document
+/// is ranked below another one.
+///
+/// The score is `None` if the target document does not match the query.
+pub struct DocScoreProbeCollector {
+    target: DocAddress,
+    // true iff the current segment is the segment of the target.
+    in_target_segment: bool,
+    score: Option<Score>,
+}
+
+impl DocScoreProbeCollector {
+    /// Creates a collector recording the score of `target`.
+    pub fn new(target: DocAddress) -> DocScoreProbeCollector {
+        DocScoreProbeCollector {
+            target,
+            in_target_segment: false,
+            score: None,
+        }
+    }
+
+    /// Returns the score of the target document, or `None`
+    /// if it did not match the query.
+    pub fn score(&self) -> Option<Score> {
+        self.score
+    }
+}
+
+impl Collector for DocScoreProbeCollector {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.in_target_segment = segment_local_id == self.target.segment_ord();
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.in_target_segment && doc == self.target.doc() {
+            self.score = Some(score);
+        }
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::DocScoreProbeCollector;
+    use collector::TopScoreCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_doc_score_probe_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a c"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "c")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("a")
+            .unwrap();
+        let mut top_collector = TopScoreCollector::with_limit(10);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let top_docs = top_collector.top_docs();
+        assert_eq!(top_docs.len(), 3);
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            for doc in 0..segment_reader.max_doc() {
+                let doc_address = DocAddress(segment_ord as u32, doc);
+                let expected_score = top_docs
+                    .iter()
+                    .find(|&&(_, top_doc_address)| top_doc_address == doc_address)
+                    .map(|&(score, _)| score);
+                let mut probe_collector = DocScoreProbeCollector::new(doc_address);
+                searcher.search(&*query, &mut probe_collector).unwrap();
+                assert_eq!(probe_collector.score(), expected_score);
+            }
+        }
+    }
+}
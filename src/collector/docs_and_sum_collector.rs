@@ -0,0 +1,167 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector returning the addresses of all of the matching documents,
+/// along with the sum of a `u64` fast field over them.
+///
+/// This computes in a single pass what would otherwise take both a
+/// search collecting the documents and a search summing their values,
+/// for instance to display a list of results with their total.
+///
+/// The addresses are ordered by `DocAddress`, that is by segment ordinal,
+/// then by doc id. Like the `ScoreExportCollector`, this collector keeps
+/// every single matching document.
+///
+/// ```rust
+/// use tantivy::collector::DocsAndSumCollector;
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let price_field = schema_builder.add_u64_field("price", FAST);
+///
+/// let collector = DocsAndSumCollector::for_field(price_field);
+/// ```
+pub struct DocsAndSumCollector {
+    field: Field,
+    doc_addresses: Vec<DocAddress>,
+    sum: u64,
+    segment_id: SegmentLocalId,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl DocsAndSumCollector {
+    /// Creates a collector summing the values of `field`.
+    pub fn for_field(field: Field) -> DocsAndSumCollector {
+        DocsAndSumCollector {
+            field,
+            doc_addresses: Vec::new(),
+            sum: 0u64,
+            segment_id: 0,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the addresses of the collected documents.
+    pub fn doc_addresses(&self) -> &[DocAddress] {
+        &self.doc_addresses[..]
+    }
+
+    /// Returns the sum of the values of the collected documents.
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Adds the documents collected by `other` to this collector.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    /// The addresses remain ordered by `DocAddress`, and the sums add up.
+    pub fn merge(&mut self, other: DocsAndSumCollector) {
+        self.doc_addresses.extend(other.doc_addresses);
+        self.doc_addresses.sort();
+        self.sum += other.sum;
+    }
+
+    /// Returns the addresses of the collected documents,
+    /// and the sum of their values.
+    pub fn harvest(self) -> (Vec<DocAddress>, u64) {
+        (self.doc_addresses, self.sum)
+    }
+}
+
+impl Collector for DocsAndSumCollector {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        self.doc_addresses.push(DocAddress(self.segment_id, doc));
+        self.sum += val;
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::DocsAndSumCollector;
+    use collector::tests::TestCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_docs_and_sum_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u64_field("price", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0u64..100u64 {
+                let text = if i % 3 == 0 { "cheap" } else { "expensive" };
+                index_writer
+                    .add_document(doc!(text_field => text, price_field => i))
+                    .unwrap();
+                if i == 40 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("cheap")
+            .unwrap();
+
+        let mut collector = DocsAndSumCollector::for_field(price_field);
+        searcher.search(&*query, &mut collector).unwrap();
+        assert_eq!(collector.doc_addresses().len(), 34);
+        let (doc_addresses, sum) = collector.harvest();
+        let expected_sum: u64 = doc_addresses
+            .iter()
+            .map(|doc_address| {
+                searcher
+                    .segment_reader(doc_address.segment_ord())
+                    .fast_field_reader::<u64>(price_field)
+                    .unwrap()
+                    .get(doc_address.doc())
+            }).sum();
+        assert_eq!(sum, expected_sum);
+        assert_eq!(sum, (0u64..100u64).filter(|i| i % 3 == 0).sum::<u64>());
+
+        // The documents are the same as the ones of a separate search.
+        let mut test_collector = TestCollector::default();
+        searcher.search(&*query, &mut test_collector).unwrap();
+        assert_eq!(doc_addresses.len(), test_collector.docs().len());
+
+        // Merging the partitions, in any order, gives the same fruit.
+        let mut merged = DocsAndSumCollector::for_field(price_field);
+        for partition_ord in (0..3).rev() {
+            let mut partition_collector = DocsAndSumCollector::for_field(price_field);
+            searcher
+                .search_partition(&*query, &mut partition_collector, (partition_ord, 3))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), (doc_addresses, sum));
+    }
+}
@@ -0,0 +1,290 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+struct ExplainedDoc {
+    score: Score,
+    doc_address: DocAddress,
+    values: Vec<u64>,
+}
+
+impl ExplainedDoc {
+    // Higher scores come first, ties are broken by `DocAddress`.
+    fn rank_cmp(&self, score: Score, doc_address: DocAddress) -> Ordering {
+        score
+            .partial_cmp(&self.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.doc_address.cmp(&doc_address))
+    }
+}
+
+// The `BinaryHeap` is a max heap: the worst ranked document
+// sits at the top.
+impl Ord for ExplainedDoc {
+    fn cmp(&self, other: &ExplainedDoc) -> Ordering {
+        self.rank_cmp(other.score, other.doc_address)
+    }
+}
+
+impl PartialOrd for ExplainedDoc {
+    fn partial_cmp(&self, other: &ExplainedDoc) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for ExplainedDoc {
+    fn eq(&self, other: &ExplainedDoc) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ExplainedDoc {}
+
+/// Collector keeping the `K` best scored documents, along with
+/// the values of a few `u64` fast fields, to help understanding
+/// why a document ranked where it did.
+///
+/// Documents are ranked by their score, ties being broken by
+/// `DocAddress`. The values are only read for documents that
+/// enter the top `K`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::collector::ExplainingTopK;
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{SchemaBuilder, FAST, TEXT};
+/// use tantivy::{Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = SchemaBuilder::new();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let rating = schema_builder.add_u64_field("rating", FAST);
+///     let year = schema_builder.add_u64_field("year", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         index_writer.add_document(doc!(
+///             title => "The Diary of Muadib",
+///             rating => 97u64,
+///             year => 1965u64
+///         ))?;
+///         index_writer.add_document(doc!(title => "A Dairy Cow", rating => 63u64, year => 2001u64))?;
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let query_parser = QueryParser::for_index(&index, vec![title]);
+///     let query = query_parser.parse_query("diary")?;
+///     let mut collector = ExplainingTopK::new(vec![rating, year], 10);
+///     searcher.search(&*query, &mut collector)?;
+///     let top_docs = collector.harvest();
+///     assert_eq!(top_docs.len(), 1);
+///     assert_eq!(top_docs[0].2, vec![97, 1965]);
+///     Ok(())
+/// }
+/// ```
+pub struct ExplainingTopK {
+    fields: Vec<Field>,
+    limit: usize,
+    heap: BinaryHeap<ExplainedDoc>,
+    segment_local_id: SegmentLocalId,
+    ff_readers: Vec<FastFieldReader<u64>>,
+}
+
+impl ExplainingTopK {
+    /// Creates a collector keeping the `k` best scored documents,
+    /// along with their values of `fields`.
+    ///
+    /// The fields must be single-valued `u64` fast fields.
+    ///
+    /// # Panics
+    /// The method panics if `k` is 0
+    pub fn new(fields: Vec<Field>, k: usize) -> ExplainingTopK {
+        if k < 1 {
+            panic!("Limit must be strictly greater than 0.");
+        }
+        ExplainingTopK {
+            fields,
+            limit: k,
+            heap: BinaryHeap::with_capacity(k),
+            segment_local_id: 0,
+            ff_readers: Vec::new(),
+        }
+    }
+
+    /// Returns the `K` best documents sorted by decreasing score,
+    /// along with their score and their values of the fields,
+    /// in the order given at construction time.
+    pub fn harvest(self) -> Vec<(DocAddress, Score, Vec<u64>)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|explained_doc| {
+                (
+                    explained_doc.doc_address,
+                    explained_doc.score,
+                    explained_doc.values,
+                )
+            }).collect()
+    }
+
+    fn enters_top(&self, score: Score, doc_address: DocAddress) -> bool {
+        if self.heap.len() < self.limit {
+            return true;
+        }
+        self.heap
+            .peek()
+            .map(|worst| worst.rank_cmp(score, doc_address) == Ordering::Greater)
+            .unwrap_or(true)
+    }
+}
+
+impl Collector for ExplainingTopK {
+    fn set_segment(
+        &mut self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> Result<()> {
+        self.segment_local_id = segment_local_id;
+        let mut ff_readers = Vec::with_capacity(self.fields.len());
+        for &field in &self.fields {
+            ff_readers.push(segment_reader.fast_field_reader(field)?);
+        }
+        self.ff_readers = ff_readers;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let doc_address = DocAddress(self.segment_local_id, doc);
+        if !self.enters_top(score, doc_address) {
+            return;
+        }
+        let values = self
+            .ff_readers
+            .iter()
+            .map(|ff_reader| ff_reader.get(doc))
+            .collect();
+        if self.heap.len() >= self.limit {
+            self.heap.pop();
+        }
+        self.heap.push(ExplainedDoc {
+            score,
+            doc_address,
+            values,
+        });
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ExplainingTopK;
+    use collector::TopScoreCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, STORED, TEXT};
+    use Index;
+    use TantivyError;
+
+    #[test]
+    fn test_explaining_top_k() {
+        let mut schema_builder = SchemaBuilder::new();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_u64_field("id", FAST | STORED);
+        let length_field = schema_builder.add_u64_field("length", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for id in 0u64..30u64 {
+                let text = vec!["rust"; (id % 7 + 1) as usize].join(" ");
+                index_writer
+                    .add_document(doc!(
+                        text_field => text,
+                        id_field => id,
+                        length_field => id % 7 + 1
+                    ))
+                    .unwrap();
+                if id % 10 == 9 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer
+                .add_document(doc!(text_field => "python", id_field => 100u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust").unwrap();
+
+        let mut collector = ExplainingTopK::new(vec![length_field, id_field], 5);
+        searcher.search(&*query, &mut collector).unwrap();
+        let top_docs = collector.harvest();
+
+        // The documents and scores are those of the top score collector.
+        let mut top_score_collector = TopScoreCollector::with_limit(5);
+        searcher.search(&*query, &mut top_score_collector).unwrap();
+        let mut expected_docs: Vec<_> = top_score_collector
+            .top_docs()
+            .into_iter()
+            .map(|(score, doc_address)| (doc_address, score))
+            .collect();
+        let mut docs: Vec<_> = top_docs
+            .iter()
+            .map(|&(doc_address, score, _)| (doc_address, score))
+            .collect();
+        expected_docs.sort_by_key(|&(doc_address, _)| doc_address);
+        docs.sort_by_key(|&(doc_address, _)| doc_address);
+        assert_eq!(docs, expected_docs);
+
+        // The values are those of the documents.
+        for &(doc_address, _, ref values) in &top_docs {
+            let doc = searcher.doc(doc_address).unwrap();
+            let id = doc.get_first(id_field).unwrap().u64_value();
+            assert_eq!(values, &vec![id % 7 + 1, id]);
+        }
+        for window in top_docs.windows(2) {
+            assert!(window[0].1 >= window[1].1);
+        }
+    }
+
+    #[test]
+    fn test_explaining_top_k_requires_fast_fields() {
+        let mut schema_builder = SchemaBuilder::new();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_u64_field("id", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "rust", id_field => 1u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust").unwrap();
+        let mut collector = ExplainingTopK::new(vec![id_field], 5);
+        match searcher.search(&*query, &mut collector) {
+            Err(TantivyError::FastFieldError(_)) => {}
+            res => panic!("Expected a fast field error, got {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,537 @@
+use fastfield::FastFieldNotAvailableError;
+use fastfield::FastFieldReader;
+use schema::FieldType;
+use DocId;
+use Result;
+use SegmentReader;
+use TantivyError;
+
+/// Arithmetic operator of an `Expression`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Sub,
+    /// `a * b`
+    Mul,
+    /// `a / b`
+    Div,
+}
+
+impl BinaryOp {
+    fn apply(self, left: f64, right: f64) -> f64 {
+        match self {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => left / right,
+        }
+    }
+}
+
+/// Function that can be called in an `Expression`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpressionFunction {
+    /// `log(x)`, the natural logarithm of `x`.
+    Log,
+    /// `exp(x)`
+    Exp,
+    /// `clamp(x, min, max)`. A `NaN` value of `x` is left as is.
+    Clamp,
+    /// `decay(timestamp, now, half_life)`, that is
+    /// `0.5 ^ ((now - timestamp) / half_life)`.
+    ///
+    /// The decay is `1.0` for a timestamp equal to `now`, and halves
+    /// every `half_life`.
+    Decay,
+}
+
+impl ExpressionFunction {
+    fn from_name(name: &str) -> Option<ExpressionFunction> {
+        match name {
+            "log" => Some(ExpressionFunction::Log),
+            "exp" => Some(ExpressionFunction::Exp),
+            "clamp" => Some(ExpressionFunction::Clamp),
+            "decay" => Some(ExpressionFunction::Decay),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the function, as used in the text expressions.
+    pub fn name(self) -> &'static str {
+        match self {
+            ExpressionFunction::Log => "log",
+            ExpressionFunction::Exp => "exp",
+            ExpressionFunction::Clamp => "clamp",
+            ExpressionFunction::Decay => "decay",
+        }
+    }
+
+    /// Returns the number of arguments of the function.
+    pub fn arity(self) -> usize {
+        match self {
+            ExpressionFunction::Log | ExpressionFunction::Exp => 1,
+            ExpressionFunction::Clamp | ExpressionFunction::Decay => 3,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            ExpressionFunction::Log => args[0].ln(),
+            ExpressionFunction::Exp => args[0].exp(),
+            ExpressionFunction::Clamp => {
+                // `f64::max` and `f64::min` would replace a `NaN` by the bound.
+                let (val, min, max) = (args[0], args[1], args[2]);
+                if val < min {
+                    min
+                } else if val > max {
+                    max
+                } else {
+                    val
+                }
+            }
+            ExpressionFunction::Decay => {
+                let (timestamp, now, half_life) = (args[0], args[1], args[2]);
+                0.5f64.powf((now - timestamp) / half_life)
+            }
+        }
+    }
+}
+
+/// Arithmetic expression over the fast fields of a document,
+/// e.g. `0.7 * ctr + 0.3 * decay(timestamp, 1540000000, 86400)`.
+///
+/// The values of the `u64` and `i64` fast fields are converted to `f64`,
+/// and the expression is evaluated with `f64` arithmetic: invalid operations
+/// such as `log(-1)` or `0 / 0` evaluate to `NaN`.
+///
+/// An expression can either be built directly, or parsed from a string
+/// with `Expression::parse`. In the latter, identifiers are field names,
+/// unless they are followed by parenthesis, as in a function call.
+/// See `ExpressionFunction` for the available functions.
+///
+/// Field names are only resolved against the schema when the expression
+/// is evaluated on a segment, e.g. by the `TopExpressionCollector`.
+///
+/// ```rust
+/// use tantivy::collector::{BinaryOp, Expression};
+///
+/// let expression = Expression::parse("2 * popularity + 1").unwrap();
+/// assert_eq!(
+///     expression,
+///     Expression::Binary(
+///         BinaryOp::Add,
+///         Box::new(Expression::Binary(
+///             BinaryOp::Mul,
+///             Box::new(Expression::Constant(2f64)),
+///             Box::new(Expression::FastField("popularity".to_string())),
+///         )),
+///         Box::new(Expression::Constant(1f64)),
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    /// A constant value.
+    Constant(f64),
+    /// The value of the `u64` or `i64` single-valued fast field
+    /// with the given name.
+    FastField(String),
+    /// The opposite of the value of the expression.
+    Neg(Box<Expression>),
+    /// An arithmetic operation.
+    Binary(BinaryOp, Box<Expression>, Box<Expression>),
+    /// A function call.
+    Call(ExpressionFunction, Vec<Expression>),
+}
+
+impl Expression {
+    /// Parses an expression.
+    ///
+    /// The usual precedence rules apply: `*` and `/` bind tighter than
+    /// `+` and `-`, and operators of the same precedence associate
+    /// to the left.
+    ///
+    /// An `InvalidArgument` error is returned if the expression
+    /// is not well formed.
+    pub fn parse(text: &str) -> Result<Expression> {
+        let mut parser = Parser { text, pos: 0 };
+        let expression = parser.parse_sum()?;
+        parser.skip_whitespaces();
+        if parser.pos < text.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(expression)
+    }
+
+    /// Resolves the fast fields of the expression against a segment.
+    pub(crate) fn for_segment(&self, reader: &SegmentReader) -> Result<SegmentExpression> {
+        match *self {
+            Expression::Constant(val) => Ok(SegmentExpression::Constant(val)),
+            Expression::FastField(ref field_name) => {
+                let schema = reader.schema();
+                let field = schema.get_field(field_name).ok_or_else(|| {
+                    TantivyError::SchemaError(format!(
+                        "Unknown field {:?} in expression.",
+                        field_name
+                    ))
+                })?;
+                let field_entry = schema.get_field_entry(field);
+                match *field_entry.field_type() {
+                    FieldType::U64(_) => {
+                        Ok(SegmentExpression::U64(reader.fast_field_reader(field)?))
+                    }
+                    FieldType::I64(_) => {
+                        Ok(SegmentExpression::I64(reader.fast_field_reader(field)?))
+                    }
+                    _ => Err(FastFieldNotAvailableError::new(field_entry).into()),
+                }
+            }
+            Expression::Neg(ref expression) => Ok(SegmentExpression::Neg(Box::new(
+                expression.for_segment(reader)?,
+            ))),
+            Expression::Binary(op, ref left, ref right) => Ok(SegmentExpression::Binary(
+                op,
+                Box::new(left.for_segment(reader)?),
+                Box::new(right.for_segment(reader)?),
+            )),
+            Expression::Call(function, ref args) => {
+                if args.len() != function.arity() {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "{}() expects {} argument(s), got {}.",
+                        function.name(),
+                        function.arity(),
+                        args.len()
+                    )));
+                }
+                let args = args
+                    .iter()
+                    .map(|arg| arg.for_segment(reader))
+                    .collect::<Result<Vec<SegmentExpression>>>()?;
+                Ok(SegmentExpression::Call(function, args))
+            }
+        }
+    }
+}
+
+/// An `Expression` whose fast fields were resolved for a given segment.
+pub(crate) enum SegmentExpression {
+    Constant(f64),
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    Neg(Box<SegmentExpression>),
+    Binary(BinaryOp, Box<SegmentExpression>, Box<SegmentExpression>),
+    Call(ExpressionFunction, Vec<SegmentExpression>),
+}
+
+impl SegmentExpression {
+    /// Evaluates the expression for a document of the segment.
+    pub fn eval(&self, doc: DocId) -> f64 {
+        match *self {
+            SegmentExpression::Constant(val) => val,
+            SegmentExpression::U64(ref reader) => reader.get(doc) as f64,
+            SegmentExpression::I64(ref reader) => reader.get(doc) as f64,
+            SegmentExpression::Neg(ref expression) => -expression.eval(doc),
+            SegmentExpression::Binary(op, ref left, ref right) => {
+                op.apply(left.eval(doc), right.eval(doc))
+            }
+            SegmentExpression::Call(function, ref args) => {
+                // All of the functions take at most 3 arguments.
+                let mut vals = [0f64; 3];
+                for (val, arg) in vals.iter_mut().zip(args) {
+                    *val = arg.eval(doc);
+                }
+                function.apply(&vals[..args.len()])
+            }
+        }
+    }
+}
+
+/// Recursive descent parser for the text expressions.
+struct Parser<'a> {
+    text: &'a str,
+    // byte offset of the next character.
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> TantivyError {
+        TantivyError::InvalidArgument(format!(
+            "Invalid expression {:?}: {} at position {}.",
+            self.text, msg, self.pos
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// Skips the whitespaces, and consumes `expected` if it comes next.
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespaces();
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the characters matching `predicate`, and returns them.
+    fn consume_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> &'a str {
+        let text = self.text;
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !predicate(c) {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+        &text[start..self.pos]
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn parse_sum(&mut self) -> Result<Expression> {
+        let mut expression = self.parse_product()?;
+        loop {
+            let op = if self.consume('+') {
+                BinaryOp::Add
+            } else if self.consume('-') {
+                BinaryOp::Sub
+            } else {
+                return Ok(expression);
+            };
+            let right = self.parse_product()?;
+            expression = Expression::Binary(op, Box::new(expression), Box::new(right));
+        }
+    }
+
+    // product := unary (('*' | '/') unary)*
+    fn parse_product(&mut self) -> Result<Expression> {
+        let mut expression = self.parse_unary()?;
+        loop {
+            let op = if self.consume('*') {
+                BinaryOp::Mul
+            } else if self.consume('/') {
+                BinaryOp::Div
+            } else {
+                return Ok(expression);
+            };
+            let right = self.parse_unary()?;
+            expression = Expression::Binary(op, Box::new(expression), Box::new(right));
+        }
+    }
+
+    // unary := '-' unary | primary
+    fn parse_unary(&mut self) -> Result<Expression> {
+        if self.consume('-') {
+            let expression = self.parse_unary()?;
+            return Ok(Expression::Neg(Box::new(expression)));
+        }
+        self.parse_primary()
+    }
+
+    // primary := number | identifier | identifier '(' arguments ')' | '(' sum ')'
+    fn parse_primary(&mut self) -> Result<Expression> {
+        if self.consume('(') {
+            let expression = self.parse_sum()?;
+            if !self.consume(')') {
+                return Err(self.error("expected ')'"));
+            }
+            return Ok(expression);
+        }
+        self.skip_whitespaces();
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => self.parse_number(),
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let start = self.pos;
+                let identifier = self.consume_while(|c| c.is_alphanumeric() || c == '_');
+                if !self.consume('(') {
+                    return Ok(Expression::FastField(identifier.to_string()));
+                }
+                let function = ExpressionFunction::from_name(identifier).ok_or_else(|| {
+                    self.pos = start;
+                    self.error(&format!("unknown function {:?}", identifier))
+                })?;
+                let args = self.parse_arguments()?;
+                if args.len() != function.arity() {
+                    self.pos = start;
+                    return Err(self.error(&format!(
+                        "{}() expects {} argument(s), got {}",
+                        function.name(),
+                        function.arity(),
+                        args.len()
+                    )));
+                }
+                Ok(Expression::Call(function, args))
+            }
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    // arguments := (sum (',' sum)*)? ')'
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>> {
+        let mut args = Vec::new();
+        if self.consume(')') {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_sum()?);
+            if self.consume(')') {
+                return Ok(args);
+            }
+            if !self.consume(',') {
+                return Err(self.error("expected ',' or ')'"));
+            }
+        }
+    }
+
+    // number := digits ('.' digits)? (('e' | 'E') ('+' | '-')? digits)?
+    fn parse_number(&mut self) -> Result<Expression> {
+        let start = self.pos;
+        self.consume_while(|c| c.is_ascii_digit() || c == '.');
+        if self.peek() == Some('e') || self.peek() == Some('E') {
+            self.pos += 1;
+            if self.peek() == Some('+') || self.peek() == Some('-') {
+                self.pos += 1;
+            }
+            self.consume_while(|c| c.is_ascii_digit());
+        }
+        let text = self.text;
+        let number = &text[start..self.pos];
+        number
+            .parse::<f64>()
+            .map(Expression::Constant)
+            .map_err(|_| {
+                self.pos = start;
+                self.error(&format!("invalid number {:?}", number))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{BinaryOp, Expression, ExpressionFunction};
+    use TantivyError;
+
+    fn constant(val: f64) -> Box<Expression> {
+        Box::new(Expression::Constant(val))
+    }
+
+    fn field(name: &str) -> Box<Expression> {
+        Box::new(Expression::FastField(name.to_string()))
+    }
+
+    fn parse_err(text: &str) -> String {
+        match Expression::parse(text) {
+            Err(TantivyError::InvalidArgument(msg)) => msg,
+            res => panic!("Expected an invalid argument error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        assert_eq!(Expression::parse("12.5").unwrap(), *constant(12.5));
+        assert_eq!(Expression::parse(" 1e3 ").unwrap(), *constant(1_000f64));
+        assert_eq!(Expression::parse("views_7d").unwrap(), *field("views_7d"));
+        // `*` binds tighter than `+`, and `-` associates to the left.
+        assert_eq!(
+            Expression::parse("a - b - 2 * c").unwrap(),
+            Expression::Binary(
+                BinaryOp::Sub,
+                Box::new(Expression::Binary(BinaryOp::Sub, field("a"), field("b"))),
+                Box::new(Expression::Binary(
+                    BinaryOp::Mul,
+                    constant(2f64),
+                    field("c")
+                )),
+            )
+        );
+        assert_eq!(
+            Expression::parse("-(a + b) / 2").unwrap(),
+            Expression::Binary(
+                BinaryOp::Div,
+                Box::new(Expression::Neg(Box::new(Expression::Binary(
+                    BinaryOp::Add,
+                    field("a"),
+                    field("b")
+                )))),
+                constant(2f64),
+            )
+        );
+        assert_eq!(
+            Expression::parse("0.7*ctr + 0.3*decay(timestamp, 1000, 10)").unwrap(),
+            Expression::Binary(
+                BinaryOp::Add,
+                Box::new(Expression::Binary(
+                    BinaryOp::Mul,
+                    constant(0.7),
+                    field("ctr")
+                )),
+                Box::new(Expression::Binary(
+                    BinaryOp::Mul,
+                    constant(0.3),
+                    Box::new(Expression::Call(
+                        ExpressionFunction::Decay,
+                        vec![*field("timestamp"), *constant(1_000f64), *constant(10f64)]
+                    )),
+                )),
+            )
+        );
+        assert_eq!(
+            Expression::parse("clamp(log(x), 0, exp(1))").unwrap(),
+            Expression::Call(
+                ExpressionFunction::Clamp,
+                vec![
+                    Expression::Call(ExpressionFunction::Log, vec![*field("x")]),
+                    *constant(0f64),
+                    Expression::Call(ExpressionFunction::Exp, vec![*constant(1f64)]),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_expression_errors() {
+        assert!(parse_err("").contains("unexpected end of expression at position 0"));
+        assert!(parse_err("a +").contains("unexpected end of expression at position 3"));
+        assert!(parse_err("a b").contains("unexpected character at position 2"));
+        assert!(parse_err("(a + b").contains("expected ')' at position 6"));
+        assert!(parse_err("1.2.3").contains("invalid number \"1.2.3\" at position 0"));
+        assert!(parse_err("2 * sqrt(a)").contains("unknown function \"sqrt\" at position 4"));
+        assert!(parse_err("log(a, b)").contains("log() expects 1 argument(s), got 2"));
+        assert!(parse_err("clamp(a, 1 2)").contains("expected ',' or ')' at position 11"));
+    }
+
+    #[test]
+    fn test_functions() {
+        let apply = |function: ExpressionFunction, args: &[f64]| function.apply(args);
+        assert_eq!(apply(ExpressionFunction::Log, &[1f64]), 0f64);
+        assert!(apply(ExpressionFunction::Log, &[-1f64]).is_nan());
+        assert_eq!(apply(ExpressionFunction::Exp, &[0f64]), 1f64);
+        assert_eq!(apply(ExpressionFunction::Clamp, &[-3f64, 0f64, 1f64]), 0f64);
+        assert_eq!(apply(ExpressionFunction::Clamp, &[0.5, 0f64, 1f64]), 0.5);
+        assert_eq!(apply(ExpressionFunction::Clamp, &[3f64, 0f64, 1f64]), 1f64);
+        assert!(apply(ExpressionFunction::Clamp, &[::std::f64::NAN, 0f64, 1f64]).is_nan());
+        assert_eq!(
+            apply(ExpressionFunction::Decay, &[100f64, 100f64, 10f64]),
+            1f64
+        );
+        assert_eq!(
+            apply(ExpressionFunction::Decay, &[80f64, 100f64, 10f64]),
+            0.25
+        );
+    }
+}
@@ -71,6 +71,15 @@ fn facet_depth(facet_bytes: &[u8]) -> usize {
 /// Facet counts will only be computed for the facet that are direct children
 /// of such a root facet.
 ///
+/// Several roots can be added to the same collector, e.g. `/brand`, `/color`
+/// and `/size`: their counts are all computed in the same pass over the
+/// facets of each document. A root cannot be the ancestor of another root,
+/// though (see `.add_facet(...)`).
+///
+/// For each root, the collector also counts the matching documents without
+/// any facet under the root, e.g. to display an "unspecified" bucket
+/// (see `FacetCounts::num_docs_without_facet`).
+///
 /// For instance, if your index represents books, your hierarchy of facets
 /// may contain `category`, `language`.
 ///
@@ -114,23 +123,23 @@ fn facet_depth(facet_bytes: &[u8]) -> usize {
 ///             title => "The Name of the Wind",
 ///             facet => Facet::from("/lang/en"),
 ///             facet => Facet::from("/category/fiction/fantasy")
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "Dune",
 ///             facet => Facet::from("/lang/en"),
 ///             facet => Facet::from("/category/fiction/sci-fi")
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "La Vénus d'Ille",
 ///             facet => Facet::from("/lang/fr"),
 ///             facet => Facet::from("/category/fiction/fantasy"),
 ///             facet => Facet::from("/category/fiction/horror")
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
 ///             facet => Facet::from("/lang/en"),
 ///             facet => Facet::from("/category/biography")
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -205,17 +214,23 @@ pub struct FacetCollector {
     current_segment_counts: Vec<u64>,
     // collapse facet_id -> facet_ord
     current_collapse_facet_ords: Vec<u64>,
+    // collapse facet_id -> ordinal of its root in `facets`
+    current_collapse_root_ords: Vec<usize>,
 
     facets: BTreeSet<Facet>,
+    // ordinal of the root in `facets` -> number of documents
+    // with at least one facet under the root.
+    docs_with_facet: Vec<u64>,
+    num_docs: u64,
 }
 
-fn skip<'a, I: Iterator<Item = &'a Facet>>(
+fn skip<'a, I: Iterator<Item = (usize, &'a Facet)>>(
     target: &[u8],
     collapse_it: &mut Peekable<I>,
 ) -> SkipResult {
     loop {
         match collapse_it.peek() {
-            Some(facet_bytes) => match facet_bytes.encoded_bytes().cmp(target) {
+            Some(&(_, facet_bytes)) => match facet_bytes.encoded_bytes().cmp(target) {
                 Ordering::Less => {}
                 Ordering::Greater => {
                     return SkipResult::OverStep;
@@ -245,9 +260,12 @@ impl FacetCollector {
             field,
             ff_reader: None,
             facets: BTreeSet::new(),
+            docs_with_facet: Vec::new(),
+            num_docs: 0u64,
 
             current_segment_collapse_mapping: Vec::new(),
             current_collapse_facet_ords: Vec::new(),
+            current_collapse_root_ords: Vec::new(),
             current_segment_counts: Vec::new(),
         }
     }
@@ -258,14 +276,25 @@ impl FacetCollector {
     /// will record the counts of all of the direct children of the facet country
     /// (e.g. `/country/FR`, `/country/UK`).
     ///
+    /// This method can be called several times, to compute the counts
+    /// of several facets in the same pass.
+    ///
     /// Adding two facets within which one is the prefix of the other is forbidden.
     /// If you need the correct number of unique documents for two such facets,
     /// just add them in separate `FacetCollector`.
+    ///
+    /// # Panics
+    /// Panics if the facet is the ancestor or the descendant of
+    /// an already added facet, or if the collection has already started.
     pub fn add_facet<T>(&mut self, facet_from: T)
     where
         Facet: From<T>,
     {
         let facet = Facet::from(facet_from);
+        assert!(
+            self.ff_reader.is_none() && self.segment_counters.is_empty(),
+            "Tried to add a facet after the collection started."
+        );
         for old_facet in &self.facets {
             assert!(
                 !old_facet.is_prefix_of(&facet),
@@ -277,14 +306,17 @@ impl FacetCollector {
             );
         }
         self.facets.insert(facet);
+        self.docs_with_facet.resize(self.facets.len(), 0u64);
     }
 
     fn set_collapse_mapping(&mut self, facet_reader: &FacetReader) {
         self.current_segment_collapse_mapping.clear();
         self.current_collapse_facet_ords.clear();
+        self.current_collapse_root_ords.clear();
         self.current_segment_counts.clear();
-        let mut collapse_facet_it = self.facets.iter().peekable();
+        let mut collapse_facet_it = self.facets.iter().enumerate().peekable();
         self.current_collapse_facet_ords.push(0);
+        self.current_collapse_root_ords.push(usize::MAX);
         let mut facet_streamer = facet_reader.facet_dict().range().into_stream();
         if !facet_streamer.advance() {
             return;
@@ -296,6 +328,10 @@ impl FacetCollector {
             match skip_result {
                 SkipResult::Reached => {
                     // we reach a facet we decided to collapse.
+                    let root_ord = collapse_facet_it
+                        .peek()
+                        .map(|&(root_ord, _)| root_ord)
+                        .expect("The facet was just reached.");
                     let collapse_depth = facet_depth(facet_streamer.key());
                     let mut collapsed_id = 0;
                     self.current_segment_collapse_mapping.push(0);
@@ -308,6 +344,7 @@ impl FacetCollector {
                             collapsed_id = self.current_collapse_facet_ords.len();
                             self.current_collapse_facet_ords
                                 .push(facet_streamer.term_ord());
+                            self.current_collapse_root_ords.push(root_ord);
                             self.current_segment_collapse_mapping.push(collapsed_id);
                         } else {
                             self.current_segment_collapse_mapping.push(collapsed_id);
@@ -386,7 +423,17 @@ impl FacetCollector {
                 facet_counts.insert(facet, count);
             }
         }
-        FacetCounts { facet_counts }
+        let num_docs = self.num_docs;
+        let docs_without_facet = self
+            .facets
+            .into_iter()
+            .zip(self.docs_with_facet)
+            .map(|(facet, docs_with_facet)| (facet, num_docs - docs_with_facet))
+            .collect();
+        FacetCounts {
+            facet_counts,
+            docs_without_facet,
+        }
     }
 }
 
@@ -410,7 +457,9 @@ impl Collector for FacetCollector {
                 .get()
         };
         facet_reader.facet_ords(doc, &mut self.facet_ords);
+        self.num_docs += 1;
         let mut previous_collapsed_ord: usize = usize::MAX;
+        let mut previous_root_ord: usize = usize::MAX;
         for &facet_ord in &self.facet_ords {
             let collapsed_ord = self.current_segment_collapse_mapping[facet_ord as usize];
             self.current_segment_counts[collapsed_ord] += if collapsed_ord == previous_collapsed_ord
@@ -420,6 +469,12 @@ impl Collector for FacetCollector {
                 1
             };
             previous_collapsed_ord = collapsed_ord;
+            // The facets under a same root have consecutive ordinals.
+            let root_ord = self.current_collapse_root_ords[collapsed_ord];
+            if root_ord != usize::MAX && root_ord != previous_root_ord {
+                self.docs_with_facet[root_ord] += 1;
+                previous_root_ord = root_ord;
+            }
         }
     }
 
@@ -432,6 +487,8 @@ impl Collector for FacetCollector {
 /// the facet counts for all the segments.
 pub struct FacetCounts {
     facet_counts: BTreeMap<Facet, u64>,
+    // root facet -> number of documents without any facet under the root
+    docs_without_facet: BTreeMap<Facet, u64>,
 }
 
 pub struct FacetChildIterator<'a> {
@@ -447,6 +504,37 @@ impl<'a> Iterator for FacetChildIterator<'a> {
 }
 
 impl FacetCounts {
+    /// Returns the number of matching documents without any facet
+    /// under `root`, e.g. the documents without any `/color/...` facet.
+    ///
+    /// Returns `None` if `root` was not added to the `FacetCollector`.
+    pub fn num_docs_without_facet<T>(&self, root: T) -> Option<u64>
+    where
+        Facet: From<T>,
+    {
+        self.docs_without_facet.get(&Facet::from(root)).cloned()
+    }
+
+    /// Adds the counts of `other` to these counts.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    /// The counts of each root are kept separate, but both `FacetCounts` are
+    /// expected to come from collectors with the same facets: the documents
+    /// without facet are only counted for the roots requested in both.
+    pub fn merge(&mut self, other: FacetCounts) {
+        for (facet, count) in other.facet_counts {
+            *self.facet_counts.entry(facet).or_insert(0u64) += count;
+        }
+        let mut docs_without_facet = BTreeMap::new();
+        for (root, count) in other.docs_without_facet {
+            if let Some(&self_count) = self.docs_without_facet.get(&root) {
+                docs_without_facet.insert(root, self_count + count);
+            }
+        }
+        self.docs_without_facet = docs_without_facet;
+    }
+
     pub fn get<T>(&self, facet_from: T) -> FacetChildIterator
     where
         Facet: From<T>,
@@ -532,7 +620,7 @@ mod tests {
         for i in 0..num_facets * 10 {
             let mut doc = Document::new();
             doc.add_facet(facet_field, facets[i % num_facets].clone());
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
@@ -574,6 +662,107 @@ mod tests {
         facet_collector.add_facet(Facet::from("/country/europe"));
     }
 
+    #[test]
+    #[should_panic(
+        expected = "Tried to add a facet which is an ancestor of \
+                    an already added facet."
+    )]
+    fn test_facet_collector_ancestor_root() {
+        let mut facet_collector = FacetCollector::for_field(Field(0));
+        facet_collector.add_facet(Facet::from("/country/europe"));
+        facet_collector.add_facet(Facet::from("/country"));
+    }
+
+    #[test]
+    fn test_facet_collector_multiple_roots() {
+        let mut schema_builder = SchemaBuilder::new();
+        let facet_field = schema_builder.add_facet_field("facet");
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/brand/acme"),
+                    facet_field => Facet::from("/color/red"),
+                    facet_field => Facet::from("/color/blue")
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/brand/initech"),
+                    facet_field => Facet::from("/size/xl")
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(facet_field => Facet::from("/color/red/dark")))
+                .unwrap();
+            index_writer.add_document(Document::new()).unwrap();
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/brand/acme"),
+                    facet_field => Facet::from("/size/s")
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let new_collector = || {
+            let mut facet_collector = FacetCollector::for_field(facet_field);
+            facet_collector.add_facet("/brand");
+            facet_collector.add_facet("/color");
+            facet_collector.add_facet("/size");
+            facet_collector
+        };
+        let check_counts = |counts: &FacetCounts| {
+            assert_eq!(
+                counts.get("/brand").collect::<Vec<_>>(),
+                vec![
+                    (&Facet::from("/brand/acme"), 2),
+                    (&Facet::from("/brand/initech"), 1),
+                ]
+            );
+            assert_eq!(
+                counts.get("/color").collect::<Vec<_>>(),
+                vec![
+                    (&Facet::from("/color/blue"), 1),
+                    (&Facet::from("/color/red"), 2),
+                ]
+            );
+            assert_eq!(
+                counts.get("/size").collect::<Vec<_>>(),
+                vec![(&Facet::from("/size/s"), 1), (&Facet::from("/size/xl"), 1)]
+            );
+            assert_eq!(counts.num_docs_without_facet("/brand"), Some(2));
+            assert_eq!(counts.num_docs_without_facet("/color"), Some(3));
+            assert_eq!(counts.num_docs_without_facet("/size"), Some(3));
+            assert_eq!(counts.num_docs_without_facet("/lang"), None);
+        };
+
+        let mut facet_collector = new_collector();
+        searcher.search(&AllQuery, &mut facet_collector).unwrap();
+        check_counts(&facet_collector.harvest());
+
+        // Merging the counts of the partitions keeps the roots separate.
+        let mut merged_counts: Option<FacetCounts> = None;
+        for partition_ord in 0..2 {
+            let mut facet_collector = new_collector();
+            searcher
+                .search_partition(&AllQuery, &mut facet_collector, (partition_ord, 2))
+                .unwrap();
+            let counts = facet_collector.harvest();
+            match merged_counts {
+                Some(ref mut merged_counts) => merged_counts.merge(counts),
+                None => merged_counts = Some(counts),
+            }
+        }
+        check_counts(&merged_counts.unwrap());
+    }
+
     #[test]
     fn test_doc_unsorted_multifacet() {
         let mut schema_builder = SchemaBuilder::new();
@@ -581,12 +770,14 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-        index_writer.add_document(doc!(
-            facet_field => Facet::from_text(&"/subjects/A/a"),
-            facet_field => Facet::from_text(&"/subjects/B/a"),
-            facet_field => Facet::from_text(&"/subjects/A/b"),
-            facet_field => Facet::from_text(&"/subjects/B/b"),
-        ));
+        index_writer
+            .add_document(doc!(
+                facet_field => Facet::from_text(&"/subjects/A/a"),
+                facet_field => Facet::from_text(&"/subjects/B/a"),
+                facet_field => Facet::from_text(&"/subjects/A/b"),
+                facet_field => Facet::from_text(&"/subjects/B/b"),
+            ))
+            .unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -631,7 +822,7 @@ mod tests {
 
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         for doc in docs {
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
@@ -688,7 +879,7 @@ mod bench {
 
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         for doc in docs {
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
@@ -0,0 +1,420 @@
+use collector::Collector;
+use docset::{DocSet, SkipResult};
+use fastfield::FastFieldNotAvailableError;
+use fastfield::FastFieldReader;
+use fieldnorm::FieldNormReader;
+use postings::SegmentPostings;
+use query::Query;
+use schema::{Field, FieldType, IndexRecordOption, Term};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, BinaryHeap};
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+use TantivyError;
+
+/// Describes one of the features computed by the `FeatureCollector`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FeatureSpec {
+    /// Score of the document for the query.
+    Score,
+    /// Value of a `u64` or `i64` single-valued fast field.
+    FastField(Field),
+    /// Number of distinct terms of the query, in the given field,
+    /// that the document contains.
+    FieldMatchCount(Field),
+    /// Length, in tokens, of the given field, as recorded by
+    /// the field norms.
+    ///
+    /// The field must be indexed.
+    FieldNorm(Field),
+}
+
+/// Position of the postings of one of the terms of the query.
+///
+/// Documents are collected in increasing order within a segment,
+/// so the postings only ever need to move forward.
+struct TermCursor {
+    postings: SegmentPostings,
+    // `None` once the postings are exhausted.
+    doc: Option<DocId>,
+}
+
+impl TermCursor {
+    fn new(mut postings: SegmentPostings) -> TermCursor {
+        let doc = if postings.advance() {
+            Some(postings.doc())
+        } else {
+            None
+        };
+        TermCursor { postings, doc }
+    }
+
+    fn contains(&mut self, target: DocId) -> bool {
+        if let Some(doc) = self.doc {
+            if doc < target {
+                self.doc = match self.postings.skip_next(target) {
+                    SkipResult::Reached | SkipResult::OverStep => Some(self.postings.doc()),
+                    SkipResult::End => None,
+                };
+            }
+        }
+        self.doc == Some(target)
+    }
+}
+
+enum FeatureReader {
+    Score,
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+    MatchCount(Vec<TermCursor>),
+    FieldNorm(FieldNormReader),
+}
+
+impl FeatureReader {
+    fn open(
+        feature: FeatureSpec,
+        terms: &BTreeSet<Term>,
+        segment_reader: &SegmentReader,
+    ) -> Result<FeatureReader> {
+        match feature {
+            FeatureSpec::Score => Ok(FeatureReader::Score),
+            FeatureSpec::FastField(field) => {
+                let field_entry = segment_reader.schema().get_field_entry(field);
+                match *field_entry.field_type() {
+                    FieldType::U64(_) => {
+                        Ok(FeatureReader::U64(segment_reader.fast_field_reader(field)?))
+                    }
+                    FieldType::I64(_) => {
+                        Ok(FeatureReader::I64(segment_reader.fast_field_reader(field)?))
+                    }
+                    _ => Err(FastFieldNotAvailableError::new(field_entry).into()),
+                }
+            }
+            FeatureSpec::FieldMatchCount(field) => {
+                let inverted_index = segment_reader.inverted_index(field);
+                let cursors = terms
+                    .iter()
+                    .filter(|term| term.field() == field)
+                    .flat_map(|term| inverted_index.read_postings(term, IndexRecordOption::Basic))
+                    .map(TermCursor::new)
+                    .collect();
+                Ok(FeatureReader::MatchCount(cursors))
+            }
+            FeatureSpec::FieldNorm(field) => {
+                let field_entry = segment_reader.schema().get_field_entry(field);
+                if !field_entry.is_indexed() {
+                    return Err(TantivyError::SchemaError(format!(
+                        "Field {:?} is not indexed, and has no field norms.",
+                        field_entry.name()
+                    )));
+                }
+                Ok(FeatureReader::FieldNorm(
+                    segment_reader.get_fieldnorms_reader(field),
+                ))
+            }
+        }
+    }
+
+    fn value(&mut self, doc: DocId, score: Score) -> f32 {
+        match *self {
+            FeatureReader::Score => score,
+            FeatureReader::U64(ref reader) => reader.get(doc) as f32,
+            FeatureReader::I64(ref reader) => reader.get(doc) as f32,
+            FeatureReader::MatchCount(ref mut cursors) => cursors
+                .iter_mut()
+                .filter(|cursor| cursor.contains(doc))
+                .count() as f32,
+            FeatureReader::FieldNorm(ref reader) => reader.fieldnorm(doc) as f32,
+        }
+    }
+}
+
+struct FeatureDoc {
+    score: Score,
+    doc_address: DocAddress,
+    features: Vec<f32>,
+}
+
+impl FeatureDoc {
+    // Higher scores come first, ties are broken by `DocAddress`.
+    fn rank_cmp(&self, score: Score, doc_address: DocAddress) -> Ordering {
+        score
+            .partial_cmp(&self.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.doc_address.cmp(&doc_address))
+    }
+}
+
+// The `BinaryHeap` is a max heap: the worst ranked document
+// sits at the top.
+impl Ord for FeatureDoc {
+    fn cmp(&self, other: &FeatureDoc) -> Ordering {
+        self.rank_cmp(other.score, other.doc_address)
+    }
+}
+
+impl PartialOrd for FeatureDoc {
+    fn partial_cmp(&self, other: &FeatureDoc) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for FeatureDoc {
+    fn eq(&self, other: &FeatureDoc) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FeatureDoc {}
+
+/// Collector computing a vector of features for the top `K`
+/// documents of a query, as an input for learning-to-rank.
+///
+/// Documents are ranked by their score, ties being broken by
+/// `DocAddress`, so that the result is deterministic.
+/// Features are only computed for documents that enter the top `K`.
+///
+/// `FeatureSpec::FieldMatchCount` relies on the terms of the query
+/// given at construction time, as reported by `Query::query_terms`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::collector::{FeatureCollector, FeatureSpec};
+/// use tantivy::query::QueryParser;
+/// use tantivy::schema::{SchemaBuilder, FAST, TEXT};
+/// use tantivy::{Index, Result};
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> Result<()> {
+///     let mut schema_builder = SchemaBuilder::new();
+///     let title = schema_builder.add_text_field("title", TEXT);
+///     let rating = schema_builder.add_u64_field("rating", FAST);
+///     let schema = schema_builder.build();
+///     let index = Index::create_in_ram(schema);
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 3_000_000)?;
+///         index_writer.add_document(doc!(title => "The Diary of Muadib", rating => 97u64))?;
+///         index_writer.add_document(doc!(title => "A Dairy Cow", rating => 63u64))?;
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     let query_parser = QueryParser::for_index(&index, vec![title]);
+///     let query = query_parser.parse_query("diary muadib")?;
+///     let features = vec![
+///         FeatureSpec::Score,
+///         FeatureSpec::FieldMatchCount(title),
+///         FeatureSpec::FastField(rating),
+///         FeatureSpec::FieldNorm(title),
+///     ];
+///     let mut feature_collector = FeatureCollector::new(&*query, features, 100);
+///     searcher.search(&*query, &mut feature_collector)?;
+///     let feature_docs = feature_collector.harvest();
+///     assert_eq!(feature_docs.len(), 1);
+///     assert_eq!(&feature_docs[0].1[1..], &[2f32, 97f32, 4f32]);
+///     Ok(())
+/// }
+/// ```
+pub struct FeatureCollector {
+    features: Vec<FeatureSpec>,
+    terms: BTreeSet<Term>,
+    limit: usize,
+    heap: BinaryHeap<FeatureDoc>,
+    segment_local_id: SegmentLocalId,
+    readers: Vec<FeatureReader>,
+}
+
+impl FeatureCollector {
+    /// Creates a collector computing `features` for the `limit`
+    /// best scored documents of `query`.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn new(query: &Query, features: Vec<FeatureSpec>, limit: usize) -> FeatureCollector {
+        if limit < 1 {
+            panic!("Limit must be strictly greater than 0.");
+        }
+        let mut terms = BTreeSet::new();
+        query.query_terms(&mut terms);
+        FeatureCollector {
+            features,
+            terms,
+            limit,
+            heap: BinaryHeap::with_capacity(limit),
+            segment_local_id: 0,
+            readers: Vec::new(),
+        }
+    }
+
+    /// Returns the features of the `K` best documents, sorted by
+    /// decreasing score.
+    ///
+    /// The features are in the order of the `FeatureSpec`s given
+    /// at construction time.
+    pub fn harvest(self) -> Vec<(DocAddress, Vec<f32>)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|feature_doc| (feature_doc.doc_address, feature_doc.features))
+            .collect()
+    }
+
+    fn enters_top(&self, score: Score, doc_address: DocAddress) -> bool {
+        if self.heap.len() < self.limit {
+            return true;
+        }
+        self.heap
+            .peek()
+            .map(|worst| worst.rank_cmp(score, doc_address) == Ordering::Greater)
+            .unwrap_or(true)
+    }
+}
+
+impl Collector for FeatureCollector {
+    fn set_segment(
+        &mut self,
+        segment_local_id: SegmentLocalId,
+        segment_reader: &SegmentReader,
+    ) -> Result<()> {
+        self.segment_local_id = segment_local_id;
+        let mut readers = Vec::with_capacity(self.features.len());
+        for &feature in &self.features {
+            readers.push(FeatureReader::open(feature, &self.terms, segment_reader)?);
+        }
+        self.readers = readers;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let doc_address = DocAddress(self.segment_local_id, doc);
+        if !self.enters_top(score, doc_address) {
+            return;
+        }
+        let features = self
+            .readers
+            .iter_mut()
+            .map(|reader| reader.value(doc, score))
+            .collect();
+        if self.heap.len() >= self.limit {
+            self.heap.pop();
+        }
+        self.heap.push(FeatureDoc {
+            score,
+            doc_address,
+            features,
+        });
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{FeatureCollector, FeatureSpec};
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, TEXT};
+    use serde_json;
+    use DocAddress;
+    use Index;
+    use TantivyError;
+
+    #[test]
+    fn test_feature_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let rating = schema_builder.add_i64_field("rating", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(title => "a b", body => "c", rating => -3i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "b", body => "a a a", rating => 5i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "c", body => "d", rating => 1i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(title => "a", body => "b c d e", rating => 2i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let query = query_parser.parse_query("a b").unwrap();
+        let features = vec![
+            FeatureSpec::FieldMatchCount(title),
+            FeatureSpec::FieldMatchCount(body),
+            FeatureSpec::FastField(rating),
+            FeatureSpec::FieldNorm(body),
+            FeatureSpec::Score,
+        ];
+
+        let mut all_collector = FeatureCollector::new(&*query, features.clone(), 10);
+        searcher.search(&*query, &mut all_collector).unwrap();
+        let all_docs = all_collector.harvest();
+        assert_eq!(all_docs.len(), 3);
+        for window in all_docs.windows(2) {
+            assert!(window[0].1[4] >= window[1].1[4]);
+        }
+        let mut sorted_docs = all_docs.clone();
+        sorted_docs.sort_by_key(|&(doc_address, _)| doc_address);
+        let expected = vec![
+            (DocAddress(0, 0), vec![2f32, 0f32, -3f32, 1f32]),
+            (DocAddress(0, 1), vec![1f32, 1f32, 5f32, 3f32]),
+            (DocAddress(1, 0), vec![1f32, 1f32, 2f32, 4f32]),
+        ];
+        for (&(doc_address, ref features), &(expected_address, ref expected_features)) in
+            sorted_docs.iter().zip(expected.iter())
+        {
+            assert_eq!(doc_address, expected_address);
+            assert_eq!(&features[..4], &expected_features[..]);
+        }
+
+        let mut top_collector = FeatureCollector::new(&*query, features.clone(), 2);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let top_docs = top_collector.harvest();
+        assert_eq!(&top_docs[..], &all_docs[..2]);
+
+        let json = serde_json::to_string(&top_docs).unwrap();
+        let deserialized: Vec<(DocAddress, Vec<f32>)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, top_docs);
+    }
+
+    #[test]
+    fn test_feature_collector_invalid_field() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(title => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![title])
+            .parse_query("a")
+            .unwrap();
+        let mut collector = FeatureCollector::new(&*query, vec![FeatureSpec::FastField(title)], 1);
+        assert!(match searcher.search(&*query, &mut collector) {
+            Err(TantivyError::FastFieldError(_)) => true,
+            _ => false,
+        });
+    }
+}
@@ -0,0 +1,178 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::collections::HashMap;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector summing a `u64` fast field over the matching documents,
+/// grouped by the value of another `u64` fast field.
+///
+/// For instance, with a `category` group field and a `price` value field,
+/// this computes the revenue of each category.
+///
+/// The sums are accumulated as `u128`, so that they cannot overflow
+/// whatever the number of documents of a group.
+///
+/// ```rust
+/// use tantivy::collector::GroupedSumCollector;
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let category_field = schema_builder.add_u64_field("category", FAST);
+/// # let price_field = schema_builder.add_u64_field("price", FAST);
+///
+/// let collector = GroupedSumCollector::new(category_field, price_field);
+/// ```
+pub struct GroupedSumCollector {
+    group_field: Field,
+    value_field: Field,
+    sums: HashMap<u64, u128>,
+    ff_readers: Option<(FastFieldReader<u64>, FastFieldReader<u64>)>,
+}
+
+impl GroupedSumCollector {
+    /// Creates a collector summing the values of `value_field`
+    /// for each value of `group_field`.
+    pub fn new(group_field: Field, value_field: Field) -> GroupedSumCollector {
+        GroupedSumCollector {
+            group_field,
+            value_field,
+            sums: HashMap::new(),
+            ff_readers: None,
+        }
+    }
+
+    /// Returns the sum of the values of the collected documents
+    /// of the given group, or `0` if no document of the group was collected.
+    pub fn sum(&self, group: u64) -> u128 {
+        self.sums.get(&group).cloned().unwrap_or(0u128)
+    }
+
+    /// Adds the sums of `other` to the sums of this collector, group by group.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    pub fn merge(&mut self, other: GroupedSumCollector) {
+        for (group, sum) in other.sums {
+            *self.sums.entry(group).or_insert(0u128) += sum;
+        }
+    }
+
+    /// Returns the `(group, sum)` pairs, sorted by increasing group.
+    pub fn harvest(self) -> Vec<(u64, u128)> {
+        let mut group_sums: Vec<(u64, u128)> = self.sums.into_iter().collect();
+        group_sums.sort_by_key(|&(group, _)| group);
+        group_sums
+    }
+}
+
+impl Collector for GroupedSumCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        let group_reader = reader.fast_field_reader(self.group_field)?;
+        let value_reader = reader.fast_field_reader(self.value_field)?;
+        self.ff_readers = Some((group_reader, value_reader));
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let (group, val) = {
+            let &(ref group_reader, ref value_reader) = self
+                .ff_readers
+                .as_ref()
+                .expect("collect() was called before set_segment. This should never happen.");
+            (group_reader.get(doc), value_reader.get(doc))
+        };
+        *self.sums.entry(group).or_insert(0u128) += u128::from(val);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::GroupedSumCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_grouped_sum_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let category_field = schema_builder.add_u64_field("category", FAST);
+        let price_field = schema_builder.add_u64_field("price", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0u64..100u64 {
+                let text = if i % 2 == 0 { "sold" } else { "returned" };
+                index_writer
+                    .add_document(doc!(
+                        text_field => text,
+                        category_field => i % 3,
+                        price_field => i
+                    ))
+                    .unwrap();
+                if i % 40 == 39 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            // Sums that would overflow a `u64`.
+            index_writer
+                .add_document(doc!(
+                    text_field => "sold",
+                    category_field => 10u64,
+                    price_field => u64::max_value()
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "sold",
+                    category_field => 10u64,
+                    price_field => u64::max_value()
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("sold")
+            .unwrap();
+
+        let mut collector = GroupedSumCollector::new(category_field, price_field);
+        searcher.search(&*query, &mut collector).unwrap();
+        let expected_sum = |category: u64| -> u128 {
+            (0u64..100u64)
+                .filter(|i| i % 2 == 0 && i % 3 == category)
+                .map(u128::from)
+                .sum()
+        };
+        for category in 0..3 {
+            assert_eq!(collector.sum(category), expected_sum(category));
+        }
+        assert_eq!(collector.sum(10), 2 * u128::from(u64::max_value()));
+        assert_eq!(collector.sum(4), 0);
+        let group_sums = collector.harvest();
+        let groups: Vec<u64> = group_sums.iter().map(|&(group, _)| group).collect();
+        assert_eq!(groups, vec![0u64, 1u64, 2u64, 10u64]);
+
+        // Merging the partitions, in any order, gives the same sums.
+        let mut merged = GroupedSumCollector::new(category_field, price_field);
+        for partition_ord in (0..3).rev() {
+            let mut partition_collector = GroupedSumCollector::new(category_field, price_field);
+            searcher
+                .search_partition(&*query, &mut partition_collector, (partition_ord, 3))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), group_sums);
+    }
+}
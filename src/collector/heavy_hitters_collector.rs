@@ -0,0 +1,247 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::collections::HashMap;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+// Hashes a value for the given row of the sketch.
+//
+// This is the finalizer of splitmix64, applied to the value
+// xored with a seed specific to the row.
+fn hash_for_row(val: u64, row: usize) -> u64 {
+    let mut h = val ^ (row as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+/// Collector estimating the `k` most frequent values of a
+/// `u64` fast field over the matching documents.
+///
+/// An exact count of each value, as computed by the `ValueCountCollector`,
+/// takes memory proportional to the number of distinct values.
+/// This collector relies on a count-min sketch instead: its memory usage
+/// is fixed, `width * depth` counters, whatever the cardinality of the field.
+///
+/// # Error bounds
+///
+/// Estimated counts never underestimate the actual counts.
+/// With `N` the number of counted values, each estimate exceeds the
+/// actual count by at most `e / width * N`, with a probability of
+/// at least `1 - exp(-depth)`. For instance, a `width` of `2_718` and
+/// a `depth` of `5` give an error under `0.1%` of `N` for more than
+/// `99%` of the values.
+///
+/// On top of the sketch, the collector keeps track of the `k` values
+/// with the highest estimates seen so far. Values much more frequent than
+/// the others are reliably found, but the tail of the top `k` may be
+/// missing values that are just as frequent as the `k`-th value.
+pub struct HeavyHittersCollector {
+    field: Field,
+    width: usize,
+    depth: usize,
+    k: usize,
+    sketch: Vec<u64>,
+    candidates: HashMap<u64, u64>,
+    num_values: u64,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl HeavyHittersCollector {
+    /// Creates a collector estimating the `k` most frequent values
+    /// of `field`, with a sketch of `depth` rows of `width` counters.
+    ///
+    /// # Panics
+    /// Panics if `width`, `depth` or `k` is `0`.
+    pub fn new(field: Field, width: usize, depth: usize, k: usize) -> HeavyHittersCollector {
+        assert!(
+            width > 0 && depth > 0 && k > 0,
+            "The width, the depth and k must all be positive."
+        );
+        HeavyHittersCollector {
+            field,
+            width,
+            depth,
+            k,
+            sketch: vec![0u64; width * depth],
+            candidates: HashMap::new(),
+            num_values: 0,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the number of values counted in the sketch.
+    pub fn num_values(&self) -> u64 {
+        self.num_values
+    }
+
+    /// Returns the estimated number of occurrences of `val`.
+    pub fn estimate(&self, val: u64) -> u64 {
+        (0..self.depth)
+            .map(|row| self.sketch[self.counter_ord(val, row)])
+            .min()
+            .unwrap_or(0u64)
+    }
+
+    /// Returns the (at most) `k` values with the highest estimated
+    /// counts, along with their estimated counts, by decreasing count.
+    pub fn top_k(&self) -> Vec<(u64, u64)> {
+        let mut top_k: Vec<(u64, u64)> = self
+            .candidates
+            .keys()
+            .map(|&val| (val, self.estimate(val)))
+            .collect();
+        top_k.sort_by(|left, right| right.1.cmp(&left.1).then(left.0.cmp(&right.0)));
+        top_k
+    }
+
+    /// Adds the values counted by `other` to this collector.
+    ///
+    /// Count-min sketches are additive, so that merging the collectors
+    /// of several searches, for instance the different partitions
+    /// of a `Searcher::search_partition`, gives the same sketch as a
+    /// single search over all of their documents.
+    ///
+    /// # Panics
+    /// Panics if the sketches of the two collectors do not have the same
+    /// dimensions.
+    pub fn merge(&mut self, other: HeavyHittersCollector) {
+        assert!(
+            self.width == other.width && self.depth == other.depth,
+            "Cannot merge sketches of different dimensions."
+        );
+        for (counter, other_counter) in self.sketch.iter_mut().zip(other.sketch) {
+            *counter += other_counter;
+        }
+        self.num_values += other.num_values;
+        let mut candidates: Vec<u64> = self.candidates.keys().cloned().collect();
+        candidates.extend(other.candidates.keys().cloned());
+        candidates.sort();
+        candidates.dedup();
+        self.candidates.clear();
+        for val in candidates {
+            let count = self.estimate(val);
+            self.update_candidates(val, count);
+        }
+    }
+
+    fn counter_ord(&self, val: u64, row: usize) -> usize {
+        row * self.width + (hash_for_row(val, row) % self.width as u64) as usize
+    }
+
+    fn update_candidates(&mut self, val: u64, count: u64) {
+        if self.candidates.len() < self.k || self.candidates.contains_key(&val) {
+            self.candidates.insert(val, count);
+            return;
+        }
+        let (min_val, min_count) = self
+            .candidates
+            .iter()
+            .map(|(&val, &count)| (val, count))
+            .min_by_key(|&(val, count)| (count, val))
+            .expect("k is positive, so that there is at least one candidate.");
+        if count > min_count {
+            self.candidates.remove(&min_val);
+            self.candidates.insert(val, count);
+        }
+    }
+}
+
+impl Collector for HeavyHittersCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        let mut count = u64::max_value();
+        for row in 0..self.depth {
+            let counter_ord = self.counter_ord(val, row);
+            self.sketch[counter_ord] += 1;
+            count = count.min(self.sketch[counter_ord]);
+        }
+        self.num_values += 1;
+        self.update_candidates(val, count);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::HeavyHittersCollector;
+    use query::AllQuery;
+    use schema::{SchemaBuilder, FAST};
+    use Index;
+
+    #[test]
+    fn test_heavy_hitters_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let category_field = schema_builder.add_u64_field("category", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // A few dominant categories, and a long tail of rare ones.
+            for i in 0u64..3_000u64 {
+                let category = if i % 10 < 5 {
+                    1u64
+                } else if i % 10 < 8 {
+                    2u64
+                } else if i % 10 == 8 {
+                    3u64
+                } else {
+                    1_000u64 + i
+                };
+                index_writer
+                    .add_document(doc!(category_field => category))
+                    .unwrap();
+                if i == 1_500 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+
+        let mut collector = HeavyHittersCollector::new(category_field, 1_024, 4, 3);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        assert_eq!(collector.num_values(), 3_000);
+        let top_k = collector.top_k();
+        assert_eq!(
+            top_k.iter().map(|&(val, _)| val).collect::<Vec<u64>>(),
+            vec![1u64, 2u64, 3u64]
+        );
+        // Estimates never underestimate, and are close to the actual counts.
+        for (&(_, estimate), &actual) in top_k.iter().zip(&[1_500u64, 900u64, 300u64]) {
+            assert!(estimate >= actual);
+            assert!(estimate - actual <= 3_000 * 3 / 1_024);
+        }
+        assert!(collector.estimate(1_009) >= 1);
+
+        // Merging the partitions gives the same sketch.
+        let mut merged = HeavyHittersCollector::new(category_field, 1_024, 4, 3);
+        for partition_ord in 0..2 {
+            let mut partition_collector = HeavyHittersCollector::new(category_field, 1_024, 4, 3);
+            searcher
+                .search_partition(&AllQuery, &mut partition_collector, (partition_ord, 2))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.num_values(), 3_000);
+        assert_eq!(merged.top_k(), top_k);
+    }
+}
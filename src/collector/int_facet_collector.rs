@@ -95,7 +95,7 @@ mod tests {
                         num_field_i64 => ((i as i64) % 3i64) as i64,
                         num_field_u64 => (i % 2u64) as u64,
                         text_field => "text"
-                    ));
+                    )).unwrap();
                 }
             }
             assert_eq!(index_writer.commit().unwrap(), 10u64);
@@ -0,0 +1,170 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::collections::HashMap;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector building the mapping from the values of a `u64` fast field
+/// to the matching documents having this value.
+///
+/// This makes it possible to answer "show me all of the documents
+/// with this exact value" without running one query per value.
+///
+/// Doc ids are first grouped per segment, and translated into
+/// `DocAddress`es when moving on to the next segment.
+/// For each value, the documents are sorted by `DocAddress`.
+pub struct InvertedValueCollector {
+    field: Field,
+    docs: HashMap<u64, Vec<DocAddress>>,
+    segment_docs: HashMap<u64, Vec<DocId>>,
+    segment_local_id: SegmentLocalId,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl InvertedValueCollector {
+    /// Creates a collector grouping documents by the value of the given
+    /// `u64` fast field.
+    pub fn for_field(field: Field) -> InvertedValueCollector {
+        InvertedValueCollector {
+            field,
+            docs: HashMap::new(),
+            segment_docs: HashMap::new(),
+            segment_local_id: 0,
+            ff_reader: None,
+        }
+    }
+
+    fn flush_segment(&mut self) {
+        let segment_local_id = self.segment_local_id;
+        for (val, doc_ids) in self.segment_docs.drain() {
+            self.docs.entry(val).or_insert_with(Vec::new).extend(
+                doc_ids
+                    .into_iter()
+                    .map(|doc_id| DocAddress(segment_local_id, doc_id)),
+            );
+        }
+    }
+
+    /// Returns the matching documents for each of the values.
+    pub fn harvest(mut self) -> HashMap<u64, Vec<DocAddress>> {
+        self.flush_segment();
+        for doc_addresses in self.docs.values_mut() {
+            doc_addresses.sort();
+        }
+        self.docs
+    }
+}
+
+impl Collector for InvertedValueCollector {
+    fn set_segment(
+        &mut self,
+        segment_local_id: SegmentLocalId,
+        reader: &SegmentReader,
+    ) -> Result<()> {
+        self.flush_segment();
+        self.segment_local_id = segment_local_id;
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        self.segment_docs
+            .entry(val)
+            .or_insert_with(Vec::new)
+            .push(doc);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::InvertedValueCollector;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, TEXT};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_inverted_value_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let val_field = schema_builder.add_u64_field("val", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", val_field => 3u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "b", val_field => 1u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", val_field => 3u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", val_field => 1u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "b", val_field => 3u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a", val_field => 7u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_ord = |expected_num_docs: u32| {
+            searcher
+                .segment_readers()
+                .iter()
+                .position(|segment_reader| segment_reader.num_docs() == expected_num_docs)
+                .unwrap() as u32
+        };
+        let first = segment_ord(3);
+        let second = 1 - first;
+
+        let mut collector = InvertedValueCollector::for_field(val_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        let docs = collector.harvest();
+        assert_eq!(docs.len(), 3);
+        let mut expected_3 = vec![
+            DocAddress(first, 0),
+            DocAddress(first, 2),
+            DocAddress(second, 1),
+        ];
+        expected_3.sort();
+        assert_eq!(docs[&3u64], expected_3);
+        let mut expected_1 = vec![DocAddress(first, 1), DocAddress(second, 0)];
+        expected_1.sort();
+        assert_eq!(docs[&1u64], expected_1);
+        assert_eq!(docs[&7u64], vec![DocAddress(second, 2)]);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "b"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = InvertedValueCollector::for_field(val_field);
+        searcher.search(&query, &mut collector).unwrap();
+        let docs = collector.harvest();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[&1u64], vec![DocAddress(first, 1)]);
+        assert_eq!(docs[&3u64], vec![DocAddress(second, 1)]);
+    }
+}
@@ -0,0 +1,159 @@
+use collector::Collector;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector keeping track of the highest score seen so far,
+/// and of the document that achieved it.
+///
+/// The running maximum is available at any point of the collection
+/// through `.max_score()`, for instance to give feedback to a query
+/// planner pruning the clauses that cannot reach it.
+///
+/// On a tie, the document with the lowest `DocAddress` is kept.
+///
+/// ```rust
+/// use tantivy::collector::MaxScoreCollector;
+///
+/// let collector = MaxScoreCollector::default();
+/// assert!(collector.max_score().is_none());
+/// ```
+pub struct MaxScoreCollector {
+    max_score: Option<(Score, DocAddress)>,
+    segment_id: SegmentLocalId,
+}
+
+impl Default for MaxScoreCollector {
+    fn default() -> MaxScoreCollector {
+        MaxScoreCollector {
+            max_score: None,
+            segment_id: 0,
+        }
+    }
+}
+
+impl MaxScoreCollector {
+    /// Returns the highest score seen so far, and the address of the
+    /// document that achieved it, or `None` if no document was collected.
+    pub fn max_score(&self) -> Option<(Score, DocAddress)> {
+        self.max_score
+    }
+
+    fn update(&mut self, score: Score, doc_address: DocAddress) {
+        let is_new_max = match self.max_score {
+            Some((max_score, max_doc_address)) => {
+                score > max_score || (score == max_score && doc_address < max_doc_address)
+            }
+            None => true,
+        };
+        if is_new_max {
+            self.max_score = Some((score, doc_address));
+        }
+    }
+
+    /// Combines the maximum of `other` with the maximum of this collector.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    pub fn merge(&mut self, other: MaxScoreCollector) {
+        if let Some((score, doc_address)) = other.max_score {
+            self.update(score, doc_address);
+        }
+    }
+
+    /// Returns the highest score, and the address of the
+    /// document that achieved it.
+    pub fn harvest(self) -> Option<(Score, DocAddress)> {
+        self.max_score
+    }
+}
+
+impl Collector for MaxScoreCollector {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let doc_address = DocAddress(self.segment_id, doc);
+        self.update(score, doc_address);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::MaxScoreCollector;
+    use collector::TopCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use Index;
+
+    #[test]
+    fn test_max_score_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..30 {
+                let text = match i % 5 {
+                    0 => "a b c",
+                    1 => "a",
+                    2 => "b c",
+                    3 => "c",
+                    _ => "a b b",
+                };
+                index_writer.add_document(doc!(text_field => text)).unwrap();
+                if i % 10 == 9 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("a b")
+            .unwrap();
+
+        let mut top_collector = TopCollector::with_limit(1);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let expected = top_collector.top_docs()[0];
+
+        let mut collector = MaxScoreCollector::default();
+        searcher.search(&*query, &mut collector).unwrap();
+        let (max_score, doc_address) = collector.max_score().unwrap();
+        assert_eq!(max_score, expected.0);
+        // The first of the documents "a b b" in the first segment.
+        assert_eq!(doc_address.segment_ord(), 0);
+        assert_eq!(doc_address.doc(), 4);
+
+        // Merging the partitions, in any order, gives the same maximum.
+        let mut merged = MaxScoreCollector::default();
+        for partition_ord in (0..3).rev() {
+            let mut partition_collector = MaxScoreCollector::default();
+            searcher
+                .search_partition(&*query, &mut partition_collector, (partition_ord, 3))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), Some((max_score, doc_address)));
+
+        // No document, no maximum.
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("d")
+            .unwrap();
+        let mut collector = MaxScoreCollector::default();
+        searcher.search(&*query, &mut collector).unwrap();
+        assert!(collector.harvest().is_none());
+    }
+}
@@ -24,12 +24,104 @@ pub use self::top_score_collector::TopScoreCollector as TopCollector;
 mod top_field_collector;
 pub use self::top_field_collector::TopFieldCollector;
 
+mod top_freshness_collector;
+pub use self::top_freshness_collector::TopFreshnessCollector;
+
+mod top_docs_with_count;
+pub use self::top_docs_with_count::TopDocsWithCount;
+
 mod facet_collector;
 pub use self::facet_collector::FacetCollector;
 
 mod chained_collector;
 pub use self::chained_collector::{chain, ChainedCollector};
 
+mod cancellable_collector;
+pub use self::cancellable_collector::CancellableCollector;
+
+mod time_range_collector;
+pub use self::time_range_collector::TimeRangeCollector;
+
+mod value_count_collector;
+pub use self::value_count_collector::{SparseCounts, ValueCountCollector};
+
+mod spilling_collector;
+pub use self::spilling_collector::{SpilledDocs, SpillingCollector};
+
+mod diff_collector;
+pub use self::diff_collector::DiffCollector;
+
+mod bytes_count_collector;
+pub use self::bytes_count_collector::BytesCountCollector;
+
+mod delta_doc_list_collector;
+pub use self::delta_doc_list_collector::{decode_doc_list, DeltaDocListCollector};
+
+mod feature_collector;
+pub use self::feature_collector::{FeatureCollector, FeatureSpec};
+
+mod inverted_value_collector;
+pub use self::inverted_value_collector::InvertedValueCollector;
+
+mod doc_score_probe_collector;
+pub use self::doc_score_probe_collector::DocScoreProbeCollector;
+
+mod weighted_mean_collector;
+pub use self::weighted_mean_collector::WeightedMeanCollector;
+
+mod score_filter_collector;
+pub use self::score_filter_collector::ScoreFilterCollector;
+
+mod named_bucket_collector;
+pub use self::named_bucket_collector::{BucketPredicate, NamedBucketCollector};
+
+mod pagination_collector;
+pub use self::pagination_collector::{PageCursor, PaginationCollector};
+
+mod product_collector;
+pub use self::product_collector::ProductCollector;
+
+mod score_export_collector;
+pub use self::score_export_collector::ScoreExportCollector;
+
+mod range_doc_collector;
+pub use self::range_doc_collector::RangeDocCollector;
+
+mod heavy_hitters_collector;
+pub use self::heavy_hitters_collector::HeavyHittersCollector;
+
+mod docs_and_sum_collector;
+pub use self::docs_and_sum_collector::DocsAndSumCollector;
+
+mod max_score_collector;
+pub use self::max_score_collector::MaxScoreCollector;
+
+mod diversified_top_k;
+pub use self::diversified_top_k::DiversifiedTopK;
+
+mod grouped_sum_collector;
+pub use self::grouped_sum_collector::GroupedSumCollector;
+
+mod nearest_value_collector;
+pub use self::nearest_value_collector::NearestValueCollector;
+
+mod top_by_fast_field;
+pub use self::top_by_fast_field::TopByFastField;
+
+mod expression;
+pub use self::expression::{BinaryOp, Expression, ExpressionFunction};
+
+mod top_expression_collector;
+pub use self::top_expression_collector::TopExpressionCollector;
+
+mod explaining_top_k;
+pub use self::explaining_top_k::ExplainingTopK;
+
+#[cfg(feature = "roaring-bitmap")]
+mod roaring_collector;
+#[cfg(feature = "roaring-bitmap")]
+pub use self::roaring_collector::RoaringCollector;
+
 /// Collectors are in charge of collecting and retaining relevant
 /// information from the document found and scored by the query.
 ///
@@ -62,6 +154,11 @@ pub use self::chained_collector::{chain, ChainedCollector};
 pub trait Collector {
     /// `set_segment` is called before beginning to enumerate
     /// on this segment.
+    ///
+    /// It is not called for the segments that the query cannot match
+    /// (see [`Weight::can_match`](../query/trait.Weight.html#method.can_match)).
+    /// Empty segments are not skipped otherwise: `set_segment` is called
+    /// for segments whose documents are all deleted, as for any other segment.
     fn set_segment(
         &mut self,
         segment_local_id: SegmentLocalId,
@@ -72,6 +169,50 @@ pub trait Collector {
 
     /// Returns true iff the collector requires to compute scores for documents.
     fn requires_scoring(&self) -> bool;
+
+    /// Returns the score below which documents are of no interest
+    /// to the collector, if any.
+    ///
+    /// Segments in which no document can reach this score, according to
+    /// [`Weight::max_score`](../query/trait.Weight.html#method.max_score),
+    /// are not scored at all: `set_segment` is called, but `collect` is not.
+    /// This is only an optimization, and documents scoring below the
+    /// threshold may still be collected.
+    fn score_threshold(&self) -> Option<Score> {
+        None
+    }
+
+    /// Returns true iff the collector can process the matching documents
+    /// block by block, through `.collect_block(...)`.
+    ///
+    /// Blocks are only pushed to collectors that do not require scoring,
+    /// by the queries that can go through their documents block by block
+    /// (see [`Scorer::block_docset`](../query/trait.Scorer.html#method.block_docset)).
+    /// Other queries push the documents one at a time, with `.collect(...)`.
+    fn accepts_blocks(&self) -> bool {
+        false
+    }
+
+    /// Pushes a block of sorted, non-deleted documents to the collector.
+    ///
+    /// By default, the documents are collected one at a time,
+    /// with a score of `1`.
+    fn collect_block(&mut self, docs: &[DocId]) {
+        for &doc in docs {
+            self.collect(doc, 1f32);
+        }
+    }
+
+    /// Returns true iff the collector does not need any more documents,
+    /// for instance because the search was cancelled.
+    ///
+    /// The search checks it before each segment, and periodically while
+    /// going through the documents of a segment, so that it stops early.
+    /// A few more documents may still be pushed to the collector after
+    /// it returns true.
+    fn is_done(&self) -> bool {
+        false
+    }
 }
 
 impl<'a, C: Collector> Collector for &'a mut C {
@@ -90,6 +231,22 @@ impl<'a, C: Collector> Collector for &'a mut C {
     fn requires_scoring(&self) -> bool {
         C::requires_scoring(self)
     }
+
+    fn score_threshold(&self) -> Option<Score> {
+        C::score_threshold(self)
+    }
+
+    fn accepts_blocks(&self) -> bool {
+        C::accepts_blocks(self)
+    }
+
+    fn collect_block(&mut self, docs: &[DocId]) {
+        C::collect_block(self, docs)
+    }
+
+    fn is_done(&self) -> bool {
+        C::is_done(self)
+    }
 }
 
 #[cfg(test)]
@@ -124,6 +281,11 @@ pub mod tests {
         pub fn scores(self) -> Vec<Score> {
             self.scores
         }
+
+        /// Returns both the documents and their scores.
+        pub fn docs_and_scores(self) -> (Vec<DocId>, Vec<Score>) {
+            (self.docs, self.scores)
+        }
     }
 
     impl Default for TestCollector {
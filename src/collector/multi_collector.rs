@@ -28,16 +28,16 @@ use SegmentReader;
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -94,13 +94,19 @@ impl<'a> Collector for MultiCollector<'a> {
             .iter()
             .any(|collector| collector.requires_scoring())
     }
+
+    fn is_done(&self) -> bool {
+        self.collectors.iter().all(|collector| collector.is_done())
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use collector::{Collector, CountCollector, TopScoreCollector};
+    use collector::{CancellableCollector, Collector, CountCollector, TopScoreCollector};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn test_multi_collector() {
@@ -116,4 +122,21 @@ mod tests {
         assert_eq!(count_collector.count(), 3);
         assert!(top_collector.at_capacity());
     }
+
+    #[test]
+    fn test_multi_collector_is_done() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut first = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        let mut second = CancellableCollector::new(CountCollector::default(), cancel.clone());
+        let mut count_collector = CountCollector::default();
+        {
+            let collectors = MultiCollector::from(vec![&mut first, &mut second]);
+            assert!(!collectors.is_done());
+            cancel.store(true, Ordering::SeqCst);
+            assert!(collectors.is_done());
+        }
+        // The count collector still needs documents.
+        let collectors = MultiCollector::from(vec![&mut first, &mut count_collector]);
+        assert!(!collectors.is_done());
+    }
 }
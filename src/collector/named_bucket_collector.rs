@@ -0,0 +1,176 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Predicate deciding whether a fast field value belongs to a bucket.
+pub type BucketPredicate = Box<Fn(u64) -> bool + Sync>;
+
+/// Collector counting the matching documents falling into a fixed set
+/// of named buckets, according to the value of a `u64` fast field.
+///
+/// Each bucket is defined by a predicate on the value.
+/// Buckets may overlap: a document is counted once in every bucket
+/// whose predicate accepts its value, and documents accepted by none
+/// of the predicates are not counted at all.
+///
+/// ```rust
+/// use tantivy::collector::{BucketPredicate, NamedBucketCollector};
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let price_field = schema_builder.add_u64_field("price", FAST);
+///
+/// let collector = NamedBucketCollector::new(
+///     vec![
+///         ("cheap", Box::new(|price: u64| price < 10) as BucketPredicate),
+///         ("affordable", Box::new(|price: u64| price < 100) as BucketPredicate),
+///     ],
+///     price_field,
+/// );
+/// ```
+pub struct NamedBucketCollector {
+    field: Field,
+    predicates: Vec<BucketPredicate>,
+    bucket_counts: Vec<(String, u64)>,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl NamedBucketCollector {
+    /// Creates a collector counting the documents in each of the `buckets`,
+    /// given as `(name, predicate)` pairs.
+    pub fn new(buckets: Vec<(&str, BucketPredicate)>, field: Field) -> NamedBucketCollector {
+        let mut predicates = Vec::with_capacity(buckets.len());
+        let mut bucket_counts = Vec::with_capacity(buckets.len());
+        for (name, predicate) in buckets {
+            predicates.push(predicate);
+            bucket_counts.push((name.to_string(), 0u64));
+        }
+        NamedBucketCollector {
+            field,
+            predicates,
+            bucket_counts,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the `(name, count)` of each bucket, in the order
+    /// in which the buckets were given.
+    pub fn bucket_counts(&self) -> &[(String, u64)] {
+        &self.bucket_counts[..]
+    }
+
+    /// Returns the `(name, count)` of each bucket.
+    pub fn harvest(self) -> Vec<(String, u64)> {
+        self.bucket_counts
+    }
+}
+
+impl Collector for NamedBucketCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        for (predicate, bucket_count) in self.predicates.iter().zip(self.bucket_counts.iter_mut()) {
+            if predicate(val) {
+                bucket_count.1 += 1;
+            }
+        }
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{BucketPredicate, NamedBucketCollector};
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_named_bucket_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let price_field = schema_builder.add_u64_field("price", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for (i, price) in [5u64, 15, 50, 150].iter().enumerate() {
+                let text = if i % 2 == 0 { "even" } else { "odd" };
+                index_writer
+                    .add_document(doc!(text_field => text, price_field => *price))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            for (i, price) in [8u64, 99, 1_000].iter().enumerate() {
+                let text = if i % 2 == 0 { "even" } else { "odd" };
+                index_writer
+                    .add_document(doc!(text_field => text, price_field => *price))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let buckets = || -> Vec<(&'static str, BucketPredicate)> {
+            vec![
+                (
+                    "cheap",
+                    Box::new(|price: u64| price < 10) as BucketPredicate,
+                ),
+                (
+                    "affordable",
+                    Box::new(|price: u64| price < 100) as BucketPredicate,
+                ),
+                (
+                    "expensive",
+                    Box::new(|price: u64| price >= 100) as BucketPredicate,
+                ),
+                ("free", Box::new(|price: u64| price == 0) as BucketPredicate),
+            ]
+        };
+
+        let mut collector = NamedBucketCollector::new(buckets(), price_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        assert_eq!(
+            collector.harvest(),
+            vec![
+                ("cheap".to_string(), 2),
+                ("affordable".to_string(), 5),
+                ("expensive".to_string(), 2),
+                ("free".to_string(), 0),
+            ]
+        );
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = NamedBucketCollector::new(buckets(), price_field);
+        searcher.search(&query, &mut collector).unwrap();
+        assert_eq!(
+            collector.bucket_counts(),
+            &[
+                ("cheap".to_string(), 2),
+                ("affordable".to_string(), 3),
+                ("expensive".to_string(), 1),
+                ("free".to_string(), 0),
+            ]
+        );
+    }
+}
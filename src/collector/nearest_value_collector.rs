@@ -0,0 +1,213 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use fastfield::FastValue;
+use schema::Field;
+use std::collections::BinaryHeap;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector keeping the `k` documents whose value for a `u64` or `i64`
+/// fast field is the nearest to a `target` value.
+///
+/// The documents are ranked by increasing distance `|value - target|`,
+/// ties being broken in favor of the lowest `DocAddress`.
+///
+/// The implementation is based on a `BinaryHeap` holding the `k` nearest
+/// documents so far, the farthest one on top.
+///
+/// ```rust
+/// use tantivy::collector::NearestValueCollector;
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let price_field = schema_builder.add_u64_field("price", FAST);
+///
+/// // The 10 items whose price is the closest to 150.
+/// let collector = NearestValueCollector::new(price_field, 150u64, 10);
+/// ```
+pub struct NearestValueCollector<T: FastValue> {
+    field: Field,
+    target: T,
+    k: usize,
+    heap: BinaryHeap<(u64, DocAddress)>,
+    segment_id: SegmentLocalId,
+    ff_reader: Option<FastFieldReader<T>>,
+}
+
+impl<T: FastValue> NearestValueCollector<T> {
+    /// Creates a collector keeping the `k` documents whose value of `field`
+    /// is the nearest to `target`.
+    ///
+    /// # Panics
+    /// The method panics if `k` is 0.
+    pub fn new(field: Field, target: T, k: usize) -> NearestValueCollector<T> {
+        assert!(k > 0, "k must be strictly positive.");
+        NearestValueCollector {
+            field,
+            target,
+            k,
+            heap: BinaryHeap::with_capacity(k),
+            segment_id: 0,
+            ff_reader: None,
+        }
+    }
+
+    fn push(&mut self, distance: u64, doc_address: DocAddress) {
+        if self.heap.len() < self.k {
+            self.heap.push((distance, doc_address));
+        } else {
+            let mut farthest = self
+                .heap
+                .peek_mut()
+                .expect("Collector with k = 0 is forbidden");
+            if (distance, doc_address) < *farthest {
+                *farthest = (distance, doc_address);
+            }
+        }
+    }
+
+    /// Adds the documents kept by `other` to this collector,
+    /// keeping the `k` nearest ones.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    pub fn merge(&mut self, other: NearestValueCollector<T>) {
+        for (distance, doc_address) in other.heap {
+            self.push(distance, doc_address);
+        }
+    }
+
+    /// Returns the (at most) `k` nearest documents along with
+    /// their distance to the target, sorted by increasing distance.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn top_docs(&self) -> Vec<(u64, DocAddress)> {
+        let mut nearest_docs: Vec<(u64, DocAddress)> = self.heap.iter().cloned().collect();
+        nearest_docs.sort();
+        nearest_docs
+    }
+
+    /// Returns the nearest documents, as in `.top_docs()`.
+    pub fn harvest(self) -> Vec<(u64, DocAddress)> {
+        let mut nearest_docs = self.heap.into_vec();
+        nearest_docs.sort();
+        nearest_docs
+    }
+}
+
+impl<T: FastValue> Collector for NearestValueCollector<T> {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let value = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        // The `u64` representation of fast values preserves their order,
+        // and the distance between `i64` values.
+        let (value, target) = (value.to_u64(), self.target.to_u64());
+        let distance = if value > target {
+            value - target
+        } else {
+            target - value
+        };
+        let doc_address = DocAddress(self.segment_id, doc);
+        self.push(distance, doc_address);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::NearestValueCollector;
+    use query::AllQuery;
+    use schema::{SchemaBuilder, FAST};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_nearest_value_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let price_field = schema_builder.add_u64_field("price", FAST);
+        let temperature_field = schema_builder.add_i64_field("temperature", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(price, temperature) in &[(100u64, -10i64), (180, 12), (140, -3), (160, 3)] {
+                index_writer
+                    .add_document(doc!(
+                        price_field => price,
+                        temperature_field => temperature
+                    ))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            for &(price, temperature) in &[(150u64, 40i64), (400, 0), (145, -1)] {
+                index_writer
+                    .add_document(doc!(
+                        price_field => price,
+                        temperature_field => temperature
+                    ))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let price = |doc_address: DocAddress| {
+            searcher
+                .segment_reader(doc_address.segment_ord())
+                .fast_field_reader::<u64>(price_field)
+                .unwrap()
+                .get(doc_address.doc())
+        };
+
+        let mut collector = NearestValueCollector::new(price_field, 150u64, 4);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        let nearest_docs = collector.top_docs();
+        let distances: Vec<u64> = nearest_docs.iter().map(|&(distance, _)| distance).collect();
+        assert_eq!(distances, vec![0, 5, 10, 10]);
+        let prices: Vec<u64> = nearest_docs
+            .iter()
+            .map(|&(_, doc_address)| price(doc_address))
+            .collect();
+        assert_eq!(&prices[..2], &[150, 145]);
+        // 140 and 160 are at the same distance: they are sorted by `DocAddress`.
+        assert!(nearest_docs[2].1 < nearest_docs[3].1);
+
+        // Merging the partitions, in any order, gives the same documents.
+        let mut merged = NearestValueCollector::new(price_field, 150u64, 4);
+        for partition_ord in (0..2).rev() {
+            let mut partition_collector = NearestValueCollector::new(price_field, 150u64, 4);
+            searcher
+                .search_partition(&AllQuery, &mut partition_collector, (partition_ord, 2))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), nearest_docs);
+
+        // Distances between signed values.
+        let mut collector = NearestValueCollector::new(temperature_field, -2i64, 3);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        let distances: Vec<u64> = collector
+            .harvest()
+            .into_iter()
+            .map(|(distance, _)| distance)
+            .collect();
+        assert_eq!(distances, vec![1, 1, 2]);
+    }
+}
@@ -0,0 +1,230 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Position of a document in the order of a `PaginationCollector`:
+/// its score, the value of its tiebreak fast field, and its address.
+pub type PageCursor = (Score, u64, DocAddress);
+
+/// Wraps a `PageCursor` to rank documents: by decreasing score,
+/// then by increasing tiebreak value, then by increasing address.
+///
+/// The `BinaryHeap` being a max heap, its top is the document
+/// ranking last.
+#[derive(Clone, Copy)]
+struct RankedDoc(PageCursor);
+
+impl Ord for RankedDoc {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (score, tiebreak, doc_address) = self.0;
+        let (other_score, other_tiebreak, other_doc_address) = other.0;
+        other_score
+            .partial_cmp(&score)
+            .unwrap_or(Ordering::Equal)
+            .then(tiebreak.cmp(&other_tiebreak))
+            .then(doc_address.cmp(&other_doc_address))
+    }
+}
+
+impl PartialOrd for RankedDoc {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for RankedDoc {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RankedDoc {}
+
+/// Collector returning a page of results, for search-after pagination.
+///
+/// Documents are ranked by decreasing score, then by increasing value
+/// of a `u64` fast field, then by increasing `DocAddress`. This order is
+/// total, so that the last document of a page is a cursor from which the
+/// next page deterministically resumes, without overlaps nor gaps.
+///
+/// The collector only keeps the `limit` best documents ranking strictly
+/// after the `after` cursor.
+///
+/// `DocAddress`es are only meaningful for a given `Searcher`: all of the
+/// pages should be collected with the same searcher.
+pub struct PaginationCollector {
+    tiebreak_field: Field,
+    after: Option<RankedDoc>,
+    limit: usize,
+    heap: BinaryHeap<RankedDoc>,
+    segment_id: SegmentLocalId,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl PaginationCollector {
+    /// Creates a collector returning the `limit` documents following
+    /// the `after` cursor, or the first `limit` documents if `after` is `None`.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn new(
+        tiebreak_field: Field,
+        after: Option<PageCursor>,
+        limit: usize,
+    ) -> PaginationCollector {
+        if limit < 1 {
+            panic!("Limit must be strictly greater than 0.");
+        }
+        PaginationCollector {
+            tiebreak_field,
+            after: after.map(RankedDoc),
+            limit,
+            heap: BinaryHeap::with_capacity(limit),
+            segment_id: 0,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the documents of the page, in order.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn page(&self) -> Vec<PageCursor> {
+        let mut ranked_docs: Vec<RankedDoc> = self.heap.iter().cloned().collect();
+        ranked_docs.sort();
+        ranked_docs
+            .into_iter()
+            .map(|ranked_doc| ranked_doc.0)
+            .collect()
+    }
+
+    /// Returns the cursor to pass to the collector of the next page,
+    /// that is the last document of this page.
+    ///
+    /// Returns `None` if the page is empty.
+    pub fn next_cursor(&self) -> Option<PageCursor> {
+        self.heap.peek().map(|ranked_doc| ranked_doc.0)
+    }
+}
+
+impl Collector for PaginationCollector {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        self.ff_reader = Some(reader.fast_field_reader(self.tiebreak_field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let tiebreak = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        let ranked_doc = RankedDoc((score, tiebreak, DocAddress(self.segment_id, doc)));
+        if let Some(ref after) = self.after {
+            if ranked_doc <= *after {
+                return;
+            }
+        }
+        if self.heap.len() < self.limit {
+            self.heap.push(ranked_doc);
+        } else {
+            // It's ok to unwrap as long as a limit of 0 is forbidden.
+            let mut last_doc = self
+                .heap
+                .peek_mut()
+                .expect("Pagination collector with size 0 is forbidden");
+            if ranked_doc < *last_doc {
+                *last_doc = ranked_doc;
+            }
+        }
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::PaginationCollector;
+    use collector::CountCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_pagination_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let rank_field = schema_builder.add_u64_field("rank", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let texts = ["a", "a b", "a a", "b", "a b c"];
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0u64..60u64 {
+                let text = texts[(i % 5) as usize];
+                index_writer
+                    .add_document(doc!(text_field => text, rank_field => i % 3))
+                    .unwrap();
+                if i == 30 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("a")
+            .unwrap();
+        let mut count_collector = CountCollector::default();
+        searcher.search(&*query, &mut count_collector).unwrap();
+        assert_eq!(count_collector.count(), 48);
+
+        let mut full_collector = PaginationCollector::new(rank_field, None, 1_000);
+        searcher.search(&*query, &mut full_collector).unwrap();
+        let all_docs = full_collector.page();
+        assert_eq!(all_docs.len(), 48);
+
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let mut collector = PaginationCollector::new(rank_field, cursor, 7);
+            searcher.search(&*query, &mut collector).unwrap();
+            let page = collector.page();
+            if page.is_empty() {
+                assert!(collector.next_cursor().is_none());
+                break;
+            }
+            assert!(page.len() <= 7);
+            assert_eq!(collector.next_cursor(), page.last().cloned());
+            cursor = collector.next_cursor();
+            pages.push(page);
+        }
+        assert_eq!(pages.len(), 7);
+        let paged_docs: Vec<_> = pages.into_iter().flat_map(|page| page).collect();
+        assert_eq!(paged_docs, all_docs);
+
+        // The order is by decreasing score, then increasing rank and address.
+        for window in all_docs.windows(2) {
+            let (score, rank, doc_address) = window[0];
+            let (next_score, next_rank, next_doc_address) = window[1];
+            assert!(
+                score > next_score
+                    || (score == next_score && rank < next_rank)
+                    || (score == next_score && rank == next_rank && doc_address < next_doc_address)
+            );
+        }
+    }
+}
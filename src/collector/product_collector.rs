@@ -0,0 +1,198 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector computing the product of a fast field over the
+/// matching documents, for instance to combine probabilities
+/// in naive-Bayes-style scoring.
+///
+/// The values are `f64` stored in a `u64` fast field as their bit
+/// representation, as returned by `f64::to_bits`.
+///
+/// The product is accumulated in log-space, as a sum of logarithms,
+/// so that multiplying many small values does not underflow.
+/// It is only exponentiated in `.product()`.
+/// A value of `0` makes the product `0`, whatever the other values.
+///
+/// ```rust
+/// use tantivy::collector::ProductCollector;
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let probability_field = schema_builder.add_u64_field("probability", FAST);
+/// # let mut doc = tantivy::Document::default();
+///
+/// // At indexing time
+/// doc.add_u64(probability_field, 0.25f64.to_bits());
+///
+/// // At search time
+/// let collector = ProductCollector::for_field(probability_field);
+/// ```
+pub struct ProductCollector {
+    field: Field,
+    log_sum: f64,
+    has_zero: bool,
+    is_negative: bool,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl ProductCollector {
+    /// Creates a collector multiplying the values of `field`.
+    pub fn for_field(field: Field) -> ProductCollector {
+        ProductCollector {
+            field,
+            log_sum: 0f64,
+            has_zero: false,
+            is_negative: false,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the natural logarithm of the absolute value of the product.
+    ///
+    /// It is `f64::NEG_INFINITY` if one of the values is `0`.
+    pub fn log_product(&self) -> f64 {
+        if self.has_zero {
+            ::std::f64::NEG_INFINITY
+        } else {
+            self.log_sum
+        }
+    }
+
+    /// Returns the product of the values of the matching documents.
+    ///
+    /// The product of no values is `1`.
+    pub fn product(&self) -> f64 {
+        if self.has_zero {
+            return 0f64;
+        }
+        let abs_product = self.log_sum.exp();
+        if self.is_negative {
+            -abs_product
+        } else {
+            abs_product
+        }
+    }
+}
+
+impl Collector for ProductCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = f64::from_bits(
+            self.ff_reader
+                .as_ref()
+                .expect("collect() was called before set_segment. This should never happen.")
+                .get(doc),
+        );
+        if val == 0f64 {
+            self.has_zero = true;
+            return;
+        }
+        if val < 0f64 {
+            self.is_negative = !self.is_negative;
+        }
+        self.log_sum += val.abs().ln();
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ProductCollector;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_product_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let probability_field = schema_builder.add_u64_field("probability", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let probabilities = [0.5f64, 0.25, 0.9, 0.01, 0.75, 0.3];
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for (i, probability) in probabilities.iter().enumerate() {
+                index_writer
+                    .add_document(doc!(
+                        text_field => "likely",
+                        probability_field => probability.to_bits()
+                    ))
+                    .unwrap();
+                if i == 2 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer
+                .add_document(doc!(
+                    text_field => "impossible",
+                    probability_field => 0f64.to_bits()
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "likely"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = ProductCollector::for_field(probability_field);
+        searcher.search(&query, &mut collector).unwrap();
+        let expected: f64 = probabilities.iter().product();
+        assert!((collector.product() - expected).abs() < 1e-12);
+        assert!((collector.log_product() - expected.ln()).abs() < 1e-9);
+
+        let mut collector = ProductCollector::for_field(probability_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        assert_eq!(collector.product(), 0f64);
+        assert_eq!(collector.log_product(), ::std::f64::NEG_INFINITY);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "absent"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = ProductCollector::for_field(probability_field);
+        searcher.search(&query, &mut collector).unwrap();
+        assert_eq!(collector.product(), 1f64);
+    }
+
+    #[test]
+    fn test_product_collector_does_not_underflow() {
+        let mut schema_builder = SchemaBuilder::default();
+        let probability_field = schema_builder.add_u64_field("probability", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for _ in 0..1_000 {
+                index_writer
+                    .add_document(doc!(probability_field => 1e-3f64.to_bits()))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut collector = ProductCollector::for_field(probability_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        // 1e-3000 is not representable as a f64, but its logarithm is.
+        assert_eq!(collector.product(), 0f64);
+        let expected_log_product = 1_000f64 * 1e-3f64.ln();
+        assert!((collector.log_product() - expected_log_product).abs() < 1e-6);
+    }
+}
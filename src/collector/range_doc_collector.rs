@@ -0,0 +1,127 @@
+use collector::Collector;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector returning the doc ids of the matching documents
+/// of each segment as ranges of contiguous doc ids.
+///
+/// Each range `(start, end)` holds the doc ids `start..end`, `end`
+/// being excluded. Within a segment, the ranges are ordered and do not
+/// touch one another.
+///
+/// When the matches are dense, for instance with a filter on
+/// a sorted index, this is far more compact than a list of doc ids.
+/// When they are sparse, each match takes a range on its own:
+/// consider a `DeltaDocListCollector` instead.
+pub struct RangeDocCollector {
+    segment_ranges: Vec<(SegmentLocalId, Vec<(DocId, DocId)>)>,
+}
+
+impl Default for RangeDocCollector {
+    fn default() -> RangeDocCollector {
+        RangeDocCollector {
+            segment_ranges: Vec::new(),
+        }
+    }
+}
+
+impl RangeDocCollector {
+    /// Returns the ranges of matching doc ids of the segments
+    /// containing at least one matching document,
+    /// ordered by segment ordinal.
+    pub fn harvest(self) -> Vec<(SegmentLocalId, Vec<(DocId, DocId)>)> {
+        self.segment_ranges
+            .into_iter()
+            .filter(|&(_, ref ranges)| !ranges.is_empty())
+            .collect()
+    }
+}
+
+impl Collector for RangeDocCollector {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_ranges.push((segment_local_id, Vec::new()));
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let ranges = &mut self
+            .segment_ranges
+            .last_mut()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .1;
+        // Doc ids are collected in increasing order, so that a doc id
+        // can only extend the last range.
+        if let Some(&mut (_, ref mut end)) = ranges.last_mut() {
+            debug_assert!(doc >= *end);
+            if doc == *end {
+                *end += 1;
+                return;
+            }
+        }
+        ranges.push((doc, doc + 1));
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::RangeDocCollector;
+    use collector::tests::TestCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use Index;
+
+    #[test]
+    fn test_range_doc_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1_000 {
+                if (i >= 100 && i < 900) || i == 950 || i == 952 {
+                    index_writer.add_document(doc!(text_field => "a")).unwrap();
+                } else {
+                    index_writer.add_document(doc!(text_field => "b")).unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("a").unwrap();
+
+        let mut collector = RangeDocCollector::default();
+        searcher.search(&*query, &mut collector).unwrap();
+        let segment_ranges = collector.harvest();
+        assert_eq!(segment_ranges.len(), 1);
+        let (segment_local_id, ref ranges) = segment_ranges[0];
+        assert_eq!(searcher.segment_reader(segment_local_id).max_doc(), 1_000);
+        assert_eq!(ranges, &vec![(100, 900), (950, 951), (952, 953)]);
+
+        let offset: u32 = searcher.segment_readers()[..segment_local_id as usize]
+            .iter()
+            .map(|segment_reader| segment_reader.max_doc())
+            .sum();
+        let docs: Vec<u32> = ranges
+            .iter()
+            .flat_map(|&(start, end)| start..end)
+            .map(|doc| doc + offset)
+            .collect();
+        let mut test_collector = TestCollector::default();
+        searcher.search(&*query, &mut test_collector).unwrap();
+        assert_eq!(docs, test_collector.docs());
+    }
+}
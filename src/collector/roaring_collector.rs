@@ -0,0 +1,148 @@
+extern crate roaring;
+
+use self::roaring::RoaringBitmap;
+use collector::Collector;
+use std::collections::BTreeMap;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector gathering the matching documents of each segment
+/// in a [Roaring bitmap](https://roaringbitmap.org/).
+///
+/// The bitmaps are harvested in the portable serialization format
+/// of Roaring bitmaps, so that they can be handed over to other systems,
+/// e.g. to compute set operations over the results of several queries.
+///
+/// This collector requires the `roaring-bitmap` feature.
+///
+/// ```rust
+/// use tantivy::collector::RoaringCollector;
+///
+/// let collector = RoaringCollector::default();
+/// ```
+#[derive(Default)]
+pub struct RoaringCollector {
+    bitmaps: BTreeMap<SegmentLocalId, RoaringBitmap>,
+    segment_id: SegmentLocalId,
+}
+
+impl RoaringCollector {
+    /// Returns the bitmap of the documents collected in the given segment,
+    /// or `None` if no document of the segment was collected.
+    pub fn bitmap(&self, segment_id: SegmentLocalId) -> Option<&RoaringBitmap> {
+        self.bitmaps.get(&segment_id)
+    }
+
+    /// Adds the documents collected by `other` to this collector.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    pub fn merge(&mut self, other: RoaringCollector) {
+        for (segment_id, bitmap) in other.bitmaps {
+            self.bitmaps
+                .entry(segment_id)
+                .or_insert_with(RoaringBitmap::new)
+                .union_with(&bitmap);
+        }
+    }
+
+    /// Returns the serialized bitmap of each segment having
+    /// collected documents, sorted by segment.
+    pub fn harvest(self) -> Vec<(SegmentLocalId, Vec<u8>)> {
+        self.bitmaps
+            .into_iter()
+            .map(|(segment_id, bitmap)| {
+                let mut bytes = Vec::with_capacity(bitmap.serialized_size());
+                bitmap
+                    .serialize_into(&mut bytes)
+                    .expect("Writing to a Vec<u8> should never fail.");
+                (segment_id, bytes)
+            }).collect()
+    }
+}
+
+impl Collector for RoaringCollector {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        self.bitmaps
+            .entry(self.segment_id)
+            .or_insert_with(RoaringBitmap::new)
+            .insert(doc);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::roaring::RoaringBitmap;
+    use super::RoaringCollector;
+    use collector::TopCollector;
+    use indexer::NoMergePolicy;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_roaring_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for i in 0..1_000 {
+                let text = if i % 3 == 0 { "fizz" } else { "buzz" };
+                index_writer.add_document(doc!(text_field => text)).unwrap();
+                if i % 400 == 399 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("fizz")
+            .unwrap();
+
+        let mut top_collector = TopCollector::with_limit(1_000);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let mut expected_docs = top_collector.docs();
+        expected_docs.sort();
+
+        let mut collector = RoaringCollector::default();
+        searcher.search(&*query, &mut collector).unwrap();
+        let segment_bitmaps = collector.harvest();
+        assert_eq!(segment_bitmaps.len(), 3);
+        let mut docs: Vec<DocAddress> = Vec::new();
+        for &(segment_id, ref bytes) in &segment_bitmaps {
+            let bitmap = RoaringBitmap::deserialize_from(&bytes[..]).unwrap();
+            docs.extend(bitmap.iter().map(|doc| DocAddress(segment_id, doc)));
+        }
+        assert_eq!(docs, expected_docs);
+
+        // Merging the partitions, in any order, gives the same bitmaps.
+        let mut merged = RoaringCollector::default();
+        for partition_ord in (0..4).rev() {
+            let mut partition_collector = RoaringCollector::default();
+            searcher
+                .search_partition(&*query, &mut partition_collector, (partition_ord, 4))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), segment_bitmaps);
+    }
+}
@@ -0,0 +1,194 @@
+use collector::Collector;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector exporting the address and the score of all of the
+/// matching documents, for instance for offline analysis.
+///
+/// The results are columnar: a vector of `DocAddress`es, and a
+/// parallel vector holding their scores. They are ordered by `DocAddress`,
+/// that is by segment ordinal, then by doc id.
+///
+/// # Warning
+///
+/// Unlike the top-k collectors, this collector keeps every single
+/// matching document, in about 12 bytes per document: its memory usage
+/// is proportional to the number of matches, which may be as large as
+/// the number of documents in the index.
+/// Consider a `CountCollector` first, to check the size of the export.
+pub struct ScoreExportCollector {
+    doc_addresses: Vec<DocAddress>,
+    scores: Vec<Score>,
+    segment_id: SegmentLocalId,
+}
+
+impl Default for ScoreExportCollector {
+    fn default() -> ScoreExportCollector {
+        ScoreExportCollector {
+            doc_addresses: Vec::new(),
+            scores: Vec::new(),
+            segment_id: 0,
+        }
+    }
+}
+
+impl ScoreExportCollector {
+    /// Returns the number of collected documents.
+    pub fn len(&self) -> usize {
+        self.doc_addresses.len()
+    }
+
+    /// Returns true if no document was collected.
+    pub fn is_empty(&self) -> bool {
+        self.doc_addresses.is_empty()
+    }
+
+    /// Returns the addresses of the collected documents.
+    pub fn doc_addresses(&self) -> &[DocAddress] {
+        &self.doc_addresses[..]
+    }
+
+    /// Returns the scores of the collected documents, in the order
+    /// of `.doc_addresses()`.
+    pub fn scores(&self) -> &[Score] {
+        &self.scores[..]
+    }
+
+    /// Adds the documents collected by `other` to this collector.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    /// The result is ordered by `DocAddress`, whatever the order of
+    /// the merges.
+    pub fn merge(&mut self, other: ScoreExportCollector) {
+        let num_docs = self.len() + other.len();
+        let mut doc_addresses = Vec::with_capacity(num_docs);
+        let mut scores = Vec::with_capacity(num_docs);
+        {
+            let mut left = self.doc_addresses.iter().zip(self.scores.iter()).peekable();
+            let mut right = other
+                .doc_addresses
+                .iter()
+                .zip(other.scores.iter())
+                .peekable();
+            loop {
+                let take_left = match (left.peek(), right.peek()) {
+                    (Some(&(left_address, _)), Some(&(right_address, _))) => {
+                        left_address <= right_address
+                    }
+                    (Some(_), None) => true,
+                    (None, Some(_)) => false,
+                    (None, None) => break,
+                };
+                let (&doc_address, &score) = if take_left {
+                    left.next().unwrap()
+                } else {
+                    right.next().unwrap()
+                };
+                doc_addresses.push(doc_address);
+                scores.push(score);
+            }
+        }
+        self.doc_addresses = doc_addresses;
+        self.scores = scores;
+    }
+
+    /// Returns the addresses of the collected documents,
+    /// and the parallel vector of their scores.
+    pub fn harvest(self) -> (Vec<DocAddress>, Vec<Score>) {
+        (self.doc_addresses, self.scores)
+    }
+}
+
+impl Collector for ScoreExportCollector {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.segment_id = segment_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.doc_addresses.push(DocAddress(self.segment_id, doc));
+        self.scores.push(score);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ScoreExportCollector;
+    use collector::TopCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use Index;
+
+    #[test]
+    fn test_score_export_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let texts = ["a", "a b", "a a", "b", "a b c"];
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..50 {
+                index_writer
+                    .add_document(doc!(text_field => texts[i % 5]))
+                    .unwrap();
+                if i % 20 == 19 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("a")
+            .unwrap();
+
+        let mut collector = ScoreExportCollector::default();
+        searcher.search(&*query, &mut collector).unwrap();
+        assert_eq!(collector.len(), 40);
+        let mut top_collector = TopCollector::with_limit(100);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let mut expected = top_collector.top_docs();
+        expected.sort_by_key(|&(_, doc_address)| doc_address);
+        let (doc_addresses, scores) = collector.harvest();
+        assert_eq!(
+            doc_addresses,
+            expected
+                .iter()
+                .map(|&(_, doc_address)| doc_address)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            scores,
+            expected.iter().map(|&(score, _)| score).collect::<Vec<_>>()
+        );
+
+        // Merging the partitions, in any order, gives the same columns.
+        let mut partitions: Vec<ScoreExportCollector> = (0..4)
+            .map(|partition_ord| {
+                let mut collector = ScoreExportCollector::default();
+                searcher
+                    .search_partition(&*query, &mut collector, (partition_ord, 4))
+                    .unwrap();
+                collector
+            }).collect();
+        partitions.swap(0, 3);
+        let mut merged = ScoreExportCollector::default();
+        for partition in partitions {
+            merged.merge(partition);
+        }
+        assert_eq!(merged.doc_addresses(), &doc_addresses[..]);
+        assert_eq!(merged.scores(), &scores[..]);
+    }
+}
@@ -0,0 +1,235 @@
+use collector::Collector;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector wrapper that only forwards the documents scoring at least
+/// `threshold` to the underlying collector.
+///
+/// The threshold is also exposed through `Collector::score_threshold`,
+/// so that the segments in which no document can reach it are not scored
+/// at all, if the query is able to bound its scores.
+///
+/// Filtering requires scores: this collector always requires scoring,
+/// even if the underlying collector does not. For instance, wrapping
+/// a `CountCollector` counts the documents scoring above the threshold,
+/// at the cost of scoring them.
+pub struct ScoreFilterCollector<C: Collector> {
+    threshold: Score,
+    inner: C,
+}
+
+impl<C: Collector> ScoreFilterCollector<C> {
+    /// Wraps `inner`, dropping the documents scoring below `threshold`.
+    pub fn new(threshold: Score, inner: C) -> ScoreFilterCollector<C> {
+        ScoreFilterCollector { threshold, inner }
+    }
+
+    /// Returns a reference to the underlying collector.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the underlying collector.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Collector> Collector for ScoreFilterCollector<C> {
+    fn set_segment(
+        &mut self,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<()> {
+        self.inner.set_segment(segment_local_id, segment)
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if score >= self.threshold {
+            self.inner.collect(doc, score);
+        }
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn score_threshold(&self) -> Option<Score> {
+        match self.inner.score_threshold() {
+            Some(inner_threshold) if inner_threshold > self.threshold => Some(inner_threshold),
+            _ => Some(self.threshold),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::ScoreFilterCollector;
+    use collector::{CountCollector, TopScoreCollector};
+    use core::SegmentReader;
+    use query::{Query, QueryParser, Scorer, TermQuery, Weight};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use std::fmt;
+    use Index;
+    use Result;
+    use Score;
+    use Searcher;
+
+    /// Query claiming that the scores of its documents are
+    /// bounded by `max_score`.
+    struct BoundedQuery {
+        query: Box<Query>,
+        max_score: Score,
+    }
+
+    impl Clone for BoundedQuery {
+        fn clone(&self) -> BoundedQuery {
+            BoundedQuery {
+                query: self.query.box_clone(),
+                max_score: self.max_score,
+            }
+        }
+    }
+
+    impl fmt::Debug for BoundedQuery {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "Bounded({:?}, {})", self.query, self.max_score)
+        }
+    }
+
+    impl Query for BoundedQuery {
+        fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+            Ok(Box::new(BoundedWeight {
+                weight: self.query.weight(searcher, scoring_enabled)?,
+                max_score: self.max_score,
+            }))
+        }
+    }
+
+    struct BoundedWeight {
+        weight: Box<Weight>,
+        max_score: Score,
+    }
+
+    impl Weight for BoundedWeight {
+        fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+            self.weight.scorer(reader)
+        }
+
+        fn max_score(&self, _reader: &SegmentReader) -> Option<Score> {
+            Some(self.max_score)
+        }
+    }
+
+    #[test]
+    fn test_score_filter_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b c d e f"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a a"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.add_document(doc!(text_field => "c")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = QueryParser::for_index(&index, vec![text_field])
+            .parse_query("a b")
+            .unwrap();
+        let mut top_collector = TopScoreCollector::with_limit(10);
+        searcher.search(&*query, &mut top_collector).unwrap();
+        let top_docs = top_collector.top_docs();
+        assert_eq!(top_docs.len(), 5);
+        let threshold = top_docs[2].0;
+        let expected_top_docs: Vec<_> = top_docs
+            .iter()
+            .cloned()
+            .filter(|&(score, _)| score >= threshold)
+            .collect();
+        assert!(expected_top_docs.len() >= 3);
+        assert!(expected_top_docs.len() < 5);
+
+        let mut filter_collector =
+            ScoreFilterCollector::new(threshold, TopScoreCollector::with_limit(10));
+        searcher.search(&*query, &mut filter_collector).unwrap();
+        assert_eq!(filter_collector.into_inner().top_docs(), expected_top_docs);
+
+        // Scoring is enabled for collectors that do not require it.
+        let mut filter_collector = ScoreFilterCollector::new(threshold, CountCollector::default());
+        searcher.search(&*query, &mut filter_collector).unwrap();
+        assert_eq!(filter_collector.inner().count(), expected_top_docs.len());
+    }
+
+    #[test]
+    fn test_score_filter_collector_skips_segments() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+
+        // The term query bounds its scores.
+        let mut top_collector = TopScoreCollector::with_limit(10);
+        searcher.search(&term_query, &mut top_collector).unwrap();
+        let top_docs = top_collector.top_docs();
+        let weight = term_query.weight(&searcher, true).unwrap();
+        let max_score = weight.max_score(searcher.segment_reader(0)).unwrap();
+        assert_eq!(top_docs.len(), 2);
+        assert!(top_docs.iter().all(|&(score, _)| score <= max_score));
+        let mut filter_collector = ScoreFilterCollector::new(max_score, CountCollector::default());
+        searcher.search(&term_query, &mut filter_collector).unwrap();
+        assert_eq!(filter_collector.inner().count(), 0);
+
+        // Segments are skipped based on the bound alone.
+        let threshold = top_docs[1].0;
+        let bounded_query = BoundedQuery {
+            query: Box::new(term_query.clone()),
+            max_score: threshold / 2f32,
+        };
+        let mut filter_collector = ScoreFilterCollector::new(threshold, CountCollector::default());
+        searcher
+            .search(&bounded_query, &mut filter_collector)
+            .unwrap();
+        assert_eq!(filter_collector.inner().count(), 0);
+        let bounded_query = BoundedQuery {
+            query: Box::new(term_query),
+            max_score: top_docs[0].0,
+        };
+        let mut filter_collector = ScoreFilterCollector::new(threshold, CountCollector::default());
+        searcher
+            .search(&bounded_query, &mut filter_collector)
+            .unwrap();
+        assert_eq!(filter_collector.inner().count(), 2);
+    }
+}
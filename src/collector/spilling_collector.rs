@@ -0,0 +1,284 @@
+use collector::Collector;
+use common::BinarySerializable;
+use directory::{Directory, ReadOnlySource, WritePtr};
+use std::ffi::OsString;
+use std::io::{self, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+use TantivyError;
+
+/// Number of bytes used to spill a single document:
+/// its segment ordinal, its doc id and its score.
+const ENTRY_NUM_BYTES: usize = 12;
+
+fn write_entry<W: Write>(
+    writer: &mut W,
+    segment_local_id: SegmentLocalId,
+    doc: DocId,
+    score: Score,
+) -> io::Result<()> {
+    segment_local_id.serialize(writer)?;
+    doc.serialize(writer)?;
+    score.to_bits().serialize(writer)?;
+    Ok(())
+}
+
+/// Collector writing the matching documents and their scores
+/// to a `Directory` instead of buffering them in memory.
+///
+/// This is useful for queries matching a very large number of documents,
+/// for which collecting a `Vec<DocAddress>` would be prohibitive.
+///
+/// The documents of each segment are first written to a temporary file
+/// named after the given path and the segment ordinal.
+/// `.finish()` concatenates these files into a single file at the given path,
+/// removes the temporary files and returns a handle over the result.
+///
+/// Each document is encoded over 12 bytes in little endian:
+/// the segment ordinal (`u32`), the doc id (`u32`) and the bits of the score (`f32`).
+pub struct SpillingCollector {
+    directory: Box<Directory>,
+    path: PathBuf,
+    segment_paths: Vec<PathBuf>,
+    writer: Option<WritePtr>,
+    segment_local_id: SegmentLocalId,
+    error: Option<TantivyError>,
+}
+
+impl SpillingCollector {
+    /// Creates a collector spilling the matching documents to
+    /// the file `path` of the given `directory`.
+    ///
+    /// Neither `path` nor the temporary files (`path` followed by `.` and
+    /// the segment ordinal) should exist in the directory.
+    pub fn new<P: AsRef<Path>>(directory: Box<Directory>, path: P) -> SpillingCollector {
+        SpillingCollector {
+            directory,
+            path: path.as_ref().to_path_buf(),
+            segment_paths: Vec::new(),
+            writer: None,
+            segment_local_id: 0,
+            error: None,
+        }
+    }
+
+    fn segment_path(&self, segment_local_id: SegmentLocalId) -> PathBuf {
+        let mut file_name: OsString = self.path.as_os_str().to_owned();
+        file_name.push(format!(".{}", segment_local_id));
+        PathBuf::from(file_name)
+    }
+
+    fn close_segment_file(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Merges the spilled files into a single file,
+    /// and returns a handle over it.
+    ///
+    /// Returns the first error encountered while spilling
+    /// documents, if any.
+    pub fn finish(mut self) -> Result<SpilledDocs> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.close_segment_file()?;
+        let mut writer = self.directory.open_write(&self.path)?;
+        for segment_path in mem::replace(&mut self.segment_paths, Vec::new()) {
+            {
+                let segment_source = self.directory.open_read(&segment_path)?;
+                writer.write_all(segment_source.as_slice())?;
+            }
+            self.directory.delete(&segment_path)?;
+        }
+        writer.flush()?;
+        let source = self.directory.open_read(&self.path)?;
+        Ok(SpilledDocs { source })
+    }
+}
+
+impl Drop for SpillingCollector {
+    fn drop(&mut self) {
+        // `finish()` was not called, the temporary files are removed.
+        if let Some(mut writer) = self.writer.take() {
+            let _ = writer.flush();
+        }
+        for segment_path in &self.segment_paths {
+            let _ = self.directory.delete(segment_path);
+        }
+    }
+}
+
+impl Collector for SpillingCollector {
+    fn set_segment(&mut self, segment_local_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.close_segment_file()?;
+        let segment_path = self.segment_path(segment_local_id);
+        let writer = self.directory.open_write(&segment_path)?;
+        self.segment_paths.push(segment_path);
+        self.writer = Some(writer);
+        self.segment_local_id = segment_local_id;
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.error.is_some() {
+            return;
+        }
+        let segment_local_id = self.segment_local_id;
+        let result = write_entry(
+            self.writer
+                .as_mut()
+                .expect("collect() was called before set_segment. This should never happen."),
+            segment_local_id,
+            doc,
+            score,
+        );
+        if let Err(io_error) = result {
+            self.error = Some(TantivyError::from(io_error));
+        }
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+/// Documents spilled by a `SpillingCollector`.
+pub struct SpilledDocs {
+    source: ReadOnlySource,
+}
+
+impl SpilledDocs {
+    /// Returns the underlying data.
+    pub fn source(&self) -> &ReadOnlySource {
+        &self.source
+    }
+
+    /// Returns the number of spilled documents.
+    pub fn len(&self) -> usize {
+        self.source.len() / ENTRY_NUM_BYTES
+    }
+
+    /// Returns true iff no document was spilled.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the `ord`-th spilled document, along with its score.
+    ///
+    /// Documents are in the order in which they were collected.
+    ///
+    /// # Panics
+    /// Panics if `ord >= self.len()`.
+    pub fn get(&self, ord: usize) -> (Score, DocAddress) {
+        let start = ord * ENTRY_NUM_BYTES;
+        let mut entry: &[u8] = &self.source.as_slice()[start..start + ENTRY_NUM_BYTES];
+        let segment_local_id =
+            u32::deserialize(&mut entry).expect("Reading from a slice cannot fail");
+        let doc = u32::deserialize(&mut entry).expect("Reading from a slice cannot fail");
+        let score_bits = u32::deserialize(&mut entry).expect("Reading from a slice cannot fail");
+        (
+            f32::from_bits(score_bits),
+            DocAddress(segment_local_id, doc),
+        )
+    }
+
+    /// Returns an iterator over the spilled documents and their scores.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (Score, DocAddress)> + 'a {
+        (0..self.len()).map(move |ord| self.get(ord))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SpillingCollector;
+    use collector::TopScoreCollector;
+    use directory::{Directory, RAMDirectory};
+    use query::TermQuery;
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use std::path::Path;
+    use Index;
+
+    #[test]
+    fn test_spilling_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a c"))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "c")).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+
+        let directory = RAMDirectory::create();
+        let mut spilling_collector =
+            SpillingCollector::new(directory.box_clone(), Path::new("results.spill"));
+        searcher.search(&query, &mut spilling_collector).unwrap();
+        let spilled_docs = spilling_collector.finish().unwrap();
+        assert_eq!(spilled_docs.len(), 3);
+        assert_eq!(spilled_docs.source().len(), 3 * 12);
+
+        let mut top_collector = TopScoreCollector::with_limit(10);
+        searcher.search(&query, &mut top_collector).unwrap();
+        let mut expected = top_collector.top_docs();
+        expected.sort_by_key(|&(_, doc_address)| doc_address);
+        let mut spilled: Vec<_> = spilled_docs.iter().collect();
+        spilled.sort_by_key(|&(_, doc_address)| doc_address);
+        assert_eq!(spilled, expected);
+
+        assert!(directory.exists(Path::new("results.spill")));
+        assert!(!directory.exists(Path::new("results.spill.0")));
+        assert!(!directory.exists(Path::new("results.spill.1")));
+    }
+
+    #[test]
+    fn test_spilling_collector_no_match() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "z"),
+            IndexRecordOption::Basic,
+        );
+        let directory = RAMDirectory::create();
+        let mut spilling_collector =
+            SpillingCollector::new(directory.box_clone(), Path::new("results.spill"));
+        searcher.search(&query, &mut spilling_collector).unwrap();
+        let spilled_docs = spilling_collector.finish().unwrap();
+        assert!(spilled_docs.is_empty());
+        assert_eq!(spilled_docs.iter().count(), 0);
+    }
+}
@@ -0,0 +1,167 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use schema::FieldType;
+use std::cmp;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+enum TimestampReader {
+    Signed(FastFieldReader<i64>),
+    Unsigned(FastFieldReader<u64>),
+}
+
+impl TimestampReader {
+    fn get(&self, doc: DocId) -> i64 {
+        match *self {
+            TimestampReader::Signed(ref ff_reader) => ff_reader.get(doc),
+            TimestampReader::Unsigned(ref ff_reader) => {
+                cmp::min(ff_reader.get(doc), i64::max_value() as u64) as i64
+            }
+        }
+    }
+}
+
+/// Collector computing the earliest and latest value of a timestamp
+/// fast field over the matching documents.
+///
+/// The field can either be an `i64` or a `u64` fast field.
+/// Timestamps are returned as `i64`, so that pre-epoch
+/// timestamps of signed fields are handled correctly.
+/// `u64` values that do not fit in an `i64` are saturated
+/// to `i64::max_value()`.
+pub struct TimeRangeCollector {
+    field: Field,
+    range: Option<(i64, i64)>,
+    ff_reader: Option<TimestampReader>,
+}
+
+impl TimeRangeCollector {
+    /// Creates a collector for the given `i64` or `u64` fast field.
+    pub fn for_field(field: Field) -> TimeRangeCollector {
+        TimeRangeCollector {
+            field,
+            range: None,
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the `(earliest, latest)` timestamps over the matching documents,
+    /// or `None` if no document matched.
+    pub fn range(&self) -> Option<(i64, i64)> {
+        self.range
+    }
+}
+
+impl Collector for TimeRangeCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        let ff_reader = match *reader.schema().get_field_entry(self.field).field_type() {
+            FieldType::I64(_) => TimestampReader::Signed(reader.fast_field_reader(self.field)?),
+            _ => TimestampReader::Unsigned(reader.fast_field_reader(self.field)?),
+        };
+        self.ff_reader = Some(ff_reader);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let timestamp = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        self.range = Some(match self.range {
+            Some((earliest, latest)) => (cmp::min(earliest, timestamp), cmp::max(latest, timestamp)),
+            None => (timestamp, timestamp),
+        });
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::TimeRangeCollector;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, STRING};
+    use Index;
+
+    #[test]
+    fn test_time_range_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let timestamp_field = schema_builder.add_i64_field("timestamp", FAST);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 1_500_000_000i64, tag_field => "a"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => -86_400i64, tag_field => "b"))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 1_600_000_000i64, tag_field => "b"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => -1i64, tag_field => "a"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        {
+            let mut collector = TimeRangeCollector::for_field(timestamp_field);
+            searcher.search(&AllQuery, &mut collector).unwrap();
+            assert_eq!(collector.range(), Some((-86_400i64, 1_600_000_000i64)));
+        }
+        {
+            let mut collector = TimeRangeCollector::for_field(timestamp_field);
+            let query = TermQuery::new(
+                Term::from_field_text(tag_field, "a"),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &mut collector).unwrap();
+            assert_eq!(collector.range(), Some((-1i64, 1_500_000_000i64)));
+        }
+        {
+            let mut collector = TimeRangeCollector::for_field(timestamp_field);
+            let query = TermQuery::new(
+                Term::from_field_text(tag_field, "c"),
+                IndexRecordOption::Basic,
+            );
+            searcher.search(&query, &mut collector).unwrap();
+            assert_eq!(collector.range(), None);
+        }
+    }
+
+    #[test]
+    fn test_time_range_collector_unsigned() {
+        let mut schema_builder = SchemaBuilder::default();
+        let timestamp_field = schema_builder.add_u64_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 1_500_000_000u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 1_000u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut collector = TimeRangeCollector::for_field(timestamp_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        assert_eq!(collector.range(), Some((1_000i64, 1_500_000_000i64)));
+    }
+}
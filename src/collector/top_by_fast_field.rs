@@ -0,0 +1,174 @@
+use collector::top_collector::TopCollector;
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector keeping the `k` matching documents with the highest value
+/// for a `u64` fast field, e.g. the most viewed items matching a query.
+///
+/// The query still decides which documents match, but their score is
+/// irrelevant: the collector does not require scoring.
+///
+/// Unlike the `TopFieldCollector`, the collectors of the different
+/// partitions of a search can be merged.
+///
+/// ```rust
+/// use tantivy::collector::TopByFastField;
+/// # use tantivy::schema::{SchemaBuilder, FAST};
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let views_field = schema_builder.add_u64_field("views", FAST);
+///
+/// // The 10 most viewed documents matching the query.
+/// let collector = TopByFastField::new(views_field, 10);
+/// ```
+pub struct TopByFastField {
+    field: Field,
+    collector: TopCollector<u64>,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl TopByFastField {
+    /// Creates a collector keeping the `k` documents with the highest
+    /// value of `field`.
+    ///
+    /// # Panics
+    /// The method panics if `k` is 0.
+    pub fn new(field: Field, k: usize) -> TopByFastField {
+        TopByFastField {
+            field,
+            collector: TopCollector::with_limit(k),
+            ff_reader: None,
+        }
+    }
+
+    /// Adds the documents kept by `other` to this collector,
+    /// keeping the `k` best ones.
+    ///
+    /// This makes it possible to combine the results of several searches,
+    /// for instance the different partitions of a `Searcher::search_partition`.
+    pub fn merge(&mut self, other: TopByFastField) {
+        self.collector.merge(other.collector);
+    }
+
+    /// Returns the (at most) `k` best documents along with their
+    /// value of the fast field, sorted by decreasing value.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn top_docs(&self) -> Vec<(u64, DocAddress)> {
+        self.collector.top_docs()
+    }
+
+    /// Returns the best documents, as in `.top_docs()`.
+    pub fn harvest(self) -> Vec<(u64, DocAddress)> {
+        self.top_docs()
+    }
+}
+
+impl Collector for TopByFastField {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.collector.set_segment_id(segment_id);
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let value = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        self.collector.collect(doc, value);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::TopByFastField;
+    use collector::{Collector, TopScoreCollector};
+    use query::QueryParser;
+    use schema::{SchemaBuilder, FAST, TEXT};
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_top_by_fast_field() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let views_field = schema_builder.add_u64_field("views", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // The more a document repeats "rust", the fewer views it has.
+            for &(text, views) in &[
+                ("rust rust rust rust", 1u64),
+                ("rust rust rust", 10),
+                ("python", 1_000),
+                ("rust rust", 100),
+            ] {
+                index_writer
+                    .add_document(doc!(text_field => text, views_field => views))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            for &(text, views) in &[("rust", 50u64), ("python", 2_000), ("rust rust", 5)] {
+                index_writer
+                    .add_document(doc!(text_field => text, views_field => views))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("rust").unwrap();
+        let views = |doc_address: DocAddress| {
+            searcher
+                .segment_reader(doc_address.segment_ord())
+                .fast_field_reader::<u64>(views_field)
+                .unwrap()
+                .get(doc_address.doc())
+        };
+
+        let mut collector = TopByFastField::new(views_field, 3);
+        assert!(!collector.requires_scoring());
+        searcher.search(&*query, &mut collector).unwrap();
+        let top_docs = collector.top_docs();
+        let top_views: Vec<u64> = top_docs.iter().map(|&(value, _)| value).collect();
+        // The documents that do not match are filtered out.
+        assert_eq!(top_views, vec![100, 50, 10]);
+        for &(value, doc_address) in &top_docs {
+            assert_eq!(views(doc_address), value);
+        }
+
+        // The ranking does not depend on the score.
+        let mut top_score_collector = TopScoreCollector::with_limit(3);
+        searcher.search(&*query, &mut top_score_collector).unwrap();
+        let top_score_views: Vec<u64> =
+            top_score_collector.docs().into_iter().map(&views).collect();
+        assert_ne!(top_score_views, top_views);
+
+        // Merging the partitions, in any order, gives the same documents.
+        let mut merged = TopByFastField::new(views_field, 3);
+        for partition_ord in (0..2).rev() {
+            let mut partition_collector = TopByFastField::new(views_field, 3);
+            searcher
+                .search_partition(&*query, &mut partition_collector, (partition_ord, 2))
+                .unwrap();
+            merged.merge(partition_collector);
+        }
+        assert_eq!(merged.harvest(), top_docs);
+    }
+}
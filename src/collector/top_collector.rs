@@ -111,6 +111,19 @@ impl<T: PartialOrd + Clone> TopCollector<T> {
     /// It collects documents until it has reached the max capacity. Once it reaches capacity, it
     /// will compare the lowest scoring item with the given one and keep whichever is greater.
     pub fn collect(&mut self, doc: DocId, feature: T) {
+        let doc_address = DocAddress(self.segment_id, doc);
+        self.push(feature, doc_address);
+    }
+
+    /// Adds the documents kept by `other` to this collector,
+    /// keeping the K best ones.
+    pub fn merge(&mut self, other: TopCollector<T>) {
+        for comparable_doc in other.heap {
+            self.push(comparable_doc.feature, comparable_doc.doc_address);
+        }
+    }
+
+    fn push(&mut self, feature: T, doc_address: DocAddress) {
         if self.at_capacity() {
             // It's ok to unwrap as long as a limit of 0 is forbidden.
             let limit_doc: ComparableDoc<T> = self
@@ -124,12 +137,12 @@ impl<T: PartialOrd + Clone> TopCollector<T> {
                     .peek_mut()
                     .expect("Top collector with size 0 is forbidden");
                 mut_head.feature = feature;
-                mut_head.doc_address = DocAddress(self.segment_id, doc);
+                mut_head.doc_address = doc_address;
             }
         } else {
             let wrapped_doc = ComparableDoc {
                 feature,
-                doc_address: DocAddress(self.segment_id, doc),
+                doc_address,
             };
             self.heap.push(wrapped_doc);
         }
@@ -184,6 +197,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_top_collector_merge() {
+        let mut top_collector = TopCollector::with_limit(3);
+        top_collector.collect(1, 0.8);
+        top_collector.collect(3, 0.2);
+        let mut other_top_collector = TopCollector::with_limit(3);
+        other_top_collector.set_segment_id(1);
+        other_top_collector.collect(1, 0.9);
+        other_top_collector.collect(2, 0.1);
+        other_top_collector.collect(4, 0.5);
+        top_collector.merge(other_top_collector);
+        assert_eq!(
+            top_collector.top_docs(),
+            vec![
+                (0.9, DocAddress(1, 1)),
+                (0.8, DocAddress(0, 1)),
+                (0.5, DocAddress(1, 4)),
+            ]
+        );
+    }
+
     #[test]
     #[should_panic]
     fn test_top_0() {
@@ -0,0 +1,120 @@
+use super::Collector;
+use collector::top_collector::TopCollector;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector computing both the K documents with the best score,
+/// and the total number of matching documents, in a single pass.
+///
+/// This is equivalent to running a `TopScoreCollector` and
+/// a `CountCollector` together, and typically serves to display
+/// the first page of results along with the total number of hits.
+pub struct TopDocsWithCount {
+    collector: TopCollector<Score>,
+    count: usize,
+}
+
+impl TopDocsWithCount {
+    /// Creates a collector keeping track of the `limit` best documents.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn with_limit(limit: usize) -> TopDocsWithCount {
+        TopDocsWithCount {
+            collector: TopCollector::with_limit(limit),
+            count: 0,
+        }
+    }
+
+    /// Returns K best ScoredDocuments sorted in decreasing order.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn top_docs(&self) -> Vec<(Score, DocAddress)> {
+        self.collector.top_docs()
+    }
+
+    /// Returns the total number of documents that matched the query.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns both the K best ScoredDocuments sorted in decreasing order,
+    /// and the total number of documents that matched the query.
+    pub fn top_docs_and_count(&self) -> (Vec<(Score, DocAddress)>, usize) {
+        (self.top_docs(), self.count)
+    }
+}
+
+impl Collector for TopDocsWithCount {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+        self.collector.set_segment_id(segment_id);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.count += 1;
+        self.collector.collect(doc, score);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::TopDocsWithCount;
+    use collector::{CountCollector, TopScoreCollector};
+    use query::QueryParser;
+    use schema::{SchemaBuilder, TEXT};
+    use Index;
+
+    #[test]
+    fn test_top_docs_with_count() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b c"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c c"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "d")).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![text_field]);
+        let query = query_parser.parse_query("a c").unwrap();
+
+        let mut top_docs_with_count = TopDocsWithCount::with_limit(2);
+        searcher.search(&*query, &mut top_docs_with_count).unwrap();
+
+        let mut count_collector = CountCollector::default();
+        searcher.search(&*query, &mut count_collector).unwrap();
+        let mut top_collector = TopScoreCollector::with_limit(2);
+        searcher.search(&*query, &mut top_collector).unwrap();
+
+        let (top_docs, count) = top_docs_with_count.top_docs_and_count();
+        assert_eq!(count, 4);
+        assert_eq!(count, count_collector.count());
+        assert_eq!(top_docs.len(), 2);
+        assert_eq!(top_docs, top_collector.top_docs());
+    }
+}
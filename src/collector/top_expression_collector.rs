@@ -0,0 +1,315 @@
+use collector::expression::{Expression, SegmentExpression};
+use collector::top_collector::TopCollector;
+use collector::Collector;
+use std::cmp::Ordering;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Value of the expression for a document.
+///
+/// `NaN` is ordered below any other value,
+/// so that these documents are ranked last.
+#[derive(Clone, Copy, Debug)]
+struct ExpressionValue(f64);
+
+impl PartialEq for ExpressionValue {
+    fn eq(&self, other: &ExpressionValue) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for ExpressionValue {
+    fn partial_cmp(&self, other: &ExpressionValue) -> Option<Ordering> {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => self.0.partial_cmp(&other.0),
+        }
+    }
+}
+
+/// The Top Expression Collector keeps track of the K documents
+/// with the highest value of an `Expression` over their fast fields,
+/// e.g. `0.7 * ctr + 0.3 * decay(timestamp, 1540000000, 86400)`.
+///
+/// The expression is resolved against each segment in `set_segment`:
+/// the search fails if the expression refers to an unknown field, or to a
+/// field that is not a `u64` or `i64` fast field.
+///
+/// Documents for which the expression evaluates to `NaN` are ranked last.
+/// Among documents with the same value, the first collected ones are kept,
+/// and they are sorted by `DocAddress`.
+///
+/// The score of the documents is irrelevant: the collector does not
+/// require scoring.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate tantivy;
+/// use tantivy::collector::{Expression, TopExpressionCollector};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{SchemaBuilder, FAST};
+/// use tantivy::Index;
+///
+/// # fn main() { example().unwrap(); }
+/// fn example() -> tantivy::Result<()> {
+///     let mut schema_builder = SchemaBuilder::default();
+///     let clicks = schema_builder.add_u64_field("clicks", FAST);
+///     let views = schema_builder.add_u64_field("views", FAST);
+///     let index = Index::create_in_ram(schema_builder.build());
+///     {
+///         let mut index_writer = index.writer_with_num_threads(1, 40_000_000)?;
+///         index_writer.add_document(doc!(clicks => 10u64, views => 100u64))?;
+///         index_writer.add_document(doc!(clicks => 30u64, views => 200u64))?;
+///         index_writer.add_document(doc!(clicks => 2u64, views => 10u64))?;
+///         index_writer.commit()?;
+///     }
+///     index.load_searchers()?;
+///     let searcher = index.searcher();
+///
+///     // Ranks the documents by click-through rate.
+///     let expression = Expression::parse("clicks / views")?;
+///     let mut collector = TopExpressionCollector::with_limit(expression, 2);
+///     searcher.search(&AllQuery, &mut collector)?;
+///     let ctrs: Vec<f64> = collector.top_docs().into_iter().map(|(ctr, _)| ctr).collect();
+///     assert_eq!(ctrs, vec![0.2, 0.15]);
+///     Ok(())
+/// }
+/// ```
+pub struct TopExpressionCollector {
+    expression: Expression,
+    collector: TopCollector<ExpressionValue>,
+    segment_expression: Option<SegmentExpression>,
+}
+
+impl TopExpressionCollector {
+    /// Creates a top expression collector, with a number of documents equal to "limit".
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn with_limit(expression: Expression, limit: usize) -> TopExpressionCollector {
+        TopExpressionCollector {
+            expression,
+            collector: TopCollector::with_limit(limit),
+            segment_expression: None,
+        }
+    }
+
+    /// Returns K best documents sorted in decreasing order of
+    /// the value of the expression.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn docs(&self) -> Vec<DocAddress> {
+        self.top_docs()
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect()
+    }
+
+    /// Returns K best documents along with the value of the expression,
+    /// sorted in decreasing order. `NaN` values come last.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn top_docs(&self) -> Vec<(f64, DocAddress)> {
+        let mut top_docs = self.collector.top_docs();
+        // The top collector leaves the order of the ties unspecified.
+        top_docs.sort_by(|&(ref left_val, left_doc), &(ref right_val, right_doc)| {
+            right_val
+                .partial_cmp(left_val)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left_doc.cmp(&right_doc))
+        });
+        top_docs
+            .into_iter()
+            .map(|(ExpressionValue(val), doc_address)| (val, doc_address))
+            .collect()
+    }
+
+    /// Return true iff at least K documents have gone through
+    /// the collector.
+    #[inline]
+    pub fn at_capacity(&self) -> bool {
+        self.collector.at_capacity()
+    }
+}
+
+impl Collector for TopExpressionCollector {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.collector.set_segment_id(segment_id);
+        self.segment_expression = Some(self.expression.for_segment(reader)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self
+            .segment_expression
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .eval(doc);
+        self.collector.collect(doc, ExpressionValue(val));
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::TopExpressionCollector;
+    use collector::expression::{BinaryOp, Expression, ExpressionFunction};
+    use query::AllQuery;
+    use schema::{SchemaBuilder, Value, FAST, INT_INDEXED, STORED, TEXT};
+    use DocAddress;
+    use Index;
+    use TantivyError;
+
+    #[test]
+    fn test_top_expression_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let name_field = schema_builder.add_text_field("name", TEXT | STORED);
+        let clicks_field = schema_builder.add_u64_field("clicks", FAST);
+        let views_field = schema_builder.add_u64_field("views", FAST);
+        let age_field = schema_builder.add_i64_field("age", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for &(name, clicks, views, age) in &[
+                ("popular", 50u64, 100u64, 10i64),
+                ("unseen", 0, 0, 1),
+                ("fresh", 2, 10, 0),
+                ("old", 60, 100, 100),
+            ] {
+                index_writer
+                    .add_document(doc!(
+                        name_field => name,
+                        clicks_field => clicks,
+                        views_field => views,
+                        age_field => age
+                    ))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            // Ties with "popular", in another segment.
+            index_writer
+                .add_document(doc!(
+                    name_field => "popular twin",
+                    clicks_field => 50u64,
+                    views_field => 100u64,
+                    age_field => 10i64
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let name = |doc_address: DocAddress| {
+            let doc = searcher.doc(doc_address).unwrap();
+            match doc.get_first(name_field) {
+                Some(&Value::Str(ref name)) => name.clone(),
+                _ => panic!("Every document has a name"),
+            }
+        };
+        let search = |expression: &str, limit: usize| {
+            let expression = Expression::parse(expression).unwrap();
+            let mut collector = TopExpressionCollector::with_limit(expression, limit);
+            searcher.search(&AllQuery, &mut collector).unwrap();
+            collector
+                .top_docs()
+                .into_iter()
+                .map(|(val, doc_address)| (val, name(doc_address)))
+                .collect::<Vec<(f64, String)>>()
+        };
+
+        // The click-through rate of "unseen" is 0 / 0, and is ranked last.
+        let ctrs = search("clicks / views - age / 100", 5);
+        let names: Vec<&str> = ctrs.iter().map(|&(_, ref name)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["popular", "popular twin", "fresh", "old", "unseen"]
+        );
+        assert!((ctrs[0].0 - 0.4).abs() < 1e-9);
+        assert!((ctrs[3].0 + 0.4).abs() < 1e-9);
+        assert!(ctrs[4].0.is_nan());
+        assert!(!ctrs[..4].iter().any(|&(val, _)| val.is_nan()));
+
+        // Documents with a NaN value are only kept if there are not enough others.
+        let ctrs = search("clamp(clicks / views, 0, 0.5)", 3);
+        let names: Vec<&str> = ctrs.iter().map(|&(_, ref name)| name.as_str()).collect();
+        assert_eq!(names, vec!["popular", "old", "popular twin"]);
+        assert!(ctrs.iter().all(|&(val, _)| val == 0.5));
+
+        // Ties are resolved in the order the documents are collected,
+        // so that searching again gives the same results.
+        assert_eq!(search("clicks", 1), vec![(60f64, "old".to_string())]);
+        let tied = search("clicks / views - age / 100", 1);
+        assert_eq!(tied[0].1, "popular");
+        for _ in 0..5 {
+            assert_eq!(search("clicks / views - age / 100", 1), tied);
+        }
+
+        // The expression can be built without parsing it.
+        let mut collector = TopExpressionCollector::with_limit(
+            Expression::Binary(
+                BinaryOp::Mul,
+                Box::new(Expression::Constant(-1f64)),
+                Box::new(Expression::FastField("age".to_string())),
+            ),
+            1,
+        );
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        assert_eq!(name(collector.docs()[0]), "fresh");
+    }
+
+    #[test]
+    fn test_top_expression_collector_errors() {
+        let mut schema_builder = SchemaBuilder::default();
+        let name_field = schema_builder.add_text_field("name", TEXT);
+        let rank_field = schema_builder.add_i64_field("rank", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(name_field => "a", rank_field => 1i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let search = |expression: Expression| {
+            let mut collector = TopExpressionCollector::with_limit(expression, 1);
+            searcher.search(&AllQuery, &mut collector)
+        };
+
+        match search(Expression::parse("2 * unknown").unwrap()) {
+            Err(TantivyError::SchemaError(msg)) => assert!(msg.contains("\"unknown\"")),
+            res => panic!("Expected a schema error, got {:?}", res),
+        }
+        // A text field, and an integer field that is not a fast field.
+        match search(Expression::parse("name + 1").unwrap()) {
+            Err(TantivyError::FastFieldError(_)) => {}
+            res => panic!("Expected a fast field error, got {:?}", res),
+        }
+        match search(Expression::parse("log(rank)").unwrap()) {
+            Err(TantivyError::FastFieldError(_)) => {}
+            res => panic!("Expected a fast field error, got {:?}", res),
+        }
+        // The arity of the functions is checked for expressions built directly.
+        let call = Expression::Call(ExpressionFunction::Log, vec![]);
+        match search(call) {
+            Err(TantivyError::InvalidArgument(msg)) => {
+                assert!(msg.contains("log() expects 1 argument(s), got 0"))
+            }
+            res => panic!("Expected an invalid argument error, got {:?}", res),
+        }
+    }
+}
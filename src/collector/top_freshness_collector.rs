@@ -0,0 +1,223 @@
+use super::Collector;
+use collector::top_collector::TopCollector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use DocAddress;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// The Top Freshness Collector keeps track of the K documents
+/// with the best score, boosted by their recency.
+///
+/// The recency of a document is read from an `i64` timestamp fast field.
+/// The score of each document is multiplied by `decay(now - timestamp)`,
+/// so that among documents with similar relevance, newer documents rank first.
+///
+/// The `decay` function is typically decreasing, and returns `1.0` for
+/// an age of `0`. For instance, an exponential decay with a half life
+/// of one day (for timestamps expressed in seconds) can be expressed as
+///
+/// ```rust
+/// # let decay =
+/// |age: i64| 0.5f32.powf(age as f32 / 86_400f32)
+/// # ;
+/// # assert_eq!(decay(0), 1f32);
+/// # assert_eq!(decay(86_400), 0.5f32);
+/// ```
+///
+/// The implementation is based on a `BinaryHeap`.
+/// The theorical complexity for collecting the top `K` out of `n` documents
+/// is `O(n log K)`.
+pub struct TopFreshnessCollector<D: Fn(i64) -> Score> {
+    timestamp_field: Field,
+    now: i64,
+    decay: D,
+    collector: TopCollector<Score>,
+    timestamp_reader: Option<FastFieldReader<i64>>,
+}
+
+impl<D: Fn(i64) -> Score> TopFreshnessCollector<D> {
+    /// Creates a top freshness collector, with a number of documents equal to "limit".
+    ///
+    /// - `timestamp_field` must be an `i64` fast field.
+    /// - `now` is the reference time used to compute the age of documents.
+    /// - `decay` maps the age of a document (`now - timestamp`) to a multiplier
+    /// applied to its score. The age saturates at the bounds of `i64`
+    /// instead of overflowing.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn with_limit(
+        timestamp_field: Field,
+        now: i64,
+        decay: D,
+        limit: usize,
+    ) -> TopFreshnessCollector<D> {
+        TopFreshnessCollector {
+            timestamp_field,
+            now,
+            decay,
+            collector: TopCollector::with_limit(limit),
+            timestamp_reader: None,
+        }
+    }
+
+    /// Returns K best documents sorted in decreasing order of boosted score.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn docs(&self) -> Vec<DocAddress> {
+        self.collector.docs()
+    }
+
+    /// Returns K best documents along with their boosted score,
+    /// sorted in decreasing order.
+    ///
+    /// Calling this method triggers the sort.
+    /// The result of the sort is not cached.
+    pub fn top_docs(&self) -> Vec<(Score, DocAddress)> {
+        self.collector.top_docs()
+    }
+
+    /// Return true iff at least K documents have gone through
+    /// the collector.
+    #[inline]
+    pub fn at_capacity(&self) -> bool {
+        self.collector.at_capacity()
+    }
+}
+
+impl<D: Fn(i64) -> Score> Collector for TopFreshnessCollector<D> {
+    fn set_segment(&mut self, segment_id: SegmentLocalId, segment: &SegmentReader) -> Result<()> {
+        self.collector.set_segment_id(segment_id);
+        self.timestamp_reader = Some(segment.fast_field_reader(self.timestamp_field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        let timestamp = self
+            .timestamp_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        let boosted_score = score * (self.decay)(self.now.saturating_sub(timestamp));
+        self.collector.collect(doc, boosted_score);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopFreshnessCollector;
+    use collector::TopScoreCollector;
+    use query::TermQuery;
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, TEXT};
+    use DocId;
+    use Index;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_top_freshness_collector() {
+        let mut schema_builder = SchemaBuilder::new();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let now = 1_000 * DAY;
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "beer beer beer",
+                    timestamp => now - 300 * DAY,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "beer and pretzels",
+                    timestamp => now - DAY,
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title => "wine",
+                    timestamp => now,
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(title, "beer"),
+            IndexRecordOption::WithFreqs,
+        );
+
+        let mut top_score_collector = TopScoreCollector::with_limit(2);
+        searcher.search(&query, &mut top_score_collector).unwrap();
+        let docs: Vec<DocId> = top_score_collector
+            .docs()
+            .into_iter()
+            .map(|doc_address| doc_address.doc())
+            .collect();
+        assert_eq!(docs, vec![0, 1]);
+
+        let decay = |age: i64| 0.5f32.powf(age as f32 / (30 * DAY) as f32);
+        let mut top_freshness_collector =
+            TopFreshnessCollector::with_limit(timestamp, now, decay, 2);
+        searcher.search(&query, &mut top_freshness_collector).unwrap();
+        let docs: Vec<DocId> = top_freshness_collector
+            .docs()
+            .into_iter()
+            .map(|doc_address| doc_address.doc())
+            .collect();
+        assert_eq!(docs, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_top_freshness_collector_extreme_timestamps() {
+        let mut schema_builder = SchemaBuilder::new();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let timestamp = schema_builder.add_i64_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(title => "beer", timestamp => i64::min_value()))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title => "beer", timestamp => i64::max_value()))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(title, "beer"),
+            IndexRecordOption::WithFreqs,
+        );
+        let decay = |age: i64| if age <= 0 { 1f32 } else { 0.5f32 };
+        let mut top_freshness_collector =
+            TopFreshnessCollector::with_limit(timestamp, i64::max_value(), decay, 2);
+        searcher.search(&query, &mut top_freshness_collector).unwrap();
+        let docs: Vec<DocId> = top_freshness_collector
+            .docs()
+            .into_iter()
+            .map(|doc_address| doc_address.doc())
+            .collect();
+        assert_eq!(docs, vec![1, 0]);
+
+        let mut top_freshness_collector =
+            TopFreshnessCollector::with_limit(timestamp, i64::min_value(), decay, 2);
+        searcher.search(&query, &mut top_freshness_collector).unwrap();
+        assert_eq!(top_freshness_collector.docs().len(), 2);
+    }
+}
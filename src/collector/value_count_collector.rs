@@ -0,0 +1,266 @@
+use collector::Collector;
+use common::BinarySerializable;
+use common::VInt;
+use fastfield::FastFieldReader;
+use schema::Field;
+use std::cmp;
+use std::collections::HashMap;
+use std::io;
+use std::io::{Read, Write};
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+// The number of values is read from the input, and cannot be trusted
+// to size the allocation.
+const MAX_PREALLOCATED_VALUES: u64 = 1_024;
+
+/// Compact representation of a value → count breakdown.
+///
+/// The pairs are sorted by increasing value and values are unique.
+/// `SparseCounts` implements `BinarySerializable`: values are
+/// delta-encoded and both values and counts are written as `VInt`s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparseCounts {
+    value_counts: Vec<(u64, u64)>,
+}
+
+impl SparseCounts {
+    /// Returns the count associated to the given value.
+    ///
+    /// Values that were never collected have a count of 0.
+    pub fn get(&self, value: u64) -> u64 {
+        self.value_counts
+            .binary_search_by_key(&value, |&(val, _)| val)
+            .map(|ord| self.value_counts[ord].1)
+            .unwrap_or(0u64)
+    }
+
+    /// Returns the number of distinct values.
+    pub fn len(&self) -> usize {
+        self.value_counts.len()
+    }
+
+    /// Returns true iff no value was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.value_counts.is_empty()
+    }
+
+    /// Returns the `(value, count)` pairs, sorted by increasing value.
+    pub fn value_counts(&self) -> &[(u64, u64)] {
+        &self.value_counts[..]
+    }
+}
+
+impl From<HashMap<u64, u64>> for SparseCounts {
+    fn from(counts: HashMap<u64, u64>) -> SparseCounts {
+        let mut value_counts: Vec<(u64, u64)> = counts.into_iter().collect();
+        value_counts.sort_by_key(|&(val, _)| val);
+        SparseCounts { value_counts }
+    }
+}
+
+impl BinarySerializable for SparseCounts {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.value_counts.len() as u64).serialize(writer)?;
+        let mut previous_val = 0u64;
+        for &(val, count) in &self.value_counts {
+            VInt(val - previous_val).serialize(writer)?;
+            VInt(count).serialize(writer)?;
+            previous_val = val;
+        }
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<SparseCounts> {
+        let num_values = VInt::deserialize(reader)?.val();
+        // Reading fails as soon as the input holds fewer values than
+        // announced, so that the vector only grows with the data
+        // actually read.
+        let mut value_counts =
+            Vec::with_capacity(cmp::min(num_values, MAX_PREALLOCATED_VALUES) as usize);
+        let mut val = 0u64;
+        for _ in 0..num_values {
+            let delta = VInt::deserialize(reader)?.val();
+            // Only the first value may be encoded with a delta of 0:
+            // the values are strictly increasing.
+            if delta == 0 && !value_counts.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Duplicate value while deserializing SparseCounts.",
+                ));
+            }
+            val = val.checked_add(delta).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Value overflow while deserializing SparseCounts.",
+                )
+            })?;
+            let count = VInt::deserialize(reader)?.val();
+            value_counts.push((val, count));
+        }
+        Ok(SparseCounts { value_counts })
+    }
+}
+
+/// Collector counting the number of matching documents
+/// for each value of a `u64` fast field.
+///
+/// Once the search is done, the result can be harvested
+/// as a `SparseCounts` object.
+pub struct ValueCountCollector {
+    field: Field,
+    counts: HashMap<u64, u64>,
+    ff_reader: Option<FastFieldReader<u64>>,
+}
+
+impl ValueCountCollector {
+    /// Creates a collector counting the values of the given `u64` fast field.
+    pub fn for_field(field: Field) -> ValueCountCollector {
+        ValueCountCollector {
+            field,
+            counts: HashMap::new(),
+            ff_reader: None,
+        }
+    }
+
+    /// Returns the value counts, sorted by value.
+    pub fn harvest(self) -> SparseCounts {
+        SparseCounts::from(self.counts)
+    }
+}
+
+impl Collector for ValueCountCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        self.ff_reader = Some(reader.fast_field_reader(self.field)?);
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let val = self
+            .ff_reader
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.")
+            .get(doc);
+        *self.counts.entry(val).or_insert(0) += 1;
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{SparseCounts, ValueCountCollector};
+    use common::BinarySerializable;
+    use common::VInt;
+    use query::AllQuery;
+    use schema::{SchemaBuilder, FAST};
+    use std::collections::HashMap;
+    use std::io;
+    use Index;
+
+    #[test]
+    fn test_sparse_counts_serialization() {
+        let mut counts = HashMap::new();
+        counts.insert(1_000_000u64, 3u64);
+        counts.insert(2u64, 1u64);
+        counts.insert(17u64, 100_000u64);
+        let sparse_counts = SparseCounts::from(counts);
+        assert_eq!(
+            sparse_counts.value_counts(),
+            &[(2u64, 1u64), (17u64, 100_000u64), (1_000_000u64, 3u64)]
+        );
+        let mut buffer: Vec<u8> = Vec::new();
+        sparse_counts.serialize(&mut buffer).unwrap();
+        let mut cursor = &buffer[..];
+        let deserialized = SparseCounts::deserialize(&mut cursor).unwrap();
+        assert!(cursor.is_empty());
+        assert_eq!(deserialized, sparse_counts);
+        assert_eq!(deserialized.get(17u64), 100_000u64);
+        assert_eq!(deserialized.get(18u64), 0u64);
+    }
+
+    #[test]
+    fn test_sparse_counts_serialization_empty() {
+        let sparse_counts = SparseCounts::default();
+        let mut buffer: Vec<u8> = Vec::new();
+        sparse_counts.serialize(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 1);
+        let deserialized = SparseCounts::deserialize(&mut &buffer[..]).unwrap();
+        assert!(deserialized.is_empty());
+    }
+
+    #[test]
+    fn test_sparse_counts_deserialization_invalid() {
+        // The length announces more values than the input holds.
+        let mut buffer: Vec<u8> = Vec::new();
+        VInt(u64::max_value()).serialize(&mut buffer).unwrap();
+        VInt(3u64).serialize(&mut buffer).unwrap();
+        VInt(1u64).serialize(&mut buffer).unwrap();
+        let err = SparseCounts::deserialize(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // The deltas overflow.
+        let mut buffer: Vec<u8> = Vec::new();
+        VInt(2u64).serialize(&mut buffer).unwrap();
+        for _ in 0..2 {
+            VInt(u64::max_value()).serialize(&mut buffer).unwrap();
+            VInt(1u64).serialize(&mut buffer).unwrap();
+        }
+        let err = SparseCounts::deserialize(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A value is repeated.
+        let mut buffer: Vec<u8> = Vec::new();
+        VInt(2u64).serialize(&mut buffer).unwrap();
+        for _ in 0..2 {
+            VInt(0u64).serialize(&mut buffer).unwrap();
+            VInt(1u64).serialize(&mut buffer).unwrap();
+        }
+        let err = SparseCounts::deserialize(&mut &buffer[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_sparse_counts_serialization_zero_value() {
+        let mut counts = HashMap::new();
+        counts.insert(0u64, 2u64);
+        counts.insert(1u64, 5u64);
+        let sparse_counts = SparseCounts::from(counts);
+        let mut buffer: Vec<u8> = Vec::new();
+        sparse_counts.serialize(&mut buffer).unwrap();
+        let deserialized = SparseCounts::deserialize(&mut &buffer[..]).unwrap();
+        assert_eq!(deserialized, sparse_counts);
+    }
+
+    #[test]
+    fn test_value_count_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let val_field = schema_builder.add_u64_field("val", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for val in &[3u64, 1u64, 3u64, 7u64, 3u64] {
+                index_writer.add_document(doc!(val_field => *val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(val_field => 7u64)).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut collector = ValueCountCollector::for_field(val_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        let sparse_counts = collector.harvest();
+        assert_eq!(
+            sparse_counts.value_counts(),
+            &[(1u64, 1u64), (3u64, 3u64), (7u64, 2u64)]
+        );
+    }
+}
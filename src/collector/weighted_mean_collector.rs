@@ -0,0 +1,159 @@
+use collector::Collector;
+use fastfield::FastFieldReader;
+use schema::Field;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Collector computing the mean of a `u64` fast field over the
+/// matching documents, weighted by another `u64` fast field.
+///
+/// For instance, the average rating of a set of products,
+/// weighted by their number of reviews.
+///
+/// The sums of `value * weight` and of `weight` are accumulated
+/// exactly, as `u128`, and the division only happens in `.mean()`.
+pub struct WeightedMeanCollector {
+    value_field: Field,
+    weight_field: Field,
+    weighted_sum: u128,
+    total_weight: u128,
+    ff_readers: Option<(FastFieldReader<u64>, FastFieldReader<u64>)>,
+}
+
+impl WeightedMeanCollector {
+    /// Creates a collector averaging `value_field`, weighted by `weight_field`.
+    pub fn new(value_field: Field, weight_field: Field) -> WeightedMeanCollector {
+        WeightedMeanCollector {
+            value_field,
+            weight_field,
+            weighted_sum: 0u128,
+            total_weight: 0u128,
+            ff_readers: None,
+        }
+    }
+
+    /// Returns the sum of `value * weight` over the matching documents.
+    pub fn weighted_sum(&self) -> u128 {
+        self.weighted_sum
+    }
+
+    /// Returns the sum of the weights of the matching documents.
+    pub fn total_weight(&self) -> u128 {
+        self.total_weight
+    }
+
+    /// Returns the weighted mean, or `None` if the total weight is 0.
+    pub fn mean(&self) -> Option<f64> {
+        if self.total_weight == 0 {
+            None
+        } else {
+            Some(self.weighted_sum as f64 / self.total_weight as f64)
+        }
+    }
+}
+
+impl Collector for WeightedMeanCollector {
+    fn set_segment(&mut self, _: SegmentLocalId, reader: &SegmentReader) -> Result<()> {
+        let value_reader = reader.fast_field_reader(self.value_field)?;
+        let weight_reader = reader.fast_field_reader(self.weight_field)?;
+        self.ff_readers = Some((value_reader, weight_reader));
+        Ok(())
+    }
+
+    fn collect(&mut self, doc: DocId, _: Score) {
+        let &(ref value_reader, ref weight_reader) = self
+            .ff_readers
+            .as_ref()
+            .expect("collect() was called before set_segment. This should never happen.");
+        let weight = u128::from(weight_reader.get(doc));
+        self.weighted_sum += u128::from(value_reader.get(doc)) * weight;
+        self.total_weight += weight;
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::WeightedMeanCollector;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, TEXT};
+    use Index;
+
+    #[test]
+    fn test_weighted_mean_collector() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let rating_field = schema_builder.add_u64_field("rating", FAST);
+        let num_reviews_field = schema_builder.add_u64_field("num_reviews", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "a",
+                    rating_field => 4u64,
+                    num_reviews_field => 10u64
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "b",
+                    rating_field => 1u64,
+                    num_reviews_field => 0u64
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "a",
+                    rating_field => 2u64,
+                    num_reviews_field => 30u64
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    text_field => "c",
+                    rating_field => u64::max_value(),
+                    num_reviews_field => 2u64
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let mut collector = WeightedMeanCollector::new(rating_field, num_reviews_field);
+        searcher.search(&AllQuery, &mut collector).unwrap();
+        // does not overflow
+        let expected_sum = 4u128 * 10 + 2 * 30 + u128::from(u64::max_value()) * 2;
+        assert_eq!(collector.weighted_sum(), expected_sum);
+        assert_eq!(collector.total_weight(), 42);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = WeightedMeanCollector::new(rating_field, num_reviews_field);
+        searcher.search(&query, &mut collector).unwrap();
+        assert_eq!(collector.mean(), Some(2.5f64));
+
+        // zero total weight
+        let query = TermQuery::new(
+            Term::from_field_text(text_field, "b"),
+            IndexRecordOption::Basic,
+        );
+        let mut collector = WeightedMeanCollector::new(rating_field, num_reviews_field);
+        searcher.search(&query, &mut collector).unwrap();
+        assert_eq!(collector.total_weight(), 0);
+        assert_eq!(collector.mean(), None);
+    }
+}
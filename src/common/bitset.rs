@@ -119,6 +119,10 @@ impl TinySet {
     }
 }
 
+/// Set of `u32` within `[0, max_value[`, stored as a bitmap.
+///
+/// It can be turned into a `DocSet` through
+/// [`BitSetDocSet`](../query/struct.BitSetDocSet.html).
 #[derive(Clone)]
 pub struct BitSet {
     tinysets: Box<[TinySet]>,
@@ -187,6 +191,7 @@ impl BitSet {
             .map(|delta_bucket| bucket + delta_bucket as u32)
     }
 
+    /// Returns the bound passed to `.with_max_value(...)`.
     pub fn max_value(&self) -> u32 {
         self.max_value
     }
@@ -6,18 +6,37 @@ use directory::WritePtr;
 use schema::Field;
 use space_usage::PerFieldSpaceUsage;
 use space_usage::FieldUsage;
-use std::collections::HashMap;
+use std::collections::btree_map;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 use std::io::{self, Read};
 
+/// Version of the footer written by `CompositeWrite`.
+///
+/// Files written before the footer was versioned are still readable:
+/// their footer is read as the version `0`.
+pub const COMPOSITE_FILE_VERSION: u8 = 1;
+
+// The footer length of a versioned footer has this bit set,
+// which a legacy footer length, being far smaller, never has.
+const VERSIONED_FOOTER_FLAG: u32 = 1u32 << 31;
+
+/// Address of a sub-file within a `CompositeFile`.
+///
+/// Most fields are associated with a single sub-file of index `0`,
+/// but some structures use several, e.g. the multivalued fast fields
+/// store their offsets in the sub-file `0` and their values in `1`.
 #[derive(Eq, PartialEq, Hash, Copy, Ord, PartialOrd, Clone, Debug)]
 pub struct FileAddr {
-    field: Field,
-    idx: usize,
+    /// Field the sub-file belongs to.
+    pub field: Field,
+    /// Index of the sub-file among the ones of the field.
+    pub idx: usize,
 }
 
 impl FileAddr {
-    fn new(field: Field, idx: usize) -> FileAddr {
+    /// Creates the address of the sub-file `idx` of `field`.
+    pub fn new(field: Field, idx: usize) -> FileAddr {
         FileAddr { field, idx }
     }
 }
@@ -70,6 +89,10 @@ impl<W: Write> CompositeWrite<W> {
     ///
     /// An index of the different field offsets
     /// will be written as a footer.
+    ///
+    /// The footer lists the `(field, idx, offset)` triples of the sub-files,
+    /// by increasing offset, followed by the `COMPOSITE_FILE_VERSION` byte
+    /// and by the length of the footer.
     pub fn close(mut self) -> io::Result<()> {
         let footer_offset = self.write.written_bytes();
         VInt(self.offsets.len() as u64).serialize(&mut self.write)?;
@@ -82,15 +105,14 @@ impl<W: Write> CompositeWrite<W> {
 
         offset_fields.sort();
 
-        let mut prev_offset = 0;
         for (offset, file_addr) in offset_fields {
-            VInt((offset - prev_offset) as u64).serialize(&mut self.write)?;
             file_addr.serialize(&mut self.write)?;
-            prev_offset = offset;
+            VInt(offset as u64).serialize(&mut self.write)?;
         }
+        COMPOSITE_FILE_VERSION.serialize(&mut self.write)?;
 
         let footer_len = (self.write.written_bytes() - footer_offset) as u32;
-        footer_len.serialize(&mut self.write)?;
+        (footer_len | VERSIONED_FOOTER_FLAG).serialize(&mut self.write)?;
         self.write.flush()?;
         Ok(())
     }
@@ -102,46 +124,93 @@ impl<W: Write> CompositeWrite<W> {
 /// The file needs to be written field by field.
 /// A footer describes the start and stop offsets
 /// for each field.
+///
+/// The sub-files are addressed by `FileAddr`. The byte range of a sub-file
+/// is given by `.byte_range(...)`, and its content by `.open_read_addr(...)`.
 #[derive(Clone)]
 pub struct CompositeFile {
     data: ReadOnlySource,
-    offsets_index: HashMap<FileAddr, (usize, usize)>,
+    offsets_index: BTreeMap<FileAddr, (usize, usize)>,
+}
+
+fn corrupted_footer(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Corrupted composite file footer: {}", msg),
+    )
+}
+
+// Reads the `(offset, file_addr)` pairs of a footer, without
+// its version byte.
+fn read_footer_entries(
+    mut footer_buffer: &[u8],
+    version: u8,
+) -> io::Result<Vec<(usize, FileAddr)>> {
+    let num_fields = VInt::deserialize(&mut footer_buffer)?.0 as usize;
+    let mut entries = Vec::with_capacity(num_fields);
+    let mut offset = 0;
+    for _ in 0..num_fields {
+        let file_addr;
+        if version == 0 {
+            // Legacy footers store the offsets as deltas, before the address.
+            offset += VInt::deserialize(&mut footer_buffer)?.0 as usize;
+            file_addr = FileAddr::deserialize(&mut footer_buffer)?;
+        } else {
+            file_addr = FileAddr::deserialize(&mut footer_buffer)?;
+            offset = VInt::deserialize(&mut footer_buffer)?.0 as usize;
+        }
+        entries.push((offset, file_addr));
+    }
+    Ok(entries)
 }
 
 impl CompositeFile {
     /// Opens a composite file stored in a given
     /// `ReadOnlySource`.
+    ///
+    /// Both the current and the legacy, unversioned, footers are supported.
     pub fn open(data: &ReadOnlySource) -> io::Result<CompositeFile> {
-        let end = data.len();
-        let footer_len_data = data.slice_from(end - 4);
-        let footer_len = u32::deserialize(&mut footer_len_data.as_slice())? as usize;
-        let footer_start = end - 4 - footer_len;
-        let footer_data = data.slice(footer_start, footer_start + footer_len);
-        let mut footer_buffer = footer_data.as_slice();
-        let num_fields = VInt::deserialize(&mut footer_buffer)?.0 as usize;
-
-        let mut file_addrs = vec![];
-        let mut offsets = vec![];
-
-        let mut field_index = HashMap::new();
-
-        let mut offset = 0;
-        for _ in 0..num_fields {
-            offset += VInt::deserialize(&mut footer_buffer)?.0 as usize;
-            let file_addr = FileAddr::deserialize(&mut footer_buffer)?;
-            offsets.push(offset);
-            file_addrs.push(file_addr);
+        if data.len() < 4 {
+            return Err(corrupted_footer("the file is too short"));
         }
-        offsets.push(footer_start);
-        for i in 0..num_fields {
-            let file_addr = file_addrs[i];
-            let start_offset = offsets[i];
-            let end_offset = offsets[i + 1];
+        let (body, footer_len_data) = data.clone().split_from_end(4);
+        let footer_len_flagged = footer_len_data.read_u32_at(0)?;
+        let footer_len = (footer_len_flagged & !VERSIONED_FOOTER_FLAG) as usize;
+        if footer_len > body.len() {
+            return Err(corrupted_footer("the footer is longer than the file"));
+        }
+        let (body, footer_data) = body.split_from_end(footer_len);
+        let footer_start = body.len();
+        let entries = if footer_len_flagged & VERSIONED_FOOTER_FLAG != 0 {
+            let (version, footer_entries) = match footer_data.as_slice().split_last() {
+                Some((&version, footer_entries)) => (version, footer_entries),
+                None => return Err(corrupted_footer("the version is missing")),
+            };
+            if version > COMPOSITE_FILE_VERSION {
+                return Err(corrupted_footer(&format!(
+                    "unsupported version {}",
+                    version
+                )));
+            }
+            read_footer_entries(footer_entries, version)?
+        } else {
+            read_footer_entries(footer_data.as_slice(), 0u8)?
+        };
+
+        let mut field_index = BTreeMap::new();
+        for (i, &(start_offset, file_addr)) in entries.iter().enumerate() {
+            let end_offset = entries
+                .get(i + 1)
+                .map(|&(next_offset, _)| next_offset)
+                .unwrap_or(footer_start);
+            if start_offset > end_offset {
+                return Err(corrupted_footer("the offsets are not sorted"));
+            }
             field_index.insert(file_addr, (start_offset, end_offset));
         }
 
         Ok(CompositeFile {
-            data: data.slice_to(footer_start),
+            data: body,
             offsets_index: field_index,
         })
     }
@@ -150,7 +219,7 @@ impl CompositeFile {
     /// no fields.
     pub fn empty() -> CompositeFile {
         CompositeFile {
-            offsets_index: HashMap::new(),
+            offsets_index: BTreeMap::new(),
             data: ReadOnlySource::empty(),
         }
     }
@@ -164,11 +233,29 @@ impl CompositeFile {
     /// Returns the `ReadOnlySource` associated
     /// to a given `Field` and stored in a `CompositeFile`.
     pub fn open_read_with_idx(&self, field: Field, idx: usize) -> Option<ReadOnlySource> {
-        self.offsets_index
-            .get(&FileAddr { field, idx })
-            .map(|&(from, to)| self.data.slice(from, to))
+        self.open_read_addr(FileAddr::new(field, idx))
     }
 
+    /// Returns the `ReadOnlySource` of the sub-file at the given address,
+    /// or `None` if the composite file does not contain it.
+    pub fn open_read_addr(&self, file_addr: FileAddr) -> Option<ReadOnlySource> {
+        self.byte_range(file_addr)
+            .map(|(from, to)| self.data.slice(from, to))
+    }
+
+    /// Returns the start and stop offsets, within the composite file,
+    /// of the sub-file at the given address.
+    pub fn byte_range(&self, file_addr: FileAddr) -> Option<(usize, usize)> {
+        self.offsets_index.get(&file_addr).cloned()
+    }
+
+    /// Returns the addresses of the sub-files of the composite file,
+    /// sorted by field and index.
+    pub fn iter_fields(&self) -> btree_map::Keys<FileAddr, (usize, usize)> {
+        self.offsets_index.keys()
+    }
+
+    /// Returns the space used by the sub-files, per field.
     pub fn space_usage(&self) -> PerFieldSpaceUsage {
         let mut fields = HashMap::new();
         for (&field_addr, &(start, end)) in self.offsets_index.iter() {
@@ -183,10 +270,10 @@ impl CompositeFile {
 #[cfg(test)]
 mod test {
 
-    use super::{CompositeFile, CompositeWrite};
+    use super::{CompositeFile, CompositeWrite, FileAddr};
     use common::BinarySerializable;
     use common::VInt;
-    use directory::{Directory, RAMDirectory};
+    use directory::{Directory, RAMDirectory, ReadOnlySource};
     use schema::Field;
     use std::io::Write;
     use std::path::Path;
@@ -228,7 +315,51 @@ mod test {
                 assert_eq!(file4_buf.len(), 0);
                 assert_eq!(payload_4, 2u64);
             }
+            let file_addrs: Vec<FileAddr> = composite_file.iter_fields().cloned().collect();
+            assert_eq!(
+                file_addrs,
+                vec![FileAddr::new(Field(0u32), 0), FileAddr::new(Field(4u32), 0)]
+            );
+            let (start, stop) = composite_file
+                .byte_range(FileAddr::new(Field(4u32), 0))
+                .unwrap();
+            assert_eq!(&r.as_slice()[start..stop], &[2u8 | 128u8]);
+            assert!(composite_file
+                .open_read_addr(FileAddr::new(Field(1u32), 0))
+                .is_none());
         }
     }
 
+    #[test]
+    fn test_composite_file_legacy_footer() {
+        // A file written before the footer was versioned:
+        // 2 bytes for the field 3, 1 byte for the index 1 of the field 5,
+        // then the (delta offset, field, idx) entries and the footer length.
+        let mut data: Vec<u8> = vec![7u8, 8u8, 9u8];
+        let mut footer: Vec<u8> = Vec::new();
+        let file_addr3 = FileAddr::new(Field(3u32), 0);
+        let file_addr5 = FileAddr::new(Field(5u32), 1);
+        VInt(2).serialize(&mut footer).unwrap();
+        VInt(0).serialize(&mut footer).unwrap();
+        file_addr3.serialize(&mut footer).unwrap();
+        VInt(2).serialize(&mut footer).unwrap();
+        file_addr5.serialize(&mut footer).unwrap();
+        data.extend_from_slice(&footer);
+        (footer.len() as u32).serialize(&mut data).unwrap();
+
+        let composite_file = CompositeFile::open(&ReadOnlySource::from(data)).unwrap();
+        let file3 = composite_file.open_read(Field(3u32)).unwrap();
+        assert_eq!(file3.as_slice(), &[7u8, 8u8]);
+        let file5 = composite_file.open_read_with_idx(Field(5u32), 1).unwrap();
+        assert_eq!(file5.as_slice(), &[9u8]);
+        assert_eq!(composite_file.iter_fields().count(), 2);
+    }
+
+    #[test]
+    fn test_composite_file_corrupted_footer() {
+        let data: Vec<u8> = vec![0u8, 0u8, 0u8, 100u8];
+        assert!(CompositeFile::open(&ReadOnlySource::from(data)).is_err());
+        assert!(CompositeFile::open(&ReadOnlySource::from(vec![1u8])).is_err());
+    }
+
 }
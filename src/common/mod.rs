@@ -7,7 +7,7 @@ mod vint;
 
 pub use self::bitset::BitSet;
 pub(crate) use self::bitset::TinySet;
-pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
+pub use self::composite_file::{CompositeFile, CompositeWrite, FileAddr, COMPOSITE_FILE_VERSION};
 pub use self::counting_writer::CountingWriter;
 pub use self::serialize::{BinarySerializable, FixedSize};
 pub use self::vint::VInt;
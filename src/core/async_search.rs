@@ -0,0 +1,192 @@
+use collector::{CancellableCollector, Collector};
+use core::Searcher;
+use futures::sync::oneshot::Receiver;
+use futures::{Async, Future, Poll};
+use query::Query;
+use schema::Document;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use DocAddress;
+use Result;
+use TantivyError;
+
+/// Future resolving to the result of some search work offloaded
+/// onto the index search `Executor`.
+///
+/// Dropping the future before it is resolved cancels the search:
+/// the worker stops collecting documents as soon as it notices
+/// the cancellation.
+pub struct SearchFuture<T> {
+    receiver: Receiver<Result<T>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T> Future for SearchFuture<T> {
+    type Item = T;
+    type Error = TantivyError;
+
+    fn poll(&mut self) -> Poll<T, TantivyError> {
+        match self.receiver.poll() {
+            Ok(Async::Ready(Ok(val))) => Ok(Async::Ready(val)),
+            Ok(Async::Ready(Err(err))) => Err(err),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(_) => Err(TantivyError::ErrorInThread(
+                "The search worker panicked.".to_string(),
+            )),
+        }
+    }
+}
+
+impl<T> Drop for SearchFuture<T> {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+}
+
+impl Searcher {
+    /// Runs a query on the index search `Executor`, and returns a future
+    /// resolving to the collector once the search is done.
+    ///
+    /// This method is meant to be called from an asynchronous runtime:
+    /// the blocking work (page faults, decompression, scoring...) never
+    /// happens on the calling thread. It runs on the pool of the executor
+    /// if the index was configured with a multithreaded executor
+    /// (see `Index::set_multithread_executor`), and on a dedicated
+    /// thread otherwise.
+    ///
+    /// Dropping the returned future cancels the search.
+    pub fn search_async<C>(&self, query: Box<Query + Send>, collector: C) -> SearchFuture<C>
+    where
+        C: Collector + Send + 'static,
+    {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut collector = CancellableCollector::new(collector, Arc::clone(&cancelled));
+        let searcher = self.clone();
+        let receiver = self.index().search_executor().spawn_detached(move || {
+            query.search(&searcher, &mut collector)?;
+            Ok(collector.into_inner())
+        });
+        SearchFuture {
+            receiver,
+            cancelled,
+        }
+    }
+
+    /// Fetches a document from the doc store on the index search `Executor`.
+    ///
+    /// See `Searcher::search_async`.
+    pub fn doc_async(&self, doc_address: DocAddress) -> SearchFuture<Document> {
+        let DocAddress(segment_local_id, doc_id) = doc_address;
+        let segment_reader = self.segment_reader(segment_local_id).clone();
+        let receiver = self
+            .index()
+            .search_executor()
+            .spawn_detached(move || segment_reader.doc(doc_id));
+        SearchFuture {
+            receiver,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::{Collector, CountCollector, TopScoreCollector};
+    use futures::Future;
+    use query::{AllQuery, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, STORED, TEXT};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::time::Duration;
+    use DocId;
+    use Index;
+    use Result;
+    use Score;
+    use SegmentLocalId;
+    use SegmentReader;
+
+    /// Waits for a signal before collecting the first document.
+    struct WaitingCollector {
+        signal: Receiver<()>,
+        signaled: Option<bool>,
+    }
+
+    impl Collector for WaitingCollector {
+        fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+            Ok(())
+        }
+
+        fn collect(&mut self, _: DocId, _: Score) {
+            if self.signaled.is_none() {
+                let signaled = self.signal.recv_timeout(Duration::from_secs(10)).is_ok();
+                self.signaled = Some(signaled);
+            }
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_search_async() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let mut index = Index::create_in_ram(schema);
+        index.set_multithread_executor(2);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::Basic,
+        );
+        let count_collector = searcher
+            .search_async(Box::new(term_query.clone()), CountCollector::default())
+            .wait()
+            .unwrap();
+        assert_eq!(count_collector.count(), 2);
+        let top_collector = searcher
+            .search_async(Box::new(term_query), TopScoreCollector::with_limit(1))
+            .wait()
+            .unwrap();
+        let doc_address = top_collector.docs()[0];
+        let doc = searcher.doc_async(doc_address).wait().unwrap();
+        assert_eq!(doc, searcher.doc(doc_address).unwrap());
+    }
+
+    #[test]
+    fn test_search_async_single_thread_does_not_block() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let (sender, receiver) = channel();
+        let collector = WaitingCollector {
+            signal: receiver,
+            signaled: None,
+        };
+        // If the search ran in the calling thread, the signal
+        // would only be sent once the collector gave up waiting.
+        let search_future = searcher.search_async(Box::new(AllQuery), collector);
+        sender.send(()).unwrap();
+        let collector = search_future.wait().unwrap();
+        assert_eq!(collector.signaled, Some(true));
+    }
+}
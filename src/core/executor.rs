@@ -0,0 +1,697 @@
+use error::TantivyError;
+use futures::sync::oneshot;
+use futures::sync::oneshot::Receiver;
+use futures::Future;
+use futures_cpupool::Builder as CpuPoolBuilder;
+use futures_cpupool::CpuPool;
+use std::any::Any;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use Result;
+
+/// Describes a task that panicked in `Executor::map_best_effort`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskPanic {
+    /// Ordinal of the task, in the order of the arguments.
+    pub task_ord: usize,
+    /// Message of the panic, if it could be extracted from its payload.
+    pub message: String,
+}
+
+pub(crate) fn panic_message(payload: &Any) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Search executor.
+///
+/// The executor decides where the search work is run:
+/// either in the caller thread, or in a pool of threads.
+pub enum Executor {
+    /// Runs the work in the calling thread.
+    SingleThread,
+    /// Runs the work in a pool of threads.
+    ThreadPool(CpuPool),
+    /// Runs the work in the calling thread if it consists of fewer tasks
+    /// than the given minimum, and in a pool of threads otherwise.
+    Adaptive(CpuPool, usize),
+}
+
+// Runs `f` and sends its result.
+//
+// If `f` panics, the panic is caught and the sender is dropped,
+// so that the receiver resolves to `Canceled` wherever `f` was run.
+fn run_and_send<F, R>(f: F, sender: oneshot::Sender<R>)
+where
+    F: FnOnce() -> R,
+{
+    if let Ok(result) = panic::catch_unwind(AssertUnwindSafe(f)) {
+        let _ = sender.send(result);
+    }
+}
+
+// Runs `f` on the pool if any, or in the calling thread, and delivers
+// its result through a oneshot channel.
+fn spawn_on<F, R>(pool_opt: Option<&CpuPool>, f: F) -> Receiver<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    if let Some(pool) = pool_opt {
+        pool.spawn_fn(move || {
+            run_and_send(f, sender);
+            Ok::<(), ()>(())
+        }).forget();
+    } else {
+        run_and_send(f, sender);
+    }
+    receiver
+}
+
+impl Executor {
+    /// Creates an `Executor` that runs the work in the calling thread.
+    pub fn single_thread() -> Executor {
+        Executor::SingleThread
+    }
+
+    /// Creates an `Executor` running the work on a pool of `num_threads` threads.
+    ///
+    /// The threads are named after the given `prefix`.
+    ///
+    /// A pool without any thread would never run its tasks: if `num_threads`
+    /// is `0`, the executor falls back to running the work in the calling
+    /// thread, as `Executor::single_thread()`.
+    pub fn multi_thread(num_threads: usize, prefix: &str) -> Executor {
+        if num_threads == 0 {
+            warn!("Multithreaded executor requested with 0 threads. Running in the calling thread instead.");
+            return Executor::SingleThread;
+        }
+        let pool = CpuPoolBuilder::new()
+            .name_prefix(prefix)
+            .pool_size(num_threads)
+            .create();
+        Executor::ThreadPool(pool)
+    }
+
+    /// Creates an `Executor` running the work on a pool of `num_threads` threads,
+    /// unless it consists of fewer than `min_tasks` tasks.
+    ///
+    /// Dispatching a few small tasks to a pool of threads typically costs
+    /// more than it saves: with this executor, the `map` methods run
+    /// their tasks in the calling thread if they have fewer than `min_tasks`
+    /// arguments, e.g. when searching an index with few segments.
+    ///
+    /// `spawn` always runs its closure on the pool.
+    ///
+    /// As with `Executor::multi_thread`, the threads are named after
+    /// the given `prefix`, and a `num_threads` of `0` gives
+    /// an executor running all of the work in the calling thread.
+    pub fn adaptive(num_threads: usize, min_tasks: usize, prefix: &str) -> Executor {
+        if num_threads == 0 {
+            warn!("Adaptive executor requested with 0 threads. Running in the calling thread instead.");
+            return Executor::SingleThread;
+        }
+        let pool = CpuPoolBuilder::new()
+            .name_prefix(prefix)
+            .pool_size(num_threads)
+            .create();
+        Executor::Adaptive(pool, min_tasks)
+    }
+
+    fn pool(&self) -> Option<&CpuPool> {
+        match *self {
+            Executor::SingleThread => None,
+            Executor::ThreadPool(ref pool) | Executor::Adaptive(ref pool, _) => Some(pool),
+        }
+    }
+
+    // Returns the pool on which to run a workload of `num_tasks` tasks,
+    // or `None` if the tasks should run in the calling thread.
+    fn pool_for(&self, num_tasks: usize) -> Option<&CpuPool> {
+        match *self {
+            Executor::Adaptive(_, min_tasks) if num_tasks < min_tasks => None,
+            _ => self.pool(),
+        }
+    }
+
+    /// Runs the closure `f` and delivers its result through a oneshot channel.
+    ///
+    /// With the `SingleThread` executor, `f` is run before `spawn` returns.
+    /// With the `ThreadPool` executor, `f` is run on one of the pool's threads.
+    ///
+    /// If `f` panics, the panic does not reach the caller, even with the
+    /// `SingleThread` executor: the sender is dropped and the receiver
+    /// resolves to `Canceled`.
+    pub fn spawn<F, R>(&self, f: F) -> Receiver<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        spawn_on(self.pool(), f)
+    }
+
+    /// Same as `spawn`, except that `f` is never run in the calling thread:
+    /// without a pool of threads, `f` is run on a new thread.
+    pub(crate) fn spawn_detached<F, R>(&self, f: F) -> Receiver<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        if let Some(pool) = self.pool() {
+            return spawn_on(Some(pool), f);
+        }
+        let (sender, receiver) = oneshot::channel();
+        thread::spawn(move || run_and_send(f, sender));
+        receiver
+    }
+
+    /// Applies `f` to each of the `args`, and returns the results
+    /// of the tasks that completed, along with a description of the tasks
+    /// that panicked.
+    ///
+    /// A panicking task does not prevent the other tasks from running.
+    /// The results are returned in the order of the `args`.
+    pub fn map_best_effort<A, R, F>(&self, f: F, args: Vec<A>) -> (Vec<R>, Vec<TaskPanic>)
+    where
+        A: Send + 'static,
+        R: Send + 'static,
+        F: Fn(A) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let pool_opt = self.pool_for(args.len());
+        let receivers: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                let f = Arc::clone(&f);
+                spawn_on(pool_opt, move || {
+                    panic::catch_unwind(AssertUnwindSafe(|| f(arg)))
+                })
+            }).collect();
+        let mut results = Vec::with_capacity(receivers.len());
+        let mut panics = Vec::new();
+        for (task_ord, receiver) in receivers.into_iter().enumerate() {
+            match receiver.wait() {
+                Ok(Ok(result)) => {
+                    results.push(result);
+                }
+                Ok(Err(payload)) => {
+                    panics.push(TaskPanic {
+                        task_ord,
+                        message: panic_message(&*payload),
+                    });
+                }
+                Err(_) => {
+                    panics.push(TaskPanic {
+                        task_ord,
+                        message: "the task was dropped before completing".to_string(),
+                    });
+                }
+            }
+        }
+        (results, panics)
+    }
+
+    /// Applies `f` to each of the `args`, and returns the results
+    /// in the order of the `args`.
+    ///
+    /// Each time a task finishes, `on_done` is called with the number
+    /// of tasks completed so far. With the `ThreadPool` executor, it is called
+    /// from the thread that ran the task, so that when tasks complete
+    /// concurrently, the counts may be reported out of order.
+    ///
+    /// This makes it possible to report the progress of a long search,
+    /// (e.g. "3/40 segments done").
+    ///
+    /// # Panics
+    /// If one of the tasks, or one of the calls to `on_done`, panics,
+    /// the panic is propagated to the caller once all of the tasks are done.
+    pub fn map_with_progress<A, R, F, P>(&self, f: F, args: Vec<A>, on_done: P) -> Vec<R>
+    where
+        A: Send + 'static,
+        R: Send + 'static,
+        F: Fn(A) -> R + Send + Sync + 'static,
+        P: Fn(usize) + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let on_done = Arc::new(on_done);
+        let num_completed = Arc::new(AtomicUsize::new(0));
+        let pool_opt = self.pool_for(args.len());
+        let receivers: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                let f = Arc::clone(&f);
+                let on_done = Arc::clone(&on_done);
+                let num_completed = Arc::clone(&num_completed);
+                spawn_on(pool_opt, move || {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| f(arg)));
+                    let num_completed = num_completed.fetch_add(1, Ordering::SeqCst) + 1;
+                    // A panicking `on_done` must not be mistaken for a dropped task.
+                    let progress = panic::catch_unwind(AssertUnwindSafe(|| on_done(num_completed)));
+                    result.and_then(|result| progress.map(|_| result))
+                })
+            }).collect();
+        let results: Vec<_> = receivers
+            .into_iter()
+            .map(|receiver| receiver.wait())
+            .collect();
+        results
+            .into_iter()
+            .map(|result| match result {
+                Ok(Ok(result)) => result,
+                Ok(Err(payload)) => panic::resume_unwind(payload),
+                Err(_) => panic!("The task was dropped before completing."),
+            }).collect()
+    }
+
+    /// Applies `f` to each of the `args`, and returns the results
+    /// in the order of the `args`.
+    ///
+    /// # Panics
+    /// If one of the tasks panics, the panic is propagated to the caller
+    /// once all of the tasks are done.
+    pub fn map<A, R, F>(&self, f: F, args: Vec<A>) -> Vec<R>
+    where
+        A: Send + 'static,
+        R: Send + 'static,
+        F: Fn(A) -> R + Send + Sync + 'static,
+    {
+        self.map_with_progress(f, args, |_| {})
+    }
+
+    /// Applies `f` to each of the `args`, and returns the results
+    /// in the order of the `args`.
+    ///
+    /// Unlike `.map(...)`, a panicking task does not propagate its panic.
+    /// The panic is logged along with the `Debug` representation of the
+    /// argument of the task, e.g. the segment it was processing, and
+    /// once all of the tasks are done, the first of the panics is returned
+    /// as an `ErrorInThread` error naming that argument.
+    pub fn try_map<A, R, F>(&self, f: F, args: Vec<A>) -> Result<Vec<R>>
+    where
+        A: Debug + Send + 'static,
+        R: Send + 'static,
+        F: Fn(A) -> R + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        let pool_opt = self.pool_for(args.len());
+        let receivers: Vec<_> = args
+            .into_iter()
+            .map(|arg| {
+                let f = Arc::clone(&f);
+                // The argument is consumed by the task: its representation
+                // has to be computed beforehand.
+                let arg_repr = format!("{:?}", arg);
+                spawn_on(pool_opt, move || {
+                    panic::catch_unwind(AssertUnwindSafe(|| f(arg))).map_err(|payload| {
+                        let message = format!(
+                            "Task panicked on argument {}: {}",
+                            arg_repr,
+                            panic_message(&*payload)
+                        );
+                        error!("{}", message);
+                        message
+                    })
+                })
+            }).collect();
+        let mut results = Vec::with_capacity(receivers.len());
+        let mut first_error = None;
+        for receiver in receivers {
+            let result = receiver
+                .wait()
+                .unwrap_or_else(|_| Err("The task was dropped before completing.".to_string()));
+            match result {
+                Ok(result) => results.push(result),
+                Err(message) => {
+                    if first_error.is_none() {
+                        first_error = Some(message);
+                    }
+                }
+            }
+        }
+        match first_error {
+            Some(message) => Err(TantivyError::ErrorInThread(message)),
+            None => Ok(results),
+        }
+    }
+
+    /// Wraps the executor so that it records the number of tasks it runs
+    /// and their durations.
+    pub fn with_metrics(self) -> MeteredExecutor {
+        MeteredExecutor {
+            executor: self,
+            counters: Arc::new(TaskCounters::default()),
+        }
+    }
+}
+
+/// Snapshot of the metrics recorded by a `MeteredExecutor`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecutorMetrics {
+    /// Number of tasks run.
+    pub num_tasks: usize,
+    /// Sum of the durations of the tasks.
+    ///
+    /// With the `ThreadPool` executor, tasks run concurrently, so that
+    /// this is typically larger than the elapsed time.
+    pub total_duration: Duration,
+    /// Duration of the slowest task.
+    pub max_task_duration: Duration,
+}
+
+// `AtomicU64` has not landed in stable, and nanoseconds could overflow
+// an `AtomicUsize` on 32-bit platforms: the metrics are kept behind a mutex.
+// Tasks only hold it for a few additions.
+#[derive(Default)]
+struct TaskCounters(Mutex<ExecutorMetrics>);
+
+impl TaskCounters {
+    fn record(&self, duration: Duration) {
+        // The metrics cannot be left in an inconsistent state,
+        // so it is safe to ignore poisoning.
+        let mut metrics = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        metrics.num_tasks += 1;
+        metrics.total_duration += duration;
+        if duration > metrics.max_task_duration {
+            metrics.max_task_duration = duration;
+        }
+    }
+
+    fn snapshot(&self) -> ExecutorMetrics {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// `Executor` recording the number of tasks it runs and their durations,
+/// for capacity planning.
+///
+/// The metrics are accumulated across calls, and updated by the tasks
+/// themselves, from the thread running them.
+///
+/// See `Executor::with_metrics`.
+pub struct MeteredExecutor {
+    executor: Executor,
+    counters: Arc<TaskCounters>,
+}
+
+impl MeteredExecutor {
+    /// Applies `f` to each of the `args`, and returns the results
+    /// in the order of the `args`.
+    ///
+    /// # Panics
+    /// If one of the tasks panics, the panic is propagated to the caller
+    /// once all of the tasks are done.
+    /// Panicking tasks are not recorded.
+    pub fn map<A, R, F>(&self, f: F, args: Vec<A>) -> Vec<R>
+    where
+        A: Send + 'static,
+        R: Send + 'static,
+        F: Fn(A) -> R + Send + Sync + 'static,
+    {
+        let counters = Arc::clone(&self.counters);
+        self.executor.map(
+            move |arg| {
+                let start = Instant::now();
+                let result = f(arg);
+                counters.record(start.elapsed());
+                result
+            },
+            args,
+        )
+    }
+
+    /// Returns a snapshot of the metrics recorded so far.
+    pub fn metrics(&self) -> ExecutorMetrics {
+        self.counters.snapshot()
+    }
+
+    /// Returns the wrapped executor.
+    pub fn executor(&self) -> &Executor {
+        &self.executor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Executor, TaskPanic};
+    use futures::Future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use TantivyError;
+
+    #[test]
+    fn test_single_thread_executor_spawn() {
+        let executor = Executor::single_thread();
+        let receiver = executor.spawn(|| 2 + 3);
+        assert_eq!(receiver.wait().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_multi_thread_executor_spawn() {
+        let executor = Executor::multi_thread(2, "search-test");
+        let receivers: Vec<_> = (0..10).map(|i| executor.spawn(move || i * 2)).collect();
+        let results: Vec<usize> = receivers
+            .into_iter()
+            .map(|receiver| receiver.wait().unwrap())
+            .collect();
+        assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_zero_thread_executor_falls_back_to_single_thread() {
+        for executor in &[
+            Executor::multi_thread(0, "search-test"),
+            Executor::adaptive(0, 2, "search-test"),
+        ] {
+            match *executor {
+                Executor::SingleThread => {}
+                _ => panic!("Expected a single thread executor."),
+            }
+            let results = executor.map(|i: usize| i * 2, (0..10).collect());
+            assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<usize>>());
+            assert_eq!(executor.spawn(|| 2 + 3).wait().unwrap(), 5);
+        }
+    }
+
+    fn test_executor_spawn_panic_aux(executor: &Executor) {
+        let receiver = executor.spawn(|| -> usize { panic!("panic in spawned closure") });
+        assert!(receiver.wait().is_err());
+        // The executor is still usable.
+        assert_eq!(executor.spawn(|| 2 + 3).wait().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_executor_spawn_panic_single_thread() {
+        test_executor_spawn_panic_aux(&Executor::single_thread());
+    }
+
+    #[test]
+    fn test_executor_spawn_panic_multi_thread() {
+        test_executor_spawn_panic_aux(&Executor::multi_thread(1, "search-test"));
+    }
+
+    #[test]
+    fn test_executor_spawn_panic_adaptive() {
+        test_executor_spawn_panic_aux(&Executor::adaptive(1, 100, "search-test"));
+    }
+
+    fn test_map_best_effort_aux(executor: &Executor) {
+        let (results, panics) = executor.map_best_effort(
+            |i: usize| {
+                if i % 2 == 1 {
+                    panic!("task {} panicked", i);
+                }
+                i * 10
+            },
+            vec![0, 1, 2, 3],
+        );
+        assert_eq!(results, vec![0, 20]);
+        assert_eq!(
+            panics,
+            vec![
+                TaskPanic {
+                    task_ord: 1,
+                    message: "task 1 panicked".to_string(),
+                },
+                TaskPanic {
+                    task_ord: 3,
+                    message: "task 3 panicked".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_map_best_effort_single_thread() {
+        test_map_best_effort_aux(&Executor::single_thread());
+    }
+
+    #[test]
+    fn test_map_best_effort_multi_thread() {
+        test_map_best_effort_aux(&Executor::multi_thread(3, "search-test"));
+    }
+
+    fn test_map_with_progress_aux(executor: &Executor) {
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let reported_counts = Arc::new(Mutex::new(Vec::new()));
+        let results = {
+            let num_calls = Arc::clone(&num_calls);
+            let reported_counts = Arc::clone(&reported_counts);
+            executor.map_with_progress(
+                |i: usize| i * 10,
+                (0..40).collect(),
+                move |num_completed| {
+                    num_calls.fetch_add(1, Ordering::SeqCst);
+                    reported_counts.lock().unwrap().push(num_completed);
+                },
+            )
+        };
+        assert_eq!(results, (0..40).map(|i| i * 10).collect::<Vec<usize>>());
+        assert_eq!(num_calls.load(Ordering::SeqCst), 40);
+        let mut reported_counts = reported_counts.lock().unwrap().clone();
+        reported_counts.sort();
+        assert_eq!(reported_counts, (1..41).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_map_with_progress_single_thread() {
+        test_map_with_progress_aux(&Executor::single_thread());
+    }
+
+    #[test]
+    fn test_map_with_progress_multi_thread() {
+        test_map_with_progress_aux(&Executor::multi_thread(3, "search-test"));
+    }
+
+    #[test]
+    #[should_panic(expected = "task 2 panicked")]
+    fn test_map_with_progress_panic() {
+        let executor = Executor::multi_thread(2, "search-test");
+        executor.map_with_progress(
+            |i: usize| {
+                if i == 2 {
+                    panic!("task 2 panicked");
+                }
+                i
+            },
+            vec![0, 1, 2, 3],
+            |_| {},
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "progress report failed")]
+    fn test_map_with_progress_on_done_panic() {
+        let executor = Executor::multi_thread(2, "search-test");
+        executor.map_with_progress(
+            |i: usize| i,
+            vec![0, 1, 2, 3],
+            |num_completed| {
+                if num_completed == 2 {
+                    panic!("progress report failed");
+                }
+            },
+        );
+    }
+
+    fn test_try_map_aux(executor: &Executor) {
+        let square = |i: usize| {
+            if i == 3 {
+                panic!("cannot process three");
+            }
+            i * i
+        };
+        let squares = executor.try_map(square, vec![0, 1, 2]).unwrap();
+        assert_eq!(squares, vec![0, 1, 4]);
+        match executor.try_map(square, (0..6).collect()) {
+            Err(TantivyError::ErrorInThread(message)) => {
+                assert!(message.contains("argument 3"));
+                assert!(message.contains("cannot process three"));
+            }
+            _ => panic!("The panic of the task was not reported."),
+        }
+    }
+
+    #[test]
+    fn test_try_map_single_thread() {
+        test_try_map_aux(&Executor::single_thread());
+    }
+
+    #[test]
+    fn test_try_map_multi_thread() {
+        test_try_map_aux(&Executor::multi_thread(3, "search-test"));
+    }
+
+    fn test_metered_executor_aux(executor: Executor) {
+        let executor = executor.with_metrics();
+        assert_eq!(executor.metrics().num_tasks, 0);
+        let results = executor.map(
+            |i: u64| {
+                thread::sleep(Duration::from_millis(i));
+                i
+            },
+            vec![1, 20, 3, 5],
+        );
+        assert_eq!(results, vec![1, 20, 3, 5]);
+        let metrics = executor.metrics();
+        assert_eq!(metrics.num_tasks, 4);
+        assert!(metrics.max_task_duration >= Duration::from_millis(20));
+        assert!(metrics.total_duration >= Duration::from_millis(29));
+        assert!(metrics.total_duration >= metrics.max_task_duration);
+
+        // Metrics accumulate across calls.
+        executor.map(|i: usize| i, (0..10).collect());
+        assert_eq!(executor.metrics().num_tasks, 14);
+    }
+
+    #[test]
+    fn test_metered_executor_single_thread() {
+        test_metered_executor_aux(Executor::single_thread());
+    }
+
+    #[test]
+    fn test_metered_executor_multi_thread() {
+        test_metered_executor_aux(Executor::multi_thread(3, "search-test"));
+    }
+
+    #[test]
+    fn test_adaptive_executor() {
+        let executor = Executor::adaptive(2, 3, "adaptive-test");
+        let caller_thread_id = thread::current().id();
+        // The threads of the pool are named after the prefix.
+        let thread_name = executor
+            .spawn(|| thread::current().name().map(|name| name.to_string()))
+            .wait()
+            .unwrap();
+        assert!(thread_name.unwrap().starts_with("adaptive-test"));
+        // Below the threshold, the tasks run in the calling thread.
+        let thread_ids = executor.map(|_: usize| thread::current().id(), vec![0]);
+        assert_eq!(thread_ids, vec![caller_thread_id]);
+        let thread_ids = executor.map(|_: usize| thread::current().id(), vec![0, 1]);
+        assert_eq!(thread_ids, vec![caller_thread_id; 2]);
+        // Otherwise, they run on the pool.
+        let thread_ids = executor.map(|_: usize| thread::current().id(), (0..10).collect());
+        assert_eq!(thread_ids.len(), 10);
+        assert!(thread_ids
+            .iter()
+            .all(|thread_id| *thread_id != caller_thread_id));
+        test_map_best_effort_aux(&executor);
+        test_map_with_progress_aux(&executor);
+        test_map_with_progress_aux(&Executor::adaptive(2, 100, "search-test"));
+    }
+}
@@ -3,10 +3,18 @@ use super::pool::Pool;
 use super::segment::create_segment;
 use super::segment::Segment;
 use core::searcher::Searcher;
+use core::Executor;
+use core::IndexBuilder;
 use core::IndexMeta;
+use core::IndexSettings;
+use core::Metrics;
+use core::NoMetrics;
+use core::RelevanceProfile;
+use core::SegmentComponents;
 use core::SegmentId;
 use core::SegmentMeta;
 use core::SegmentReader;
+use core::WarmUpSet;
 use core::META_FILEPATH;
 use directory::ManagedDirectory;
 #[cfg(feature = "mmap")]
@@ -15,14 +23,22 @@ use directory::{Directory, RAMDirectory};
 use error::TantivyError;
 use indexer::index_writer::open_index_writer;
 use indexer::index_writer::HEAP_SIZE_MIN;
-use indexer::segment_updater::save_new_metas;
+use indexer::index_writer::PIPELINE_MAX_SIZE_IN_DOCS;
+use indexer::recover_index;
+use indexer::rewrite_index;
+use indexer::segment_updater::{save_metas, save_new_metas};
+use indexer::FieldReshape;
 use indexer::LockType;
+use indexer::RecoveryReport;
 use num_cpus;
 use schema::Field;
+use schema::FieldEntry;
 use schema::FieldType;
 use schema::Schema;
 use serde_json;
 use std::borrow::BorrowMut;
+use std::cmp;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -35,7 +51,12 @@ use Result;
 fn load_metas(directory: &Directory) -> Result<IndexMeta> {
     let meta_data = directory.atomic_read(&META_FILEPATH)?;
     let meta_string = String::from_utf8_lossy(&meta_data);
-    serde_json::from_str(&meta_string)
+    let meta_json: serde_json::Value = serde_json::from_str(&meta_string)
+        .map_err(|_| TantivyError::CorruptedFile(META_FILEPATH.clone()))?;
+    if let Some(settings_json) = meta_json.get("settings") {
+        IndexSettings::check_json(settings_json)?;
+    }
+    serde_json::from_value(meta_json)
         .map_err(|_| TantivyError::CorruptedFile(META_FILEPATH.clone()))
 }
 
@@ -43,12 +64,22 @@ fn load_metas(directory: &Directory) -> Result<IndexMeta> {
 pub struct Index {
     directory: ManagedDirectory,
     schema: Schema,
+    settings: IndexSettings,
     num_searchers: Arc<AtomicUsize>,
     searcher_pool: Arc<Pool<Searcher>>,
     tokenizers: TokenizerManager,
+    executor: Arc<Executor>,
+    metrics: Arc<Metrics>,
+    warm_up_set: Option<WarmUpSet>,
 }
 
 impl Index {
+    /// Returns a builder creating or opening an index with
+    /// its schema, settings and tokenizers, see `IndexBuilder`.
+    pub fn builder() -> IndexBuilder {
+        IndexBuilder::new()
+    }
+
     /// Examines the director to see if it contains an index
     pub fn exists<Dir: Directory>(dir: &Dir) -> bool {
         dir.exists(&META_FILEPATH)
@@ -77,19 +108,50 @@ impl Index {
         Index::create(mmap_directory, schema)
     }
 
-    /// Opens or creates a new index in the provided directory
-    #[cfg(feature = "mmap")]
+    /// Opens or creates a new index in the provided directory.
+    ///
+    /// If the directory already contains an index, it is opened,
+    /// and its schema must be equal to `schema`. Otherwise, a
+    /// `SchemaMismatch` error describing the differences is returned.
+    ///
+    /// The existence check and the creation happen while holding
+    /// the `MetaLock`, so that concurrent calls on the same directory
+    /// create the index only once.
     pub fn open_or_create<Dir: Directory>(dir: Dir, schema: Schema) -> Result<Index> {
-        if Index::exists(&dir) {
-            let index = Index::open(dir)?;
-            if index.schema() == schema {
-                Ok(index)
-            } else {
-                Err(TantivyError::SchemaError("An index exists but the schema does not match.".to_string()))
-            }
-        } else {
-            Index::create(dir, schema)
+        let index = Index::open_or_create_without_searchers(dir, schema, IndexSettings::default())?;
+        index.load_searchers()?;
+        Ok(index)
+    }
+
+    /// Same as `open_or_create`, creating the index with `settings`
+    /// if it does not exist yet, but without loading its searchers.
+    ///
+    /// An existing index keeps the settings recorded in its `meta.json`.
+    pub(crate) fn open_or_create_without_searchers<Dir: Directory>(
+        dir: Dir,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> Result<Index> {
+        // `load_searchers` acquires the `MetaLock` too, and the lock
+        // is not reentrant: the caller loads the searchers once it is released.
+        let _meta_lock = LockType::MetaLock.acquire_lock(&dir)?;
+        if !Index::exists(&dir) {
+            return Index::create_without_searchers(dir, schema, settings);
         }
+        let directory = ManagedDirectory::new(dir)?;
+        let metas = load_metas(&directory)?;
+        if metas.schema != schema {
+            return Err(TantivyError::SchemaMismatch(schema_diff(
+                &metas.schema,
+                &schema,
+            )));
+        }
+        metas.settings.validate(&metas.schema)?;
+        Ok(Index::without_searchers(
+            directory,
+            metas.schema,
+            metas.settings,
+        ))
     }
 
     /// Creates a new index in a temp directory.
@@ -108,32 +170,117 @@ impl Index {
 
     /// Creates a new index given an implementation of the trait `Directory`
     pub fn create<Dir: Directory>(dir: Dir, schema: Schema) -> Result<Index> {
-        let directory = ManagedDirectory::new(dir)?;
-        Index::from_directory(directory, schema)
+        Index::create_with_settings(dir, schema, IndexSettings::default())
     }
 
-    /// Create a new index from a directory.
+    /// Creates a new index with the given settings, which are
+    /// persisted in the `meta.json` of the index.
+    ///
+    /// Returns an `UnsupportedSetting` error if this build
+    /// of tantivy does not support the settings.
+    pub fn create_with_settings<Dir: Directory>(
+        dir: Dir,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> Result<Index> {
+        let index = Index::create_without_searchers(dir, schema, settings)?;
+        index.load_searchers()?;
+        Ok(index)
+    }
+
+    /// Creates a new index with the given settings, without loading its searchers.
     ///
     /// This will overwrite existing meta.json
-    fn from_directory(mut directory: ManagedDirectory, schema: Schema) -> Result<Index> {
-        save_new_metas(schema.clone(), 0, directory.borrow_mut())?;
-        let metas = IndexMeta::with_schema(schema);
-        Index::create_from_metas(directory, &metas)
+    pub(crate) fn create_without_searchers<Dir: Directory>(
+        dir: Dir,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> Result<Index> {
+        settings.validate(&schema)?;
+        let mut directory = ManagedDirectory::new(dir)?;
+        save_new_metas(schema.clone(), settings.clone(), 0, directory.borrow_mut())?;
+        Ok(Index::without_searchers(directory, schema, settings))
     }
 
     /// Creates a new index given a directory and an `IndexMeta`.
     fn create_from_metas(directory: ManagedDirectory, metas: &IndexMeta) -> Result<Index> {
-        let schema = metas.schema.clone();
+        metas.settings.validate(&metas.schema)?;
+        let index =
+            Index::without_searchers(directory, metas.schema.clone(), metas.settings.clone());
+        index.load_searchers()?;
+        Ok(index)
+    }
+
+    /// Creates an index object without loading its searchers,
+    /// and therefore without reading `meta.json`.
+    pub(crate) fn without_searchers(
+        directory: ManagedDirectory,
+        schema: Schema,
+        settings: IndexSettings,
+    ) -> Index {
         let n_cpus = num_cpus::get();
-        let index = Index {
+        Index {
             directory,
             schema,
+            settings,
             num_searchers: Arc::new(AtomicUsize::new(n_cpus)),
             searcher_pool: Arc::new(Pool::new()),
             tokenizers: TokenizerManager::default(),
-        };
-        index.load_searchers()?;
-        Ok(index)
+            executor: Arc::new(Executor::single_thread()),
+            metrics: Arc::new(NoMetrics),
+            warm_up_set: None,
+        }
+    }
+
+    /// Accessor to the search executor.
+    ///
+    /// By default the search executor runs the work in the calling thread.
+    pub fn search_executor(&self) -> &Executor {
+        self.executor.as_ref()
+    }
+
+    /// Replace the default single thread search executor pool
+    /// by a thread pool with a given number of threads.
+    ///
+    /// Only works for the searchers loaded after the next call to `load_searchers`.
+    pub fn set_multithread_executor(&mut self, num_threads: usize) {
+        self.executor = Arc::new(Executor::multi_thread(num_threads, "search-"));
+    }
+
+    /// Replace the default single thread search executor pool
+    /// by a thread pool with as many threads as there are CPUs on the system.
+    ///
+    /// Only works for the searchers loaded after the next call to `load_searchers`.
+    pub fn set_default_multithread_executor(&mut self) {
+        let default_num_threads = num_cpus::get();
+        self.set_multithread_executor(default_num_threads);
+    }
+
+    /// Accessor to the metrics hooks.
+    ///
+    /// By default, the hooks do nothing.
+    pub fn metrics(&self) -> &Metrics {
+        self.metrics.as_ref()
+    }
+
+    /// Sets the hooks called during search and indexing.
+    ///
+    /// Only works for the searchers loaded after the next call to `load_searchers`,
+    /// and for the `IndexWriter`s created afterwards.
+    pub fn set_metrics(&mut self, metrics: Arc<Metrics>) {
+        self.metrics = metrics;
+    }
+
+    /// Sets the components warmed up each time searchers are loaded,
+    /// or `None` to disable the warm-up.
+    ///
+    /// The searchers are only published once warmed up, so that queries
+    /// keep running on the previous searchers in the meantime.
+    /// See `Searcher::warm`.
+    ///
+    /// Only works for the searchers loaded after the next call to `load_searchers`.
+    pub fn set_warm_up_on_load(&mut self, warm_up_set: Option<WarmUpSet>) {
+        self.warm_up_set = warm_up_set;
     }
 
     /// Accessor for the tokenizer manager.
@@ -141,6 +288,11 @@ impl Index {
         &self.tokenizers
     }
 
+    /// Replaces the tokenizer manager of the index.
+    pub(crate) fn set_tokenizers(&mut self, tokenizers: TokenizerManager) {
+        self.tokenizers = tokenizers;
+    }
+
     /// Helper to access the tokenizer associated to a specific field.
     pub fn tokenizer_for_field(&self, field: Field) -> Result<Box<BoxedTokenizer>> {
         let field_entry = self.schema.get_field_entry(field);
@@ -204,6 +356,31 @@ impl Index {
         &self,
         num_threads: usize,
         overall_heap_size_in_bytes: usize,
+    ) -> Result<IndexWriter> {
+        self.writer_with_pipeline_capacity(
+            num_threads,
+            overall_heap_size_in_bytes,
+            PIPELINE_MAX_SIZE_IN_DOCS,
+        )
+    }
+
+    /// Same as `writer_with_num_threads`, but also sets the capacity
+    /// of the indexing pipeline.
+    ///
+    /// - `pipeline_capacity` is the maximum number of documents waiting
+    /// to be picked by an indexing thread. When the pipeline is full,
+    /// `IndexWriter::add_document` blocks and `IndexWriter::try_add_document`
+    /// returns a `WouldBlock` error. This bounds the memory used by
+    /// documents that have been added but not indexed yet.
+    ///
+    /// # Errors
+    /// If the lockfile already exists, returns `Error::FileAlreadyExists`.
+    /// If `pipeline_capacity` is 0, returns `Error::InvalidArgument`.
+    pub fn writer_with_pipeline_capacity(
+        &self,
+        num_threads: usize,
+        overall_heap_size_in_bytes: usize,
+        pipeline_capacity: usize,
     ) -> Result<IndexWriter> {
         let directory_lock = LockType::IndexWriterLock.acquire_lock(&self.directory)?;
         let heap_size_in_bytes_per_thread = overall_heap_size_in_bytes / num_threads;
@@ -211,6 +388,7 @@ impl Index {
             self,
             num_threads,
             heap_size_in_bytes_per_thread,
+            pipeline_capacity,
             directory_lock,
         )
     }
@@ -234,6 +412,83 @@ impl Index {
         self.writer_with_num_threads(num_threads, overall_heap_size_in_bytes)
     }
 
+    /// Removes fields from the doc store and/or the inverted index,
+    /// without reindexing the documents.
+    ///
+    /// All of the segments are merged into a single segment, in which
+    /// the fields listed in `reshape` are not stored (resp. indexed) anymore,
+    /// and the updated schema is saved in `meta.json`.
+    ///
+    /// Returns a new `Index` with the updated schema. Searchers
+    /// obtained from `self` remain valid, but the files of the previous
+    /// segments cannot be removed until they are dropped.
+    ///
+    /// Like `.writer(...)`, this method acquires the lockfile of the index,
+    /// and fails with `LockFailure` if an `IndexWriter` is alive.
+    /// It returns a `SchemaError` if `reshape` is not valid for the schema.
+    pub fn rewrite(&self, reshape: &FieldReshape) -> Result<Index> {
+        let metas = rewrite_index(self, reshape)?;
+        let num_searchers = self.num_searchers.load(Ordering::Acquire);
+        let index = Index {
+            directory: self.directory.clone(),
+            schema: metas.schema,
+            settings: metas.settings,
+            num_searchers: Arc::new(AtomicUsize::new(num_searchers)),
+            searcher_pool: Arc::new(Pool::new()),
+            tokenizers: self.tokenizers.clone(),
+            executor: Arc::clone(&self.executor),
+            metrics: Arc::clone(&self.metrics),
+            warm_up_set: self.warm_up_set,
+        };
+        index.load_searchers()?;
+        Ok(index)
+    }
+
+    /// Rebuilds the `meta.json` file of an index from the segment files
+    /// found in the directory.
+    ///
+    /// This is a last resort tool for an index whose `meta.json` was lost
+    /// or corrupted. Since the schema and the settings are only saved
+    /// in `meta.json`, they have to be provided by the caller. In particular,
+    /// the settings must have the `docstore_compression` the index was
+    /// created with, otherwise the doc store of the segments cannot be read.
+    ///
+    /// Segments are discovered through the list of files managed by
+    /// tantivy, which must therefore be present. Only the committed segments
+    /// are recovered: the segments that were never committed, and the
+    /// segments that were removed from the index by a later commit or merge
+    /// but not garbage collected yet, are skipped. This information is read
+    /// from the `.info` file written for each segment when it is first committed.
+    ///
+    /// Each of the remaining segments is validated before being added to the index:
+    /// all of its files must be present, its number of documents must be
+    /// consistent across its field norms and its delete file, and
+    /// the segment must be readable. Segment files do not carry checksums,
+    /// so this validation is structural: silently corrupted data may
+    /// go undetected.
+    ///
+    /// Skipped segments are listed in the returned report
+    /// along with the reason why they were skipped.
+    /// When several delete files exist for a segment, the most recent one is used.
+    ///
+    /// Unless `force` is true, this method returns `IndexAlreadyExists`
+    /// if the directory contains a readable `meta.json`.
+    /// Like `.writer(...)`, it fails with `LockFailure` if an `IndexWriter`
+    /// is alive.
+    pub fn recover<Dir: Directory>(
+        dir: Dir,
+        schema: Schema,
+        settings: IndexSettings,
+        force: bool,
+    ) -> Result<RecoveryReport> {
+        settings.validate(&schema)?;
+        if !force && Index::exists(&dir) && load_metas(&dir).is_ok() {
+            return Err(TantivyError::IndexAlreadyExists);
+        }
+        let directory = ManagedDirectory::new(dir)?;
+        recover_index(directory, schema, settings)
+    }
+
     /// Accessor to the index schema
     ///
     /// The schema is actually cloned.
@@ -241,6 +496,57 @@ impl Index {
         self.schema.clone()
     }
 
+    /// Accessor to the index settings, as of the opening of the index
+    /// or of the last call to `.update_settings(...)`.
+    pub fn settings(&self) -> &IndexSettings {
+        &self.settings
+    }
+
+    /// Returns the relevance profiles of the index, by name.
+    ///
+    /// See `IndexSettings::relevance_profiles`.
+    pub fn relevance_profiles(&self) -> &BTreeMap<String, RelevanceProfile> {
+        &self.settings.relevance_profiles
+    }
+
+    /// Changes the settings of the index, and persists them in `meta.json`.
+    ///
+    /// The new settings are used by the `IndexWriter`s created afterwards,
+    /// and only apply to the segments they write: for instance, the
+    /// existing segments keep their doc store block size.
+    /// The doc store compression codec cannot be changed.
+    ///
+    /// Like `.writer(...)`, this method fails with `LockFailure` if an
+    /// `IndexWriter` is alive.
+    pub fn update_settings(&mut self, settings: IndexSettings) -> Result<()> {
+        settings.validate(&self.schema)?;
+        if settings.docstore_compression != self.settings.docstore_compression {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The docstore compression codec cannot be changed from '{}' to '{}'",
+                self.settings.docstore_compression.name(),
+                settings.docstore_compression.name()
+            )));
+        }
+        let _directory_lock = LockType::IndexWriterLock.acquire_lock(self.directory())?;
+        let IndexMeta {
+            segments,
+            schema,
+            opstamp,
+            payload,
+            ..
+        } = self.load_metas()?;
+        save_metas(
+            segments,
+            schema,
+            settings.clone(),
+            opstamp,
+            payload,
+            self.directory.borrow_mut(),
+        )?;
+        self.settings = settings;
+        Ok(())
+    }
+
     /// Returns the list of segments that are searchable
     pub fn searchable_segments(&self) -> Result<Vec<Segment>> {
         Ok(self
@@ -305,20 +611,65 @@ impl Index {
     /// call `load_searchers` whenever a changes happen.
     pub fn load_searchers(&self) -> Result<()> {
         let _meta_lock = LockType::MetaLock.acquire_lock(self.directory())?;
-        let searchable_segments = self.searchable_segments()?;
-        let segment_readers: Vec<SegmentReader> = searchable_segments
-            .iter()
-            .map(SegmentReader::open)
+        let IndexMeta {
+            segments, opstamp, ..
+        } = self.load_metas()?;
+        let segment_readers: Vec<SegmentReader> = segments
+            .into_iter()
+            .map(|segment_meta| SegmentReader::open(&self.segment(segment_meta)))
             .collect::<Result<_>>()?;
         let schema = self.schema();
         let num_searchers: usize = self.num_searchers.load(Ordering::Acquire);
-        let searchers = (0..num_searchers)
-            .map(|_| Searcher::new(schema.clone(), self.clone(), segment_readers.clone()))
-            .collect();
+        let searchers: Vec<Searcher> = (0..num_searchers)
+            .map(|_| {
+                Searcher::new(
+                    schema.clone(),
+                    self.clone(),
+                    segment_readers.clone(),
+                    opstamp,
+                )
+            }).collect();
+        if let Some(warm_up_set) = self.warm_up_set {
+            // The searchers share their segment readers.
+            if let Some(searcher) = searchers.first() {
+                let report = searcher.warm(warm_up_set);
+                info!(
+                    "Warmed up {} bytes before loading the searchers",
+                    report.num_bytes()
+                );
+            }
+        }
         self.searcher_pool.publish_new_generation(searchers);
         Ok(())
     }
 
+    /// Returns a new searcher over the last commit, only loading
+    /// the given segment components.
+    ///
+    /// This searcher is not taken from the searcher pool:
+    /// it is meant for jobs that only require a few datastructures
+    /// (e.g. a couple of fast fields), and do not want to pay for
+    /// opening the other files of every segment.
+    ///
+    /// See `SegmentReader::open_with_components`.
+    pub fn searcher_with_components(&self, components: SegmentComponents) -> Result<Searcher> {
+        let _meta_lock = LockType::MetaLock.acquire_lock(self.directory())?;
+        let IndexMeta {
+            segments, opstamp, ..
+        } = self.load_metas()?;
+        let segment_readers: Vec<SegmentReader> = segments
+            .into_iter()
+            .map(|segment_meta| {
+                SegmentReader::open_with_components(&self.segment(segment_meta), components)
+            }).collect::<Result<_>>()?;
+        Ok(Searcher::new(
+            self.schema(),
+            self.clone(),
+            segment_readers,
+            opstamp,
+        ))
+    }
+
     /// Returns a searcher
     ///
     /// This method should be called every single time a search
@@ -345,18 +696,82 @@ impl Clone for Index {
         Index {
             directory: self.directory.clone(),
             schema: self.schema.clone(),
+            settings: self.settings.clone(),
             num_searchers: Arc::clone(&self.num_searchers),
             searcher_pool: Arc::clone(&self.searcher_pool),
             tokenizers: self.tokenizers.clone(),
+            executor: Arc::clone(&self.executor),
+            metrics: Arc::clone(&self.metrics),
+            warm_up_set: self.warm_up_set,
+        }
+    }
+}
+
+/// Describes the differences between the schema of an existing index
+/// and the schema it is expected to have.
+fn schema_diff(index_schema: &Schema, schema: &Schema) -> String {
+    let to_json = |field_entry: &FieldEntry| {
+        serde_json::to_string(field_entry).expect("Serializing a field entry should never fail")
+    };
+    let num_fields = cmp::max(index_schema.fields().len(), schema.fields().len());
+    let mut differences = Vec::new();
+    for field_id in 0..num_fields {
+        match (
+            index_schema.fields().get(field_id),
+            schema.fields().get(field_id),
+        ) {
+            (Some(index_entry), Some(entry)) => {
+                if index_entry != entry {
+                    differences.push(format!(
+                        "field #{} is {} in the index, {} in the provided schema",
+                        field_id,
+                        to_json(index_entry),
+                        to_json(entry)
+                    ));
+                }
+            }
+            (Some(index_entry), None) => {
+                differences.push(format!(
+                    "field #{} {} is missing from the provided schema",
+                    field_id,
+                    to_json(index_entry)
+                ));
+            }
+            (None, Some(entry)) => {
+                differences.push(format!(
+                    "field #{} {} is not in the index",
+                    field_id,
+                    to_json(entry)
+                ));
+            }
+            (None, None) => {}
         }
     }
+    if index_schema.aliases() != schema.aliases() {
+        differences.push(format!(
+            "aliases are {:?} in the index, {:?} in the provided schema",
+            index_schema.aliases(),
+            schema.aliases()
+        ));
+    }
+    differences.join("; ")
 }
 
 #[cfg(test)]
 mod tests {
-    use schema::{Schema, SchemaBuilder, INT_INDEXED, TEXT};
+    use schema::{Document, Schema, SchemaBuilder, INT_INDEXED, STORED, TEXT};
     use Index;
-    use directory::RAMDirectory;
+    use directory::{Directory, RAMDirectory};
+    use collector::TopCollector;
+    use core::{IndexSettings, RelevanceProfile, META_FILEPATH};
+    use indexer::LockType;
+    use query::QueryParser;
+    use std::path::Path;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use store::StoreCompressor;
+    use DocAddress;
+    use TantivyError;
 
     #[test]
     fn test_indexer_for_field() {
@@ -388,6 +803,22 @@ mod tests {
         assert!(Index::exists(&directory));
     }
 
+    #[test]
+    fn open_or_create_should_create_then_open() {
+        let directory = RAMDirectory::create();
+        let start = Instant::now();
+        {
+            let index = Index::open_or_create(directory.clone(), throw_away_schema()).unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.add_document(Document::default()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let index = Index::open_or_create(directory.clone(), throw_away_schema()).unwrap();
+        assert_eq!(index.searcher().num_docs(), 1);
+        // The meta lock is released, and was never waited for.
+        assert!(!directory.exists(Path::new(".tantivy-meta.lock")));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
 
     #[test]
     fn open_or_create_should_open() {
@@ -412,7 +843,196 @@ mod tests {
         assert!(Index::exists(&directory));
         assert!(Index::open_or_create(directory.clone(), throw_away_schema()).is_ok());
         let err = Index::open_or_create(directory, SchemaBuilder::default().build());
-        assert_eq!(format!("{:?}", err.unwrap_err()), "SchemaError(\"An index exists but the schema does not match.\")");
+        match err {
+            Err(TantivyError::SchemaMismatch(ref diff)) => {
+                assert!(diff.starts_with("field #0 {\"name\":\"num_likes\""));
+                assert!(diff.ends_with("is missing from the provided schema"));
+            }
+            _ => panic!("Expected a schema mismatch"),
+        }
+    }
+
+    #[test]
+    fn open_or_create_concurrently() {
+        let directory = RAMDirectory::create();
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let directory = directory.clone();
+                thread::spawn(move || {
+                    let index = Index::open_or_create(directory, throw_away_schema())?;
+                    let mut index_writer = index.writer_with_num_threads(1, 40_000_000)?;
+                    index_writer.add_document(Document::default())?;
+                    index_writer.commit()
+                })
+            }).collect();
+        for handle in handles {
+            // Writers of different threads may conflict, but creation must not fail.
+            match handle.join().unwrap() {
+                Ok(_) | Err(TantivyError::LockFailure(LockType::IndexWriterLock)) => {}
+                Err(err) => panic!("Unexpected error {:?}", err),
+            }
+        }
+        let index = Index::open(directory).unwrap();
+        assert!(index.schema() == throw_away_schema());
+        index.load_searchers().unwrap();
+        assert!(index.searcher().num_docs() >= 1);
+    }
+
+    #[test]
+    fn test_index_settings() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let directory = RAMDirectory::create();
+        let settings = IndexSettings {
+            docstore_compression: StoreCompressor::Snappy,
+            docstore_blocksize: 100,
+            ..IndexSettings::default()
+        };
+        let mut index =
+            Index::create_with_settings(directory.clone(), schema, settings.clone()).unwrap();
+        assert_eq!(index.settings(), &settings);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..50 {
+                index_writer
+                    .add_document(doc!(text_field => format!("document {}", i)))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+
+            // Settings cannot be updated while a writer is alive.
+            match index.update_settings(settings.clone()) {
+                Err(TantivyError::LockFailure(LockType::IndexWriterLock)) => {}
+                other => panic!("Unexpected result {:?}", other),
+            }
+        }
+        let reopened_index = Index::open(directory.clone()).unwrap();
+        assert_eq!(reopened_index.settings(), &settings);
+
+        // The docstore compression is fixed at creation...
+        let mut new_settings = settings.clone();
+        new_settings.docstore_compression = StoreCompressor::Lz4;
+        assert!(index.update_settings(new_settings).is_err());
+        // ... but the block size can be changed for the new segments.
+        let mut new_settings = settings.clone();
+        new_settings.docstore_blocksize = 10_000;
+        index.update_settings(new_settings.clone()).unwrap();
+        assert_eq!(index.settings(), &new_settings);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "last document"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        let index = Index::open(directory.clone()).unwrap();
+        assert_eq!(index.settings(), &new_settings);
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 51);
+        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+            for doc in 0..segment_reader.max_doc() {
+                let doc = searcher.doc(DocAddress(segment_ord as u32, doc)).unwrap();
+                let text = doc.get_first(text_field).unwrap().text().unwrap();
+                assert!(text.contains("document"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_settings_unknown_codec() {
+        let mut directory = RAMDirectory::create();
+        let settings = IndexSettings {
+            docstore_compression: StoreCompressor::Snappy,
+            docstore_blocksize: 100,
+            ..IndexSettings::default()
+        };
+        Index::create_with_settings(directory.clone(), throw_away_schema(), settings).unwrap();
+        let meta = String::from_utf8(directory.atomic_read(&META_FILEPATH).unwrap()).unwrap();
+        let meta = meta.replace("\"snappy\"", "\"zstd\"");
+        directory
+            .atomic_write(&META_FILEPATH, meta.as_bytes())
+            .unwrap();
+        match Index::open(directory) {
+            Err(TantivyError::UnsupportedSetting(msg)) => assert!(msg.contains("zstd")),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_relevance_profiles() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let mut directory = RAMDirectory::create();
+        let mut settings = IndexSettings::default();
+        let profile = |title_boost: f32, body_boost: f32| {
+            let mut profile = RelevanceProfile::new();
+            profile.insert("title".to_string(), title_boost);
+            profile.insert("body".to_string(), body_boost);
+            profile
+        };
+        settings
+            .relevance_profiles
+            .insert("title_first".to_string(), profile(3.0, 1.0));
+        settings
+            .relevance_profiles
+            .insert("body_first".to_string(), profile(1.0, 3.0));
+        {
+            let index =
+                Index::create_with_settings(directory.clone(), schema.clone(), settings.clone())
+                    .unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(title_field => "rust", body_field => "go"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(title_field => "go", body_field => "rust"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        let index = Index::open(directory.clone()).unwrap();
+        assert_eq!(index.relevance_profiles(), &settings.relevance_profiles);
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let top_doc = |profile_name: &str| {
+            let query_parser = QueryParser::for_index_with_profile(&index, profile_name).unwrap();
+            let query = query_parser.parse_query("rust").unwrap();
+            let mut top_collector = TopCollector::with_limit(1);
+            searcher.search(&*query, &mut top_collector).unwrap();
+            top_collector.docs()[0].doc()
+        };
+        assert_eq!(top_doc("title_first"), 0);
+        assert_eq!(top_doc("body_first"), 1);
+        match QueryParser::for_index_with_profile(&index, "unknown") {
+            Err(TantivyError::InvalidArgument(msg)) => assert!(msg.contains("unknown")),
+            _ => panic!("Expected an InvalidArgument error"),
+        }
+
+        // Profiles may only boost the fields of the schema,
+        // whether the index is created...
+        let mut invalid_settings = settings.clone();
+        invalid_settings
+            .relevance_profiles
+            .get_mut("title_first")
+            .unwrap()
+            .insert("price".to_string(), 2.0);
+        match Index::create_with_settings(RAMDirectory::create(), schema, invalid_settings) {
+            Err(TantivyError::SchemaError(msg)) => assert!(msg.contains("'price'")),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        // ... or loaded.
+        let meta = String::from_utf8(directory.atomic_read(&META_FILEPATH).unwrap()).unwrap();
+        let meta = meta.replace("\"title\":3.0", "\"subtitle\":3.0");
+        directory
+            .atomic_write(&META_FILEPATH, meta.as_bytes())
+            .unwrap();
+        match Index::open(directory) {
+            Err(TantivyError::SchemaError(msg)) => assert!(msg.contains("'subtitle'")),
+            other => panic!("Unexpected result {:?}", other),
+        }
     }
 
     fn throw_away_schema() -> Schema {
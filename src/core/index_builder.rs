@@ -0,0 +1,269 @@
+use core::Index;
+use core::IndexSettings;
+use core::WarmUpSet;
+#[cfg(feature = "mmap")]
+use directory::MmapDirectory;
+use directory::{Directory, RAMDirectory};
+use error::TantivyError;
+use schema::FieldType;
+use schema::Schema;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+use tokenizer::Tokenizer;
+use tokenizer::TokenizerManager;
+use Result;
+
+/// Builder creating or opening an index, with its schema,
+/// its settings and its tokenizers.
+///
+/// Before any file is written, the builder checks that:
+/// - a schema was given,
+/// - the settings are supported by this build of tantivy,
+/// - the tokenizer of each indexed text field is registered,
+///   either by default or through `.tokenizer(...)`.
+///
+/// ```rust
+/// use tantivy::Index;
+/// use tantivy::schema::{SchemaBuilder, TextFieldIndexing, TextOptions};
+/// use tantivy::tokenizer::{LowerCaser, SimpleTokenizer, Tokenizer};
+///
+/// let mut schema_builder = SchemaBuilder::default();
+/// let text_indexing = TextFieldIndexing::default().set_tokenizer("lowercase");
+/// let text_options = TextOptions::default().set_indexing_options(text_indexing);
+/// schema_builder.add_text_field("title", text_options);
+/// let schema = schema_builder.build();
+///
+/// let index = Index::builder()
+///     .schema(schema)
+///     .tokenizer("lowercase", SimpleTokenizer.filter(LowerCaser))
+///     .create_in_ram()
+///     .unwrap();
+/// assert!(index.tokenizers().get("lowercase").is_some());
+/// ```
+pub struct IndexBuilder {
+    schema: Option<Schema>,
+    settings: IndexSettings,
+    tokenizers: TokenizerManager,
+    warm_up_set: Option<WarmUpSet>,
+}
+
+impl IndexBuilder {
+    /// Creates a builder, with the default settings and tokenizers.
+    pub fn new() -> IndexBuilder {
+        IndexBuilder {
+            schema: None,
+            settings: IndexSettings::default(),
+            tokenizers: TokenizerManager::default(),
+            warm_up_set: None,
+        }
+    }
+
+    /// Sets the schema of the index.
+    pub fn schema(mut self, schema: Schema) -> IndexBuilder {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Sets the settings the index is created with.
+    ///
+    /// An existing index opened by `.open_or_create(...)`
+    /// keeps the settings recorded in its `meta.json`.
+    pub fn settings(mut self, settings: IndexSettings) -> IndexBuilder {
+        self.settings = settings;
+        self
+    }
+
+    /// Registers a tokenizer under the given name.
+    pub fn tokenizer<T>(self, tokenizer_name: &str, tokenizer: T) -> IndexBuilder
+    where
+        T: 'static + Send + Sync + for<'a> Tokenizer<'a>,
+    {
+        self.tokenizers.register(tokenizer_name, tokenizer);
+        self
+    }
+
+    /// Warms up the given components of the segments each time
+    /// the searchers are loaded, starting with the index returned
+    /// by the builder.
+    ///
+    /// See `Index::set_warm_up_on_load`.
+    pub fn warm_up_on_load(mut self, warm_up_set: WarmUpSet) -> IndexBuilder {
+        self.warm_up_set = Some(warm_up_set);
+        self
+    }
+
+    /// Creates a new index using the `RAMDirectory`.
+    pub fn create_in_ram(self) -> Result<Index> {
+        self.create(RAMDirectory::create())
+    }
+
+    /// Creates a new index in a given filepath, using the `MmapDirectory`.
+    ///
+    /// Returns an `IndexAlreadyExists` error if the directory
+    /// already contains an index.
+    #[cfg(feature = "mmap")]
+    pub fn create_in_dir<P: AsRef<Path>>(self, directory_path: P) -> Result<Index> {
+        let schema = self.validate()?;
+        let mmap_directory = MmapDirectory::open(directory_path)?;
+        if Index::exists(&mmap_directory) {
+            return Err(TantivyError::IndexAlreadyExists);
+        }
+        let index = Index::create_without_searchers(mmap_directory, schema, self.settings)?;
+        load_searchers(index, self.tokenizers, self.warm_up_set)
+    }
+
+    /// Creates a new index given an implementation of the trait `Directory`.
+    ///
+    /// If a previous index was in this directory, its meta file will be destroyed.
+    pub fn create<Dir: Directory>(self, dir: Dir) -> Result<Index> {
+        let schema = self.validate()?;
+        let index = Index::create_without_searchers(dir, schema, self.settings)?;
+        load_searchers(index, self.tokenizers, self.warm_up_set)
+    }
+
+    /// Opens or creates an index in the provided directory,
+    /// as in `Index::open_or_create`.
+    pub fn open_or_create<Dir: Directory>(self, dir: Dir) -> Result<Index> {
+        let schema = self.validate()?;
+        let index = Index::open_or_create_without_searchers(dir, schema, self.settings)?;
+        load_searchers(index, self.tokenizers, self.warm_up_set)
+    }
+
+    // Checks the constraints documented on `IndexBuilder`,
+    // and returns the schema of the index.
+    fn validate(&self) -> Result<Schema> {
+        let schema = self.schema.clone().ok_or_else(|| {
+            TantivyError::InvalidArgument("The schema of the index was not set".to_string())
+        })?;
+        self.settings.validate(&schema)?;
+        for field_entry in schema.fields() {
+            if let FieldType::Str(ref text_options) = *field_entry.field_type() {
+                if let Some(text_indexing) = text_options.get_indexing_options() {
+                    let tokenizer_name = text_indexing.tokenizer();
+                    if self.tokenizers.get(tokenizer_name).is_none() {
+                        return Err(TantivyError::SchemaError(format!(
+                            "The tokenizer '{}' of the field {:?} is not registered",
+                            tokenizer_name,
+                            field_entry.name()
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(schema)
+    }
+}
+
+// Enables the warm-up of the searchers loaded from now on,
+// and warms up the searchers already loaded.
+// Configures the index before loading its searchers, so that
+// the searchers refer to the tokenizers of the builder, and are warmed up.
+fn load_searchers(
+    mut index: Index,
+    tokenizers: TokenizerManager,
+    warm_up_set: Option<WarmUpSet>,
+) -> Result<Index> {
+    index.set_tokenizers(tokenizers);
+    index.set_warm_up_on_load(warm_up_set);
+    index.load_searchers()?;
+    Ok(index)
+}
+
+impl Default for IndexBuilder {
+    fn default() -> IndexBuilder {
+        IndexBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::{IndexSettings, META_FILEPATH};
+    use directory::{Directory, RAMDirectory};
+    use schema::{SchemaBuilder, TextFieldIndexing, TextOptions, TEXT};
+    use std::path::Path;
+    use tokenizer::{LowerCaser, SimpleTokenizer, Tokenizer};
+    use Index;
+    use TantivyError;
+
+    fn schema_with_tokenizer(tokenizer_name: &str) -> ::schema::Schema {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("body", TEXT);
+        let text_indexing = TextFieldIndexing::default().set_tokenizer(tokenizer_name);
+        let text_options = TextOptions::default().set_indexing_options(text_indexing);
+        schema_builder.add_text_field("title", text_options);
+        schema_builder.build()
+    }
+
+    #[test]
+    fn test_index_builder() {
+        let directory = RAMDirectory::create();
+        let settings = IndexSettings {
+            docstore_blocksize: 1_000,
+            ..IndexSettings::default()
+        };
+        let index = Index::builder()
+            .schema(schema_with_tokenizer("lowercase"))
+            .settings(settings.clone())
+            .tokenizer("lowercase", SimpleTokenizer.filter(LowerCaser))
+            .open_or_create(directory.clone())
+            .unwrap();
+        assert_eq!(index.settings(), &settings);
+        let title_field = index.schema().get_field("title").unwrap();
+        assert!(index.tokenizer_for_field(title_field).is_ok());
+        // The searchers refer to the tokenizers of the builder too.
+        assert!(index
+            .searcher()
+            .index()
+            .tokenizer_for_field(title_field)
+            .is_ok());
+
+        // Opening the index again keeps its settings.
+        let index = Index::builder()
+            .schema(schema_with_tokenizer("lowercase"))
+            .tokenizer("lowercase", SimpleTokenizer.filter(LowerCaser))
+            .open_or_create(directory.clone())
+            .unwrap();
+        assert_eq!(index.settings(), &settings);
+        assert!(index
+            .searcher()
+            .index()
+            .tokenizer_for_field(title_field)
+            .is_ok());
+        assert!(!directory.exists(Path::new(".tantivy-meta.lock")));
+    }
+
+    #[test]
+    fn test_index_builder_checks_before_writing() {
+        let directory = RAMDirectory::create();
+
+        let err = Index::builder().open_or_create(directory.clone());
+        assert!(match err {
+            Err(TantivyError::InvalidArgument(_)) => true,
+            _ => false,
+        });
+
+        let err = Index::builder()
+            .schema(schema_with_tokenizer("lowercase"))
+            .open_or_create(directory.clone());
+        assert!(match err {
+            Err(TantivyError::SchemaError(msg)) => msg.contains("lowercase"),
+            _ => false,
+        });
+
+        let settings = IndexSettings {
+            docstore_blocksize: 0,
+            ..IndexSettings::default()
+        };
+        let err = Index::builder()
+            .schema(schema_with_tokenizer("default"))
+            .settings(settings)
+            .open_or_create(directory.clone());
+        assert!(match err {
+            Err(TantivyError::InvalidArgument(_)) => true,
+            _ => false,
+        });
+
+        assert!(!directory.exists(&META_FILEPATH));
+    }
+}
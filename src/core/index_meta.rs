@@ -1,3 +1,4 @@
+use core::IndexSettings;
 use core::SegmentMeta;
 use schema::Schema;
 use serde_json;
@@ -10,11 +11,16 @@ use std::fmt;
 /// * the searchable segments,
 /// * the index `docstamp`
 /// * the schema
+/// * the index settings
 ///
 #[derive(Clone, Serialize, Deserialize)]
 pub struct IndexMeta {
     pub segments: Vec<SegmentMeta>,
     pub schema: Schema,
+    /// Indexes created before the settings were persisted
+    /// get the default settings.
+    #[serde(default)]
+    pub settings: IndexSettings,
     pub opstamp: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload: Option<String>,
@@ -22,9 +28,14 @@ pub struct IndexMeta {
 
 impl IndexMeta {
     pub fn with_schema(schema: Schema) -> IndexMeta {
+        IndexMeta::with_schema_and_settings(schema, IndexSettings::default())
+    }
+
+    pub fn with_schema_and_settings(schema: Schema, settings: IndexSettings) -> IndexMeta {
         IndexMeta {
             segments: vec![],
             schema,
+            settings,
             opstamp: 0u64,
             payload: None,
         }
@@ -46,8 +57,10 @@ impl fmt::Debug for IndexMeta {
 mod tests {
 
     use super::IndexMeta;
+    use core::IndexSettings;
     use schema::{SchemaBuilder, TEXT};
     use serde_json;
+    use store::StoreCompressor;
 
     #[test]
     fn test_serialize_metas() {
@@ -59,10 +72,25 @@ mod tests {
         let index_metas = IndexMeta {
             segments: Vec::new(),
             schema,
+            settings: IndexSettings {
+                docstore_compression: StoreCompressor::Snappy,
+                docstore_blocksize: 16_384,
+                ..IndexSettings::default()
+            },
             opstamp: 0u64,
             payload: None,
         };
         let json = serde_json::ser::to_string(&index_metas).expect("serialization failed");
-        assert_eq!(json, r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0}"#);
+        assert_eq!(
+            json,
+            r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"settings":{"docstore_compression":"snappy","docstore_blocksize":16384},"opstamp":0}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize_metas_without_settings() {
+        let json = r#"{"segments":[],"schema":[{"name":"text","type":"text","options":{"indexing":{"record":"position","tokenizer":"default"},"stored":false}}],"opstamp":0}"#;
+        let index_metas: IndexMeta = serde_json::from_str(json).unwrap();
+        assert_eq!(index_metas.settings, IndexSettings::default());
     }
 }
@@ -0,0 +1,114 @@
+use error::TantivyError;
+use schema::Schema;
+use serde_json;
+use std::collections::BTreeMap;
+use store::{StoreCompressor, DEFAULT_STORE_BLOCK_SIZE};
+use Result;
+use Score;
+
+/// Boost of each field, by field name.
+pub type RelevanceProfile = BTreeMap<String, Score>;
+
+/// Settings of an `Index`, persisted in its `meta.json`.
+///
+/// The settings live with the index rather than with the process
+/// opening it, so that all of the readers and the writers of an index
+/// agree on them.
+///
+/// They are chosen when creating the index (see `Index::create_with_settings`),
+/// and can be changed afterwards with `Index::update_settings`, within
+/// the limits documented on each setting.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexSettings {
+    /// Compression codec of the doc store.
+    ///
+    /// The codec cannot be changed once the index is created.
+    #[serde(default)]
+    pub docstore_compression: StoreCompressor,
+    /// Number of bytes of documents accumulated before compressing
+    /// a block of the doc store.
+    ///
+    /// Changing the block size only affects the segments written
+    /// afterwards: the existing blocks are left as they are, including
+    /// when merging segments.
+    #[serde(default = "default_docstore_blocksize")]
+    pub docstore_blocksize: usize,
+    /// Named sets of field boosts, applied at query time by the
+    /// `QueryParser` created with `QueryParser::for_index_with_profile`.
+    ///
+    /// Shipping them with the index ensures that all of the consumers
+    /// of the index rank the documents the same way.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub relevance_profiles: BTreeMap<String, RelevanceProfile>,
+}
+
+fn default_docstore_blocksize() -> usize {
+    DEFAULT_STORE_BLOCK_SIZE
+}
+
+impl Default for IndexSettings {
+    fn default() -> IndexSettings {
+        IndexSettings {
+            docstore_compression: StoreCompressor::default(),
+            docstore_blocksize: DEFAULT_STORE_BLOCK_SIZE,
+            relevance_profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl IndexSettings {
+    /// Checks that this build of tantivy supports the settings,
+    /// and that they are consistent with the schema of the index.
+    ///
+    /// In particular, the relevance profiles may only boost
+    /// fields of the schema, by a positive factor.
+    pub fn validate(&self, schema: &Schema) -> Result<()> {
+        if !self.docstore_compression.is_supported() {
+            return Err(TantivyError::UnsupportedSetting(format!(
+                "The docstore compression codec '{}' is not supported by this build",
+                self.docstore_compression.name()
+            )));
+        }
+        if self.docstore_blocksize == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The docstore block size must be positive".to_string(),
+            ));
+        }
+        for (profile_name, profile) in &self.relevance_profiles {
+            for (field_name, &boost) in profile {
+                if schema.get_field(field_name).is_none() {
+                    return Err(TantivyError::SchemaError(format!(
+                        "The relevance profile '{}' boosts the unknown field '{}'",
+                        profile_name, field_name
+                    )));
+                }
+                if !(boost.is_finite() && boost > 0.0) {
+                    return Err(TantivyError::InvalidArgument(format!(
+                        "The relevance profile '{}' boosts the field '{}' by {}, \
+                         boosts must be positive",
+                        profile_name, field_name, boost
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Checks the codecs named in the JSON representation of the settings,
+    // so that an unknown codec is reported as such rather than as
+    // a corrupted `meta.json`.
+    pub(crate) fn check_json(settings_json: &serde_json::Value) -> Result<()> {
+        if let Some(codec_name) = settings_json
+            .get("docstore_compression")
+            .and_then(|codec_json| codec_json.as_str())
+        {
+            if StoreCompressor::from_name(codec_name).is_none() {
+                return Err(TantivyError::UnsupportedSetting(format!(
+                    "Unknown docstore compression codec '{}'",
+                    codec_name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
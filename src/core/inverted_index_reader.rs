@@ -1,4 +1,3 @@
-use common::BinarySerializable;
 use directory::ReadOnlySource;
 use owned_read::OwnedRead;
 use positions::PositionReader;
@@ -28,6 +27,7 @@ pub struct InvertedIndexReader {
     positions_source: ReadOnlySource,
     positions_idx_source: ReadOnlySource,
     record_option: IndexRecordOption,
+    payload_size: usize,
     total_num_tokens: u64,
 }
 
@@ -42,16 +42,16 @@ impl InvertedIndexReader {
         positions_source: ReadOnlySource,
         positions_idx_source: ReadOnlySource,
         record_option: IndexRecordOption,
+        payload_size: usize,
     ) -> InvertedIndexReader {
-        let total_num_tokens_data = postings_source.slice(0, 8);
-        let mut total_num_tokens_cursor = total_num_tokens_data.as_slice();
-        let total_num_tokens = u64::deserialize(&mut total_num_tokens_cursor).unwrap_or(0u64);
+        let total_num_tokens = postings_source.read_u64_at(0).unwrap_or(0u64);
         InvertedIndexReader {
             termdict,
             postings_source: postings_source.slice_from(8),
             positions_source,
             positions_idx_source,
             record_option,
+            payload_size,
             total_num_tokens,
         }
     }
@@ -68,15 +68,31 @@ impl InvertedIndexReader {
             positions_source: ReadOnlySource::empty(),
             positions_idx_source: ReadOnlySource::empty(),
             record_option,
+            payload_size: field_type.posting_payload_size(),
             total_num_tokens: 0u64,
         }
     }
 
     /// Returns the term info associated with the term.
+    ///
+    /// The `TermInfo` gives access to the document frequency
+    /// of the term without decoding its postings.
     pub fn get_term_info(&self, term: &Term) -> Option<TermInfo> {
         self.termdict.get(term.value_bytes())
     }
 
+    /// Returns the term infos associated with a batch of terms,
+    /// in the same order as `terms`.
+    ///
+    /// The terms are expected to belong to the field of this
+    /// inverted index. The term dictionary is walked once for the whole
+    /// batch, which is faster than calling `.get_term_info(...)` for
+    /// each term.
+    pub fn get_term_infos(&self, terms: &[Term]) -> Vec<Option<TermInfo>> {
+        let keys: Vec<&[u8]> = terms.iter().map(|term| term.value_bytes()).collect();
+        self.termdict.get_many(&keys)
+    }
+
     /// Return the term dictionary datastructure.
     pub fn terms(&self) -> &TermDictionary {
         &self.termdict
@@ -133,6 +149,7 @@ impl InvertedIndexReader {
             OwnedRead::new(postings_data),
             self.record_option,
             requested_option,
+            self.payload_size,
         )
     }
 
@@ -0,0 +1,279 @@
+use collector::Collector;
+use std::time::Duration;
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+use SegmentReader;
+
+/// Numbers describing the search of a single segment.
+#[derive(Clone, Debug)]
+pub struct SegmentSearchMetrics {
+    /// Ordinal of the segment within the `Searcher`.
+    pub segment_ord: SegmentLocalId,
+    /// Number of documents passed to the collector.
+    pub num_docs_collected: u64,
+    /// Time spent creating the scorer and collecting the documents.
+    pub duration: Duration,
+}
+
+/// Numbers describing a search.
+#[derive(Clone, Debug)]
+pub struct SearchMetrics {
+    /// Time spent creating the `Weight` of the query.
+    pub weight_duration: Duration,
+    /// Time spent collecting the documents of all of the segments.
+    pub collection_duration: Duration,
+    /// Number of segments searched.
+    ///
+    /// The segments that were skipped, e.g. because they could not match
+    /// or because the collector was done, are not counted.
+    pub num_segments: usize,
+    /// Number of documents passed to the collector.
+    pub num_docs_collected: u64,
+}
+
+/// Numbers describing the flush of an in-memory segment by
+/// an indexing thread.
+#[derive(Clone, Debug)]
+pub struct FlushMetrics {
+    /// Number of documents in the flushed segment.
+    pub num_docs: u32,
+    /// Time spent indexing the documents and serializing the segment.
+    pub duration: Duration,
+}
+
+/// Numbers describing a merge.
+#[derive(Clone, Debug)]
+pub struct MergeMetrics {
+    /// Number of merged segments.
+    pub num_segments: usize,
+    /// Number of documents in the resulting segment.
+    pub num_docs: u32,
+    /// Number of documents dropped by the `MergeDedupPolicy` of the
+    /// `IndexWriter`, as they were superseded by another document
+    /// with the same key.
+    pub num_collapsed_docs: u32,
+    /// Time spent applying deletes and writing the resulting segment.
+    pub duration: Duration,
+}
+
+/// Hooks called at well-defined points of search and indexing,
+/// for instrumentation purposes.
+///
+/// All of the methods default to doing nothing, so that an implementation
+/// only needs to define the hooks it is interested in.
+/// Hooks are called synchronously, from the searching thread or from
+/// the indexing and merging threads: they should be cheap.
+///
+/// See `Index::set_metrics`.
+pub trait Metrics: Send + Sync {
+    /// Called once the `Weight` of the query has been created.
+    fn on_weight_created(&self, _duration: Duration) {}
+
+    /// Called after the documents of a segment have been collected.
+    fn on_segment_searched(&self, _segment_metrics: &SegmentSearchMetrics) {}
+
+    /// Called at the end of a search.
+    fn on_search(&self, _search_metrics: &SearchMetrics) {}
+
+    /// Called after an indexing thread has flushed a segment.
+    fn on_flush(&self, _flush_metrics: &FlushMetrics) {}
+
+    /// Called after a merge has written the resulting segment.
+    fn on_merge(&self, _merge_metrics: &MergeMetrics) {}
+}
+
+/// `Metrics` implementation ignoring all of the hooks.
+///
+/// This is the default implementation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoMetrics;
+
+impl Metrics for NoMetrics {}
+
+/// Wraps a collector to count the documents it collects.
+pub(crate) struct CountingCollector<'a> {
+    collector: &'a mut Collector,
+    num_docs: u64,
+}
+
+impl<'a> CountingCollector<'a> {
+    pub fn new(collector: &'a mut Collector) -> CountingCollector<'a> {
+        CountingCollector {
+            collector,
+            num_docs: 0u64,
+        }
+    }
+
+    pub fn num_docs(&self) -> u64 {
+        self.num_docs
+    }
+}
+
+impl<'a> Collector for CountingCollector<'a> {
+    fn set_segment(
+        &mut self,
+        segment_local_id: SegmentLocalId,
+        segment: &SegmentReader,
+    ) -> Result<()> {
+        self.collector.set_segment(segment_local_id, segment)
+    }
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.num_docs += 1;
+        self.collector.collect(doc, score);
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn score_threshold(&self) -> Option<Score> {
+        self.collector.score_threshold()
+    }
+
+    fn accepts_blocks(&self) -> bool {
+        self.collector.accepts_blocks()
+    }
+
+    fn collect_block(&mut self, docs: &[DocId]) {
+        self.num_docs += docs.len() as u64;
+        self.collector.collect_block(docs);
+    }
+
+    fn is_done(&self) -> bool {
+        self.collector.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{FlushMetrics, MergeMetrics, Metrics, SearchMetrics, SegmentSearchMetrics};
+    use collector::CountCollector;
+    use futures::Future;
+    use indexer::NoMergePolicy;
+    use query::TermQuery;
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use Index;
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl RecordingMetrics {
+        fn record(&self, event: String) {
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn take_events(&self) -> Vec<String> {
+            let mut events = self.events.lock().unwrap();
+            events.drain(..).collect()
+        }
+    }
+
+    impl Metrics for RecordingMetrics {
+        fn on_weight_created(&self, _duration: Duration) {
+            self.record("weight".to_string());
+        }
+
+        fn on_segment_searched(&self, segment_metrics: &SegmentSearchMetrics) {
+            self.record(format!(
+                "segment {}: {} docs",
+                segment_metrics.segment_ord, segment_metrics.num_docs_collected
+            ));
+        }
+
+        fn on_search(&self, search_metrics: &SearchMetrics) {
+            self.record(format!(
+                "search: {} segments, {} docs",
+                search_metrics.num_segments, search_metrics.num_docs_collected
+            ));
+        }
+
+        fn on_flush(&self, flush_metrics: &FlushMetrics) {
+            self.record(format!("flush: {} docs", flush_metrics.num_docs));
+        }
+
+        fn on_merge(&self, merge_metrics: &MergeMetrics) {
+            self.record(format!(
+                "merge: {} segments, {} docs",
+                merge_metrics.num_segments, merge_metrics.num_docs
+            ));
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let mut index = Index::create_in_ram(schema);
+        let metrics = Arc::new(RecordingMetrics::default());
+        index.set_metrics(metrics.clone());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            assert_eq!(metrics.take_events(), vec!["flush: 3 docs".to_string()]);
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c"))
+                .unwrap();
+            index_writer.commit().unwrap();
+            assert_eq!(metrics.take_events(), vec!["flush: 2 docs".to_string()]);
+        }
+        index.load_searchers().unwrap();
+        {
+            let searcher = index.searcher();
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, "a"),
+                IndexRecordOption::Basic,
+            );
+            searcher
+                .search(&query, &mut CountCollector::default())
+                .unwrap();
+            assert_eq!(
+                metrics.take_events(),
+                vec![
+                    "weight".to_string(),
+                    "segment 0: 2 docs".to_string(),
+                    "segment 1: 2 docs".to_string(),
+                    "search: 2 segments, 4 docs".to_string(),
+                ]
+            );
+            // The first partition only covers the first two docs
+            // of the first segment: the second segment is skipped.
+            searcher
+                .search_partition(&query, &mut CountCollector::default(), (0, 2))
+                .unwrap();
+            assert_eq!(
+                metrics.take_events(),
+                vec![
+                    "weight".to_string(),
+                    "segment 0: 2 docs".to_string(),
+                    "search: 1 segments, 2 docs".to_string(),
+                ]
+            );
+        }
+        {
+            let segment_ids = index.searchable_segment_ids().unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+            index_writer.wait_merging_threads().unwrap();
+            assert_eq!(
+                metrics.take_events(),
+                vec!["merge: 2 segments, 5 docs".to_string()]
+            );
+        }
+    }
+}
@@ -1,6 +1,12 @@
+#[cfg(feature = "async-search")]
+mod async_search;
+mod executor;
 pub mod index;
+mod index_builder;
 mod index_meta;
+mod index_settings;
 mod inverted_index_reader;
+pub(crate) mod metrics;
 mod pool;
 pub mod searcher;
 mod segment;
@@ -8,17 +14,30 @@ mod segment_component;
 mod segment_id;
 mod segment_meta;
 mod segment_reader;
+mod stable_doc_ref;
+mod warm_up;
 
+#[cfg(feature = "async-search")]
+pub use self::async_search::SearchFuture;
+pub(crate) use self::executor::panic_message;
+pub use self::executor::{Executor, ExecutorMetrics, MeteredExecutor, TaskPanic};
 pub use self::index::Index;
+pub use self::index_builder::IndexBuilder;
 pub use self::index_meta::IndexMeta;
+pub use self::index_settings::{IndexSettings, RelevanceProfile};
 pub use self::inverted_index_reader::InvertedIndexReader;
-pub use self::searcher::Searcher;
+pub use self::metrics::{
+    FlushMetrics, MergeMetrics, Metrics, NoMetrics, SearchMetrics, SegmentSearchMetrics,
+};
+pub use self::searcher::{Searcher, TermStatistics};
 pub use self::segment::Segment;
 pub use self::segment::SerializableSegment;
-pub use self::segment_component::SegmentComponent;
+pub use self::segment_component::{SegmentComponent, SegmentComponents};
 pub use self::segment_id::SegmentId;
 pub use self::segment_meta::SegmentMeta;
-pub use self::segment_reader::SegmentReader;
+pub use self::segment_reader::{FastFieldColumn, SegmentReader};
+pub use self::stable_doc_ref::StableDocRef;
+pub use self::warm_up::{ComponentWarmUp, WarmUpReport, WarmUpSet};
 
 use std::path::PathBuf;
 
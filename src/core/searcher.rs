@@ -1,27 +1,54 @@
 use collector::Collector;
+use core::segment_reader::COLUMN_BLOCK_LEN;
 use core::InvertedIndexReader;
 use core::SegmentReader;
-use query::Query;
+use core::StableDocRef;
+use core::{WarmUpReport, WarmUpSet};
+use error::TantivyError;
+use fastfield::{FastFieldNotAvailableError, FastFieldReader};
+use query::{search_with_profile, search_with_weight};
+use query::{Query, SearchProfile};
 use schema::Document;
 use schema::Schema;
-use schema::{Field, Term};
+use schema::{Field, FieldType, Term};
 use space_usage::SearcherSpaceUsage;
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
+use std::time::Instant;
 use termdict::TermMerger;
 use DocAddress;
+use DocId;
 use Index;
 use Result;
+use SegmentLocalId;
+
+/// Statistics of a term, aggregated over all of the segments
+/// of a `Searcher`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TermStatistics {
+    /// Number of documents containing the term.
+    ///
+    /// Like `Searcher::doc_freq`, deleted documents are included in the count.
+    pub doc_freq: u64,
+}
 
 /// Holds a list of `SegmentReader`s ready for search.
 ///
 /// It guarantees that the `Segment` will not be removed before
 /// the destruction of the `Searcher`.
 ///
+/// A `Searcher` is `Send + Sync`: it can be shared between threads,
+/// and cloning it is cheap. The only internally synchronized operation
+/// is document retrieval, which goes through a per-segment lock
+/// protecting the doc store block cache.
+#[derive(Clone)]
 pub struct Searcher {
     schema: Schema,
     index: Index,
     segment_readers: Vec<SegmentReader>,
+    generation: u64,
 }
 
 impl Searcher {
@@ -30,11 +57,13 @@ impl Searcher {
         schema: Schema,
         index: Index,
         segment_readers: Vec<SegmentReader>,
+        generation: u64,
     ) -> Searcher {
         Searcher {
             schema,
             index,
             segment_readers,
+            generation,
         }
     }
 
@@ -53,6 +82,60 @@ impl Searcher {
         segment_reader.doc(doc_id)
     }
 
+    /// Returns the generation of the searcher, that is the opstamp
+    /// of the commit it reflects.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns a reference to the document at `doc_address` that remains
+    /// meaningful across searchers, as long as its segment exists.
+    ///
+    /// See `StableDocRef`.
+    pub fn stable_address(&self, doc_address: DocAddress) -> StableDocRef {
+        let DocAddress(segment_local_id, doc_id) = doc_address;
+        let segment_reader = &self.segment_readers[segment_local_id as usize];
+        StableDocRef {
+            segment_id: segment_reader.segment_id(),
+            doc_id,
+            generation: self.generation,
+        }
+    }
+
+    /// Returns the address of the document referenced by `stable_ref`
+    /// in this searcher.
+    ///
+    /// Returns `None` if the segment of the document is not part of this
+    /// searcher anymore, typically because it was merged, or if the
+    /// document was deleted. The document then has to be searched again.
+    ///
+    /// Returns an `InvalidArgument` error if the document does not exist
+    /// in its segment, which means that the reference is corrupted.
+    pub fn resolve_stable(&self, stable_ref: &StableDocRef) -> Result<Option<DocAddress>> {
+        let segment_ord_opt = self
+            .segment_readers
+            .iter()
+            .position(|segment_reader| segment_reader.segment_id() == stable_ref.segment_id);
+        let segment_ord = match segment_ord_opt {
+            Some(segment_ord) => segment_ord,
+            None => return Ok(None),
+        };
+        let segment_reader = &self.segment_readers[segment_ord];
+        if stable_ref.doc_id >= segment_reader.max_doc() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The stable document reference '{}' points past the end of its segment",
+                stable_ref
+            )));
+        }
+        if segment_reader.is_deleted(stable_ref.doc_id) {
+            return Ok(None);
+        }
+        Ok(Some(DocAddress(
+            segment_ord as SegmentLocalId,
+            stable_ref.doc_id,
+        )))
+    }
+
     /// Access the schema associated to the index of this searcher.
     pub fn schema(&self) -> &Schema {
         &self.schema
@@ -76,6 +159,37 @@ impl Searcher {
             }).sum::<u64>()
     }
 
+    /// Returns the statistics of each of the given terms,
+    /// in the same order as `terms`.
+    ///
+    /// The statistics are read from the term dictionaries, without
+    /// decoding any postings. Within each segment, the terms of a given field
+    /// are looked up in a single pass over the term dictionary.
+    pub fn term_statistics(&self, terms: &[Term]) -> Vec<TermStatistics> {
+        let mut terms_per_field: BTreeMap<Field, (Vec<usize>, Vec<Term>)> = BTreeMap::new();
+        for (term_ord, term) in terms.iter().enumerate() {
+            let (ref mut term_ords, ref mut field_terms) = *terms_per_field
+                .entry(term.field())
+                .or_insert_with(|| (Vec::new(), Vec::new()));
+            term_ords.push(term_ord);
+            field_terms.push(term.clone());
+        }
+        let mut statistics = vec![TermStatistics::default(); terms.len()];
+        for segment_reader in &self.segment_readers {
+            for (&field, &(ref term_ords, ref field_terms)) in &terms_per_field {
+                let term_infos = segment_reader
+                    .inverted_index(field)
+                    .get_term_infos(field_terms);
+                for (&term_ord, term_info_opt) in term_ords.iter().zip(term_infos) {
+                    if let Some(term_info) = term_info_opt {
+                        statistics[term_ord].doc_freq += u64::from(term_info.doc_freq);
+                    }
+                }
+            }
+        }
+        statistics
+    }
+
     /// Return the list of segment readers
     pub fn segment_readers(&self) -> &[SegmentReader] {
         &self.segment_readers
@@ -86,11 +200,140 @@ impl Searcher {
         &self.segment_readers[segment_ord as usize]
     }
 
+    /// Streams the values of the given single-valued fast fields,
+    /// one row per document, to `sink`.
+    ///
+    /// Rows are emitted segment after segment, in doc id order, and
+    /// deleted documents are skipped. The values of a row are in the order
+    /// of `fields`, and the slice passed to `sink` is only valid for the
+    /// duration of the call.
+    ///
+    /// `i64` values are passed as their two's complement bit pattern,
+    /// and can be recovered with `as i64`.
+    ///
+    /// Returns a `FastFieldNotAvailableError` if one of the fields is not
+    /// a `u64` or `i64` single-valued fast field.
+    pub fn export_columns<F>(&self, fields: &[Field], mut sink: F) -> Result<()>
+    where
+        F: FnMut(DocAddress, &[u64]),
+    {
+        let mut blocks: Vec<Vec<u64>> = vec![vec![0u64; COLUMN_BLOCK_LEN]; fields.len()];
+        let mut i64_block: Vec<i64> = vec![0i64; COLUMN_BLOCK_LEN];
+        let mut row: Vec<u64> = vec![0u64; fields.len()];
+        for (segment_ord, segment_reader) in self.segment_readers.iter().enumerate() {
+            let columns = fields
+                .iter()
+                .map(|&field| ColumnReader::open(segment_reader, field))
+                .collect::<Result<Vec<ColumnReader>>>()?;
+            let max_doc = segment_reader.max_doc();
+            let mut block_start = 0u32;
+            while block_start < max_doc {
+                let block_len = min(COLUMN_BLOCK_LEN, (max_doc - block_start) as usize);
+                for (column, block) in columns.iter().zip(blocks.iter_mut()) {
+                    column.read_block(block_start, &mut block[..block_len], &mut i64_block);
+                }
+                for doc_offset in 0..block_len {
+                    let doc = block_start + doc_offset as DocId;
+                    if segment_reader.is_deleted(doc) {
+                        continue;
+                    }
+                    for (val, block) in row.iter_mut().zip(blocks.iter()) {
+                        *val = block[doc_offset];
+                    }
+                    sink(DocAddress(segment_ord as SegmentLocalId, doc), &row[..]);
+                }
+                block_start += block_len as DocId;
+            }
+        }
+        Ok(())
+    }
+
     /// Runs a query on the segment readers wrapped by the searcher
     pub fn search<C: Collector>(&self, query: &Query, collector: &mut C) -> Result<()> {
         query.search(self, collector)
     }
 
+    /// Runs a query like `.search(...)`, and returns statistics
+    /// on its execution.
+    ///
+    /// The profile breaks down the number of documents produced, the
+    /// number of seeks and the time spent by the query and each of its
+    /// subqueries, as well as the time spent in each segment.
+    ///
+    /// The scorers of the query are instrumented, which slows the
+    /// search down: this is meant to debug slow queries, while `.search(...)`
+    /// is left untouched. The counts are exact, but the durations
+    /// include the overhead of the instrumentation.
+    pub fn search_with_profile<C: Collector>(
+        &self,
+        query: &Query,
+        collector: &mut C,
+    ) -> Result<SearchProfile> {
+        search_with_profile(self, query, collector)
+    }
+
+    /// Runs a query on the `partition_ord`-th of `num_partitions` partitions
+    /// of the searcher, given as `(partition_ord, num_partitions)`.
+    ///
+    /// The documents of the searcher, segment after segment, are split into
+    /// `num_partitions` contiguous ranges of doc ids of (almost) the same size.
+    /// Small segments therefore typically belong to a single partition,
+    /// while large segments are split across several partitions.
+    ///
+    /// The partitions only depend on the segments of the searcher,
+    /// so that running all of the partitions, for instance from
+    /// different workers, visits each matching document exactly once.
+    /// The collector is only called for the segments that intersect
+    /// the partition, and the results of the different partitions can
+    /// then be combined by the caller.
+    ///
+    /// Otherwise, the search goes as in `Query::search`: it skips the
+    /// segments that cannot match, honors the score threshold of the collector
+    /// and is reported to the `Metrics` of the index.
+    ///
+    /// # Panics
+    /// Panics if `partition_ord >= num_partitions`.
+    pub fn search_partition<C: Collector>(
+        &self,
+        query: &Query,
+        collector: &mut C,
+        partition: (usize, usize),
+    ) -> Result<()> {
+        let (partition_ord, num_partitions) = partition;
+        assert!(
+            partition_ord < num_partitions,
+            "Partition {} is out of bounds: there are {} partitions.",
+            partition_ord,
+            num_partitions
+        );
+        let total_max_doc: u64 = self
+            .segment_readers
+            .iter()
+            .map(|segment_reader| u64::from(segment_reader.max_doc()))
+            .sum();
+        let partition_start = total_max_doc * partition_ord as u64 / num_partitions as u64;
+        let partition_end = total_max_doc * (partition_ord as u64 + 1) / num_partitions as u64;
+        let mut doc_ranges = Vec::with_capacity(self.segment_readers.len());
+        let mut segment_start = 0u64;
+        for segment_reader in &self.segment_readers {
+            let segment_end = segment_start + u64::from(segment_reader.max_doc());
+            let start = max(partition_start, segment_start);
+            let end = max(start, min(partition_end, segment_end));
+            doc_ranges.push((start - segment_start) as DocId..(end - segment_start) as DocId);
+            segment_start = segment_end;
+        }
+        let start = Instant::now();
+        let weight = query.weight(self, collector.requires_scoring())?;
+        search_with_weight(
+            self,
+            &*weight,
+            start.elapsed(),
+            collector,
+            Some(&doc_ranges[..]),
+        )?;
+        Ok(())
+    }
+
     /// Return the field searcher associated to a `Field`.
     pub fn field(&self, field: Field) -> FieldSearcher {
         let inv_index_readers = self
@@ -109,6 +352,57 @@ impl Searcher {
         }
         space_usage
     }
+
+    /// Reads through the components selected by `warm_up_set`
+    /// for each segment, so that they are in the page cache
+    /// before the first queries.
+    ///
+    /// With an `MmapDirectory`, the first queries after opening an index
+    /// would otherwise have to wait for the pages they hit to be loaded
+    /// from disk. The segments are warmed up on the search executor.
+    ///
+    /// See also `Index::set_warm_up_on_load`.
+    pub fn warm(&self, warm_up_set: WarmUpSet) -> WarmUpReport {
+        let segment_reports = self.index.search_executor().map(
+            move |segment_reader: SegmentReader| segment_reader.warm(warm_up_set),
+            self.segment_readers.clone(),
+        );
+        let mut report = WarmUpReport::default();
+        for segment_report in &segment_reports {
+            report.merge(segment_report);
+        }
+        report
+    }
+}
+
+/// Fast field reader used by `Searcher::export_columns`.
+enum ColumnReader {
+    U64(FastFieldReader<u64>),
+    I64(FastFieldReader<i64>),
+}
+
+impl ColumnReader {
+    fn open(segment_reader: &SegmentReader, field: Field) -> Result<ColumnReader> {
+        let field_entry = segment_reader.schema().get_field_entry(field);
+        match *field_entry.field_type() {
+            FieldType::U64(_) => Ok(ColumnReader::U64(segment_reader.fast_field_reader(field)?)),
+            FieldType::I64(_) => Ok(ColumnReader::I64(segment_reader.fast_field_reader(field)?)),
+            _ => Err(FastFieldNotAvailableError::new(field_entry).into()),
+        }
+    }
+
+    fn read_block(&self, start: DocId, output: &mut [u64], i64_block: &mut [i64]) {
+        match *self {
+            ColumnReader::U64(ref reader) => reader.get_range(start, output),
+            ColumnReader::I64(ref reader) => {
+                let i64_block = &mut i64_block[..output.len()];
+                reader.get_range(start, i64_block);
+                for (out, &val) in output.iter_mut().zip(i64_block.iter()) {
+                    *out = val as u64;
+                }
+            }
+        }
+    }
 }
 
 pub struct FieldSearcher {
@@ -142,3 +436,183 @@ impl fmt::Debug for Searcher {
         write!(f, "Searcher({:?})", segment_ids)
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::TermStatistics;
+    use collector::DiffCollector;
+    use query::QueryParser;
+    use schema::{SchemaBuilder, Term, FAST, INT_INDEXED, TEXT};
+    use std::collections::HashSet;
+    use DocAddress;
+    use Index;
+
+    #[test]
+    fn test_term_statistics() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(title => "a b", body => "a"))
+                .unwrap();
+            index_writer.add_document(doc!(title => "b c")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(title => "a", body => "c"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let terms = vec![
+            Term::from_field_text(title, "b"),
+            Term::from_field_text(body, "a"),
+            Term::from_field_text(title, "a"),
+            Term::from_field_text(title, "d"),
+            Term::from_field_text(body, "c"),
+        ];
+        let doc_freqs: Vec<u64> = searcher
+            .term_statistics(&terms)
+            .into_iter()
+            .map(|term_statistics: TermStatistics| term_statistics.doc_freq)
+            .collect();
+        assert_eq!(doc_freqs, vec![2, 1, 2, 0, 1]);
+        for term in &terms {
+            assert_eq!(
+                searcher.term_statistics(&[term.clone()])[0].doc_freq,
+                searcher.doc_freq(term)
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_partition() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..50 {
+                index_writer
+                    .add_document(doc!(text => format!("a b{}", i % 7)))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            for i in 0..3 {
+                index_writer
+                    .add_document(doc!(text => format!("a b{}", i)))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            for i in 0..20 {
+                index_writer
+                    .add_document(doc!(text => format!("a b{}", i % 5)))
+                    .unwrap();
+            }
+            index_writer
+                .delete_term(Term::from_field_text(text, "b3"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let query_parser = QueryParser::for_index(&index, vec![text]);
+        for query_str in &["a", "b1 b2", "b3", "b6"] {
+            let query = query_parser.parse_query(query_str).unwrap();
+            let mut collector = DiffCollector::new(HashSet::new());
+            searcher.search(&*query, &mut collector).unwrap();
+            let expected = collector.added();
+            for num_partitions in 1..8 {
+                let search_partitions = || {
+                    let mut docs: Vec<DocAddress> = Vec::new();
+                    for partition_ord in 0..num_partitions {
+                        let mut collector = DiffCollector::new(HashSet::new());
+                        searcher
+                            .search_partition(
+                                &*query,
+                                &mut collector,
+                                (partition_ord, num_partitions),
+                            )
+                            .unwrap();
+                        docs.extend(collector.added());
+                    }
+                    docs
+                };
+                let mut docs = search_partitions();
+                assert_eq!(docs, search_partitions());
+                let num_docs = docs.len();
+                docs.sort();
+                docs.dedup();
+                assert_eq!(docs.len(), num_docs);
+                assert_eq!(docs, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_columns() {
+        let mut schema_builder = SchemaBuilder::default();
+        let id = schema_builder.add_u64_field("id", FAST | INT_INDEXED);
+        let price = schema_builder.add_i64_field("price", FAST);
+        let text = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1_500u64 {
+                index_writer
+                    .add_document(doc!(id => i, price => 700i64 - i as i64))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(id => 1_500u64, price => -3i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(id => 1_501u64, price => 4i64))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(id, 1_200u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let mut rows: Vec<(DocAddress, u64, i64)> = Vec::new();
+        searcher
+            .export_columns(&[id, price], |doc_address, row| {
+                assert_eq!(row.len(), 2);
+                rows.push((doc_address, row[0], row[1] as i64));
+            }).unwrap();
+        let mut segment_ords: Vec<u32> = rows
+            .iter()
+            .map(|&(DocAddress(segment_ord, _), _, _)| segment_ord)
+            .collect();
+        segment_ords.dedup();
+        assert_eq!(segment_ords.len(), 2);
+        for &(doc_address, id_val, price_val) in &rows {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord());
+            let id_reader = segment_reader.fast_field_reader::<u64>(id).unwrap();
+            assert_eq!(id_reader.get(doc_address.doc()), id_val);
+            let expected_price = match id_val {
+                1_500 => -3i64,
+                1_501 => 4i64,
+                _ => 700i64 - id_val as i64,
+            };
+            assert_eq!(price_val, expected_price);
+        }
+        let mut ids: Vec<u64> = rows.iter().map(|&(_, id_val, _)| id_val).collect();
+        ids.sort();
+        let expected_ids: Vec<u64> = (0..1_502u64).filter(|&i| i != 1_200).collect();
+        assert_eq!(ids, expected_ids);
+        assert!(searcher.export_columns(&[text], |_, _| {}).is_err());
+    }
+}
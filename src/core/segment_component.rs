@@ -4,7 +4,7 @@ use std::slice;
 /// Each component is stored in its own file,
 /// using the pattern `segment_uuid`.`component_extension`,
 /// except the delete component that takes an `segment_uuid`.`delete_opstamp`.`component_extension`
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SegmentComponent {
     /// Postings (or inverted list). Sorted lists of document ids, associated to terms
     POSTINGS,
@@ -44,3 +44,47 @@ impl SegmentComponent {
         SEGMENT_COMPONENTS.into_iter()
     }
 }
+
+/// Selects which components of a segment should be loaded
+/// when opening a `SegmentReader`.
+///
+/// Skipping components that are not needed avoids opening
+/// (and mmapping) their files.
+/// The delete bitset, if any, is always loaded.
+///
+/// By default, all components are loaded.
+///
+/// ```rust
+/// use tantivy::SegmentComponents;
+///
+/// // Only load the fast fields.
+/// let components = SegmentComponents {
+///     postings: false,
+///     fieldnorms: false,
+///     store: false,
+///     ..Default::default()
+/// };
+/// # assert!(components.fast_fields);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SegmentComponents {
+    /// Term dictionary, postings and positions.
+    pub postings: bool,
+    /// Fast fields.
+    pub fast_fields: bool,
+    /// Field norms.
+    pub fieldnorms: bool,
+    /// Doc store.
+    pub store: bool,
+}
+
+impl Default for SegmentComponents {
+    fn default() -> SegmentComponents {
+        SegmentComponents {
+            postings: true,
+            fast_fields: true,
+            fieldnorms: true,
+            store: true,
+        }
+    }
+}
@@ -59,6 +59,11 @@ impl SegmentId {
     pub fn uuid_string(&self) -> String {
         self.0.to_simple_ref().to_string()
     }
+
+    /// Parses a segment uuid string, as returned by `uuid_string`.
+    pub(crate) fn from_uuid_string(uuid_string: &str) -> Option<SegmentId> {
+        Uuid::parse_str(uuid_string).ok().map(SegmentId)
+    }
 }
 
 impl fmt::Debug for SegmentId {
@@ -2,7 +2,7 @@ use super::SegmentComponent;
 use census::{Inventory, TrackedObject};
 use core::SegmentId;
 use serde;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::path::PathBuf;
 
@@ -71,6 +71,8 @@ impl SegmentMeta {
             segment_id,
             max_doc,
             deletes: None,
+            user_metadata: BTreeMap::new(),
+            num_collapsed_docs: 0,
         };
         SegmentMeta {
             tracked: INVENTORY.track(inner),
@@ -98,9 +100,20 @@ impl SegmentMeta {
     /// is by removing all files that have been created by tantivy
     /// and are not used by any segment anymore.
     pub fn list_files(&self) -> HashSet<PathBuf> {
-        SegmentComponent::iterator()
+        let mut files = SegmentComponent::iterator()
             .map(|component| self.relative_path(*component))
-            .collect::<HashSet<PathBuf>>()
+            .collect::<HashSet<PathBuf>>();
+        files.insert(self.info_path());
+        files
+    }
+
+    /// Returns the relative path of the file describing the commit
+    /// in which the segment first appeared.
+    ///
+    /// This file is only read to rebuild a lost `meta.json`,
+    /// see `Index::recover`.
+    pub(crate) fn info_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.info", self.id().uuid_string()))
     }
 
     /// Returns the relative path of a component of our segment.
@@ -161,9 +174,54 @@ impl SegmentMeta {
             segment_id: inner_meta.segment_id,
             max_doc: inner_meta.max_doc,
             deletes: Some(delete_meta),
+            user_metadata: inner_meta.user_metadata.clone(),
+            num_collapsed_docs: inner_meta.num_collapsed_docs,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Returns the user-defined metadata of the segment.
+    ///
+    /// See `SegmentMetadataBuilder` and `MergeMetadataCombiner`.
+    pub fn user_metadata(&self) -> &BTreeMap<String, String> {
+        &self.tracked.user_metadata
+    }
+
+    #[doc(hidden)]
+    pub fn with_user_metadata(self, user_metadata: BTreeMap<String, String>) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            user_metadata,
+            num_collapsed_docs: inner_meta.num_collapsed_docs,
         });
         SegmentMeta { tracked }
     }
+
+    /// Returns the number of documents dropped by the `MergeDedupPolicy`
+    /// of the merge that produced this segment.
+    ///
+    /// This is 0 for segments that were not produced by a merge.
+    pub fn num_collapsed_docs(&self) -> u32 {
+        self.tracked.num_collapsed_docs
+    }
+
+    #[doc(hidden)]
+    pub fn with_num_collapsed_docs(self, num_collapsed_docs: u32) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            user_metadata: inner_meta.user_metadata.clone(),
+            num_collapsed_docs,
+        });
+        SegmentMeta { tracked }
+    }
+}
+
+fn is_zero(val: &u32) -> bool {
+    *val == 0
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -171,4 +229,8 @@ struct InnerSegmentMeta {
     segment_id: SegmentId,
     max_doc: u32,
     deletes: Option<DeleteMeta>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    user_metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    num_collapsed_docs: u32,
 }
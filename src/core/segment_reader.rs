@@ -1,14 +1,19 @@
+use bit_set::BitSet;
 use common::CompositeFile;
 use common::HasLen;
+use core::warm_up::{warm_composite_files, warm_sources};
 use core::InvertedIndexReader;
 use core::Segment;
 use core::SegmentComponent;
+use core::SegmentComponents;
 use core::SegmentId;
+use core::{WarmUpReport, WarmUpSet};
+use directory::ReadOnlySource;
 use error::TantivyError;
-use fastfield::DeleteBitSet;
 use fastfield::FacetReader;
 use fastfield::FastFieldReader;
 use fastfield::{self, FastFieldNotAvailableError};
+use fastfield::{write_delete_bitset, DeleteBitSet};
 use fastfield::{BytesFastFieldReader, FastValue, MultiValueIntFastFieldReader};
 use fieldnorm::FieldNormReader;
 use schema::Cardinality;
@@ -17,15 +22,55 @@ use schema::Field;
 use schema::FieldType;
 use schema::Schema;
 use space_usage::SegmentSpaceUsage;
+use space_usage::StoreSpaceUsage;
+use std::cmp;
 use std::collections::HashMap;
 use std::fmt;
+use std::io;
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{PoisonError, RwLock};
 use store::StoreReader;
 use termdict::TermDictionary;
 use DocId;
 use Result;
 
+/// Cache of the readers of a `SegmentReader`, opened on first access.
+///
+/// Segments are immutable, so that the cached readers never
+/// need to be invalidated.
+#[derive(Clone)]
+struct ReaderCache<T: Clone>(Arc<RwLock<HashMap<Field, T>>>);
+
+impl<T: Clone> ReaderCache<T> {
+    fn new() -> ReaderCache<T> {
+        ReaderCache(Arc::new(RwLock::new(HashMap::new())))
+    }
+
+    fn get_or_open<E, F>(&self, field: Field, open: F) -> ::std::result::Result<T, E>
+    where
+        F: FnOnce() -> ::std::result::Result<T, E>,
+    {
+        // The map is only ever updated by a single insertion,
+        // so that it is safe to ignore poisoning.
+        if let Some(reader) = self
+            .0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&field)
+        {
+            return Ok(reader.clone());
+        }
+        // by releasing the lock in between, we may end up opening the reader
+        // twice, but this is fine.
+        let reader = open()?;
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(field, reader.clone());
+        Ok(reader)
+    }
+}
+
 /// Entry point to access all of the datastructures of the `Segment`
 ///
 /// - term dictionary
@@ -37,11 +82,18 @@ use Result;
 /// The segment reader has a very low memory footprint,
 /// as close to all of the memory data is mmapped.
 ///
+/// The inverted index, fast field and field norm readers are
+/// cached: opening them again is cheap, and returns
+/// a clone of the same reader.
+///
 ///
 /// TODO fix not decoding docfreq
 #[derive(Clone)]
 pub struct SegmentReader {
     inv_idx_reader_cache: Arc<RwLock<HashMap<Field, Arc<InvertedIndexReader>>>>,
+    fast_field_reader_cache: ReaderCache<FastFieldReader<u64>>,
+    bytes_fast_field_reader_cache: ReaderCache<BytesFastFieldReader>,
+    fieldnorm_reader_cache: ReaderCache<FieldNormReader>,
 
     segment_id: SegmentId,
     max_doc: DocId,
@@ -54,7 +106,8 @@ pub struct SegmentReader {
     fast_fields_composite: CompositeFile,
     fieldnorms_composite: CompositeFile,
 
-    store_reader: StoreReader,
+    loaded_components: SegmentComponents,
+    store_reader: Option<StoreReader>,
     delete_bitset_opt: Option<DeleteBitSet>,
     schema: Schema,
 }
@@ -101,23 +154,39 @@ impl SegmentReader {
     /// is a u64 field indexed as "fast".
     ///
     /// Return a FastFieldNotAvailableError if the field is not
-    /// declared as a fast field in the schema.
+    /// declared as a fast field in the schema, and a `ComponentNotLoaded`
+    /// error if the fast fields of the segment were not loaded.
     ///
     /// # Panics
     /// May panic if the index is corrupted.
     pub fn fast_field_reader<Item: FastValue>(
         &self,
         field: Field,
-    ) -> fastfield::Result<FastFieldReader<Item>> {
+    ) -> Result<FastFieldReader<Item>> {
         let field_entry = self.schema.get_field_entry(field);
-        if Item::fast_field_cardinality(field_entry.field_type()) == Some(Cardinality::SingleValue)
+        if Item::fast_field_cardinality(field_entry.field_type()) != Some(Cardinality::SingleValue)
         {
+            return Err(FastFieldNotAvailableError::new(field_entry).into());
+        }
+        self.check_fast_fields_loaded()?;
+        // The readers are cached untyped: the type of the values of a field
+        // is given by the schema.
+        let reader = self.fast_field_reader_cache.get_or_open(field, || {
             self.fast_fields_composite
                 .open_read(field)
                 .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))
-                .map(FastFieldReader::open)
+                .map(FastFieldReader::<u64>::open)
+        })?;
+        Ok(reader.cast())
+    }
+
+    fn check_fast_fields_loaded(&self) -> Result<()> {
+        if self.loaded_components.fast_fields {
+            Ok(())
         } else {
-            Err(FastFieldNotAvailableError::new(field_entry))
+            Err(TantivyError::ComponentNotLoaded(
+                SegmentComponent::FASTFIELDS,
+            ))
         }
     }
 
@@ -134,40 +203,67 @@ impl SegmentReader {
         }
     }
 
+    /// Returns an iterator over the `(DocId, value)` pairs of a single-valued
+    /// fast field, in doc id order.
+    ///
+    /// Deleted documents are skipped. Values are decoded block by block,
+    /// which is much faster than calling `FastFieldReader::get` for each document.
+    ///
+    /// Return a FastFieldNotAvailableError if the field is not
+    /// declared as a fast field in the schema, and a `ComponentNotLoaded`
+    /// error if the fast fields of the segment were not loaded.
+    pub fn fast_field_column<Item: FastValue>(
+        &self,
+        field: Field,
+    ) -> Result<FastFieldColumn<Item>> {
+        let reader = self.fast_field_reader(field)?;
+        Ok(FastFieldColumn {
+            reader,
+            delete_bitset: self.delete_bitset(),
+            max_doc: self.max_doc(),
+            block_start: 0,
+            block: Vec::with_capacity(COLUMN_BLOCK_LEN),
+            cursor: 0,
+        })
+    }
+
     /// Accessor to the `MultiValueIntFastFieldReader` associated to a given `Field`.
     /// May panick if the field is not a multivalued fastfield of the type `Item`.
     pub fn multi_fast_field_reader<Item: FastValue>(
         &self,
         field: Field,
-    ) -> fastfield::Result<MultiValueIntFastFieldReader<Item>> {
+    ) -> Result<MultiValueIntFastFieldReader<Item>> {
         let field_entry = self.schema.get_field_entry(field);
-        if Item::fast_field_cardinality(field_entry.field_type()) == Some(Cardinality::MultiValues)
+        if Item::fast_field_cardinality(field_entry.field_type()) != Some(Cardinality::MultiValues)
         {
-            let idx_reader = self.fast_field_reader_with_idx(field, 0)?;
-            let vals_reader = self.fast_field_reader_with_idx(field, 1)?;
-            Ok(MultiValueIntFastFieldReader::open(idx_reader, vals_reader))
-        } else {
-            Err(FastFieldNotAvailableError::new(field_entry))
+            return Err(FastFieldNotAvailableError::new(field_entry).into());
         }
+        self.check_fast_fields_loaded()?;
+        let idx_reader = self.fast_field_reader_with_idx(field, 0)?;
+        let vals_reader = self.fast_field_reader_with_idx(field, 1)?;
+        Ok(MultiValueIntFastFieldReader::open(idx_reader, vals_reader))
     }
 
     /// Accessor to the `BytesFastFieldReader` associated to a given `Field`.
-    pub fn bytes_fast_field_reader(&self, field: Field) -> fastfield::Result<BytesFastFieldReader> {
+    pub fn bytes_fast_field_reader(&self, field: Field) -> Result<BytesFastFieldReader> {
         let field_entry = self.schema.get_field_entry(field);
         match *field_entry.field_type() {
-            FieldType::Bytes => {}
-            _ => return Err(FastFieldNotAvailableError::new(field_entry)),
+            FieldType::Bytes(ref bytes_options) if bytes_options.is_fast() => {}
+            _ => return Err(FastFieldNotAvailableError::new(field_entry).into()),
         }
-        let idx_reader = self
-            .fast_fields_composite
-            .open_read_with_idx(field, 0)
-            .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))
-            .map(FastFieldReader::open)?;
-        let values = self
-            .fast_fields_composite
-            .open_read_with_idx(field, 1)
-            .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
-        Ok(BytesFastFieldReader::open(idx_reader, values))
+        self.check_fast_fields_loaded()?;
+        self.bytes_fast_field_reader_cache.get_or_open(field, || {
+            let idx_reader = self
+                .fast_fields_composite
+                .open_read_with_idx(field, 0)
+                .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))
+                .map(FastFieldReader::open)?;
+            let values = self
+                .fast_fields_composite
+                .open_read_with_idx(field, 1)
+                .ok_or_else(|| FastFieldNotAvailableError::new(field_entry))?;
+            Ok(BytesFastFieldReader::open(idx_reader, values))
+        })
     }
 
     /// Accessor to the `FacetReader` associated to a given `Field`.
@@ -180,6 +276,10 @@ impl SegmentReader {
                 field_entry
             )));
         }
+        self.check_fast_fields_loaded()?;
+        if !self.loaded_components.postings {
+            return Err(TantivyError::ComponentNotLoaded(SegmentComponent::TERMS));
+        }
         let term_ords_reader = self.multi_fast_field_reader(field)?;
         let termdict_source = self.termdict_composite.open_read(field).ok_or_else(|| {
             TantivyError::InvalidArgument(format!(
@@ -202,9 +302,21 @@ impl SegmentReader {
     ///
     /// They are simply stored as a fast field, serialized in
     /// the `.fieldnorm` file of the segment.
+    ///
+    /// # Panics
+    /// Panics if the field norms were not loaded, see `try_fieldnorms_reader`.
     pub fn get_fieldnorms_reader(&self, field: Field) -> FieldNormReader {
-        if let Some(fieldnorm_source) = self.fieldnorms_composite.open_read(field) {
-            FieldNormReader::open(fieldnorm_source)
+        if !self.loaded_components.fieldnorms {
+            panic!("The field norms of this segment were not loaded.");
+        }
+        let fieldnorm_reader_res = self.fieldnorm_reader_cache.get_or_open(field, || {
+            self.fieldnorms_composite
+                .open_read(field)
+                .map(FieldNormReader::open)
+                .ok_or(())
+        });
+        if let Ok(fieldnorm_reader) = fieldnorm_reader_res {
+            fieldnorm_reader
         } else {
             let field_name = self.schema.get_field_name(field);
             let err_msg = format!(
@@ -215,26 +327,78 @@ impl SegmentReader {
         }
     }
 
+    /// Same as `get_fieldnorms_reader`, except that a `ComponentNotLoaded`
+    /// error is returned if the field norms of the segment were not loaded.
+    pub fn try_fieldnorms_reader(&self, field: Field) -> Result<FieldNormReader> {
+        if !self.loaded_components.fieldnorms {
+            return Err(TantivyError::ComponentNotLoaded(
+                SegmentComponent::FIELDNORMS,
+            ));
+        }
+        Ok(self.get_fieldnorms_reader(field))
+    }
+
     /// Accessor to the segment's `StoreReader`.
+    ///
+    /// # Panics
+    /// Panics if the store was not loaded.
+    /// (See `SegmentReader::open_with_components`)
     pub fn get_store_reader(&self) -> &StoreReader {
-        &self.store_reader
+        self.store_reader
+            .as_ref()
+            .expect("The store of this segment was not loaded.")
+    }
+
+    /// Returns the components that were loaded when opening this segment.
+    pub fn loaded_components(&self) -> SegmentComponents {
+        self.loaded_components
     }
 
     /// Open a new segment for reading.
     pub fn open(segment: &Segment) -> Result<SegmentReader> {
-        let termdict_source = segment.open_read(SegmentComponent::TERMS)?;
-        let termdict_composite = CompositeFile::open(&termdict_source)?;
+        SegmentReader::open_with_components(segment, SegmentComponents::default())
+    }
 
-        let store_source = segment.open_read(SegmentComponent::STORE)?;
-        let store_reader = StoreReader::from_source(store_source);
+    /// Open a new segment for reading, only loading the given components.
+    ///
+    /// The files of the components that are not loaded are never opened.
+    /// Accessing them afterwards returns a `TantivyError::ComponentNotLoaded`
+    /// error, and in particular, searching a query that requires them fails
+    /// with this error. The accessors that do not return a `Result`
+    /// (`inverted_index`, `get_fieldnorms_reader`) panic instead: their
+    /// `try_` counterparts return the error.
+    pub fn open_with_components(
+        segment: &Segment,
+        components: SegmentComponents,
+    ) -> Result<SegmentReader> {
+        let termdict_composite = if components.postings {
+            let termdict_source = segment.open_read(SegmentComponent::TERMS)?;
+            CompositeFile::open(&termdict_source)?
+        } else {
+            CompositeFile::empty()
+        };
+
+        let store_reader = if components.store {
+            let store_source = segment.open_read(SegmentComponent::STORE)?;
+            let compressor = segment.index().settings().docstore_compression;
+            Some(StoreReader::from_source(store_source, compressor))
+        } else {
+            None
+        };
 
         fail_point!("SegmentReader::open#middle");
 
-        let postings_source = segment.open_read(SegmentComponent::POSTINGS)?;
-        let postings_composite = CompositeFile::open(&postings_source)?;
+        let postings_composite = if components.postings {
+            let postings_source = segment.open_read(SegmentComponent::POSTINGS)?;
+            CompositeFile::open(&postings_source)?
+        } else {
+            CompositeFile::empty()
+        };
 
         let positions_composite = {
-            if let Ok(source) = segment.open_read(SegmentComponent::POSITIONS) {
+            if !components.postings {
+                CompositeFile::empty()
+            } else if let Ok(source) = segment.open_read(SegmentComponent::POSITIONS) {
                 CompositeFile::open(&source)?
             } else {
                 CompositeFile::empty()
@@ -242,18 +406,28 @@ impl SegmentReader {
         };
 
         let positions_idx_composite = {
-            if let Ok(source) = segment.open_read(SegmentComponent::POSITIONSSKIP) {
+            if !components.postings {
+                CompositeFile::empty()
+            } else if let Ok(source) = segment.open_read(SegmentComponent::POSITIONSSKIP) {
                 CompositeFile::open(&source)?
             } else {
                 CompositeFile::empty()
             }
         };
 
-        let fast_fields_data = segment.open_read(SegmentComponent::FASTFIELDS)?;
-        let fast_fields_composite = CompositeFile::open(&fast_fields_data)?;
+        let fast_fields_composite = if components.fast_fields {
+            let fast_fields_data = segment.open_read(SegmentComponent::FASTFIELDS)?;
+            CompositeFile::open(&fast_fields_data)?
+        } else {
+            CompositeFile::empty()
+        };
 
-        let fieldnorms_data = segment.open_read(SegmentComponent::FIELDNORMS)?;
-        let fieldnorms_composite = CompositeFile::open(&fieldnorms_data)?;
+        let fieldnorms_composite = if components.fieldnorms {
+            let fieldnorms_data = segment.open_read(SegmentComponent::FIELDNORMS)?;
+            CompositeFile::open(&fieldnorms_data)?
+        } else {
+            CompositeFile::empty()
+        };
 
         let delete_bitset_opt = if segment.meta().has_deletes() {
             let delete_data = segment.open_read(SegmentComponent::DELETE)?;
@@ -265,6 +439,9 @@ impl SegmentReader {
         let schema = segment.schema();
         Ok(SegmentReader {
             inv_idx_reader_cache: Arc::new(RwLock::new(HashMap::new())),
+            fast_field_reader_cache: ReaderCache::new(),
+            bytes_fast_field_reader_cache: ReaderCache::new(),
+            fieldnorm_reader_cache: ReaderCache::new(),
             max_doc: segment.meta().max_doc(),
             num_docs: segment.meta().num_docs(),
             termdict_composite,
@@ -272,6 +449,7 @@ impl SegmentReader {
             fast_fields_composite,
             fieldnorms_composite,
             segment_id: segment.id(),
+            loaded_components: components,
             store_reader,
             delete_bitset_opt,
             positions_composite,
@@ -287,6 +465,9 @@ impl SegmentReader {
     /// The field reader is in charge of iterating through the
     /// term dictionary associated to a specific field,
     /// and opening the posting list associated to any term.
+    ///
+    /// # Panics
+    /// Panics if the postings were not loaded, see `try_inverted_index`.
     pub fn inverted_index(&self, field: Field) -> Arc<InvertedIndexReader> {
         if let Some(inv_idx_reader) = self
             .inv_idx_reader_cache
@@ -296,6 +477,9 @@ impl SegmentReader {
         {
             return Arc::clone(inv_idx_reader);
         }
+        if !self.loaded_components.postings {
+            panic!("The postings of this segment were not loaded.");
+        }
         let field_entry = self.schema.get_field_entry(field);
         let field_type = field_entry.field_type();
         let record_option_opt = field_type.get_index_record_option();
@@ -339,6 +523,7 @@ impl SegmentReader {
             positions_source,
             positions_idx_source,
             record_option,
+            field_type.posting_payload_size(),
         ));
 
         // by releasing the lock in between, we may end up opening the inverting index
@@ -351,12 +536,24 @@ impl SegmentReader {
         inv_idx_reader
     }
 
+    /// Same as `inverted_index`, except that a `ComponentNotLoaded`
+    /// error is returned if the postings of the segment were not loaded.
+    pub fn try_inverted_index(&self, field: Field) -> Result<Arc<InvertedIndexReader>> {
+        if !self.loaded_components.postings {
+            return Err(TantivyError::ComponentNotLoaded(SegmentComponent::POSTINGS));
+        }
+        Ok(self.inverted_index(field))
+    }
+
     /// Returns the document (or to be accurate, its stored field)
     /// bearing the given doc id.
     /// This method is slow and should seldom be called from
     /// within a collector.
     pub fn doc(&self, doc_id: DocId) -> Result<Document> {
-        self.store_reader.get(doc_id)
+        self.store_reader
+            .as_ref()
+            .ok_or(TantivyError::ComponentNotLoaded(SegmentComponent::STORE))?
+            .get(doc_id)
     }
 
     /// Returns the segment id
@@ -378,6 +575,30 @@ impl SegmentReader {
             .unwrap_or(false)
     }
 
+    /// Returns a reader of the same segment, on which the given documents
+    /// are deleted on top of the already deleted documents.
+    ///
+    /// The additional deletes only live in memory: the segment
+    /// itself is left untouched.
+    pub(crate) fn with_deleted_docs(&self, docs: &[DocId]) -> io::Result<SegmentReader> {
+        let mut delete_bitset = BitSet::with_capacity(self.max_doc as usize);
+        for doc in 0..self.max_doc {
+            if self.is_deleted(doc) {
+                delete_bitset.insert(doc as usize);
+            }
+        }
+        for &doc in docs {
+            delete_bitset.insert(doc as usize);
+        }
+        let mut delete_data = Vec::new();
+        write_delete_bitset(&delete_bitset, &mut delete_data)?;
+        let mut segment_reader = self.clone();
+        segment_reader.num_docs = self.max_doc - delete_bitset.len() as DocId;
+        segment_reader.delete_bitset_opt =
+            Some(DeleteBitSet::open(ReadOnlySource::from(delete_data)));
+        Ok(segment_reader)
+    }
+
     /// Returns an iterator that will iterate over the alive document ids
     pub fn doc_ids_alive(&self) -> SegmentReaderAliveDocsIterator {
         SegmentReaderAliveDocsIterator::new(&self)
@@ -393,10 +614,41 @@ impl SegmentReader {
             self.positions_idx_composite.space_usage(),
             self.fast_fields_composite.space_usage(),
             self.fieldnorms_composite.space_usage(),
-            self.store_reader.space_usage(),
+            self.store_reader
+                .as_ref()
+                .map(|store_reader| store_reader.space_usage())
+                .unwrap_or_else(|| StoreSpaceUsage::new(0, 0)),
             self.delete_bitset_opt.as_ref().map(|x| x.space_usage()).unwrap_or(0),
         )
     }
+
+    /// Reads through the components of the segment selected
+    /// by `warm_up_set`, so that they are loaded in the page cache.
+    ///
+    /// Components that were not loaded when opening the segment
+    /// are skipped. See `Searcher::warm`.
+    pub fn warm(&self, warm_up_set: WarmUpSet) -> WarmUpReport {
+        let mut report = WarmUpReport::default();
+        if warm_up_set.term_dictionaries {
+            report.term_dictionaries = warm_composite_files(&[&self.termdict_composite]);
+        }
+        if warm_up_set.fast_fields {
+            report.fast_fields = warm_composite_files(&[&self.fast_fields_composite]);
+        }
+        if warm_up_set.fieldnorms {
+            report.fieldnorms = warm_composite_files(&[&self.fieldnorms_composite]);
+        }
+        if warm_up_set.positions {
+            report.positions =
+                warm_composite_files(&[&self.positions_composite, &self.positions_idx_composite]);
+        }
+        if warm_up_set.store {
+            if let Some(ref store_reader) = self.store_reader {
+                report.store = warm_sources(store_reader.sources().iter().cloned());
+            }
+        }
+        report
+    }
 }
 
 impl fmt::Debug for SegmentReader {
@@ -451,11 +703,184 @@ impl<'a> Iterator for SegmentReaderAliveDocsIterator<'a> {
     }
 }
 
+/// Number of fast field values decoded at once when iterating over a column.
+pub(crate) const COLUMN_BLOCK_LEN: usize = 1_024;
+
+/// Iterator over the `(DocId, value)` pairs of a fast field
+/// of a segment, skipping deleted documents.
+///
+/// See `SegmentReader::fast_field_column`.
+pub struct FastFieldColumn<'a, Item: FastValue> {
+    reader: FastFieldReader<Item>,
+    delete_bitset: Option<&'a DeleteBitSet>,
+    max_doc: DocId,
+    block_start: DocId,
+    block: Vec<Item>,
+    cursor: usize,
+}
+
+impl<'a, Item: FastValue> Iterator for FastFieldColumn<'a, Item> {
+    type Item = (DocId, Item);
+
+    fn next(&mut self) -> Option<(DocId, Item)> {
+        loop {
+            if self.cursor == self.block.len() {
+                let next_block_start = self.block_start + self.block.len() as DocId;
+                if next_block_start >= self.max_doc {
+                    return None;
+                }
+                let block_len =
+                    cmp::min(COLUMN_BLOCK_LEN, (self.max_doc - next_block_start) as usize);
+                self.block.resize(block_len, Item::default());
+                self.reader.get_range(next_block_start, &mut self.block[..]);
+                self.block_start = next_block_start;
+                self.cursor = 0;
+            }
+            let doc = self.block_start + self.cursor as DocId;
+            let val = self.block[self.cursor];
+            self.cursor += 1;
+            let is_deleted = self
+                .delete_bitset
+                .map(|delete_bitset| delete_bitset.is_deleted(doc))
+                .unwrap_or(false);
+            if !is_deleted {
+                return Some((doc, val));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use collector::CountCollector;
     use core::Index;
-    use schema::{SchemaBuilder, Term, STORED, TEXT};
+    use core::SegmentComponents;
+    use core::SegmentReader;
+    use directory::error::{DeleteError, OpenReadError, OpenWriteError};
+    use directory::{Directory, RAMDirectory, ReadOnlySource, WritePtr};
+    use query::TermQuery;
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, INT_INDEXED, STORED, TEXT};
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, RwLock};
     use DocId;
+    use TantivyError;
+
+    /// Directory recording all of the paths opened via `open_read`.
+    #[derive(Clone, Debug)]
+    struct OpenReadRecordingDirectory {
+        underlying: RAMDirectory,
+        opened_paths: Arc<RwLock<Vec<PathBuf>>>,
+    }
+
+    impl OpenReadRecordingDirectory {
+        fn new() -> OpenReadRecordingDirectory {
+            OpenReadRecordingDirectory {
+                underlying: RAMDirectory::create(),
+                opened_paths: Arc::default(),
+            }
+        }
+
+        fn take_opened_paths(&self) -> Vec<PathBuf> {
+            let mut opened_paths = self.opened_paths.write().unwrap();
+            opened_paths.drain(..).collect()
+        }
+    }
+
+    impl Directory for OpenReadRecordingDirectory {
+        fn open_read(&self, path: &Path) -> Result<ReadOnlySource, OpenReadError> {
+            self.opened_paths.write().unwrap().push(path.to_owned());
+            self.underlying.open_read(path)
+        }
+
+        fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+            self.underlying.delete(path)
+        }
+
+        fn exists(&self, path: &Path) -> bool {
+            self.underlying.exists(path)
+        }
+
+        fn open_write(&mut self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+            self.underlying.open_write(path)
+        }
+
+        fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+            self.underlying.atomic_read(path)
+        }
+
+        fn atomic_write(&mut self, path: &Path, data: &[u8]) -> io::Result<()> {
+            self.underlying.atomic_write(path, data)
+        }
+    }
+
+    fn is_store_path(path: &Path) -> bool {
+        path.extension().map(|ext| ext == "store").unwrap_or(false)
+    }
+
+    #[test]
+    fn test_open_with_components() {
+        let mut schema_builder = SchemaBuilder::new();
+        let name = schema_builder.add_text_field("name", TEXT | STORED);
+        let val = schema_builder.add_u64_field("val", FAST);
+        let schema = schema_builder.build();
+        let directory = OpenReadRecordingDirectory::new();
+        let index = Index::create(directory.clone(), schema).unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(name => "tantivy", val => 3u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(name => "horse", val => 7u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        assert!(directory.take_opened_paths().iter().any(|path| is_store_path(path)));
+
+        let fast_field_only = SegmentComponents {
+            postings: false,
+            fieldnorms: false,
+            store: false,
+            ..Default::default()
+        };
+        let searcher = index.searcher_with_components(fast_field_only).unwrap();
+        let opened_paths = directory.take_opened_paths();
+        assert!(!opened_paths.is_empty());
+        assert!(!opened_paths.iter().any(|path| is_store_path(path)));
+
+        let segment_reader = searcher.segment_reader(0);
+        assert_eq!(segment_reader.loaded_components(), fast_field_only);
+        let fast_field_reader = segment_reader.fast_field_reader::<u64>(val).unwrap();
+        assert_eq!(fast_field_reader.get(1), 7u64);
+        match segment_reader.doc(0) {
+            Err(TantivyError::ComponentNotLoaded(_)) => {}
+            _ => panic!("Expected a ComponentNotLoaded error"),
+        }
+        let term_query = TermQuery::new(
+            Term::from_field_text(name, "horse"),
+            IndexRecordOption::Basic,
+        );
+        match searcher.search(&term_query, &mut CountCollector::default()) {
+            Err(TantivyError::ComponentNotLoaded(_)) => {}
+            res => panic!("Expected a ComponentNotLoaded error, got {:?}", res),
+        }
+
+        let without_fast_fields = SegmentComponents {
+            fast_fields: false,
+            ..Default::default()
+        };
+        let searcher = index.searcher_with_components(without_fast_fields).unwrap();
+        let segment_reader = searcher.segment_reader(0);
+        match segment_reader.fast_field_reader::<u64>(val) {
+            Err(TantivyError::ComponentNotLoaded(_)) => {}
+            _ => panic!("Expected a ComponentNotLoaded error"),
+        }
+        let mut count_collector = CountCollector::default();
+        searcher.search(&term_query, &mut count_collector).unwrap();
+        assert_eq!(count_collector.count(), 1);
+    }
 
     #[test]
     fn test_alive_docs_iterator() {
@@ -467,10 +892,10 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => "tantivy"));
-            index_writer.add_document(doc!(name => "horse"));
-            index_writer.add_document(doc!(name => "jockey"));
-            index_writer.add_document(doc!(name => "cap"));
+            index_writer.add_document(doc!(name => "tantivy")).unwrap();
+            index_writer.add_document(doc!(name => "horse")).unwrap();
+            index_writer.add_document(doc!(name => "jockey")).unwrap();
+            index_writer.add_document(doc!(name => "cap")).unwrap();
 
             // we should now have one segment with two docs
             index_writer.commit().unwrap();
@@ -478,8 +903,12 @@ mod test {
 
         {
             let mut index_writer2 = index.writer(50_000_000).unwrap();
-            index_writer2.delete_term(Term::from_field_text(name, "horse"));
-            index_writer2.delete_term(Term::from_field_text(name, "cap"));
+            index_writer2
+                .delete_term(Term::from_field_text(name, "horse"))
+                .unwrap();
+            index_writer2
+                .delete_term(Term::from_field_text(name, "cap"))
+                .unwrap();
 
             // ok, now we should have a deleted doc
             index_writer2.commit().unwrap();
@@ -490,4 +919,125 @@ mod test {
         let docs: Vec<DocId> = searcher.segment_reader(0).doc_ids_alive().collect();
         assert_eq!(vec![0u32, 2u32], docs);
     }
+
+    #[test]
+    fn test_fast_field_column() {
+        let mut schema_builder = SchemaBuilder::new();
+        let val = schema_builder.add_u64_field("val", FAST | INT_INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..2_500u64 {
+                index_writer.add_document(doc!(val => i * 3)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(val, 0u64))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(val, 3_072u64))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_u64(val, 7_497u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let column: Vec<(DocId, u64)> = segment_reader.fast_field_column(val).unwrap().collect();
+        let expected: Vec<(DocId, u64)> = (0..2_500u32)
+            .filter(|&doc| doc != 0 && doc != 1_024 && doc != 2_499)
+            .map(|doc| (doc, u64::from(doc) * 3))
+            .collect();
+        assert_eq!(column, expected);
+        assert!(segment_reader.fast_field_column::<i64>(val).is_err());
+    }
+
+    #[test]
+    fn test_reader_cache() {
+        let mut schema_builder = SchemaBuilder::new();
+        let name = schema_builder.add_text_field("name", TEXT);
+        let val = schema_builder.add_u64_field("val", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(name => "a b", val => 3u64, delta => -4i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(name => "c", val => 7u64, delta => 5i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let num_cached = |segment_reader: &SegmentReader| {
+            segment_reader
+                .fast_field_reader_cache
+                .0
+                .read()
+                .unwrap()
+                .len()
+        };
+        assert_eq!(num_cached(segment_reader), 0);
+        for _ in 0..2 {
+            assert_eq!(
+                segment_reader.fast_field_reader::<u64>(val).unwrap().get(1),
+                7u64
+            );
+            assert_eq!(
+                segment_reader
+                    .fast_field_reader::<i64>(delta)
+                    .unwrap()
+                    .get(0),
+                -4i64
+            );
+            assert!(segment_reader.fast_field_reader::<i64>(val).is_err());
+            assert_eq!(num_cached(segment_reader), 2);
+            assert_eq!(segment_reader.get_fieldnorms_reader(name).fieldnorm(0), 2);
+        }
+        // Clones of the segment reader share the cache.
+        assert_eq!(num_cached(&segment_reader.clone()), 2);
+    }
+}
+
+#[cfg(all(test, feature = "unstable"))]
+mod bench {
+
+    use schema::{SchemaBuilder, FAST};
+    use test::Bencher;
+    use Index;
+
+    #[bench]
+    fn bench_fast_field_reader_open(b: &mut Bencher) {
+        let mut schema_builder = SchemaBuilder::new();
+        let val = schema_builder.add_u64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1_000u64 {
+                index_writer.add_document(doc!(val => i)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        // Opening the reader once per query, for 10k small queries.
+        b.iter(|| {
+            (0..10_000u32)
+                .map(|doc| {
+                    segment_reader
+                        .fast_field_reader::<u64>(val)
+                        .unwrap()
+                        .get(doc % 1_000)
+                })
+                .sum::<u64>()
+        });
+    }
 }
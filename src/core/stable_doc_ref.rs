@@ -0,0 +1,168 @@
+use core::SegmentId;
+use error::TantivyError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use DocId;
+
+/// Reference to a document that remains meaningful across searchers.
+///
+/// A `DocAddress` identifies its segment by its ordinal in a given
+/// `Searcher`, so that it cannot be passed around, for instance through
+/// an HTTP API, without keeping the searcher alive.
+/// A `StableDocRef` identifies the segment by its `SegmentId` instead,
+/// and records the generation of the searcher it was created from,
+/// that is the opstamp of the commit the searcher reflects.
+///
+/// It is created with `Searcher::stable_address` and turned back into a
+/// `DocAddress` with `Searcher::resolve_stable`. Once the segment of the
+/// document is merged away, the reference cannot be resolved anymore,
+/// and the document has to be searched again, for instance by its
+/// primary key.
+///
+/// Its string representation, `<segment uuid>.<doc id>.<generation>`,
+/// is also the one used by its serde implementation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StableDocRef {
+    /// Id of the segment of the document.
+    pub segment_id: SegmentId,
+    /// `DocId` of the document within its segment.
+    pub doc_id: DocId,
+    /// Generation of the searcher the reference was created from.
+    pub generation: u64,
+}
+
+impl fmt::Display for StableDocRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.segment_id.uuid_string(),
+            self.doc_id,
+            self.generation
+        )
+    }
+}
+
+impl FromStr for StableDocRef {
+    type Err = TantivyError;
+
+    fn from_str(stable_ref_str: &str) -> Result<StableDocRef, TantivyError> {
+        let invalid_ref = || {
+            TantivyError::InvalidArgument(format!(
+                "Invalid stable document reference '{}'",
+                stable_ref_str
+            ))
+        };
+        let parts: Vec<&str> = stable_ref_str.split('.').collect();
+        if parts.len() != 3 {
+            return Err(invalid_ref());
+        }
+        let segment_id = SegmentId::from_uuid_string(parts[0]).ok_or_else(&invalid_ref)?;
+        let doc_id = parts[1].parse::<DocId>().map_err(|_| invalid_ref())?;
+        let generation = parts[2].parse::<u64>().map_err(|_| invalid_ref())?;
+        Ok(StableDocRef {
+            segment_id,
+            doc_id,
+            generation,
+        })
+    }
+}
+
+impl Serialize for StableDocRef {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for StableDocRef {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let stable_ref_str = String::deserialize(deserializer)?;
+        stable_ref_str
+            .parse::<StableDocRef>()
+            .map_err(::serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::StableDocRef;
+    use indexer::NoMergePolicy;
+    use schema::{SchemaBuilder, STRING};
+    use serde_json;
+    use DocAddress;
+    use Index;
+    use Term;
+
+    #[test]
+    fn test_stable_doc_ref_across_merge() {
+        let mut schema_builder = SchemaBuilder::default();
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id_field => "a")).unwrap();
+        index_writer.add_document(doc!(id_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(id_field => "c")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        // The ordinal of the segment containing "a" and "b".
+        let first_segment_ord = searcher
+            .segment_readers()
+            .iter()
+            .position(|segment_reader| segment_reader.max_doc() == 2)
+            .unwrap() as u32;
+        let doc_address = DocAddress(1 - first_segment_ord, 0);
+        let stable_ref = searcher.stable_address(doc_address);
+        assert_eq!(stable_ref.generation, searcher.generation());
+        assert_eq!(
+            searcher.resolve_stable(&stable_ref).unwrap(),
+            Some(doc_address)
+        );
+
+        // String and serde representations.
+        let stable_ref_str = stable_ref.to_string();
+        assert_eq!(stable_ref_str.parse::<StableDocRef>().unwrap(), stable_ref);
+        let json = serde_json::to_string(&stable_ref).unwrap();
+        assert_eq!(json, format!("\"{}\"", stable_ref_str));
+        let deserialized: StableDocRef = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, stable_ref);
+        assert!("not.a.ref".parse::<StableDocRef>().is_err());
+        assert!(serde_json::from_str::<StableDocRef>("\"12\"").is_err());
+
+        // A deleted document cannot be resolved.
+        let deleted_ref = searcher.stable_address(DocAddress(first_segment_ord, 1));
+        index_writer
+            .delete_term(Term::from_field_text(id_field, "b"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert!(searcher.generation() > stable_ref.generation);
+        assert_eq!(searcher.resolve_stable(&deleted_ref).unwrap(), None);
+        assert!(searcher.resolve_stable(&stable_ref).unwrap().is_some());
+
+        // After a merge, the segment does not exist anymore.
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.resolve_stable(&stable_ref).unwrap(), None);
+        let stable_ref = searcher.stable_address(DocAddress(0, 0));
+        assert_eq!(
+            searcher.resolve_stable(&stable_ref).unwrap(),
+            Some(DocAddress(0, 0))
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use common::CompositeFile;
+use directory::ReadOnlySource;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+// Pages are touched by reading one byte every `PAGE_SIZE` bytes.
+const PAGE_SIZE: usize = 4_096;
+
+/// Selects the components of the segments warmed up by `Searcher::warm`.
+///
+/// The default set holds the components read by most queries:
+/// the term dictionaries, the fast fields and the fieldnorms.
+/// The positions and the doc store are typically much larger,
+/// and are only warmed up on demand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WarmUpSet {
+    /// Warm up the term dictionaries.
+    pub term_dictionaries: bool,
+    /// Warm up the fast fields.
+    pub fast_fields: bool,
+    /// Warm up the fieldnorms.
+    pub fieldnorms: bool,
+    /// Warm up the positions, and their skip index.
+    pub positions: bool,
+    /// Warm up the doc store.
+    pub store: bool,
+}
+
+impl WarmUpSet {
+    /// Returns a set selecting all of the components.
+    pub fn all() -> WarmUpSet {
+        WarmUpSet {
+            term_dictionaries: true,
+            fast_fields: true,
+            fieldnorms: true,
+            positions: true,
+            store: true,
+        }
+    }
+}
+
+impl Default for WarmUpSet {
+    fn default() -> WarmUpSet {
+        WarmUpSet {
+            term_dictionaries: true,
+            fast_fields: true,
+            fieldnorms: true,
+            positions: false,
+            store: false,
+        }
+    }
+}
+
+/// Bytes read through while warming up a component, and the time it took.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComponentWarmUp {
+    /// Number of bytes of the component.
+    pub num_bytes: usize,
+    /// Time spent reading through the component.
+    ///
+    /// The segments are warmed up on the search executor: with a pool
+    /// of threads, this is the sum of the durations for each segment,
+    /// and typically larger than the elapsed time.
+    pub duration: Duration,
+}
+
+impl ComponentWarmUp {
+    fn add(&mut self, other: &ComponentWarmUp) {
+        self.num_bytes += other.num_bytes;
+        self.duration += other.duration;
+    }
+}
+
+/// Report of a `Searcher::warm` call, per component.
+///
+/// The components that were not selected are reported
+/// with `0` bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// Term dictionaries.
+    pub term_dictionaries: ComponentWarmUp,
+    /// Fast fields.
+    pub fast_fields: ComponentWarmUp,
+    /// Fieldnorms.
+    pub fieldnorms: ComponentWarmUp,
+    /// Positions, and their skip index.
+    pub positions: ComponentWarmUp,
+    /// Doc store.
+    pub store: ComponentWarmUp,
+}
+
+impl WarmUpReport {
+    /// Returns the total number of bytes read through.
+    pub fn num_bytes(&self) -> usize {
+        self.term_dictionaries.num_bytes
+            + self.fast_fields.num_bytes
+            + self.fieldnorms.num_bytes
+            + self.positions.num_bytes
+            + self.store.num_bytes
+    }
+
+    /// Adds the bytes and the durations of `other` to this report.
+    pub(crate) fn merge(&mut self, other: &WarmUpReport) {
+        self.term_dictionaries.add(&other.term_dictionaries);
+        self.fast_fields.add(&other.fast_fields);
+        self.fieldnorms.add(&other.fieldnorms);
+        self.positions.add(&other.positions);
+        self.store.add(&other.store);
+    }
+}
+
+/// Reads through the given sources, and reports their total length
+/// along with the time it took.
+pub(crate) fn warm_sources<'a, I>(sources: I) -> ComponentWarmUp
+where
+    I: IntoIterator<Item = &'a ReadOnlySource>,
+{
+    let start = Instant::now();
+    let mut num_bytes = 0;
+    for source in sources {
+        let data = source.as_slice();
+        let mut page_start = 0;
+        while page_start < data.len() {
+            // The volatile read cannot be optimized away,
+            // so that the page is actually loaded.
+            unsafe {
+                ptr::read_volatile(&data[page_start]);
+            }
+            page_start += PAGE_SIZE;
+        }
+        num_bytes += data.len();
+    }
+    ComponentWarmUp {
+        num_bytes,
+        duration: start.elapsed(),
+    }
+}
+
+/// Reads through all of the sub-files of the given composite files.
+pub(crate) fn warm_composite_files(composite_files: &[&CompositeFile]) -> ComponentWarmUp {
+    let sources: Vec<ReadOnlySource> = composite_files
+        .iter()
+        .flat_map(|composite_file| {
+            composite_file
+                .iter_fields()
+                .filter_map(move |&file_addr| composite_file.open_read_addr(file_addr))
+        }).collect();
+    warm_sources(&sources)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{WarmUpReport, WarmUpSet};
+    use indexer::NoMergePolicy;
+    use schema::{SchemaBuilder, FAST, STORED, TEXT};
+    use Index;
+
+    #[test]
+    fn test_warm_up() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let price_field = schema_builder.add_u64_field("price", FAST);
+        let schema = schema_builder.build();
+        let mut index = Index::builder()
+            .schema(schema)
+            .warm_up_on_load(WarmUpSet::default())
+            .create_in_ram()
+            .unwrap();
+        index.set_multithread_executor(2);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for i in 0u64..100u64 {
+                index_writer
+                    .add_document(doc!(
+                        text_field => "hello happy tax payer",
+                        price_field => i
+                    ))
+                    .unwrap();
+                if i % 30 == 29 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 4);
+
+        let report = searcher.warm(WarmUpSet::default());
+        assert!(report.term_dictionaries.num_bytes > 0);
+        assert!(report.fast_fields.num_bytes > 0);
+        assert!(report.fieldnorms.num_bytes > 0);
+        assert_eq!(report.positions.num_bytes, 0);
+        assert_eq!(report.store.num_bytes, 0);
+
+        // The report sums the reports of the segments.
+        let mut expected_report = WarmUpReport::default();
+        for segment_reader in searcher.segment_readers() {
+            expected_report.merge(&segment_reader.warm(WarmUpSet::default()));
+        }
+        assert_eq!(report.num_bytes(), expected_report.num_bytes());
+
+        let report = searcher.warm(WarmUpSet::all());
+        assert!(report.positions.num_bytes > 0);
+        assert!(report.store.num_bytes > 0);
+        let nothing = WarmUpSet {
+            term_dictionaries: false,
+            fast_fields: false,
+            fieldnorms: false,
+            positions: false,
+            store: false,
+        };
+        assert_eq!(searcher.warm(nothing).num_bytes(), 0);
+    }
+}
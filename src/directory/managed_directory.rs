@@ -80,6 +80,16 @@ impl ManagedDirectory {
         }
     }
 
+    /// Returns the list of the files created by tantivy
+    /// that have not been deleted.
+    pub(crate) fn list_managed_files(&self) -> HashSet<PathBuf> {
+        self.meta_informations
+            .read()
+            .expect("Managed directory rlock poisoned.")
+            .managed_paths
+            .clone()
+    }
+
     /// Garbage collect unused files.
     ///
     /// Removes the files that were created by `tantivy` and are not
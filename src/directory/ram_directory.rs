@@ -9,6 +9,7 @@ use std::io::{self, BufWriter, Cursor, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::result;
 use std::sync::{Arc, RwLock};
+use std::thread;
 
 /// Writer associated with the `RAMDirectory`
 ///
@@ -19,6 +20,8 @@ use std::sync::{Arc, RwLock};
 /// On drop, if the writer was left in a *dirty* state.
 /// That is, if flush was not called after the last call
 /// to write.
+/// The check is skipped if the thread is already panicking, in order
+/// to not turn a panic into an abort.
 ///
 struct VecWriter {
     path: PathBuf,
@@ -40,7 +43,7 @@ impl VecWriter {
 
 impl Drop for VecWriter {
     fn drop(&mut self) {
-        if !self.is_flushed {
+        if !self.is_flushed && !thread::panicking() {
             panic!(
                 "You forgot to flush {:?} before its writter got Drop. Do not rely on drop.",
                 self.path
@@ -1,8 +1,11 @@
 use super::shared_vec_slice::SharedVecSlice;
+use byteorder::ByteOrder;
+use common::Endianness;
 use common::HasLen;
 #[cfg(feature = "mmap")]
 use fst::raw::MmapReadOnly;
 use stable_deref_trait::{CloneStableDeref, StableDeref};
+use std::io;
 use std::ops::Deref;
 
 /// Read object that represents files in tantivy.
@@ -11,6 +14,9 @@ use std::ops::Deref;
 /// the data in the form of a constant read-only `&[u8]`.
 /// Whatever happens to the directory file, the data
 /// hold by this object should never be altered or destroyed.
+///
+/// Slicing and splitting a `ReadOnlySource` never copies the data:
+/// the resulting sources share the same underlying mmap or heap buffer.
 pub enum ReadOnlySource {
     /// Mmap source of data
     #[cfg(feature = "mmap")]
@@ -48,11 +54,68 @@ impl ReadOnlySource {
     /// Splits into 2 `ReadOnlySource`, at the offset given
     /// as an argument.
     pub fn split(self, addr: usize) -> (ReadOnlySource, ReadOnlySource) {
+        self.split_at(addr)
+    }
+
+    /// Returns the two `ReadOnlySource`s before and after the given
+    /// offset, like `<[u8]>::split_at`, without consuming the source.
+    ///
+    /// # Panics
+    /// Panics if `addr` is greater than the length of the source.
+    pub fn split_at(&self, addr: usize) -> (ReadOnlySource, ReadOnlySource) {
         let left = self.slice(0, addr);
         let right = self.slice_from(addr);
         (left, right)
     }
 
+    /// Splits into 2 `ReadOnlySource`, the right one holding
+    /// the last `num_bytes` bytes.
+    ///
+    /// This is typically useful to read the footer of a file.
+    ///
+    /// # Panics
+    /// Panics if the source is shorter than `num_bytes`.
+    pub fn split_from_end(self, num_bytes: usize) -> (ReadOnlySource, ReadOnlySource) {
+        let len = self.len();
+        assert!(
+            num_bytes <= len,
+            "Cannot split {} bytes from the end of a source of {} bytes",
+            num_bytes,
+            len
+        );
+        self.split(len - num_bytes)
+    }
+
+    /// Reads the `u32` serialized at the given offset.
+    ///
+    /// Returns an `UnexpectedEof` error if the source is too short.
+    pub fn read_u32_at(&self, offset: usize) -> io::Result<u32> {
+        self.bytes_at(offset, 4).map(Endianness::read_u32)
+    }
+
+    /// Reads the `u64` serialized at the given offset.
+    ///
+    /// Returns an `UnexpectedEof` error if the source is too short.
+    pub fn read_u64_at(&self, offset: usize) -> io::Result<u64> {
+        self.bytes_at(offset, 8).map(Endianness::read_u64)
+    }
+
+    fn bytes_at(&self, offset: usize, num_bytes: usize) -> io::Result<&[u8]> {
+        let data = self.as_slice();
+        if offset > data.len() || data.len() - offset < num_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Cannot read {} bytes at offset {} of a source of {} bytes",
+                    num_bytes,
+                    offset,
+                    data.len()
+                ),
+            ));
+        }
+        Ok(&data[offset..offset + num_bytes])
+    }
+
     /// Creates a ReadOnlySource that is just a
     /// view over a slice of the data.
     ///
@@ -118,3 +181,63 @@ impl From<Vec<u8>> for ReadOnlySource {
         ReadOnlySource::Anonymous(shared_data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::ReadOnlySource;
+    use common::BinarySerializable;
+    use common::HasLen;
+    use std::io;
+
+    #[test]
+    fn test_read_only_source_footer() {
+        let mut buffer: Vec<u8> = vec![1u8, 2u8, 3u8];
+        17u64.serialize(&mut buffer).unwrap();
+        3u32.serialize(&mut buffer).unwrap();
+        let source = ReadOnlySource::from(buffer);
+        let (body, footer) = source.split_from_end(4);
+        assert_eq!(footer.read_u32_at(0).unwrap(), 3u32);
+        assert_eq!(body.len(), 11);
+        assert_eq!(body.read_u64_at(3).unwrap(), 17u64);
+        let (data, _) = body.split_from_end(8);
+        assert_eq!(data.as_slice(), &[1u8, 2u8, 3u8]);
+        // Slices share the data.
+        assert_eq!(data.slice(1, 3).as_slice(), &[2u8, 3u8]);
+    }
+
+    #[test]
+    fn test_read_only_source_split_at() {
+        let source = ReadOnlySource::from(vec![1u8, 2u8, 3u8, 4u8, 5u8]);
+        let (left, right) = source.split_at(2);
+        assert_eq!(&*left, &[1u8, 2u8]);
+        assert_eq!(&*right, &[3u8, 4u8, 5u8]);
+        // The source is still usable, and derefs to its bytes.
+        assert_eq!(source.len(), 5);
+        assert_eq!(source.iter().map(|&b| u32::from(b)).sum::<u32>(), 15);
+        let (empty, all) = source.split_at(0);
+        assert!(empty.is_empty());
+        assert_eq!(&all[..], &source[..]);
+        let (all, empty) = source.split_at(5);
+        assert_eq!(all.len(), 5);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_read_only_source_read_out_of_bounds() {
+        let source = ReadOnlySource::from(vec![0u8; 6]);
+        assert!(source.read_u32_at(2).is_ok());
+        assert_eq!(
+            source.read_u32_at(3).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        assert!(source.read_u64_at(7).is_err());
+        assert!(ReadOnlySource::empty().read_u64_at(0).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot split 4 bytes from the end of a source of 3 bytes")]
+    fn test_read_only_source_split_from_end_too_short() {
+        ReadOnlySource::from(vec![0u8; 3]).split_from_end(4);
+    }
+}
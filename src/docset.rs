@@ -17,8 +17,23 @@ pub enum SkipResult {
 }
 
 /// Represents an iterable set of sorted doc ids.
+///
+/// A `DocSet` is a cursor. It starts positioned before its
+/// first document, and must go through its documents in strictly
+/// increasing order of `DocId`.
+///
+/// There is no sentinel `DocId` marking the end of a `DocSet`:
+/// its exhaustion is signaled by `.advance()` returning `false`.
+/// From then on, `.advance()` must keep returning `false`, and the
+/// value returned by `.doc()` is unspecified.
+///
+/// Implementing `.advance()`, `.doc()` and `.size_hint()` is
+/// sufficient. The other methods have default implementations
+/// relying on them, that may be overridden for performance.
 pub trait DocSet {
-    /// Goes to the next element.
+    /// Goes to the next element, and returns `false` if the `DocSet`
+    /// has no more elements.
+    ///
     /// `.advance(...)` needs to be called a first time to point to the correct
     /// element.
     fn advance(&mut self) -> bool;
@@ -81,6 +96,9 @@ pub trait DocSet {
     }
 
     /// Returns the current document
+    ///
+    /// It is only meaningful after a call to `.advance()` or `.skip_next(...)`
+    /// that did not reach the end of the `DocSet`.
     fn doc(&self) -> DocId;
 
     /// Returns a best-effort hint of the
@@ -106,6 +124,36 @@ pub trait DocSet {
     }
 }
 
+/// Number of `DocId`s of a block, as filled by `BlockDocSet::fill_block`.
+///
+/// This is the size of the blocks of the postings lists.
+pub const DOC_BLOCK_LEN: usize = 128;
+
+/// Extension of `DocSet` going through its documents block by block.
+///
+/// This makes it possible to process the `DocId`s of a `DocSet` in bulk,
+/// e.g. with SIMD instructions, rather than one at a time.
+///
+/// The postings lists are stored by blocks of `DOC_BLOCK_LEN` documents:
+/// `SegmentPostings` hands over its decoded blocks without going
+/// through `.advance()` for each document.
+///
+/// See also `Collector::accepts_blocks`.
+pub trait BlockDocSet: DocSet {
+    /// Fills `block` with the next `DocId`s of the `DocSet`, and returns
+    /// the number of `DocId`s written, or `0` once the `DocSet` is consumed.
+    ///
+    /// Fewer than `DOC_BLOCK_LEN` `DocId`s may be written even though the
+    /// `DocSet` is not consumed, e.g. when the `DocSet` was positioned in
+    /// the middle of one of its blocks.
+    ///
+    /// Like `.fill_buffer(...)`, the method advances the `DocSet`:
+    /// it is then positioned on the last `DocId` written.
+    fn fill_block(&mut self, block: &mut [DocId; DOC_BLOCK_LEN]) -> usize {
+        self.fill_buffer(&mut block[..])
+    }
+}
+
 impl<TDocSet: DocSet + ?Sized> DocSet for Box<TDocSet> {
     fn advance(&mut self) -> bool {
         let unboxed: &mut TDocSet = self.borrow_mut();
@@ -2,7 +2,8 @@
 
 use std::io;
 
-use directory::error::{IOError, OpenDirectoryError, OpenReadError, OpenWriteError};
+use core::SegmentComponent;
+use directory::error::{DeleteError, IOError, OpenDirectoryError, OpenReadError, OpenWriteError};
 use fastfield::FastFieldNotAvailableError;
 use indexer::LockType;
 use query;
@@ -49,9 +50,26 @@ pub enum TantivyError {
     /// An Error appeared related to the schema.
     #[fail(display = "Schema error: '{}'", _0)]
     SchemaError(String),
+    /// The schema of an existing index differs from the expected one.
+    #[fail(display = "the schema of the index does not match: '{}'", _0)]
+    SchemaMismatch(String),
+    /// The settings of the index are not supported by this build of tantivy.
+    #[fail(display = "unsupported index setting: '{}'", _0)]
+    UnsupportedSetting(String),
     /// Tried to access a fastfield reader for a field not configured accordingly.
     #[fail(display = "fast field not available: '{:?}'", _0)]
     FastFieldError(#[cause] FastFieldNotAvailableError),
+    /// Tried to access a segment component that was not loaded.
+    #[fail(display = "segment component not loaded: '{:?}'", _0)]
+    ComponentNotLoaded(SegmentComponent),
+    /// An indexing thread panicked.
+    ///
+    /// The documents that were being indexed by this thread are lost.
+    #[fail(display = "an indexing thread panicked: '{}'", message)]
+    IndexingPanicked {
+        /// Message of the panic.
+        message: String,
+    },
 }
 
 impl From<FastFieldNotAvailableError> for TantivyError {
@@ -93,6 +111,15 @@ impl From<OpenReadError> for TantivyError {
     }
 }
 
+impl From<DeleteError> for TantivyError {
+    fn from(error: DeleteError) -> TantivyError {
+        match error {
+            DeleteError::FileDoesNotExist(filepath) => TantivyError::PathDoesNotExist(filepath),
+            DeleteError::IOError(io_error) => TantivyError::IOError(io_error),
+        }
+    }
+}
+
 impl From<schema::DocParsingError> for TantivyError {
     fn from(error: schema::DocParsingError) -> TantivyError {
         TantivyError::InvalidArgument(format!("Failed to parse document {:?}", error))
@@ -6,7 +6,10 @@ pub use self::writer::BytesFastFieldWriter;
 
 #[cfg(test)]
 mod tests {
-    use schema::SchemaBuilder;
+    use query::{Query, QueryParser, QueryParserError, TermQuery};
+    use schema::{BytesOptions, FieldType, IndexRecordOption, Schema, SchemaBuilder, Term, Value};
+    use serde_json;
+    use DocAddress;
     use Index;
 
     #[test]
@@ -16,11 +19,17 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-        index_writer.add_document(doc!(field=>vec![0u8, 1, 2, 3]));
-        index_writer.add_document(doc!(field=>vec![]));
-        index_writer.add_document(doc!(field=>vec![255u8]));
-        index_writer.add_document(doc!(field=>vec![1u8, 3, 5, 7, 9]));
-        index_writer.add_document(doc!(field=>vec![0u8; 1000]));
+        index_writer
+            .add_document(doc!(field=>vec![0u8, 1, 2, 3]))
+            .unwrap();
+        index_writer.add_document(doc!(field=>vec![])).unwrap();
+        index_writer.add_document(doc!(field=>vec![255u8])).unwrap();
+        index_writer
+            .add_document(doc!(field=>vec![1u8, 3, 5, 7, 9]))
+            .unwrap();
+        index_writer
+            .add_document(doc!(field=>vec![0u8; 1000]))
+            .unwrap();
         assert!(index_writer.commit().is_ok());
 
         index.load_searchers().unwrap();
@@ -35,4 +44,78 @@ mod tests {
         let long = vec![0u8; 1000];
         assert_eq!(bytes_reader.get_val(4), long.as_slice());
     }
+
+    #[test]
+    fn test_indexed_and_stored_bytes() {
+        let mut schema_builder = SchemaBuilder::default();
+        let field = schema_builder.add_bytes_field_with_options(
+            "hash",
+            BytesOptions::default().set_indexed().set_stored(),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+            index_writer
+                .add_document(doc!(field=>vec![0u8, 1, 2, 3]))
+                .unwrap();
+            index_writer
+                .add_document(doc!(field=>vec![0u8, 1, 2]))
+                .unwrap();
+            index_writer
+                .add_document(doc!(field=>vec![0u8, 1, 2, 3]))
+                .unwrap();
+            // base64 encoding of [0u8, 1, 2, 3]
+            let json_doc = schema.parse_document(r#"{"hash": "AAECAw=="}"#).unwrap();
+            index_writer.add_document(json_doc).unwrap();
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |bytes: &[u8]| {
+            let term_query = TermQuery::new(
+                Term::from_field_bytes(field, bytes),
+                IndexRecordOption::Basic,
+            );
+            term_query.count(&*searcher).unwrap()
+        };
+        assert_eq!(count(&[0u8, 1, 2, 3]), 3);
+        assert_eq!(count(&[0u8, 1, 2]), 1);
+        assert_eq!(count(&[0u8, 1]), 0);
+
+        let retrieved_doc = searcher.doc(DocAddress(0u32, 1u32)).unwrap();
+        assert_eq!(
+            retrieved_doc.get_first(field),
+            Some(&Value::Bytes(vec![0u8, 1, 2]))
+        );
+        assert_eq!(schema.to_json(&retrieved_doc), r#"{"hash":["AAEC"]}"#);
+
+        // the field was not declared as fast.
+        assert!(
+            searcher
+                .segment_reader(0)
+                .bytes_fast_field_reader(field)
+                .is_err()
+        );
+
+        let query_parser = QueryParser::for_index(&index, vec![]);
+        assert_eq!(
+            query_parser.parse_query("hash:abc").unwrap_err(),
+            QueryParserError::BytesFieldNotQueryable("hash".to_string())
+        );
+    }
+
+    #[test]
+    fn test_legacy_bytes_field_deserialization() {
+        let schema_json = r#"[{"name": "bytesfield", "type": "bytes"}]"#;
+        let schema: Schema = serde_json::from_str(schema_json).unwrap();
+        let field = schema.get_field("bytesfield").unwrap();
+        let field_entry = schema.get_field_entry(field);
+        assert!(!field_entry.is_indexed());
+        assert!(!field_entry.is_stored());
+        assert_eq!(
+            field_entry.field_type(),
+            &FieldType::Bytes(BytesOptions::default().set_fast())
+        );
+    }
 }
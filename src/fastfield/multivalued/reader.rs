@@ -62,17 +62,17 @@ mod tests {
             let mut doc = Document::new();
             doc.add_facet(facet_field, "/category/cat2");
             doc.add_facet(facet_field, "/category/cat1");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         {
             let mut doc = Document::new();
             doc.add_facet(facet_field, "/category/cat2");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         {
             let mut doc = Document::new();
             doc.add_facet(facet_field, "/category/cat3");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().expect("Commit failed");
         index.load_searchers().expect("Reloading searchers");
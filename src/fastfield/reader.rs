@@ -1,7 +1,6 @@
 use super::FastValue;
 use common::bitpacker::BitUnpacker;
 use common::compute_num_bits;
-use common::BinarySerializable;
 use common::CompositeFile;
 use directory::ReadOnlySource;
 use directory::{Directory, RAMDirectory, WritePtr};
@@ -29,15 +28,12 @@ pub struct FastFieldReader<Item: FastValue> {
 impl<Item: FastValue> FastFieldReader<Item> {
     /// Opens a fast field given a source.
     pub fn open(data: ReadOnlySource) -> Self {
-        let min_value: u64;
-        let amplitude: u64;
-        {
-            let mut cursor = data.as_slice();
-            min_value =
-                u64::deserialize(&mut cursor).expect("Failed to read the min_value of fast field.");
-            amplitude =
-                u64::deserialize(&mut cursor).expect("Failed to read the amplitude of fast field.");
-        }
+        let min_value = data
+            .read_u64_at(0)
+            .expect("Failed to read the min_value of fast field.");
+        let amplitude = data
+            .read_u64_at(8)
+            .expect("Failed to read the amplitude of fast field.");
         let max_value = min_value + amplitude;
         let num_bits = compute_num_bits(amplitude);
         let owning_ref = OwningRef::new(data).map(|data| &data[16..]);
@@ -50,6 +46,16 @@ impl<Item: FastValue> FastFieldReader<Item> {
         }
     }
 
+    /// Returns a reader of the same data, decoding the values as `TItem`.
+    pub(crate) fn cast<TItem: FastValue>(&self) -> FastFieldReader<TItem> {
+        FastFieldReader {
+            bit_unpacker: self.bit_unpacker.clone(),
+            min_value_u64: self.min_value_u64,
+            max_value_u64: self.max_value_u64,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Return the value associated to the given document.
     ///
     /// This accessor should return as fast as possible.
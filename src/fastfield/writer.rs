@@ -49,9 +49,11 @@ impl FastFieldsWriter {
                     let fast_field_writer = MultiValueIntFastFieldWriter::new(field, true);
                     multi_values_writers.push(fast_field_writer);
                 }
-                FieldType::Bytes => {
-                    let fast_field_writer = BytesFastFieldWriter::new(field);
-                    bytes_value_writers.push(fast_field_writer);
+                FieldType::Bytes(ref bytes_options) => {
+                    if bytes_options.is_fast() {
+                        let fast_field_writer = BytesFastFieldWriter::new(field);
+                        bytes_value_writers.push(fast_field_writer);
+                    }
                 }
                 _ => {}
             }
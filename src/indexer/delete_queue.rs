@@ -37,7 +37,10 @@ impl DeleteQueue {
 
         let next_block = NextBlock::from(delete_queue.clone());
         {
-            let mut delete_queue_wlock = delete_queue.inner.write().unwrap();
+            let mut delete_queue_wlock = delete_queue
+                .inner
+                .write()
+                .expect("The delete queue was just created. It cannot be poisoned.");
             delete_queue_wlock.last_block = Some(Arc::new(Block {
                 operations: Arc::default(),
                 next: next_block,
@@ -14,8 +14,10 @@ use DocId;
 // The doc to opstamp mapping stores precisely an array
 // indexed by doc id and storing the opstamp of the document.
 //
-// This mapping is (for the moment) stricly increasing
+// This mapping is (for the moment) increasing
 // because of the way document id are allocated.
+// It is not strictly increasing: the documents added
+// in a single batch share the same opstamp.
 #[derive(Clone)]
 pub enum DocToOpstampMapping {
     WithMap(Arc<Vec<u64>>),
@@ -32,15 +34,20 @@ impl DocToOpstampMapping {
     /// Given an opstamp return the limit doc id L
     /// such that all doc id D such that
     // D >= L iff opstamp(D) >= than `target_opstamp`.
-    //
-    // The edge case opstamp = some doc opstamp is in practise
-    // never called.
     pub fn compute_doc_limit(&self, target_opstamp: u64) -> DocId {
         match *self {
             DocToOpstampMapping::WithMap(ref doc_opstamps) => {
-                match doc_opstamps.binary_search(&target_opstamp) {
-                    Ok(doc_id) | Err(doc_id) => doc_id as DocId,
+                // first doc whose opstamp is >= `target_opstamp`.
+                let (mut start, mut end) = (0, doc_opstamps.len());
+                while start < end {
+                    let mid = start + (end - start) / 2;
+                    if doc_opstamps[mid] < target_opstamp {
+                        start = mid + 1;
+                    } else {
+                        end = mid;
+                    }
                 }
+                start as DocId
             }
             DocToOpstampMapping::None => DocId::max_value(),
         }
@@ -90,4 +97,16 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_doc_to_opstamp_mapping_with_duplicates() {
+        let doc_to_opstamp_mapping =
+            DocToOpstampMapping::from(vec![1u64, 3u64, 3u64, 3u64, 3u64, 5u64]);
+        assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(1u64), 0);
+        assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(2u64), 1);
+        assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(3u64), 1);
+        assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(4u64), 5);
+        assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(5u64), 5);
+        assert_eq!(doc_to_opstamp_mapping.compute_doc_limit(6u64), 6);
+    }
 }
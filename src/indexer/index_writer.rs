@@ -2,6 +2,8 @@ use super::operation::AddOperation;
 use super::segment_updater::SegmentUpdater;
 use super::PreparedCommit;
 use bit_set::BitSet;
+use core::panic_message;
+use core::FlushMetrics;
 use core::Index;
 use core::Segment;
 use core::SegmentComponent;
@@ -15,21 +17,27 @@ use fastfield::write_delete_bitset;
 use futures::sync::oneshot::Receiver;
 use indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use indexer::doc_opstamp_mapping::DocToOpstampMapping;
-use indexer::operation::DeleteOperation;
+use indexer::operation::{DeleteOperation, UserOperation};
 use indexer::stamper::Stamper;
 use indexer::DirectoryLock;
 use indexer::MergePolicy;
 use indexer::SegmentEntry;
 use indexer::SegmentWriter;
+use indexer::{MergeDedupPolicy, MergeMetadataCombiner, SegmentMetadataBuilder};
 use postings::compute_table_size;
 use schema::Document;
 use schema::IndexRecordOption;
 use schema::Term;
+use std::collections::BTreeMap;
 use std::mem;
 use std::mem::swap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 use Result;
+use MAX_DOC_LIMIT;
 
 // Size of the margin for the heap. A segment is closed when the remaining memory
 // in the heap goes below MARGIN_IN_BYTES.
@@ -41,10 +49,26 @@ pub const HEAP_SIZE_MAX: usize = u32::max_value() as usize - MARGIN_IN_BYTES;
 
 // Add document will block if the number of docs waiting in the queue to be indexed
 // reaches `PIPELINE_MAX_SIZE_IN_DOCS`
-const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
-
-type DocumentSender = channel::Sender<AddOperation>;
-type DocumentReceiver = channel::Receiver<AddOperation>;
+pub const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
+
+// Add operations are sent through the pipeline in batches.
+// All of the operations of a batch are indexed by the same
+// indexing thread.
+type AddBatch = Vec<AddOperation>;
+type DocumentSender = channel::Sender<AddBatch>;
+type DocumentReceiver = channel::Receiver<AddBatch>;
+
+/// Error returned by `IndexWriter::try_add_document`.
+#[derive(Debug)]
+pub enum TryAddDocumentError {
+    /// The indexing pipeline is full.
+    ///
+    /// The document was not added, and is handed back to the caller.
+    WouldBlock(Document),
+    /// The document could not be stamped, because the index writer
+    /// ran out of opstamps.
+    Error(TantivyError),
+}
 
 /// Split the thread memory budget into
 /// - the heap size
@@ -79,6 +103,8 @@ pub struct IndexWriter {
 
     heap_size_in_bytes_per_thread: usize,
 
+    pipeline_capacity: usize,
+
     workers_join_handle: Vec<JoinHandle<Result<()>>>,
 
     document_receiver: DocumentReceiver,
@@ -108,6 +134,9 @@ pub struct IndexWriter {
 ///
 /// `num_threads` specifies the number of indexing workers that
 /// should work at the same time.
+///
+/// `pipeline_capacity` is the maximum number of documents waiting
+/// in the indexing queue. Once it is reached, `add_document` blocks.
 /// # Errors
 /// If the lockfile already exists, returns `Error::FileAlreadyExists`.
 /// # Panics
@@ -116,6 +145,7 @@ pub fn open_index_writer(
     index: &Index,
     num_threads: usize,
     heap_size_in_bytes_per_thread: usize,
+    pipeline_capacity: usize,
     directory_lock: DirectoryLock,
 ) -> Result<IndexWriter> {
     if heap_size_in_bytes_per_thread < HEAP_SIZE_MIN {
@@ -129,8 +159,12 @@ pub fn open_index_writer(
         let err_msg = format!("The heap size per thread cannot exceed {}", HEAP_SIZE_MAX);
         return Err(TantivyError::InvalidArgument(err_msg));
     }
+    if pipeline_capacity == 0 {
+        let err_msg = "The pipeline capacity needs to be at least 1.".to_string();
+        return Err(TantivyError::InvalidArgument(err_msg));
+    }
     let (document_sender, document_receiver): (DocumentSender, DocumentReceiver) =
-        channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
+        channel::bounded(pipeline_capacity);
 
     let delete_queue = DeleteQueue::new();
 
@@ -145,6 +179,7 @@ pub fn open_index_writer(
         _directory_lock: Some(directory_lock),
 
         heap_size_in_bytes_per_thread,
+        pipeline_capacity,
         index: index.clone(),
 
         document_receiver,
@@ -267,11 +302,20 @@ fn index_documents(
     segment_updater: &mut SegmentUpdater,
     mut delete_cursor: DeleteCursor,
 ) -> Result<bool> {
+    let start = Instant::now();
     let schema = segment.schema();
     let segment_id = segment.id();
     let table_size = initial_table_size(memory_budget);
     let mut segment_writer = SegmentWriter::for_segment(table_size, segment.clone(), &schema)?;
+    segment_writer.set_max_tokens_per_doc(segment_updater.get_max_tokens_per_doc());
+    let max_docs = segment_updater.get_max_docs_per_segment();
+    segment_writer.set_max_docs(max_docs);
+    let metadata_builder = segment_updater.get_segment_metadata_builder();
+    let mut user_metadata = BTreeMap::new();
     for doc in document_iterator {
+        if let Some(ref metadata_builder) = metadata_builder {
+            metadata_builder.add_document(&doc.document, &mut user_metadata);
+        }
         segment_writer.add_document(doc, &schema)?;
 
         let mem_usage = segment_writer.mem_usage();
@@ -283,6 +327,22 @@ fn index_documents(
             );
             break;
         }
+        if segment_writer.max_doc() >= max_docs {
+            info!(
+                "Segment is full, flushing segment with maxdoc={}.",
+                max_docs
+            );
+            break;
+        }
+    }
+
+    let num_truncated_docs = segment_writer.num_truncated_docs();
+    if num_truncated_docs > 0 {
+        warn!(
+            "Truncated {} documents of segment {:?} to the maximum number of tokens per document.",
+            num_truncated_docs, segment_id
+        );
+        segment_updater.record_truncated_docs(num_truncated_docs);
     }
 
     if !segment_updater.is_alive() {
@@ -297,7 +357,12 @@ fn index_documents(
 
     let doc_opstamps: Vec<u64> = segment_writer.finalize()?;
 
-    let segment_meta = SegmentMeta::new(segment_id, num_docs);
+    segment.index().metrics().on_flush(&FlushMetrics {
+        num_docs,
+        duration: start.elapsed(),
+    });
+
+    let segment_meta = SegmentMeta::new(segment_id, num_docs).with_user_metadata(user_metadata);
 
     let last_docstamp: u64 = *(doc_opstamps.last().unwrap());
 
@@ -338,9 +403,12 @@ impl IndexWriter {
         for join_handle in former_workers_handles {
             join_handle
                 .join()
-                .expect("Indexing Worker thread panicked")
                 .map_err(|_| {
-                    TantivyError::ErrorInThread("Error in indexing worker thread.".into())
+                    TantivyError::ErrorInThread("Indexing worker thread panicked.".into())
+                })?
+                .map_err(|error| match error {
+                    TantivyError::IndexingPanicked { .. } => error,
+                    _ => TantivyError::ErrorInThread("Error in indexing worker thread.".into()),
                 })?;
         }
         drop(self.workers_join_handle);
@@ -391,33 +459,50 @@ impl IndexWriter {
                 "indexing thread {} for gen {}",
                 self.worker_id, generation
             )).spawn(move || {
-                loop {
-                    let mut document_iterator = document_receiver_clone.clone().into_iter().peekable();
-
-                    // the peeking here is to avoid
-                    // creating a new segment's files
-                    // if no document are available.
-                    //
-                    // this is a valid guarantee as the
-                    // peeked document now belongs to
-                    // our local iterator.
-                    if let Some(operation) = document_iterator.peek() {
-                        delete_cursor.skip_to(operation.opstamp);
-                    } else {
-                        // No more documents.
-                        // Happens when there is a commit, or if the `IndexWriter`
-                        // was dropped.
-                        return Ok(());
+                let indexing_result = panic::catch_unwind(AssertUnwindSafe(|| -> Result<()> {
+                    let mut document_iterator = document_receiver_clone
+                        .clone()
+                        .into_iter()
+                        .flat_map(|add_batch| add_batch.into_iter())
+                        .peekable();
+                    loop {
+                        // the peeking here is to avoid
+                        // creating a new segment's files
+                        // if no document are available.
+                        //
+                        // this is a valid guarantee as the
+                        // peeked document now belongs to
+                        // our local iterator.
+                        if let Some(operation) = document_iterator.peek() {
+                            delete_cursor.skip_to(operation.opstamp);
+                        } else {
+                            // No more documents.
+                            // Happens when there is a commit, or if the `IndexWriter`
+                            // was dropped.
+                            return Ok(());
+                        }
+                        let segment = segment_updater.new_segment();
+                        index_documents(
+                            mem_budget,
+                            &segment,
+                            generation,
+                            &mut document_iterator,
+                            &mut segment_updater,
+                            delete_cursor.clone(),
+                        )?;
+                    }
+                }));
+                match indexing_result {
+                    Ok(result) => result,
+                    Err(panic_payload) => {
+                        let message = panic_message(&*panic_payload);
+                        error!("Indexing thread panicked: {}", message);
+                        // Keeps on consuming the pipeline, so that `add_document`
+                        // does not block. The documents are lost, and the error
+                        // is reported on the next commit.
+                        for _ in document_receiver_clone {}
+                        Err(TantivyError::IndexingPanicked { message })
                     }
-                    let segment = segment_updater.new_segment();
-                    index_documents(
-                        mem_budget,
-                        &segment,
-                        generation,
-                        &mut document_iterator,
-                        &mut segment_updater,
-                        delete_cursor.clone(),
-                    )?;
                 }
             })?;
         self.worker_id += 1;
@@ -435,6 +520,84 @@ impl IndexWriter {
         self.segment_updater.set_merge_policy(merge_policy);
     }
 
+    /// Sets the builder computing the user metadata of the
+    /// segments flushed by this index writer.
+    ///
+    /// By default, segments are flushed without any user metadata.
+    pub fn set_segment_metadata_builder(&self, builder: Arc<SegmentMetadataBuilder>) {
+        self.segment_updater.set_segment_metadata_builder(builder);
+    }
+
+    /// Sets the combiner computing the user metadata of merged segments.
+    ///
+    /// Defaults to `KeepCommonMetadata`.
+    pub fn set_merge_metadata_combiner(&self, combiner: Arc<MergeMetadataCombiner>) {
+        self.segment_updater.set_merge_metadata_combiner(combiner);
+    }
+
+    /// Sets the policy collapsing the documents sharing the same key
+    /// during merges.
+    ///
+    /// By default, merges keep all of the documents that are not deleted.
+    /// The policy applies to the merges started after this call.
+    ///
+    /// Returns a `SchemaError` if the fields of the policy are not
+    /// single-valued `u64` or `i64` fast fields.
+    pub fn set_merge_dedup_policy(&self, dedup_policy: MergeDedupPolicy) -> Result<()> {
+        dedup_policy.validate(&self.index.schema())?;
+        self.segment_updater
+            .set_merge_dedup_policy(Some(dedup_policy));
+        Ok(())
+    }
+
+    /// Stops collapsing the documents sharing the same key during merges.
+    pub fn clear_merge_dedup_policy(&self) {
+        self.segment_updater.set_merge_dedup_policy(None);
+    }
+
+    /// Sets the maximum number of tokens indexed per document,
+    /// over all of its text fields.
+    ///
+    /// This guards the index against pathological documents: the tokens
+    /// of a document following this limit are ignored, and the document
+    /// is counted in `.num_truncated_docs()`. Its stored fields are
+    /// left untouched.
+    ///
+    /// By default, there is no limit.
+    pub fn set_max_tokens_per_doc(&self, max_tokens_per_doc: u32) {
+        self.segment_updater
+            .set_max_tokens_per_doc(Some(max_tokens_per_doc));
+    }
+
+    /// Returns the number of documents truncated by this index writer,
+    /// because they exceeded the maximum number of tokens per document.
+    ///
+    /// See `.set_max_tokens_per_doc(...)`.
+    pub fn num_truncated_docs(&self) -> u64 {
+        self.segment_updater.num_truncated_docs()
+    }
+
+    /// Sets the maximum number of documents of the segments
+    /// written by the indexing threads.
+    ///
+    /// An indexing thread flushes its segment as soon as it reaches
+    /// this number of documents, even if its memory budget is not
+    /// exhausted. It is capped at `MAX_DOC_LIMIT`, which is the default.
+    ///
+    /// Merges are not affected: they fail if the merged segment would
+    /// exceed `MAX_DOC_LIMIT`.
+    ///
+    /// # Panics
+    /// Panics if `max_docs_per_segment` is `0`.
+    pub fn set_max_docs_per_segment(&self, max_docs_per_segment: u32) {
+        assert!(
+            max_docs_per_segment > 0,
+            "The maximum number of documents per segment must be positive."
+        );
+        self.segment_updater
+            .set_max_docs_per_segment(max_docs_per_segment.min(MAX_DOC_LIMIT));
+    }
+
     fn start_workers(&mut self) -> Result<()> {
         for _ in 0..self.num_threads {
             self.add_indexing_worker()?;
@@ -467,7 +630,7 @@ impl IndexWriter {
         let (mut document_sender, mut document_receiver): (
             DocumentSender,
             DocumentReceiver,
-        ) = channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
+        ) = channel::bounded(self.pipeline_capacity);
         swap(&mut self.document_sender, &mut document_sender);
         swap(&mut self.document_receiver, &mut document_receiver);
         document_receiver
@@ -500,6 +663,7 @@ impl IndexWriter {
             &self.index,
             self.num_threads,
             self.heap_size_in_bytes_per_thread,
+            self.pipeline_capacity,
             directory_lock,
         )?;
 
@@ -521,6 +685,12 @@ impl IndexWriter {
 
     /// Prepares a commit.
     ///
+    /// If one of the indexing threads failed or panicked since the
+    /// last commit, the error is returned, and a new indexing thread
+    /// is started in its place. The documents it was indexing are lost,
+    /// while the segments produced by the other threads are kept.
+    /// Call `.rollback()` to discard them as well.
+    ///
     /// Calling `prepare_commit()` will cut the indexing
     /// queue. All pending documents will be sent to the
     /// indexing workers. They will then terminate, regardless
@@ -563,17 +733,28 @@ impl IndexWriter {
             &mut self.workers_join_handle,
         );
 
+        let mut indexing_error = None;
         for worker_handle in former_workers_join_handle {
             let indexing_worker_result = worker_handle
                 .join()
-                .map_err(|e| TantivyError::ErrorInThread(format!("{:?}", e)))?;
+                .map_err(|e| TantivyError::ErrorInThread(format!("{:?}", e)))
+                .and_then(|result| result);
 
-            indexing_worker_result?;
-            // add a new worker for the next generation.
+            // add a new worker for the next generation,
+            // even if the former worker failed.
             self.add_indexing_worker()?;
+
+            if let Err(error) = indexing_worker_result {
+                if indexing_error.is_none() {
+                    indexing_error = Some(error);
+                }
+            }
+        }
+        if let Some(error) = indexing_error {
+            return Err(error);
         }
 
-        let commit_opstamp = self.stamper.stamp();
+        let commit_opstamp = self.stamper.stamp()?;
         let prepared_commit = PreparedCommit::new(self, commit_opstamp);
         info!("Prepared commit {}", commit_opstamp);
         Ok(prepared_commit)
@@ -609,11 +790,13 @@ impl IndexWriter {
     ///
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
-    pub fn delete_term(&mut self, term: Term) -> u64 {
-        let opstamp = self.stamper.stamp();
+    ///
+    /// Returns an error if the index writer ran out of opstamps.
+    pub fn delete_term(&mut self, term: Term) -> Result<u64> {
+        let opstamp = self.stamper.stamp()?;
         let delete_operation = DeleteOperation { opstamp, term };
         self.delete_queue.push(delete_operation);
-        opstamp
+        Ok(opstamp)
     }
 
     /// Returns the opstamp of the last successful commit.
@@ -627,9 +810,24 @@ impl IndexWriter {
         self.committed_opstamp
     }
 
+    /// Returns the number of documents waiting in the indexing pipeline.
+    ///
+    /// This number never exceeds the pipeline capacity.
+    /// A batch of documents submitted via `.run(...)` counts as a single entry.
+    pub fn pending_documents(&self) -> usize {
+        self.document_sender.len()
+    }
+
+    /// Returns the maximum number of documents that can wait in
+    /// the indexing pipeline before `add_document` blocks.
+    pub fn pipeline_capacity(&self) -> usize {
+        self.pipeline_capacity
+    }
+
     /// Adds a document.
     ///
     /// If the indexing pipeline is full, this call may block.
+    /// See `try_add_document` for a non-blocking alternative.
     ///
     /// The opstamp is an increasing `u64` that can
     /// be used by the client to align commits with its own
@@ -637,14 +835,84 @@ impl IndexWriter {
     ///
     /// Currently it represents the number of documents that
     /// have been added since the creation of the index.
-    pub fn add_document(&mut self, document: Document) -> u64 {
-        let opstamp = self.stamper.stamp();
+    ///
+    /// If an indexing thread panicked, the documents it receives are
+    /// discarded until the next commit, which returns a
+    /// `TantivyError::IndexingPanicked` error.
+    ///
+    /// Returns an error if the index writer ran out of opstamps.
+    pub fn add_document(&mut self, document: Document) -> Result<u64> {
+        let opstamp = self.stamper.stamp()?;
         let add_operation = AddOperation { opstamp, document };
-        let send_result = self.document_sender.send(add_operation);
+        let send_result = self.document_sender.send(vec![add_operation]);
         if let Err(e) = send_result {
             panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
         }
-        opstamp
+        Ok(opstamp)
+    }
+
+    /// Adds a document, unless the indexing pipeline is full.
+    ///
+    /// If the indexing pipeline is full, the document is handed back
+    /// in a `TryAddDocumentError::WouldBlock` error.
+    /// In that case, the opstamp that was reserved for the document is
+    /// simply skipped: opstamps remain increasing, but are not necessarily
+    /// consecutive.
+    pub fn try_add_document(
+        &mut self,
+        document: Document,
+    ) -> ::std::result::Result<u64, TryAddDocumentError> {
+        let opstamp = self.stamper.stamp().map_err(TryAddDocumentError::Error)?;
+        let add_operation = AddOperation { opstamp, document };
+        match self.document_sender.try_send(vec![add_operation]) {
+            Ok(()) => Ok(opstamp),
+            Err(channel::TrySendError::Full(mut add_batch)) => {
+                let add_operation = add_batch
+                    .pop()
+                    .expect("The batch contains exactly one document");
+                Err(TryAddDocumentError::WouldBlock(add_operation.document))
+            }
+            Err(channel::TrySendError::Disconnected(_)) => {
+                panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked.");
+            }
+        }
+    }
+
+    /// Runs a batch of operations under a single opstamp, which is returned.
+    ///
+    /// The documents of the batch are sent together to a single indexing
+    /// thread, in order. The batch can therefore not be split by a commit:
+    /// the whole batch becomes visible at once.
+    ///
+    /// Because all of the operations share the same opstamp, the delete
+    /// operations of the batch only affect the documents added *before*
+    /// the batch. They never affect the documents added by the batch itself,
+    /// regardless of their position within the batch.
+    /// For instance, `[Delete(id:1), Add(id:1 ...)]` replaces the previous
+    /// documents with `id:1` by the new one.
+    ///
+    /// If the indexing pipeline is full, this call may block.
+    ///
+    /// Returns an error if the index writer ran out of opstamps.
+    pub fn run(&mut self, user_operations: Vec<UserOperation>) -> Result<u64> {
+        let opstamp = self.stamper.stamp()?;
+        let mut add_batch: AddBatch = Vec::new();
+        for user_operation in user_operations {
+            match user_operation {
+                UserOperation::Delete(term) => {
+                    self.delete_queue.push(DeleteOperation { opstamp, term });
+                }
+                UserOperation::Add(document) => {
+                    add_batch.push(AddOperation { opstamp, document });
+                }
+            }
+        }
+        if !add_batch.is_empty() {
+            if let Err(e) = self.document_sender.send(add_batch) {
+                panic!("Failed to index document. Sending to indexing channel failed. This probably means all of the indexing threads have panicked. {:?}", e);
+            }
+        }
+        Ok(opstamp)
     }
 }
 
@@ -652,9 +920,13 @@ impl IndexWriter {
 mod tests {
 
     use super::initial_table_size;
+    use super::TryAddDocumentError;
     use error::*;
     use indexer::NoMergePolicy;
-    use schema::{self, Document};
+    use schema::{self, Document, IndexRecordOption, TextFieldIndexing, TextOptions};
+    use std::thread;
+    use std::time::Duration;
+    use tokenizer::{RawTokenizer, Tokenizer};
     use Index;
     use Term;
 
@@ -729,14 +1001,14 @@ mod tests {
         {
             // writing the segment
             let mut index_writer = index.writer(3_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a"));
+            index_writer.add_document(doc!(text_field=>"a")).unwrap();
             index_writer.rollback().unwrap();
 
             assert_eq!(index_writer.commit_opstamp(), 0u64);
             assert_eq!(num_docs_containing("a"), 0);
             {
-                index_writer.add_document(doc!(text_field=>"b"));
-                index_writer.add_document(doc!(text_field=>"c"));
+                index_writer.add_document(doc!(text_field=>"b")).unwrap();
+                index_writer.add_document(doc!(text_field=>"c")).unwrap();
             }
             assert_eq!(index_writer.commit().unwrap(), 2u64);
             index.load_searchers().unwrap();
@@ -765,13 +1037,13 @@ mod tests {
             for _doc in 0..100 {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().expect("commit failed");
             for _doc in 0..100 {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             // this should create 8 segments and trigger a merge.
             index_writer.commit().expect("commit failed");
@@ -796,7 +1068,7 @@ mod tests {
             let mut index_writer = index.writer(12_000_000).unwrap();
             // create 8 segments with 100 tiny docs
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "a"));
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
             }
             {
                 let mut prepared_commit = index_writer.prepare_commit().expect("commit failed");
@@ -809,7 +1081,7 @@ mod tests {
                 assert_eq!(metas.payload.unwrap(), "first commit");
             }
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "a"));
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
             }
             index_writer.commit().unwrap();
             {
@@ -830,7 +1102,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(4, 12_000_000).unwrap();
             // create 8 segments with 100 tiny docs
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "a"));
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
             }
             {
                 let mut prepared_commit = index_writer.prepare_commit().expect("commit failed");
@@ -843,7 +1115,7 @@ mod tests {
                 assert!(metas.payload.is_none());
             }
             for _doc in 0..100 {
-                index_writer.add_document(doc!(text_field => "b"));
+                index_writer.add_document(doc!(text_field => "b")).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -857,6 +1129,225 @@ mod tests {
         assert_eq!(num_docs_containing("b"), 100);
     }
 
+    /// Raw tokenizer that sleeps before tokenizing each text,
+    /// in order to simulate an indexing pipeline slower than its producer.
+    #[derive(Clone)]
+    struct SlowTokenizer;
+
+    impl<'a> Tokenizer<'a> for SlowTokenizer {
+        type TokenStreamImpl = <RawTokenizer as Tokenizer<'a>>::TokenStreamImpl;
+
+        fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+            thread::sleep(Duration::from_millis(5));
+            RawTokenizer.token_stream(text)
+        }
+    }
+
+    #[test]
+    fn test_bounded_pipeline() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("slow")
+                .set_index_option(IndexRecordOption::Basic),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register("slow", SlowTokenizer);
+        assert!(index.writer_with_pipeline_capacity(1, 3_000_000, 0).is_err());
+        let mut index_writer = index
+            .writer_with_pipeline_capacity(1, 3_000_000, 2)
+            .unwrap();
+        assert_eq!(index_writer.pipeline_capacity(), 2);
+        let mut num_would_block = 0;
+        for _ in 0..20 {
+            match index_writer.try_add_document(doc!(text_field => "a")) {
+                Ok(_) => {}
+                Err(TryAddDocumentError::WouldBlock(doc)) => {
+                    num_would_block += 1;
+                    index_writer.add_document(doc).unwrap();
+                }
+                Err(TryAddDocumentError::Error(e)) => panic!("Failed to stamp: {:?}", e),
+            }
+            assert!(index_writer.pending_documents() <= 2);
+        }
+        assert!(num_would_block > 0);
+        index_writer.commit().unwrap();
+        assert_eq!(index_writer.pending_documents(), 0);
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.doc_freq(&Term::from_field_text(text_field, "a")), 20);
+    }
+
+    /// Raw tokenizer that panics when asked to tokenize "panic".
+    #[derive(Clone)]
+    struct PanickingTokenizer;
+
+    impl<'a> Tokenizer<'a> for PanickingTokenizer {
+        type TokenStreamImpl = <RawTokenizer as Tokenizer<'a>>::TokenStreamImpl;
+
+        fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+            if text == "panic" {
+                panic!("tokenizer panicked");
+            }
+            RawTokenizer.token_stream(text)
+        }
+    }
+
+    #[test]
+    fn test_indexing_thread_panic() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("panicking")
+                .set_index_option(IndexRecordOption::Basic),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index
+            .tokenizers()
+            .register("panicking", PanickingTokenizer);
+        let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer
+            .add_document(doc!(text_field => "panic"))
+            .unwrap();
+        for _ in 0..100 {
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+        }
+        match index_writer.commit() {
+            Err(TantivyError::IndexingPanicked { message }) => {
+                assert_eq!(message, "tokenizer panicked");
+            }
+            _ => panic!("Expected an IndexingPanicked error"),
+        }
+        // the writer is still usable after the error.
+        index_writer.add_document(doc!(text_field => "c")).unwrap();
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        assert_eq!(searcher.doc_freq(&Term::from_field_text(text_field, "c")), 1);
+        index_writer
+            .add_document(doc!(text_field => "panic"))
+            .unwrap();
+        drop(index_writer);
+    }
+
+    #[test]
+    fn test_run_batch() {
+        use collector::CountCollector;
+        use indexer::UserOperation;
+        use query::TermQuery;
+
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let id_field = schema_builder.add_text_field("id", schema::STRING);
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(2, 12_000_000).unwrap();
+        index_writer
+            .add_document(doc!(id_field => "1", text_field => "old"))
+            .unwrap();
+        index_writer
+            .add_document(doc!(id_field => "2", text_field => "old"))
+            .unwrap();
+        index_writer.commit().unwrap();
+
+        let opstamp = index_writer
+            .run(vec![
+                UserOperation::Delete(Term::from_field_text(id_field, "1")),
+                UserOperation::Add(doc!(id_field => "1", text_field => "new")),
+                UserOperation::Add(doc!(id_field => "3", text_field => "new")),
+                // deletes of the batch do not affect the documents of the batch.
+                UserOperation::Delete(Term::from_field_text(id_field, "3")),
+            ])
+            .unwrap();
+        assert_eq!(
+            index_writer.add_document(doc!(id_field => "4")).unwrap(),
+            opstamp + 1
+        );
+        index_writer.commit().unwrap();
+
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 4);
+        let count = |field, text| {
+            let query = TermQuery::new(
+                Term::from_field_text(field, text),
+                IndexRecordOption::Basic,
+            );
+            let mut count_collector = CountCollector::default();
+            searcher.search(&query, &mut count_collector).unwrap();
+            count_collector.count()
+        };
+        assert_eq!(count(id_field, "1"), 1);
+        assert_eq!(count(id_field, "2"), 1);
+        assert_eq!(count(id_field, "3"), 1);
+        assert_eq!(count(text_field, "old"), 1);
+        assert_eq!(count(text_field, "new"), 2);
+    }
+
+    #[test]
+    fn test_max_tokens_per_doc() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", schema::TEXT);
+        let body_field = schema_builder.add_text_field("body", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_max_tokens_per_doc(4);
+            index_writer
+                .add_document(doc!(
+                    title_field => "a b",
+                    body_field => "c d e f"
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    title_field => "a",
+                    body_field => "c d"
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+            assert_eq!(index_writer.num_truncated_docs(), 1);
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let doc_freq = |field, text| searcher.doc_freq(&Term::from_field_text(field, text));
+        assert_eq!(doc_freq(title_field, "a"), 2);
+        assert_eq!(doc_freq(title_field, "b"), 1);
+        assert_eq!(doc_freq(body_field, "c"), 2);
+        assert_eq!(doc_freq(body_field, "d"), 2);
+        assert_eq!(doc_freq(body_field, "e"), 0);
+        assert_eq!(doc_freq(body_field, "f"), 0);
+    }
+
+    #[test]
+    fn test_max_docs_per_segment() {
+        let mut schema_builder = schema::SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", schema::TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer.set_max_docs_per_segment(3);
+            for _ in 0..10 {
+                index_writer.add_document(doc!(text_field => "a")).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 10);
+        let mut segment_max_docs: Vec<u32> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.max_doc())
+            .collect();
+        segment_max_docs.sort();
+        assert_eq!(segment_max_docs, vec![1, 3, 3, 3]);
+    }
+
     #[test]
     fn test_hashmap_size() {
         assert_eq!(initial_table_size(100_000), 12);
@@ -875,12 +1366,12 @@ mod tests {
 
         let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
         for _ in 0..100 {
-            index_writer.add_document(doc!(text_field => "a"));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
         }
         index_writer.commit().unwrap();
         fail::cfg("RAMDirectory::atomic_write", "return(error_write_failed)").unwrap();
         for _ in 0..100 {
-            index_writer.add_document(doc!(text_field => "b"));
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
         }
         assert!(index_writer.commit().is_err());
         index.load_searchers().unwrap();
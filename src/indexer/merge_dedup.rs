@@ -0,0 +1,286 @@
+use core::SegmentReader;
+use error::TantivyError;
+use fastfield::FastFieldReader;
+use schema::{Cardinality, Field, FieldType, Schema};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use DocId;
+use Result;
+
+/// Rule deciding which of the documents sharing the same key
+/// is kept by a `MergeDedupPolicy`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum KeepRule {
+    /// Keeps the document with the highest value of the given
+    /// single-valued `u64` or `i64` fast field, typically a version
+    /// number or a timestamp.
+    ///
+    /// On a tie, the document of the last merged segment is kept.
+    MaxOf(Field),
+}
+
+/// Collapses the documents sharing the same key while merging segments,
+/// similarly to the compaction of a log-structured store.
+///
+/// Append-only pipelines may write several versions of the same entity,
+/// without deleting the previous versions. With this policy, each merge
+/// only keeps one document per value of the `key_field`, as chosen by the
+/// `keep` rule: the other documents are dropped from the merged segment,
+/// as if they had been deleted.
+///
+/// Deduplication only happens among the segments being merged: documents
+/// of other segments, or flushed after the merge started, are not affected.
+/// Deleted documents are ignored altogether, so that deleting the most
+/// recent version of an entity does not drop its older versions.
+///
+/// The `key_field` must be a single-valued `u64` or `i64` fast field.
+///
+/// See `IndexWriter::set_merge_dedup_policy`. The number of collapsed
+/// documents is available on the `SegmentMeta` of the merged segment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MergeDedupPolicy {
+    /// Field identifying the documents to collapse.
+    pub key_field: Field,
+    /// Rule deciding which of the documents sharing a key is kept.
+    pub keep: KeepRule,
+}
+
+// Opens a single-valued `u64` or `i64` fast field, as `u64`s.
+// The mapping of `i64`s to `u64`s preserves their order.
+fn u64_reader(reader: &SegmentReader, field: Field) -> Result<FastFieldReader<u64>> {
+    match *reader.schema().get_field_entry(field).field_type() {
+        FieldType::I64(_) => reader
+            .fast_field_reader::<i64>(field)
+            .map(|ff_reader| ff_reader.cast()),
+        _ => reader.fast_field_reader::<u64>(field),
+    }
+}
+
+// Checks that the field is a single-valued `u64` or `i64` fast field.
+fn check_u64_fast_field(schema: &Schema, field: Field) -> Result<()> {
+    let field_entry = schema
+        .fields()
+        .get(field.0 as usize)
+        .ok_or_else(|| TantivyError::SchemaError(format!("Unknown field {:?}.", field)))?;
+    let cardinality = match *field_entry.field_type() {
+        FieldType::U64(ref options) | FieldType::I64(ref options) => {
+            options.get_fastfield_cardinality()
+        }
+        _ => None,
+    };
+    if cardinality != Some(Cardinality::SingleValue) {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not a single-valued u64 or i64 fast field.",
+            field_entry.name()
+        )));
+    }
+    Ok(())
+}
+
+impl MergeDedupPolicy {
+    /// Checks that the key field and the field of the keep rule are
+    /// single-valued `u64` or `i64` fast fields of the schema.
+    pub(crate) fn validate(&self, schema: &Schema) -> Result<()> {
+        let KeepRule::MaxOf(version_field) = self.keep;
+        check_u64_fast_field(schema, self.key_field)?;
+        check_u64_fast_field(schema, version_field)
+    }
+
+    /// Returns, for each of the readers, the documents superseded by
+    /// another document with the same key.
+    pub(crate) fn superseded_docs(&self, readers: &[SegmentReader]) -> Result<Vec<Vec<DocId>>> {
+        let KeepRule::MaxOf(version_field) = self.keep;
+        // key -> (version, reader ordinal, doc) of the best document so far.
+        let mut best_docs: HashMap<u64, (u64, usize, DocId)> = HashMap::new();
+        let mut superseded_docs = vec![Vec::new(); readers.len()];
+        for (reader_ord, reader) in readers.iter().enumerate() {
+            let key_reader = u64_reader(reader, self.key_field)?;
+            let version_reader = u64_reader(reader, version_field)?;
+            for doc in reader.doc_ids_alive() {
+                let key = key_reader.get(doc);
+                let version = version_reader.get(doc);
+                let candidate = (version, reader_ord, doc);
+                let (superseded_reader_ord, superseded_doc) = match best_docs.entry(key) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(candidate);
+                        continue;
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let (best_version, best_reader_ord, best_doc) = *entry.get();
+                        if version >= best_version {
+                            entry.insert(candidate);
+                            (best_reader_ord, best_doc)
+                        } else {
+                            (reader_ord, doc)
+                        }
+                    }
+                };
+                superseded_docs[superseded_reader_ord].push(superseded_doc);
+            }
+        }
+        Ok(superseded_docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{KeepRule, MergeDedupPolicy};
+    use core::{MergeMetrics, Metrics};
+    use futures::Future;
+    use indexer::NoMergePolicy;
+    use schema::{Cardinality, Field, IntOptions, SchemaBuilder, Term, FAST, INT_INDEXED, TEXT};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use Index;
+    use TantivyError;
+
+    #[derive(Default)]
+    struct CollapsedDocsMetrics(AtomicUsize);
+
+    impl Metrics for CollapsedDocsMetrics {
+        fn on_merge(&self, merge_metrics: &MergeMetrics) {
+            self.0
+                .fetch_add(merge_metrics.num_collapsed_docs as usize, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_merge_dedup() {
+        let mut schema_builder = SchemaBuilder::default();
+        let key_field = schema_builder.add_u64_field("key", INT_INDEXED | FAST);
+        let version_field = schema_builder.add_i64_field("version", FAST);
+        let schema = schema_builder.build();
+        let mut index = Index::create_in_ram(schema);
+        let metrics = Arc::new(CollapsedDocsMetrics::default());
+        index.set_metrics(metrics.clone());
+        let segment_ids = {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer
+                .add_document(doc!(key_field => 1u64, version_field => -1i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 2u64, version_field => 1i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 4u64, version_field => 1i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(key_field => 1u64, version_field => 3i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 2u64, version_field => -2i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 3u64, version_field => 1i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(key_field => 1u64, version_field => 2i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 3u64, version_field => 2i64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 4u64, version_field => 2i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            // Deleted documents are ignored, whatever their version.
+            index_writer
+                .delete_term(Term::from_field_u64(key_field, 4u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(key_field => 4u64, version_field => 0i64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index.searchable_segment_ids().unwrap()
+        };
+        assert_eq!(segment_ids.len(), 4);
+
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer
+                .set_merge_dedup_policy(MergeDedupPolicy {
+                    key_field,
+                    keep: KeepRule::MaxOf(version_field),
+                }).unwrap();
+            let merged_segment_meta = index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+            assert_eq!(merged_segment_meta.num_docs(), 4);
+            assert_eq!(merged_segment_meta.num_collapsed_docs(), 4);
+            index_writer.wait_merging_threads().unwrap();
+        }
+        assert_eq!(metrics.0.load(Ordering::SeqCst), 4);
+
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let key_reader = segment_reader.fast_field_reader::<u64>(key_field).unwrap();
+        let version_reader = segment_reader
+            .fast_field_reader::<i64>(version_field)
+            .unwrap();
+        let mut docs: Vec<(u64, i64)> = segment_reader
+            .doc_ids_alive()
+            .map(|doc| (key_reader.get(doc), version_reader.get(doc)))
+            .collect();
+        docs.sort();
+        assert_eq!(docs, vec![(1, 3), (2, 1), (3, 2), (4, 0)]);
+    }
+
+    #[test]
+    fn test_merge_dedup_disabled_by_default() {
+        let mut schema_builder = SchemaBuilder::default();
+        let key_field = schema_builder.add_u64_field("key", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(key_field => 1u64)).unwrap();
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(key_field => 1u64)).unwrap();
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        let merged_segment_meta = index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        assert_eq!(merged_segment_meta.num_docs(), 2);
+        assert_eq!(merged_segment_meta.num_collapsed_docs(), 0);
+        index_writer.wait_merging_threads().unwrap();
+    }
+
+    #[test]
+    fn test_merge_dedup_policy_validation() {
+        let mut schema_builder = SchemaBuilder::default();
+        let key_field = schema_builder.add_u64_field("key", FAST);
+        let version_field = schema_builder.add_i64_field("version", FAST);
+        let indexed_field = schema_builder.add_u64_field("indexed", INT_INDEXED);
+        let multivalued_field = schema_builder.add_u64_field(
+            "multivalued",
+            IntOptions::default().set_fast(Cardinality::MultiValues),
+        );
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        let policy = |key_field, version_field| MergeDedupPolicy {
+            key_field,
+            keep: KeepRule::MaxOf(version_field),
+        };
+        assert!(index_writer
+            .set_merge_dedup_policy(policy(key_field, version_field))
+            .is_ok());
+        for &(key_field, version_field) in &[
+            (indexed_field, version_field),
+            (key_field, indexed_field),
+            (multivalued_field, version_field),
+            (key_field, text_field),
+            (key_field, Field(42)),
+        ] {
+            match index_writer.set_merge_dedup_policy(policy(key_field, version_field)) {
+                Err(TantivyError::SchemaError(_)) => {}
+                _ => panic!("Expected a schema error"),
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use core::Segment;
 use core::SegmentReader;
 use core::SerializableSegment;
 use docset::DocSet;
+use error::TantivyError;
 use fastfield::DeleteBitSet;
 use fastfield::FastFieldReader;
 use fastfield::FastFieldSerializer;
@@ -9,6 +10,7 @@ use fastfield::MultiValueIntFastFieldReader;
 use fieldnorm::FieldNormReader;
 use fieldnorm::FieldNormsSerializer;
 use fieldnorm::FieldNormsWriter;
+use indexer::MergeDedupPolicy;
 use indexer::SegmentSerializer;
 use itertools::Itertools;
 use postings::InvertedIndexSerializer;
@@ -23,6 +25,7 @@ use termdict::TermMerger;
 use termdict::TermOrdinal;
 use DocId;
 use Result;
+use MAX_DOC_LIMIT;
 
 fn compute_total_num_tokens(readers: &[SegmentReader], field: Field) -> u64 {
     let mut total_tokens = 0u64;
@@ -140,14 +143,21 @@ impl DeltaComputer {
 impl IndexMerger {
     pub fn open(schema: Schema, segments: &[Segment]) -> Result<IndexMerger> {
         let mut readers = vec![];
-        let mut max_doc: u32 = 0u32;
+        let mut max_doc: u64 = 0u64;
         for segment in segments {
             if segment.meta().num_docs() > 0 {
                 let reader = SegmentReader::open(segment)?;
-                max_doc += reader.num_docs();
+                max_doc += u64::from(reader.num_docs());
                 readers.push(reader);
             }
         }
+        if max_doc > u64::from(MAX_DOC_LIMIT) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "The merged segment would have {} documents, more than the limit of {}",
+                max_doc, MAX_DOC_LIMIT
+            )));
+        }
+        let max_doc = max_doc as u32;
         Ok(IndexMerger {
             schema,
             readers,
@@ -155,6 +165,30 @@ impl IndexMerger {
         })
     }
 
+    /// Drops the documents superseded by another document with the same key,
+    /// as defined by the `MergeDedupPolicy`.
+    ///
+    /// Returns the number of dropped documents.
+    pub fn dedup(&mut self, dedup_policy: &MergeDedupPolicy) -> Result<u32> {
+        let superseded_docs = dedup_policy.superseded_docs(&self.readers[..])?;
+        let mut num_collapsed_docs = 0u32;
+        let mut readers = Vec::with_capacity(self.readers.len());
+        for (reader, docs) in self.readers.iter().zip(superseded_docs) {
+            if docs.is_empty() {
+                readers.push(reader.clone());
+                continue;
+            }
+            num_collapsed_docs += docs.len() as u32;
+            let reader = reader.with_deleted_docs(&docs[..])?;
+            if reader.num_docs() > 0 {
+                readers.push(reader);
+            }
+        }
+        self.readers = readers;
+        self.max_doc -= num_collapsed_docs;
+        Ok(num_collapsed_docs)
+    }
+
     fn write_fieldnorms(&self, fieldnorms_serializer: &mut FieldNormsSerializer) -> Result<()> {
         let fields = FieldNormsWriter::fields_with_fieldnorm(&self.schema);
         let mut fieldnorms_data = Vec::with_capacity(self.max_doc as usize);
@@ -208,8 +242,10 @@ impl IndexMerger {
                     // They can be implemented using what is done
                     // for facets in the future.
                 }
-                FieldType::Bytes => {
-                    self.write_bytes_fast_field(field, fast_field_serializer)?;
+                FieldType::Bytes(ref bytes_options) => {
+                    if bytes_options.is_fast() {
+                        self.write_bytes_fast_field(field, fast_field_serializer)?;
+                    }
                 }
             }
         }
@@ -565,10 +601,11 @@ impl IndexMerger {
                             segment_postings.positions(&mut positions_buffer);
 
                             let delta_positions = delta_computer.compute_delta(&positions_buffer);
-                            field_serializer.write_doc(
+                            field_serializer.write_doc_with_payload(
                                 remapped_doc_id,
                                 term_freq,
                                 delta_positions,
+                                segment_postings.payload(),
                             )?;
                         }
                         if !segment_postings.advance() {
@@ -608,9 +645,20 @@ impl IndexMerger {
     fn write_storable_fields(&self, store_writer: &mut StoreWriter) -> Result<()> {
         for reader in &self.readers {
             let store_reader = reader.get_store_reader();
-            if reader.num_deleted_docs() > 0 {
+            // Fields stored in the segment may have been removed from
+            // the doc store of the merged segment.
+            let drops_stored_fields = reader
+                .schema()
+                .fields()
+                .iter()
+                .zip(self.schema.fields())
+                .any(|(reader_entry, field_entry)| {
+                    reader_entry.is_stored() && !field_entry.is_stored()
+                });
+            if reader.num_deleted_docs() > 0 || drops_stored_fields {
                 for doc_id in reader.doc_ids_alive() {
-                    let doc = store_reader.get(doc_id)?;
+                    let mut doc = store_reader.get(doc_id)?;
+                    doc.filter_fields(|field| self.schema.get_field_entry(field).is_stored());
                     store_writer.store(&doc)?;
                 }
             } else {
@@ -688,21 +736,21 @@ mod tests {
                     doc.add_text(text_field, "af b");
                     doc.add_u64(score_field, 3);
                     add_score_bytes(&mut doc, 3);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c");
                     doc.add_u64(score_field, 5);
                     add_score_bytes(&mut doc, 5);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c d");
                     doc.add_u64(score_field, 7);
                     add_score_bytes(&mut doc, 7);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().expect("committed");
             }
@@ -714,14 +762,14 @@ mod tests {
                     doc.add_text(text_field, "af b");
                     doc.add_u64(score_field, 11);
                     add_score_bytes(&mut doc, 11);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c g");
                     doc.add_u64(score_field, 13);
                     add_score_bytes(&mut doc, 13);
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().expect("Commit failed");
             }
@@ -853,22 +901,30 @@ mod tests {
 
         {
             // a first commit
-            index_writer.add_document(doc!(
+            index_writer
+                .add_document(doc!(
                     text_field => "a b d",
                     score_field => 1u64,
                     bytes_score_field => vec![0u8, 0, 0, 1],
-                ));
-            index_writer.add_document(doc!(
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
                     text_field => "b c",
                     score_field => 2u64,
                     bytes_score_field => vec![0u8, 0, 0, 2],
-                ));
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
-            index_writer.add_document(doc!(
+                ))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
                     text_field => "c d",
                     score_field => 3u64,
                     bytes_score_field => vec![0u8, 0, 0, 3],
-                ));
+                ))
+                .unwrap();
             index_writer.commit().expect("committed");
             index.load_searchers().unwrap();
             let ref searcher = *index.searcher();
@@ -894,28 +950,40 @@ mod tests {
         }
         {
             // a second commit
-            index_writer.add_document(doc!(
+            index_writer
+                .add_document(doc!(
                     text_field => "a d e",
                     score_field => 4_000u64,
                     bytes_score_field => vec![0u8, 0, 0, 4],
-                ));
-            index_writer.add_document(doc!(
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
                     text_field => "e f",
                     score_field => 5_000u64,
                     bytes_score_field => vec![0u8, 0, 0, 5],
-                ));
-            index_writer.delete_term(Term::from_field_text(text_field, "a"));
-            index_writer.delete_term(Term::from_field_text(text_field, "f"));
-            index_writer.add_document(doc!(
+                ))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "a"))
+                .unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "f"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
                     text_field => "f g",
                     score_field => 6_000u64,
                     bytes_score_field => vec![0u8, 0, 23, 112],
-                ));
-            index_writer.add_document(doc!(
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
                     text_field => "g h",
                     score_field => 7_000u64,
                     bytes_score_field => vec![0u8, 0, 27, 88],
-                ));
+                ))
+                .unwrap();
             index_writer.commit().expect("committed");
             index.load_searchers().unwrap();
             let searcher = index.searcher();
@@ -1022,7 +1090,9 @@ mod tests {
         }
         {
             // test a commit with only deletes
-            index_writer.delete_term(Term::from_field_text(text_field, "c"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "c"))
+                .unwrap();
             index_writer.commit().unwrap();
 
             index.load_searchers().unwrap();
@@ -1121,7 +1191,9 @@ mod tests {
 
         {
             // Test removing all docs
-            index_writer.delete_term(Term::from_field_text(text_field, "g"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "g"))
+                .unwrap();
             let segment_ids = index
                 .searchable_segment_ids()
                 .expect("Searchable segments failed.");
@@ -1151,7 +1223,7 @@ mod tests {
                 for facet in doc_facets {
                     doc.add_facet(facet_field, Facet::from(facet));
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             };
 
             index_doc(&mut index_writer, &["/top/a/firstdoc", "/top/b"]);
@@ -1242,7 +1314,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             let facet = Facet::from_path(vec!["top", "a", "firstdoc"]);
             let facet_term = Term::from_facet(facet_field, &facet);
-            index_writer.delete_term(facet_term);
+            index_writer.delete_term(facet_term).unwrap();
             index_writer.commit().unwrap();
             index.load_searchers().unwrap();
             test_searcher(
@@ -1272,11 +1344,13 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             let mut doc = Document::default();
             doc.add_u64(int_field, 1);
-            index_writer.add_document(doc.clone());
+            index_writer.add_document(doc.clone()).unwrap();
             index_writer.commit().expect("commit failed");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
             index_writer.commit().expect("commit failed");
-            index_writer.delete_term(Term::from_field_u64(int_field, 1));
+            index_writer
+                .delete_term(Term::from_field_u64(int_field, 1))
+                .unwrap();
             index_writer.commit().expect("commit failed");
         }
         index.load_searchers().unwrap();
@@ -1316,7 +1390,7 @@ mod tests {
                 for &val in int_vals {
                     doc.add_u64(int_field, val);
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             };
 
             index_doc(&mut index_writer, &[1, 2]);
@@ -3,12 +3,16 @@ mod directory_lock;
 mod doc_opstamp_mapping;
 pub mod index_writer;
 mod log_merge_policy;
+mod merge_dedup;
 pub mod merge_policy;
 pub mod merger;
 pub mod operation;
 mod prepared_commit;
+mod recovery;
+mod reshape;
 mod segment_entry;
 mod segment_manager;
+mod segment_metadata;
 mod segment_register;
 pub mod segment_serializer;
 pub mod segment_updater;
@@ -18,12 +22,21 @@ mod stamper;
 pub(crate) use self::directory_lock::DirectoryLock;
 pub use self::directory_lock::LockType;
 
-pub use self::index_writer::IndexWriter;
+pub use self::index_writer::{IndexWriter, TryAddDocumentError};
 pub use self::log_merge_policy::LogMergePolicy;
+pub use self::merge_dedup::{KeepRule, MergeDedupPolicy};
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
+pub use self::operation::UserOperation;
 pub use self::prepared_commit::PreparedCommit;
+pub(crate) use self::recovery::recover_index;
+pub use self::recovery::{RecoveryReport, SkippedSegment};
+pub(crate) use self::reshape::rewrite_index;
+pub use self::reshape::FieldReshape;
 pub use self::segment_entry::{SegmentEntry, SegmentState};
 pub use self::segment_manager::SegmentManager;
+pub use self::segment_metadata::{
+    KeepCommonMetadata, MergeMetadataCombiner, SegmentMetadataBuilder,
+};
 pub use self::segment_serializer::SegmentSerializer;
 pub use self::segment_writer::SegmentWriter;
 
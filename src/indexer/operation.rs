@@ -14,3 +14,12 @@ pub struct AddOperation {
     pub opstamp: u64,
     pub document: Document,
 }
+
+/// Operation that can be submitted in a batch to `IndexWriter::run`.
+#[derive(Eq, PartialEq, Debug)]
+pub enum UserOperation {
+    /// Adds a document.
+    Add(Document),
+    /// Deletes all of the documents containing a given term.
+    Delete(Term),
+}
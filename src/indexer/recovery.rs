@@ -0,0 +1,512 @@
+use common::CompositeFile;
+use common::HasLen;
+use core::Index;
+use core::IndexSettings;
+use core::SegmentComponent;
+use core::SegmentId;
+use core::SegmentMeta;
+use core::SegmentReader;
+use core::MANAGED_FILEPATH;
+use core::META_FILEPATH;
+use directory::{Directory, ManagedDirectory};
+use fastfield::DeleteBitSet;
+use fieldnorm::FieldNormsWriter;
+use indexer::segment_updater::save_metas;
+use indexer::LockType;
+use schema::Schema;
+use serde_json;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::result;
+use Result;
+use TantivyError;
+
+/// Content of the `.info` file of a segment, written when the segment
+/// is committed for the first time.
+///
+/// These files are only read by `Index::recover`: they tell the committed
+/// segments apart from the uncommitted ones, and the segments
+/// that were removed from the index (e.g. by a merge) apart from the others.
+#[derive(Debug, Serialize, Deserialize)]
+struct SegmentInfo {
+    /// Opstamp of the commit in which the segment first appeared.
+    opstamp: u64,
+    /// Segments removed from the index by this same commit,
+    /// typically the segments this segment was merged from.
+    removed_segments: Vec<SegmentId>,
+}
+
+// Segments listed in the current `meta.json`, if any.
+fn committed_segment_ids(directory: &Directory) -> HashSet<SegmentId> {
+    let segments_json = directory
+        .atomic_read(&META_FILEPATH)
+        .ok()
+        .and_then(|meta_data| serde_json::from_slice::<serde_json::Value>(&meta_data).ok())
+        .and_then(|meta_json| meta_json.get("segments").cloned());
+    match segments_json {
+        Some(serde_json::Value::Array(segments_json)) => segments_json
+            .iter()
+            .filter_map(|segment_json| segment_json.get("segment_id").cloned())
+            .filter_map(|segment_id_json| serde_json::from_value(segment_id_json).ok())
+            .collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// Writes the `.info` file of the segments committed for the first time.
+///
+/// This function is called before `meta.json` is overwritten with
+/// `segment_metas`, so that the segments it drops can be recorded.
+pub(crate) fn save_segment_infos(
+    segment_metas: &[SegmentMeta],
+    opstamp: u64,
+    directory: &mut Directory,
+) -> Result<()> {
+    let new_segment_metas: Vec<&SegmentMeta> = segment_metas
+        .iter()
+        .filter(|segment_meta| !directory.exists(&segment_meta.info_path()))
+        .collect();
+    if new_segment_metas.is_empty() {
+        return Ok(());
+    }
+    let segment_ids: HashSet<SegmentId> = segment_metas.iter().map(SegmentMeta::id).collect();
+    let segment_info = SegmentInfo {
+        opstamp,
+        removed_segments: committed_segment_ids(directory)
+            .into_iter()
+            .filter(|segment_id| !segment_ids.contains(segment_id))
+            .collect(),
+    };
+    let buffer = serde_json::to_vec(&segment_info)?;
+    for segment_meta in new_segment_metas {
+        directory.atomic_write(&segment_meta.info_path(), &buffer[..])?;
+    }
+    Ok(())
+}
+
+fn read_segment_info(
+    directory: &Directory,
+    segment_id: SegmentId,
+) -> result::Result<SegmentInfo, String> {
+    let path = SegmentMeta::new(segment_id, 0).info_path();
+    if !directory.exists(&path) {
+        return Err(format!(
+            "missing file {:?}, the segment was never committed",
+            path
+        ));
+    }
+    let data = directory
+        .atomic_read(&path)
+        .map_err(|err| format!("failed to open {:?}: {:?}", path, err))?;
+    serde_json::from_slice(&data).map_err(|err| format!("failed to read {:?}: {:?}", path, err))
+}
+
+/// A segment that was found in the directory, but could not
+/// be recovered.
+#[derive(Clone, Debug)]
+pub struct SkippedSegment {
+    /// Id of the segment.
+    pub segment_id: SegmentId,
+    /// Human readable reason why the segment was skipped.
+    pub reason: String,
+}
+
+/// Outcome of [`Index::recover`](../struct.Index.html#method.recover).
+#[derive(Clone, Debug)]
+pub struct RecoveryReport {
+    /// Segments listed in the rebuilt `meta.json`.
+    pub recovered_segments: Vec<SegmentMeta>,
+    /// Segments that were left out of the rebuilt `meta.json`.
+    pub skipped_segments: Vec<SkippedSegment>,
+    /// Opstamp written in the rebuilt `meta.json`: the opstamp of the
+    /// last commit that touched one of the recovered segments.
+    pub opstamp: u64,
+}
+
+/// Splits a file name of the form `<uuid>.<extension>`,
+/// and returns the opstamp of the delete files.
+///
+/// Returns `None` if the file does not belong to a segment.
+fn parse_segment_file(path: &Path) -> Option<(SegmentId, Option<u64>)> {
+    let filename = path.to_str()?;
+    let dot_pos = filename.find('.')?;
+    let segment_id = SegmentId::from_uuid_string(&filename[..dot_pos])?;
+    let extension = &filename[dot_pos + 1..];
+    if extension.ends_with(".del") {
+        let opstamp = extension[..extension.len() - 4].parse::<u64>().ok()?;
+        Some((segment_id, Some(opstamp)))
+    } else {
+        Some((segment_id, None))
+    }
+}
+
+/// Reads the number of documents of the segment from its field norms,
+/// which hold one byte per document for each indexed field.
+fn read_max_doc(index: &Index, segment_meta: &SegmentMeta) -> result::Result<u32, String> {
+    let schema = index.schema();
+    let field = *FieldNormsWriter::fields_with_fieldnorm(&schema)
+        .first()
+        .ok_or_else(|| "the schema does not have any indexed field".to_string())?;
+    let path = segment_meta.relative_path(SegmentComponent::FIELDNORMS);
+    let source = index
+        .directory()
+        .open_read(&path)
+        .map_err(|err| format!("failed to open {:?}: {:?}", path, err))?;
+    let composite_file = CompositeFile::open(&source)
+        .map_err(|err| format!("failed to read {:?}: {:?}", path, err))?;
+    let fieldnorms = composite_file
+        .open_read(field)
+        .ok_or_else(|| format!("{:?} does not contain the field norms of {:?}", path, field))?;
+    Ok(fieldnorms.len() as u32)
+}
+
+fn inspect_segment(
+    index: &Index,
+    segment_id: SegmentId,
+    delete_opstamp: Option<u64>,
+) -> result::Result<SegmentMeta, String> {
+    let mut segment_meta = SegmentMeta::new(segment_id, 0);
+    let missing_files: Vec<_> = SegmentComponent::iterator()
+        .filter(|&&component| component != SegmentComponent::DELETE)
+        .map(|&component| segment_meta.relative_path(component))
+        .filter(|path| !index.directory().exists(path))
+        .collect();
+    if !missing_files.is_empty() {
+        return Err(format!("missing files {:?}", missing_files));
+    }
+    let max_doc = read_max_doc(index, &segment_meta)?;
+    segment_meta = SegmentMeta::new(segment_id, max_doc);
+    if let Some(opstamp) = delete_opstamp {
+        let delete_meta = segment_meta.clone().with_delete_meta(0, opstamp);
+        let path = delete_meta.relative_path(SegmentComponent::DELETE);
+        let source = index
+            .directory()
+            .open_read(&path)
+            .map_err(|err| format!("failed to open {:?}: {:?}", path, err))?;
+        let expected_len = (max_doc as usize + 7) / 8;
+        if source.len() != expected_len {
+            return Err(format!(
+                "{:?} has {} bytes, expected {} for {} documents",
+                path,
+                source.len(),
+                expected_len,
+                max_doc
+            ));
+        }
+        let num_deleted_docs = DeleteBitSet::open(source).len() as u32;
+        segment_meta = segment_meta.with_delete_meta(num_deleted_docs, opstamp);
+    }
+    let segment_reader = SegmentReader::open(&index.segment(segment_meta.clone()))
+        .map_err(|err| format!("failed to open the segment: {:?}", err))?;
+    if max_doc > 0 {
+        segment_reader
+            .get_store_reader()
+            .get(max_doc - 1)
+            .map_err(|err| format!("failed to read the doc store: {:?}", err))?;
+    }
+    Ok(segment_meta)
+}
+
+/// Rebuilds the `meta.json` of the index from the segment files
+/// listed in the managed file list.
+///
+/// Acquires the `IndexWriterLock` for the duration of the recovery.
+pub(crate) fn recover_index(
+    directory: ManagedDirectory,
+    schema: Schema,
+    settings: IndexSettings,
+) -> Result<RecoveryReport> {
+    let _directory_lock = LockType::IndexWriterLock.acquire_lock(&directory)?;
+    if !directory.exists(&MANAGED_FILEPATH) {
+        return Err(TantivyError::PathDoesNotExist(MANAGED_FILEPATH.clone()));
+    }
+    // Latest delete opstamp of each of the segments found in the directory.
+    let mut segment_delete_opstamps: BTreeMap<SegmentId, Option<u64>> = BTreeMap::new();
+    for path in directory.list_managed_files() {
+        if let Some((segment_id, delete_opstamp)) = parse_segment_file(&path) {
+            let latest_delete_opstamp = segment_delete_opstamps.entry(segment_id).or_insert(None);
+            if delete_opstamp > *latest_delete_opstamp {
+                *latest_delete_opstamp = delete_opstamp;
+            }
+        }
+    }
+    let mut skipped_segments = Vec::new();
+    let mut segment_infos: BTreeMap<SegmentId, SegmentInfo> = BTreeMap::new();
+    for &segment_id in segment_delete_opstamps.keys() {
+        match read_segment_info(&directory, segment_id) {
+            Ok(segment_info) => {
+                segment_infos.insert(segment_id, segment_info);
+            }
+            Err(reason) => skipped_segments.push(SkippedSegment { segment_id, reason }),
+        }
+    }
+    // Segment removed from the index -> segment committed in its place.
+    let mut removed_segments: HashMap<SegmentId, SegmentId> = HashMap::new();
+    for (&segment_id, segment_info) in &segment_infos {
+        for &removed_segment_id in &segment_info.removed_segments {
+            removed_segments.insert(removed_segment_id, segment_id);
+        }
+    }
+    let index = Index::without_searchers(directory.clone(), schema.clone(), settings.clone());
+    let mut recovered_segments = Vec::new();
+    let mut opstamp = 0u64;
+    for (segment_id, segment_info) in segment_infos {
+        if let Some(replacing_segment_id) = removed_segments.get(&segment_id) {
+            skipped_segments.push(SkippedSegment {
+                segment_id,
+                reason: format!(
+                    "the segment was replaced by {}",
+                    replacing_segment_id.uuid_string()
+                ),
+            });
+            continue;
+        }
+        let delete_opstamp = segment_delete_opstamps[&segment_id];
+        // Truncated files may trigger panics when opening the segment.
+        let inspect_result = panic::catch_unwind(AssertUnwindSafe(|| {
+            inspect_segment(&index, segment_id, delete_opstamp)
+        }))
+        .unwrap_or_else(|_| Err("panicked while opening the segment".to_string()));
+        match inspect_result {
+            Ok(segment_meta) => {
+                opstamp = opstamp
+                    .max(segment_info.opstamp)
+                    .max(segment_meta.delete_opstamp().unwrap_or(0u64));
+                recovered_segments.push(segment_meta);
+            }
+            Err(reason) => skipped_segments.push(SkippedSegment { segment_id, reason }),
+        }
+    }
+    let mut directory = directory;
+    save_metas(
+        recovered_segments.clone(),
+        schema,
+        settings,
+        opstamp,
+        None,
+        &mut directory,
+    )?;
+    info!(
+        "Rebuilt {:?} with {} segments, skipped {} segments",
+        *META_FILEPATH,
+        recovered_segments.len(),
+        skipped_segments.len()
+    );
+    Ok(RecoveryReport {
+        recovered_segments,
+        skipped_segments,
+        opstamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::CountCollector;
+    use core::{IndexSettings, META_FILEPATH};
+    use directory::{Directory, RAMDirectory};
+    use futures::Future;
+    use indexer::NoMergePolicy;
+    use query::TermQuery;
+    use schema::{IndexRecordOption, SchemaBuilder, Term, STORED, TEXT};
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use store::StoreCompressor;
+    use DocAddress;
+    use Index;
+    use TantivyError;
+
+    #[test]
+    fn test_recover_index() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let settings = IndexSettings::default();
+        let directory = RAMDirectory::create();
+        {
+            let index = Index::create(directory.clone(), schema.clone()).unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "c")).unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "d")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        let opstamp = Index::open(directory.clone())
+            .unwrap()
+            .load_metas()
+            .unwrap()
+            .opstamp;
+        // meta.json exists and is valid.
+        assert!(
+            match Index::recover(directory.clone(), schema.clone(), settings.clone(), false) {
+                Err(TantivyError::IndexAlreadyExists) => true,
+                _ => false,
+            }
+        );
+
+        directory.delete(&*META_FILEPATH).unwrap();
+        assert!(Index::open(directory.clone()).is_err());
+        let report =
+            Index::recover(directory.clone(), schema.clone(), settings.clone(), false).unwrap();
+        assert_eq!(report.recovered_segments.len(), 3);
+        assert!(report.skipped_segments.is_empty());
+        assert_eq!(report.opstamp, opstamp);
+        {
+            let index = Index::open(directory.clone()).unwrap();
+            let searcher = index.searcher();
+            assert_eq!(searcher.num_docs(), 4);
+            let query = TermQuery::new(
+                Term::from_field_text(text_field, "a"),
+                IndexRecordOption::Basic,
+            );
+            let mut count_collector = CountCollector::default();
+            searcher.search(&query, &mut count_collector).unwrap();
+            assert_eq!(count_collector.count(), 2);
+        }
+
+        // Removing a file of a segment leaves the segment out.
+        let damaged_segment = report
+            .recovered_segments
+            .iter()
+            .find(|segment_meta| segment_meta.num_docs() == 2)
+            .unwrap()
+            .clone();
+        let store_path = PathBuf::from(format!("{}.store", damaged_segment.id().uuid_string()));
+        directory.delete(&store_path).unwrap();
+        let report =
+            Index::recover(directory.clone(), schema.clone(), settings.clone(), true).unwrap();
+        assert_eq!(report.recovered_segments.len(), 2);
+        assert_eq!(report.skipped_segments.len(), 1);
+        assert_eq!(report.skipped_segments[0].segment_id, damaged_segment.id());
+        assert!(report.skipped_segments[0].reason.contains(".store"));
+        let index = Index::open(directory).unwrap();
+        assert_eq!(index.searcher().num_docs(), 2);
+    }
+
+    #[test]
+    fn test_recover_index_skips_uncommitted_and_merged_segments() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let settings = IndexSettings::default();
+        let directory = RAMDirectory::create();
+        let mut index = Index::create(directory.clone(), schema.clone()).unwrap();
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text_field => "a")).unwrap();
+        index_writer.commit().unwrap();
+        index_writer.add_document(doc!(text_field => "b")).unwrap();
+        index_writer.commit().unwrap();
+        // The files of the merged segments are restored after the merge,
+        // as if they had not been garbage collected.
+        let merged_files: Vec<(PathBuf, Vec<u8>)> = index
+            .searchable_segment_metas()
+            .unwrap()
+            .iter()
+            .flat_map(|segment_meta| segment_meta.list_files())
+            .filter(|path| directory.exists(path))
+            .map(|path| {
+                let data = directory.atomic_read(&path).unwrap();
+                (path, data)
+            }).collect();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        assert_eq!(segment_ids.len(), 2);
+        index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        for &(ref path, ref data) in &merged_files {
+            index.directory_mut().atomic_write(path, data).unwrap();
+        }
+        let opstamp = index.load_metas().unwrap().opstamp;
+        // A segment that is flushed, but never committed.
+        index_writer.add_document(doc!(text_field => "c")).unwrap();
+        drop(index_writer.prepare_commit().unwrap());
+        drop(index_writer);
+
+        directory.delete(&*META_FILEPATH).unwrap();
+        let report =
+            Index::recover(directory.clone(), schema.clone(), settings.clone(), false).unwrap();
+        assert_eq!(report.recovered_segments.len(), 1);
+        assert_eq!(report.recovered_segments[0].max_doc(), 2);
+        assert_eq!(report.opstamp, opstamp);
+        assert_eq!(report.skipped_segments.len(), 3);
+        let num_skipped = |pattern: &str| {
+            report
+                .skipped_segments
+                .iter()
+                .filter(|skipped_segment| skipped_segment.reason.contains(pattern))
+                .count()
+        };
+        assert_eq!(num_skipped("was replaced by"), 2);
+        assert_eq!(num_skipped("was never committed"), 1);
+        let index = Index::open(directory).unwrap();
+        assert_eq!(index.searcher().num_docs(), 2);
+    }
+
+    #[test]
+    fn test_recover_index_with_settings() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT | STORED);
+        let schema = schema_builder.build();
+        let mut profile = BTreeMap::new();
+        profile.insert("text".to_string(), 2f32);
+        let mut relevance_profiles = BTreeMap::new();
+        relevance_profiles.insert("boosted".to_string(), profile);
+        // Snappy is not the default codec when lz4 is enabled,
+        // and the doc store block size and the profiles are not the default ones.
+        let settings = IndexSettings {
+            docstore_compression: StoreCompressor::Snappy,
+            docstore_blocksize: 100,
+            relevance_profiles,
+        };
+        let directory = RAMDirectory::create();
+        {
+            let index =
+                Index::create_with_settings(directory.clone(), schema.clone(), settings.clone())
+                    .unwrap();
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..50 {
+                index_writer
+                    .add_document(doc!(text_field => format!("document {}", i)))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        directory.delete(&*META_FILEPATH).unwrap();
+
+        // The settings are validated.
+        let invalid_settings = IndexSettings {
+            docstore_blocksize: 0,
+            ..settings.clone()
+        };
+        assert!(
+            Index::recover(directory.clone(), schema.clone(), invalid_settings, false).is_err()
+        );
+
+        let report =
+            Index::recover(directory.clone(), schema.clone(), settings.clone(), false).unwrap();
+        assert_eq!(report.recovered_segments.len(), 1);
+        assert!(report.skipped_segments.is_empty());
+        let index = Index::open(directory).unwrap();
+        assert_eq!(index.settings(), &settings);
+        let searcher = index.searcher();
+        assert_eq!(searcher.num_docs(), 50);
+        let doc = searcher.doc(DocAddress(0, 49)).unwrap();
+        assert_eq!(
+            doc.get_first(text_field).unwrap().text(),
+            Some("document 49")
+        );
+    }
+}
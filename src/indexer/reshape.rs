@@ -0,0 +1,318 @@
+use core::Index;
+use core::IndexMeta;
+use core::SegmentMeta;
+use core::SerializableSegment;
+use core::META_FILEPATH;
+use indexer::merger::IndexMerger;
+use indexer::segment_updater::save_metas;
+use indexer::LockType;
+use indexer::SegmentSerializer;
+use indexer::{KeepCommonMetadata, MergeMetadataCombiner};
+use schema::{Field, FieldEntry, FieldType, Schema};
+use std::collections::HashSet;
+use Result;
+use TantivyError;
+
+/// Fields to remove from the doc store and/or from the inverted index
+/// of an existing index.
+///
+/// The schema keeps all of its fields: a field dropped from the doc store
+/// simply stops being stored, and a field dropped from the inverted
+/// index stops being indexed.
+///
+/// See [`Index::rewrite`](../struct.Index.html#method.rewrite).
+#[derive(Clone, Debug, Default)]
+pub struct FieldReshape {
+    /// Fields that should not be stored anymore.
+    pub drop_stored: Vec<Field>,
+    /// Fields that should not be indexed anymore.
+    pub drop_indexed: Vec<Field>,
+}
+
+fn get_field_entry(field_entries: &[FieldEntry], field: Field) -> Result<&FieldEntry> {
+    field_entries
+        .get(field.0 as usize)
+        .ok_or_else(|| TantivyError::SchemaError(format!("Unknown field {:?}.", field)))
+}
+
+fn drop_stored(field_entry: &FieldEntry) -> Result<FieldEntry> {
+    let field_name = field_entry.name().to_string();
+    if !field_entry.is_stored() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not stored.",
+            field_name
+        )));
+    }
+    match *field_entry.field_type() {
+        FieldType::Str(ref options) => Ok(FieldEntry::new_text(
+            field_name,
+            options.clone().unset_stored(),
+        )),
+        FieldType::U64(ref options) => Ok(FieldEntry::new_u64(
+            field_name,
+            options.clone().unset_stored(),
+        )),
+        FieldType::I64(ref options) => Ok(FieldEntry::new_i64(
+            field_name,
+            options.clone().unset_stored(),
+        )),
+        FieldType::Bytes(ref options) => Ok(FieldEntry::new_bytes(
+            field_name,
+            options.clone().unset_stored(),
+        )),
+        FieldType::HierarchicalFacet => Err(TantivyError::SchemaError(format!(
+            "Facet field {:?} cannot be dropped from the doc store.",
+            field_name
+        ))),
+    }
+}
+
+fn drop_indexed(field_entry: &FieldEntry) -> Result<FieldEntry> {
+    let field_name = field_entry.name().to_string();
+    if !field_entry.is_indexed() {
+        return Err(TantivyError::SchemaError(format!(
+            "Field {:?} is not indexed.",
+            field_name
+        )));
+    }
+    match *field_entry.field_type() {
+        FieldType::Str(ref options) => Ok(FieldEntry::new_text(
+            field_name,
+            options.clone().unset_indexing_options(),
+        )),
+        FieldType::U64(ref options) => Ok(FieldEntry::new_u64(
+            field_name,
+            options.clone().unset_indexed(),
+        )),
+        FieldType::I64(ref options) => Ok(FieldEntry::new_i64(
+            field_name,
+            options.clone().unset_indexed(),
+        )),
+        FieldType::Bytes(ref options) => Ok(FieldEntry::new_bytes(
+            field_name,
+            options.clone().unset_indexed(),
+        )),
+        // The facet fast field stores term ordinals, and facet collection
+        // relies on the term dictionary to resolve them.
+        FieldType::HierarchicalFacet => Err(TantivyError::SchemaError(format!(
+            "Facet field {:?} cannot be dropped from the inverted index.",
+            field_name
+        ))),
+    }
+}
+
+impl FieldReshape {
+    /// Returns the schema obtained by applying the reshape to `schema`.
+    ///
+    /// Returns a `SchemaError` if one of the fields does not exist,
+    /// is not stored (resp. indexed), or cannot be dropped because other
+    /// features depend on it, as is the case for facet fields.
+    pub fn reshape_schema(&self, schema: &Schema) -> Result<Schema> {
+        let mut field_entries = schema.fields().to_vec();
+        for &field in &self.drop_stored {
+            let field_entry = drop_stored(get_field_entry(&field_entries, field)?)?;
+            field_entries[field.0 as usize] = field_entry;
+        }
+        for &field in &self.drop_indexed {
+            let field_entry = drop_indexed(get_field_entry(&field_entries, field)?)?;
+            field_entries[field.0 as usize] = field_entry;
+        }
+        Ok(schema.with_field_entries(field_entries))
+    }
+}
+
+/// Merges all of the segments of the index into a single segment
+/// serialized with the reshaped schema, and saves the new `meta.json`.
+///
+/// Acquires the `IndexWriterLock` for the duration of the rewrite.
+pub(crate) fn rewrite_index(index: &Index, reshape: &FieldReshape) -> Result<IndexMeta> {
+    let schema = reshape.reshape_schema(&index.schema())?;
+    let _directory_lock = LockType::IndexWriterLock.acquire_lock(index.directory())?;
+    let IndexMeta {
+        segments: previous_segment_metas,
+        settings,
+        opstamp,
+        payload,
+        ..
+    } = index.load_metas()?;
+    let mut segment_metas = Vec::new();
+    {
+        let segments: Vec<_> = previous_segment_metas
+            .into_iter()
+            .map(|segment_meta| index.segment(segment_meta))
+            .collect();
+        if !segments.is_empty() {
+            let merger = IndexMerger::open(schema.clone(), &segments[..])?;
+            let mut rewritten_segment = index.new_segment();
+            let segment_serializer = SegmentSerializer::for_segment(&mut rewritten_segment)?;
+            let num_docs = merger.write(segment_serializer)?;
+            if num_docs > 0 {
+                let user_metadatas: Vec<_> = segments
+                    .iter()
+                    .map(|segment| segment.meta().user_metadata())
+                    .collect();
+                let user_metadata = KeepCommonMetadata.combine(&user_metadatas[..]);
+                segment_metas.push(
+                    SegmentMeta::new(rewritten_segment.id(), num_docs)
+                        .with_user_metadata(user_metadata),
+                );
+            }
+        }
+    }
+    let mut directory = index.directory().clone();
+    save_metas(
+        segment_metas.clone(),
+        schema.clone(),
+        settings.clone(),
+        opstamp,
+        payload.clone(),
+        &mut directory,
+    )?;
+    // Segments that are still in use, by searchers for instance,
+    // are kept until the next garbage collection.
+    directory.garbage_collect(|| {
+        let mut living_files = HashSet::new();
+        living_files.insert(META_FILEPATH.clone());
+        for segment_meta in SegmentMeta::all() {
+            living_files.extend(segment_meta.list_files());
+        }
+        living_files
+    });
+    Ok(IndexMeta {
+        segments: segment_metas,
+        schema,
+        settings,
+        opstamp,
+        payload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FieldReshape;
+    use collector::CountCollector;
+    use directory::RAMDirectory;
+    use query::TermQuery;
+    use schema::{Facet, IndexRecordOption, SchemaBuilder, Term, INT_STORED, STORED, TEXT};
+    use DocAddress;
+    use Index;
+    use TantivyError;
+
+    #[test]
+    fn test_rewrite_drops_fields() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+        let payload_field = schema_builder.add_text_field("raw_payload", TEXT | STORED);
+        let count_field = schema_builder.add_u64_field("count", INT_STORED);
+        let facet_field = schema_builder.add_facet_field("category");
+        let schema = schema_builder.build();
+        let directory = RAMDirectory::create();
+        let index = Index::create(directory.clone(), schema).unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..100u64 {
+                let payload: String = (0..100u64)
+                    .map(|j| format!("{:x}", (i * 7919 + j * 104_729) % 65_521))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                index_writer
+                    .add_document(doc!(
+                        title_field => format!("title{}", i % 3),
+                        payload_field => payload,
+                        count_field => i,
+                        facet_field => Facet::from("/category/a")
+                    ))
+                    .unwrap();
+                if i == 49 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer
+                .delete_term(Term::from_field_text(title_field, "title0"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let store_num_bytes_before = index
+            .searcher()
+            .space_usage()
+            .segments()
+            .iter()
+            .map(|segment| segment.store().total())
+            .sum::<usize>();
+
+        // Invalid reshapes
+        let reshape_facet = FieldReshape {
+            drop_stored: vec![],
+            drop_indexed: vec![facet_field],
+        };
+        assert!(match index.rewrite(&reshape_facet) {
+            Err(TantivyError::SchemaError(_)) => true,
+            _ => false,
+        });
+        let reshape_not_indexed = FieldReshape {
+            drop_stored: vec![],
+            drop_indexed: vec![count_field],
+        };
+        assert!(match index.rewrite(&reshape_not_indexed) {
+            Err(TantivyError::SchemaError(_)) => true,
+            _ => false,
+        });
+
+        let reshape = FieldReshape {
+            drop_stored: vec![payload_field],
+            drop_indexed: vec![payload_field],
+        };
+        {
+            let _index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            assert!(match index.rewrite(&reshape) {
+                Err(TantivyError::LockFailure(_)) => true,
+                _ => false,
+            });
+        }
+        let rewritten_index = index.rewrite(&reshape).unwrap();
+
+        let schema = rewritten_index.schema();
+        assert!(!schema.get_field_entry(payload_field).is_stored());
+        assert!(!schema.get_field_entry(payload_field).is_indexed());
+        assert!(schema.get_field_entry(title_field).is_stored());
+        let metas = rewritten_index.load_metas().unwrap();
+        assert_eq!(metas.segments.len(), 1);
+        assert!(metas.schema == schema);
+
+        let searcher = rewritten_index.searcher();
+        assert_eq!(searcher.num_docs(), 66);
+        for doc_id in 0..66 {
+            let doc = searcher.doc(DocAddress(0, doc_id)).unwrap();
+            assert!(doc.get_first(title_field).is_some());
+            assert!(doc.get_first(count_field).is_some());
+            assert!(doc.get_first(payload_field).is_none());
+        }
+        let store_num_bytes_after = searcher.space_usage().segments()[0].store().total();
+        assert!(store_num_bytes_after < store_num_bytes_before / 2);
+
+        let title_query = TermQuery::new(
+            Term::from_field_text(title_field, "title2"),
+            IndexRecordOption::Basic,
+        );
+        let mut count_collector = CountCollector::default();
+        searcher.search(&title_query, &mut count_collector).unwrap();
+        assert_eq!(count_collector.count(), 33);
+        let payload_query = TermQuery::new(
+            Term::from_field_text(payload_field, "0"),
+            IndexRecordOption::Basic,
+        );
+        let mut count_collector = CountCollector::default();
+        assert!(
+            match searcher.search(&payload_query, &mut count_collector) {
+                Err(TantivyError::SchemaError(_)) => true,
+                _ => false,
+            }
+        );
+
+        // The updated schema is persisted in meta.json.
+        let reopened_index = Index::open(directory).unwrap();
+        assert!(reopened_index.schema() == schema);
+    }
+}
@@ -106,11 +106,20 @@ impl SegmentManager {
             .expect("Failed to acquire write lock on SegmentManager.")
     }
 
+    /// Replaces the committed segments with the given segments.
+    ///
+    /// Segments without any document left, for instance because all
+    /// of their documents were deleted, are dropped: they do not appear in
+    /// the committed metas, and their files can be garbage collected.
     pub fn commit(&self, segment_entries: Vec<SegmentEntry>) {
         let mut registers_lock = self.write();
         registers_lock.committed.clear();
         registers_lock.uncommitted.clear();
         for segment_entry in segment_entries {
+            if segment_entry.meta().num_docs() == 0 {
+                info!("Dropping empty segment {:?}", segment_entry.segment_id());
+                continue;
+            }
             registers_lock.committed.add_segment_entry(segment_entry);
         }
     }
@@ -225,6 +234,14 @@ impl SegmentManager {
         for segment_id in before_merge_segment_ids {
             target_register.remove_segment(segment_id);
         }
+        // All of the merged documents may have been deleted in the meantime.
+        if after_merge_segment_entry.meta().num_docs() == 0 {
+            info!(
+                "Dropping empty merged segment {:?}",
+                after_merge_segment_entry.segment_id()
+            );
+            return;
+        }
         target_register.add_segment_entry(after_merge_segment_entry);
     }
 
@@ -0,0 +1,213 @@
+use schema::Document;
+use std::collections::BTreeMap;
+
+/// Computes the user metadata of the segments flushed by an `IndexWriter`.
+///
+/// The metadata of a segment starts empty, and is updated with each
+/// of the documents added to the segment, in order.
+/// It is persisted in `meta.json`, and accessible through
+/// `SegmentMeta::user_metadata`, for instance from a `MergePolicy`.
+///
+/// The builder is called from the indexing threads, and
+/// should be cheap.
+///
+/// See `IndexWriter::set_segment_metadata_builder`.
+pub trait SegmentMetadataBuilder: Send + Sync {
+    /// Updates the metadata of a segment being written with a document
+    /// added to this segment.
+    fn add_document(&self, doc: &Document, metadata: &mut BTreeMap<String, String>);
+}
+
+/// Computes the user metadata of the segment resulting from a merge.
+///
+/// See `IndexWriter::set_merge_metadata_combiner`.
+pub trait MergeMetadataCombiner: Send + Sync {
+    /// Returns the metadata of the merged segment, given the metadata
+    /// of the segments being merged.
+    fn combine(&self, metadatas: &[&BTreeMap<String, String>]) -> BTreeMap<String, String>;
+}
+
+/// `MergeMetadataCombiner` keeping the entries that have the same value
+/// in all of the merged segments.
+///
+/// This is the default combiner.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeepCommonMetadata;
+
+impl MergeMetadataCombiner for KeepCommonMetadata {
+    fn combine(&self, metadatas: &[&BTreeMap<String, String>]) -> BTreeMap<String, String> {
+        let first = match metadatas.first() {
+            Some(&first) => first,
+            None => return BTreeMap::new(),
+        };
+        first
+            .iter()
+            .filter(|&(key, value)| {
+                metadatas[1..]
+                    .iter()
+                    .all(|metadata| metadata.get(key) == Some(value))
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{KeepCommonMetadata, MergeMetadataCombiner, SegmentMetadataBuilder};
+    use futures::Future;
+    use indexer::NoMergePolicy;
+    use schema::{Document, Field, SchemaBuilder, FAST};
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use Index;
+
+    /// Records the range of a timestamp field.
+    struct TimeRange(Field);
+
+    impl SegmentMetadataBuilder for TimeRange {
+        fn add_document(&self, doc: &Document, metadata: &mut BTreeMap<String, String>) {
+            let timestamp = doc.get_first(self.0).unwrap().u64_value();
+            let start = metadata
+                .get("start")
+                .map(|start| start.parse::<u64>().unwrap())
+                .unwrap_or(timestamp);
+            let end = metadata
+                .get("end")
+                .map(|end| end.parse::<u64>().unwrap())
+                .unwrap_or(timestamp);
+            metadata.insert("start".to_string(), start.min(timestamp).to_string());
+            metadata.insert("end".to_string(), end.max(timestamp).to_string());
+            metadata.insert("source".to_string(), "shard-1".to_string());
+        }
+    }
+
+    struct MergeTimeRanges;
+
+    impl MergeMetadataCombiner for MergeTimeRanges {
+        fn combine(&self, metadatas: &[&BTreeMap<String, String>]) -> BTreeMap<String, String> {
+            let values = |key: &str| -> Vec<String> {
+                metadatas
+                    .iter()
+                    .map(|metadata| metadata[key].clone())
+                    .collect()
+            };
+            let mut combined = BTreeMap::new();
+            combined.insert(
+                "start".to_string(),
+                values("start").into_iter().min().unwrap(),
+            );
+            combined.insert("end".to_string(), values("end").into_iter().max().unwrap());
+            combined
+        }
+    }
+
+    fn metadata(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_common_metadata() {
+        let first = metadata(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let second = metadata(&[("a", "1"), ("b", "4")]);
+        assert_eq!(
+            KeepCommonMetadata.combine(&[&first, &second]),
+            metadata(&[("a", "1")])
+        );
+        assert_eq!(KeepCommonMetadata.combine(&[&first]), first);
+        assert!(KeepCommonMetadata.combine(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_segment_user_metadata() {
+        let mut schema_builder = SchemaBuilder::default();
+        let timestamp_field = schema_builder.add_u64_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            index_writer.set_segment_metadata_builder(Arc::new(TimeRange(timestamp_field)));
+            index_writer
+                .add_document(doc!(timestamp_field => 3u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 1u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 5u64))
+                .unwrap();
+            index_writer
+                .add_document(doc!(timestamp_field => 7u64))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        let mut segment_metas = index.searchable_segment_metas().unwrap();
+        segment_metas.sort_by_key(|segment_meta| segment_meta.user_metadata()["start"].clone());
+        assert_eq!(segment_metas.len(), 2);
+        assert_eq!(
+            segment_metas[0].user_metadata(),
+            &metadata(&[("end", "3"), ("source", "shard-1"), ("start", "1")])
+        );
+        assert_eq!(
+            segment_metas[1].user_metadata(),
+            &metadata(&[("end", "7"), ("source", "shard-1"), ("start", "5")])
+        );
+
+        // Merging with the default combiner.
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            let merged_segment_meta = index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+            assert_eq!(
+                merged_segment_meta.user_metadata(),
+                &metadata(&[("source", "shard-1")])
+            );
+            index_writer.wait_merging_threads().unwrap();
+        }
+
+        // The metadata is persisted in meta.json.
+        index.load_searchers().unwrap();
+        let segment_metas = index.searchable_segment_metas().unwrap();
+        assert_eq!(segment_metas.len(), 1);
+        assert_eq!(
+            segment_metas[0].user_metadata(),
+            &metadata(&[("source", "shard-1")])
+        );
+    }
+
+    #[test]
+    fn test_merge_metadata_combiner() {
+        let mut schema_builder = SchemaBuilder::default();
+        let timestamp_field = schema_builder.add_u64_field("timestamp", FAST);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.set_segment_metadata_builder(Arc::new(TimeRange(timestamp_field)));
+        index_writer.set_merge_metadata_combiner(Arc::new(MergeTimeRanges));
+        index_writer
+            .add_document(doc!(timestamp_field => 3u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        index_writer
+            .add_document(doc!(timestamp_field => 8u64))
+            .unwrap();
+        index_writer
+            .add_document(doc!(timestamp_field => 6u64))
+            .unwrap();
+        index_writer.commit().unwrap();
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        let merged_segment_meta = index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        assert_eq!(
+            merged_segment_meta.user_metadata(),
+            &metadata(&[("end", "8"), ("start", "3")])
+        );
+        index_writer.wait_merging_threads().unwrap();
+    }
+}
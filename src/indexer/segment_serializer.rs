@@ -18,8 +18,18 @@ pub struct SegmentSerializer {
 
 impl SegmentSerializer {
     /// Creates a new `SegmentSerializer`.
+    ///
+    /// The doc store is written as defined by the settings of the index.
     pub fn for_segment(segment: &mut Segment) -> Result<SegmentSerializer> {
         let store_write = segment.open_write(SegmentComponent::STORE)?;
+        let store_writer = {
+            let settings = segment.index().settings();
+            StoreWriter::new(
+                store_write,
+                settings.docstore_compression,
+                settings.docstore_blocksize,
+            )
+        };
 
         let fast_field_write = segment.open_write(SegmentComponent::FASTFIELDS)?;
         let fast_field_serializer = FastFieldSerializer::from_write(fast_field_write)?;
@@ -29,7 +39,7 @@ impl SegmentSerializer {
 
         let postings_serializer = InvertedIndexSerializer::open(segment)?;
         Ok(SegmentSerializer {
-            store_writer: StoreWriter::new(store_write),
+            store_writer,
             fast_field_serializer,
             fieldnorms_serializer,
             postings_serializer,
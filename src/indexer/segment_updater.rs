@@ -1,6 +1,8 @@
 use super::segment_manager::{get_mergeable_segments, SegmentManager};
 use core::Index;
 use core::IndexMeta;
+use core::IndexSettings;
+use core::MergeMetrics;
 use core::Segment;
 use core::SegmentId;
 use core::SegmentMeta;
@@ -17,11 +19,13 @@ use futures_cpupool::CpuPool;
 use indexer::delete_queue::DeleteCursor;
 use indexer::index_writer::advance_deletes;
 use indexer::merger::IndexMerger;
+use indexer::recovery::save_segment_infos;
 use indexer::stamper::Stamper;
 use indexer::MergeCandidate;
 use indexer::SegmentEntry;
 use indexer::SegmentSerializer;
-use indexer::{DefaultMergePolicy, MergePolicy};
+use indexer::{DefaultMergePolicy, MergeDedupPolicy, MergePolicy};
+use indexer::{KeepCommonMetadata, MergeMetadataCombiner, SegmentMetadataBuilder};
 use schema::Schema;
 use serde_json;
 use std::borrow::BorrowMut;
@@ -31,10 +35,12 @@ use std::mem;
 use std::ops::DerefMut;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{PoisonError, RwLock};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Instant;
 use Result;
+use MAX_DOC_LIMIT;
 
 /// Save the index meta file.
 /// This operation is atomic :
@@ -45,8 +51,13 @@ use Result;
 /// and flushed.
 ///
 /// This method is not part of tantivy's public API
-pub fn save_new_metas(schema: Schema, opstamp: u64, directory: &mut Directory) -> Result<()> {
-    save_metas(vec![], schema, opstamp, None, directory)
+pub fn save_new_metas(
+    schema: Schema,
+    settings: IndexSettings,
+    opstamp: u64,
+    directory: &mut Directory,
+) -> Result<()> {
+    save_metas(vec![], schema, settings, opstamp, None, directory)
 }
 
 /// Save the index meta file.
@@ -57,17 +68,23 @@ pub fn save_new_metas(schema: Schema, opstamp: u64, directory: &mut Directory) -
 /// - it success, and `meta.json` is written
 /// and flushed.
 ///
+/// The `.info` files of the segments committed for the first time
+/// are written beforehand.
+///
 /// This method is not part of tantivy's public API
 pub fn save_metas(
     segment_metas: Vec<SegmentMeta>,
     schema: Schema,
+    settings: IndexSettings,
     opstamp: u64,
     payload: Option<String>,
     directory: &mut Directory,
 ) -> Result<()> {
+    save_segment_infos(&segment_metas[..], opstamp, directory)?;
     let metas = IndexMeta {
         segments: segment_metas,
         schema,
+        settings,
         opstamp,
         payload,
     };
@@ -91,10 +108,13 @@ fn perform_merge(
     mut segment_entries: Vec<SegmentEntry>,
     mut merged_segment: Segment,
     target_opstamp: u64,
+    metadata_combiner: &MergeMetadataCombiner,
+    dedup_policy: Option<MergeDedupPolicy>,
 ) -> Result<SegmentEntry> {
     // first we need to apply deletes to our segment.
 
     // TODO add logging
+    let start = Instant::now();
     let schema = index.schema();
 
     for segment_entry in &mut segment_entries {
@@ -110,7 +130,12 @@ fn perform_merge(
         .collect();
 
     // An IndexMerger is like a "view" of our merged segments.
-    let merger: IndexMerger = IndexMerger::open(schema, &segments[..])?;
+    let mut merger: IndexMerger = IndexMerger::open(schema, &segments[..])?;
+
+    let num_collapsed_docs = match dedup_policy {
+        Some(dedup_policy) => merger.dedup(&dedup_policy)?,
+        None => 0,
+    };
 
     // ... we just serialize this index merger in our new segment
     // to merge the two segments.
@@ -119,7 +144,23 @@ fn perform_merge(
 
     let num_docs = merger.write(segment_serializer)?;
 
-    let segment_meta = SegmentMeta::new(merged_segment.id(), num_docs);
+    index.metrics().on_merge(&MergeMetrics {
+        num_segments: segments.len(),
+        num_docs,
+        num_collapsed_docs,
+        duration: start.elapsed(),
+    });
+
+    let user_metadata = {
+        let user_metadatas: Vec<_> = segment_entries
+            .iter()
+            .map(|segment_entry| segment_entry.meta().user_metadata())
+            .collect();
+        metadata_combiner.combine(&user_metadatas[..])
+    };
+    let segment_meta = SegmentMeta::new(merged_segment.id(), num_docs)
+        .with_user_metadata(user_metadata)
+        .with_num_collapsed_docs(num_collapsed_docs);
 
     let after_merge_segment_entry = SegmentEntry::new(segment_meta.clone(), delete_cursor, None);
     Ok(after_merge_segment_entry)
@@ -130,6 +171,12 @@ struct InnerSegmentUpdater {
     index: Index,
     segment_manager: SegmentManager,
     merge_policy: RwLock<Box<MergePolicy>>,
+    segment_metadata_builder: RwLock<Option<Arc<SegmentMetadataBuilder>>>,
+    merge_metadata_combiner: RwLock<Arc<MergeMetadataCombiner>>,
+    merge_dedup_policy: RwLock<Option<MergeDedupPolicy>>,
+    max_tokens_per_doc: RwLock<Option<u32>>,
+    num_truncated_docs: AtomicUsize,
+    max_docs_per_segment: RwLock<u32>,
     merging_thread_id: AtomicUsize,
     merging_threads: RwLock<HashMap<usize, JoinHandle<Result<()>>>>,
     generation: AtomicUsize,
@@ -154,6 +201,12 @@ impl SegmentUpdater {
             index,
             segment_manager,
             merge_policy: RwLock::new(Box::new(DefaultMergePolicy::default())),
+            segment_metadata_builder: RwLock::new(None),
+            merge_metadata_combiner: RwLock::new(Arc::new(KeepCommonMetadata)),
+            merge_dedup_policy: RwLock::new(None),
+            max_tokens_per_doc: RwLock::new(None),
+            num_truncated_docs: AtomicUsize::default(),
+            max_docs_per_segment: RwLock::new(MAX_DOC_LIMIT),
             merging_thread_id: AtomicUsize::default(),
             merging_threads: RwLock::new(HashMap::new()),
             generation: AtomicUsize::default(),
@@ -170,11 +223,111 @@ impl SegmentUpdater {
     }
 
     pub fn get_merge_policy(&self) -> Box<MergePolicy> {
-        self.0.merge_policy.read().unwrap().box_clone()
+        // Replacing the merge policy cannot leave it in an
+        // inconsistent state, so it is safe to ignore poisoning.
+        self.0
+            .merge_policy
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .box_clone()
     }
 
     pub fn set_merge_policy(&self, merge_policy: Box<MergePolicy>) {
-        *self.0.merge_policy.write().unwrap() = merge_policy;
+        *self
+            .0
+            .merge_policy
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = merge_policy;
+    }
+
+    pub fn get_segment_metadata_builder(&self) -> Option<Arc<SegmentMetadataBuilder>> {
+        self.0
+            .segment_metadata_builder
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn set_segment_metadata_builder(&self, builder: Arc<SegmentMetadataBuilder>) {
+        *self
+            .0
+            .segment_metadata_builder
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(builder);
+    }
+
+    pub fn get_merge_metadata_combiner(&self) -> Arc<MergeMetadataCombiner> {
+        self.0
+            .merge_metadata_combiner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn set_merge_metadata_combiner(&self, combiner: Arc<MergeMetadataCombiner>) {
+        *self
+            .0
+            .merge_metadata_combiner
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = combiner;
+    }
+
+    pub fn get_merge_dedup_policy(&self) -> Option<MergeDedupPolicy> {
+        *self
+            .0
+            .merge_dedup_policy
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set_merge_dedup_policy(&self, dedup_policy: Option<MergeDedupPolicy>) {
+        *self
+            .0
+            .merge_dedup_policy
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = dedup_policy;
+    }
+
+    pub fn get_max_tokens_per_doc(&self) -> Option<u32> {
+        *self
+            .0
+            .max_tokens_per_doc
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set_max_tokens_per_doc(&self, max_tokens_per_doc: Option<u32>) {
+        *self
+            .0
+            .max_tokens_per_doc
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = max_tokens_per_doc;
+    }
+
+    pub fn record_truncated_docs(&self, num_truncated_docs: u64) {
+        self.0
+            .num_truncated_docs
+            .fetch_add(num_truncated_docs as usize, Ordering::SeqCst);
+    }
+
+    pub fn num_truncated_docs(&self) -> u64 {
+        self.0.num_truncated_docs.load(Ordering::SeqCst) as u64
+    }
+
+    pub fn get_max_docs_per_segment(&self) -> u32 {
+        *self
+            .0
+            .max_docs_per_segment
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set_max_docs_per_segment(&self, max_docs_per_segment: u32) {
+        *self
+            .0
+            .max_docs_per_segment
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = max_docs_per_segment;
     }
 
     fn get_merging_thread_id(&self) -> usize {
@@ -230,6 +383,7 @@ impl SegmentUpdater {
             save_metas(
                 self.0.segment_manager.committed_segment_metas(),
                 index.schema(),
+                index.settings().clone(),
                 opstamp,
                 commit_message,
                 directory.box_clone().borrow_mut(),
@@ -289,7 +443,9 @@ impl SegmentUpdater {
         );
         let (merging_future_send, merging_future_recv) = oneshot();
 
-        let target_opstamp = self.0.stamper.stamp();
+        let target_opstamp = self.0.stamper.stamp()?;
+        let metadata_combiner = self.get_merge_metadata_combiner();
+        let dedup_policy = self.get_merge_dedup_policy();
 
         // first we need to apply deletes to our segment.
         let merging_join_handle = thread::Builder::new()
@@ -303,6 +459,8 @@ impl SegmentUpdater {
                     segment_entries,
                     merged_segment,
                     target_opstamp,
+                    &*metadata_combiner,
+                    dedup_policy,
                 );
 
                 match merge_result {
@@ -333,14 +491,13 @@ impl SegmentUpdater {
                     .0
                     .merging_threads
                     .write()
-                    .unwrap()
+                    .unwrap_or_else(PoisonError::into_inner)
                     .remove(&merging_thread_id);
                 Ok(())
             }).expect("Failed to spawn a thread.");
         self.0
             .merging_threads
-            .write()
-            .unwrap()
+            .write()?
             .insert(merging_thread_id, merging_join_handle);
         Ok(merging_future_recv)
     }
@@ -452,7 +609,7 @@ impl SegmentUpdater {
 
             let mut new_merging_threads = HashMap::new();
             {
-                let mut merging_threads = self.0.merging_threads.write().unwrap();
+                let mut merging_threads = self.0.merging_threads.write()?;
                 mem::swap(&mut new_merging_threads, merging_threads.deref_mut());
             }
             debug!("wait merging thread {}", new_merging_threads.len());
@@ -478,10 +635,104 @@ impl SegmentUpdater {
 #[cfg(test)]
 mod tests {
 
+    use collector::{CountCollector, TopCollector};
+    use futures::Future;
     use indexer::merge_policy::tests::MergeWheneverPossible;
+    use indexer::NoMergePolicy;
+    use query::{AllQuery, Query, TermQuery};
     use schema::*;
+    use std::path::PathBuf;
     use Index;
 
+    // Searches the index with a few different queries and collectors,
+    // and returns the number of matching documents.
+    fn search_everything(index: &Index, text_field: Field) -> usize {
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let mut top_collector = TopCollector::with_limit(10);
+        searcher.search(&term_query, &mut top_collector).unwrap();
+        for &(score, _) in &top_collector.top_docs() {
+            assert!(score.is_finite());
+        }
+        let mut count_collector = CountCollector::default();
+        AllQuery.search(&*searcher, &mut count_collector).unwrap();
+        assert_eq!(count_collector.count(), searcher.num_docs() as usize);
+        count_collector.count()
+    }
+
+    #[test]
+    fn test_empty_segments() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+
+        // Committing without any document does not create any segment.
+        index_writer.commit().unwrap();
+        assert!(index.searchable_segment_ids().unwrap().is_empty());
+        assert_eq!(search_everything(&index, text_field), 0);
+
+        for _ in 0..10 {
+            index_writer.add_document(doc!(text_field=>"a")).unwrap();
+        }
+        index_writer.commit().unwrap();
+        for _ in 0..10 {
+            index_writer.add_document(doc!(text_field=>"b")).unwrap();
+            index_writer.add_document(doc!(text_field=>"a b")).unwrap();
+        }
+        index_writer.commit().unwrap();
+        assert_eq!(index.searchable_segment_ids().unwrap().len(), 2);
+        assert_eq!(search_everything(&index, text_field), 30);
+
+        // A segment whose documents are all deleted is dropped,
+        // along with its files.
+        let first_segment_files: Vec<PathBuf> = index
+            .searchable_segment_metas()
+            .unwrap()
+            .into_iter()
+            .find(|segment_meta| segment_meta.num_docs() == 10)
+            .unwrap()
+            .list_files()
+            .into_iter()
+            .collect();
+        index_writer
+            .delete_term(Term::from_field_text(text_field, "a"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        assert_eq!(index.searchable_segment_ids().unwrap().len(), 1);
+        assert_eq!(search_everything(&index, text_field), 10);
+        index_writer.garbage_collect_files().unwrap();
+        for path in &first_segment_files {
+            assert!(!index.directory().exists(path));
+        }
+
+        // Merging a segment with deletes.
+        let segment_ids = index.searchable_segment_ids().unwrap();
+        let merged_segment_meta = index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+        assert_eq!(merged_segment_meta.num_docs(), 10);
+        assert_eq!(search_everything(&index, text_field), 10);
+
+        // Deleting everything leaves an index without any segment.
+        index_writer
+            .delete_term(Term::from_field_text(text_field, "b"))
+            .unwrap();
+        index_writer.commit().unwrap();
+        assert!(index.searchable_segment_ids().unwrap().is_empty());
+        assert_eq!(search_everything(&index, text_field), 0);
+
+        // The index remains usable.
+        index_writer.add_document(doc!(text_field=>"a")).unwrap();
+        index_writer.commit().unwrap();
+        index_writer.wait_merging_threads().unwrap();
+        assert_eq!(search_everything(&index, text_field), 1);
+    }
+
     #[test]
     fn test_delete_during_merge() {
         let mut schema_builder = SchemaBuilder::default();
@@ -496,29 +747,29 @@ mod tests {
 
         {
             for _ in 0..100 {
-                index_writer.add_document(doc!(text_field=>"a"));
-                index_writer.add_document(doc!(text_field=>"b"));
+                index_writer.add_document(doc!(text_field=>"a")).unwrap();
+                index_writer.add_document(doc!(text_field=>"b")).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
 
         {
             for _ in 0..100 {
-                index_writer.add_document(doc!(text_field=>"c"));
-                index_writer.add_document(doc!(text_field=>"d"));
+                index_writer.add_document(doc!(text_field=>"c")).unwrap();
+                index_writer.add_document(doc!(text_field=>"d")).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
 
         {
-            index_writer.add_document(doc!(text_field=>"e"));
-            index_writer.add_document(doc!(text_field=>"f"));
+            index_writer.add_document(doc!(text_field=>"e")).unwrap();
+            index_writer.add_document(doc!(text_field=>"f")).unwrap();
             assert!(index_writer.commit().is_ok());
         }
 
         {
             let term = Term::from_field_text(text_field, "a");
-            index_writer.delete_term(term);
+            index_writer.delete_term(term).unwrap();
             assert!(index_writer.commit().is_ok());
         }
 
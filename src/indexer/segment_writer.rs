@@ -6,16 +6,23 @@ use fieldnorm::FieldNormsWriter;
 use indexer::segment_serializer::SegmentSerializer;
 use postings::MultiFieldPostingsWriter;
 use schema::FieldType;
+use schema::FieldValue;
+use schema::PayloadToken;
 use schema::Schema;
 use schema::Term;
+use schema::TextFieldIndexing;
 use schema::Value;
+use std::collections::HashMap;
 use std::io;
 use std::str;
 use tokenizer::BoxedTokenizer;
 use tokenizer::FacetTokenizer;
-use tokenizer::{TokenStream, Tokenizer};
+use tokenizer::Token;
+use tokenizer::{validate_tokens, TokenValidationError};
+use tokenizer::{PreTokenizedStream, TokenStream, TokenStreamChain, Tokenizer};
 use DocId;
 use Result;
+use MAX_DOC_LIMIT;
 
 /// A `SegmentWriter` is in charge of creating segment index from a
 /// documents.
@@ -30,6 +37,93 @@ pub struct SegmentWriter {
     fieldnorms_writer: FieldNormsWriter,
     doc_opstamps: Vec<u64>,
     tokenizers: Vec<Option<Box<BoxedTokenizer>>>,
+    term_occurrences: HashMap<String, u32>,
+    max_tokens_per_doc: Option<u32>,
+    num_truncated_docs: u64,
+    max_docs: u32,
+}
+
+// Token stream dropping the occurrences of each term beyond
+// `max_occurrences`, if any, and stopping after `max_tokens` tokens.
+//
+// The number of occurrences of the terms is tracked in `term_occurrences`,
+// which is reused from one document to the other.
+struct CappedTokenStream<'a, TTokenStream: TokenStream> {
+    token_stream: TTokenStream,
+    max_occurrences: Option<u32>,
+    term_occurrences: &'a mut HashMap<String, u32>,
+    max_tokens: u32,
+    num_tokens: u32,
+    truncated: bool,
+}
+
+impl<'a, TTokenStream: TokenStream> CappedTokenStream<'a, TTokenStream> {
+    fn new(
+        token_stream: TTokenStream,
+        max_occurrences: Option<u32>,
+        term_occurrences: &'a mut HashMap<String, u32>,
+        max_tokens: u32,
+    ) -> CappedTokenStream<'a, TTokenStream> {
+        term_occurrences.clear();
+        CappedTokenStream {
+            token_stream,
+            max_occurrences,
+            term_occurrences,
+            max_tokens,
+            num_tokens: 0,
+            truncated: false,
+        }
+    }
+
+    // Number of tokens read from the underlying token stream, including
+    // the dropped occurrences.
+    fn num_tokens(&self) -> u32 {
+        self.num_tokens
+    }
+
+    // Returns true if tokens were left out because of `max_tokens`.
+    fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl<'a, TTokenStream: TokenStream> TokenStream for CappedTokenStream<'a, TTokenStream> {
+    fn advance(&mut self) -> bool {
+        loop {
+            if self.num_tokens >= self.max_tokens {
+                self.truncated = self.truncated || self.token_stream.advance();
+                return false;
+            }
+            if !self.token_stream.advance() {
+                return false;
+            }
+            self.num_tokens += 1;
+            let max_occurrences = match self.max_occurrences {
+                Some(max_occurrences) => max_occurrences,
+                None => return true,
+            };
+            let text = &self.token_stream.token().text;
+            if let Some(num_occurrences) = self.term_occurrences.get_mut(text) {
+                *num_occurrences += 1;
+                if *num_occurrences > max_occurrences {
+                    continue;
+                }
+                return true;
+            }
+            self.term_occurrences.insert(text.clone(), 1);
+            if max_occurrences > 0 {
+                return true;
+            }
+        }
+    }
+
+    fn token(&self) -> &Token {
+        self.token_stream.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token_stream.token_mut()
+    }
 }
 
 impl SegmentWriter {
@@ -71,9 +165,37 @@ impl SegmentWriter {
             fast_field_writers: FastFieldsWriter::from_schema(schema),
             doc_opstamps: Vec::with_capacity(1_000),
             tokenizers,
+            term_occurrences: HashMap::new(),
+            max_tokens_per_doc: None,
+            num_truncated_docs: 0,
+            max_docs: MAX_DOC_LIMIT,
         })
     }
 
+    /// Sets the maximum number of tokens indexed per document,
+    /// over all of its text fields.
+    ///
+    /// The tokens following this limit are ignored, and the
+    /// document is counted as truncated.
+    pub fn set_max_tokens_per_doc(&mut self, max_tokens_per_doc: Option<u32>) {
+        self.max_tokens_per_doc = max_tokens_per_doc;
+    }
+
+    /// Returns the number of documents truncated because they
+    /// exceeded the maximum number of tokens per document.
+    pub fn num_truncated_docs(&self) -> u64 {
+        self.num_truncated_docs
+    }
+
+    /// Sets the maximum number of documents of the segment.
+    ///
+    /// Once the segment is full, `add_document` fails: the segment
+    /// should be flushed before reaching the limit.
+    /// It is capped at `MAX_DOC_LIMIT`, which is the default.
+    pub fn set_max_docs(&mut self, max_docs: u32) {
+        self.max_docs = max_docs.min(MAX_DOC_LIMIT);
+    }
+
     /// Lay on disk the current content of the `SegmentWriter`
     ///
     /// Finalize consumes the `SegmentWriter`, so that it cannot
@@ -97,12 +219,23 @@ impl SegmentWriter {
     ///
     /// As a user, you should rather use `IndexWriter`'s add_document.
     pub fn add_document(&mut self, add_operation: AddOperation, schema: &Schema) -> io::Result<()> {
+        if self.max_doc >= self.max_docs {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Segment is full: it cannot hold more than {} documents",
+                    self.max_docs
+                ),
+            ));
+        }
         let doc_id = self.max_doc;
         let mut doc = add_operation.document;
         self.doc_opstamps.push(add_operation.opstamp);
 
         self.fast_field_writers.add_document(&doc);
 
+        let mut remaining_tokens = self.max_tokens_per_doc.unwrap_or(u32::max_value());
+        let mut truncated = false;
         for (field, field_values) in doc.get_sorted_field_values() {
             let field_options = schema.get_field_entry(field);
             if !field_options.is_indexed() {
@@ -136,26 +269,91 @@ impl SegmentWriter {
                         }
                     }
                 }
-                FieldType::Str(_) => {
-                    let num_tokens = if let Some(ref mut tokenizer) =
-                        self.tokenizers[field.0 as usize]
+                FieldType::Str(ref text_options) => {
+                    let payload_tokens: Vec<&[PayloadToken]> = field_values
+                        .iter()
+                        .flat_map(|field_value| match *field_value.value() {
+                            Value::PayloadTokens(ref tokens) => Some(&tokens[..]),
+                            _ => None,
+                        }).collect();
+                    let payload_size = text_options
+                        .get_indexing_options()
+                        .map(|indexing_options| indexing_options.payload_size())
+                        .unwrap_or(0);
+                    let max_occurrences = text_options
+                        .get_indexing_options()
+                        .and_then(TextFieldIndexing::max_token_occurrences_per_doc);
+                    for token in payload_tokens.iter().flat_map(|tokens| tokens.iter()) {
+                        if token.payload.len() != payload_size {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "Payload of token {:?} has {} bytes, expected {} in field {:?}",
+                                    token.text,
+                                    token.payload.len(),
+                                    payload_size,
+                                    field_options.name()
+                                ),
+                            ));
+                        }
+                    }
+                    let invalid_tokens = |err: TokenValidationError| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("{} in field {:?}", err, field_options.name()),
+                        )
+                    };
+                    // Pretokenized texts do not go through the tokenizer,
+                    // and are always validated.
+                    for field_value in &field_values {
+                        if let Value::PreTokStr(ref pre_tokenized_text) = *field_value.value() {
+                            validate_tokens(
+                                &pre_tokenized_text.text,
+                                &mut PreTokenizedStream::from(pre_tokenized_text),
+                            ).map_err(&invalid_tokens)?;
+                        }
+                    }
+                    let tokenizer_opt = self.tokenizers[field.0 as usize].as_ref();
+                    #[cfg(debug_assertions)]
                     {
-                        let texts: Vec<&str> = field_values
-                            .iter()
-                            .flat_map(|field_value| match *field_value.value() {
-                                Value::Str(ref text) => Some(text.as_str()),
-                                _ => None,
-                            }).collect();
-                        if texts.is_empty() {
-                            0
-                        } else {
-                            let mut token_stream = tokenizer.token_stream_texts(&texts[..]);
-                            self.multifield_postings
-                                .index_text(doc_id, field, &mut token_stream)
+                        if let Some(tokenizer) = tokenizer_opt {
+                            for field_value in &field_values {
+                                if let Value::Str(ref text) = *field_value.value() {
+                                    validate_tokens(text, &mut tokenizer.token_stream(text))
+                                        .map_err(&invalid_tokens)?;
+                                }
+                            }
                         }
+                    }
+                    let (offsets, token_streams) = text_token_streams(
+                        tokenizer_opt.map(|tokenizer| &**tokenizer),
+                        &field_values,
+                    );
+                    let (mut num_tokens, mut position) = if token_streams.is_empty() {
+                        (0, 0)
                     } else {
-                        0
+                        let mut token_stream = CappedTokenStream::new(
+                            TokenStreamChain::new(offsets, token_streams),
+                            max_occurrences,
+                            &mut self.term_occurrences,
+                            remaining_tokens,
+                        );
+                        let (_, position) =
+                            self.multifield_postings
+                                .index_text(doc_id, field, &mut token_stream);
+                        remaining_tokens -= token_stream.num_tokens();
+                        truncated |= token_stream.is_truncated();
+                        // The dropped occurrences count in the number of tokens.
+                        (token_stream.num_tokens(), position)
                     };
+                    // Pretokenized payload tokens are positioned after the text tokens.
+                    for tokens in payload_tokens {
+                        let num_payload_tokens = self
+                            .multifield_postings
+                            .index_payload_tokens(doc_id, field, tokens, position);
+                        num_tokens += num_payload_tokens;
+                        position += num_payload_tokens;
+                    }
                     self.fieldnorms_writer.record(doc_id, field, num_tokens);
                 }
                 FieldType::U64(ref int_option) => {
@@ -180,12 +378,22 @@ impl SegmentWriter {
                         }
                     }
                 }
-                FieldType::Bytes => {
-                    // Do nothing. Bytes only supports fast fields.
+                FieldType::Bytes(_) => {
+                    // Bytes are indexed as is, as a single term.
+                    for field_value in field_values {
+                        if let Value::Bytes(ref bytes) = *field_value.value() {
+                            let term = Term::from_field_bytes(field, bytes);
+                            self.multifield_postings.subscribe(doc_id, &term);
+                        }
+                    }
                 }
             }
         }
+        if truncated {
+            self.num_truncated_docs += 1;
+        }
         doc.filter_fields(|field| schema.get_field_entry(field).is_stored());
+        doc.strip_tokens();
         let doc_writer = self.segment_serializer.get_store_writer();
         doc_writer.store(&doc)?;
         self.max_doc += 1;
@@ -213,6 +421,38 @@ impl SegmentWriter {
     }
 }
 
+// Returns the token streams of the texts of a field, along with
+// the offset of each text, in the order of the field values.
+//
+// Texts are tokenized by the tokenizer of the field, if any,
+// while pretokenized texts emit their own tokens.
+fn text_token_streams<'a>(
+    tokenizer_opt: Option<&BoxedTokenizer>,
+    field_values: &[&'a FieldValue],
+) -> (Vec<usize>, Vec<Box<TokenStream + 'a>>) {
+    let mut offsets = Vec::new();
+    let mut token_streams: Vec<Box<TokenStream + 'a>> = Vec::new();
+    let mut total_offset = 0;
+    for field_value in field_values {
+        match *field_value.value() {
+            Value::Str(ref text) => {
+                if let Some(tokenizer) = tokenizer_opt {
+                    offsets.push(total_offset);
+                    total_offset += text.len();
+                    token_streams.push(tokenizer.token_stream(text));
+                }
+            }
+            Value::PreTokStr(ref pre_tokenized_text) => {
+                offsets.push(total_offset);
+                total_offset += pre_tokenized_text.text.len();
+                token_streams.push(Box::new(PreTokenizedStream::from(pre_tokenized_text)));
+            }
+            _ => {}
+        }
+    }
+    (offsets, token_streams)
+}
+
 // This method is used as a trick to workaround the borrow checker
 fn write(
     multifield_postings: &MultiFieldPostingsWriter,
@@ -239,3 +479,33 @@ impl SerializableSegment for SegmentWriter {
         Ok(max_doc)
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::SegmentWriter;
+    use indexer::operation::AddOperation;
+    use schema::{SchemaBuilder, TEXT};
+    use Index;
+
+    #[test]
+    fn test_segment_writer_max_docs() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut segment_writer =
+            SegmentWriter::for_segment(18, index.new_segment(), &schema).unwrap();
+        segment_writer.set_max_docs(2);
+        for opstamp in 0u64..3u64 {
+            let add_operation = AddOperation {
+                opstamp,
+                document: doc!(text_field => "a"),
+            };
+            let add_result = segment_writer.add_document(add_operation, &schema);
+            assert_eq!(add_result.is_ok(), opstamp < 2);
+        }
+        assert_eq!(segment_writer.max_doc(), 2);
+        assert_eq!(segment_writer.finalize().unwrap(), vec![0u64, 1u64]);
+    }
+}
@@ -1,31 +1,54 @@
+use error::TantivyError;
+use Result;
+
 // AtomicU64 have not landed in stable.
 // For the moment let's just use AtomicUsize on
 // x86/64 bit platform, and a mutex on other platform.
 
-#[cfg(target = "x86_64")]
+/// Opstamps are not allowed to go beyond this value,
+/// so that they can never wrap around.
+///
+/// The stamper returns an error rather than issuing a larger opstamp,
+/// which would take billions of operations per second for centuries.
+pub(crate) const MAX_OPSTAMP: u64 = u64::max_value() >> 1;
+
+fn check_opstamp(opstamp: u64) -> Result<u64> {
+    if opstamp >= MAX_OPSTAMP {
+        return Err(TantivyError::InvalidArgument(format!(
+            "Opstamp overflow: the index writer cannot stamp more than {} operations.",
+            MAX_OPSTAMP
+        )));
+    }
+    Ok(opstamp)
+}
+
+#[cfg(target_arch = "x86_64")]
 mod archicture_impl {
 
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
+    use Result;
 
     #[derive(Clone, Default)]
-    pub struct Stamper(Arc<AtomicU64>);
+    pub struct Stamper(Arc<AtomicUsize>);
 
     impl Stamper {
         pub fn new(first_opstamp: u64) -> Stamper {
-            Stamper(Arc::new(AtomicU64::new(first_opstamp)))
+            Stamper(Arc::new(AtomicUsize::new(first_opstamp as usize)))
         }
 
-        pub fn stamp(&self) -> u64 {
-            self.0.fetch_add(1u64, Ordering::SeqCst) as u64
+        pub fn stamp(&self) -> Result<u64> {
+            let opstamp = self.0.fetch_add(1usize, Ordering::SeqCst) as u64;
+            super::check_opstamp(opstamp)
         }
     }
 }
 
-#[cfg(not(target = "x86_64"))]
+#[cfg(not(target_arch = "x86_64"))]
 mod archicture_impl {
 
-    use std::sync::{Arc, Mutex};
+    use std::sync::{Arc, Mutex, PoisonError};
+    use Result;
 
     #[derive(Clone, Default)]
     pub struct Stamper(Arc<Mutex<u64>>);
@@ -35,11 +58,13 @@ mod archicture_impl {
             Stamper(Arc::new(Mutex::new(first_opstamp)))
         }
 
-        pub fn stamp(&self) -> u64 {
-            let mut guard = self.0.lock().expect("Failed to lock the stamper");
-            let previous_val = *guard;
+        pub fn stamp(&self) -> Result<u64> {
+            // The counter cannot be left in an inconsistent state,
+            // so it is safe to ignore poisoning.
+            let mut guard = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            let previous_val = super::check_opstamp(*guard)?;
             *guard = previous_val + 1;
-            previous_val
+            Ok(previous_val)
         }
     }
 }
@@ -49,18 +74,31 @@ pub use self::archicture_impl::Stamper;
 #[cfg(test)]
 mod test {
 
-    use super::Stamper;
+    use super::{Stamper, MAX_OPSTAMP};
+    use TantivyError;
 
     #[test]
     fn test_stamper() {
         let stamper = Stamper::new(7u64);
-        assert_eq!(stamper.stamp(), 7u64);
-        assert_eq!(stamper.stamp(), 8u64);
+        assert_eq!(stamper.stamp().unwrap(), 7u64);
+        assert_eq!(stamper.stamp().unwrap(), 8u64);
 
         let stamper_clone = stamper.clone();
-        assert_eq!(stamper.stamp(), 9u64);
+        assert_eq!(stamper.stamp().unwrap(), 9u64);
 
-        assert_eq!(stamper.stamp(), 10u64);
-        assert_eq!(stamper_clone.stamp(), 11u64);
+        assert_eq!(stamper.stamp().unwrap(), 10u64);
+        assert_eq!(stamper_clone.stamp().unwrap(), 11u64);
+    }
+
+    #[test]
+    fn test_stamper_overflow() {
+        let stamper = Stamper::new(MAX_OPSTAMP - 1);
+        assert_eq!(stamper.stamp().unwrap(), MAX_OPSTAMP - 1);
+        match stamper.stamp() {
+            Err(TantivyError::InvalidArgument(msg)) => assert!(msg.starts_with("Opstamp overflow")),
+            _ => panic!("Expected an opstamp overflow error"),
+        }
+        // The stamper keeps refusing to stamp.
+        assert!(stamper.stamp().is_err());
     }
 }
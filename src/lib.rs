@@ -65,7 +65,7 @@
 //!     body => "He was an old man who fished alone in a skiff in \
 //!             the Gulf Stream and he had gone eighty-four days \
 //!             now without taking a fish."
-//! ));
+//! ))?;
 //!
 //! // We need to call .commit() explicitly to force the
 //! // index_writer to finish processing the documents in the queue,
@@ -220,16 +220,30 @@ mod snippet;
 pub use self::snippet::SnippetGenerator;
 
 mod docset;
-pub use self::docset::{DocSet, SkipResult};
-
-pub use core::SegmentComponent;
-pub use core::{Index, Searcher, Segment, SegmentId, SegmentMeta};
+pub use self::docset::{BlockDocSet, DocSet, SkipResult, DOC_BLOCK_LEN};
+
+pub use core::{SegmentComponent, SegmentComponents};
+pub use core::{Executor, ExecutorMetrics, MeteredExecutor, TaskPanic};
+pub use core::{
+    FlushMetrics, MergeMetrics, Metrics, NoMetrics, SearchMetrics, SegmentSearchMetrics,
+};
+pub use core::{Index, IndexBuilder, IndexSettings, RelevanceProfile};
+pub use core::{Searcher, Segment, SegmentId, SegmentMeta, StableDocRef};
+pub use core::{ComponentWarmUp, WarmUpReport, WarmUpSet};
+#[cfg(feature = "async-search")]
+pub use core::SearchFuture;
 pub use core::{InvertedIndexReader, SegmentReader};
 pub use directory::Directory;
-pub use indexer::IndexWriter;
+pub use indexer::{FieldReshape, IndexWriter, TryAddDocumentError, UserOperation};
+pub use indexer::{KeepCommonMetadata, MergeMetadataCombiner, SegmentMetadataBuilder};
+pub use indexer::{KeepRule, MergeDedupPolicy};
+pub use indexer::{RecoveryReport, SkippedSegment};
 pub use postings::Postings;
 pub use schema::{Document, Term};
 
+pub use common::BinarySerializable;
+pub use common::BitSet;
+pub use common::{CompositeFile, CompositeWrite, FileAddr, COMPOSITE_FILE_VERSION};
 pub use common::{i64_to_u64, u64_to_i64};
 
 /// Expose the current version of tantivy, as well
@@ -255,6 +269,12 @@ pub mod merge_policy {
 /// as they are added in the segment.
 pub type DocId = u32;
 
+/// Maximum number of documents in a segment.
+///
+/// `u32::max_value()` is reserved as a sentinel, and is never
+/// a valid `DocId`.
+pub const MAX_DOC_LIMIT: u32 = u32::max_value() - 1;
+
 /// A f32 that represents the relevance of the document to the query
 ///
 /// This is modelled internally as a `f32`. The
@@ -289,7 +309,7 @@ impl DocAddress {
 ///
 /// The id used for the segment is actually an ordinal
 /// in the list of segment hold by a `Searcher`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct DocAddress(pub SegmentLocalId, pub DocId);
 
 #[cfg(test)]
@@ -342,6 +362,22 @@ mod tests {
         sample_with_seed(n, ratio, 4)
     }
 
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_reader_types_are_send_sync() {
+        assert_send_sync::<Index>();
+        assert_send_sync::<::Searcher>();
+        assert_send_sync::<SegmentReader>();
+        assert_send_sync::<::InvertedIndexReader>();
+        assert_send_sync::<::fastfield::FastFieldReader<u64>>();
+        assert_send_sync::<::fastfield::FastFieldReader<i64>>();
+        assert_send_sync::<::fastfield::BytesFastFieldReader>();
+        assert_send_sync::<::fastfield::FacetReader>();
+        assert_send_sync::<::termdict::TermDictionary>();
+        assert_send_sync::<::store::StoreReader>();
+    }
+
     #[test]
     #[cfg(feature = "mmap")]
     fn test_indexing() {
@@ -354,15 +390,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -375,23 +411,25 @@ mod tests {
         let index = Index::create_in_ram(schema_builder.build());
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
         {
-            index_writer.add_document(doc!(text_field=>"a b c"));
+            index_writer
+                .add_document(doc!(text_field=>"a b c"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         {
             {
                 let doc = doc!(text_field=>"a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
         {
             let doc = doc!(text_field=>"c");
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
             index_writer.commit().unwrap();
         }
         {
@@ -418,7 +456,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -446,15 +484,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!();
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -494,38 +532,42 @@ mod tests {
             {
                 // 0
                 let doc = doc!(text_field=>"a b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 1
                 let doc = doc!(text_field=>" a c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 2
                 let doc = doc!(text_field=>" b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 3
                 let doc = doc!(text_field=>" b d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
-                index_writer.delete_term(Term::from_field_text(text_field, "c"));
+                index_writer
+                    .delete_term(Term::from_field_text(text_field, "c"))
+                    .unwrap();
             }
             {
-                index_writer.delete_term(Term::from_field_text(text_field, "a"));
+                index_writer
+                    .delete_term(Term::from_field_text(text_field, "a"))
+                    .unwrap();
             }
             {
                 // 4
                 let doc = doc!(text_field=>" b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 5
                 let doc = doc!(text_field=>" a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -564,11 +606,13 @@ mod tests {
             {
                 // 0
                 let doc = doc!(text_field=>"a b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 1
-                index_writer.delete_term(Term::from_field_text(text_field, "c"));
+                index_writer
+                    .delete_term(Term::from_field_text(text_field, "c"))
+                    .unwrap();
             }
             index_writer.rollback().unwrap();
         }
@@ -607,13 +651,17 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"a b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
-                index_writer.delete_term(Term::from_field_text(text_field, "c"));
+                index_writer
+                    .delete_term(Term::from_field_text(text_field, "c"))
+                    .unwrap();
             }
             index_writer.rollback().unwrap();
-            index_writer.delete_term(Term::from_field_text(text_field, "a"));
+            index_writer
+                .delete_term(Term::from_field_text(text_field, "a"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         {
@@ -661,7 +709,7 @@ mod tests {
 
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-        index_writer.add_document(doc!(field=>1u64));
+        index_writer.add_document(doc!(field=>1u64)).unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -685,7 +733,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
         let negative_val = -1i64;
-        index_writer.add_document(doc!(value_field => negative_val));
+        index_writer
+            .add_document(doc!(value_field => negative_val))
+            .unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -708,7 +758,7 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(2, 40_000_000).unwrap();
-        index_writer.add_document(doc!(text_field=>"a"));
+        index_writer.add_document(doc!(text_field=>"a")).unwrap();
         assert!(index_writer.commit().is_ok());
         assert!(index.load_searchers().is_ok());
         let searcher = index.searcher();
@@ -728,12 +778,12 @@ mod tests {
 
         let add_document = |index_writer: &mut IndexWriter, val: &'static str| {
             let doc = doc!(text_field=>val);
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         };
 
         let remove_document = |index_writer: &mut IndexWriter, val: &'static str| {
             let delterm = Term::from_field_text(text_field, val);
-            index_writer.delete_term(delterm);
+            index_writer.delete_term(delterm).unwrap();
         };
 
         add_document(&mut index_writer, "63");
@@ -763,7 +813,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af af af bc bc");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -801,15 +851,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af af af b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -876,15 +926,15 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field=>"af b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field=>"a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -922,7 +972,7 @@ mod tests {
         let mut index_writer = index.writer_with_num_threads(1, 50_000_000).unwrap();
         {
             let document = doc!(fast_field_unsigned => 4u64, fast_field_signed=>4i64);
-            index_writer.add_document(document);
+            index_writer.add_document(document).unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -1,6 +1,6 @@
 use super::BIT_PACKER;
 use bitpacking::{BitPacker, BitPacker4x};
-use common::{BinarySerializable, FixedSize};
+use common::FixedSize;
 use directory::ReadOnlySource;
 use owned_read::OwnedRead;
 use positions::COMPRESSION_BLOCK_SIZE;
@@ -61,18 +61,18 @@ impl PositionReader {
         skip_source: ReadOnlySource,
         offset: u64,
     ) -> PositionReader {
-        let skip_len = skip_source.len();
-        let (body, footer) = skip_source.split(skip_len - u32::SIZE_IN_BYTES);
-        let num_long_skips = u32::deserialize(&mut footer.as_slice()).expect("Index corrupted");
-        let body_split = body.len() - u64::SIZE_IN_BYTES * (num_long_skips as usize);
-        let (skip_body, long_skips) = body.split(body_split);
+        let (body, footer) = skip_source.split_from_end(u32::SIZE_IN_BYTES);
+        let num_long_skips = footer.read_u32_at(0).expect("Index corrupted");
+        let (skip_body, long_skips) =
+            body.split_from_end(u64::SIZE_IN_BYTES * (num_long_skips as usize));
         let long_skip_id = (offset / LONG_SKIP_INTERVAL) as usize;
         let small_skip = (offset - (long_skip_id as u64) * (LONG_SKIP_INTERVAL as u64)) as usize;
         let offset_num_bytes: u64 = {
             if long_skip_id > 0 {
-                let mut long_skip_blocks: &[u8] =
-                    &long_skips.as_slice()[(long_skip_id - 1) * 8..][..8];
-                u64::deserialize(&mut long_skip_blocks).expect("Index corrupted") * 16
+                long_skips
+                    .read_u64_at((long_skip_id - 1) * u64::SIZE_IN_BYTES)
+                    .expect("Index corrupted")
+                    * 16
             } else {
                 0
             }
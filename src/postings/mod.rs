@@ -49,9 +49,11 @@ pub mod tests {
     use core::Index;
     use core::SegmentComponent;
     use core::SegmentReader;
-    use docset::{DocSet, SkipResult};
+    use docset::{BlockDocSet, DocSet, SkipResult, DOC_BLOCK_LEN};
     use fieldnorm::FieldNormReader;
+    use futures::Future;
     use indexer::operation::AddOperation;
+    use indexer::NoMergePolicy;
     use indexer::SegmentWriter;
     use query::Scorer;
     use rand::{Rng, SeedableRng};
@@ -59,9 +61,11 @@ pub mod tests {
     use schema::Field;
     use schema::IndexRecordOption;
     use schema::{Document, SchemaBuilder, Term, INT_INDEXED, STRING, TEXT};
+    use schema::{PayloadToken, TextFieldIndexing, TextOptions, FAST};
     use std::iter;
     use DocId;
     use Score;
+    use Searcher;
 
     #[test]
     pub fn test_position_write() {
@@ -94,12 +98,20 @@ pub mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 30_000_000).unwrap();
-        index_writer.add_document(doc!(title => r#"abc abc abc"#));
-        index_writer.add_document(doc!(title => r#"abc be be be be abc"#));
+        index_writer
+            .add_document(doc!(title => r#"abc abc abc"#))
+            .unwrap();
+        index_writer
+            .add_document(doc!(title => r#"abc be be be be abc"#))
+            .unwrap();
         for _ in 0..1_000 {
-            index_writer.add_document(doc!(title => r#"abc abc abc"#));
+            index_writer
+                .add_document(doc!(title => r#"abc abc abc"#))
+                .unwrap();
         }
-        index_writer.add_document(doc!(title => r#"abc be be be be abc"#));
+        index_writer
+            .add_document(doc!(title => r#"abc be be be be abc"#))
+            .unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
 
@@ -286,12 +298,12 @@ pub mod tests {
             {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "g b b d c g c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let mut doc = Document::default();
                 doc.add_text(text_field, "g a b b a d c g c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -309,6 +321,217 @@ pub mod tests {
         assert_eq!(&positions[..], &[1u32, 4]);
     }
 
+    fn test_payload(id: u64) -> Vec<u8> {
+        vec![id as u8, (id >> 8) as u8, 7u8, 9u8]
+    }
+
+    // Checks the payloads of all of the documents of the searcher,
+    // and returns the number of documents checked.
+    fn check_payloads(searcher: &Searcher, text_field: Field, id_field: Field) -> usize {
+        let payload_term = Term::from_field_text(text_field, "payload");
+        let hello_term = Term::from_field_text(text_field, "hello");
+        let mut positions = Vec::new();
+        let mut num_docs = 0;
+        for segment_reader in searcher.segment_readers() {
+            let id_reader = segment_reader.fast_field_reader::<u64>(id_field).unwrap();
+            let inverted_index = segment_reader.inverted_index(text_field);
+            let mut postings = inverted_index
+                .read_postings(&payload_term, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap();
+            while postings.advance() {
+                let doc = postings.doc();
+                if segment_reader.is_deleted(doc) {
+                    continue;
+                }
+                assert_eq!(postings.payload(), &test_payload(id_reader.get(doc))[..]);
+                postings.positions(&mut positions);
+                assert_eq!(&positions[..], &[1u32]);
+                num_docs += 1;
+            }
+            // Tokens coming from the text get an all-zero payload.
+            let mut postings = inverted_index
+                .read_postings(&hello_term, IndexRecordOption::WithFreqs)
+                .unwrap();
+            assert!(postings.advance());
+            assert_eq!(postings.payload(), &[0u8; 4]);
+
+            let mut postings = inverted_index
+                .read_postings(&payload_term, IndexRecordOption::Basic)
+                .unwrap();
+            if postings.skip_next(140) != SkipResult::End {
+                let doc = postings.doc();
+                assert_eq!(postings.payload(), &test_payload(id_reader.get(doc))[..]);
+            }
+        }
+        num_docs
+    }
+
+    #[test]
+    fn test_postings_payloads() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("default")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_payload_size(4),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let plain_field = schema_builder.add_text_field("plain", TEXT);
+        let id_field = schema_builder.add_u64_field("id", FAST | INT_INDEXED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for id in 0u64..400u64 {
+                let mut doc = doc!(
+                    text_field => "hello",
+                    plain_field => "hello",
+                    id_field => id
+                );
+                let tokens = vec![PayloadToken::new("payload", &test_payload(id))];
+                doc.add_payload_tokens(text_field, tokens);
+                index_writer.add_document(doc).unwrap();
+                if id == 150 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        assert_eq!(
+            check_payloads(&*index.searcher(), text_field, id_field),
+            400
+        );
+
+        // Fields without payloads return empty payloads.
+        {
+            let searcher = index.searcher();
+            let mut postings = searcher
+                .segment_reader(0)
+                .inverted_index(plain_field)
+                .read_postings(
+                    &Term::from_field_text(plain_field, "hello"),
+                    IndexRecordOption::WithFreqs,
+                ).unwrap();
+            assert!(postings.advance());
+            assert!(postings.payload().is_empty());
+        }
+
+        // Payloads survive merges, along with the doc id remapping.
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for id in (0u64..400u64).filter(|id| id % 3 == 0) {
+                index_writer
+                    .delete_term(Term::from_field_u64(id_field, id))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            let segment_ids = index.searchable_segment_ids().unwrap();
+            index_writer.merge(&segment_ids).unwrap().wait().unwrap();
+            index_writer.wait_merging_threads().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(check_payloads(&*searcher, text_field, id_field), 266);
+    }
+
+    #[test]
+    fn test_postings_payload_size_mismatch() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_options = TextOptions::default()
+            .set_indexing_options(TextFieldIndexing::default().set_payload_size(4));
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        let mut doc = Document::default();
+        doc.add_payload_tokens(text_field, vec![PayloadToken::new("a", &[1u8, 2u8])]);
+        index_writer.add_document(doc).unwrap();
+        assert!(index_writer.commit().is_err());
+    }
+
+    #[test]
+    fn test_postings_token_occurrence_cap() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("default")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_max_token_occurrences_per_doc(3),
+        );
+        let capped_field = schema_builder.add_text_field("capped", text_options);
+        let plain_field = schema_builder.add_text_field("plain", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(
+                    capped_field => "spam spam spam spam spam ham",
+                    plain_field => "spam spam spam spam spam ham"
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    capped_field => "ham and spam and ham",
+                    plain_field => "ham and spam and ham"
+                ))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let read_postings = |field: Field, text: &str| {
+            segment_reader
+                .inverted_index(field)
+                .read_postings(
+                    &Term::from_field_text(field, text),
+                    IndexRecordOption::WithFreqsAndPositions,
+                ).unwrap()
+        };
+        let mut positions = Vec::new();
+
+        // The occurrences beyond the cap are dropped...
+        let mut postings = read_postings(capped_field, "spam");
+        assert!(postings.advance());
+        assert_eq!(postings.doc(), 0);
+        assert_eq!(postings.term_freq(), 3);
+        postings.positions(&mut positions);
+        assert_eq!(&positions[..], &[0u32, 1, 2]);
+        let mut postings = read_postings(capped_field, "ham");
+        assert!(postings.advance());
+        postings.positions(&mut positions);
+        assert_eq!(&positions[..], &[5u32]);
+        // ... but they still count in the fieldnorm.
+        let fieldnorm_reader = segment_reader.get_fieldnorms_reader(capped_field);
+        assert_eq!(fieldnorm_reader.fieldnorm(0), 6);
+
+        // Documents under the cap are indexed as usual.
+        for text in &["spam", "ham", "and"] {
+            let mut capped_postings = read_postings(capped_field, text);
+            let mut plain_postings = read_postings(plain_field, text);
+            let mut plain_positions = Vec::new();
+            while plain_postings.advance() {
+                assert!(capped_postings.advance());
+                assert_eq!(capped_postings.doc(), plain_postings.doc());
+                if capped_postings.doc() == 1 {
+                    assert_eq!(capped_postings.term_freq(), plain_postings.term_freq());
+                    capped_postings.positions(&mut positions);
+                    plain_postings.positions(&mut plain_positions);
+                    assert_eq!(positions, plain_positions);
+                }
+            }
+            assert!(!capped_postings.advance());
+        }
+        assert_eq!(
+            fieldnorm_reader.fieldnorm(1),
+            segment_reader
+                .get_fieldnorms_reader(plain_field)
+                .fieldnorm(1)
+        );
+    }
+
     #[test]
     fn test_skip_next() {
         let term_0 = Term::from_field_u64(Field(0), 0);
@@ -330,7 +553,7 @@ pub mod tests {
                     doc.add_u64(value_field, 2);
                     doc.add_u64(value_field, (i % 2) as u64);
 
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 assert!(index_writer.commit().is_ok());
             }
@@ -402,7 +625,7 @@ pub mod tests {
         // delete some of the documents
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.delete_term(term_0);
+            index_writer.delete_term(term_0).unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
@@ -452,7 +675,7 @@ pub mod tests {
         // delete everything else
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.delete_term(term_1);
+            index_writer.delete_term(term_1).unwrap();
 
             assert!(index_writer.commit().is_ok());
         }
@@ -521,7 +744,7 @@ pub mod tests {
                         doc.add_text(text_field, "c");
                     }
                     doc.add_text(text_field, "d");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 assert!(index_writer.commit().is_ok());
             }
@@ -596,6 +819,94 @@ pub mod tests {
         }
     }
 
+    /// Checks that going through the docsets block by block
+    /// gives the same documents as going through them one at a time,
+    /// after skipping to each of the `targets` (and without skipping).
+    ///
+    /// The docsets are also advanced after each block, to check that
+    /// `.fill_block(...)` leaves them positioned on the last document written.
+    pub fn test_fill_block_against_advance<TDocSet: BlockDocSet, F: Fn() -> TDocSet>(
+        docset_factory: F,
+        targets: Vec<u32>,
+    ) {
+        let mut block = [0u32; DOC_BLOCK_LEN];
+        for target_opt in iter::once(None).chain(targets.into_iter().map(Some)) {
+            let mut block_docset = docset_factory();
+            let mut docset = docset_factory();
+            if let Some(target) = target_opt {
+                let skip_result = block_docset.skip_next(target);
+                assert_eq!(docset.skip_next(target), skip_result);
+                if skip_result == SkipResult::End {
+                    continue;
+                }
+                assert_eq!(block_docset.doc(), docset.doc());
+            }
+            loop {
+                let num_docs = block_docset.fill_block(&mut block);
+                if num_docs == 0 {
+                    assert!(!docset.advance());
+                    break;
+                }
+                for &doc in &block[..num_docs] {
+                    assert!(docset.advance());
+                    assert_eq!(docset.doc(), doc, "Failed after skipping to {:?}", target_opt);
+                }
+                assert_eq!(block_docset.doc(), block[num_docs - 1]);
+                if block_docset.advance() {
+                    assert!(docset.advance());
+                    assert_eq!(block_docset.doc(), docset.doc());
+                } else {
+                    assert!(!docset.advance());
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_fill_block_skips_positions() {
+        let mut schema_builder = SchemaBuilder::new();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 30_000_000).unwrap();
+            let mut rng = StdRng::from_seed([3u8; 32]);
+            for _ in 0..1_000 {
+                let num_abc = rng.gen_range(1, 5);
+                let text = iter::repeat("abc be")
+                    .take(num_abc)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                index_writer.add_document(doc!(title => text)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let inverted_index = searcher.segment_reader(0u32).inverted_index(title);
+        let term = Term::from_field_text(title, "abc");
+        let read_postings = || {
+            inverted_index
+                .read_postings(&term, IndexRecordOption::WithFreqsAndPositions)
+                .unwrap()
+        };
+        test_fill_block_against_advance(&read_postings, vec![0, 100, 127, 128, 500, 999]);
+
+        let mut block_postings = read_postings();
+        let mut postings = read_postings();
+        let mut block = [0u32; DOC_BLOCK_LEN];
+        let (mut block_positions, mut positions) = (Vec::new(), Vec::new());
+        assert_eq!(block_postings.skip_next(50), SkipResult::Reached);
+        while block_postings.fill_block(&mut block) > 0 {
+            // The positions of the last document of each block are those
+            // read when going through the documents one at a time.
+            assert_eq!(postings.skip_next(block_postings.doc()), SkipResult::Reached);
+            block_postings.positions(&mut block_positions);
+            postings.positions(&mut positions);
+            assert_eq!(block_positions, positions);
+        }
+    }
 }
 
 #[cfg(all(test, feature = "unstable"))]
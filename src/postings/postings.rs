@@ -23,4 +23,13 @@ pub trait Postings: DocSet + 'static {
     fn positions(&mut self, output: &mut Vec<u32>) {
         self.positions_with_offset(0u32, output);
     }
+
+    /// Returns the payload attached to the term in the current document.
+    ///
+    /// The payload has the size defined by `TextFieldIndexing::set_payload_size`,
+    /// and is empty if the field does not have payloads.
+    /// It is typically read by custom scorers.
+    fn payload(&self) -> &[u8] {
+        &[]
+    }
 }
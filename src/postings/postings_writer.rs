@@ -1,10 +1,13 @@
-use super::stacker::{Addr, MemoryArena, TermHashMap};
+use super::stacker::{Addr, ExpUnrolledLinkedList, MemoryArena, TermHashMap};
 
-use postings::recorder::{NothingRecorder, Recorder, TFAndPositionRecorder, TermFrequencyRecorder};
+use byteorder::{ByteOrder, LittleEndian};
+use postings::recorder::{
+    DocSink, NothingRecorder, Recorder, TFAndPositionRecorder, TermFrequencyRecorder,
+};
 use postings::UnorderedTermId;
 use postings::{FieldSerializer, InvertedIndexSerializer};
 use schema::IndexRecordOption;
-use schema::{Field, FieldEntry, FieldType, Schema, Term};
+use schema::{Field, FieldEntry, FieldType, PayloadToken, Schema, Term};
 use std::collections::HashMap;
 use std::io;
 use std::marker::PhantomData;
@@ -19,25 +22,33 @@ fn posting_from_field_entry(field_entry: &FieldEntry) -> Box<PostingsWriter> {
     match *field_entry.field_type() {
         FieldType::Str(ref text_options) => text_options
             .get_indexing_options()
-            .map(|indexing_options| match indexing_options.index_option() {
-                IndexRecordOption::Basic => {
-                    SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
-                }
-                IndexRecordOption::WithFreqs => {
-                    SpecializedPostingsWriter::<TermFrequencyRecorder>::new_boxed()
-                }
-                IndexRecordOption::WithFreqsAndPositions => {
-                    SpecializedPostingsWriter::<TFAndPositionRecorder>::new_boxed()
+            .map(|indexing_options| {
+                let payload_size = indexing_options.payload_size();
+                match (indexing_options.index_option(), payload_size) {
+                    (IndexRecordOption::Basic, 0) => {
+                        SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
+                    }
+                    (IndexRecordOption::WithFreqs, 0) => {
+                        SpecializedPostingsWriter::<TermFrequencyRecorder>::new_boxed()
+                    }
+                    (IndexRecordOption::WithFreqsAndPositions, 0) => {
+                        SpecializedPostingsWriter::<TFAndPositionRecorder>::new_boxed()
+                    }
+                    (IndexRecordOption::Basic, _) => {
+                        PayloadPostingsWriter::<NothingRecorder>::new_boxed(payload_size)
+                    }
+                    (IndexRecordOption::WithFreqs, _) => {
+                        PayloadPostingsWriter::<TermFrequencyRecorder>::new_boxed(payload_size)
+                    }
+                    (IndexRecordOption::WithFreqsAndPositions, _) => {
+                        PayloadPostingsWriter::<TFAndPositionRecorder>::new_boxed(payload_size)
+                    }
                 }
             }).unwrap_or_else(|| SpecializedPostingsWriter::<NothingRecorder>::new_boxed()),
-        FieldType::U64(_) | FieldType::I64(_) | FieldType::HierarchicalFacet => {
-            SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
-        }
-        FieldType::Bytes => {
-            // FieldType::Bytes cannot actually be indexed.
-            // TODO fix during the indexer refactoring described in #276
-            SpecializedPostingsWriter::<NothingRecorder>::new_boxed()
-        }
+        FieldType::U64(_)
+        | FieldType::I64(_)
+        | FieldType::HierarchicalFacet
+        | FieldType::Bytes(_) => SpecializedPostingsWriter::<NothingRecorder>::new_boxed(),
     }
 }
 
@@ -70,7 +81,12 @@ impl MultiFieldPostingsWriter {
         self.term_index.mem_usage() + self.heap.mem_usage()
     }
 
-    pub fn index_text(&mut self, doc: DocId, field: Field, token_stream: &mut TokenStream) -> u32 {
+    pub fn index_text(
+        &mut self,
+        doc: DocId,
+        field: Field,
+        token_stream: &mut TokenStream,
+    ) -> (u32, u32) {
         let postings_writer = self.per_field_postings_writers[field.0 as usize].deref_mut();
         postings_writer.index_text(
             &mut self.term_index,
@@ -81,6 +97,33 @@ impl MultiFieldPostingsWriter {
         )
     }
 
+    /// Indexes pretokenized text along with the payloads of its tokens,
+    /// starting at the position `start_position`.
+    ///
+    /// Returns the number of tokens.
+    pub fn index_payload_tokens(
+        &mut self,
+        doc: DocId,
+        field: Field,
+        tokens: &[PayloadToken],
+        start_position: u32,
+    ) -> u32 {
+        let postings_writer = self.per_field_postings_writers[field.0 as usize].deref_mut();
+        let mut term = Term::for_field(field);
+        for (position, token) in (start_position..).zip(tokens) {
+            term.set_text(&token.text);
+            postings_writer.subscribe_with_payload(
+                &mut self.term_index,
+                doc,
+                position,
+                &term,
+                &token.payload,
+                &mut self.heap,
+            );
+        }
+        tokens.len() as u32
+    }
+
     pub fn subscribe(&mut self, doc: DocId, term: &Term) -> UnorderedTermId {
         let postings_writer = self.per_field_postings_writers[term.field().0 as usize].deref_mut();
         postings_writer.subscribe(&mut self.term_index, doc, 0u32, term, &mut self.heap)
@@ -141,8 +184,7 @@ impl MultiFieldPostingsWriter {
                         }).collect();
                     unordered_term_mappings.insert(field, mapping);
                 }
-                FieldType::U64(_) | FieldType::I64(_) => {}
-                FieldType::Bytes => {}
+                FieldType::U64(_) | FieldType::I64(_) | FieldType::Bytes(_) => {}
             }
 
             let postings_writer = &self.per_field_postings_writers[field.0 as usize];
@@ -181,6 +223,22 @@ pub trait PostingsWriter {
         heap: &mut MemoryArena,
     ) -> UnorderedTermId;
 
+    /// Same as `subscribe`, also recording the payload of the posting
+    /// if the document does not contain the term yet.
+    ///
+    /// The default implementation ignores the payload.
+    fn subscribe_with_payload(
+        &mut self,
+        term_index: &mut TermHashMap,
+        doc: DocId,
+        pos: u32,
+        term: &Term,
+        _payload: &[u8],
+        heap: &mut MemoryArena,
+    ) -> UnorderedTermId {
+        self.subscribe(term_index, doc, pos, term, heap)
+    }
+
     /// Serializes the postings on disk.
     /// The actual serialization format is handled by the `PostingsSerializer`.
     fn serialize(
@@ -192,6 +250,9 @@ pub trait PostingsWriter {
     ) -> io::Result<()>;
 
     /// Tokenize a text and subscribe all of its token.
+    ///
+    /// Returns the number of tokens, and the position following
+    /// the position of the last token.
     fn index_text(
         &mut self,
         term_index: &mut TermHashMap,
@@ -199,13 +260,18 @@ pub trait PostingsWriter {
         field: Field,
         token_stream: &mut TokenStream,
         heap: &mut MemoryArena,
-    ) -> u32 {
+    ) -> (u32, u32) {
         let mut term = Term::for_field(field);
-        let mut sink = |token: &Token| {
-            term.set_text(token.text.as_str());
-            self.subscribe(term_index, doc_id, token.position as u32, &term, heap);
+        let mut end_position = 0u32;
+        let num_tokens = {
+            let mut sink = |token: &Token| {
+                term.set_text(token.text.as_str());
+                self.subscribe(term_index, doc_id, token.position as u32, &term, heap);
+                end_position = end_position.max(token.position as u32 + 1);
+            };
+            token_stream.process(&mut sink)
         };
-        token_stream.process(&mut sink)
+        (num_tokens, end_position)
     }
 
     fn total_num_tokens(&self) -> u64;
@@ -283,3 +349,151 @@ impl<Rec: Recorder + 'static> PostingsWriter for SpecializedPostingsWriter<Rec>
         self.total_num_tokens
     }
 }
+
+/// Recorder of a field with payloads: wraps the recorder of the field,
+/// and records the payload of each document.
+///
+/// Payloads are stored as `u32` words in a separate stack.
+#[derive(Clone, Copy)]
+struct PayloadRecorder<Rec: Recorder> {
+    recorder: Rec,
+    payloads: ExpUnrolledLinkedList,
+}
+
+/// `DocSink` attaching payloads, in order, to the documents
+/// written by a recorder.
+struct PayloadDocSink<'a, 'b: 'a, I: Iterator<Item = &'a [u8]>> {
+    serializer: &'a mut FieldSerializer<'b>,
+    payloads: I,
+}
+
+impl<'a, 'b: 'a, I: Iterator<Item = &'a [u8]>> DocSink for PayloadDocSink<'a, 'b, I> {
+    fn write_doc(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+    ) -> io::Result<()> {
+        let payload = self
+            .payloads
+            .next()
+            .expect("The IndexWriter recorded a doc without a payload.");
+        self.serializer
+            .write_doc_with_payload(doc_id, term_freq, position_deltas, payload)
+    }
+}
+
+/// `PostingsWriter` for the fields with payloads.
+pub struct PayloadPostingsWriter<Rec: Recorder + 'static> {
+    payload_size: usize,
+    total_num_tokens: u64,
+    _recorder_type: PhantomData<Rec>,
+}
+
+impl<Rec: Recorder + 'static> PayloadPostingsWriter<Rec> {
+    /// Builds a `PayloadPostingsWriter` storing its data in a heap.
+    pub fn new_boxed(payload_size: usize) -> Box<PostingsWriter> {
+        Box::new(PayloadPostingsWriter::<Rec> {
+            payload_size,
+            total_num_tokens: 0u64,
+            _recorder_type: PhantomData,
+        })
+    }
+
+    fn num_payload_words(&self) -> usize {
+        (self.payload_size + 3) / 4
+    }
+}
+
+impl<Rec: Recorder + 'static> PostingsWriter for PayloadPostingsWriter<Rec> {
+    fn subscribe(
+        &mut self,
+        term_index: &mut TermHashMap,
+        doc: DocId,
+        position: u32,
+        term: &Term,
+        heap: &mut MemoryArena,
+    ) -> UnorderedTermId {
+        self.subscribe_with_payload(term_index, doc, position, term, &[], heap)
+    }
+
+    fn subscribe_with_payload(
+        &mut self,
+        term_index: &mut TermHashMap,
+        doc: DocId,
+        position: u32,
+        term: &Term,
+        payload: &[u8],
+        heap: &mut MemoryArena,
+    ) -> UnorderedTermId {
+        debug_assert!(term.as_slice().len() >= 4);
+        debug_assert!(payload.len() <= self.payload_size);
+        self.total_num_tokens += 1;
+        let num_payload_words = self.num_payload_words();
+        let push_payload = |payloads: &mut ExpUnrolledLinkedList, heap: &mut MemoryArena| {
+            for word_ord in 0..num_payload_words {
+                let start = (word_ord * 4).min(payload.len());
+                let stop = (start + 4).min(payload.len());
+                let mut word_bytes = [0u8; 4];
+                word_bytes[..stop - start].copy_from_slice(&payload[start..stop]);
+                payloads.push(LittleEndian::read_u32(&word_bytes), heap);
+            }
+        };
+        term_index.mutate_or_create(term, |opt_recorder: Option<PayloadRecorder<Rec>>| {
+            if let Some(mut payload_recorder) = opt_recorder {
+                let current_doc = payload_recorder.recorder.current_doc();
+                if current_doc != doc {
+                    payload_recorder.recorder.close_doc(heap);
+                    payload_recorder.recorder.new_doc(doc, heap);
+                    push_payload(&mut payload_recorder.payloads, heap);
+                }
+                payload_recorder.recorder.record_position(position, heap);
+                payload_recorder
+            } else {
+                let mut payload_recorder = PayloadRecorder {
+                    recorder: Rec::new(heap),
+                    payloads: ExpUnrolledLinkedList::new(heap),
+                };
+                payload_recorder.recorder.new_doc(doc, heap);
+                push_payload(&mut payload_recorder.payloads, heap);
+                payload_recorder.recorder.record_position(position, heap);
+                payload_recorder
+            }
+        }) as UnorderedTermId
+    }
+
+    fn serialize(
+        &self,
+        term_addrs: &[(&[u8], Addr, UnorderedTermId)],
+        serializer: &mut FieldSerializer,
+        termdict_heap: &MemoryArena,
+        heap: &MemoryArena,
+    ) -> io::Result<()> {
+        let num_payload_words = self.num_payload_words();
+        let mut payload_bytes: Vec<u8> = Vec::new();
+        for &(term_bytes, addr, _) in term_addrs {
+            let payload_recorder: PayloadRecorder<Rec> = unsafe { termdict_heap.read(addr) };
+            payload_bytes.clear();
+            let mut word_bytes = [0u8; 4];
+            for (word_ord, word) in payload_recorder.payloads.iter(heap).enumerate() {
+                LittleEndian::write_u32(&mut word_bytes, word);
+                let num_bytes = (self.payload_size - (word_ord % num_payload_words) * 4).min(4);
+                payload_bytes.extend_from_slice(&word_bytes[..num_bytes]);
+            }
+            serializer.new_term(&term_bytes[4..])?;
+            {
+                let mut doc_sink = PayloadDocSink {
+                    serializer: &mut *serializer,
+                    payloads: payload_bytes.chunks(self.payload_size),
+                };
+                payload_recorder.recorder.serialize(&mut doc_sink, heap)?;
+            }
+            serializer.close_term()?;
+        }
+        Ok(())
+    }
+
+    fn total_num_tokens(&self) -> u64 {
+        self.total_num_tokens
+    }
+}
@@ -6,6 +6,28 @@ use DocId;
 const EMPTY_ARRAY: [u32; 0] = [0u32; 0];
 const POSITION_END: u32 = std::u32::MAX;
 
+/// Receives the postings of a term from a `Recorder`, one document at a time.
+pub trait DocSink {
+    /// See `FieldSerializer::write_doc`.
+    fn write_doc(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+    ) -> io::Result<()>;
+}
+
+impl<'a> DocSink for FieldSerializer<'a> {
+    fn write_doc(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+    ) -> io::Result<()> {
+        FieldSerializer::write_doc(self, doc_id, term_freq, position_deltas)
+    }
+}
+
 /// Recorder is in charge of recording relevant information about
 /// the presence of a term in a document.
 ///
@@ -29,7 +51,7 @@ pub trait Recorder: Copy {
     /// Close the document. It will help record the term frequency.
     fn close_doc(&mut self, heap: &mut MemoryArena);
     /// Pushes the postings information to the serializer.
-    fn serialize(&self, serializer: &mut FieldSerializer, heap: &MemoryArena) -> io::Result<()>;
+    fn serialize<S: DocSink>(&self, serializer: &mut S, heap: &MemoryArena) -> io::Result<()>;
 }
 
 /// Only records the doc ids
@@ -60,7 +82,7 @@ impl Recorder for NothingRecorder {
 
     fn close_doc(&mut self, _heap: &mut MemoryArena) {}
 
-    fn serialize(&self, serializer: &mut FieldSerializer, heap: &MemoryArena) -> io::Result<()> {
+    fn serialize<S: DocSink>(&self, serializer: &mut S, heap: &MemoryArena) -> io::Result<()> {
         for doc in self.stack.iter(heap) {
             serializer.write_doc(doc, 0u32, &EMPTY_ARRAY)?;
         }
@@ -104,7 +126,7 @@ impl Recorder for TermFrequencyRecorder {
         self.current_tf = 0;
     }
 
-    fn serialize(&self, serializer: &mut FieldSerializer, heap: &MemoryArena) -> io::Result<()> {
+    fn serialize<S: DocSink>(&self, serializer: &mut S, heap: &MemoryArena) -> io::Result<()> {
         // the last document has not been closed...
         // its term freq is self.current_tf.
         let mut doc_iter = self
@@ -154,7 +176,7 @@ impl Recorder for TFAndPositionRecorder {
         self.stack.push(POSITION_END, heap);
     }
 
-    fn serialize(&self, serializer: &mut FieldSerializer, heap: &MemoryArena) -> io::Result<()> {
+    fn serialize<S: DocSink>(&self, serializer: &mut S, heap: &MemoryArena) -> io::Result<()> {
         let mut doc_positions = Vec::with_capacity(100);
         let mut positions_iter = self.stack.iter(heap);
         while let Some(doc) = positions_iter.next() {
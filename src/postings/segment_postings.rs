@@ -1,7 +1,7 @@
 use common::BitSet;
 use common::HasLen;
 use common::{BinarySerializable, VInt};
-use docset::{DocSet, SkipResult};
+use docset::{BlockDocSet, DocSet, SkipResult, DOC_BLOCK_LEN};
 use fst::Streamer;
 use owned_read::OwnedRead;
 use positions::PositionReader;
@@ -99,6 +99,7 @@ impl SegmentPostings {
             OwnedRead::new(buffer),
             IndexRecordOption::Basic,
             IndexRecordOption::Basic,
+            0,
         );
         SegmentPostings::from_block_postings(block_segment_postings, None)
     }
@@ -292,6 +293,27 @@ impl DocSet for SegmentPostings {
     }
 }
 
+impl BlockDocSet for SegmentPostings {
+    // Hands over the rest of the current decoded block.
+    fn fill_block(&mut self, block: &mut [DocId; DOC_BLOCK_LEN]) -> usize {
+        if !self.advance() {
+            return 0;
+        }
+        let block_len = self.block_cursor.block_len();
+        let num_docs = block_len - self.cur;
+        block[..num_docs].copy_from_slice(&self.block_cursor.docs()[self.cur..]);
+        let last = block_len - 1;
+        if let Some(ref mut position_computer) = self.position_computer {
+            // As in `.advance()`, the positions of the documents
+            // the cursor goes past have to be skipped.
+            let num_skipped: u32 = self.block_cursor.freqs()[self.cur..last].iter().sum();
+            position_computer.add_skip(num_skipped as usize);
+        }
+        self.cur = last;
+        num_docs
+    }
+}
+
 impl HasLen for SegmentPostings {
     fn len(&self) -> usize {
         self.block_cursor.doc_freq()
@@ -314,6 +336,10 @@ impl Postings for SegmentPostings {
             output.clear();
         }
     }
+
+    fn payload(&self) -> &[u8] {
+        self.block_cursor.payload(self.cur)
+    }
 }
 
 /// `BlockSegmentPostings` is a cursor iterating over blocks
@@ -333,6 +359,9 @@ pub struct BlockSegmentPostings {
 
     num_vint_docs: usize,
 
+    payload_size: usize,
+    payloads: Vec<u8>,
+
     remaining_data: OwnedRead,
     skip_reader: SkipReader,
 }
@@ -364,6 +393,7 @@ impl BlockSegmentPostings {
         data: OwnedRead,
         record_option: IndexRecordOption,
         requested_option: IndexRecordOption,
+        payload_size: usize,
     ) -> BlockSegmentPostings {
         let freq_reading_option = match (record_option, requested_option) {
             (IndexRecordOption::Basic, _) => FreqReadingOption::NoFreq,
@@ -385,11 +415,24 @@ impl BlockSegmentPostings {
             freq_reading_option,
             doc_offset: 0,
             doc_freq,
+            payload_size,
+            payloads: Vec::new(),
             remaining_data: postings_data,
             skip_reader,
         }
     }
 
+    // Reads the payloads of the `num_docs` documents of the block.
+    fn read_payloads(&mut self, num_docs: usize) {
+        if self.payload_size > 0 {
+            let num_bytes = num_docs * self.payload_size;
+            self.payloads.clear();
+            self.payloads
+                .extend_from_slice(&self.remaining_data.as_ref()[..num_bytes]);
+            self.remaining_data.advance(num_bytes);
+        }
+    }
+
     // Resets the block segment postings on another position
     // in the postings file.
     //
@@ -459,6 +502,15 @@ impl BlockSegmentPostings {
         self.doc_decoder.output_len
     }
 
+    /// Return the payload of the document at index `idx` of the block.
+    ///
+    /// The payload is empty if the field does not have payloads.
+    #[inline]
+    pub fn payload(&self, idx: usize) -> &[u8] {
+        let start = idx * self.payload_size;
+        &self.payloads[start..start + self.payload_size]
+    }
+
     /// position on a block that may contains `doc_id`.
     /// Always advance the current block.
     ///
@@ -498,11 +550,13 @@ impl BlockSegmentPostings {
                         self.remaining_data.advance(num_consumed_bytes);
                     }
                 }
+                self.read_payloads(COMPRESSION_BLOCK_SIZE);
                 self.doc_offset = self.skip_reader.doc();
                 return BlockSegmentPostingsSkipResult::Success(skip_freqs);
             } else {
                 skip_freqs += self.skip_reader.tf_sum();
-                let advance_len = self.skip_reader.total_block_len();
+                let advance_len =
+                    self.skip_reader.total_block_len() + self.payload_size * COMPRESSION_BLOCK_SIZE;
                 self.doc_offset = self.skip_reader.doc();
                 self.remaining_data.advance(advance_len);
             }
@@ -510,6 +564,8 @@ impl BlockSegmentPostings {
 
         // we are now on the last, incomplete, variable encoded block.
         if self.num_vint_docs > 0 {
+            let num_vint_docs = self.num_vint_docs;
+            self.read_payloads(num_vint_docs);
             let num_compressed_bytes = self.doc_decoder.uncompress_vint_sorted(
                 self.remaining_data.as_ref(),
                 self.doc_offset,
@@ -564,10 +620,13 @@ impl BlockSegmentPostings {
                     self.remaining_data.advance(num_consumed_bytes);
                 }
             }
+            self.read_payloads(COMPRESSION_BLOCK_SIZE);
             // it will be used as the next offset.
             self.doc_offset = self.doc_decoder.output(COMPRESSION_BLOCK_SIZE - 1);
             true
         } else if self.num_vint_docs > 0 {
+            let num_vint_docs = self.num_vint_docs;
+            self.read_payloads(num_vint_docs);
             let num_compressed_bytes = self.doc_decoder.uncompress_vint_sorted(
                 self.remaining_data.as_ref(),
                 self.doc_offset,
@@ -600,6 +659,9 @@ impl BlockSegmentPostings {
             doc_offset: 0,
             doc_freq: 0,
 
+            payload_size: 0,
+            payloads: Vec::new(),
+
             remaining_data: OwnedRead::new(vec![]),
             skip_reader: SkipReader::new(OwnedRead::new(vec![]), IndexRecordOption::Basic),
         }
@@ -629,10 +691,12 @@ mod tests {
     use core::Index;
     use docset::DocSet;
     use fst::Streamer;
+    use postings::tests::test_fill_block_against_advance;
     use schema::IndexRecordOption;
     use schema::SchemaBuilder;
     use schema::Term;
     use schema::INT_INDEXED;
+    use tests::sample_with_seed;
     use DocId;
 
     #[test]
@@ -643,6 +707,17 @@ mod tests {
         assert_eq!(postings.len(), 0);
     }
 
+    #[test]
+    fn test_segment_postings_fill_block() {
+        for &(num_docs, ratio, seed) in &[(300, 0.5, 1u8), (10_000, 0.1, 2u8), (10_000, 0.9, 3u8)] {
+            let docs = sample_with_seed(num_docs, ratio, seed);
+            let targets = sample_with_seed(num_docs + 10, 0.01, seed + 1);
+            test_fill_block_against_advance(|| SegmentPostings::create_from_docs(&docs), targets);
+        }
+        test_fill_block_against_advance(|| SegmentPostings::create_from_docs(&[]), vec![0]);
+        test_fill_block_against_advance(SegmentPostings::empty, vec![]);
+    }
+
     #[test]
     fn test_empty_block_segment_postings() {
         let mut postings = BlockSegmentPostings::empty();
@@ -715,9 +790,9 @@ mod tests {
         let mut last_doc = 0u32;
         for doc in docs {
             for _ in last_doc..doc {
-                index_writer.add_document(doc!(int_field=>1u64));
+                index_writer.add_document(doc!(int_field=>1u64)).unwrap();
             }
-            index_writer.add_document(doc!(int_field=>0u64));
+            index_writer.add_document(doc!(int_field=>0u64)).unwrap();
             last_doc = doc + 1;
         }
         index_writer.commit().unwrap();
@@ -787,7 +862,7 @@ mod tests {
         // the other containing odd numbers.
         for i in 0..6 {
             let doc = doc!(int_field=> (i % 2) as u64);
-            index_writer.add_document(doc);
+            index_writer.add_document(doc).unwrap();
         }
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
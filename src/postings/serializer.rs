@@ -137,6 +137,7 @@ impl<'a> FieldSerializer<'a> {
         positions_write: &'a mut CountingWriter<WritePtr>,
         positionsidx_write: &'a mut CountingWriter<WritePtr>,
     ) -> io::Result<FieldSerializer<'a>> {
+        let payload_size = field_type.posting_payload_size();
         let (term_freq_enabled, position_enabled): (bool, bool) = match field_type {
             FieldType::Str(ref text_options) => {
                 if let Some(text_indexing_options) = text_options.get_indexing_options() {
@@ -154,7 +155,8 @@ impl<'a> FieldSerializer<'a> {
         let term_dictionary_builder =
             TermDictionaryBuilder::new(term_dictionary_write, &field_type)?;
         let postings_serializer =
-            PostingsSerializer::new(postings_write, term_freq_enabled, position_enabled);
+            PostingsSerializer::new(postings_write, term_freq_enabled, position_enabled)
+                .with_payload_size(payload_size);
         let positions_serializer_opt = if position_enabled {
             Some(PositionSerializer::new(positions_write, positionsidx_write))
         } else {
@@ -216,9 +218,25 @@ impl<'a> FieldSerializer<'a> {
         doc_id: DocId,
         term_freq: u32,
         position_deltas: &[u32],
+    ) -> io::Result<()> {
+        self.write_doc_with_payload(doc_id, term_freq, position_deltas, &[])
+    }
+
+    /// Same as `write_doc`, with the payload of the posting.
+    ///
+    /// The payload is ignored if the field does not have payloads,
+    /// and padded with zeros if it is shorter than the payload size
+    /// of the field.
+    pub fn write_doc_with_payload(
+        &mut self,
+        doc_id: DocId,
+        term_freq: u32,
+        position_deltas: &[u32],
+        payload: &[u8],
     ) -> io::Result<()> {
         self.current_term_info.doc_freq += 1;
-        self.postings_serializer.write_doc(doc_id, term_freq);
+        self.postings_serializer
+            .write_doc_with_payload(doc_id, term_freq, payload);
         if let Some(ref mut positions_serializer) = self.positions_serializer_opt.as_mut() {
             positions_serializer.write_all(position_deltas)?;
         }
@@ -255,6 +273,9 @@ impl<'a> FieldSerializer<'a> {
 struct Block {
     doc_ids: [DocId; COMPRESSION_BLOCK_SIZE],
     term_freqs: [u32; COMPRESSION_BLOCK_SIZE],
+    // `payload_size` bytes per document.
+    // Remains empty if postings do not have any payload.
+    payloads: Vec<u8>,
     len: usize,
 }
 
@@ -263,6 +284,7 @@ impl Block {
         Block {
             doc_ids: [0u32; COMPRESSION_BLOCK_SIZE],
             term_freqs: [0u32; COMPRESSION_BLOCK_SIZE],
+            payloads: Vec::new(),
             len: 0,
         }
     }
@@ -277,6 +299,14 @@ impl Block {
 
     fn clear(&mut self) {
         self.len = 0;
+        self.payloads.clear();
+    }
+
+    fn append_payload(&mut self, payload: &[u8], payload_size: usize) {
+        let payload = &payload[..payload.len().min(payload_size)];
+        self.payloads.extend_from_slice(payload);
+        let padded_len = self.payloads.len() + payload_size - payload.len();
+        self.payloads.resize(padded_len, 0u8);
     }
 
     fn append_doc(&mut self, doc: DocId, term_freq: u32) {
@@ -312,6 +342,7 @@ pub struct PostingsSerializer<W: Write> {
 
     termfreq_enabled: bool,
     termfreq_sum_enabled: bool,
+    payload_size: usize,
 }
 
 impl<W: Write> PostingsSerializer<W> {
@@ -332,9 +363,21 @@ impl<W: Write> PostingsSerializer<W> {
             last_doc_id_encoded: 0u32,
             termfreq_enabled,
             termfreq_sum_enabled,
+            payload_size: 0,
         }
     }
 
+    /// Attaches a payload of `payload_size` bytes to each document.
+    ///
+    /// The payloads of a block are written, as is, after its term
+    /// frequencies. For the last, variable int encoded, block, they
+    /// are written before the doc ids, so that the block can be
+    /// decoded without consuming the term frequencies.
+    pub fn with_payload_size(mut self, payload_size: usize) -> PostingsSerializer<W> {
+        self.payload_size = payload_size;
+        self
+    }
+
     fn write_block(&mut self) {
         {
             // encode the doc ids
@@ -359,11 +402,19 @@ impl<W: Write> PostingsSerializer<W> {
                 self.skip_write.write_total_term_freq(sum_freq);
             }
         }
+        self.postings_write.extend(&self.block.payloads);
         self.block.clear();
     }
 
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32) {
+        self.write_doc_with_payload(doc_id, term_freq, &[]);
+    }
+
+    pub fn write_doc_with_payload(&mut self, doc_id: DocId, term_freq: u32, payload: &[u8]) {
         self.block.append_doc(doc_id, term_freq);
+        if self.payload_size > 0 {
+            self.block.append_payload(payload, self.payload_size);
+        }
         if self.block.is_full() {
             self.write_block();
         }
@@ -381,6 +432,7 @@ impl<W: Write> PostingsSerializer<W> {
             //
             // In that case, the remaining part is encoded
             // using variable int encoding.
+            self.postings_write.write_all(&self.block.payloads)?;
             {
                 let block_encoded = self
                     .block_encoder
@@ -3,6 +3,10 @@ use std::io;
 
 /// `TermInfo` wraps the metadata associated to a Term.
 /// It is segment-local.
+///
+/// The total number of occurrences of the term is not stored,
+/// and the postings of a term end where the postings of the next
+/// term start.
 #[derive(Debug, Default, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub struct TermInfo {
     /// Number of documents in the segment containing the term
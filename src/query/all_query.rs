@@ -1,7 +1,8 @@
 use core::Searcher;
 use core::SegmentReader;
-use docset::DocSet;
+use docset::{BlockDocSet, DocSet, DOC_BLOCK_LEN};
 use query::{Query, Scorer, Weight};
+use std::cmp;
 use DocId;
 use Result;
 use Score;
@@ -29,6 +30,10 @@ impl Weight for AllWeight {
             max_doc: reader.max_doc(),
         }))
     }
+
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        u64::from(reader.max_doc())
+    }
 }
 
 enum State {
@@ -75,16 +80,35 @@ impl DocSet for AllScorer {
     }
 }
 
+impl BlockDocSet for AllScorer {
+    fn fill_block(&mut self, block: &mut [DocId; DOC_BLOCK_LEN]) -> usize {
+        if !self.advance() {
+            return 0;
+        }
+        let num_docs = cmp::min(DOC_BLOCK_LEN as DocId, self.max_doc - self.doc) as usize;
+        for (i, block_doc) in block[..num_docs].iter_mut().enumerate() {
+            *block_doc = self.doc + i as DocId;
+        }
+        self.doc += num_docs as DocId - 1;
+        num_docs
+    }
+}
+
 impl Scorer for AllScorer {
     fn score(&mut self) -> Score {
         1f32
     }
+
+    fn block_docset(&mut self) -> Option<&mut BlockDocSet> {
+        Some(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use super::AllQuery;
+    use super::{AllQuery, AllScorer, State};
+    use postings::tests::test_fill_block_against_advance;
     use query::Query;
     use schema::{SchemaBuilder, TEXT};
     use Index;
@@ -96,10 +120,10 @@ mod tests {
         let schema = schema_builder.build();
         let index = Index::create_in_ram(schema);
         let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
-        index_writer.add_document(doc!(field=>"aaa"));
-        index_writer.add_document(doc!(field=>"bbb"));
+        index_writer.add_document(doc!(field=>"aaa")).unwrap();
+        index_writer.add_document(doc!(field=>"bbb")).unwrap();
         index_writer.commit().unwrap();
-        index_writer.add_document(doc!(field=>"ccc"));
+        index_writer.add_document(doc!(field=>"ccc")).unwrap();
         index_writer.commit().unwrap();
         index.load_searchers().unwrap();
         let searcher = index.searcher();
@@ -122,4 +146,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_all_scorer_fill_block() {
+        for &max_doc in &[0u32, 1u32, 128u32, 1_000u32] {
+            let all_scorer = || AllScorer {
+                state: State::NotStarted,
+                doc: 0u32,
+                max_doc,
+            };
+            test_fill_block_against_advance(all_scorer, vec![0, 5, 127, 128, 999, 1_000]);
+        }
+    }
+
 }
@@ -1,6 +1,7 @@
 use common::{BitSet, TinySet};
-use docset::{DocSet, SkipResult};
+use docset::{BlockDocSet, DocSet, SkipResult};
 use std::cmp::Ordering;
+use std::sync::Arc;
 use DocId;
 
 /// A `BitSetDocSet` makes it possible to iterate through a bitset as if it was a `DocSet`.
@@ -12,8 +13,11 @@ use DocId;
 ///
 /// TODO: Consider implementing a `BitTreeSet` in order to advance faster
 /// when the bitset is sparse
+///
+/// The bitset is only read, so that it can be shared with other
+/// `BitSetDocSet`s, e.g. when it is cached across searches.
 pub struct BitSetDocSet {
-    docs: BitSet,
+    docs: Arc<BitSet>,
     cursor_bucket: u32, //< index associated to the current tiny bitset
     cursor_tinybitset: TinySet,
     doc: u32,
@@ -28,6 +32,12 @@ impl BitSetDocSet {
 
 impl From<BitSet> for BitSetDocSet {
     fn from(docs: BitSet) -> BitSetDocSet {
+        BitSetDocSet::from(Arc::new(docs))
+    }
+}
+
+impl From<Arc<BitSet>> for BitSetDocSet {
+    fn from(docs: Arc<BitSet>) -> BitSetDocSet {
         let first_tiny_bitset = if docs.max_value() == 0 {
             TinySet::empty()
         } else {
@@ -118,11 +128,15 @@ impl DocSet for BitSetDocSet {
     }
 }
 
+impl BlockDocSet for BitSetDocSet {}
+
 #[cfg(test)]
 mod tests {
     use super::BitSetDocSet;
     use common::BitSet;
     use docset::{DocSet, SkipResult};
+    use postings::tests::test_fill_block_against_advance;
+    use tests::sample_with_seed;
     use DocId;
 
     fn create_docbitset(docs: &[DocId], max_doc: DocId) -> BitSetDocSet {
@@ -152,6 +166,13 @@ mod tests {
         test_go_through_sequential(&[1, 2, 3, 4, 95, 96, 97, 98, 99]);
     }
 
+    #[test]
+    fn test_docbitset_fill_block() {
+        let docs = sample_with_seed(10_000, 0.3, 5u8);
+        let targets = sample_with_seed(10_000, 0.01, 6u8);
+        test_fill_block_against_advance(|| create_docbitset(&docs, 10_000), targets);
+    }
+
     #[test]
     fn test_docbitset_skip() {
         {
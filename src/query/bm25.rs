@@ -51,19 +51,32 @@ impl BM25Weight {
 
         let mut total_num_tokens = 0u64;
         let mut total_num_docs = 0u64;
+        let mut doc_freqs = vec![0u64; terms.len()];
         for segment_reader in searcher.segment_readers() {
-            let inverted_index = segment_reader.inverted_index(field);
+            // The segments whose postings were not loaded cannot be searched:
+            // creating their scorers returns a `ComponentNotLoaded` error.
+            let inverted_index = match segment_reader.try_inverted_index(field) {
+                Ok(inverted_index) => inverted_index,
+                Err(_) => continue,
+            };
             total_num_tokens += inverted_index.total_num_tokens();
             total_num_docs += u64::from(segment_reader.max_doc());
+            for (doc_freq, term) in doc_freqs.iter_mut().zip(terms) {
+                *doc_freq += u64::from(inverted_index.doc_freq(term));
+            }
         }
-        let average_fieldnorm = total_num_tokens as f32 / total_num_docs as f32;
-
-        let idf = terms
-            .iter()
-            .map(|term| {
-                let term_doc_freq = searcher.doc_freq(term);
-                idf(term_doc_freq, total_num_docs)
-            }).sum::<f32>();
+        // An index without any token, for instance an empty index,
+        // has no meaningful average: any positive value will do.
+        let average_fieldnorm = if total_num_tokens == 0 {
+            1f32
+        } else {
+            total_num_tokens as f32 / total_num_docs as f32
+        };
+
+        let idf = doc_freqs
+            .into_iter()
+            .map(|term_doc_freq| idf(term_doc_freq, total_num_docs))
+            .sum::<f32>();
         BM25Weight::new(idf, average_fieldnorm)
     }
 
@@ -74,6 +87,12 @@ impl BM25Weight {
         }
     }
 
+    /// Upper bound of the scores returned by `.score(...)`,
+    /// as the term frequency component is lower than 1.
+    pub fn max_score(&self) -> Score {
+        self.weight
+    }
+
     #[inline(always)]
     pub fn score(&self, fieldnorm_id: u8, term_freq: u32) -> Score {
         let norm = self.cache[fieldnorm_id as usize];
@@ -85,12 +104,29 @@ impl BM25Weight {
 #[cfg(test)]
 mod tests {
 
-    use super::idf;
+    use super::{idf, BM25Weight};
+    use schema::{SchemaBuilder, Term, TEXT};
     use tests::assert_nearly_equals;
+    use Index;
 
     #[test]
     fn test_idf() {
         assert_nearly_equals(idf(1, 2), 0.6931472);
     }
 
+    #[test]
+    fn test_bm25_weight_empty_index() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term = Term::from_field_text(text_field, "a");
+        let bm25_weight = BM25Weight::for_terms(&*searcher, &[term]);
+        assert!(bm25_weight.max_score().is_finite());
+        for fieldnorm_id in 0..256 {
+            assert!(bm25_weight.score(fieldnorm_id as u8, 1).is_finite());
+        }
+    }
+
 }
@@ -1,6 +1,7 @@
 use super::boolean_weight::BooleanWeight;
 use query::Occur;
 use query::Query;
+use query::QueryProfiler;
 use query::TermQuery;
 use query::Weight;
 use schema::IndexRecordOption;
@@ -51,6 +52,21 @@ impl Query for BooleanQuery {
         Ok(Box::new(BooleanWeight::new(sub_weights, scoring_enabled)))
     }
 
+    fn profiled_weight(
+        &self,
+        searcher: &Searcher,
+        scoring_enabled: bool,
+        profiler: &mut QueryProfiler,
+    ) -> Result<Box<Weight>> {
+        let mut sub_weights = Vec::with_capacity(self.subqueries.len());
+        for &(occur, ref subquery) in &self.subqueries {
+            let sub_weight = profiler.profile_subquery(&**subquery, searcher, scoring_enabled)?;
+            sub_weights.push((occur, sub_weight));
+        }
+        let weight = BooleanWeight::new(sub_weights, scoring_enabled);
+        Ok(profiler.wrap(Box::new(weight)))
+    }
+
     fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
         for (_occur, subquery) in &self.subqueries {
             subquery.query_terms(term_set);
@@ -1,6 +1,6 @@
 use core::SegmentReader;
 use downcast::Downcast;
-use query::intersect_scorers;
+use query::intersection::intersect_sorted_scorers;
 use query::score_combiner::{DoNothingCombiner, ScoreCombiner, SumWithCoordsCombiner};
 use query::term_query::TermScorer;
 use query::EmptyScorer;
@@ -60,12 +60,17 @@ impl BooleanWeight {
         reader: &SegmentReader,
     ) -> Result<Box<Scorer>> {
         let mut per_occur_scorers: HashMap<Occur, Vec<Box<Scorer>>> = HashMap::new();
+        let mut must_scorers: Vec<(u64, Box<Scorer>)> = Vec::new();
         for &(ref occur, ref subweight) in &self.weights {
             let sub_scorer: Box<Scorer> = subweight.scorer(reader)?;
-            per_occur_scorers
-                .entry(*occur)
-                .or_insert_with(Vec::new)
-                .push(sub_scorer);
+            if *occur == Occur::Must {
+                must_scorers.push((subweight.cost(reader), sub_scorer));
+            } else {
+                per_occur_scorers
+                    .entry(*occur)
+                    .or_insert_with(Vec::new)
+                    .push(sub_scorer);
+            }
         }
 
         let should_scorer_opt: Option<Box<Scorer>> = per_occur_scorers
@@ -76,9 +81,17 @@ impl BooleanWeight {
             .remove(&Occur::MustNot)
             .map(scorer_union::<TScoreCombiner>);
 
-        let must_scorer_opt: Option<Box<Scorer>> = per_occur_scorers
-            .remove(&Occur::Must)
-            .map(intersect_scorers);
+        // The cheapest clause drives the intersection.
+        let must_scorer_opt: Option<Box<Scorer>> = if must_scorers.is_empty() {
+            None
+        } else {
+            must_scorers.sort_by_key(|&(cost, _)| cost);
+            let must_scorers = must_scorers
+                .into_iter()
+                .map(|(_, scorer)| scorer)
+                .collect::<Vec<_>>();
+            Some(intersect_sorted_scorers(must_scorers))
+        };
 
         let positive_scorer: Box<Scorer> = match (should_scorer_opt, must_scorer_opt) {
             (Some(should_scorer), Some(must_scorer)) => {
@@ -123,4 +136,42 @@ impl Weight for BooleanWeight {
             self.complex_scorer::<DoNothingCombiner>(reader)
         }
     }
+
+    /// The cost of a conjunction is bounded by its cheapest `Must` clause.
+    /// Without any `Must` clause, the cost is the sum of the `Should` clauses.
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        let must_cost_opt = self
+            .weights
+            .iter()
+            .filter(|&&(occur, _)| occur == Occur::Must)
+            .map(|&(_, ref weight)| weight.cost(reader))
+            .min();
+        if let Some(must_cost) = must_cost_opt {
+            return must_cost;
+        }
+        let should_cost: u64 = self
+            .weights
+            .iter()
+            .filter(|&&(occur, _)| occur == Occur::Should)
+            .map(|&(_, ref weight)| weight.cost(reader))
+            .sum();
+        should_cost.min(u64::from(reader.max_doc()))
+    }
+
+    /// A conjunction can only match if all of its `Must` clauses can match.
+    /// Without any `Must` clause, one of the `Should` clauses has to match.
+    fn can_match(&self, reader: &SegmentReader) -> bool {
+        let has_must_clause = self.weights.iter().any(|&(occur, _)| occur == Occur::Must);
+        if has_must_clause {
+            self.weights
+                .iter()
+                .filter(|&&(occur, _)| occur == Occur::Must)
+                .all(|&(_, ref weight)| weight.can_match(reader))
+        } else {
+            self.weights
+                .iter()
+                .filter(|&&(occur, _)| occur == Occur::Should)
+                .any(|&(_, ref weight)| weight.can_match(reader))
+        }
+    }
 }
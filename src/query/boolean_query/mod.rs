@@ -18,6 +18,8 @@ mod tests {
     use query::RequiredOptionalScorer;
     use query::Scorer;
     use query::TermQuery;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     use schema::*;
     use Index;
 
@@ -31,23 +33,23 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field => "a b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "a c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "b c");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "a b c d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 let doc = doc!(text_field => "d");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -199,4 +201,165 @@ mod tests {
             assert_eq!(score_docs(&boolean_query), vec![0.977973, 0.84699446]);
         }
     }
+
+    #[test]
+    pub fn test_boolean_weight_cost() {
+        let (index, text_field) = aux_test_helper();
+        let searcher = index.searcher();
+        let segment_reader = searcher.segment_reader(0u32);
+        let make_term_query = |text: &str| {
+            let term_query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            );
+            let query: Box<Query> = Box::new(term_query);
+            query
+        };
+        let cost = |query: &Query| query.weight(&searcher, false).unwrap().cost(segment_reader);
+        assert_eq!(cost(&*make_term_query("a")), 3);
+        assert_eq!(cost(&*make_term_query("d")), 2);
+        assert_eq!(cost(&*make_term_query("z")), 0);
+        {
+            let boolean_query = BooleanQuery::from(vec![
+                (Occur::Must, make_term_query("c")),
+                (Occur::Must, make_term_query("d")),
+            ]);
+            assert_eq!(cost(&boolean_query), 2);
+        }
+        {
+            let boolean_query = BooleanQuery::from(vec![
+                (Occur::Should, make_term_query("a")),
+                (Occur::Should, make_term_query("d")),
+            ]);
+            assert_eq!(cost(&boolean_query), 5);
+        }
+        {
+            let boolean_query = BooleanQuery::from(vec![
+                (Occur::Should, make_term_query("a")),
+                (Occur::Should, make_term_query("c")),
+            ]);
+            // the sum of the costs is capped by max_doc.
+            assert_eq!(cost(&boolean_query), 5);
+        }
+    }
+
+    #[test]
+    pub fn test_boolean_must_clause_order_does_not_change_results() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut rng = StdRng::from_seed([3u8; 32]);
+        let mut expected_docs = Vec::new();
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for doc_id in 0u32..5_000u32 {
+                let mut text = String::from("common");
+                let is_rare = rng.gen_bool(1f64 / 50f64);
+                let is_medium = rng.gen_bool(1f64 / 3f64);
+                if is_rare {
+                    text.push_str(" rare");
+                }
+                if is_medium {
+                    text.push_str(" medium");
+                }
+                if is_rare && is_medium {
+                    expected_docs.push(doc_id);
+                }
+                index_writer.add_document(doc!(text_field => text)).unwrap();
+            }
+            assert!(index_writer.commit().is_ok());
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let make_term_query = |text: &str| {
+            let term_query = TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::WithFreqs,
+            );
+            let query: Box<Query> = Box::new(term_query);
+            query
+        };
+        let search = |terms: &[&str]| {
+            let clauses: Vec<(Occur, Box<Query>)> = terms
+                .iter()
+                .map(|text| (Occur::Must, make_term_query(text)))
+                .collect();
+            let boolean_query = BooleanQuery::from(clauses);
+            let mut test_collector = TestCollector::default();
+            searcher.search(&boolean_query, &mut test_collector).unwrap();
+            test_collector.docs_and_scores()
+        };
+        let (reference_docs, reference_scores) = search(&["rare", "medium", "common"]);
+        assert_eq!(reference_docs, expected_docs);
+        for permutation in &[
+            ["rare", "common", "medium"],
+            ["medium", "rare", "common"],
+            ["medium", "common", "rare"],
+            ["common", "rare", "medium"],
+            ["common", "medium", "rare"],
+        ] {
+            let (docs, scores) = search(&permutation[..]);
+            assert_eq!(docs, reference_docs);
+            assert_eq!(scores, reference_scores);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "unstable"))]
+mod bench {
+
+    use super::BooleanQuery;
+    use collector::CountCollector;
+    use query::{Occur, Query, TermQuery};
+    use schema::{Field, IndexRecordOption, SchemaBuilder, Term, STRING};
+    use test::Bencher;
+    use Index;
+
+    lazy_static! {
+        static ref RARE_AND_UBIQUITOUS_INDEX: Index = {
+            let mut schema_builder = SchemaBuilder::default();
+            let text_field = schema_builder.add_text_field("text", STRING);
+            let schema = schema_builder.build();
+            let index = Index::create_in_ram(schema);
+            {
+                let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+                for doc_id in 0..1_000_000 {
+                    if doc_id % 10_000 == 0 {
+                        index_writer
+                            .add_document(doc!(text_field => "rare", text_field => "ubiquitous"))
+                            .unwrap();
+                    } else {
+                        index_writer
+                            .add_document(doc!(text_field => "ubiquitous"))
+                            .unwrap();
+                    }
+                }
+                assert!(index_writer.commit().is_ok());
+            }
+            index.load_searchers().unwrap();
+            index
+        };
+    }
+
+    fn term_query(text: &str) -> Box<Query> {
+        Box::new(TermQuery::new(
+            Term::from_field_text(Field(0), text),
+            IndexRecordOption::Basic,
+        ))
+    }
+
+    #[bench]
+    fn bench_boolean_ubiquitous_first_intersection(b: &mut Bencher) {
+        let searcher = RARE_AND_UBIQUITOUS_INDEX.searcher();
+        let boolean_query = BooleanQuery::from(vec![
+            (Occur::Must, term_query("ubiquitous")),
+            (Occur::Must, term_query("rare")),
+        ]);
+        b.iter(|| {
+            let mut count_collector = CountCollector::default();
+            searcher.search(&boolean_query, &mut count_collector).unwrap();
+            count_collector.count()
+        });
+    }
 }
@@ -0,0 +1,207 @@
+use common::BitSet;
+use core::Searcher;
+use core::SegmentReader;
+use docset::{DocSet, SkipResult};
+use query::{Query, Scorer, Weight};
+use schema::Term;
+use std::collections::BTreeSet;
+use DocId;
+use Result;
+use Score;
+
+/// Query multiplying the scores of the documents matched
+/// by another query by a constant `boost`.
+///
+/// The documents matched are the same as the ones of the boosted query.
+#[derive(Debug)]
+pub struct BoostQuery {
+    query: Box<Query>,
+    boost: Score,
+}
+
+impl Clone for BoostQuery {
+    fn clone(&self) -> Self {
+        BoostQuery {
+            query: self.query.box_clone(),
+            boost: self.boost,
+        }
+    }
+}
+
+impl BoostQuery {
+    /// Creates a query multiplying the scores of `query` by `boost`.
+    pub fn new(query: Box<Query>, boost: Score) -> BoostQuery {
+        BoostQuery { query, boost }
+    }
+
+    /// Returns the factor applied to the scores.
+    pub fn boost(&self) -> Score {
+        self.boost
+    }
+}
+
+impl Query for BoostQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        let weight = self.query.weight(searcher, scoring_enabled)?;
+        Ok(Box::new(BoostWeight {
+            weight,
+            boost: self.boost,
+        }))
+    }
+
+    fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
+        self.query.query_terms(term_set)
+    }
+}
+
+struct BoostWeight {
+    weight: Box<Weight>,
+    boost: Score,
+}
+
+impl Weight for BoostWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let scorer = self.weight.scorer(reader)?;
+        Ok(Box::new(BoostScorer {
+            scorer,
+            boost: self.boost,
+        }))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> Result<u32> {
+        self.weight.count(reader)
+    }
+
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        self.weight.cost(reader)
+    }
+
+    fn max_score(&self, reader: &SegmentReader) -> Option<Score> {
+        // A negative boost turns the lowest score into the highest one,
+        // and the scores of the boosted query have no known lower bound.
+        if self.boost < 0f32 {
+            return None;
+        }
+        self.weight
+            .max_score(reader)
+            .map(|max_score| max_score * self.boost)
+    }
+
+    fn can_match(&self, reader: &SegmentReader) -> bool {
+        self.weight.can_match(reader)
+    }
+}
+
+struct BoostScorer {
+    scorer: Box<Scorer>,
+    boost: Score,
+}
+
+impl DocSet for BoostScorer {
+    fn advance(&mut self) -> bool {
+        self.scorer.advance()
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        self.scorer.skip_next(target)
+    }
+
+    fn fill_buffer(&mut self, buffer: &mut [DocId]) -> usize {
+        self.scorer.fill_buffer(buffer)
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+
+    fn append_to_bitset(&mut self, bitset: &mut BitSet) {
+        self.scorer.append_to_bitset(bitset)
+    }
+
+    fn count(&mut self) -> u32 {
+        self.scorer.count()
+    }
+}
+
+impl Scorer for BoostScorer {
+    fn score(&mut self) -> Score {
+        self.scorer.score() * self.boost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::BoostQuery;
+    use collector::TopCollector;
+    use query::{Query, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use Index;
+
+    #[test]
+    fn test_boost_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a a c"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "c")).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_query = TermQuery::new(
+            Term::from_field_text(text_field, "a"),
+            IndexRecordOption::WithFreqs,
+        );
+        let top_docs = |query: &Query| {
+            let mut top_collector = TopCollector::with_limit(3);
+            searcher.search(query, &mut top_collector).unwrap();
+            top_collector.top_docs()
+        };
+        let boost_query = BoostQuery::new(Box::new(term_query.clone()), 2.5);
+        let boosted_top_docs = top_docs(&boost_query);
+        let expected_top_docs: Vec<_> = top_docs(&term_query)
+            .into_iter()
+            .map(|(score, doc_address)| (score * 2.5, doc_address))
+            .collect();
+        assert_eq!(boosted_top_docs, expected_top_docs);
+        assert_eq!(boost_query.count(&searcher).unwrap(), 2);
+
+        let segment_reader = searcher.segment_reader(0);
+        let term_max_score = term_query
+            .weight(&searcher, true)
+            .unwrap()
+            .max_score(segment_reader)
+            .unwrap();
+        let max_score = |boost: f32| {
+            BoostQuery::new(Box::new(term_query.clone()), boost)
+                .weight(&searcher, true)
+                .unwrap()
+                .max_score(segment_reader)
+        };
+        assert_eq!(max_score(2.5), Some(term_max_score * 2.5));
+        assert_eq!(max_score(-2.5), None);
+
+        // With a negative boost, the least relevant document comes first.
+        let negative_boost_query = BoostQuery::new(Box::new(term_query.clone()), -1.0);
+        let negative_top_docs = top_docs(&negative_boost_query);
+        let mut expected_top_docs: Vec<_> = top_docs(&term_query)
+            .into_iter()
+            .map(|(score, doc_address)| (-score, doc_address))
+            .collect();
+        expected_top_docs.reverse();
+        assert_eq!(negative_top_docs, expected_top_docs);
+    }
+}
@@ -32,6 +32,14 @@ impl Weight for EmptyWeight {
     fn scorer(&self, _reader: &SegmentReader) -> Result<Box<Scorer>> {
         Ok(Box::new(EmptyScorer))
     }
+
+    fn cost(&self, _reader: &SegmentReader) -> u64 {
+        0u64
+    }
+
+    fn can_match(&self, _reader: &SegmentReader) -> bool {
+        false
+    }
 }
 
 /// `EmptyScorer` is a dummy `Scorer` in which no document matches.
@@ -0,0 +1,167 @@
+use query::{Query, RangeQuery, Weight};
+use schema::{Facet, Field, Type};
+use std::collections::Bound;
+use Result;
+use Searcher;
+
+/// `FacetTermQuery` matches the documents associated to a given facet,
+/// or to any of its descendants.
+///
+/// For instance, the facet `/category/electronics` matches documents
+/// associated to `/category/electronics` or `/category/electronics/phones`,
+/// but not to `/category/electronics2`.
+///
+/// The root facet `/` matches all of the documents with at least
+/// one facet in the field.
+///
+/// Matched documents all get a constant `Score` of one.
+///
+/// # Implementation
+///
+/// In the encoded representation of facets, the steps of the path are separated
+/// by `0u8`, which is also the smallest possible byte.
+/// The terms of the subtree are therefore exactly the terms within
+/// `[facet, facet + [1u8])`, and the query is run as a range query over
+/// the term dictionary of the facet field. The root facet is encoded as
+/// an empty path, so that its subtree is the whole term dictionary.
+#[derive(Clone, Debug)]
+pub struct FacetTermQuery {
+    range_query: RangeQuery,
+}
+
+impl FacetTermQuery {
+    /// Creates a query matching the documents associated to `facet`
+    /// or any of its descendants, in the facet field `field`.
+    pub fn new(field: Field, facet: &Facet) -> FacetTermQuery {
+        let (lower_bound, upper_bound) = if facet.is_root() {
+            (Bound::Unbounded, Bound::Unbounded)
+        } else {
+            let lower_bound = facet.encoded_bytes().to_vec();
+            let mut upper_bound = lower_bound.clone();
+            upper_bound.push(1u8);
+            (Bound::Included(lower_bound), Bound::Excluded(upper_bound))
+        };
+        FacetTermQuery {
+            range_query: RangeQuery::new_value_bytes_bounds(
+                field,
+                Type::HierarchicalFacet,
+                lower_bound,
+                upper_bound,
+            ),
+        }
+    }
+}
+
+impl Query for FacetTermQuery {
+    fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        self.range_query.weight(searcher, scoring_enabled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::FacetTermQuery;
+    use collector::TopScoreCollector;
+    use query::Query;
+    use schema::{Facet, SchemaBuilder};
+    use DocId;
+    use Index;
+
+    #[test]
+    fn test_facet_term_query() {
+        let mut schema_builder = SchemaBuilder::default();
+        let facet_field = schema_builder.add_facet_field("category");
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // 0
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/category/electronics")
+                ))
+                .unwrap();
+            // 1
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/category/electronics/phones")
+                ))
+                .unwrap();
+            // 2
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/category/electronics/phones/android")
+                ))
+                .unwrap();
+            // 3
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/category/electronics2")
+                ))
+                .unwrap();
+            // 4
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/category/books"),
+                    facet_field => Facet::from("/category/electronics/tv")
+                ))
+                .unwrap();
+            // 5
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/category/books/fantasy")
+                ))
+                .unwrap();
+            // 6
+            index_writer
+                .add_document(doc!(
+                    facet_field => Facet::from("/brand/acme")
+                ))
+                .unwrap();
+            // 7, without any facet.
+            index_writer.add_document(doc!()).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let matching_docs = |facet: &str| {
+            let query = FacetTermQuery::new(facet_field, &Facet::from(facet));
+            let mut top_collector = TopScoreCollector::with_limit(10);
+            searcher.search(&query, &mut top_collector).unwrap();
+            for (score, _) in top_collector.top_docs() {
+                assert_eq!(score, 1f32);
+            }
+            let mut docs: Vec<DocId> = top_collector
+                .docs()
+                .into_iter()
+                .map(|doc_address| doc_address.doc())
+                .collect();
+            docs.sort();
+            docs
+        };
+        assert_eq!(matching_docs("/category/electronics"), vec![0, 1, 2, 4]);
+        assert_eq!(matching_docs("/category/electronics/phones"), vec![1, 2]);
+        assert_eq!(
+            matching_docs("/category/electronics/phones/android"),
+            vec![2]
+        );
+        assert_eq!(matching_docs("/category/electronics2"), vec![3]);
+        assert_eq!(matching_docs("/category/books"), vec![4, 5]);
+        assert_eq!(matching_docs("/category"), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(matching_docs("/"), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            FacetTermQuery::new(facet_field, &Facet::root())
+                .count(&searcher)
+                .unwrap(),
+            7
+        );
+        assert!(matching_docs("/category/electro").is_empty());
+        assert_eq!(
+            FacetTermQuery::new(facet_field, &Facet::from("/category/books"))
+                .count(&searcher)
+                .unwrap(),
+            2
+        );
+    }
+}
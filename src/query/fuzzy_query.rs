@@ -1,22 +1,94 @@
 use levenshtein_automata::{LevenshteinAutomatonBuilder, DFA};
 use query::{AutomatonWeight, Query, Weight};
 use schema::Term;
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 use Result;
 use Searcher;
 
-lazy_static! {
-    static ref LEV_BUILDER: HashMap<(u8, bool), LevenshteinAutomatonBuilder> = {
-        let mut lev_builder_cache = HashMap::new();
-        // TODO make population lazy on a `(distance, val)` basis
-        for distance in 0..3 {
-            for &transposition in &[false, true] {
-                let lev_automaton_builder = LevenshteinAutomatonBuilder::new(distance, transposition);
-                lev_builder_cache.insert((distance, transposition), lev_automaton_builder);
-            }
+// Holds the builders of all of the distances up to `2`,
+// with and without transpositions.
+const DEFAULT_LEV_BUILDER_CACHE_CAPACITY: usize = 6;
+
+type LevBuilderKey = (u8, bool);
+
+// LRU cache of the Levenshtein automaton builders.
+//
+// A builder only depends on the distance and on the cost of
+// transpositions: it can build the automata of any query string.
+struct LevBuilderCache {
+    capacity: usize,
+    // Ordered from the least recently used to the most recently used.
+    builders: Vec<(LevBuilderKey, Arc<LevenshteinAutomatonBuilder>)>,
+}
+
+impl LevBuilderCache {
+    fn with_capacity(capacity: usize) -> LevBuilderCache {
+        LevBuilderCache {
+            capacity,
+            builders: Vec::new(),
         }
-        lev_builder_cache
-    };
+    }
+
+    fn get(&mut self, key: LevBuilderKey) -> Option<Arc<LevenshteinAutomatonBuilder>> {
+        let pos = self
+            .builders
+            .iter()
+            .position(|&(builder_key, _)| builder_key == key)?;
+        let entry = self.builders.remove(pos);
+        let builder = entry.1.clone();
+        self.builders.push(entry);
+        Some(builder)
+    }
+
+    fn insert(&mut self, key: LevBuilderKey, builder: Arc<LevenshteinAutomatonBuilder>) {
+        self.builders.retain(|&(builder_key, _)| builder_key != key);
+        self.builders.push((key, builder));
+        self.evict();
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        if self.builders.len() > self.capacity {
+            let num_evicted = self.builders.len() - self.capacity;
+            self.builders.drain(..num_evicted);
+        }
+    }
+}
+
+lazy_static! {
+    static ref LEV_BUILDER_CACHE: Mutex<LevBuilderCache> = Mutex::new(
+        LevBuilderCache::with_capacity(DEFAULT_LEV_BUILDER_CACHE_CAPACITY)
+    );
+}
+
+fn lev_automaton_builder(
+    distance: u8,
+    transposition_cost_one: bool,
+) -> Arc<LevenshteinAutomatonBuilder> {
+    let key = (distance, transposition_cost_one);
+    // The cache is always left in a consistent state,
+    // so that it is safe to ignore poisoning.
+    if let Some(builder) = LEV_BUILDER_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(key)
+    {
+        return builder;
+    }
+    // Creating a builder is expensive, and is done without holding the lock.
+    let builder = Arc::new(LevenshteinAutomatonBuilder::new(
+        distance,
+        transposition_cost_one,
+    ));
+    LEV_BUILDER_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(key, builder.clone());
+    builder
 }
 
 /// A Fuzzy Query matches all of the documents
@@ -40,16 +112,16 @@ lazy_static! {
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -105,9 +177,34 @@ impl FuzzyTermQuery {
         }
     }
 
+    /// Sets the number of Levenshtein automaton builders kept
+    /// in the cache shared by all of the fuzzy queries of the process.
+    ///
+    /// Creating the automaton of a fuzzy query requires a builder for
+    /// its distance and its cost of transpositions. Creating a builder
+    /// takes tens of milliseconds for a distance of `2`, while a cached
+    /// builder makes the creation of the automaton cheap.
+    /// The least recently used builders are evicted first, and a
+    /// capacity of `0` disables the cache.
+    ///
+    /// Defaults to `6`, which holds the builders of all of the
+    /// distances up to `2`, with and without transpositions.
+    pub fn set_automaton_cache_capacity(capacity: usize) {
+        LEV_BUILDER_CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_capacity(capacity);
+    }
+
+    /// Creates and caches the Levenshtein automaton builder for the
+    /// given parameters, so that the first fuzzy query using them
+    /// does not pay for its creation.
+    pub fn prewarm(distance: u8, transposition_cost_one: bool) {
+        lev_automaton_builder(distance, transposition_cost_one);
+    }
+
     fn specialized_weight(&self) -> Result<AutomatonWeight<DFA>> {
-        let automaton = LEV_BUILDER.get(&(self.distance, false))
-            .unwrap() // TODO return an error
+        let automaton = lev_automaton_builder(self.distance, self.transposition_cost_one)
             .build_dfa(self.term.text());
         Ok(AutomatonWeight::new(self.term.field(), automaton))
     }
@@ -121,14 +218,82 @@ impl Query for FuzzyTermQuery {
 
 #[cfg(test)]
 mod test {
-    use super::FuzzyTermQuery;
-    use collector::TopCollector;
+    use super::{FuzzyTermQuery, LevBuilderCache, DEFAULT_LEV_BUILDER_CACHE_CAPACITY};
+    use collector::{CountCollector, TopCollector};
+    use levenshtein_automata::LevenshteinAutomatonBuilder;
     use schema::SchemaBuilder;
     use schema::TEXT;
+    use std::sync::Arc;
     use tests::assert_nearly_equals;
     use Index;
     use Term;
 
+    #[test]
+    fn test_lev_builder_cache_lru() {
+        let builder = Arc::new(LevenshteinAutomatonBuilder::new(0, false));
+        let mut cache = LevBuilderCache::with_capacity(2);
+        cache.insert((0, false), builder.clone());
+        cache.insert((0, true), builder.clone());
+        assert!(cache.get((0, false)).is_some());
+        // (0, true) is now the least recently used builder.
+        cache.insert((1, false), builder.clone());
+        assert!(cache.get((0, true)).is_none());
+        assert!(cache.get((0, false)).is_some());
+        assert!(cache.get((1, false)).is_some());
+        cache.set_capacity(0);
+        assert!(cache.get((0, false)).is_none());
+        cache.insert((0, false), builder);
+        assert!(cache.get((0, false)).is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_term_with_and_without_cache() {
+        let mut schema_builder = SchemaBuilder::new();
+        let country_field = schema_builder.add_text_field("country", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
+            for country in &["japan", "korea", "china", "chile", "chad"] {
+                index_writer
+                    .add_document(doc!(country_field => *country))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |text: &str, distance: u8, transposition_cost_one: bool| {
+            let term = Term::from_field_text(country_field, text);
+            let query = FuzzyTermQuery::new(term, distance, transposition_cost_one);
+            let mut count_collector = CountCollector::default();
+            searcher.search(&query, &mut count_collector).unwrap();
+            count_collector.count()
+        };
+        let queries = [
+            ("chine", 1, false),
+            ("cha", 1, false),
+            ("cha", 2, false),
+            ("japna", 1, false),
+            ("japna", 1, true),
+            ("kroea", 2, true),
+        ];
+        FuzzyTermQuery::prewarm(2, true);
+        let counts_with_cache: Vec<usize> = queries
+            .iter()
+            .map(|&(text, distance, transposition_cost_one)| {
+                count(text, distance, transposition_cost_one)
+            }).collect();
+        assert_eq!(counts_with_cache, vec![2, 1, 2, 0, 1, 1]);
+        FuzzyTermQuery::set_automaton_cache_capacity(0);
+        let counts_without_cache: Vec<usize> = queries
+            .iter()
+            .map(|&(text, distance, transposition_cost_one)| {
+                count(text, distance, transposition_cost_one)
+            }).collect();
+        FuzzyTermQuery::set_automaton_cache_capacity(DEFAULT_LEV_BUILDER_CACHE_CAPACITY);
+        assert_eq!(counts_with_cache, counts_without_cache);
+    }
+
     #[test]
     pub fn test_fuzzy_term() {
         let mut schema_builder = SchemaBuilder::new();
@@ -137,12 +302,16 @@ mod test {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
-            index_writer.add_document(doc!(
-                country_field => "japan",
-            ));
-            index_writer.add_document(doc!(
-                country_field => "korea",
-            ));
+            index_writer
+                .add_document(doc!(
+                    country_field => "japan",
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    country_field => "korea",
+                ))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -160,3 +329,25 @@ mod test {
         }
     }
 }
+
+#[cfg(all(test, feature = "unstable"))]
+mod bench {
+
+    use super::FuzzyTermQuery;
+    use levenshtein_automata::LevenshteinAutomatonBuilder;
+    use schema::{Field, Term};
+    use test::Bencher;
+
+    #[bench]
+    fn bench_fuzzy_weight_uncached_builder(b: &mut Bencher) {
+        b.iter(|| LevenshteinAutomatonBuilder::new(2, true).build_dfa("autocomplete"));
+    }
+
+    #[bench]
+    fn bench_fuzzy_weight_cached_builder(b: &mut Bencher) {
+        FuzzyTermQuery::prewarm(2, true);
+        let term = Term::from_field_text(Field(0), "autocomplete");
+        let query = FuzzyTermQuery::new(term, 2, true);
+        b.iter(|| query.specialized_weight().unwrap());
+    }
+}
@@ -16,11 +16,20 @@ use Score;
 /// specialized implementation if the two
 /// shortest scorers are `TermScorer`s.
 pub fn intersect_scorers(mut scorers: Vec<Box<Scorer>>) -> Box<Scorer> {
+    scorers.sort_by_key(|scorer| scorer.size_hint());
+    intersect_sorted_scorers(scorers)
+}
+
+/// Returns the intersection scorer, assuming the `scorers`
+/// are already sorted by increasing cost.
+///
+/// The first scorer drives the iteration.
+pub(crate) fn intersect_sorted_scorers(scorers: Vec<Box<Scorer>>) -> Box<Scorer> {
     let num_docsets = scorers.len();
-    scorers.sort_by(|left, right| right.size_hint().cmp(&left.size_hint()));
-    let rarest_opt = scorers.pop();
-    let second_rarest_opt = scorers.pop();
-    scorers.reverse();
+    let mut scorers_it = scorers.into_iter();
+    let rarest_opt = scorers_it.next();
+    let second_rarest_opt = scorers_it.next();
+    let scorers: Vec<Box<Scorer>> = scorers_it.collect();
     match (rarest_opt, second_rarest_opt) {
         (None, None) => Box::new(EmptyScorer),
         (Some(single_docset), None) => single_docset,
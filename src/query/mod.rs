@@ -7,12 +7,16 @@ mod automaton_weight;
 mod bitset;
 mod bm25;
 mod boolean_query;
+mod boost_query;
 mod empty_query;
 mod exclude;
+mod facet_term_query;
 mod fuzzy_query;
 mod intersection;
 mod occur;
 mod phrase_query;
+mod precomputed_filter;
+mod profile;
 mod query;
 mod query_parser;
 mod range_query;
@@ -28,6 +32,8 @@ mod vec_docset;
 
 pub(crate) mod score_combiner;
 pub use self::intersection::Intersection;
+pub(crate) use self::profile::search_with_profile;
+pub(crate) use self::query::search_with_weight;
 pub use self::union::Union;
 
 #[cfg(test)]
@@ -37,15 +43,21 @@ pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
 pub use self::boolean_query::BooleanQuery;
+pub use self::boost_query::BoostQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
 pub use self::exclude::Exclude;
+pub use self::facet_term_query::FacetTermQuery;
 pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::intersect_scorers;
 pub use self::occur::Occur;
 pub use self::phrase_query::PhraseQuery;
-pub use self::query::Query;
+pub use self::precomputed_filter::PrecomputedFilter;
+pub use self::profile::{QueryProfile, QueryProfiler, SearchProfile, SegmentProfile};
+pub use self::query::{Query, QueryClone};
 pub use self::query_parser::QueryParser;
 pub use self::query_parser::QueryParserError;
+pub use self::query_parser::QueryParserDiagnostic;
+pub use self::query_parser::{QueryParserWarning, QueryParserWarnings};
 pub use self::range_query::RangeQuery;
 pub use self::regex_query::RegexQuery;
 pub use self::reqopt_scorer::RequiredOptionalScorer;
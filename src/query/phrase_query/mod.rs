@@ -25,7 +25,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             for &text in texts {
                 let doc = doc!(text_field=>text);
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -82,7 +82,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a b c"));
+            index_writer
+                .add_document(doc!(text_field=>"a b c"))
+                .unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
@@ -140,17 +142,17 @@ mod tests {
             {
                 // 0
                 let doc = doc!(text_field=>"b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 1
                 let doc = doc!(text_field=>"a b");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             {
                 // 2
                 let doc = doc!(text_field=>"b a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -181,7 +183,9 @@ mod tests {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field=>"a b c d e f g h"));
+            index_writer
+                .add_document(doc!(text_field=>"a b c d e f g h"))
+                .unwrap();
             assert!(index_writer.commit().is_ok());
         }
         index.load_searchers().unwrap();
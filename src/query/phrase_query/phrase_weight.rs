@@ -33,12 +33,12 @@ impl Weight for PhraseWeight {
     fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
         let similarity_weight = self.similarity_weight.clone();
         let field = self.phrase_terms[0].1.field();
-        let fieldnorm_reader = reader.get_fieldnorms_reader(field);
+        let fieldnorm_reader = reader.try_fieldnorms_reader(field)?;
         if reader.has_deletes() {
             let mut term_postings_list = Vec::new();
             for &(offset, ref term) in &self.phrase_terms {
                 if let Some(postings) = reader
-                    .inverted_index(term.field())
+                    .try_inverted_index(term.field())?
                     .read_postings(&term, IndexRecordOption::WithFreqsAndPositions)
                 {
                     term_postings_list.push((offset, postings));
@@ -56,7 +56,7 @@ impl Weight for PhraseWeight {
             let mut term_postings_list = Vec::new();
             for &(offset, ref term) in &self.phrase_terms {
                 if let Some(postings) = reader
-                    .inverted_index(term.field())
+                    .try_inverted_index(term.field())?
                     .read_postings_no_deletes(&term, IndexRecordOption::WithFreqsAndPositions)
                 {
                     term_postings_list.push((offset, postings));
@@ -72,4 +72,17 @@ impl Weight for PhraseWeight {
             )))
         }
     }
+
+    /// A phrase cannot match more documents than its rarest term.
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        self.phrase_terms
+            .iter()
+            .map(|&(_, ref term)| {
+                reader
+                    .try_inverted_index(term.field())
+                    .map(|inverted_index| u64::from(inverted_index.doc_freq(term)))
+                    .unwrap_or_else(|_| u64::from(reader.max_doc()))
+            }).min()
+            .unwrap_or(0u64)
+    }
 }
@@ -0,0 +1,408 @@
+use common::BitSet;
+use core::Searcher;
+use core::SegmentId;
+use core::SegmentReader;
+use error::TantivyError;
+use postings::BlockSegmentPostings;
+use query::BitSetDocSet;
+use query::ConstScorer;
+use query::{Query, Scorer, Weight};
+use schema::{Field, IndexRecordOption, Term};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use Result;
+
+/// Per-segment bitsets, resolved for a given searcher generation.
+struct CachedBitSets {
+    generation: u64,
+    bitsets: Arc<HashMap<SegmentId, Arc<BitSet>>>,
+}
+
+/// `PrecomputedFilter` matches the documents having one of a (possibly very
+/// large) set of primary keys, e.g. a list of ids computed offline.
+///
+/// The keys are resolved against the term dictionary of the key field
+/// into one `BitSet` per segment. The resolution is batched: the keys
+/// are sorted once, and each term dictionary is walked in a single pass.
+///
+/// The bitsets are cached in the query, and shared by its clones.
+/// They are reused as long as the searcher generation does not change,
+/// and resolved again after the searchers are reloaded on a new commit.
+///
+/// All of the matched documents get a constant `Score` of one.
+/// To filter the results of another query, add the filter to a
+/// `BooleanQuery` as an `Occur::Must` clause.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate tantivy;
+/// # use tantivy::collector::CountCollector;
+/// # use tantivy::query::{BooleanQuery, Occur, PrecomputedFilter, Query, TermQuery};
+/// # use tantivy::schema::{IndexRecordOption, SchemaBuilder, Term, INT_INDEXED, TEXT};
+/// # use tantivy::Index;
+/// # fn main() {
+/// # let mut schema_builder = SchemaBuilder::default();
+/// # let id_field = schema_builder.add_u64_field("id", INT_INDEXED);
+/// # let text_field = schema_builder.add_text_field("text", TEXT);
+/// # let index = Index::create_in_ram(schema_builder.build());
+/// # {
+/// #     let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+/// #     for id in 0u64..10u64 {
+/// #         index_writer.add_document(doc!(id_field => id, text_field => "hello")).unwrap();
+/// #     }
+/// #     index_writer.commit().unwrap();
+/// # }
+/// # index.load_searchers().unwrap();
+/// # let searcher = index.searcher();
+/// let allowed_ids = PrecomputedFilter::for_u64_keys(id_field, vec![1, 3, 5, 42]);
+/// let hello_query = TermQuery::new(
+///     Term::from_field_text(text_field, "hello"),
+///     IndexRecordOption::Basic,
+/// );
+/// let query = BooleanQuery::from(vec![
+///     (Occur::Must, Box::new(hello_query) as Box<Query>),
+///     (Occur::Must, Box::new(allowed_ids) as Box<Query>),
+/// ]);
+/// let mut count_collector = CountCollector::default();
+/// searcher.search(&query, &mut count_collector).unwrap();
+/// assert_eq!(count_collector.count(), 3);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct PrecomputedFilter {
+    field: Field,
+    // sorted and deduplicated.
+    terms: Arc<Vec<Term>>,
+    cache: Arc<Mutex<Option<CachedBitSets>>>,
+}
+
+impl PrecomputedFilter {
+    /// Creates a filter matching the documents containing
+    /// one of the given terms of `field`.
+    ///
+    /// # Panics
+    /// The method panics if one of the terms does not belong to `field`.
+    pub fn new<I: IntoIterator<Item = Term>>(field: Field, terms: I) -> PrecomputedFilter {
+        let mut terms: Vec<Term> = terms.into_iter().collect();
+        assert!(
+            terms.iter().all(|term| term.field() == field),
+            "All of the terms of a PrecomputedFilter must belong to its field."
+        );
+        terms.sort();
+        terms.dedup();
+        PrecomputedFilter {
+            field,
+            terms: Arc::new(terms),
+            cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Creates a filter matching the documents having one of
+    /// the given `u64` keys.
+    pub fn for_u64_keys<I: IntoIterator<Item = u64>>(field: Field, keys: I) -> PrecomputedFilter {
+        let terms = keys.into_iter().map(|key| Term::from_field_u64(field, key));
+        PrecomputedFilter::new(field, terms)
+    }
+
+    /// Creates a filter matching the documents having one of
+    /// the given text keys.
+    ///
+    /// The keys are not tokenized: the field is expected to be indexed
+    /// with the `raw` tokenizer.
+    pub fn for_text_keys<I, S>(field: Field, keys: I) -> PrecomputedFilter
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let terms = keys
+            .into_iter()
+            .map(|key| Term::from_field_text(field, key.as_ref()));
+        PrecomputedFilter::new(field, terms)
+    }
+
+    /// Returns the number of distinct keys of the filter.
+    pub fn num_keys(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Returns the bitsets of the segments of the searcher,
+    /// resolving them if the cache is stale.
+    fn bitsets(&self, searcher: &Searcher) -> Result<Arc<HashMap<SegmentId, Arc<BitSet>>>> {
+        let generation = searcher.generation();
+        // The cache is only ever replaced as a whole,
+        // so that it is safe to ignore poisoning.
+        if let Some(ref cached) = *self.cache.lock().unwrap_or_else(PoisonError::into_inner) {
+            if cached.generation == generation {
+                return Ok(cached.bitsets.clone());
+            }
+        }
+        let bitsets: HashMap<SegmentId, Arc<BitSet>> = searcher
+            .segment_readers()
+            .iter()
+            .map(|reader| {
+                let bitset = resolve_terms(&self.terms, self.field, reader)?;
+                Ok((reader.segment_id(), Arc::new(bitset)))
+            })
+            .collect::<Result<_>>()?;
+        let bitsets = Arc::new(bitsets);
+        *self.cache.lock().unwrap_or_else(PoisonError::into_inner) = Some(CachedBitSets {
+            generation,
+            bitsets: bitsets.clone(),
+        });
+        Ok(bitsets)
+    }
+}
+
+impl fmt::Debug for PrecomputedFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PrecomputedFilter(field={:?}, num_keys={})",
+            self.field,
+            self.terms.len()
+        )
+    }
+}
+
+/// Resolves the sorted `terms` against the term dictionary of the segment,
+/// and returns the bitset of the documents containing one of them.
+fn resolve_terms(terms: &[Term], field: Field, reader: &SegmentReader) -> Result<BitSet> {
+    let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+    let inverted_index = reader.try_inverted_index(field)?;
+    // The block postings are reset for each term, rather than reallocated.
+    let mut block_postings = BlockSegmentPostings::empty();
+    let mut block_postings_initialized = false;
+    for term_info in inverted_index
+        .get_term_infos(terms)
+        .into_iter()
+        .filter_map(|term_info| term_info)
+    {
+        if block_postings_initialized {
+            inverted_index.reset_block_postings_from_terminfo(&term_info, &mut block_postings);
+        } else {
+            block_postings = inverted_index
+                .read_block_postings_from_terminfo(&term_info, IndexRecordOption::Basic);
+            block_postings_initialized = true;
+        }
+        while block_postings.advance() {
+            for &doc in block_postings.docs() {
+                doc_bitset.insert(doc);
+            }
+        }
+    }
+    Ok(doc_bitset)
+}
+
+impl Query for PrecomputedFilter {
+    fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<Weight>> {
+        let field_entry = searcher.schema().get_field_entry(self.field);
+        if !field_entry.is_indexed() {
+            let err_msg = format!(
+                "The key field {:?} of a PrecomputedFilter must be indexed.",
+                field_entry.name()
+            );
+            return Err(TantivyError::SchemaError(err_msg));
+        }
+        Ok(Box::new(PrecomputedFilterWeight {
+            field: self.field,
+            terms: self.terms.clone(),
+            bitsets: self.bitsets(searcher)?,
+        }))
+    }
+}
+
+struct PrecomputedFilterWeight {
+    field: Field,
+    terms: Arc<Vec<Term>>,
+    bitsets: Arc<HashMap<SegmentId, Arc<BitSet>>>,
+}
+
+impl PrecomputedFilterWeight {
+    /// Returns the bitset of the segment. Segments that were not part of
+    /// the searcher the weight was created for are resolved on the fly.
+    fn bitset(&self, reader: &SegmentReader) -> Result<Arc<BitSet>> {
+        match self.bitsets.get(&reader.segment_id()) {
+            Some(bitset) => Ok(Arc::clone(bitset)),
+            None => resolve_terms(&self.terms, self.field, reader).map(Arc::new),
+        }
+    }
+}
+
+impl Weight for PrecomputedFilterWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let doc_bitset = BitSetDocSet::from(self.bitset(reader)?);
+        Ok(Box::new(ConstScorer::new(doc_bitset)))
+    }
+
+    /// The cost is the number of documents of the bitset,
+    /// which is exact up to the deleted documents.
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        match self.bitsets.get(&reader.segment_id()) {
+            Some(bitset) => bitset.len() as u64,
+            None => u64::from(reader.max_doc()),
+        }
+    }
+
+    fn can_match(&self, reader: &SegmentReader) -> bool {
+        match self.bitsets.get(&reader.segment_id()) {
+            Some(bitset) => bitset.len() > 0,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::PrecomputedFilter;
+    use collector::{CountCollector, TopScoreCollector};
+    use query::{BooleanQuery, Occur, Query, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, FAST, INT_INDEXED, STRING, TEXT};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use Index;
+
+    #[test]
+    fn test_precomputed_filter() {
+        let mut schema_builder = SchemaBuilder::default();
+        let id_field = schema_builder.add_u64_field("id", INT_INDEXED | FAST);
+        let sku_field = schema_builder.add_text_field("sku", STRING);
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for id in 0u64..100u64 {
+                let text = if id % 2 == 0 { "even" } else { "odd" };
+                index_writer
+                    .add_document(doc!(
+                        id_field => id,
+                        sku_field => format!("sku-{}", id),
+                        text_field => text
+                    ))
+                    .unwrap();
+                if id % 30 == 29 {
+                    index_writer.commit().unwrap();
+                }
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count = |query: &Query| {
+            let mut count_collector = CountCollector::default();
+            searcher.search(query, &mut count_collector).unwrap();
+            count_collector.count()
+        };
+
+        // Missing keys, and duplicates, are ignored.
+        let filter = PrecomputedFilter::for_u64_keys(id_field, vec![3, 4, 5, 5, 60, 99, 1_000]);
+        assert_eq!(filter.num_keys(), 6);
+        assert_eq!(count(&filter), 5);
+        let sku_filter = PrecomputedFilter::for_text_keys(sku_field, vec!["sku-4", "sku-61"]);
+        assert_eq!(count(&sku_filter), 2);
+
+        let even_query = TermQuery::new(
+            Term::from_field_text(text_field, "even"),
+            IndexRecordOption::Basic,
+        );
+        let query = BooleanQuery::from(vec![
+            (Occur::Must, Box::new(even_query) as Box<Query>),
+            (Occur::Must, Box::new(filter.clone()) as Box<Query>),
+        ]);
+        let mut top_collector = TopScoreCollector::with_limit(10);
+        searcher.search(&query, &mut top_collector).unwrap();
+        let mut ids: Vec<u64> = top_collector
+            .docs()
+            .into_iter()
+            .map(|doc_address| {
+                searcher
+                    .segment_reader(doc_address.segment_ord())
+                    .fast_field_reader::<u64>(id_field)
+                    .unwrap()
+                    .get(doc_address.doc())
+            }).collect();
+        ids.sort();
+        assert_eq!(ids, vec![4, 60]);
+    }
+
+    #[test]
+    fn test_precomputed_filter_cache() {
+        let mut schema_builder = SchemaBuilder::default();
+        let id_field = schema_builder.add_u64_field("id", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        for id in 0u64..10u64 {
+            index_writer.add_document(doc!(id_field => id)).unwrap();
+        }
+        index_writer.commit().unwrap();
+        index.load_searchers().unwrap();
+
+        let filter = PrecomputedFilter::for_u64_keys(id_field, vec![1, 2, 12]);
+        let count = |filter: &PrecomputedFilter| {
+            let mut count_collector = CountCollector::default();
+            index
+                .searcher()
+                .search(filter, &mut count_collector)
+                .unwrap();
+            count_collector.count()
+        };
+        let cached_bitsets = || filter.bitsets(&*index.searcher()).unwrap();
+
+        assert_eq!(count(&filter), 2);
+        let bitsets = cached_bitsets();
+        // The bitsets are reused for the same generation, including by the clones.
+        assert!(Arc::ptr_eq(&bitsets, &cached_bitsets()));
+        assert!(Arc::ptr_eq(
+            &bitsets,
+            &filter.clone().bitsets(&*index.searcher()).unwrap()
+        ));
+
+        // A new commit invalidates them once the searchers are reloaded.
+        index_writer.add_document(doc!(id_field => 12u64)).unwrap();
+        index_writer
+            .delete_term(Term::from_field_u64(id_field, 1))
+            .unwrap();
+        index_writer.commit().unwrap();
+        assert!(Arc::ptr_eq(&bitsets, &cached_bitsets()));
+        index.load_searchers().unwrap();
+        assert!(!Arc::ptr_eq(&bitsets, &cached_bitsets()));
+        assert_eq!(count(&filter), 2);
+    }
+
+    /// Builds a filter of a million keys over an index of a million documents.
+    ///
+    /// This test is slow to index in debug mode. Run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_precomputed_filter_million_keys() {
+        const NUM_DOCS: u64 = 1_000_000;
+        let mut schema_builder = SchemaBuilder::default();
+        let id_field = schema_builder.add_u64_field("id", INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 200_000_000).unwrap();
+            for id in 0..NUM_DOCS {
+                index_writer.add_document(doc!(id_field => id * 2)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+
+        // Half of the keys are in the index. They are given in reverse order.
+        let keys = (0..NUM_DOCS).rev().map(|key| key * 2 + key % 2);
+        let start = Instant::now();
+        let filter = PrecomputedFilter::for_u64_keys(id_field, keys);
+        let mut count_collector = CountCollector::default();
+        searcher.search(&filter, &mut count_collector).unwrap();
+        let elapsed = start.elapsed();
+        assert_eq!(count_collector.count(), NUM_DOCS as usize / 2);
+        assert!(
+            elapsed < Duration::from_secs(1),
+            "Building the filter took {:?}",
+            elapsed
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use collector::Collector;
+use core::searcher::Searcher;
+use core::SegmentReader;
+use docset::{DocSet, SkipResult};
+use query::{search_with_weight, Query, Scorer, Weight};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use DocId;
+use Result;
+use Score;
+use SegmentLocalId;
+
+/// Statistics on the execution of a query, and of its subqueries.
+///
+/// See `Searcher::search_with_profile`.
+#[derive(Clone, Debug, Serialize)]
+pub struct QueryProfile {
+    /// Debug representation of the query.
+    pub query: String,
+    /// Number of documents produced by the scorers of the query,
+    /// over all of the segments. Deleted documents are included.
+    pub num_docs: u64,
+    /// Number of calls to `skip_next` on the scorers of the query.
+    pub num_seeks: u64,
+    /// Time spent creating and running the scorers of the query,
+    /// including the time spent in the scorers of its subqueries.
+    pub duration: Duration,
+    /// Profiles of the subqueries, if any.
+    pub children: Vec<QueryProfile>,
+}
+
+/// Statistics on the collection of the documents of a segment.
+#[derive(Clone, Debug, Serialize)]
+pub struct SegmentProfile {
+    /// Ordinal of the segment in the searcher.
+    pub segment_ord: SegmentLocalId,
+    /// Number of documents passed to the collector.
+    pub num_docs_collected: u64,
+    /// Time spent searching the segment.
+    pub duration: Duration,
+}
+
+/// Statistics on a search, returned by `Searcher::search_with_profile`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchProfile {
+    /// Time spent creating the `Weight` of the query.
+    pub weight_duration: Duration,
+    /// Profiles of the searched segments. Segments skipped
+    /// because they could not match are omitted.
+    pub segments: Vec<SegmentProfile>,
+    /// Profile of the query.
+    pub query: QueryProfile,
+}
+
+#[derive(Default)]
+struct ProfileCounters {
+    num_docs: Cell<u64>,
+    num_seeks: Cell<u64>,
+    duration: Cell<Duration>,
+}
+
+impl ProfileCounters {
+    fn record_doc(&self) {
+        self.num_docs.set(self.num_docs.get() + 1);
+    }
+
+    fn record_seek(&self) {
+        self.num_seeks.set(self.num_seeks.get() + 1);
+    }
+
+    fn record_duration(&self, start: Instant) {
+        self.duration.set(self.duration.get() + start.elapsed());
+    }
+}
+
+/// Builds the `QueryProfile` of a query.
+///
+/// See `Query::profiled_weight`.
+pub struct QueryProfiler {
+    query: String,
+    counters: Rc<ProfileCounters>,
+    children: Vec<QueryProfiler>,
+}
+
+impl QueryProfiler {
+    /// Creates a profiler for the given query.
+    pub fn for_query(query: &Query) -> QueryProfiler {
+        QueryProfiler {
+            query: format!("{:?}", query),
+            counters: Rc::new(ProfileCounters::default()),
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the profiled weight of a subquery, and records
+    /// its profile as a child of the profile of the query.
+    pub fn profile_subquery(
+        &mut self,
+        subquery: &Query,
+        searcher: &Searcher,
+        scoring_enabled: bool,
+    ) -> Result<Box<Weight>> {
+        let mut child_profiler = QueryProfiler::for_query(subquery);
+        let weight = subquery.profiled_weight(searcher, scoring_enabled, &mut child_profiler)?;
+        self.children.push(child_profiler);
+        Ok(weight)
+    }
+
+    /// Wraps the weight of the query, so that its scorers
+    /// update the profile of the query.
+    pub fn wrap(&self, weight: Box<Weight>) -> Box<Weight> {
+        Box::new(ProfiledWeight {
+            weight,
+            counters: self.counters.clone(),
+        })
+    }
+
+    fn into_profile(self) -> QueryProfile {
+        QueryProfile {
+            query: self.query,
+            num_docs: self.counters.num_docs.get(),
+            num_seeks: self.counters.num_seeks.get(),
+            duration: self.counters.duration.get(),
+            children: self
+                .children
+                .into_iter()
+                .map(QueryProfiler::into_profile)
+                .collect(),
+        }
+    }
+}
+
+struct ProfiledWeight {
+    weight: Box<Weight>,
+    counters: Rc<ProfileCounters>,
+}
+
+impl Weight for ProfiledWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let start = Instant::now();
+        let scorer = self.weight.scorer(reader)?;
+        self.counters.record_duration(start);
+        Ok(Box::new(ProfiledScorer {
+            scorer,
+            counters: self.counters.clone(),
+        }))
+    }
+
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        self.weight.cost(reader)
+    }
+
+    fn max_score(&self, reader: &SegmentReader) -> Option<Score> {
+        self.weight.max_score(reader)
+    }
+
+    fn can_match(&self, reader: &SegmentReader) -> bool {
+        self.weight.can_match(reader)
+    }
+}
+
+// Scorer counting the documents and the seeks of the scorer it wraps.
+//
+// Its `DocSet` methods all go through `advance` and `skip_next`,
+// so that the specialized implementations of the wrapped scorer
+// cannot bypass the counters.
+struct ProfiledScorer {
+    scorer: Box<Scorer>,
+    counters: Rc<ProfileCounters>,
+}
+
+impl DocSet for ProfiledScorer {
+    fn advance(&mut self) -> bool {
+        let start = Instant::now();
+        let has_doc = self.scorer.advance();
+        self.counters.record_duration(start);
+        if has_doc {
+            self.counters.record_doc();
+        }
+        has_doc
+    }
+
+    fn skip_next(&mut self, target: DocId) -> SkipResult {
+        let start = Instant::now();
+        let skip_result = self.scorer.skip_next(target);
+        self.counters.record_duration(start);
+        self.counters.record_seek();
+        if skip_result != SkipResult::End {
+            self.counters.record_doc();
+        }
+        skip_result
+    }
+
+    fn doc(&self) -> DocId {
+        self.scorer.doc()
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.scorer.size_hint()
+    }
+}
+
+impl Scorer for ProfiledScorer {
+    fn score(&mut self) -> Score {
+        let start = Instant::now();
+        let score = self.scorer.score();
+        self.counters.record_duration(start);
+        score
+    }
+}
+
+pub(crate) fn search_with_profile(
+    searcher: &Searcher,
+    query: &Query,
+    collector: &mut Collector,
+) -> Result<SearchProfile> {
+    let start = Instant::now();
+    let scoring_enabled = collector.requires_scoring();
+    let mut profiler = QueryProfiler::for_query(query);
+    let weight = query.profiled_weight(searcher, scoring_enabled, &mut profiler)?;
+    let weight_duration = start.elapsed();
+    let segments = search_with_weight(searcher, &*weight, weight_duration, collector, None)?
+        .into_iter()
+        .map(|segment_metrics| SegmentProfile {
+            segment_ord: segment_metrics.segment_ord,
+            num_docs_collected: segment_metrics.num_docs_collected,
+            duration: segment_metrics.duration,
+        }).collect();
+    Ok(SearchProfile {
+        weight_duration,
+        segments,
+        query: profiler.into_profile(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use collector::CountCollector;
+    use query::{BooleanQuery, Occur, Query, TermQuery};
+    use schema::{IndexRecordOption, SchemaBuilder, Term, TEXT};
+    use serde_json;
+    use Index;
+
+    #[test]
+    fn test_search_with_profile() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b c"))
+                .unwrap();
+            index_writer.add_document(doc!(text_field => "b")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a c"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let term_query = |text: &str| -> Box<Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, text),
+                IndexRecordOption::Basic,
+            ))
+        };
+        // "b" matches 3 documents, "c" 2 documents and "a" 4 documents.
+        let query = BooleanQuery::from(vec![
+            (Occur::Must, term_query("a")),
+            (Occur::Must, term_query("b")),
+            (Occur::MustNot, term_query("c")),
+        ]);
+
+        let mut collector = CountCollector::default();
+        let profile = searcher
+            .search_with_profile(&query, &mut collector)
+            .unwrap();
+        assert_eq!(collector.count(), 1);
+        assert_eq!(profile.segments.len(), 1);
+        assert_eq!(profile.segments[0].num_docs_collected, 1);
+
+        let query_profile = &profile.query;
+        assert_eq!(query_profile.num_docs, 1);
+        assert_eq!(query_profile.num_seeks, 0);
+        assert_eq!(query_profile.children.len(), 3);
+        let a_profile = &query_profile.children[0];
+        let b_profile = &query_profile.children[1];
+        let c_profile = &query_profile.children[2];
+        assert!(a_profile.query.contains("TermQuery"));
+        assert!(a_profile.children.is_empty());
+        // "b" is the cheapest required clause: it drives the intersection,
+        // and "a" seeks to each of its documents 0, 2 and 3, landing on 0, 2 and 4.
+        // "b" then seeks past its last document, 4.
+        assert_eq!(b_profile.num_docs, 3);
+        assert_eq!(b_profile.num_seeks, 1);
+        assert_eq!(a_profile.num_docs, 3);
+        assert_eq!(a_profile.num_seeks, 3);
+        // The exclusion reads the first document of "c", 2, which is
+        // enough to exclude the document 2 of the intersection.
+        assert_eq!(c_profile.num_docs, 1);
+        assert_eq!(c_profile.num_seeks, 0);
+
+        // The profile can be serialized.
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(json.contains("\"num_seeks\":3"));
+    }
+}
@@ -1,9 +1,18 @@
+use super::QueryProfiler;
+use super::Scorer;
 use super::Weight;
 use collector::Collector;
+use core::metrics::CountingCollector;
 use core::searcher::Searcher;
+use core::SegmentReader;
+use core::{SearchMetrics, SegmentSearchMetrics};
+use docset::{DocSet, SkipResult};
 use downcast;
 use std::collections::BTreeSet;
 use std::fmt;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use DocId;
 use Result;
 use SegmentLocalId;
 use Term;
@@ -41,6 +50,12 @@ use Term;
 ///
 /// When implementing a new type of `Query`, it is normal to implement a
 /// dedicated `Query`, `Weight` and `Scorer`.
+/// The query itself should implement `Clone` and `Debug`: `Clone` provides
+/// an implementation of [`QueryClone`](./trait.QueryClone.html).
+///
+/// Queries implemented outside of tantivy can be combined with
+/// the built-in ones, for instance in a
+/// [`BooleanQuery`](./struct.BooleanQuery.html).
 pub trait Query: QueryClone + downcast::Any + fmt::Debug {
     /// Create the weight associated to a query.
     ///
@@ -50,12 +65,33 @@ pub trait Query: QueryClone + downcast::Any + fmt::Debug {
     /// See [`Weight`](./trait.Weight.html).
     fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>>;
 
+    /// Create the weight associated to a query, instrumented to record
+    /// the execution statistics of the query in `profiler`.
+    ///
+    /// The default implementation profiles the query as a whole.
+    /// Queries made of subqueries should create the weights of their
+    /// subqueries with `QueryProfiler::profile_subquery`, so that they
+    /// get a profile on their own.
+    ///
+    /// See `Searcher::search_with_profile`.
+    fn profiled_weight(
+        &self,
+        searcher: &Searcher,
+        scoring_enabled: bool,
+        profiler: &mut QueryProfiler,
+    ) -> Result<Box<Weight>> {
+        let weight = self.weight(searcher, scoring_enabled)?;
+        Ok(profiler.wrap(weight))
+    }
+
     /// Returns the number of documents matching the query.
     fn count(&self, searcher: &Searcher) -> Result<usize> {
         let weight = self.weight(searcher, false)?;
         let mut result = 0;
         for reader in searcher.segment_readers() {
-            result += weight.count(reader)? as usize;
+            if weight.can_match(reader) {
+                result += weight.count(reader)? as usize;
+            }
         }
         Ok(result)
     }
@@ -68,24 +104,126 @@ pub trait Query: QueryClone + downcast::Any + fmt::Debug {
     ///
     /// First the weight object associated to the query is created.
     ///
-    /// Then, the query loops over the segments and for each segment :
+    /// Then, the query loops over the segments that the weight
+    /// [can match](./trait.Weight.html#method.can_match) and for each segment :
     /// - setup the collector and informs it that the segment being processed has changed.
     /// - creates a `Scorer` object associated for this segment
     /// - iterate throw the matched documents and push them to the collector.
     ///
     fn search(&self, searcher: &Searcher, collector: &mut Collector) -> Result<()> {
+        let start = Instant::now();
         let scoring_enabled = collector.requires_scoring();
         let weight = self.weight(searcher, scoring_enabled)?;
-        for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
-            collector.set_segment(segment_ord as SegmentLocalId, segment_reader)?;
+        search_with_weight(searcher, &*weight, start.elapsed(), collector, None)?;
+        Ok(())
+    }
+}
+
+/// Collects the documents of `scorer` within `doc_range`
+/// that are not deleted.
+fn collect_doc_range(
+    scorer: &mut Scorer,
+    collector: &mut Collector,
+    segment_reader: &SegmentReader,
+    doc_range: Range<DocId>,
+) {
+    if !scorer.advance() {
+        return;
+    }
+    if scorer.doc() < doc_range.start && scorer.skip_next(doc_range.start) == SkipResult::End {
+        return;
+    }
+    loop {
+        let doc = scorer.doc();
+        if doc >= doc_range.end || collector.is_done() {
+            return;
+        }
+        if !segment_reader.is_deleted(doc) {
+            collector.collect(doc, scorer.score());
+        }
+        if !scorer.advance() {
+            return;
+        }
+    }
+}
+
+/// Pushes the documents matching `weight` to the collector, segment by segment,
+/// and reports the search to the `Metrics` of the index.
+///
+/// This is the collection loop behind all of the ways to run a search.
+/// Segments that the weight cannot match are skipped, as well as the segments
+/// in which no document can reach the score threshold of the collector.
+/// The search stops as soon as the collector is done.
+///
+/// If `doc_ranges` is given, only the documents of the `i`-th segment
+/// within `doc_ranges[i]` are collected.
+///
+/// Returns the metrics of the segments that were searched.
+pub(crate) fn search_with_weight(
+    searcher: &Searcher,
+    weight: &Weight,
+    weight_duration: Duration,
+    collector: &mut Collector,
+    doc_ranges: Option<&[Range<DocId>]>,
+) -> Result<Vec<SegmentSearchMetrics>> {
+    let metrics = searcher.index().metrics();
+    metrics.on_weight_created(weight_duration);
+    let collection_start = Instant::now();
+    let mut collector = CountingCollector::new(collector);
+    let mut segments_metrics = Vec::new();
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        if collector.is_done() {
+            break;
+        }
+        let doc_range = doc_ranges.map(|doc_ranges| doc_ranges[segment_ord].clone());
+        if let Some(ref doc_range) = doc_range {
+            if doc_range.start >= doc_range.end {
+                continue;
+            }
+        }
+        if !weight.can_match(segment_reader) {
+            continue;
+        }
+        let segment_start = Instant::now();
+        let num_docs_before = collector.num_docs();
+        collector.set_segment(segment_ord as SegmentLocalId, segment_reader)?;
+        let skip_segment = match (
+            collector.score_threshold(),
+            weight.max_score(segment_reader),
+        ) {
+            (Some(threshold), Some(max_score)) => max_score < threshold,
+            _ => false,
+        };
+        if !skip_segment {
             let mut scorer = weight.scorer(segment_reader)?;
-            scorer.collect(collector, segment_reader.delete_bitset());
+            if let Some(doc_range) = doc_range {
+                collect_doc_range(&mut *scorer, &mut collector, segment_reader, doc_range);
+            } else {
+                scorer.collect(&mut collector, segment_reader.delete_bitset());
+            }
         }
-        Ok(())
+        let segment_metrics = SegmentSearchMetrics {
+            segment_ord: segment_ord as SegmentLocalId,
+            num_docs_collected: collector.num_docs() - num_docs_before,
+            duration: segment_start.elapsed(),
+        };
+        metrics.on_segment_searched(&segment_metrics);
+        segments_metrics.push(segment_metrics);
     }
+    metrics.on_search(&SearchMetrics {
+        weight_duration,
+        collection_duration: collection_start.elapsed(),
+        num_segments: segments_metrics.len(),
+        num_docs_collected: collector.num_docs(),
+    });
+    Ok(segments_metrics)
 }
 
+/// Makes it possible to clone a boxed `Query`.
+///
+/// It is implemented for all of the queries implementing `Clone`.
 pub trait QueryClone {
+    /// Returns a boxed copy of the query.
     fn box_clone(&self) -> Box<Query>;
 }
 
@@ -0,0 +1,503 @@
+use super::query_grammar::{is_term_char, is_term_start_char};
+use super::query_parser::{
+    full_width_punctuation_to_ascii, QueryParserDiagnostic, QueryParserError,
+};
+use std::ops::Range;
+
+// Recovery of malformed queries, for `QueryParser::parse_query_lenient`.
+//
+// The query is split into tokens, whose problems are fixed one
+// at a time, and the remaining tokens are written back as a query
+// that the strict grammar accepts:
+//
+// * an unbalanced quote is dropped,
+// * an unbalanced parenthesis is dropped,
+// * a literal targeting an unknown field is searched, field name included,
+//   as text in the default fields,
+// * a literal or a range that cannot be searched (e.g. an invalid range bound)
+//   is dropped,
+// * an operator or a `+`, `-` prefix that does not apply to any clause is dropped,
+// * the clauses mixing explicit operators and juxtaposed terms are grouped,
+//   so that `a AND b c` is read as `(a AND b) c`,
+// * any other unexpected character is dropped.
+//
+// Each of the fixes is reported by a diagnostic, with the byte range of the
+// original query that it concerns.
+
+struct LeafToken {
+    field: Option<(String, Range<usize>)>,
+    value: String,
+}
+
+impl LeafToken {
+    fn text(&self) -> String {
+        match self.field {
+            Some((ref field_name, _)) => format!("{}:{}", field_name, self.value),
+            None => self.value.clone(),
+        }
+    }
+
+    fn is_range(&self) -> bool {
+        self.value.starts_with('[') || self.value.starts_with('{')
+    }
+}
+
+enum TokenKind {
+    Open,
+    Close,
+    Prefix(char),
+    Not,
+    Operator(&'static str),
+    Leaf(LeafToken),
+}
+
+struct Token {
+    kind: TokenKind,
+    span: Range<usize>,
+}
+
+enum Element {
+    Clause(String),
+    Operator(&'static str),
+}
+
+/// Clauses joined by operators.
+struct Run {
+    text: String,
+    span: Range<usize>,
+    has_operator: bool,
+}
+
+struct Recovery {
+    // Byte offset in the original query, and (normalized) character.
+    chars: Vec<(usize, char)>,
+    query_len: usize,
+    pos: usize,
+    diagnostics: Vec<QueryParserDiagnostic>,
+}
+
+impl Recovery {
+    fn offset(&self, pos: usize) -> usize {
+        self.chars
+            .get(pos)
+            .map(|&(offset, _)| offset)
+            .unwrap_or(self.query_len)
+    }
+
+    fn peek(&self, pos: usize) -> Option<char> {
+        self.chars.get(pos).map(|&(_, c)| c)
+    }
+
+    fn text(&self, start: usize, end: usize) -> String {
+        self.chars[start..end].iter().map(|&(_, c)| c).collect()
+    }
+
+    fn report(&mut self, error: QueryParserError, span: Range<usize>) {
+        self.diagnostics.push(QueryParserDiagnostic { error, span });
+    }
+
+    fn report_chars(&mut self, error: QueryParserError, start: usize, end: usize) {
+        let span = self.offset(start)..self.offset(end);
+        self.report(error, span);
+    }
+
+    fn find(&self, start: usize, predicate: fn(char) -> bool) -> Option<usize> {
+        (start..self.chars.len()).find(|&pos| predicate(self.chars[pos].1))
+    }
+
+    fn scan_tokens(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek(self.pos) {
+            let start = self.pos;
+            let kind_opt = match c {
+                c if c.is_whitespace() => {
+                    self.pos += 1;
+                    None
+                }
+                '(' => {
+                    self.pos += 1;
+                    Some(TokenKind::Open)
+                }
+                ')' => {
+                    self.pos += 1;
+                    Some(TokenKind::Close)
+                }
+                '+' | '-' => {
+                    self.pos += 1;
+                    match self.peek(self.pos) {
+                        Some(next) if !next.is_whitespace() && next != ')' => {
+                            Some(TokenKind::Prefix(c))
+                        }
+                        _ => {
+                            self.report_chars(QueryParserError::SyntaxError, start, self.pos);
+                            None
+                        }
+                    }
+                }
+                '*' => {
+                    self.pos += 1;
+                    Some(TokenKind::Leaf(LeafToken {
+                        field: None,
+                        value: "*".to_string(),
+                    }))
+                }
+                '"' | '[' | '{' => self
+                    .scan_value()
+                    .map(|value| TokenKind::Leaf(LeafToken { field: None, value })),
+                c if is_term_start_char(c) => self.scan_word(),
+                _ => {
+                    self.pos += 1;
+                    self.report_chars(QueryParserError::SyntaxError, start, self.pos);
+                    None
+                }
+            };
+            if let Some(kind) = kind_opt {
+                let span = self.offset(start)..self.offset(self.pos);
+                tokens.push(Token { kind, span });
+            }
+        }
+        tokens
+    }
+
+    /// Scans a word, an operator, or a literal targeting a field.
+    fn scan_word(&mut self) -> Option<TokenKind> {
+        let start = self.pos;
+        let end = self
+            .find(start, |c| !is_term_char(c))
+            .unwrap_or(self.chars.len());
+        let word = self.text(start, end);
+        self.pos = end;
+        let starts_with_letter = word.chars().next().map_or(false, char::is_alphabetic);
+        if !starts_with_letter || self.peek(end) != Some(':') {
+            let kind = match word.as_str() {
+                "AND" => TokenKind::Operator("AND"),
+                "OR" => TokenKind::Operator("OR"),
+                "NOT" => TokenKind::Not,
+                _ => TokenKind::Leaf(LeafToken {
+                    field: None,
+                    value: word,
+                }),
+            };
+            return Some(kind);
+        }
+        let field_span = self.offset(start)..self.offset(end);
+        self.pos = end + 1;
+        let value_opt = match self.peek(self.pos) {
+            Some('"') => {
+                // After an unbalanced quote, the field applies to the next word.
+                self.scan_value().or_else(|| self.scan_word_run())
+            }
+            Some('[') | Some('{') => self.scan_value(),
+            Some('-') if self.peek(self.pos + 1).map_or(false, char::is_numeric) => {
+                let value_start = self.pos;
+                self.pos = self
+                    .find(value_start + 1, |c| !c.is_numeric())
+                    .unwrap_or(self.chars.len());
+                Some(self.text(value_start, self.pos))
+            }
+            Some(c) if is_term_start_char(c) => self.scan_word_run(),
+            _ => {
+                // A field name without any value.
+                self.report_chars(QueryParserError::SyntaxError, start, self.pos);
+                None
+            }
+        };
+        value_opt.map(|value| {
+            TokenKind::Leaf(LeafToken {
+                field: Some((word, field_span)),
+                value,
+            })
+        })
+    }
+
+    /// Scans a run of word characters, if any.
+    fn scan_word_run(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.pos = self
+            .find(start, |c| !is_term_char(c))
+            .unwrap_or(self.chars.len());
+        if self.pos == start {
+            None
+        } else {
+            Some(self.text(start, self.pos))
+        }
+    }
+
+    /// Scans a phrase or a range.
+    fn scan_value(&mut self) -> Option<String> {
+        let start = self.pos;
+        let (closing_opt, unbalanced_error) = if self.peek(start) == Some('"') {
+            (
+                self.find(start + 1, |c| c == '"'),
+                QueryParserError::UnbalancedQuote,
+            )
+        } else {
+            (
+                self.find(start + 1, |c| c == ']' || c == '}'),
+                QueryParserError::SyntaxError,
+            )
+        };
+        match closing_opt {
+            Some(closing) => {
+                self.pos = closing + 1;
+                if closing == start + 1 {
+                    // Empty phrase or range.
+                    self.report_chars(QueryParserError::SyntaxError, start, self.pos);
+                    None
+                } else {
+                    Some(self.text(start, self.pos))
+                }
+            }
+            None => {
+                self.pos = start + 1;
+                self.report_chars(unbalanced_error, start, self.pos);
+                None
+            }
+        }
+    }
+
+    /// Drops the leaves that cannot be searched, and rewrites the literals
+    /// targeting an unknown field as text.
+    fn check_leaves(
+        &mut self,
+        tokens: Vec<Token>,
+        check_leaf: &Fn(&str) -> Result<(), QueryParserError>,
+    ) -> Vec<Token> {
+        let mut checked_tokens = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let span = token.span;
+            let mut leaf = match token.kind {
+                TokenKind::Leaf(leaf) => leaf,
+                kind => {
+                    checked_tokens.push(Token { kind, span });
+                    continue;
+                }
+            };
+            let mut result = check_leaf(&leaf.text());
+            if let Err(QueryParserError::FieldDoesNotExist(field_name)) = result {
+                if leaf.is_range() {
+                    result = Err(QueryParserError::FieldDoesNotExist(field_name));
+                } else {
+                    let field_span = leaf.field.take().map(|(_, field_span)| field_span);
+                    let field_span = field_span.unwrap_or_else(|| span.clone());
+                    // A negative number would otherwise become an excluded term.
+                    leaf.value = format!("({} {})", field_name, leaf.value.trim_left_matches('-'));
+                    self.report(QueryParserError::FieldDoesNotExist(field_name), field_span);
+                    result = check_leaf(&leaf.text());
+                }
+            }
+            match result {
+                Ok(()) => checked_tokens.push(Token {
+                    kind: TokenKind::Leaf(leaf),
+                    span,
+                }),
+                Err(error) => self.report(error, span),
+            }
+        }
+        checked_tokens
+    }
+
+    /// Drops the parentheses that are not balanced.
+    fn balance_parentheses(&mut self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut unbalanced = vec![false; tokens.len()];
+        let mut open_stack = Vec::new();
+        for (ord, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::Open => open_stack.push(ord),
+                TokenKind::Close => {
+                    if open_stack.pop().is_none() {
+                        unbalanced[ord] = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        for ord in open_stack {
+            unbalanced[ord] = true;
+        }
+        let mut balanced_tokens = Vec::with_capacity(tokens.len());
+        for (token, is_unbalanced) in tokens.into_iter().zip(unbalanced) {
+            if is_unbalanced {
+                self.report(QueryParserError::UnbalancedParenthesis, token.span);
+            } else {
+                balanced_tokens.push(token);
+            }
+        }
+        balanced_tokens
+    }
+
+    /// Writes back the clauses up to the closing parenthesis of the group,
+    /// or to the end of the query.
+    ///
+    /// Returns `None` if the group does not have any clause.
+    fn recover_group(&mut self, tokens: &[Token], pos: &mut usize) -> Option<String> {
+        let mut elements = Vec::new();
+        while let Some(token) = tokens.get(*pos) {
+            match token.kind {
+                TokenKind::Close => break,
+                TokenKind::Operator(operator) => {
+                    *pos += 1;
+                    elements.push((Element::Operator(operator), token.span.clone()));
+                }
+                _ => {
+                    if let Some(clause) = self.recover_clause(tokens, pos) {
+                        elements.push(clause);
+                    }
+                }
+            }
+        }
+        self.join_elements(elements)
+    }
+
+    /// Writes back a clause, with its prefixes.
+    fn recover_clause(
+        &mut self,
+        tokens: &[Token],
+        pos: &mut usize,
+    ) -> Option<(Element, Range<usize>)> {
+        let start = *pos;
+        while let Some(token) = tokens.get(*pos) {
+            match token.kind {
+                TokenKind::Prefix(_) | TokenKind::Not => *pos += 1,
+                _ => break,
+            }
+        }
+        let prefixes = &tokens[start..*pos];
+        let clause_opt = match tokens.get(*pos).map(|token| &token.kind) {
+            Some(&TokenKind::Leaf(ref leaf)) => {
+                *pos += 1;
+                Some(leaf.text())
+            }
+            Some(&TokenKind::Open) => {
+                let open_start = tokens[*pos].span.start;
+                *pos += 1;
+                let group_opt = self.recover_group(tokens, pos);
+                // The parentheses are balanced.
+                let close_end = tokens[*pos].span.end;
+                *pos += 1;
+                if group_opt.is_none() {
+                    // Empty parentheses.
+                    self.report(QueryParserError::SyntaxError, open_start..close_end);
+                }
+                group_opt.map(|group| format!("({})", group))
+            }
+            _ => None,
+        };
+        let clause = match clause_opt {
+            Some(clause) => clause,
+            None => {
+                // The prefixes do not apply to any clause.
+                for prefix in prefixes {
+                    self.report(QueryParserError::SyntaxError, prefix.span.clone());
+                }
+                return None;
+            }
+        };
+        let mut text = String::new();
+        for prefix in prefixes {
+            match prefix.kind {
+                TokenKind::Prefix(c) => text.push(c),
+                _ => text.push_str("NOT "),
+            }
+        }
+        text.push_str(&clause);
+        let span = tokens[start].span.start..tokens[*pos - 1].span.end;
+        Some((Element::Clause(text), span))
+    }
+
+    fn join_elements(&mut self, elements: Vec<(Element, Range<usize>)>) -> Option<String> {
+        // Drops the operators that are not between two clauses.
+        let is_clause: Vec<bool> = elements
+            .iter()
+            .map(|&(ref element, _)| match *element {
+                Element::Clause(_) => true,
+                Element::Operator(_) => false,
+            }).collect();
+        let mut runs: Vec<Run> = Vec::new();
+        let mut after_operator = false;
+        for (ord, (element, span)) in elements.into_iter().enumerate() {
+            match element {
+                Element::Clause(text) => {
+                    if after_operator {
+                        let run = runs.last_mut().unwrap();
+                        run.text.push(' ');
+                        run.text.push_str(&text);
+                        run.span.end = span.end;
+                    } else {
+                        runs.push(Run {
+                            text,
+                            span,
+                            has_operator: false,
+                        });
+                    }
+                    after_operator = false;
+                }
+                Element::Operator(operator) => {
+                    let next_is_clause = is_clause.get(ord + 1).cloned().unwrap_or(false);
+                    if runs.is_empty() || after_operator || !next_is_clause {
+                        self.report(QueryParserError::SyntaxError, span);
+                        continue;
+                    }
+                    let run = runs.last_mut().unwrap();
+                    run.text.push(' ');
+                    run.text.push_str(operator);
+                    run.has_operator = true;
+                    after_operator = true;
+                }
+            }
+        }
+        if runs.is_empty() {
+            return None;
+        }
+        if runs.len() == 1 {
+            return runs.pop().map(|run| run.text);
+        }
+        let mut texts = Vec::with_capacity(runs.len());
+        for run in runs {
+            if run.has_operator {
+                // Operators and juxtaposed clauses cannot be mixed.
+                self.report(QueryParserError::SyntaxError, run.span);
+                texts.push(format!("({})", run.text));
+            } else {
+                texts.push(run.text);
+            }
+        }
+        Some(texts.join(" "))
+    }
+}
+
+/// Rewrites `query` as a query accepted by the strict grammar.
+///
+/// `check_leaf` returns the error encountered when parsing one of
+/// the literals or ranges of the query by itself, if any.
+/// The diagnostics are sorted by their position in `query`.
+pub(crate) fn recover_query(
+    query: &str,
+    normalize_full_width_punctuation: bool,
+    check_leaf: &Fn(&str) -> Result<(), QueryParserError>,
+) -> (String, Vec<QueryParserDiagnostic>) {
+    let chars = query
+        .char_indices()
+        .map(|(offset, c)| {
+            if normalize_full_width_punctuation {
+                (offset, full_width_punctuation_to_ascii(c).unwrap_or(c))
+            } else {
+                (offset, c)
+            }
+        }).collect();
+    let mut recovery = Recovery {
+        chars,
+        query_len: query.len(),
+        pos: 0,
+        diagnostics: Vec::new(),
+    };
+    let tokens = recovery.scan_tokens();
+    let tokens = recovery.check_leaves(tokens, check_leaf);
+    let tokens = recovery.balance_parentheses(tokens);
+    let recovered_query = recovery
+        .recover_group(&tokens, &mut 0)
+        .unwrap_or_else(String::new);
+    let mut diagnostics = recovery.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    (recovered_query, diagnostics)
+}
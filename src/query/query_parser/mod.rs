@@ -1,3 +1,4 @@
+mod lenient;
 mod query_grammar;
 mod query_parser;
 mod user_input_ast;
@@ -5,3 +6,5 @@ mod user_input_ast;
 pub mod logical_ast;
 pub use self::query_parser::QueryParser;
 pub use self::query_parser::QueryParserError;
+pub use self::query_parser::QueryParserDiagnostic;
+pub use self::query_parser::{QueryParserWarning, QueryParserWarnings};
@@ -19,10 +19,24 @@ parser! {
     }
 }
 
+// Characters with a meaning in the query syntax.
+// Any other character may be part of a term, e.g. `in-progress` or `foo.com`.
+pub(super) fn is_term_char(c: char) -> bool {
+    !c.is_whitespace() && !"()[]{}\":*".contains(c)
+}
+
+// A leading `+` or `-` is an occur prefix, not part of the term.
+pub(super) fn is_term_start_char(c: char) -> bool {
+    is_term_char(c) && c != '+' && c != '-'
+}
+
 parser! {
     fn word[I]()(I) -> String
     where [I: Stream<Item = char>] {
-        many1(satisfy(|c: char| c.is_alphanumeric()))
+        (
+            satisfy(is_term_start_char),
+            many(satisfy(is_term_char)),
+        ).map(|(s1, s2): (char, String)| format!("{}{}", s1, s2))
                .and_then(|s: String| {
                    match s.as_str() {
                      "OR" => Err(StreamErrorFor::<I>::unexpected_static_message("OR")),
@@ -67,10 +81,19 @@ parser! {
     }
 }
 
+// Separators are any of the `char::is_whitespace` characters, including
+// the ideographic space (U+3000) of CJK keyboards.
+parser! {
+    fn spaces0[I]()(I) -> ()
+    where [I: Stream<Item = char>] {
+        skip_many(satisfy(|c: char| c.is_whitespace()))
+    }
+}
+
 parser! {
     fn spaces1[I]()(I) -> ()
     where [I: Stream<Item = char>] {
-        skip_many1(space())
+        skip_many1(satisfy(|c: char| c.is_whitespace()))
     }
 }
 
@@ -93,9 +116,9 @@ parser! {
         (
             optional((field(), char(':')).map(|x| x.0)),
             lower_bound,
-            spaces(),
+            spaces0(),
             string("TO"),
-            spaces(),
+            spaces0(),
             upper_bound,
         ).map(|(field, lower, _, _, _, upper)| UserInputLeaf::Range {
                 field,
@@ -198,7 +221,7 @@ parser! {
                 })
             )
             .or(
-                sep_by(leaf(), spaces())
+                sep_by(leaf(), spaces0())
                 .map(|subqueries: Vec<UserInputAST>| {
                     if subqueries.len() == 1 {
                         subqueries.into_iter().next().unwrap()
@@ -237,6 +260,14 @@ mod test {
         test_parse_query_to_ast_helper("NOT a", "-(\"a\")");
     }
 
+    #[test]
+    fn test_parse_query_to_ast_unicode_whitespace() {
+        test_parse_query_to_ast_helper("東京\u{3000}大阪", "(\"東京\" \"大阪\")");
+        test_parse_query_to_ast_helper("北京\u{3000}AND\u{3000}上海", "(+(\"北京\") +(\"上海\"))");
+        test_parse_query_to_ast_helper("NOT\u{3000}a", "-(\"a\")");
+        test_parse_query_to_ast_helper("[a\u{3000}TO\u{3000}b]", "[\"a\" TO \"b\"]");
+    }
+
     #[test]
     fn test_parse_query_to_ast_binary_op() {
         test_parse_query_to_ast_helper("a AND b", "(+(\"a\") +(\"b\"))");
@@ -268,6 +299,10 @@ mod test {
         test_parse_query_to_ast_helper("(+a)", "+(\"a\")");
         test_parse_query_to_ast_helper("(+a +b)", "(+(\"a\") +(\"b\"))");
         test_parse_query_to_ast_helper("abc:toto", "abc:\"toto\"");
+        test_parse_query_to_ast_helper("abc:IN_PROGRESS", "abc:\"IN_PROGRESS\"");
+        test_parse_query_to_ast_helper("abc:in-progress", "abc:\"in-progress\"");
+        test_parse_query_to_ast_helper("foo.com", "\"foo.com\"");
+        test_parse_query_to_ast_helper("a+b -c", "(\"a+b\" -(\"c\"))");
         test_parse_query_to_ast_helper("+abc:toto", "+(abc:\"toto\")");
         test_parse_query_to_ast_helper("(+abc:toto -titi)", "(+(abc:\"toto\") -(\"titi\"))");
         test_parse_query_to_ast_helper("-abc:toto", "-(abc:\"toto\")");
@@ -1,12 +1,15 @@
+use super::lenient::recover_query;
 use super::logical_ast::*;
 use super::query_grammar::parse_to_ast;
 use super::user_input_ast::*;
 use combine::Parser;
 use core::Index;
+use error::TantivyError;
 use query::occur::compose_occur;
 use query::query_parser::logical_ast::LogicalAST;
 use query::AllQuery;
 use query::BooleanQuery;
+use query::BoostQuery;
 use query::EmptyQuery;
 use query::Occur;
 use query::PhraseQuery;
@@ -17,10 +20,43 @@ use schema::IndexRecordOption;
 use schema::{Field, Schema};
 use schema::{FieldType, Term};
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::num::ParseIntError;
-use std::ops::Bound;
+use std::ops::{Bound, Range};
 use std::str::FromStr;
 use tokenizer::TokenizerManager;
+use Score;
+
+// Returns the ASCII counterpart of the full-width variants of the ASCII
+// punctuation characters (U+FF01 to U+FF5E), as typed on CJK and mobile keyboards.
+// Full-width letters and digits are left as they are.
+pub(crate) fn full_width_punctuation_to_ascii(c: char) -> Option<char> {
+    let code = c as u32;
+    if code < 0xFF01 || code > 0xFF5E {
+        return None;
+    }
+    let ascii = (code - 0xFEE0) as u8 as char;
+    if ascii.is_ascii_punctuation() {
+        Some(ascii)
+    } else {
+        None
+    }
+}
+
+fn normalize_full_width_punctuation(query: &str) -> Cow<str> {
+    if !query
+        .chars()
+        .any(|c| full_width_punctuation_to_ascii(c).is_some())
+    {
+        return Cow::Borrowed(query);
+    }
+    Cow::Owned(
+        query
+            .chars()
+            .map(|c| full_width_punctuation_to_ascii(c).unwrap_or(c))
+            .collect(),
+    )
+}
 
 /// Possible error that may happen when parsing a query.
 #[derive(Debug, PartialEq, Eq)]
@@ -30,9 +66,10 @@ pub enum QueryParserError {
     /// `FieldDoesNotExist(field_name: String)`
     /// The query references a field that is not in the schema
     FieldDoesNotExist(String),
-    /// The query contains a term for a `u64`-field, but the value
-    /// is not a u64.
-    ExpectedInt(ParseIntError),
+    /// `ExpectedInt(field_name: String, error: ParseIntError)`
+    /// The query contains a term for a `u64` or `i64` field, but the value
+    /// is not a valid integer.
+    ExpectedInt(String, ParseIntError),
     /// It is forbidden queries that are only "excluding". (e.g. -title:pop)
     AllButQueryForbidden,
     /// If no default field is declared, running a query without any
@@ -50,11 +87,60 @@ pub enum QueryParserError {
     /// The query contains a range query with a phrase as one of the bounds.
     /// Only terms can be used as bounds.
     RangeMustNotHavePhrase,
+    /// The query targets a bytes field.
+    /// Bytes fields cannot be searched via the query parser.
+    /// Use a `TermQuery` built with `Term::from_field_bytes` instead.
+    BytesFieldNotQueryable(String),
+    /// A quote is not closed.
+    /// Only reported by `QueryParser::parse_query_lenient`,
+    /// the strict parser returns a `SyntaxError`.
+    UnbalancedQuote,
+    /// A parenthesis is not closed, or closes a group that was not opened.
+    /// Only reported by `QueryParser::parse_query_lenient`,
+    /// the strict parser returns a `SyntaxError`.
+    UnbalancedParenthesis,
+}
+
+/// Problem found in a query by `QueryParser::parse_query_lenient`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct QueryParserDiagnostic {
+    /// The error that the part of the query would cause,
+    /// if it was parsed strictly.
+    pub error: QueryParserError,
+    /// Byte range of the part of the query concerned.
+    pub span: Range<usize>,
+}
+
+/// Non-fatal issue encountered while parsing a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryParserWarning {
+    /// `FieldDoesNotHavePositionsIndexed(field_name: String)`
+    /// A phrase targeting several fields was not searched in this field,
+    /// because the field does not have any positions indexed.
+    FieldDoesNotHavePositionsIndexed(String),
+}
+
+/// Warnings collected while parsing a query.
+///
+/// See `QueryParser::parse_query_with_warnings`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryParserWarnings {
+    warnings: Vec<QueryParserWarning>,
 }
 
-impl From<ParseIntError> for QueryParserError {
-    fn from(err: ParseIntError) -> QueryParserError {
-        QueryParserError::ExpectedInt(err)
+impl QueryParserWarnings {
+    /// Returns true iff the query was parsed without any warning.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Returns the warnings, in the order in which they were encountered.
+    pub fn warnings(&self) -> &[QueryParserWarning] {
+        &self.warnings[..]
+    }
+
+    fn push(&mut self, warning: QueryParserWarning) {
+        self.warnings.push(warning);
     }
 }
 
@@ -100,6 +186,15 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///   are not relevant anymore.
 ///
 ///   Switching to a default of `AND` can be done by calling `.set_conjunction_by_default()`.
+///   The default can also be overridden for the terms targeting a given field,
+///   by calling `.set_field_default_occur(...)`.
+///
+///   The terms are analyzed with the tokenizer of their field. In particular,
+///   the terms targeting a `STRING` field (using the `raw` tokenizer) are searched as is:
+///   `status:IN_PROGRESS` searches for the single term `IN_PROGRESS`.
+///   Terms may contain any character that has no meaning in the query syntax,
+///   so that `status:in-progress` or `domain:foo.com` are searched as is too.
+///   The terms targeting an `u64` or `i64` field are parsed as integers.
 ///
 ///
 /// * boolean operators `AND`, `OR`. `AND` takes precedence over `OR`, so that `a AND b OR c` is interpreted
@@ -121,6 +216,11 @@ fn trim_ast(logical_ast: LogicalAST) -> Option<LogicalAST> {
 ///   e.g., `title:"Barack Obama"` will only find documents that have "barack" immediately followed
 ///   by "obama".
 ///
+///   Without an explicit field, one phrase query is built per default field, each analyzed
+///   with the tokenizer of its field. If the analysis of a field yields a single token,
+///   a term query is used instead. Default fields that do not have positions indexed are
+///   skipped, which is reported by `.parse_query_with_warnings(...)`.
+///
 /// * range terms: Range searches can be done by specifying the start and end bound. These can be
 ///   inclusive or exclusive. e.g., `title:[a TO c}` will find all documents whose title contains
 ///   a word lexicographically between `a` and `c` (inclusive lower bound, exclusive upper bound).
@@ -133,6 +233,9 @@ pub struct QueryParser {
     schema: Schema,
     default_fields: Vec<Field>,
     conjunction_by_default: bool,
+    field_default_occurs: HashMap<Field, Occur>,
+    field_boosts: HashMap<Field, Score>,
+    normalize_full_width_punctuation: bool,
     tokenizer_manager: TokenizerManager,
 }
 
@@ -151,6 +254,9 @@ impl QueryParser {
             default_fields,
             tokenizer_manager,
             conjunction_by_default: false,
+            field_default_occurs: HashMap::new(),
+            field_boosts: HashMap::new(),
+            normalize_full_width_punctuation: false,
         }
     }
 
@@ -162,6 +268,47 @@ impl QueryParser {
         QueryParser::new(index.schema(), default_fields, index.tokenizers().clone())
     }
 
+    /// Creates a `QueryParser` for the given index, with the field boosts
+    /// of its relevance profile named `profile_name`.
+    ///
+    /// The default fields are the fields boosted by the profile.
+    /// See `IndexSettings::relevance_profiles`.
+    ///
+    /// Returns an `InvalidArgument` error if the index has no such profile.
+    pub fn for_index_with_profile(
+        index: &Index,
+        profile_name: &str,
+    ) -> Result<QueryParser, TantivyError> {
+        let profile = index
+            .relevance_profiles()
+            .get(profile_name)
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(format!(
+                    "Unknown relevance profile '{}'",
+                    profile_name
+                ))
+            })?;
+        let schema = index.schema();
+        let mut field_boosts = Vec::with_capacity(profile.len());
+        for (field_name, &boost) in profile {
+            // The profiles are validated against the schema when loading the index.
+            let field = schema.get_field(field_name).ok_or_else(|| {
+                TantivyError::SchemaError(format!(
+                    "The relevance profile '{}' boosts the unknown field '{}'",
+                    profile_name, field_name
+                ))
+            })?;
+            field_boosts.push((field, boost));
+        }
+        field_boosts.sort_by_key(|&(field, _)| field);
+        let default_fields = field_boosts.iter().map(|&(field, _)| field).collect();
+        let mut query_parser = QueryParser::for_index(index, default_fields);
+        for (field, boost) in field_boosts {
+            query_parser.set_field_boost(field, boost);
+        }
+        Ok(query_parser)
+    }
+
     /// Set the default way to compose queries to a conjunction.
     ///
     /// By default, the query `happy tax payer` is equivalent to the query
@@ -171,27 +318,135 @@ impl QueryParser {
         self.conjunction_by_default = true;
     }
 
+    /// Set the default way to compose the terms targeting `field`
+    /// with the rest of the query.
+    ///
+    /// It takes precedence over `.set_conjunction_by_default()`, but only
+    /// applies to the terms that explicitly target the field and do not
+    /// have an explicit operator.
+    /// For instance, after calling `.set_field_default_occur(tags, Occur::Must)`,
+    /// `tags:rust tokio` is interpreted as `+tags:rust tokio`.
+    pub fn set_field_default_occur(&mut self, field: Field, occur: Occur) {
+        self.field_default_occurs.insert(field, occur);
+    }
+
+    /// Multiply the scores of the terms targeting `field` by `boost`.
+    ///
+    /// The boost applies to the terms explicitly targeting the field,
+    /// as well as to the terms searched in the field as a default field.
+    /// For instance, after calling `.set_field_boost(title, 2.0)`,
+    /// a match in the title weighs twice as much as it used to.
+    pub fn set_field_boost(&mut self, field: Field, boost: Score) {
+        self.field_boosts.insert(field, boost);
+    }
+
+    /// Replace the full-width punctuation characters of the queries
+    /// by their ASCII counterpart before parsing them.
+    ///
+    /// Queries typed on CJK and mobile keyboards may contain for instance
+    /// a full-width colon: after calling `.set_normalize_full_width_punctuation()`,
+    /// `title：東京` is interpreted as `title:東京`.
+    /// Full-width letters and digits are not normalized, so that field names
+    /// still have to match the schema exactly.
+    pub fn set_normalize_full_width_punctuation(&mut self) {
+        self.normalize_full_width_punctuation = true;
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
     /// is not a valid query.
+    /// See `.parse_query_lenient(...)` for queries typed by end users.
+    pub fn parse_query(&self, query: &str) -> Result<Box<Query>, QueryParserError> {
+        self.parse_query_with_warnings(query)
+            .map(|(query, _warnings)| query)
+    }
+
+    /// Parse a query, and returns the warnings encountered along
+    /// with the query.
+    ///
+    /// Warnings report the parts of the query that were ignored,
+    /// without making the query invalid. For instance, a phrase
+    /// targeting several default fields is not searched in the fields
+    /// that do not have positions indexed.
+    pub fn parse_query_with_warnings(
+        &self,
+        query: &str,
+    ) -> Result<(Box<Query>, QueryParserWarnings), QueryParserError> {
+        let mut warnings = QueryParserWarnings::default();
+        let logical_ast = self.parse_query_to_logical_ast_with_warnings(query, &mut warnings)?;
+        Ok((convert_to_query(logical_ast, &self.field_boosts), warnings))
+    }
+
+    /// Parse a query, recovering from the errors it contains.
     ///
-    /// There is currently no lenient mode for the query parser
-    /// which makes it a bad choice for a public/broad user search engine.
+    /// Rather than failing on the first error, the lenient mode searches
+    /// whatever can be made sense of, and returns the problems it found
+    /// along with the query, so that they can be pointed out to the user:
     ///
-    /// Implementing a lenient mode for this query parser is tracked
-    /// in [Issue 5](https://github.com/fulmicoton/tantivy/issues/5)
-    pub fn parse_query(&self, query: &str) -> Result<Box<Query>, QueryParserError> {
-        let logical_ast = self.parse_query_to_logical_ast(query)?;
-        Ok(convert_to_query(logical_ast))
+    /// * unbalanced quotes and parentheses are ignored,
+    ///   e.g. `title:"barack obama` is searched as `title:barack obama`,
+    /// * a literal targeting a field that does not exist is searched
+    ///   as text in the default fields, the field name included,
+    ///   e.g. `author:obama` is searched as `(author obama)`,
+    /// * the clauses that cannot be searched are ignored, for instance
+    ///   ranges with invalid bounds, or terms that are not valid integers
+    ///   for an integer field,
+    /// * the operators and the `+`, `-` prefixes that do not apply to any clause
+    ///   are ignored, e.g. `barack AND` is searched as `barack`,
+    /// * explicit operators and juxtaposed clauses are grouped,
+    ///   e.g. `barack AND obama president` is searched as `(barack AND obama) president`,
+    /// * any other unexpected character is ignored.
+    ///
+    /// The diagnostics are sorted by their byte range in the query.
+    /// A valid query is parsed as by `.parse_query(...)`.
+    pub fn parse_query_lenient(&self, query: &str) -> (Box<Query>, Vec<QueryParserDiagnostic>) {
+        let (logical_ast, diagnostics) = self.parse_query_to_logical_ast_lenient(query);
+        (
+            convert_to_query(logical_ast, &self.field_boosts),
+            diagnostics,
+        )
+    }
+
+    fn parse_query_to_logical_ast_lenient(
+        &self,
+        query: &str,
+    ) -> (LogicalAST, Vec<QueryParserDiagnostic>) {
+        let check_leaf = |leaf: &str| self.parse_query_to_logical_ast(leaf).map(|_| ());
+        let (recovered_query, mut diagnostics) =
+            recover_query(query, self.normalize_full_width_punctuation, &check_leaf);
+        match self.parse_query_to_logical_ast(&recovered_query) {
+            Ok(logical_ast) => (logical_ast, diagnostics),
+            Err(error) => {
+                // e.g. the query only excludes documents.
+                diagnostics.push(QueryParserDiagnostic {
+                    error,
+                    span: 0..query.len(),
+                });
+                (LogicalAST::Clause(Vec::new()), diagnostics)
+            }
+        }
     }
 
     /// Parse the user query into an AST.
     fn parse_query_to_logical_ast(&self, query: &str) -> Result<LogicalAST, QueryParserError> {
+        self.parse_query_to_logical_ast_with_warnings(query, &mut QueryParserWarnings::default())
+    }
+
+    fn parse_query_to_logical_ast_with_warnings(
+        &self,
+        query: &str,
+        warnings: &mut QueryParserWarnings,
+    ) -> Result<LogicalAST, QueryParserError> {
+        let query = if self.normalize_full_width_punctuation {
+            normalize_full_width_punctuation(query)
+        } else {
+            Cow::Borrowed(query)
+        };
         let (user_input_ast, _remaining) = parse_to_ast()
-            .parse(query)
+            .parse(query.as_ref())
             .map_err(|_| QueryParserError::SyntaxError)?;
-        self.compute_logical_ast(user_input_ast)
+        self.compute_logical_ast(user_input_ast, warnings)
     }
 
     fn resolve_field_name(&self, field_name: &str) -> Result<Field, QueryParserError> {
@@ -203,8 +458,9 @@ impl QueryParser {
     fn compute_logical_ast(
         &self,
         user_input_ast: UserInputAST,
+        warnings: &mut QueryParserWarnings,
     ) -> Result<LogicalAST, QueryParserError> {
-        let (occur, ast) = self.compute_logical_ast_with_occur(user_input_ast)?;
+        let (occur, ast) = self.compute_logical_ast_with_occur(user_input_ast, warnings)?;
         if occur == Occur::MustNot {
             return Err(QueryParserError::AllButQueryForbidden);
         }
@@ -224,12 +480,16 @@ impl QueryParser {
         }
         match *field_type {
             FieldType::I64(_) => {
-                let val: i64 = i64::from_str(phrase)?;
+                let val: i64 = i64::from_str(phrase).map_err(|err| {
+                    QueryParserError::ExpectedInt(field_entry.name().to_string(), err)
+                })?;
                 let term = Term::from_field_i64(field, val);
                 Ok(vec![(0, term)])
             }
             FieldType::U64(_) => {
-                let val: u64 = u64::from_str(phrase)?;
+                let val: u64 = u64::from_str(phrase).map_err(|err| {
+                    QueryParserError::ExpectedInt(field_entry.name().to_string(), err)
+                })?;
                 let term = Term::from_field_u64(field, val);
                 Ok(vec![(0, term)])
             }
@@ -279,9 +539,9 @@ impl QueryParser {
                 }
             }
             FieldType::HierarchicalFacet => Ok(vec![(0, Term::from_field_text(field, phrase))]),
-            FieldType::Bytes => {
+            FieldType::Bytes(_) => {
                 let field_name = self.schema.get_field_name(field).to_string();
-                Err(QueryParserError::FieldNotIndexed(field_name))
+                Err(QueryParserError::BytesFieldNotQueryable(field_name))
             }
         }
     }
@@ -307,6 +567,23 @@ impl QueryParser {
         }
     }
 
+    /// Returns the default occur of a sub query,
+    /// taking in account the per-field overrides.
+    fn default_occur_for(&self, user_input_ast: &UserInputAST) -> Occur {
+        let field_name: Option<&String> = match *user_input_ast {
+            UserInputAST::Leaf(ref leaf) => match **leaf {
+                UserInputLeaf::Literal(ref literal) => literal.field_name.as_ref(),
+                UserInputLeaf::Range { ref field, .. } => field.as_ref(),
+                UserInputLeaf::All => None,
+            },
+            _ => None,
+        };
+        field_name
+            .and_then(|field_name| self.schema.get_field(field_name))
+            .and_then(|field| self.field_default_occurs.get(&field).cloned())
+            .unwrap_or_else(|| self.default_occur())
+    }
+
     fn resolve_bound(
         &self,
         field: Field,
@@ -345,13 +622,15 @@ impl QueryParser {
     fn compute_logical_ast_with_occur(
         &self,
         user_input_ast: UserInputAST,
+        warnings: &mut QueryParserWarnings,
     ) -> Result<(Occur, LogicalAST), QueryParserError> {
         match user_input_ast {
             UserInputAST::Clause(sub_queries) => {
-                let default_occur = self.default_occur();
                 let mut logical_sub_queries: Vec<(Occur, LogicalAST)> = Vec::new();
                 for sub_query in sub_queries {
-                    let (occur, sub_ast) = self.compute_logical_ast_with_occur(sub_query)?;
+                    let default_occur = self.default_occur_for(&sub_query);
+                    let (occur, sub_ast) =
+                        self.compute_logical_ast_with_occur(sub_query, warnings)?;
                     let new_occur = compose_occur(default_occur, occur);
                     logical_sub_queries.push((new_occur, sub_ast));
                 }
@@ -359,11 +638,11 @@ impl QueryParser {
             }
             UserInputAST::Unary(left_occur, subquery) => {
                 let (right_occur, logical_sub_queries) =
-                    self.compute_logical_ast_with_occur(*subquery)?;
+                    self.compute_logical_ast_with_occur(*subquery, warnings)?;
                 Ok((compose_occur(left_occur, right_occur), logical_sub_queries))
             }
             UserInputAST::Leaf(leaf) => {
-                let result_ast = self.compute_logical_ast_from_leaf(*leaf)?;
+                let result_ast = self.compute_logical_ast_from_leaf(*leaf, warnings)?;
                 Ok((Occur::Should, result_ast))
             }
         }
@@ -372,6 +651,7 @@ impl QueryParser {
     fn compute_logical_ast_from_leaf(
         &self,
         leaf: UserInputLeaf,
+        warnings: &mut QueryParserWarnings,
     ) -> Result<LogicalAST, QueryParserError> {
         match leaf {
             UserInputLeaf::Literal(literal) => {
@@ -391,12 +671,40 @@ impl QueryParser {
                         }
                     }
                 };
+                let num_fields = term_phrases.len();
                 let mut asts: Vec<LogicalAST> = Vec::new();
+                let mut fields_without_positions: Vec<String> = Vec::new();
                 for (field, phrase) in term_phrases {
-                    if let Some(ast) = self.compute_logical_ast_for_leaf(field, &phrase)? {
-                        asts.push(LogicalAST::Leaf(Box::new(ast)));
+                    match self.compute_logical_ast_for_leaf(field, &phrase) {
+                        Ok(Some(ast)) => {
+                            asts.push(LogicalAST::Leaf(Box::new(ast)));
+                        }
+                        Ok(None) => {}
+                        // Fields without positions are skipped, as long as
+                        // the phrase can be searched in another field.
+                        Err(QueryParserError::FieldDoesNotHavePositionsIndexed(field_name)) => {
+                            if num_fields == 1 {
+                                return Err(QueryParserError::FieldDoesNotHavePositionsIndexed(
+                                    field_name,
+                                ));
+                            }
+                            fields_without_positions.push(field_name);
+                        }
+                        Err(err) => {
+                            return Err(err);
+                        }
                     }
                 }
+                if asts.is_empty() && !fields_without_positions.is_empty() {
+                    return Err(QueryParserError::FieldDoesNotHavePositionsIndexed(
+                        fields_without_positions.swap_remove(0),
+                    ));
+                }
+                for field_name in fields_without_positions {
+                    warnings.push(QueryParserWarning::FieldDoesNotHavePositionsIndexed(
+                        field_name,
+                    ));
+                }
                 let result_ast: LogicalAST = if asts.len() == 1 {
                     asts.into_iter().next().unwrap()
                 } else {
@@ -439,8 +747,19 @@ impl QueryParser {
     }
 }
 
-fn convert_literal_to_query(logical_literal: LogicalLiteral) -> Box<Query> {
-    match logical_literal {
+fn convert_literal_to_query(
+    logical_literal: LogicalLiteral,
+    field_boosts: &HashMap<Field, Score>,
+) -> Box<Query> {
+    let field_opt = match logical_literal {
+        LogicalLiteral::Term(ref term) => Some(term.field()),
+        LogicalLiteral::Phrase(ref term_with_offsets) => {
+            term_with_offsets.first().map(|&(_, ref term)| term.field())
+        }
+        LogicalLiteral::Range { field, .. } => Some(field),
+        LogicalLiteral::All => None,
+    };
+    let query: Box<Query> = match logical_literal {
         LogicalLiteral::Term(term) => Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)),
         LogicalLiteral::Phrase(term_with_offsets) => {
             Box::new(PhraseQuery::new_with_offset(term_with_offsets))
@@ -454,15 +773,19 @@ fn convert_literal_to_query(logical_literal: LogicalLiteral) -> Box<Query> {
             field, value_type, &lower, &upper,
         )),
         LogicalLiteral::All => Box::new(AllQuery),
+    };
+    match field_opt.and_then(|field| field_boosts.get(&field)) {
+        Some(&boost) => Box::new(BoostQuery::new(query, boost)),
+        None => query,
     }
 }
 
-fn convert_to_query(logical_ast: LogicalAST) -> Box<Query> {
+fn convert_to_query(logical_ast: LogicalAST, field_boosts: &HashMap<Field, Score>) -> Box<Query> {
     match trim_ast(logical_ast) {
         Some(LogicalAST::Clause(trimmed_clause)) => {
             let occur_subqueries = trimmed_clause
                 .into_iter()
-                .map(|(occur, subquery)| (occur, convert_to_query(subquery)))
+                .map(|(occur, subquery)| (occur, convert_to_query(subquery, field_boosts)))
                 .collect::<Vec<_>>();
             assert!(
                 !occur_subqueries.is_empty(),
@@ -471,7 +794,7 @@ fn convert_to_query(logical_ast: LogicalAST) -> Box<Query> {
             Box::new(BooleanQuery::from(occur_subqueries))
         }
         Some(LogicalAST::Leaf(trimmed_logical_literal)) => {
-            convert_literal_to_query(*trimmed_logical_literal)
+            convert_literal_to_query(*trimmed_logical_literal, field_boosts)
         }
         None => Box::new(EmptyQuery),
     }
@@ -482,10 +805,14 @@ mod test {
     use super::super::logical_ast::*;
     use super::QueryParser;
     use super::QueryParserError;
+    use super::{QueryParserDiagnostic, QueryParserWarning, QueryParserWarnings};
+    use query::Occur;
     use query::Query;
     use schema::Field;
     use schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
     use schema::{SchemaBuilder, Term, INT_INDEXED, STORED, STRING, TEXT};
+    use std::ops::Range;
+    use std::str::FromStr;
     use tokenizer::{LowerCaser, SimpleTokenizer, StopWordFilter, Tokenizer, TokenizerManager};
     use Index;
 
@@ -501,6 +828,7 @@ mod test {
         let text = schema_builder.add_text_field("text", TEXT);
         schema_builder.add_i64_field("signed", INT_INDEXED);
         schema_builder.add_u64_field("unsigned", INT_INDEXED);
+        schema_builder.add_alias("heading", "title");
         schema_builder.add_text_field("notindexed_text", STORED);
         schema_builder.add_text_field("notindexed_u64", STORED);
         schema_builder.add_text_field("notindexed_i64", STORED);
@@ -630,6 +958,15 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_field_alias() {
+        test_parse_query_to_logical_ast_helper(
+            "heading:toto",
+            "Term([0, 0, 0, 0, 116, 111, 116, 111])",
+            false,
+        );
+    }
+
     #[test]
     pub fn test_parse_query_to_ast_disjunction() {
         test_parse_query_to_logical_ast_helper(
@@ -762,12 +1099,148 @@ mod test {
         let query_parser = make_query_parser();
         assert_matches!(
             query_parser.parse_query("unsigned:18a"),
-            Err(QueryParserError::ExpectedInt(_))
+            Err(QueryParserError::ExpectedInt(_, _))
         );
         assert!(query_parser.parse_query("unsigned:\"18\"").is_ok());
         assert_matches!(
             query_parser.parse_query("signed:18b"),
-            Err(QueryParserError::ExpectedInt(_))
+            Err(QueryParserError::ExpectedInt(_, _))
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_expected_int_names_field() {
+        let query_parser = make_query_parser();
+        match query_parser.parse_query("title:a unsigned:abc") {
+            Err(QueryParserError::ExpectedInt(field_name, _)) => {
+                assert_eq!(field_name, "unsigned");
+            }
+            _ => panic!("Expected an ExpectedInt error"),
+        }
+        match query_parser.parse_query("signed:abc") {
+            Err(QueryParserError::ExpectedInt(field_name, _)) => {
+                assert_eq!(field_name, "signed");
+            }
+            _ => panic!("Expected an ExpectedInt error"),
+        }
+    }
+
+    #[test]
+    pub fn test_parse_query_mixed_fields() {
+        // raw fields are not split, analyzed fields are.
+        test_parse_query_to_logical_ast_helper(
+            "nottokenized:IN_PROGRESS",
+            "Term([0, 0, 0, 7, 73, 78, 95, 80, 82, 79, 71, 82, 69, 83, 83])",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "title:IN_PROGRESS",
+            "\"[(0, Term([0, 0, 0, 0, 105, 110])), \
+             (1, Term([0, 0, 0, 0, 112, 114, 111, 103, 114, 101, 115, 115]))]\"",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "nottokenized:IN_PROGRESS unsigned:18 title:a",
+            "(Term([0, 0, 0, 7, 73, 78, 95, 80, 82, 79, 71, 82, 69, 83, 83]) \
+             Term([0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 18]) \
+             Term([0, 0, 0, 0, 97]))",
+            false,
+        );
+        // punctuation is part of the terms of raw fields.
+        test_parse_query_to_logical_ast_helper(
+            "nottokenized:in-progress",
+            "Term([0, 0, 0, 7, 105, 110, 45, 112, 114, 111, 103, 114, 101, 115, 115])",
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "nottokenized:foo.com",
+            "Term([0, 0, 0, 7, 102, 111, 111, 46, 99, 111, 109])",
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_field_default_occur() {
+        let mut query_parser = make_query_parser();
+        let nottokenized = query_parser.schema.get_field("nottokenized").unwrap();
+        query_parser.set_field_default_occur(nottokenized, Occur::Must);
+        let query = query_parser
+            .parse_query_to_logical_ast("nottokenized:a title:b")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            "(+Term([0, 0, 0, 7, 97]) Term([0, 0, 0, 0, 98]))"
+        );
+        // explicit operators take precedence.
+        let query = query_parser
+            .parse_query_to_logical_ast("-nottokenized:a title:b")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            "(-Term([0, 0, 0, 7, 97]) Term([0, 0, 0, 0, 98]))"
+        );
+        // the override also applies with conjunction by default.
+        query_parser.set_conjunction_by_default();
+        query_parser.set_field_default_occur(nottokenized, Occur::Should);
+        let query = query_parser
+            .parse_query_to_logical_ast("nottokenized:a title:b")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", query),
+            "(Term([0, 0, 0, 7, 97]) +Term([0, 0, 0, 0, 98]))"
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_field_boost() {
+        let mut query_parser = make_query_parser();
+        let title = query_parser.schema.get_field("title").unwrap();
+        query_parser.set_field_boost(title, 2.5);
+        let query_str = |query: &str| format!("{:?}", query_parser.parse_query(query).unwrap());
+        assert!(query_str("title:a").starts_with("BoostQuery {"));
+        assert!(query_str("title:a").ends_with("boost: 2.5 }"));
+        // The boost also applies to the default fields...
+        assert_eq!(query_str("a").matches("BoostQuery").count(), 1);
+        // ... but not to the other fields.
+        assert!(!query_str("text:a").contains("BoostQuery"));
+    }
+
+    #[test]
+    pub fn test_parse_query_unicode_whitespace_and_full_width() {
+        let mut query_parser = make_query_parser();
+        let ast_str = |query_parser: &QueryParser, query: &str| {
+            let query = query_parser.parse_query_to_logical_ast(query).unwrap();
+            format!("{:?}", query)
+        };
+        // Ideographic spaces separate the terms, as ASCII spaces do.
+        assert_eq!(
+            ast_str(&query_parser, "東京\u{3000}大阪"),
+            ast_str(&query_parser, "東京 大阪")
+        );
+        assert_eq!(
+            ast_str(&query_parser, "title:北京\u{3000}AND\u{3000}text:上海"),
+            ast_str(&query_parser, "title:北京 AND text:上海")
+        );
+        // Without normalization, the full-width colon is not a field separator.
+        assert_ne!(
+            ast_str(&query_parser, "title\u{FF1A}東京"),
+            ast_str(&query_parser, "title:東京")
+        );
+        query_parser.set_normalize_full_width_punctuation();
+        assert_eq!(
+            ast_str(&query_parser, "title\u{FF1A}東京\u{3000}text:大阪"),
+            ast_str(&query_parser, "title:東京 text:大阪")
+        );
+        assert_eq!(
+            ast_str(&query_parser, "\u{FF0B}北京\u{3000}\u{FF0D}上海"),
+            ast_str(&query_parser, "+北京 -上海")
+        );
+        // Full-width letters are not normalized, field names still match exactly.
+        assert_eq!(
+            query_parser.parse_query("\u{FF54}itle\u{FF1A}東京").err(),
+            Some(QueryParserError::FieldDoesNotExist(
+                "\u{FF54}itle".to_string()
+            ))
         );
     }
 
@@ -817,4 +1290,306 @@ mod test {
             true,
         );
     }
+
+    #[test]
+    pub fn test_query_parser_phrase_per_field_analysis() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let stemmed_indexing = TextFieldIndexing::default()
+            .set_tokenizer("en_stem")
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let stemmed = schema_builder.add_text_field(
+            "stemmed",
+            TextOptions::default().set_indexing_options(stemmed_indexing),
+        );
+        let no_positions_indexing = TextFieldIndexing::default()
+            .set_tokenizer("default")
+            .set_index_option(IndexRecordOption::WithFreqs);
+        let no_positions = schema_builder.add_text_field(
+            "no_positions",
+            TextOptions::default().set_indexing_options(no_positions_indexing),
+        );
+        let schema = schema_builder.build();
+        let query_parser = QueryParser::new(
+            schema,
+            vec![title, stemmed, no_positions],
+            TokenizerManager::default(),
+        );
+        let parse = |query: &str| {
+            let mut warnings = QueryParserWarnings::default();
+            let logical_ast = query_parser
+                .parse_query_to_logical_ast_with_warnings(query, &mut warnings)
+                .unwrap();
+            (format!("{:?}", logical_ast), warnings)
+        };
+        let phrase = |field: Field, words: &[&str]| {
+            let terms: Vec<(usize, Term)> = words
+                .iter()
+                .enumerate()
+                .map(|(position, word)| (position, Term::from_field_text(field, word)))
+                .collect();
+            format!("\"{:?}\"", terms)
+        };
+
+        let (logical_ast, warnings) = parse("\"Running Dogs\"");
+        assert_eq!(
+            logical_ast,
+            format!(
+                "({} {})",
+                phrase(title, &["running", "dogs"]),
+                phrase(stemmed, &["run", "dog"])
+            )
+        );
+        assert_eq!(
+            warnings.warnings(),
+            &[QueryParserWarning::FieldDoesNotHavePositionsIndexed(
+                "no_positions".to_string()
+            )]
+        );
+        assert!(query_parser.parse_query("\"Running Dogs\"").is_ok());
+
+        // A single token falls back to a term query, in all of the fields.
+        let (logical_ast, warnings) = parse("\"Dogs\"");
+        assert_eq!(
+            logical_ast,
+            format!(
+                "({:?} {:?} {:?})",
+                Term::from_field_text(title, "dogs"),
+                Term::from_field_text(stemmed, "dog"),
+                Term::from_field_text(no_positions, "dogs")
+            )
+        );
+        assert!(warnings.is_empty());
+
+        // An explicit field without positions is still an error.
+        assert_eq!(
+            query_parser
+                .parse_query("no_positions:\"running dogs\"")
+                .unwrap_err(),
+            QueryParserError::FieldDoesNotHavePositionsIndexed("no_positions".to_string())
+        );
+    }
+
+    fn test_parse_query_lenient_helper(
+        query: &str,
+        expected_query: &str,
+        expected_diagnostics: Vec<(QueryParserError, Range<usize>)>,
+    ) {
+        let query_parser = make_query_parser();
+        let (logical_ast, diagnostics) = query_parser.parse_query_to_logical_ast_lenient(query);
+        let expected_logical_ast = query_parser
+            .parse_query_to_logical_ast(expected_query)
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", logical_ast),
+            format!("{:?}", expected_logical_ast),
+            "{}",
+            query
+        );
+        let expected_diagnostics: Vec<QueryParserDiagnostic> = expected_diagnostics
+            .into_iter()
+            .map(|(error, span)| QueryParserDiagnostic { error, span })
+            .collect();
+        assert_eq!(diagnostics, expected_diagnostics, "{}", query);
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_valid_query() {
+        for query in &[
+            "",
+            "toto",
+            "+title:toto -titi",
+            "title:\"a b\" text:c",
+            "a AND b OR c",
+            "NOT a AND b",
+            "(+a +b) -(c d)",
+            "title:{titi TO *} [a TO b]",
+            "signed:-2324 unsigned:[1 TO 5]",
+            "* -toto",
+        ] {
+            test_parse_query_lenient_helper(query, query, vec![]);
+        }
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_unbalanced_quote() {
+        test_parse_query_lenient_helper(
+            "title:\"barack obama",
+            "title:barack obama",
+            vec![(QueryParserError::UnbalancedQuote, 6..7)],
+        );
+        test_parse_query_lenient_helper(
+            "\"a b\" \"c",
+            "\"a b\" c",
+            vec![(QueryParserError::UnbalancedQuote, 6..7)],
+        );
+        // The spans are byte ranges.
+        test_parse_query_lenient_helper(
+            "東京 \"大阪",
+            "東京 大阪",
+            vec![(QueryParserError::UnbalancedQuote, 7..8)],
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_unbalanced_parenthesis() {
+        test_parse_query_lenient_helper(
+            "(a b",
+            "a b",
+            vec![(QueryParserError::UnbalancedParenthesis, 0..1)],
+        );
+        test_parse_query_lenient_helper(
+            "+(a b)) c",
+            "+(a b) c",
+            vec![(QueryParserError::UnbalancedParenthesis, 6..7)],
+        );
+        test_parse_query_lenient_helper(
+            "title:(foo AND",
+            "foo",
+            vec![
+                (QueryParserError::SyntaxError, 0..6),
+                (QueryParserError::UnbalancedParenthesis, 6..7),
+                (QueryParserError::SyntaxError, 11..14),
+            ],
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_unknown_field() {
+        test_parse_query_lenient_helper(
+            "author:obama",
+            "(author obama)",
+            vec![(
+                QueryParserError::FieldDoesNotExist("author".to_string()),
+                0..6,
+            )],
+        );
+        test_parse_query_lenient_helper(
+            "+author:\"barack obama\" title:a",
+            "+(author \"barack obama\") title:a",
+            vec![(
+                QueryParserError::FieldDoesNotExist("author".to_string()),
+                1..7,
+            )],
+        );
+        test_parse_query_lenient_helper(
+            "author:-3",
+            "(author 3)",
+            vec![(
+                QueryParserError::FieldDoesNotExist("author".to_string()),
+                0..6,
+            )],
+        );
+        // Ranges are not searched as text.
+        test_parse_query_lenient_helper(
+            "author:[a TO b] c",
+            "c",
+            vec![(
+                QueryParserError::FieldDoesNotExist("author".to_string()),
+                0..15,
+            )],
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_invalid_clause() {
+        let parse_int_error = u64::from_str("abc").unwrap_err();
+        test_parse_query_lenient_helper(
+            "title:a unsigned:[1 TO abc]",
+            "title:a",
+            vec![(
+                QueryParserError::ExpectedInt("unsigned".to_string(), parse_int_error),
+                8..27,
+            )],
+        );
+        test_parse_query_lenient_helper(
+            "title:[a_b TO c] d",
+            "d",
+            vec![(QueryParserError::RangeMustNotHavePhrase, 0..16)],
+        );
+        test_parse_query_lenient_helper(
+            "notindexed_text:titi a",
+            "a",
+            vec![(
+                QueryParserError::FieldNotIndexed("notindexed_text".to_string()),
+                0..20,
+            )],
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_dangling_operator() {
+        test_parse_query_lenient_helper("a AND", "a", vec![(QueryParserError::SyntaxError, 2..5)]);
+        test_parse_query_lenient_helper("OR a", "a", vec![(QueryParserError::SyntaxError, 0..2)]);
+        test_parse_query_lenient_helper(
+            "a AND OR b",
+            "a OR b",
+            vec![(QueryParserError::SyntaxError, 2..5)],
+        );
+        test_parse_query_lenient_helper("a -", "a", vec![(QueryParserError::SyntaxError, 2..3)]);
+        test_parse_query_lenient_helper("NOT", "", vec![(QueryParserError::SyntaxError, 0..3)]);
+        test_parse_query_lenient_helper("a ()", "a", vec![(QueryParserError::SyntaxError, 2..4)]);
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_mixed_operators() {
+        test_parse_query_lenient_helper(
+            "a AND b c",
+            "(a AND b) c",
+            vec![(QueryParserError::SyntaxError, 0..7)],
+        );
+        test_parse_query_lenient_helper(
+            "c a OR b",
+            "c (a OR b)",
+            vec![(QueryParserError::SyntaxError, 2..8)],
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_unexpected_char() {
+        test_parse_query_lenient_helper("a}b", "a b", vec![(QueryParserError::SyntaxError, 1..2)]);
+        test_parse_query_lenient_helper("a.b", "a.b", vec![]);
+        test_parse_query_lenient_helper(
+            "title:a ]",
+            "title:a",
+            vec![(QueryParserError::SyntaxError, 8..9)],
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_all_but_query() {
+        let query_parser = make_query_parser();
+        let (query, diagnostics) = query_parser.parse_query_lenient("-a");
+        assert_eq!(format!("{:?}", query), "EmptyQuery");
+        assert_eq!(
+            diagnostics,
+            vec![QueryParserDiagnostic {
+                error: QueryParserError::AllButQueryForbidden,
+                span: 0..2,
+            }]
+        );
+    }
+
+    #[test]
+    pub fn test_parse_query_lenient_full_width() {
+        let mut query_parser = make_query_parser();
+        query_parser.set_normalize_full_width_punctuation();
+        let (logical_ast, diagnostics) =
+            query_parser.parse_query_to_logical_ast_lenient("title\u{FF1A}東京 \u{FF08}大阪");
+        let expected_logical_ast = query_parser
+            .parse_query_to_logical_ast("title:東京 大阪")
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", logical_ast),
+            format!("{:?}", expected_logical_ast)
+        );
+        // The span refers to the full-width parenthesis of the original query.
+        assert_eq!(
+            diagnostics,
+            vec![QueryParserDiagnostic {
+                error: QueryParserError::UnbalancedParenthesis,
+                span: 15..18,
+            }]
+        );
+    }
 }
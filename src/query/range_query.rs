@@ -1,3 +1,5 @@
+use byteorder::{BigEndian, ByteOrder};
+use common;
 use common::BitSet;
 use core::Searcher;
 use core::SegmentReader;
@@ -57,7 +59,7 @@ fn map_bound<TFrom, TTo, Transform: Fn(&TFrom) -> TTo>(
 /// #         for year in 1950u64..2017u64 {
 /// #             let num_docs_within_year = 10 + (year - 1950) * (year - 1950);
 /// #             for _ in 0..num_docs_within_year {
-/// #                 index_writer.add_document(doc!(year_field => year));
+/// #                 index_writer.add_document(doc!(year_field => year)).unwrap();
 /// #             }
 /// #         }
 /// #         index_writer.commit().unwrap();
@@ -89,6 +91,21 @@ pub struct RangeQuery {
 }
 
 impl RangeQuery {
+    /// Creates a new `RangeQuery` from bounds expressed as raw term values.
+    pub(crate) fn new_value_bytes_bounds(
+        field: Field,
+        value_type: Type,
+        left_bound: Bound<Vec<u8>>,
+        right_bound: Bound<Vec<u8>>,
+    ) -> RangeQuery {
+        RangeQuery {
+            field,
+            value_type,
+            left_bound,
+            right_bound,
+        }
+    }
+
     /// Creates a new `RangeQuery` from bounded start and end terms.
     ///
     /// If the value type is not correct, something may go terribly wrong when
@@ -229,7 +246,14 @@ impl RangeQuery {
 impl Query for RangeQuery {
     fn weight(&self, searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<Weight>> {
         let schema = searcher.schema();
-        let value_type = schema.get_field_entry(self.field).field_type().value_type();
+        let field_entry = schema.get_field_entry(self.field);
+        if !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not indexed.",
+                field_entry.name()
+            )));
+        }
+        let value_type = field_entry.field_type().value_type();
         if value_type != self.value_type {
             let err_msg = format!(
                 "Create a range query of the type {:?}, when the field given was of type {:?}",
@@ -239,6 +263,7 @@ impl Query for RangeQuery {
         }
         Ok(Box::new(RangeWeight {
             field: self.field,
+            value_type,
             left_bound: self.left_bound.clone(),
             right_bound: self.right_bound.clone(),
         }))
@@ -247,6 +272,7 @@ impl Query for RangeQuery {
 
 pub struct RangeWeight {
     field: Field,
+    value_type: Type,
     left_bound: Bound<Vec<u8>>,
     right_bound: Bound<Vec<u8>>,
 }
@@ -267,6 +293,28 @@ impl RangeWeight {
         };
         term_stream_builder.into_stream()
     }
+
+    /// Returns the min and max values of the field in the segment,
+    /// mapped to `u64` as in the terms, if the field is a
+    /// single-valued fast field.
+    fn fast_field_range(&self, reader: &SegmentReader) -> Option<(u64, u64)> {
+        match self.value_type {
+            Type::U64 => reader
+                .fast_field_reader::<u64>(self.field)
+                .ok()
+                .map(|ff_reader| (ff_reader.min_value(), ff_reader.max_value())),
+            Type::I64 => reader
+                .fast_field_reader::<i64>(self.field)
+                .ok()
+                .map(|ff_reader| {
+                    (
+                        common::i64_to_u64(ff_reader.min_value()),
+                        common::i64_to_u64(ff_reader.max_value()),
+                    )
+                }),
+            _ => None,
+        }
+    }
 }
 
 impl Weight for RangeWeight {
@@ -274,7 +322,7 @@ impl Weight for RangeWeight {
         let max_doc = reader.max_doc();
         let mut doc_bitset = BitSet::with_max_value(max_doc);
 
-        let inverted_index = reader.inverted_index(self.field);
+        let inverted_index = reader.try_inverted_index(self.field)?;
         let term_dict = inverted_index.terms();
         let mut term_range = self.term_range(term_dict);
         while term_range.advance() {
@@ -290,18 +338,47 @@ impl Weight for RangeWeight {
         let doc_bitset = BitSetDocSet::from(doc_bitset);
         Ok(Box::new(ConstScorer::new(doc_bitset)))
     }
+
+    /// For fast fields, checks the range against the min and max values
+    /// of the segment. The min and max values may include deleted documents,
+    /// so that a segment may be visited without matching any document.
+    fn can_match(&self, reader: &SegmentReader) -> bool {
+        use std::collections::Bound::*;
+        let (min_value, max_value) = match self.fast_field_range(reader) {
+            Some(min_max) => min_max,
+            None => return true,
+        };
+        let above_left_bound = match self.left_bound {
+            Included(ref term_val) => max_value >= BigEndian::read_u64(term_val),
+            Excluded(ref term_val) => max_value > BigEndian::read_u64(term_val),
+            Unbounded => true,
+        };
+        let below_right_bound = match self.right_bound {
+            Included(ref term_val) => min_value <= BigEndian::read_u64(term_val),
+            Excluded(ref term_val) => min_value < BigEndian::read_u64(term_val),
+            Unbounded => true,
+        };
+        above_left_bound && below_right_bound
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::RangeQuery;
-    use collector::CountCollector;
-    use query::Query;
-    use schema::{Document, Field, SchemaBuilder, INT_INDEXED};
+    use collector::{Collector, CountCollector};
+    use indexer::NoMergePolicy;
+    use query::{BooleanQuery, Occur, Query, TermQuery};
+    use schema::{Document, Field, IndexRecordOption, SchemaBuilder, Term};
+    use schema::{FAST, INT_INDEXED, TEXT};
     use std::collections::Bound;
+    use DocId;
     use Index;
     use Result;
+    use Score;
+    use SegmentLocalId;
+    use SegmentReader;
+    use TantivyError;
 
     #[test]
     fn test_range_query_simple() {
@@ -316,7 +393,7 @@ mod tests {
                 for year in 1950u64..2017u64 {
                     let num_docs_within_year = 10 + (year - 1950) * (year - 1950);
                     for _ in 0..num_docs_within_year {
-                        index_writer.add_document(doc!(year_field => year));
+                        index_writer.add_document(doc!(year_field => year)).unwrap();
                     }
                 }
                 index_writer.commit().unwrap();
@@ -356,7 +433,7 @@ mod tests {
                         doc.add_i64(int_field, j as i64);
                     }
                 }
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
 
             index_writer.commit().unwrap();
@@ -396,4 +473,151 @@ mod tests {
         );
     }
 
+    /// Counts the segments visited by a search.
+    #[derive(Default)]
+    struct SegmentVisitCollector {
+        num_segments: usize,
+        num_docs: usize,
+    }
+
+    impl Collector for SegmentVisitCollector {
+        fn set_segment(&mut self, _: SegmentLocalId, _: &SegmentReader) -> Result<()> {
+            self.num_segments += 1;
+            Ok(())
+        }
+
+        fn collect(&mut self, _: DocId, _: Score) {
+            self.num_docs += 1;
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_range_query_prunes_segments() {
+        let mut schema_builder = SchemaBuilder::new();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let timestamp_field = schema_builder.add_u64_field("timestamp", FAST | INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for segment in 0u64..10u64 {
+                for i in 0u64..10u64 {
+                    let text = if i % 2 == 0 { "a" } else { "b" };
+                    let timestamp = segment * 100 + i * 10;
+                    index_writer
+                        .add_document(doc!(
+                            text_field => text,
+                            timestamp_field => timestamp
+                        ))
+                        .unwrap();
+                }
+                index_writer.commit().unwrap();
+            }
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        assert_eq!(searcher.segment_readers().len(), 10);
+        let search = |query: &Query| {
+            let mut collector = SegmentVisitCollector::default();
+            query.search(&searcher, &mut collector).unwrap();
+            assert_eq!(query.count(&searcher).unwrap(), collector.num_docs);
+            (collector.num_segments, collector.num_docs)
+        };
+        let range_query =
+            || -> Box<Query> { Box::new(RangeQuery::new_u64(timestamp_field, 250u64..520u64)) };
+        let term_query = || -> Box<Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, "a"),
+                IndexRecordOption::Basic,
+            ))
+        };
+
+        assert_eq!(search(&*range_query()), (4, 27));
+        assert_eq!(
+            search(&RangeQuery::new_u64_bounds(
+                timestamp_field,
+                Bound::Excluded(290),
+                Bound::Included(300)
+            )),
+            (1, 1)
+        );
+        assert_eq!(
+            search(&RangeQuery::new_u64(timestamp_field, 2_000u64..3_000u64)),
+            (0, 0)
+        );
+
+        // The pruning goes through the `Must` clauses of boolean queries.
+        let conjunction = BooleanQuery::from(vec![
+            (Occur::Must, range_query()),
+            (Occur::Must, term_query()),
+        ]);
+        assert_eq!(search(&conjunction), (4, 13));
+
+        // ... but a `Should` clause may match any segment.
+        let disjunction = BooleanQuery::from(vec![
+            (Occur::Should, range_query()),
+            (Occur::Should, term_query()),
+        ]);
+        assert_eq!(search(&disjunction), (10, 64));
+    }
+
+    #[test]
+    fn test_range_query_prunes_segments_i64() {
+        let mut schema_builder = SchemaBuilder::new();
+        let int_field = schema_builder.add_i64_field("intfield", FAST | INT_INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            index_writer.set_merge_policy(Box::new(NoMergePolicy));
+            for val in -10i64..0i64 {
+                index_writer.add_document(doc!(int_field => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+            for val in 1i64..11i64 {
+                index_writer.add_document(doc!(int_field => val)).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let search = |range_query: RangeQuery| {
+            let mut collector = SegmentVisitCollector::default();
+            range_query.search(&searcher, &mut collector).unwrap();
+            (collector.num_segments, collector.num_docs)
+        };
+        assert_eq!(search(RangeQuery::new_i64(int_field, -5..0)), (1, 5));
+        assert_eq!(search(RangeQuery::new_i64(int_field, -5..3)), (2, 7));
+        assert_eq!(search(RangeQuery::new_i64(int_field, 0..1)), (0, 0));
+    }
+
+    #[test]
+    fn test_queries_on_field_not_indexed() {
+        let mut schema_builder = SchemaBuilder::new();
+        let int_field = schema_builder.add_u64_field("intfield", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 6_000_000).unwrap();
+            index_writer.add_document(doc!(int_field => 3u64)).unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let is_schema_error = |query: &Query| {
+            let mut count_collector = CountCollector::default();
+            match searcher.search(query, &mut count_collector) {
+                Err(TantivyError::SchemaError(_)) => true,
+                _ => false,
+            }
+        };
+        assert!(is_schema_error(&RangeQuery::new_u64(int_field, 0..10)));
+        assert!(is_schema_error(&TermQuery::new(
+            Term::from_field_u64(int_field, 3),
+            IndexRecordOption::Basic,
+        )));
+    }
+
 }
@@ -31,16 +31,16 @@ use Searcher;
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit().unwrap();
 ///     }
 ///
@@ -109,12 +109,16 @@ mod test {
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_with_num_threads(1, 10_000_000).unwrap();
-            index_writer.add_document(doc!(
-                country_field => "japan",
-            ));
-            index_writer.add_document(doc!(
-                country_field => "korea",
-            ));
+            index_writer
+                .add_document(doc!(
+                    country_field => "japan",
+                ))
+                .unwrap();
+            index_writer
+                .add_document(doc!(
+                    country_field => "korea",
+                ))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -1,35 +1,116 @@
 use collector::Collector;
 use common::BitSet;
-use docset::{DocSet, SkipResult};
+use docset::{BlockDocSet, DocSet, SkipResult, DOC_BLOCK_LEN};
 use downcast;
 use fastfield::DeleteBitSet;
 use std::ops::DerefMut;
 use DocId;
 use Score;
 
+// Number of documents between two checks of `Collector::is_done`.
+const IS_DONE_CHECK_INTERVAL: u32 = 1_024;
+
 /// Scored set of documents matching a query within a specific segment.
 ///
+/// A `Scorer` goes through the matching documents of the segment,
+/// deleted documents included, following the contract of
+/// [`DocSet`](../trait.DocSet.html).
+/// Deleted documents are filtered out by `.collect(...)`.
+///
+/// A scorer that does not need to score its documents can wrap
+/// a `DocSet` into a [`ConstScorer`](./struct.ConstScorer.html).
+///
 /// See [`Query`](./trait.Query.html).
 pub trait Scorer: downcast::Any + DocSet + 'static {
-    /// Returns the score.
+    /// Returns the score of the current document.
     ///
+    /// It is only meaningful while the scorer is positioned on a document.
     /// This method will perform a bit of computation and is not cached.
     fn score(&mut self) -> Score;
 
+    /// Returns the `BlockDocSet` of the scorer, if it can go through
+    /// its documents block by block.
+    ///
+    /// This is the case of the scorers of a single term
+    /// and of the `AllQuery`.
+    fn block_docset(&mut self) -> Option<&mut BlockDocSet> {
+        None
+    }
+
     /// Consumes the complete `DocSet` and
     /// push the scored documents to the collector.
+    ///
+    /// The documents are pushed block by block if the collector
+    /// accepts blocks and the scorer has a `.block_docset()`.
+    ///
+    /// The collection stops early once `collector.is_done()`.
     fn collect(&mut self, collector: &mut Collector, delete_bitset_opt: Option<&DeleteBitSet>) {
+        if collector.accepts_blocks() && !collector.requires_scoring() {
+            if let Some(block_docset) = self.block_docset() {
+                collect_blocks(block_docset, collector, delete_bitset_opt);
+                return;
+            }
+        }
+        let mut num_docs_before_check = IS_DONE_CHECK_INTERVAL;
         if let Some(delete_bitset) = delete_bitset_opt {
             while self.advance() {
                 let doc = self.doc();
                 if !delete_bitset.is_deleted(doc) {
                     collector.collect(doc, self.score());
                 }
+                num_docs_before_check -= 1;
+                if num_docs_before_check == 0 {
+                    if collector.is_done() {
+                        return;
+                    }
+                    num_docs_before_check = IS_DONE_CHECK_INTERVAL;
+                }
             }
         } else {
             while self.advance() {
                 collector.collect(self.doc(), self.score());
+                num_docs_before_check -= 1;
+                if num_docs_before_check == 0 {
+                    if collector.is_done() {
+                        return;
+                    }
+                    num_docs_before_check = IS_DONE_CHECK_INTERVAL;
+                }
+            }
+        }
+    }
+}
+
+/// Pushes the documents of `block_docset` that are not deleted
+/// to the collector, block by block.
+fn collect_blocks(
+    block_docset: &mut BlockDocSet,
+    collector: &mut Collector,
+    delete_bitset_opt: Option<&DeleteBitSet>,
+) {
+    let mut block = [0u32; DOC_BLOCK_LEN];
+    let mut alive_block = [0u32; DOC_BLOCK_LEN];
+    loop {
+        if collector.is_done() {
+            return;
+        }
+        let num_docs = block_docset.fill_block(&mut block);
+        if num_docs == 0 {
+            return;
+        }
+        if let Some(delete_bitset) = delete_bitset_opt {
+            let mut num_alive = 0;
+            for &doc in &block[..num_docs] {
+                if !delete_bitset.is_deleted(doc) {
+                    alive_block[num_alive] = doc;
+                    num_alive += 1;
+                }
+            }
+            if num_alive > 0 {
+                collector.collect_block(&alive_block[..num_alive]);
             }
+        } else {
+            collector.collect_block(&block[..num_docs]);
         }
     }
 }
@@ -44,6 +125,10 @@ impl Scorer for Box<Scorer> {
         self.deref_mut().score()
     }
 
+    fn block_docset(&mut self) -> Option<&mut BlockDocSet> {
+        self.deref_mut().block_docset()
+    }
+
     fn collect(&mut self, collector: &mut Collector, delete_bitset: Option<&DeleteBitSet>) {
         let scorer = self.deref_mut();
         scorer.collect(collector, delete_bitset);
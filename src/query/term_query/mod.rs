@@ -9,14 +9,71 @@ pub use self::term_weight::TermWeight;
 #[cfg(test)]
 mod tests {
 
-    use collector::TopCollector;
+    use collector::{Collector, TopCollector};
     use docset::DocSet;
-    use query::{Query, QueryParser, Scorer, TermQuery};
+    use query::{AllQuery, Query, QueryParser, Scorer, TermQuery};
     use schema::{IndexRecordOption, SchemaBuilder, STRING, TEXT};
     use tests::assert_nearly_equals;
+    use DocAddress;
+    use DocId;
     use Index;
+    use Result;
+    use Score;
+    use SegmentLocalId;
+    use SegmentReader;
     use Term;
 
+    /// Collects the doc addresses, either doc by doc
+    /// or block by block.
+    struct DocAddressCollector {
+        blocks: bool,
+        segment_local_id: SegmentLocalId,
+        doc_addresses: Vec<DocAddress>,
+    }
+
+    impl DocAddressCollector {
+        fn new(blocks: bool) -> DocAddressCollector {
+            DocAddressCollector {
+                blocks,
+                segment_local_id: 0,
+                doc_addresses: Vec::new(),
+            }
+        }
+    }
+
+    impl Collector for DocAddressCollector {
+        fn set_segment(
+            &mut self,
+            segment_local_id: SegmentLocalId,
+            _: &SegmentReader,
+        ) -> Result<()> {
+            self.segment_local_id = segment_local_id;
+            Ok(())
+        }
+
+        fn collect(&mut self, doc: DocId, _score: Score) {
+            assert!(!self.blocks);
+            self.doc_addresses
+                .push(DocAddress(self.segment_local_id, doc));
+        }
+
+        fn requires_scoring(&self) -> bool {
+            false
+        }
+
+        fn accepts_blocks(&self) -> bool {
+            self.blocks
+        }
+
+        fn collect_block(&mut self, docs: &[DocId]) {
+            assert!(self.blocks);
+            for &doc in docs {
+                self.doc_addresses
+                    .push(DocAddress(self.segment_local_id, doc));
+            }
+        }
+    }
+
     #[test]
     pub fn test_term_query_no_freq() {
         let mut schema_builder = SchemaBuilder::default();
@@ -28,7 +85,7 @@ mod tests {
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc!(text_field => "a");
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             assert!(index_writer.commit().is_ok());
         }
@@ -61,8 +118,10 @@ mod tests {
                 left_field => "left1 left2 left2 left2f2 left2f2 left3 abcde abcde abcde abcde abcde abcde abcde abcde abcde abcewde abcde abcde",
                 right_field => "right1 right2",
                 large_field => "large0 large1 large2 large3 large4 large5 large6 large7 large8 large9 large10 large11 large12 large13 large14 large15 large16 large17 large18 large19 large20 large21 large22 large23 large24 large25 large26 large27 large28 large29 large30 large31 large32 large33 large34 large35 large36 large37 large38 large39 large40 large41 large42 large43 large44 large45 large46 large47 large48 large49 large50 large51 large52 large53 large54 large55 large56 large57 large58 large59 large60 large61 large62 large63 large64 large65 large66 large67 large68 large69 large70 large71 large72 large73 large74 large75 large76 large77 large78 large79 large80 large81 large82 large83 large84 large85 large86 large87 large88 large89 large90 large91 large92 large93 large94 large95 large96 large97 large98 large99 large100 large101 large102 large103 large104 large105 large106 large107 large108 large109 large110 large111 large112 large113 large114 large115 large116 large117 large118 large119 large120 large121 large122 large123 large124 large125 large126 large127 large128 large129 large130 large131 large132 large133 large134 large135 large136 large137 large138 large139 large140 large141 large142 large143 large144 large145 large146 large147 large148 large149 large150 large151 large152 large153 large154 large155 large156 large157 large158 large159 large160 large161 large162 large163 large164 large165 large166 large167 large168 large169 large170 large171 large172 large173 large174 large175 large176 large177 large178 large179 large180 large181 large182 large183 large184 large185 large186 large187 large188 large189 large190 large191 large192 large193 large194 large195 large196 large197 large198 large199 large200 large201 large202 large203 large204 large205 large206 large207 large208 large209 large210 large211 large212 large213 large214 large215 large216 large217 large218 large219 large220 large221 large222 large223 large224 large225 large226 large227 large228 large229 large230 large231 large232 large233 large234 large235 large236 large237 large238 large239 large240 large241 large242 large243 large244 large245 large246 large247 large248 large249 large250 large251 large252 large253 large254 large255 large256 large257 large258 large259 large260 large261 large262 large263 large264 large265 large266 large267 large268 large269 large270 large271 large272 large273 large274 large275 large276 large277 large278 large279 large280 large281 large282 large283 large284 large285 large286"
-            ));
-            index_writer.add_document(doc!(left_field => "left4 left1"));
+            )).unwrap();
+            index_writer
+                .add_document(doc!(left_field => "left4 left1"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
         index.load_searchers().unwrap();
@@ -103,4 +162,53 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_term_query_collect_blocks() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let id_field = schema_builder.add_text_field("id", STRING);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            for i in 0..1_000 {
+                let text = if i % 3 == 0 { "a b" } else { "b" };
+                index_writer
+                    .add_document(doc!(
+                        text_field => text,
+                        id_field => format!("{}", i % 7)
+                    ))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+            index_writer
+                .delete_term(Term::from_field_text(id_field, "3"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let queries: Vec<Box<Query>> = vec![
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, "a"),
+                IndexRecordOption::WithFreqsAndPositions,
+            )),
+            Box::new(TermQuery::new(
+                Term::from_field_text(text_field, "b"),
+                IndexRecordOption::Basic,
+            )),
+            Box::new(AllQuery),
+        ];
+        for query in &queries {
+            let mut scalar_collector = DocAddressCollector::new(false);
+            searcher.search(&**query, &mut scalar_collector).unwrap();
+            let mut block_collector = DocAddressCollector::new(true);
+            searcher.search(&**query, &mut block_collector).unwrap();
+            assert!(!scalar_collector.doc_addresses.is_empty());
+            assert_eq!(
+                scalar_collector.doc_addresses,
+                block_collector.doc_addresses
+            );
+        }
+    }
 }
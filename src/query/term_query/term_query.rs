@@ -6,6 +6,7 @@ use schema::IndexRecordOption;
 use std::collections::BTreeSet;
 use Result;
 use Searcher;
+use TantivyError;
 use Term;
 
 /// A Term query matches all of the documents
@@ -18,6 +19,8 @@ use Term;
 /// * `term_freq`  - number of occurrences of the term in the field
 /// * `field norm` - number of tokens in the field.
 ///
+/// Searching a field that is not indexed returns a `SchemaError`.
+///
 /// ```rust
 /// #[macro_use]
 /// extern crate tantivy;
@@ -36,16 +39,16 @@ use Term;
 ///         let mut index_writer = index.writer(3_000_000)?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Name of the Wind",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of Muadib",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "A Dairy Cow",
-///         ));
+///         ))?;
 ///         index_writer.add_document(doc!(
 ///             title => "The Diary of a Young Girl",
-///         ));
+///         ))?;
 ///         index_writer.commit()?;
 ///     }
 ///
@@ -109,6 +112,13 @@ impl TermQuery {
 
 impl Query for TermQuery {
     fn weight(&self, searcher: &Searcher, scoring_enabled: bool) -> Result<Box<Weight>> {
+        let field_entry = searcher.schema().get_field_entry(self.term.field());
+        if !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not indexed.",
+                field_entry.name()
+            )));
+        }
         Ok(Box::new(self.specialized_weight(searcher, scoring_enabled)))
     }
     fn query_terms(&self, term_set: &mut BTreeSet<Term>) {
@@ -1,4 +1,4 @@
-use docset::{DocSet, SkipResult};
+use docset::{BlockDocSet, DocSet, SkipResult};
 use query::Scorer;
 use DocId;
 use Score;
@@ -53,4 +53,8 @@ impl Scorer for TermScorer {
         self.similarity_weight
             .score(fieldnorm_id, self.postings.term_freq())
     }
+
+    fn block_docset(&mut self) -> Option<&mut BlockDocSet> {
+        Some(&mut self.postings)
+    }
 }
@@ -7,6 +7,7 @@ use query::Scorer;
 use query::Weight;
 use schema::IndexRecordOption;
 use Result;
+use Score;
 use Term;
 
 pub struct TermWeight {
@@ -18,8 +19,8 @@ pub struct TermWeight {
 impl Weight for TermWeight {
     fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
         let field = self.term.field();
-        let inverted_index = reader.inverted_index(field);
-        let fieldnorm_reader = reader.get_fieldnorms_reader(field);
+        let inverted_index = reader.try_inverted_index(field)?;
+        let fieldnorm_reader = reader.try_fieldnorms_reader(field)?;
         let similarity_weight = self.similarity_weight.clone();
         let postings_opt: Option<SegmentPostings> =
             inverted_index.read_postings(&self.term, self.index_record_option);
@@ -42,7 +43,7 @@ impl Weight for TermWeight {
         if reader.num_deleted_docs() == 0 {
             let field = self.term.field();
             Ok(reader
-                .inverted_index(field)
+                .try_inverted_index(field)?
                 .get_term_info(&self.term)
                 .map(|term_info| term_info.doc_freq)
                 .unwrap_or(0))
@@ -50,6 +51,18 @@ impl Weight for TermWeight {
             Ok(self.scorer(reader)?.count())
         }
     }
+
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        let field = self.term.field();
+        reader
+            .try_inverted_index(field)
+            .map(|inverted_index| u64::from(inverted_index.doc_freq(&self.term)))
+            .unwrap_or_else(|_| u64::from(reader.max_doc()))
+    }
+
+    fn max_score(&self, _reader: &SegmentReader) -> Option<Score> {
+        Some(self.similarity_weight.max_score())
+    }
 }
 
 impl TermWeight {
@@ -1,13 +1,23 @@
 use super::Scorer;
 use core::SegmentReader;
 use Result;
+use Score;
 
 /// A Weight is the specialization of a Query
 /// for a given set of segments.
 ///
+/// A single `Weight` is used to search all of the segments of a
+/// `Searcher`: it should hold the searcher-wide statistics required to
+/// score documents, and nothing specific to a segment.
+///
 /// See [`Query`](./trait.Query.html).
 pub trait Weight {
     /// Returns the scorer for the given segment.
+    ///
+    /// The scorer is positioned before its first document.
+    /// It may be called several times for a given segment, and should
+    /// return a new scorer each time.
+    ///
     /// See [`Query`](./trait.Query.html).
     fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>>;
 
@@ -15,4 +25,40 @@ pub trait Weight {
     fn count(&self, reader: &SegmentReader) -> Result<u32> {
         Ok(self.scorer(reader)?.count())
     }
+
+    /// Returns a cheap estimate of the number of documents
+    /// the scorer for the given segment will go through.
+    ///
+    /// It is used to order the clauses of an intersection, so that
+    /// the rarest clause drives the iteration.
+    /// The estimate does not need to be exact, but it should not
+    /// require building the scorer.
+    ///
+    /// The default implementation pessimistically returns `max_doc`.
+    fn cost(&self, reader: &SegmentReader) -> u64 {
+        u64::from(reader.max_doc())
+    }
+
+    /// Returns an upper bound of the scores of the documents of
+    /// the given segment, if it can be computed cheaply.
+    ///
+    /// It is used to skip the segments in which no document can
+    /// reach the `score_threshold` of the collector.
+    ///
+    /// The default implementation returns `None`, meaning that
+    /// the scores are unbounded.
+    fn max_score(&self, _reader: &SegmentReader) -> Option<Score> {
+        None
+    }
+
+    /// Returns `false` if no document of the given segment can match,
+    /// as decided from cheap per-segment metadata.
+    ///
+    /// Segments that cannot match are skipped entirely: neither the
+    /// scorer nor `Collector::set_segment` are called for them.
+    ///
+    /// The default implementation returns `true`.
+    fn can_match(&self, _reader: &SegmentReader) -> bool {
+        true
+    }
 }
@@ -0,0 +1,83 @@
+/// Define how a bytes field should be handled by tantivy.
+///
+/// A bytes field can be
+/// - indexed: each value is indexed as a single, untokenized term,
+/// making it possible to search for exact byte sequences.
+/// - stored: the value is persisted in the doc store.
+/// - fast: the value is accessible via a `BytesFastFieldReader`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytesOptions {
+    indexed: bool,
+    fast: bool,
+    stored: bool,
+}
+
+impl BytesOptions {
+    /// Returns true iff the value is indexed.
+    pub fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
+    /// Returns true iff the value is a fast field.
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+
+    /// Returns true iff the value is stored.
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Set the bytes options as indexed.
+    ///
+    /// Each value is indexed as a single term.
+    /// No tokenization is applied.
+    pub fn set_indexed(mut self) -> BytesOptions {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the bytes options as a fast field.
+    ///
+    /// Fast fields are designed for random access.
+    pub fn set_fast(mut self) -> BytesOptions {
+        self.fast = true;
+        self
+    }
+
+    /// Set the bytes options as stored.
+    ///
+    /// Only the fields that are set as *stored* are
+    /// persisted into the Tantivy's store.
+    pub fn set_stored(mut self) -> BytesOptions {
+        self.stored = true;
+        self
+    }
+
+    /// Set the bytes options as not stored.
+    pub(crate) fn unset_stored(mut self) -> BytesOptions {
+        self.stored = false;
+        self
+    }
+
+    /// Set the bytes options as not indexed.
+    pub(crate) fn unset_indexed(mut self) -> BytesOptions {
+        self.indexed = false;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BytesOptions;
+    use serde_json;
+
+    #[test]
+    fn test_bytes_options_serialization() {
+        let bytes_options = BytesOptions::default().set_indexed().set_stored();
+        let json = serde_json::to_string(&bytes_options).unwrap();
+        assert_eq!(json, r#"{"indexed":true,"fast":false,"stored":true}"#);
+        let deserialized: BytesOptions = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, bytes_options);
+    }
+}
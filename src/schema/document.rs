@@ -3,6 +3,7 @@ use common::BinarySerializable;
 use common::VInt;
 use itertools::Itertools;
 use std::io::{self, Read, Write};
+use tokenizer::PreTokenizedString;
 
 /// Tantivy's Document is the object that can
 /// be indexed and then searched for.
@@ -92,11 +93,36 @@ impl Document {
         self.add(FieldValue::new(field, Value::Bytes(value)))
     }
 
+    /// Add pretokenized text, with a posting payload attached to each token.
+    ///
+    /// See `TextFieldIndexing::set_payload_size`.
+    pub fn add_payload_tokens(&mut self, field: Field, tokens: Vec<PayloadToken>) {
+        self.add(FieldValue::new(field, Value::PayloadTokens(tokens)))
+    }
+
+    /// Add pretokenized text, indexed without going through
+    /// the tokenizer of the field.
+    pub fn add_pre_tokenized_text(&mut self, field: Field, pre_tokenized_text: PreTokenizedString) {
+        self.add(FieldValue::new(field, Value::PreTokStr(pre_tokenized_text)))
+    }
+
     /// Add a field value
     pub fn add(&mut self, field_value: FieldValue) {
         self.field_values.push(field_value);
     }
 
+    /// Replaces the pretokenized texts by their original text,
+    /// which is the version of the document being stored.
+    pub(crate) fn strip_tokens(&mut self) {
+        for field_value in &mut self.field_values {
+            let text = match *field_value.value() {
+                Value::PreTokStr(ref pre_tokenized_text) => pre_tokenized_text.text.clone(),
+                _ => continue,
+            };
+            *field_value = FieldValue::new(field_value.field(), Value::Str(text));
+        }
+    }
+
     /// field_values accessor
     pub fn field_values(&self) -> &[FieldValue] {
         &self.field_values
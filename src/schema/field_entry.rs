@@ -1,3 +1,4 @@
+use schema::BytesOptions;
 use schema::IntOptions;
 use schema::TextOptions;
 
@@ -57,10 +58,10 @@ impl FieldEntry {
     }
 
     /// Creates a field entry for a bytes field
-    pub fn new_bytes(field_name: String) -> FieldEntry {
+    pub fn new_bytes(field_name: String, bytes_options: BytesOptions) -> FieldEntry {
         FieldEntry {
             name: field_name,
-            field_type: FieldType::Bytes,
+            field_type: FieldType::Bytes(bytes_options),
         }
     }
 
@@ -80,7 +81,7 @@ impl FieldEntry {
             FieldType::Str(ref options) => options.get_indexing_options().is_some(),
             FieldType::U64(ref options) | FieldType::I64(ref options) => options.is_indexed(),
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(ref options) => options.is_indexed(),
         }
     }
 
@@ -99,7 +100,7 @@ impl FieldEntry {
             FieldType::Str(ref options) => options.is_stored(),
             // TODO make stored hierarchical facet optional
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(ref options) => options.is_stored(),
         }
     }
 }
@@ -128,8 +129,9 @@ impl Serialize for FieldEntry {
             FieldType::HierarchicalFacet => {
                 s.serialize_field("type", "hierarchical_facet")?;
             }
-            FieldType::Bytes => {
+            FieldType::Bytes(ref options) => {
                 s.serialize_field("type", "bytes")?;
+                s.serialize_field("options", options)?;
             }
         }
 
@@ -186,7 +188,11 @@ impl<'de> Deserialize<'de> for FieldEntry {
                                     field_type = Some(FieldType::HierarchicalFacet);
                                 }
                                 "bytes" => {
-                                    field_type = Some(FieldType::Bytes);
+                                    // Bytes fields serialized without options
+                                    // predate indexed and stored bytes fields:
+                                    // they were fast fields.
+                                    field_type =
+                                        Some(FieldType::Bytes(BytesOptions::default().set_fast()));
                                 }
                                 "text" | "u64" | "i64" => {
                                     // These types require additional options to create a field_type
@@ -205,6 +211,7 @@ impl<'de> Deserialize<'de> for FieldEntry {
                                 "text" => field_type = Some(FieldType::Str(map.next_value()?)),
                                 "u64" => field_type = Some(FieldType::U64(map.next_value()?)),
                                 "i64" => field_type = Some(FieldType::I64(map.next_value()?)),
+                                "bytes" => field_type = Some(FieldType::Bytes(map.next_value()?)),
                                 _ => {
                                     let msg = format!("Unrecognised type {}", ty);
                                     return Err(de::Error::custom(msg));
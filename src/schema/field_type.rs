@@ -1,11 +1,13 @@
 use base64::decode;
 
-use schema::{IntOptions, TextOptions};
+use schema::{BytesOptions, IntOptions, TextOptions};
 
 use schema::Facet;
 use schema::IndexRecordOption;
 use schema::Value;
+use serde_json;
 use serde_json::Value as JsonValue;
+use tokenizer::PreTokenizedString;
 
 /// Possible error that may occur while parsing a field value
 /// At this point the JSON is known to be valid.
@@ -53,7 +55,7 @@ pub enum FieldType {
     /// Hierachical Facet
     HierarchicalFacet,
     /// Bytes (one per document)
-    Bytes,
+    Bytes(BytesOptions),
 }
 
 impl FieldType {
@@ -64,7 +66,7 @@ impl FieldType {
             FieldType::U64(_) => Type::U64,
             FieldType::I64(_) => Type::I64,
             FieldType::HierarchicalFacet => Type::HierarchicalFacet,
-            FieldType::Bytes => Type::Bytes,
+            FieldType::Bytes(_) => Type::Bytes,
         }
     }
 
@@ -76,7 +78,7 @@ impl FieldType {
                 int_options.is_indexed()
             }
             FieldType::HierarchicalFacet => true,
-            FieldType::Bytes => false,
+            FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
         }
     }
 
@@ -97,7 +99,25 @@ impl FieldType {
                 }
             }
             FieldType::HierarchicalFacet => Some(IndexRecordOption::Basic),
-            FieldType::Bytes => None,
+            FieldType::Bytes(ref bytes_options) => {
+                if bytes_options.is_indexed() {
+                    Some(IndexRecordOption::Basic)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns the size of the payload attached to each posting
+    /// of the field, or 0 if the postings do not have any payload.
+    pub(crate) fn posting_payload_size(&self) -> usize {
+        match *self {
+            FieldType::Str(ref text_options) => text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.payload_size())
+                .unwrap_or(0),
+            _ => 0,
         }
     }
 
@@ -106,6 +126,9 @@ impl FieldType {
     /// Tantivy will not try to cast values.
     /// For instance, If the json value is the integer `3` and the
     /// target field is a `Str`, this method will return an Error.
+    ///
+    /// Json objects are accepted for `Str` fields, as the representation
+    /// of a `PreTokenizedString`.
     pub fn value_from_json(&self, json: &JsonValue) -> Result<Value, ValueParsingError> {
         match *json {
             JsonValue::String(ref field_text) => match *self {
@@ -114,7 +137,7 @@ impl FieldType {
                     format!("Expected an integer, got {:?}", json),
                 )),
                 FieldType::HierarchicalFacet => Ok(Value::Facet(Facet::from(field_text))),
-                FieldType::Bytes => decode(field_text).map(Value::Bytes).map_err(|_| {
+                FieldType::Bytes(_) => decode(field_text).map(Value::Bytes).map_err(|_| {
                     ValueParsingError::InvalidBase64(format!(
                         "Expected base64 string, got {:?}",
                         field_text
@@ -138,11 +161,23 @@ impl FieldType {
                         Err(ValueParsingError::OverflowError(msg))
                     }
                 }
-                FieldType::Str(_) | FieldType::HierarchicalFacet | FieldType::Bytes => {
+                FieldType::Str(_) | FieldType::HierarchicalFacet | FieldType::Bytes(_) => {
                     let msg = format!("Expected a string, got {:?}", json);
                     Err(ValueParsingError::TypeError(msg))
                 }
             },
+            JsonValue::Object(_) => match *self {
+                FieldType::Str(_) => serde_json::from_value::<PreTokenizedString>(json.clone())
+                    .map(Value::PreTokStr)
+                    .map_err(|err| {
+                        let msg = format!("Expected a pretokenized text, got {:?}: {}", json, err);
+                        ValueParsingError::TypeError(msg)
+                    }),
+                _ => {
+                    let msg = format!("Expected a string or an integer, got {:?}", json);
+                    Err(ValueParsingError::TypeError(msg))
+                }
+            },
             _ => {
                 let msg = format!(
                     "Json value not supported error {:?}. Expected {:?}",
@@ -158,22 +193,23 @@ impl FieldType {
 mod tests {
     use super::FieldType;
     use schema::field_type::ValueParsingError;
+    use schema::BytesOptions;
     use schema::Value;
 
     #[test]
     fn test_bytes_value_from_json() {
-        let result = FieldType::Bytes
+        let result = FieldType::Bytes(BytesOptions::default())
             .value_from_json(&json!("dGhpcyBpcyBhIHRlc3Q="))
             .unwrap();
         assert_eq!(result, Value::Bytes("this is a test".as_bytes().to_vec()));
 
-        let result = FieldType::Bytes.value_from_json(&json!(521));
+        let result = FieldType::Bytes(BytesOptions::default()).value_from_json(&json!(521));
         match result {
             Err(ValueParsingError::TypeError(_)) => {}
             _ => panic!("Expected parse failure for wrong type"),
         }
 
-        let result = FieldType::Bytes.value_from_json(&json!("-"));
+        let result = FieldType::Bytes(BytesOptions::default()).value_from_json(&json!("-"));
         match result {
             Err(ValueParsingError::InvalidBase64(_)) => {}
             _ => panic!("Expected parse failure for invalid base64"),
@@ -55,6 +55,18 @@ impl IntOptions {
         self
     }
 
+    /// Set the u64 options as not stored.
+    pub(crate) fn unset_stored(mut self) -> IntOptions {
+        self.stored = false;
+        self
+    }
+
+    /// Set the u64 options as not indexed.
+    pub(crate) fn unset_indexed(mut self) -> IntOptions {
+        self.indexed = false;
+        self
+    }
+
     /// Set the u64 options as a single-valued fast field.
     ///
     /// Fast fields are designed for random access.
@@ -105,6 +105,7 @@ mod facet;
 mod schema;
 mod term;
 
+mod bytes_options;
 mod field_entry;
 mod field_type;
 mod field_value;
@@ -113,6 +114,7 @@ mod field;
 mod index_record_option;
 mod int_options;
 mod named_field_document;
+mod payload_token;
 mod text_options;
 mod value;
 
@@ -131,6 +133,9 @@ pub use self::term::Term;
 pub use self::field_entry::FieldEntry;
 pub use self::field_type::{FieldType, Type};
 pub use self::field_value::FieldValue;
+pub use self::payload_token::PayloadToken;
+
+pub use self::bytes_options::BytesOptions;
 
 pub use self::index_record_option::IndexRecordOption;
 pub use self::text_options::TextFieldIndexing;
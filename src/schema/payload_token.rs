@@ -0,0 +1,43 @@
+use common::BinarySerializable;
+use std::io::{self, Read, Write};
+
+/// Token of a pretokenized text, along with the payload to attach
+/// to the posting of its term in the document.
+///
+/// The text of the token is indexed as is, without going through
+/// the tokenizer of the field. If a term appears several times
+/// in a document, the payload of its first occurrence is kept.
+///
+/// See `TextFieldIndexing::set_payload_size`.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct PayloadToken {
+    /// Text of the token
+    pub text: String,
+    /// Payload of the token.
+    ///
+    /// Its length must be the payload size of the field.
+    pub payload: Vec<u8>,
+}
+
+impl PayloadToken {
+    /// Creates a token with the given payload.
+    pub fn new(text: &str, payload: &[u8]) -> PayloadToken {
+        PayloadToken {
+            text: text.to_string(),
+            payload: payload.to_vec(),
+        }
+    }
+}
+
+impl BinarySerializable for PayloadToken {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.text.serialize(writer)?;
+        self.payload.serialize(writer)
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let text = String::deserialize(reader)?;
+        let payload = Vec::<u8>::deserialize(reader)?;
+        Ok(PayloadToken { text, payload })
+    }
+}
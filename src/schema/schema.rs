@@ -4,8 +4,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use super::*;
-use serde::de::{SeqAccess, Visitor};
-use serde::ser::SerializeSeq;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{self, Map as JsonObject, Value as JsonValue};
 use std::fmt;
@@ -33,6 +33,7 @@ use std::fmt;
 pub struct SchemaBuilder {
     fields: Vec<FieldEntry>,
     fields_map: HashMap<String, Field>,
+    aliases: BTreeMap<String, String>,
 }
 
 impl SchemaBuilder {
@@ -97,10 +98,34 @@ impl SchemaBuilder {
 
     /// Adds a fast bytes field to the schema
     pub fn add_bytes_field(&mut self, field_name: &str) -> Field {
-        let field_entry = FieldEntry::new_bytes(field_name.to_string());
+        self.add_bytes_field_with_options(field_name, BytesOptions::default().set_fast())
+    }
+
+    /// Adds a bytes field to the schema.
+    ///
+    /// Depending on the options, the bytes can be indexed as a
+    /// single exact term, stored, and/or available as a fast field.
+    pub fn add_bytes_field_with_options(
+        &mut self,
+        field_name: &str,
+        bytes_options: BytesOptions,
+    ) -> Field {
+        let field_entry = FieldEntry::new_bytes(field_name.to_string(), bytes_options);
         self.add_field(field_entry)
     }
 
+    /// Adds an alias to a field of the schema.
+    ///
+    /// Once the schema is built, the alias can be used in place
+    /// of the `target` field name in `Schema::get_field`, in
+    /// `Schema::parse_document` and in the query parser.
+    ///
+    /// The target field may be added after its alias.
+    /// Aliases are validated when the schema is built.
+    pub fn add_alias(&mut self, alias: &str, target: &str) {
+        self.aliases.insert(alias.to_string(), target.to_string());
+    }
+
     /// Adds a field entry to the schema in build.
     fn add_field(&mut self, field_entry: FieldEntry) -> Field {
         let field = Field(self.fields.len() as u32);
@@ -110,13 +135,47 @@ impl SchemaBuilder {
         field
     }
 
+    /// Resolves the aliases into the fields they target.
+    ///
+    /// Returns an error if an alias has the name of a field,
+    /// or if its target is not a field.
+    fn resolve_aliases(&self) -> Result<BTreeMap<String, Field>, String> {
+        let mut aliases_map = BTreeMap::new();
+        for (alias, target) in &self.aliases {
+            if self.fields_map.contains_key(alias) {
+                return Err(format!(
+                    "The alias {:?} collides with a field of the same name.",
+                    alias
+                ));
+            }
+            let target_field = self.fields_map.get(target).cloned().ok_or_else(|| {
+                format!(
+                    "The alias {:?} targets {:?}, which is not a field.",
+                    alias, target
+                )
+            })?;
+            aliases_map.insert(alias.clone(), target_field);
+        }
+        Ok(aliases_map)
+    }
+
+    fn try_build(self) -> Result<Schema, String> {
+        let aliases = self.resolve_aliases()?;
+        Ok(Schema(Arc::new(InnerSchema {
+            fields: self.fields,
+            fields_map: self.fields_map,
+            aliases,
+        })))
+    }
+
     /// Finalize the creation of a `Schema`
     /// This will consume your `SchemaBuilder`
+    ///
+    /// # Panics
+    /// Panics if an alias has the same name as a field, or if
+    /// the target of an alias is not a field.
     pub fn build(self) -> Schema {
-        Schema(Arc::new(InnerSchema {
-            fields: self.fields,
-            fields_map: self.fields_map,
-        }))
+        self.try_build().unwrap_or_else(|err_msg| panic!("{}", err_msg))
     }
 }
 
@@ -125,6 +184,7 @@ impl Default for SchemaBuilder {
         SchemaBuilder {
             fields: Vec::new(),
             fields_map: HashMap::new(),
+            aliases: BTreeMap::new(),
         }
     }
 }
@@ -132,11 +192,12 @@ impl Default for SchemaBuilder {
 struct InnerSchema {
     fields: Vec<FieldEntry>,
     fields_map: HashMap<String, Field>, // transient
+    aliases: BTreeMap<String, Field>,
 }
 
 impl PartialEq for InnerSchema {
     fn eq(&self, other: &InnerSchema) -> bool {
-        self.fields == other.fields
+        self.fields == other.fields && self.aliases == other.aliases
     }
 }
 
@@ -182,6 +243,24 @@ impl Schema {
         &self.0.fields
     }
 
+    /// Returns a copy of the schema, in which the field entries
+    /// are replaced by `fields`.
+    ///
+    /// The names of the fields are required to be unchanged,
+    /// so that field ids and aliases remain valid.
+    pub(crate) fn with_field_entries(&self, fields: Vec<FieldEntry>) -> Schema {
+        debug_assert!(fields
+            .iter()
+            .zip(self.fields())
+            .all(|(left, right)| left.name() == right.name()));
+        debug_assert_eq!(fields.len(), self.fields().len());
+        Schema(Arc::new(InnerSchema {
+            fields,
+            fields_map: self.0.fields_map.clone(),
+            aliases: self.0.aliases.clone(),
+        }))
+    }
+
     /// Returns the field options associated with a given name.
     ///
     /// # Panics
@@ -191,11 +270,25 @@ impl Schema {
     ///
     /// If panicking is not an option for you,
     /// you may use `get(&self, field_name: &str)`.
+    ///
+    /// Aliases are resolved to the field they target.
     pub fn get_field(&self, field_name: &str) -> Option<Field> {
-        self.0.fields_map.get(field_name).cloned()
+        self.0
+            .fields_map
+            .get(field_name)
+            .or_else(|| self.0.aliases.get(field_name))
+            .cloned()
+    }
+
+    /// Returns the aliases of the schema, associated to the field they target.
+    pub fn aliases(&self) -> &BTreeMap<String, Field> {
+        &self.0.aliases
     }
 
     /// Create a named document off the doc.
+    ///
+    /// Fields are always named after their canonical name,
+    /// never after an alias.
     pub fn to_named_doc(&self, doc: &Document) -> NamedFieldDocument {
         let mut field_map = BTreeMap::new();
         for (field, field_values) in doc.get_sorted_field_values() {
@@ -218,6 +311,10 @@ impl Schema {
     }
 
     /// Build a document object from a json-object.
+    ///
+    /// Fields may be referred to by their name or by one of their aliases.
+    /// If a document refers to the same field under different names,
+    /// the values associated to these names must be the same.
     pub fn parse_document(&self, doc_json: &str) -> Result<Document, DocParsingError> {
         let json_obj: JsonObject<String, JsonValue> =
             serde_json::from_str(doc_json).map_err(|_| {
@@ -230,25 +327,34 @@ impl Schema {
             })?;
 
         let mut doc = Document::default();
+        let mut parsed_fields: HashMap<Field, (&str, Vec<Value>)> = HashMap::new();
         for (field_name, json_value) in json_obj.iter() {
             match self.get_field(field_name) {
                 Some(field) => {
                     let field_entry = self.get_field_entry(field);
                     let field_type = field_entry.field_type();
-                    match *json_value {
-                        JsonValue::Array(ref json_items) => for json_item in json_items {
-                            let value = field_type
-                                .value_from_json(json_item)
-                                .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
-                            doc.add(FieldValue::new(field, value));
-                        },
-                        _ => {
-                            let value = field_type
-                                .value_from_json(json_value)
-                                .map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
-                            doc.add(FieldValue::new(field, value));
+                    let values: Vec<Value> = match *json_value {
+                        JsonValue::Array(ref json_items) => json_items
+                            .iter()
+                            .map(|json_item| field_type.value_from_json(json_item))
+                            .collect::<Result<_, _>>(),
+                        _ => field_type.value_from_json(json_value).map(|value| vec![value]),
+                    }.map_err(|e| DocParsingError::ValueError(field_name.clone(), e))?;
+                    if let Some(&(ref previous_name, ref previous_values)) =
+                        parsed_fields.get(&field)
+                    {
+                        if *previous_values != values {
+                            return Err(DocParsingError::ConflictingAliases(
+                                previous_name.to_string(),
+                                field_name.clone(),
+                            ));
                         }
+                        continue;
                     }
+                    for value in &values {
+                        doc.add(FieldValue::new(field, value.clone()));
+                    }
+                    parsed_fields.insert(field, (field_name.as_str(), values));
                 }
                 None => return Err(DocParsingError::NoSuchFieldInSchema(field_name.clone())),
             }
@@ -262,11 +368,19 @@ impl Serialize for Schema {
     where
         S: Serializer,
     {
-        let mut seq = serializer.serialize_seq(Some(self.0.fields.len()))?;
-        for e in &self.0.fields {
-            seq.serialize_element(e)?;
+        if self.0.aliases.is_empty() {
+            return self.0.fields.serialize(serializer);
         }
-        seq.end()
+        let aliases: BTreeMap<&str, &str> = self
+            .0
+            .aliases
+            .iter()
+            .map(|(alias, &field)| (alias.as_str(), self.get_field_name(field)))
+            .collect();
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("fields", &self.0.fields)?;
+        map.serialize_entry("aliases", &aliases)?;
+        map.end()
     }
 }
 
@@ -291,6 +405,7 @@ impl<'de> Deserialize<'de> for Schema {
                 let mut schema = SchemaBuilder {
                     fields: Vec::with_capacity(seq.size_hint().unwrap_or(0)),
                     fields_map: HashMap::with_capacity(seq.size_hint().unwrap_or(0)),
+                    aliases: BTreeMap::new(),
                 };
 
                 while let Some(value) = seq.next_element()? {
@@ -299,9 +414,32 @@ impl<'de> Deserialize<'de> for Schema {
 
                 Ok(schema.build())
             }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut schema = SchemaBuilder::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "fields" => {
+                            for field_entry in map.next_value::<Vec<FieldEntry>>()? {
+                                schema.add_field(field_entry);
+                            }
+                        }
+                        "aliases" => {
+                            schema.aliases = map.next_value()?;
+                        }
+                        _ => {
+                            return Err(de::Error::unknown_field(&key, &["fields", "aliases"]));
+                        }
+                    }
+                }
+                schema.try_build().map_err(de::Error::custom)
+            }
         }
 
-        deserializer.deserialize_seq(SchemaVisitor)
+        deserializer.deserialize_any(SchemaVisitor)
     }
 }
 
@@ -315,6 +453,9 @@ pub enum DocParsingError {
     ValueError(String, ValueParsingError),
     /// The json-document contains a field that is not declared in the schema.
     NoSuchFieldInSchema(String),
+    /// The json-document refers to the same field under two different names
+    /// (e.g. the field name and one of its aliases) with different values.
+    ConflictingAliases(String, String),
 }
 
 #[cfg(test)]
@@ -539,4 +680,73 @@ mod tests {
             assert_matches!(json_err, Err(NotJSON(_)));
         }
     }
+
+    #[test]
+    pub fn test_schema_aliases() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_alias("body_text", "body");
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let schema = schema_builder.build();
+        assert_eq!(schema.get_field("body"), Some(body));
+        assert_eq!(schema.get_field("body_text"), Some(body));
+        assert_eq!(schema.get_field("title"), Some(title));
+        assert_eq!(schema.get_field("title_text"), None);
+
+        let doc = schema
+            .parse_document(r#"{"title": "my title", "body_text": "my body"}"#)
+            .unwrap();
+        assert_eq!(doc.get_first(body).unwrap().text(), Some("my body"));
+        assert_eq!(
+            schema.to_json(&doc),
+            r#"{"body":["my body"],"title":["my title"]}"#
+        );
+
+        // referring to the same field under both names is fine
+        // as long as the values are the same.
+        let doc = schema
+            .parse_document(r#"{"body": "my body", "body_text": ["my body"]}"#)
+            .unwrap();
+        assert_eq!(doc.get_all(body).len(), 1);
+        let json_err = schema.parse_document(r#"{"body": "my body", "body_text": "other"}"#);
+        assert_matches!(json_err, Err(DocParsingError::ConflictingAliases(_, _)));
+    }
+
+    #[test]
+    pub fn test_schema_aliases_serialization() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("body", STRING);
+        schema_builder.add_alias("body_text", "body");
+        let schema = schema_builder.build();
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        assert_eq!(
+            schema_json,
+            r#"{"fields":[{"name":"body","type":"text","options":{"indexing":{"record":"basic","tokenizer":"raw"},"stored":false}}],"aliases":{"body_text":"body"}}"#
+        );
+        let schema_deser: Schema = serde_json::from_str(&schema_json).unwrap();
+        assert!(schema == schema_deser);
+        assert_eq!(schema_deser.get_field("body_text"), schema.get_field("body"));
+
+        let colliding_alias_json = r#"{"fields":[{"name":"body","type":"text","options":{"indexing":{"record":"basic","tokenizer":"raw"},"stored":false}}],"aliases":{"body":"body"}}"#;
+        assert!(serde_json::from_str::<Schema>(colliding_alias_json).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_schema_alias_colliding_with_field() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_alias("title", "body");
+        schema_builder.build();
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn test_schema_alias_missing_target() {
+        let mut schema_builder = SchemaBuilder::default();
+        schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_alias("body_text", "content");
+        schema_builder.build();
+    }
 }
@@ -109,7 +109,11 @@ impl Term {
         self.0.extend(bytes);
     }
 
-    pub(crate) fn from_field_bytes(field: Field, bytes: &[u8]) -> Term {
+    /// Builds a term given a field, and a byte sequence.
+    ///
+    /// The bytes are used as is: this is the term
+    /// emitted for indexed bytes fields.
+    pub fn from_field_bytes(field: Field, bytes: &[u8]) -> Term {
         let mut term = Term::for_field(field);
         term.set_bytes(bytes);
         term
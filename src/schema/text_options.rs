@@ -31,6 +31,18 @@ impl TextOptions {
         self.indexing = Some(indexing);
         self
     }
+
+    /// Sets the field as not stored.
+    pub(crate) fn unset_stored(mut self) -> TextOptions {
+        self.stored = false;
+        self
+    }
+
+    /// Sets the field as not indexed.
+    pub(crate) fn unset_indexing_options(mut self) -> TextOptions {
+        self.indexing = None;
+        self
+    }
 }
 
 impl Default for TextOptions {
@@ -48,10 +60,24 @@ impl Default for TextOptions {
 /// - the amount of information that should be stored about the presence of a term in a document.
 /// Essentially, should we store the term frequency and/or the positions (See [`IndexRecordOption`](./enum.IndexRecordOption.html)).
 /// - the name of the `Tokenizer` that should be used to process the field.
+/// - the size of the payload attached to each posting, if any.
+/// - the maximum number of occurrences of a term indexed per document, if any.
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct TextFieldIndexing {
     record: IndexRecordOption,
     tokenizer: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    payload_size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_token_occurrences_per_doc: Option<u32>,
+}
+
+#[cfg_attr(
+    feature = "cargo-clippy",
+    allow(clippy::trivially_copy_pass_by_ref)
+)] // required by serde
+fn is_zero(val: &usize) -> bool {
+    *val == 0
 }
 
 impl Default for TextFieldIndexing {
@@ -59,6 +85,8 @@ impl Default for TextFieldIndexing {
         TextFieldIndexing {
             tokenizer: Cow::Borrowed("default"),
             record: IndexRecordOption::Basic,
+            payload_size: 0,
+            max_token_occurrences_per_doc: None,
         }
     }
 }
@@ -89,6 +117,51 @@ impl TextFieldIndexing {
     pub fn index_option(&self) -> IndexRecordOption {
         self.record
     }
+
+    /// Attaches a payload of `payload_size` bytes to each posting,
+    /// that is to each (term, document) pair.
+    ///
+    /// Payloads are supplied with the tokens of a
+    /// [`PayloadToken`](./struct.PayloadToken.html) value, and read back through
+    /// [`Postings::payload`](../postings/trait.Postings.html#method.payload).
+    /// The postings of text values get an all-zero payload.
+    ///
+    /// By default, postings do not have any payload, and
+    /// fields without payloads do not pay any cost for them.
+    pub fn set_payload_size(mut self, payload_size: usize) -> TextFieldIndexing {
+        self.payload_size = payload_size;
+        self
+    }
+
+    /// Returns the size, in bytes, of the payload attached to each posting.
+    ///
+    /// 0 means that the postings do not have any payload.
+    pub fn payload_size(&self) -> usize {
+        self.payload_size
+    }
+
+    /// Only indexes the first `max_occurrences` occurrences of
+    /// each term in a document.
+    ///
+    /// The further occurrences are not recorded in the postings: the
+    /// term frequency saturates at `max_occurrences`, and their positions
+    /// are dropped. This protects the index against documents repeating
+    /// the same term over and over.
+    /// They still count in the number of tokens of the document
+    /// (its fieldnorm), so that such documents keep a low score.
+    ///
+    /// Documents under the limit are indexed exactly as if there
+    /// were no limit. By default, there is no limit.
+    pub fn set_max_token_occurrences_per_doc(mut self, max_occurrences: u32) -> TextFieldIndexing {
+        self.max_token_occurrences_per_doc = Some(max_occurrences);
+        self
+    }
+
+    /// Returns the maximum number of occurrences of a term indexed
+    /// per document, if any.
+    pub fn max_token_occurrences_per_doc(&self) -> Option<u32> {
+        self.max_token_occurrences_per_doc
+    }
 }
 
 /// The field will be untokenized and indexed
@@ -96,6 +169,8 @@ pub const STRING: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("raw"),
         record: IndexRecordOption::Basic,
+        payload_size: 0,
+        max_token_occurrences_per_doc: None,
     }),
     stored: false,
 };
@@ -105,6 +180,8 @@ pub const TEXT: TextOptions = TextOptions {
     indexing: Some(TextFieldIndexing {
         tokenizer: Cow::Borrowed("default"),
         record: IndexRecordOption::WithFreqsAndPositions,
+        payload_size: 0,
+        max_token_occurrences_per_doc: None,
     }),
     stored: false,
 };
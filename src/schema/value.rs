@@ -1,7 +1,10 @@
+use base64::encode;
 use schema::Facet;
+use schema::PayloadToken;
 use serde::de::Visitor;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use tokenizer::PreTokenizedString;
 
 /// Value represents the value of a any field.
 /// It is an enum over all over all of the possible field type.
@@ -17,6 +20,11 @@ pub enum Value {
     Facet(Facet),
     /// Arbitrarily sized byte array
     Bytes(Vec<u8>),
+    /// Pretokenized text, with the payloads of the tokens
+    PayloadTokens(Vec<PayloadToken>),
+    /// Pretokenized text, indexed without going through the tokenizer
+    /// of the field
+    PreTokStr(PreTokenizedString),
 }
 
 impl Serialize for Value {
@@ -29,7 +37,9 @@ impl Serialize for Value {
             Value::U64(u) => serializer.serialize_u64(u),
             Value::I64(u) => serializer.serialize_i64(u),
             Value::Facet(ref facet) => facet.serialize(serializer),
-            Value::Bytes(ref bytes) => serializer.serialize_bytes(bytes),
+            Value::Bytes(ref bytes) => serializer.serialize_str(&encode(bytes)),
+            Value::PayloadTokens(ref tokens) => tokens.serialize(serializer),
+            Value::PreTokStr(ref pre_tokenized_text) => pre_tokenized_text.serialize(serializer),
         }
     }
 }
@@ -77,6 +87,7 @@ impl Value {
     pub fn text(&self) -> Option<&str> {
         match *self {
             Value::Str(ref text) => Some(text),
+            Value::PreTokStr(ref pre_tokenized_text) => Some(&pre_tokenized_text.text),
             _ => None,
         }
     }
@@ -140,17 +151,33 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<Vec<PayloadToken>> for Value {
+    fn from(tokens: Vec<PayloadToken>) -> Value {
+        Value::PayloadTokens(tokens)
+    }
+}
+
+impl From<PreTokenizedString> for Value {
+    fn from(pre_tokenized_text: PreTokenizedString) -> Value {
+        Value::PreTokStr(pre_tokenized_text)
+    }
+}
+
 mod binary_serialize {
     use super::Value;
-    use common::BinarySerializable;
+    use common::{BinarySerializable, VInt};
     use schema::Facet;
+    use schema::PayloadToken;
     use std::io::{self, Read, Write};
+    use tokenizer::{PreTokenizedString, Token};
 
     const TEXT_CODE: u8 = 0;
     const U64_CODE: u8 = 1;
     const I64_CODE: u8 = 2;
     const HIERARCHICAL_FACET_CODE: u8 = 3;
     const BYTES_CODE: u8 = 4;
+    const PAYLOAD_TOKENS_CODE: u8 = 5;
+    const PRE_TOKENIZED_TEXT_CODE: u8 = 6;
 
     impl BinarySerializable for Value {
         fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
@@ -175,6 +202,23 @@ mod binary_serialize {
                     BYTES_CODE.serialize(writer)?;
                     bytes.serialize(writer)
                 }
+                Value::PayloadTokens(ref tokens) => {
+                    PAYLOAD_TOKENS_CODE.serialize(writer)?;
+                    tokens.serialize(writer)
+                }
+                Value::PreTokStr(ref pre_tokenized_text) => {
+                    PRE_TOKENIZED_TEXT_CODE.serialize(writer)?;
+                    pre_tokenized_text.text.serialize(writer)?;
+                    VInt(pre_tokenized_text.tokens.len() as u64).serialize(writer)?;
+                    for token in &pre_tokenized_text.tokens {
+                        VInt(token.offset_from as u64).serialize(writer)?;
+                        VInt(token.offset_to as u64).serialize(writer)?;
+                        VInt(token.position as u64).serialize(writer)?;
+                        token.text.serialize(writer)?;
+                        VInt(token.position_length as u64).serialize(writer)?;
+                    }
+                    Ok(())
+                }
             }
         }
         fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
@@ -194,6 +238,29 @@ mod binary_serialize {
                 }
                 HIERARCHICAL_FACET_CODE => Ok(Value::Facet(Facet::deserialize(reader)?)),
                 BYTES_CODE => Ok(Value::Bytes(Vec::<u8>::deserialize(reader)?)),
+                PAYLOAD_TOKENS_CODE => Ok(Value::PayloadTokens(
+                    Vec::<PayloadToken>::deserialize(reader)?,
+                )),
+                PRE_TOKENIZED_TEXT_CODE => {
+                    let text = String::deserialize(reader)?;
+                    let num_tokens = VInt::deserialize(reader)?.0 as usize;
+                    let mut tokens = Vec::with_capacity(num_tokens);
+                    for _ in 0..num_tokens {
+                        let offset_from = VInt::deserialize(reader)?.0 as usize;
+                        let offset_to = VInt::deserialize(reader)?.0 as usize;
+                        let position = VInt::deserialize(reader)?.0 as usize;
+                        let text = String::deserialize(reader)?;
+                        let position_length = VInt::deserialize(reader)?.0 as usize;
+                        tokens.push(Token {
+                            offset_from,
+                            offset_to,
+                            position,
+                            text,
+                            position_length,
+                        });
+                    }
+                    Ok(Value::PreTokStr(PreTokenizedString { text, tokens }))
+                }
                 _ => Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     format!("No field type is associated with code {:?}", type_code),
@@ -217,7 +217,7 @@ fn select_best_fragment_combination(fragments: &[FragmentCandidate], text: &str)
 /// #  Quand avec mes haleurs ont fini ces tapages,
 /// #  Les Fleuves m'ont laissé descendre où je voulais.
 /// #  "#);
-/// #    index_writer.add_document(doc.clone());
+/// #    index_writer.add_document(doc.clone())?;
 /// #    index_writer.commit()?;
 /// #    let query_parser = QueryParser::for_index(&index, vec![text_field]);
 /// // ...
@@ -505,9 +505,11 @@ Survey in 2016, 2017, and 2018."#;
         {
             // writing the segment
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
-            index_writer.add_document(doc!(text_field => "a"));
-            index_writer.add_document(doc!(text_field => "a"));
-            index_writer.add_document(doc!(text_field => "a b"));
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer.add_document(doc!(text_field => "a")).unwrap();
+            index_writer
+                .add_document(doc!(text_field => "a b"))
+                .unwrap();
             index_writer.commit().unwrap();
             index.load_searchers().unwrap();
         }
@@ -551,7 +553,7 @@ Survey in 2016, 2017, and 2018."#;
             let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
             {
                 let doc = doc ! (text_field => TEST_TEXT);
-                index_writer.add_document(doc);
+                index_writer.add_document(doc).unwrap();
             }
             index_writer.commit().unwrap();
         }
@@ -329,10 +329,10 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => 1u64));
-            index_writer.add_document(doc!(name => 2u64));
-            index_writer.add_document(doc!(name => 10u64));
-            index_writer.add_document(doc!(name => 20u64));
+            index_writer.add_document(doc!(name => 1u64)).unwrap();
+            index_writer.add_document(doc!(name => 2u64)).unwrap();
+            index_writer.add_document(doc!(name => 10u64)).unwrap();
+            index_writer.add_document(doc!(name => 20u64)).unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -367,10 +367,18 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => "hi"));
-            index_writer.add_document(doc!(name => "this is a test"));
-            index_writer.add_document(doc!(name => "some more documents with some word overlap with the other test"));
-            index_writer.add_document(doc!(name => "hello hi goodbye"));
+            index_writer.add_document(doc!(name => "hi")).unwrap();
+            index_writer
+                .add_document(doc!(name => "this is a test"))
+                .unwrap();
+            index_writer
+                .add_document(
+                    doc!(name => "some more documents with some word overlap with the other test"),
+                )
+                .unwrap();
+            index_writer
+                .add_document(doc!(name => "hello hi goodbye"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -405,10 +413,18 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => "hi"));
-            index_writer.add_document(doc!(name => "this is a test"));
-            index_writer.add_document(doc!(name => "some more documents with some word overlap with the other test"));
-            index_writer.add_document(doc!(name => "hello hi goodbye"));
+            index_writer.add_document(doc!(name => "hi")).unwrap();
+            index_writer
+                .add_document(doc!(name => "this is a test"))
+                .unwrap();
+            index_writer
+                .add_document(
+                    doc!(name => "some more documents with some word overlap with the other test"),
+                )
+                .unwrap();
+            index_writer
+                .add_document(doc!(name => "hello hi goodbye"))
+                .unwrap();
             index_writer.commit().unwrap();
         }
 
@@ -443,17 +459,21 @@ mod test {
 
         {
             let mut index_writer = index.writer_with_num_threads(1, 3_000_000).unwrap();
-            index_writer.add_document(doc!(name => 1u64));
-            index_writer.add_document(doc!(name => 2u64));
-            index_writer.add_document(doc!(name => 3u64));
-            index_writer.add_document(doc!(name => 4u64));
+            index_writer.add_document(doc!(name => 1u64)).unwrap();
+            index_writer.add_document(doc!(name => 2u64)).unwrap();
+            index_writer.add_document(doc!(name => 3u64)).unwrap();
+            index_writer.add_document(doc!(name => 4u64)).unwrap();
             index_writer.commit().unwrap();
         }
 
         {
             let mut index_writer2 = index.writer(50_000_000).unwrap();
-            index_writer2.delete_term(Term::from_field_u64(name, 2u64));
-            index_writer2.delete_term(Term::from_field_u64(name, 3u64));
+            index_writer2
+                .delete_term(Term::from_field_u64(name, 2u64))
+                .unwrap();
+            index_writer2
+                .delete_term(Term::from_field_u64(name, 3u64))
+                .unwrap();
 
             // ok, now we should have a deleted doc
             index_writer2.commit().unwrap();
@@ -0,0 +1,132 @@
+use std::io;
+
+/// Compression codec of the blocks of the doc store.
+///
+/// The codec of an index is chosen when the index is created,
+/// and recorded in its `IndexSettings`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoreCompressor {
+    /// Snappy compression.
+    Snappy,
+    /// LZ4 compression.
+    ///
+    /// Only supported if tantivy is compiled with the
+    /// `lz4-compression` feature.
+    Lz4,
+}
+
+impl Default for StoreCompressor {
+    /// Returns LZ4 if tantivy is compiled with the `lz4-compression`
+    /// feature, and Snappy otherwise.
+    fn default() -> StoreCompressor {
+        if cfg!(feature = "lz4") {
+            StoreCompressor::Lz4
+        } else {
+            StoreCompressor::Snappy
+        }
+    }
+}
+
+impl StoreCompressor {
+    /// Returns the name of the codec, as serialized in `meta.json`.
+    pub fn name(self) -> &'static str {
+        match self {
+            StoreCompressor::Snappy => "snappy",
+            StoreCompressor::Lz4 => "lz4",
+        }
+    }
+
+    /// Returns the codec with the given name, if any.
+    pub fn from_name(name: &str) -> Option<StoreCompressor> {
+        match name {
+            "snappy" => Some(StoreCompressor::Snappy),
+            "lz4" => Some(StoreCompressor::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Returns true iff this build of tantivy supports the codec.
+    pub fn is_supported(self) -> bool {
+        match self {
+            StoreCompressor::Snappy => true,
+            StoreCompressor::Lz4 => cfg!(feature = "lz4"),
+        }
+    }
+
+    pub(crate) fn compress(self, uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            StoreCompressor::Snappy => super::compression_snap::compress(uncompressed, compressed),
+            StoreCompressor::Lz4 => lz4_compress(uncompressed, compressed),
+        }
+    }
+
+    pub(crate) fn decompress(
+        self,
+        compressed: &[u8],
+        decompressed: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        match self {
+            StoreCompressor::Snappy => {
+                super::compression_snap::decompress(compressed, decompressed)
+            }
+            StoreCompressor::Lz4 => lz4_decompress(compressed, decompressed),
+        }
+    }
+}
+
+#[cfg(feature = "lz4")]
+fn lz4_compress(uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
+    super::compression_lz4::compress(uncompressed, compressed)
+}
+
+#[cfg(feature = "lz4")]
+fn lz4_decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<()> {
+    super::compression_lz4::decompress(compressed, decompressed)
+}
+
+#[cfg(not(feature = "lz4"))]
+fn lz4_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        "LZ4 compression requires the lz4-compression feature",
+    )
+}
+
+#[cfg(not(feature = "lz4"))]
+fn lz4_compress(_: &[u8], _: &mut Vec<u8>) -> io::Result<()> {
+    Err(lz4_unsupported())
+}
+
+#[cfg(not(feature = "lz4"))]
+fn lz4_decompress(_: &[u8], _: &mut Vec<u8>) -> io::Result<()> {
+    Err(lz4_unsupported())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::StoreCompressor;
+
+    #[test]
+    fn test_store_compressor_roundtrip() {
+        let data: Vec<u8> = (0..10_000).map(|i| (i % 7) as u8).collect();
+        for &compressor in &[StoreCompressor::Snappy, StoreCompressor::Lz4] {
+            let name = compressor.name();
+            assert_eq!(StoreCompressor::from_name(name), Some(compressor));
+            if !compressor.is_supported() {
+                continue;
+            }
+            let mut compressed = Vec::new();
+            compressor.compress(&data[..], &mut compressed).unwrap();
+            assert!(compressed.len() < data.len());
+            let mut decompressed = Vec::new();
+            compressor
+                .decompress(&compressed[..], &mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, data);
+        }
+        assert!(StoreCompressor::default().is_supported());
+        assert_eq!(StoreCompressor::from_name("zstd"), None);
+    }
+}
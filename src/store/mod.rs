@@ -5,8 +5,8 @@ A field needs to be marked as stored in the schema in
 order to be handled in the `Store`.
 
 Internally, documents (or rather their stored fields) are serialized to a buffer.
-When the buffer exceeds 16K, the buffer is compressed using `LZ4`
-and the resulting block is written to disk.
+When the buffer exceeds the block size, 16K by default, the buffer is compressed
+using the `StoreCompressor` of the index and the resulting block is written to disk.
 
 One can then request for a specific `DocId`.
 A skip list helps navigating to the right block,
@@ -33,21 +33,18 @@ and should rely on either
 
 !*/
 
+mod compressors;
 mod reader;
 mod skiplist;
 mod writer;
+pub use self::compressors::StoreCompressor;
 pub use self::reader::StoreReader;
-pub use self::writer::StoreWriter;
+pub use self::writer::{StoreWriter, DEFAULT_STORE_BLOCK_SIZE};
 
 #[cfg(feature = "lz4")]
 mod compression_lz4;
-#[cfg(feature = "lz4")]
-use self::compression_lz4::*;
 
-#[cfg(not(feature = "lz4"))]
 mod compression_snap;
-#[cfg(not(feature = "lz4"))]
-use self::compression_snap::*;
 
 #[cfg(test)]
 pub mod tests {
@@ -77,7 +74,8 @@ pub mod tests {
              laborum.",
         );
         {
-            let mut store_writer = StoreWriter::new(writer);
+            let mut store_writer =
+                StoreWriter::new(writer, StoreCompressor::default(), DEFAULT_STORE_BLOCK_SIZE);
             for i in 0..num_docs {
                 let mut fields: Vec<FieldValue> = Vec::new();
                 {
@@ -106,7 +104,7 @@ pub mod tests {
         let schema = write_lorem_ipsum_store(store_file, 1_000);
         let field_title = schema.get_field("title").unwrap();
         let store_source = directory.open_read(path).unwrap();
-        let store = StoreReader::from_source(store_source);
+        let store = StoreReader::from_source(store_source, StoreCompressor::default());
         for i in 0..1_000 {
             assert_eq!(
                 *store
@@ -130,7 +128,7 @@ mod bench {
     use directory::Directory;
     use directory::RAMDirectory;
     use std::path::Path;
-    use store::StoreReader;
+    use store::{StoreCompressor, StoreReader};
     use test::Bencher;
 
     #[bench]
@@ -150,7 +148,7 @@ mod bench {
         let path = Path::new("store");
         write_lorem_ipsum_store(directory.open_write(path).unwrap(), 1_000);
         let store_source = directory.open_read(path).unwrap();
-        let store = StoreReader::from_source(store_source);
+        let store = StoreReader::from_source(store_source, StoreCompressor::default());
         b.iter(|| {
             store.get(12).unwrap();
         });
@@ -1,36 +1,67 @@
 use Result;
 
-use super::decompress;
 use super::skiplist::SkipList;
+use super::StoreCompressor;
 use common::BinarySerializable;
 use common::VInt;
 use directory::ReadOnlySource;
 use schema::Document;
 use space_usage::StoreSpaceUsage;
-use std::cell::RefCell;
 use std::io;
 use std::mem::size_of;
+use std::sync::Mutex;
 use DocId;
 
+/// Last decompressed block.
+struct CurrentBlock {
+    offset: usize,
+    data: Vec<u8>,
+}
+
+impl Default for CurrentBlock {
+    fn default() -> CurrentBlock {
+        CurrentBlock {
+            offset: usize::max_value(),
+            data: Vec::new(),
+        }
+    }
+}
+
 /// Reads document off tantivy's [`Store`](./index.html)
-#[derive(Clone)]
+///
+/// The `StoreReader` is `Send + Sync`. It keeps the last decompressed
+/// block in a cache protected by a mutex, so concurrent calls to `.get(doc)`
+/// on the same reader are serialized. Cloning a `StoreReader` is cheap,
+/// and the clone gets its own cache.
 pub struct StoreReader {
+    compressor: StoreCompressor,
     data: ReadOnlySource,
     offset_index_source: ReadOnlySource,
-    current_block_offset: RefCell<usize>,
-    current_block: RefCell<Vec<u8>>,
+    current_block: Mutex<CurrentBlock>,
     max_doc: DocId,
 }
 
+impl Clone for StoreReader {
+    fn clone(&self) -> StoreReader {
+        StoreReader {
+            compressor: self.compressor,
+            data: self.data.clone(),
+            offset_index_source: self.offset_index_source.clone(),
+            current_block: Mutex::default(),
+            max_doc: self.max_doc,
+        }
+    }
+}
+
 impl StoreReader {
-    /// Opens a store reader
-    pub fn from_source(data: ReadOnlySource) -> StoreReader {
+    /// Opens a store reader, whose blocks were compressed with `compressor`.
+    pub fn from_source(data: ReadOnlySource, compressor: StoreCompressor) -> StoreReader {
         let (data_source, offset_index_source, max_doc) = split_source(data);
         StoreReader {
+            compressor,
             data: data_source,
             offset_index_source,
-            current_block_offset: RefCell::new(usize::max_value()),
-            current_block: RefCell::new(Vec::new()),
+            current_block: Mutex::default(),
             max_doc,
         }
     }
@@ -57,13 +88,15 @@ impl StoreReader {
         &buffer[..block_len]
     }
 
-    fn read_block(&self, block_offset: usize) -> io::Result<()> {
-        if block_offset != *self.current_block_offset.borrow() {
-            let mut current_block_mut = self.current_block.borrow_mut();
-            current_block_mut.clear();
+    fn read_block(&self, current_block: &mut CurrentBlock, block_offset: usize) -> io::Result<()> {
+        if block_offset != current_block.offset {
+            // the offset is invalidated first, in case decompression fails.
+            current_block.offset = usize::max_value();
+            current_block.data.clear();
             let compressed_block = self.compressed_block(block_offset);
-            decompress(compressed_block, &mut current_block_mut)?;
-            *self.current_block_offset.borrow_mut() = block_offset;
+            self.compressor
+                .decompress(compressed_block, &mut current_block.data)?;
+            current_block.offset = block_offset;
         }
         Ok(())
     }
@@ -71,15 +104,15 @@ impl StoreReader {
     /// Reads a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
-    /// decompressing a LZ4-compressed block.
+    /// decompressing a compressed block.
     ///
     /// It should not be called to score documents
     /// for instance.
     pub fn get(&self, doc_id: DocId) -> Result<Document> {
         let (first_doc_id, block_offset) = self.block_offset(doc_id);
-        self.read_block(block_offset as usize)?;
-        let current_block_mut = self.current_block.borrow_mut();
-        let mut cursor = &current_block_mut[..];
+        let mut current_block = self.current_block.lock()?;
+        self.read_block(&mut current_block, block_offset as usize)?;
+        let mut cursor = &current_block.data[..];
         for _ in first_doc_id..doc_id {
             let doc_length = VInt::deserialize(&mut cursor)?.val() as usize;
             cursor = &cursor[doc_length..];
@@ -89,6 +122,11 @@ impl StoreReader {
         Ok(Document::deserialize(&mut cursor)?)
     }
 
+    /// Returns the sources of the blocks and of their offset index.
+    pub(crate) fn sources(&self) -> [&ReadOnlySource; 2] {
+        [&self.data, &self.offset_index_source]
+    }
+
     /// Summarize total space usage of this store reader.
     pub fn space_usage(&self) -> StoreSpaceUsage {
         StoreSpaceUsage::new(self.data.len(), self.offset_index_source.len())
@@ -1,6 +1,5 @@
-use super::compress;
 use super::skiplist::SkipListBuilder;
-use super::StoreReader;
+use super::{StoreCompressor, StoreReader};
 use common::CountingWriter;
 use common::{BinarySerializable, VInt};
 use directory::WritePtr;
@@ -8,7 +7,8 @@ use schema::Document;
 use std::io::{self, Write};
 use DocId;
 
-const BLOCK_SIZE: usize = 16_384;
+/// Default number of bytes of the uncompressed blocks of the store.
+pub const DEFAULT_STORE_BLOCK_SIZE: usize = 16_384;
 
 /// Write tantivy's [`Store`](./index.html)
 ///
@@ -19,6 +19,8 @@ const BLOCK_SIZE: usize = 16_384;
 /// The skip list index on the other hand, is build in memory.
 ///
 pub struct StoreWriter {
+    compressor: StoreCompressor,
+    block_size: usize,
     doc: DocId,
     offset_index_writer: SkipListBuilder<u64>,
     writer: CountingWriter<WritePtr>,
@@ -30,9 +32,12 @@ impl StoreWriter {
     /// Create a store writer.
     ///
     /// The store writer will writes blocks on disc as
-    /// document are added.
-    pub fn new(writer: WritePtr) -> StoreWriter {
+    /// document are added, once they exceed `block_size` bytes,
+    /// compressed with `compressor`.
+    pub fn new(writer: WritePtr, compressor: StoreCompressor, block_size: usize) -> StoreWriter {
         StoreWriter {
+            compressor,
+            block_size,
             doc: 0,
             offset_index_writer: SkipListBuilder::new(4),
             writer: CountingWriter::wrap(writer),
@@ -54,7 +59,7 @@ impl StoreWriter {
         self.current_block
             .write_all(&self.intermediary_buffer[..])?;
         self.doc += 1;
-        if self.current_block.len() > BLOCK_SIZE {
+        if self.current_block.len() > self.block_size {
             self.write_and_compress_block()?;
         }
         Ok(())
@@ -88,7 +93,8 @@ impl StoreWriter {
 
     fn write_and_compress_block(&mut self) -> io::Result<()> {
         self.intermediary_buffer.clear();
-        compress(&self.current_block[..], &mut self.intermediary_buffer)?;
+        self.compressor
+            .compress(&self.current_block[..], &mut self.intermediary_buffer)?;
         (self.intermediary_buffer.len() as u32).serialize(&mut self.writer)?;
         self.writer.write_all(&self.intermediary_buffer)?;
         self.offset_index_writer
@@ -21,6 +21,7 @@ A second datastructure makes it possible to access a [`TermInfo`](../postings/st
 pub type TermOrdinal = u64;
 
 mod merger;
+mod sorted_keys_automaton;
 mod streamer;
 mod term_info_store;
 mod termdict;
@@ -84,6 +85,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_term_dictionary_get_many() {
+        let mut directory = RAMDirectory::create();
+        let path = PathBuf::from("TermDictionary");
+        {
+            let write = directory.open_write(&path).unwrap();
+            let field_type = FieldType::Str(TEXT);
+            let mut term_dictionary_builder =
+                TermDictionaryBuilder::new(write, &field_type).unwrap();
+            for i in 0u64..1_000u64 {
+                let key = format!("{:05}", i * 3);
+                term_dictionary_builder
+                    .insert(key.as_bytes(), &make_term_info(i))
+                    .unwrap();
+            }
+            term_dictionary_builder.finish().unwrap();
+        }
+        let source = directory.open_read(&path).unwrap();
+        let term_dict: TermDictionary = TermDictionary::from_source(&source);
+        let keys: Vec<String> = vec![2_997u64, 4, 3, 0, 3, 2_998, 5_000]
+            .into_iter()
+            .map(|val| format!("{:05}", val))
+            .collect();
+        let term_infos = term_dict.get_many(&keys);
+        let expected: Vec<Option<TermInfo>> = keys.iter().map(|key| term_dict.get(key)).collect();
+        assert_eq!(term_infos, expected);
+        assert_eq!(
+            term_infos
+                .iter()
+                .map(|term_info_opt| term_info_opt.as_ref().map(|term_info| term_info.doc_freq))
+                .collect::<Vec<_>>(),
+            vec![Some(999), None, Some(1), Some(0), Some(1), None, None]
+        );
+        assert!(term_dict.get_many::<&str>(&[]).is_empty());
+    }
+
     #[test]
     fn test_term_dictionary_simple() {
         let mut directory = RAMDirectory::create();
@@ -138,7 +175,7 @@ mod tests {
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b d f");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().unwrap();
             }
@@ -146,7 +183,7 @@ mod tests {
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "a b c d f");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().unwrap();
             }
@@ -154,7 +191,7 @@ mod tests {
                 {
                     let mut doc = Document::default();
                     doc.add_text(text_field, "e f");
-                    index_writer.add_document(doc);
+                    index_writer.add_document(doc).unwrap();
                 }
                 index_writer.commit().unwrap();
             }
@@ -431,3 +468,59 @@ mod tests {
         assert!(!range.advance());
     }
 }
+
+#[cfg(all(test, feature = "unstable"))]
+mod bench {
+
+    use super::{TermDictionary, TermDictionaryBuilder};
+    use directory::ReadOnlySource;
+    use postings::TermInfo;
+    use schema::{FieldType, STRING};
+    use test::Bencher;
+
+    const NUM_TERMS: u64 = 1_000_000;
+    const NUM_LOOKUPS: u64 = 1_000;
+
+    fn build_term_dictionary() -> TermDictionary {
+        let field_type = FieldType::Str(STRING);
+        let mut term_dictionary_builder =
+            TermDictionaryBuilder::new(Vec::new(), &field_type).unwrap();
+        for i in 0..NUM_TERMS {
+            let key = format!("{:010}", i * 7);
+            let term_info = TermInfo {
+                doc_freq: i as u32,
+                postings_offset: i,
+                positions_idx: i,
+            };
+            term_dictionary_builder
+                .insert(key.as_bytes(), &term_info)
+                .unwrap();
+        }
+        let data = term_dictionary_builder.finish().unwrap();
+        TermDictionary::from_source(&ReadOnlySource::from(data))
+    }
+
+    fn lookup_keys() -> Vec<String> {
+        (0..NUM_LOOKUPS)
+            .map(|i| format!("{:010}", i * 6_991))
+            .collect()
+    }
+
+    #[bench]
+    fn bench_term_dictionary_get(b: &mut Bencher) {
+        let term_dict = build_term_dictionary();
+        let keys = lookup_keys();
+        b.iter(|| {
+            keys.iter()
+                .map(|key| term_dict.get(key))
+                .collect::<Vec<_>>()
+        });
+    }
+
+    #[bench]
+    fn bench_term_dictionary_get_many(b: &mut Bencher) {
+        let term_dict = build_term_dictionary();
+        let keys = lookup_keys();
+        b.iter(|| term_dict.get_many(&keys));
+    }
+}
@@ -0,0 +1,148 @@
+use fst::Automaton;
+
+/// Range of keys sharing a common prefix of length `depth`.
+#[derive(Clone, Debug)]
+pub struct KeyRange {
+    start: usize,
+    end: usize,
+    depth: usize,
+}
+
+/// Automaton matching exactly a sorted, deduplicated list of keys.
+///
+/// Its state is the range of keys that share the prefix read so far,
+/// so that searching the term dictionary with this automaton visits
+/// the common prefixes of the keys only once, and prunes
+/// the branches of the fst that cannot lead to any of the keys.
+pub struct SortedKeysAutomaton<'a> {
+    keys: &'a [&'a [u8]],
+}
+
+impl<'a> SortedKeysAutomaton<'a> {
+    /// Creates an automaton for the given keys.
+    ///
+    /// The keys are required to be sorted and deduplicated.
+    pub fn new(keys: &'a [&'a [u8]]) -> SortedKeysAutomaton<'a> {
+        debug_assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        SortedKeysAutomaton { keys }
+    }
+
+    /// Returns the first ordinal within `[start, end)` of a key
+    /// whose byte at position `depth` is greater or equal to `byte`.
+    ///
+    /// Within such a range, keys of length `depth` come first, and are
+    /// considered lower than any byte.
+    fn lower_bound(&self, start: usize, end: usize, depth: usize, byte: u8) -> usize {
+        let (mut lo, mut hi) = (start, end);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let key = self.keys[mid];
+            if key.len() <= depth || key[depth] < byte {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+impl<'a> Automaton for SortedKeysAutomaton<'a> {
+    type State = Option<KeyRange>;
+
+    fn start(&self) -> Option<KeyRange> {
+        if self.keys.is_empty() {
+            None
+        } else {
+            Some(KeyRange {
+                start: 0,
+                end: self.keys.len(),
+                depth: 0,
+            })
+        }
+    }
+
+    fn is_match(&self, state: &Option<KeyRange>) -> bool {
+        if let Some(ref key_range) = *state {
+            self.keys[key_range.start].len() == key_range.depth
+        } else {
+            false
+        }
+    }
+
+    fn can_match(&self, state: &Option<KeyRange>) -> bool {
+        state.is_some()
+    }
+
+    fn accept(&self, state: &Option<KeyRange>, byte: u8) -> Option<KeyRange> {
+        let key_range = state.as_ref()?;
+        let depth = key_range.depth;
+        let start = self.lower_bound(key_range.start, key_range.end, depth, byte);
+        let end = if byte == u8::max_value() {
+            key_range.end
+        } else {
+            self.lower_bound(start, key_range.end, depth, byte + 1)
+        };
+        if start == end {
+            return None;
+        }
+        Some(KeyRange {
+            start,
+            end,
+            depth: depth + 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::SortedKeysAutomaton;
+    use fst::Automaton;
+
+    fn matches(automaton: &SortedKeysAutomaton, key: &[u8]) -> bool {
+        let mut state = automaton.start();
+        for &byte in key {
+            if !automaton.can_match(&state) {
+                return false;
+            }
+            state = automaton.accept(&state, byte);
+        }
+        automaton.is_match(&state)
+    }
+
+    #[test]
+    fn test_sorted_keys_automaton() {
+        let keys: Vec<&[u8]> = vec![
+            &b""[..],
+            &b"ab"[..],
+            &b"abc"[..],
+            &b"abd"[..],
+            &b"b"[..],
+            &b"b\xff"[..],
+        ];
+        let automaton = SortedKeysAutomaton::new(&keys);
+        for key in &keys {
+            assert!(matches(&automaton, key));
+        }
+        let non_keys: Vec<&[u8]> = vec![
+            &b"a"[..],
+            &b"abcd"[..],
+            &b"ac"[..],
+            &b"b\xfe"[..],
+            &b"c"[..],
+            &b"\xff"[..],
+        ];
+        for key in &non_keys {
+            assert!(!matches(&automaton, key));
+        }
+    }
+
+    #[test]
+    fn test_sorted_keys_automaton_empty() {
+        let keys: Vec<&[u8]> = vec![];
+        let automaton = SortedKeysAutomaton::new(&keys);
+        assert!(!matches(&automaton, b""));
+        assert!(!matches(&automaton, b"a"));
+    }
+}
@@ -1,3 +1,4 @@
+use super::sorted_keys_automaton::SortedKeysAutomaton;
 use super::term_info_store::{TermInfoStore, TermInfoStoreWriter};
 use super::{TermStreamer, TermStreamerBuilder};
 use common::BinarySerializable;
@@ -114,14 +115,11 @@ pub struct TermDictionary {
 impl TermDictionary {
     /// Opens a `TermDictionary` given a data source.
     pub fn from_source(source: &ReadOnlySource) -> Self {
-        let total_len = source.len();
-        let length_offset = total_len - 8;
-        let mut split_len_buffer: &[u8] = &source.as_slice()[length_offset..];
-        let footer_size = u64::deserialize(&mut split_len_buffer)
+        let (body, footer_len_data) = source.clone().split_from_end(8);
+        let footer_size = footer_len_data
+            .read_u64_at(0)
             .expect("Deserializing 8 bytes should always work") as usize;
-        let split_len = length_offset - footer_size;
-        let fst_source = source.slice(0, split_len);
-        let values_source = source.slice(split_len, length_offset);
+        let (fst_source, values_source) = body.split_from_end(footer_size);
         let fst_index = open_fst_index(fst_source);
         TermDictionary {
             fst_index,
@@ -181,7 +179,7 @@ impl TermDictionary {
         true
     }
 
-    /// Returns the number of terms in the dictionary.
+    /// Returns the `TermInfo` associated to a given term ordinal.
     pub fn term_info_from_ord(&self, term_ord: TermOrdinal) -> TermInfo {
         self.term_info_store.get(term_ord)
     }
@@ -192,6 +190,40 @@ impl TermDictionary {
             .map(|term_ord| self.term_info_from_ord(term_ord))
     }
 
+    /// Lookups the values corresponding to a batch of keys.
+    ///
+    /// The keys are sorted and the fst is walked once,
+    /// so that the prefixes shared by several keys are only visited once.
+    /// For large batches, this is faster than calling `.get(...)` for each key.
+    ///
+    /// The returned values are in the same order as `keys`.
+    pub fn get_many<K: AsRef<[u8]>>(&self, keys: &[K]) -> Vec<Option<TermInfo>> {
+        let mut sorted_keys: Vec<&[u8]> = keys.iter().map(|key| key.as_ref()).collect();
+        sorted_keys.sort();
+        sorted_keys.dedup();
+        let mut term_infos: Vec<Option<TermInfo>> = vec![None; sorted_keys.len()];
+        {
+            let mut stream = self
+                .search(SortedKeysAutomaton::new(&sorted_keys))
+                .into_stream();
+            let mut ord = 0;
+            while stream.advance() {
+                // the stream only emits keys of `sorted_keys`, in order.
+                while sorted_keys[ord] != stream.key() {
+                    ord += 1;
+                }
+                term_infos[ord] = Some(stream.value().clone());
+            }
+        }
+        keys.iter()
+            .map(|key| {
+                let ord = sorted_keys
+                    .binary_search(&key.as_ref())
+                    .expect("All keys are in sorted_keys");
+                term_infos[ord].clone()
+            }).collect()
+    }
+
     /// Returns a range builder, to stream all of the terms
     /// within an interval.
     pub fn range(&self) -> TermStreamerBuilder {
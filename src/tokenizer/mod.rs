@@ -137,9 +137,12 @@ mod remove_long;
 mod simple_tokenizer;
 mod stemmer;
 mod stop_word_filter;
+mod text_analyzer;
 mod token_stream_chain;
+mod tokenized_string;
 mod tokenizer;
 mod tokenizer_manager;
+mod validating_tokenizer;
 
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::facet_tokenizer::FacetTokenizer;
@@ -150,12 +153,19 @@ pub use self::remove_long::RemoveLongFilter;
 pub use self::simple_tokenizer::SimpleTokenizer;
 pub use self::stemmer::Stemmer;
 pub use self::stop_word_filter::StopWordFilter;
+pub use self::text_analyzer::{
+    box_token_filter, BoxedTokenFilter, TextAnalyzer, TextAnalyzerBuilder,
+};
 pub(crate) use self::token_stream_chain::TokenStreamChain;
+pub use self::tokenized_string::{PreTokenizedStream, PreTokenizedString};
 pub(crate) use self::tokenizer::box_tokenizer;
 pub use self::tokenizer::BoxedTokenizer;
 
 pub use self::tokenizer::{Token, TokenFilter, TokenStream, Tokenizer};
 pub use self::tokenizer_manager::TokenizerManager;
+pub use self::validating_tokenizer::{
+    validate_tokens, TokenRule, TokenValidationError, ValidatingTokenStream, ValidatingTokenizer,
+};
 
 
 #[cfg(test)]
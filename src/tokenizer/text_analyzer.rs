@@ -0,0 +1,239 @@
+use tokenizer::box_tokenizer;
+use tokenizer::BoxedTokenizer;
+use tokenizer::{TokenFilter, TokenStream, Tokenizer};
+
+/// A boxed token filter.
+///
+/// Unlike `TokenFilter`, this trait is object safe, so that
+/// filters of different types can be chained at runtime.
+pub trait BoxedTokenFilter: Send + Sync {
+    /// Wraps a token stream and returns the modified one.
+    fn transform<'a>(&self, token_stream: Box<TokenStream + 'a>) -> Box<TokenStream + 'a>;
+
+    /// Return a boxed clone of the token filter
+    fn boxed_clone(&self) -> Box<BoxedTokenFilter>;
+}
+
+#[derive(Clone)]
+struct BoxableTokenFilter<F>(F)
+where
+    F: for<'a> TokenFilter<Box<TokenStream + 'a>> + Send + Sync;
+
+impl<F> BoxedTokenFilter for BoxableTokenFilter<F>
+where
+    F: 'static + Send + Sync + for<'a> TokenFilter<Box<TokenStream + 'a>>,
+{
+    fn transform<'a>(&self, token_stream: Box<TokenStream + 'a>) -> Box<TokenStream + 'a> {
+        Box::new(self.0.transform(token_stream))
+    }
+
+    fn boxed_clone(&self) -> Box<BoxedTokenFilter> {
+        Box::new(self.clone())
+    }
+}
+
+/// Boxes a token filter.
+///
+/// All of the token filters of tantivy can be boxed.
+pub fn box_token_filter<F>(token_filter: F) -> Box<BoxedTokenFilter>
+where
+    F: 'static + Send + Sync + for<'a> TokenFilter<Box<TokenStream + 'a>>,
+{
+    Box::new(BoxableTokenFilter(token_filter))
+}
+
+/// `TextAnalyzer` is a tokenizer followed by a chain of
+/// token filters, that can be defined at runtime.
+///
+/// Chaining filters with `Tokenizer::filter` requires the full
+/// chain to be known at compile time. On the other hand,
+/// a `TextAnalyzer` can be built from runtime data, such as a
+/// list of filter names read from a configuration file.
+///
+/// This flexibility comes at the cost of one dynamic call per token
+/// and filter. For the tokenizers of tantivy, prefer chaining filters
+/// statically with `Tokenizer::filter`.
+///
+/// Like any other `Tokenizer`, a `TextAnalyzer` can be registered
+/// in the `TokenizerManager`.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate tantivy;
+///
+/// use tantivy::tokenizer::*;
+///
+/// # fn main() {
+/// let filters: Vec<Box<BoxedTokenFilter>> = vec![
+///     box_token_filter(LowerCaser),
+///     box_token_filter(Stemmer::new()),
+/// ];
+/// let mut builder = TextAnalyzer::builder(SimpleTokenizer);
+/// for filter in filters {
+///     builder = builder.filter_dyn(filter);
+/// }
+/// let analyzer = builder.build();
+/// let mut token_stream = analyzer.token_stream("Happy Tax Payers");
+/// assert_eq!(token_stream.next().unwrap().text, "happi");
+/// # }
+/// ```
+pub struct TextAnalyzer {
+    tokenizer: Box<BoxedTokenizer>,
+    filters: Vec<Box<BoxedTokenFilter>>,
+}
+
+impl TextAnalyzer {
+    /// Creates a builder for a `TextAnalyzer` starting with
+    /// the given tokenizer.
+    pub fn builder<T>(tokenizer: T) -> TextAnalyzerBuilder
+    where
+        T: 'static + Send + Sync + for<'a> Tokenizer<'a>,
+    {
+        TextAnalyzerBuilder {
+            tokenizer: box_tokenizer(tokenizer),
+            filters: Vec::new(),
+        }
+    }
+}
+
+impl Clone for TextAnalyzer {
+    fn clone(&self) -> TextAnalyzer {
+        TextAnalyzer {
+            tokenizer: self.tokenizer.boxed_clone(),
+            filters: self
+                .filters
+                .iter()
+                .map(|filter| filter.boxed_clone())
+                .collect(),
+        }
+    }
+}
+
+impl<'a> Tokenizer<'a> for TextAnalyzer {
+    type TokenStreamImpl = Box<TokenStream + 'a>;
+
+    fn token_stream(&self, text: &'a str) -> Box<TokenStream + 'a> {
+        let mut token_stream = self.tokenizer.token_stream(text);
+        for filter in &self.filters {
+            token_stream = filter.transform(token_stream);
+        }
+        token_stream
+    }
+}
+
+/// Builder for a `TextAnalyzer`.
+///
+/// Filters are applied in the order in which they are added.
+pub struct TextAnalyzerBuilder {
+    tokenizer: Box<BoxedTokenizer>,
+    filters: Vec<Box<BoxedTokenFilter>>,
+}
+
+impl TextAnalyzerBuilder {
+    /// Appends a token filter to the chain.
+    pub fn filter<F>(self, token_filter: F) -> TextAnalyzerBuilder
+    where
+        F: 'static + Send + Sync + for<'a> TokenFilter<Box<TokenStream + 'a>>,
+    {
+        self.filter_dyn(box_token_filter(token_filter))
+    }
+
+    /// Appends a boxed token filter to the chain.
+    pub fn filter_dyn(mut self, token_filter: Box<BoxedTokenFilter>) -> TextAnalyzerBuilder {
+        self.filters.push(token_filter);
+        self
+    }
+
+    /// Builds the `TextAnalyzer`.
+    pub fn build(self) -> TextAnalyzer {
+        TextAnalyzer {
+            tokenizer: self.tokenizer,
+            filters: self.filters,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{box_token_filter, BoxedTokenFilter, TextAnalyzer};
+    use tokenizer::tests::assert_token;
+    use tokenizer::{
+        LowerCaser, SimpleTokenizer, Stemmer, StopWordFilter, Token, TokenStream, Tokenizer,
+        TokenizerManager,
+    };
+
+    fn filter_from_name(name: &str) -> Box<BoxedTokenFilter> {
+        match name {
+            "lowercase" => box_token_filter(LowerCaser),
+            "stop_en" => box_token_filter(StopWordFilter::default()),
+            "stemmer_en" => box_token_filter(Stemmer::new()),
+            _ => panic!("Unknown filter {:?}", name),
+        }
+    }
+
+    fn analyze(tokenizer: &TextAnalyzer, text: &str) -> Vec<Token> {
+        let mut tokens = vec![];
+        tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_text_analyzer_from_filter_names() {
+        let filter_names = vec!["lowercase", "stop_en", "stemmer_en"];
+        let mut builder = TextAnalyzer::builder(SimpleTokenizer);
+        for filter_name in filter_names {
+            builder = builder.filter_dyn(filter_from_name(filter_name));
+        }
+        let analyzer = builder.build();
+        let tokens = analyze(&analyzer, "The Running dogs are JUMPING");
+        assert_eq!(tokens.len(), 3);
+        assert_token(&tokens[0], 1, "run", 4, 11);
+        assert_token(&tokens[1], 2, "dog", 12, 16);
+        assert_token(&tokens[2], 4, "jump", 21, 28);
+
+        // Same chain, defined statically.
+        let static_analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter(LowerCaser)
+            .filter(StopWordFilter::default())
+            .filter(Stemmer::new())
+            .build();
+        let static_tokens = analyze(&static_analyzer, "The Running dogs are JUMPING");
+        let texts = |tokens: &[Token]| -> Vec<String> {
+            tokens.iter().map(|token| token.text.clone()).collect()
+        };
+        assert_eq!(texts(&static_tokens), texts(&tokens));
+    }
+
+    #[test]
+    fn test_text_analyzer_filter_order() {
+        // the stop word filter runs before lowercasing.
+        let analyzer = TextAnalyzer::builder(SimpleTokenizer)
+            .filter_dyn(filter_from_name("stop_en"))
+            .filter_dyn(filter_from_name("lowercase"))
+            .build();
+        let tokens = analyze(&analyzer.clone(), "The fox and The dog");
+        let texts: Vec<&str> = tokens.iter().map(|token| &token.text[..]).collect();
+        assert_eq!(texts, vec!["the", "fox", "the", "dog"]);
+    }
+
+    #[test]
+    fn test_text_analyzer_registered() {
+        let tokenizer_manager = TokenizerManager::default();
+        tokenizer_manager.register(
+            "custom",
+            TextAnalyzer::builder(SimpleTokenizer)
+                .filter_dyn(filter_from_name("lowercase"))
+                .build(),
+        );
+        let tokenizer = tokenizer_manager.get("custom").unwrap();
+        let mut tokens: Vec<String> = vec![];
+        tokenizer
+            .token_stream_texts(&["Hello", "World"])
+            .process(&mut |token: &Token| tokens.push(token.text.clone()));
+        assert_eq!(tokens, vec!["hello".to_string(), "world".to_string()]);
+    }
+}
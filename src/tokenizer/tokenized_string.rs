@@ -0,0 +1,216 @@
+use tokenizer::{Token, TokenStream};
+
+/// Text along with the tokens it was split into, by an external pipeline.
+///
+/// `PreTokenizedString` values are indexed as is, bypassing the
+/// tokenizer of the field. Their tokens must uphold the invariants
+/// of [`Token`](./struct.Token.html): documents with offending tokens
+/// are rejected by the `IndexWriter`.
+/// Several tokens may share the same position, for instance to
+/// inject synonyms.
+///
+/// Only the text is stored.
+///
+/// In JSON documents, a pretokenized text is an object with the
+/// `text` and all of the fields of its `tokens`:
+///
+/// ```json
+/// {
+///     "title": {
+///         "text": "Big cat",
+///         "tokens": [
+///             {"offset_from": 0, "offset_to": 3, "position": 0, "text": "big", "position_length": 1},
+///             {"offset_from": 4, "offset_to": 7, "position": 1, "text": "cat", "position_length": 1}
+///         ]
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct PreTokenizedString {
+    /// Original text
+    pub text: String,
+    /// Tokens of the text
+    pub tokens: Vec<Token>,
+}
+
+impl PreTokenizedString {
+    /// Creates a `PreTokenizedString`.
+    pub fn new(text: &str, tokens: Vec<Token>) -> PreTokenizedString {
+        PreTokenizedString {
+            text: text.to_string(),
+            tokens,
+        }
+    }
+}
+
+/// `TokenStream` emitting the tokens of a `PreTokenizedString`.
+pub struct PreTokenizedStream<'a> {
+    tokens: &'a [Token],
+    cursor: usize,
+    token: Token,
+}
+
+impl<'a> From<&'a PreTokenizedString> for PreTokenizedStream<'a> {
+    fn from(pre_tokenized_string: &'a PreTokenizedString) -> PreTokenizedStream<'a> {
+        PreTokenizedStream {
+            tokens: &pre_tokenized_string.tokens[..],
+            cursor: 0,
+            token: Token::default(),
+        }
+    }
+}
+
+impl<'a> TokenStream for PreTokenizedStream<'a> {
+    fn advance(&mut self) -> bool {
+        if self.cursor >= self.tokens.len() {
+            return false;
+        }
+        let token = &self.tokens[self.cursor];
+        self.token.offset_from = token.offset_from;
+        self.token.offset_to = token.offset_to;
+        self.token.position = token.position;
+        self.token.position_length = token.position_length;
+        self.token.text.clear();
+        self.token.text.push_str(&token.text);
+        self.cursor += 1;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{PreTokenizedStream, PreTokenizedString};
+    use collector::CountCollector;
+    use query::PhraseQuery;
+    use schema::{Document, SchemaBuilder, Term, Value, STORED, TEXT};
+    use tokenizer::{Token, TokenStream};
+    use DocAddress;
+    use Index;
+
+    fn token(offset_from: usize, offset_to: usize, position: usize, text: &str) -> Token {
+        Token {
+            offset_from,
+            offset_to,
+            position,
+            text: text.to_string(),
+            position_length: 1,
+        }
+    }
+
+    #[test]
+    fn test_pre_tokenized_stream() {
+        let pre_tokenized_string = PreTokenizedString::new(
+            "big cat",
+            vec![token(0, 3, 0, "big"), token(4, 7, 1, "cat")],
+        );
+        let mut token_stream = PreTokenizedStream::from(&pre_tokenized_string);
+        let mut tokens = Vec::new();
+        while token_stream.advance() {
+            tokens.push(token_stream.token().clone());
+        }
+        assert_eq!(tokens, pre_tokenized_string.tokens);
+    }
+
+    #[test]
+    fn test_pre_tokenized_text_indexing() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+            // "large" is injected as a synonym of "big", at the same position.
+            let pre_tokenized_text = PreTokenizedString::new(
+                "The Big Cat",
+                vec![
+                    token(0, 3, 0, "the"),
+                    token(4, 7, 1, "big"),
+                    token(4, 7, 1, "large"),
+                    token(8, 11, 2, "cat"),
+                ],
+            );
+            let mut doc = Document::default();
+            doc.add_pre_tokenized_text(title, pre_tokenized_text);
+            index_writer.add_document(doc).unwrap();
+            index_writer
+                .add_document(doc!(title => "a large dog"))
+                .unwrap();
+            index_writer.commit().unwrap();
+        }
+        index.load_searchers().unwrap();
+        let searcher = index.searcher();
+        let count_phrase = |words: &[&str]| {
+            let terms = words
+                .iter()
+                .map(|word| Term::from_field_text(title, word))
+                .collect();
+            let mut count_collector = CountCollector::default();
+            searcher
+                .search(&PhraseQuery::new(terms), &mut count_collector)
+                .unwrap();
+            count_collector.count()
+        };
+        assert_eq!(count_phrase(&["big", "cat"]), 1);
+        assert_eq!(count_phrase(&["large", "cat"]), 1);
+        assert_eq!(count_phrase(&["the", "large"]), 1);
+        assert_eq!(count_phrase(&["big", "large"]), 0);
+        assert_eq!(count_phrase(&["large", "dog"]), 1);
+
+        // Only the text is stored.
+        let doc = searcher.doc(DocAddress(0, 0)).unwrap();
+        assert_eq!(
+            doc.get_first(title),
+            Some(&Value::Str("The Big Cat".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_pre_tokenized_text_invalid_tokens() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        let pre_tokenized_text =
+            PreTokenizedString::new("cat", vec![token(0, 3, 1, "cat"), token(0, 4, 2, "cats")]);
+        let mut doc = Document::default();
+        doc.add_pre_tokenized_text(title, pre_tokenized_text);
+        index_writer.add_document(doc).unwrap();
+        assert!(index_writer.commit().is_err());
+    }
+
+    #[test]
+    fn test_pre_tokenized_text_json() {
+        let mut schema_builder = SchemaBuilder::default();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let doc = schema
+            .parse_document(
+                r#"{
+                    "title": {
+                        "text": "Big cat",
+                        "tokens": [
+                            {"offset_from": 0, "offset_to": 3, "position": 0, "text": "big", "position_length": 1},
+                            {"offset_from": 4, "offset_to": 7, "position": 1, "text": "cat", "position_length": 1}
+                        ]
+                    }
+                }"#,
+            ).unwrap();
+        let expected = PreTokenizedString::new(
+            "Big cat",
+            vec![token(0, 3, 0, "big"), token(4, 7, 1, "cat")],
+        );
+        assert_eq!(doc.get_first(title), Some(&Value::PreTokStr(expected)));
+        assert!(schema
+            .parse_document(r#"{"title": {"text": "Big cat"}}"#)
+            .is_err());
+    }
+}
@@ -4,7 +4,18 @@ use std::borrow::{Borrow, BorrowMut};
 use tokenizer::TokenStreamChain;
 
 /// Token
-#[derive(Debug, Clone)]
+///
+/// The tokens emitted by a `TokenStream` for a given text
+/// must uphold the following invariants:
+///
+/// - `offset_from <= offset_to <= text.len()`,
+/// - `offset_from` and `offset_to` are on char boundaries of the text,
+/// - positions do not decrease from one token to the next.
+///
+/// Tokens breaking them silently corrupt snippets and phrase queries.
+/// They can be checked with the `ValidatingTokenizer`, and are checked
+/// at indexing time in debug builds.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct Token {
     /// Offset (byte index) of the first character of the token.
     /// Offsets shall not be modified by token filters.
@@ -0,0 +1,312 @@
+use std::fmt;
+use tokenizer::{Token, TokenStream, Tokenizer};
+
+/// Rule of the token contract broken by a `Token`.
+///
+/// See [`Token`](./struct.Token.html) for the invariants every
+/// tokenizer must uphold.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TokenRule {
+    /// `offset_from` is greater than `offset_to`.
+    OffsetsNotOrdered,
+    /// `offset_to` is greater than the length of the text.
+    OffsetOutOfBounds,
+    /// One of the offsets is not on a char boundary of the text.
+    OffsetNotOnCharBoundary,
+    /// The position is lower than the position of the previous token.
+    PositionDecreased,
+}
+
+impl fmt::Display for TokenRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match *self {
+            TokenRule::OffsetsNotOrdered => "offset_from must not exceed offset_to",
+            TokenRule::OffsetOutOfBounds => "offset_to must not exceed the length of the text",
+            TokenRule::OffsetNotOnCharBoundary => "offsets must be on char boundaries",
+            TokenRule::PositionDecreased => "positions must not decrease",
+        };
+        f.write_str(description)
+    }
+}
+
+/// Error describing a token breaking the token contract.
+#[derive(Clone, Debug)]
+pub struct TokenValidationError {
+    /// The offending token.
+    pub token: Token,
+    /// The rule broken by the token.
+    pub rule: TokenRule,
+}
+
+impl fmt::Display for TokenValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid token {:?}: {}", self.token, self.rule)
+    }
+}
+
+fn check_token(
+    text: &str,
+    previous_position: Option<usize>,
+    token: &Token,
+) -> Result<(), TokenRule> {
+    if token.offset_from > token.offset_to {
+        return Err(TokenRule::OffsetsNotOrdered);
+    }
+    if token.offset_to > text.len() {
+        return Err(TokenRule::OffsetOutOfBounds);
+    }
+    if !text.is_char_boundary(token.offset_from) || !text.is_char_boundary(token.offset_to) {
+        return Err(TokenRule::OffsetNotOnCharBoundary);
+    }
+    if previous_position.map(|position| token.position < position) == Some(true) {
+        return Err(TokenRule::PositionDecreased);
+    }
+    Ok(())
+}
+
+/// Consumes the token stream obtained from `text`, and checks that
+/// all of its tokens uphold the token contract.
+///
+/// Returns an error identifying the first offending token.
+pub fn validate_tokens(
+    text: &str,
+    token_stream: &mut TokenStream,
+) -> Result<(), TokenValidationError> {
+    let mut previous_position = None;
+    while token_stream.advance() {
+        let token = token_stream.token();
+        check_token(text, previous_position, token).map_err(|rule| TokenValidationError {
+            token: token.clone(),
+            rule,
+        })?;
+        previous_position = Some(token.position);
+    }
+    Ok(())
+}
+
+/// Tokenizer wrapper checking the tokens emitted by the underlying
+/// tokenizer against the token contract.
+///
+/// The token stream panics on the first offending token.
+/// It is meant to be used in the tests of custom tokenizers.
+///
+/// # Example
+///
+/// ```rust
+/// # extern crate tantivy;
+///
+/// use tantivy::tokenizer::*;
+///
+/// # fn main() {
+/// let tokenizer = ValidatingTokenizer::new(SimpleTokenizer.filter(LowerCaser));
+/// let mut token_stream = tokenizer.token_stream("Hello, happy tax payer");
+/// while token_stream.advance() {}
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ValidatingTokenizer<T>(T);
+
+impl<T> ValidatingTokenizer<T> {
+    /// Wraps `tokenizer`.
+    pub fn new(tokenizer: T) -> ValidatingTokenizer<T> {
+        ValidatingTokenizer(tokenizer)
+    }
+}
+
+impl<'a, T: Tokenizer<'a>> Tokenizer<'a> for ValidatingTokenizer<T> {
+    type TokenStreamImpl = ValidatingTokenStream<'a, T::TokenStreamImpl>;
+
+    fn token_stream(&self, text: &'a str) -> Self::TokenStreamImpl {
+        ValidatingTokenStream {
+            text,
+            token_stream: self.0.token_stream(text),
+            previous_position: None,
+        }
+    }
+}
+
+/// Token stream returned by `ValidatingTokenizer`.
+pub struct ValidatingTokenStream<'a, S> {
+    text: &'a str,
+    token_stream: S,
+    previous_position: Option<usize>,
+}
+
+impl<'a, S: TokenStream> TokenStream for ValidatingTokenStream<'a, S> {
+    fn advance(&mut self) -> bool {
+        if !self.token_stream.advance() {
+            return false;
+        }
+        let token = self.token_stream.token();
+        if let Err(rule) = check_token(self.text, self.previous_position, token) {
+            let error = TokenValidationError {
+                token: token.clone(),
+                rule,
+            };
+            panic!("{} (text: {:?})", error, self.text);
+        }
+        self.previous_position = Some(token.position);
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.token_stream.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token_stream.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{validate_tokens, TokenRule, ValidatingTokenizer};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use schema::{IndexRecordOption, SchemaBuilder, TextFieldIndexing, TextOptions};
+    use tokenizer::{NgramTokenizer, Token, TokenStream, Tokenizer, TokenizerManager};
+    use Index;
+
+    /// Token stream emitting a fixed list of tokens.
+    struct VecTokenStream {
+        tokens: Vec<Token>,
+        cursor: usize,
+    }
+
+    impl TokenStream for VecTokenStream {
+        fn advance(&mut self) -> bool {
+            self.cursor += 1;
+            self.cursor <= self.tokens.len()
+        }
+
+        fn token(&self) -> &Token {
+            &self.tokens[self.cursor - 1]
+        }
+
+        fn token_mut(&mut self) -> &mut Token {
+            &mut self.tokens[self.cursor - 1]
+        }
+    }
+
+    fn token(offset_from: usize, offset_to: usize, position: usize) -> Token {
+        Token {
+            offset_from,
+            offset_to,
+            position,
+            text: String::new(),
+            position_length: 1,
+        }
+    }
+
+    fn validate(text: &str, tokens: Vec<Token>) -> Result<(), TokenRule> {
+        let mut token_stream = VecTokenStream { tokens, cursor: 0 };
+        validate_tokens(text, &mut token_stream).map_err(|err| err.rule)
+    }
+
+    fn random_text(rng: &mut StdRng) -> String {
+        let len = rng.gen_range(0, 20);
+        (0..len)
+            .map(|_| {
+                if rng.gen_bool(0.3) {
+                    ' '
+                } else if rng.gen_bool(0.5) {
+                    rng.gen_range(b'a', b'z') as char
+                } else {
+                    rng.gen::<char>()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_tokens() {
+        assert_eq!(
+            validate("ab cd", vec![token(0, 2, 0), token(3, 5, 1)]),
+            Ok(())
+        );
+        assert_eq!(validate("ab", vec![token(0, 2, 0), token(0, 2, 0)]), Ok(()));
+        assert_eq!(
+            validate("ab", vec![token(2, 1, 0)]),
+            Err(TokenRule::OffsetsNotOrdered)
+        );
+        assert_eq!(
+            validate("ab", vec![token(0, 3, 0)]),
+            Err(TokenRule::OffsetOutOfBounds)
+        );
+        assert_eq!(
+            validate("été", vec![token(0, 1, 0)]),
+            Err(TokenRule::OffsetNotOnCharBoundary)
+        );
+        assert_eq!(
+            validate("ab cd", vec![token(0, 2, 1), token(3, 5, 0)]),
+            Err(TokenRule::PositionDecreased)
+        );
+    }
+
+    /// Tokenizer emitting two tokens with decreasing positions.
+    #[derive(Clone)]
+    struct BackwardTokenizer;
+
+    impl<'a> Tokenizer<'a> for BackwardTokenizer {
+        type TokenStreamImpl = VecTokenStream;
+
+        fn token_stream(&self, _text: &'a str) -> VecTokenStream {
+            VecTokenStream {
+                tokens: vec![token(0, 1, 1), token(0, 1, 0)],
+                cursor: 0,
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "positions must not decrease")]
+    fn test_validating_tokenizer_panics() {
+        let tokenizer = ValidatingTokenizer::new(BackwardTokenizer);
+        let mut token_stream = tokenizer.token_stream("ab");
+        while token_stream.advance() {}
+    }
+
+    #[test]
+    fn test_builtin_tokenizers_random_text() {
+        let tokenizer_manager = TokenizerManager::default();
+        let tokenizers: Vec<_> = ["raw", "default", "en_stem"]
+            .iter()
+            .map(|name| tokenizer_manager.get(name).unwrap())
+            .collect();
+        let ngram_tokenizers = vec![
+            ValidatingTokenizer::new(NgramTokenizer::new(1, 3, false)),
+            ValidatingTokenizer::new(NgramTokenizer::new(2, 4, true)),
+        ];
+        let mut rng = StdRng::from_seed([7u8; 32]);
+        for _ in 0..1_000 {
+            let text = random_text(&mut rng);
+            for tokenizer in &tokenizers {
+                if let Err(err) = validate_tokens(&text, &mut tokenizer.token_stream(&text)) {
+                    panic!("{} (text: {:?})", err, text);
+                }
+            }
+            for tokenizer in &ngram_tokenizers {
+                let mut token_stream = tokenizer.token_stream(&text);
+                while token_stream.advance() {}
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_indexing_validates_tokens() {
+        let mut schema_builder = SchemaBuilder::default();
+        let text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("backward")
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let text_field = schema_builder.add_text_field("text", text_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        index.tokenizers().register("backward", BackwardTokenizer);
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        index_writer.add_document(doc!(text_field => "ab")).unwrap();
+        assert!(index_writer.commit().is_err());
+    }
+}
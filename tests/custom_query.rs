@@ -0,0 +1,154 @@
+// # Custom query
+//
+// This test implements a query outside of tantivy, using
+// only its public API: a geo-distance filter matching the documents
+// located within a given radius of a point.
+
+#[macro_use]
+extern crate tantivy;
+
+use tantivy::collector::{CountCollector, TopCollector};
+use tantivy::query::{AllQuery, BitSetDocSet, BooleanQuery, ConstScorer, Occur};
+use tantivy::query::{Query, QueryClone, Scorer, TermQuery, Weight};
+use tantivy::schema::{Field, IndexRecordOption, SchemaBuilder, FAST, STORED, STRING};
+use tantivy::{BitSet, DocAddress, Index, Result, Searcher, SegmentReader, Term};
+
+const EARTH_RADIUS_KM: f64 = 6371f64;
+
+/// Great-circle distance between two `(latitude, longitude)` points, in km.
+fn haversine_distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2f64).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2f64).sin().powi(2);
+    2f64 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Matches the documents within `radius_km` of `center`.
+///
+/// The coordinates are `f64` stored in two `u64` fast fields,
+/// as returned by `f64::to_bits`.
+#[derive(Clone, Debug)]
+struct GeoDistanceQuery {
+    lat_field: Field,
+    lon_field: Field,
+    center: (f64, f64),
+    radius_km: f64,
+}
+
+impl Query for GeoDistanceQuery {
+    fn weight(&self, _searcher: &Searcher, _scoring_enabled: bool) -> Result<Box<Weight>> {
+        Ok(Box::new(GeoDistanceWeight(self.clone())))
+    }
+}
+
+struct GeoDistanceWeight(GeoDistanceQuery);
+
+impl Weight for GeoDistanceWeight {
+    fn scorer(&self, reader: &SegmentReader) -> Result<Box<Scorer>> {
+        let query = &self.0;
+        let lat_reader = reader.fast_field_reader::<u64>(query.lat_field)?;
+        let lon_reader = reader.fast_field_reader::<u64>(query.lon_field)?;
+        let mut matching_docs = BitSet::with_max_value(reader.max_doc());
+        for doc in 0..reader.max_doc() {
+            let location = (
+                f64::from_bits(lat_reader.get(doc)),
+                f64::from_bits(lon_reader.get(doc)),
+            );
+            if haversine_distance(query.center, location) <= query.radius_km {
+                matching_docs.insert(doc);
+            }
+        }
+        Ok(Box::new(ConstScorer::new(BitSetDocSet::from(
+            matching_docs,
+        ))))
+    }
+}
+
+#[test]
+fn test_custom_geo_distance_query() {
+    let mut schema_builder = SchemaBuilder::default();
+    let name_field = schema_builder.add_text_field("name", STRING | STORED);
+    let kind_field = schema_builder.add_text_field("kind", STRING);
+    let lat_field = schema_builder.add_u64_field("lat", FAST);
+    let lon_field = schema_builder.add_u64_field("lon", FAST);
+    let schema = schema_builder.build();
+    let index = Index::create_in_ram(schema);
+    let places = [
+        ("louvre", "museum", 48.8606f64, 2.3376f64),
+        ("orsay", "museum", 48.8600, 2.3266),
+        ("tour eiffel", "monument", 48.8584, 2.2945),
+        ("versailles", "museum", 48.8049, 2.1204),
+        ("tate modern", "museum", 51.5076, -0.0994),
+        ("big ben", "monument", 51.5007, -0.1246),
+    ];
+    {
+        let mut index_writer = index.writer_with_num_threads(1, 40_000_000).unwrap();
+        for (i, &(name, kind, lat, lon)) in places.iter().enumerate() {
+            index_writer
+                .add_document(doc!(
+                    name_field => name,
+                    kind_field => kind,
+                    lat_field => lat.to_bits(),
+                    lon_field => lon.to_bits()
+                ))
+                .unwrap();
+            if i == 2 {
+                index_writer.commit().unwrap();
+            }
+        }
+        index_writer.commit().unwrap();
+    }
+    index.load_searchers().unwrap();
+    let searcher = index.searcher();
+    let name = |doc_address: DocAddress| -> String {
+        let doc = searcher.doc(doc_address).unwrap();
+        doc.get_first(name_field)
+            .unwrap()
+            .text()
+            .unwrap()
+            .to_string()
+    };
+
+    // Within 5km of the Louvre.
+    let near_louvre = GeoDistanceQuery {
+        lat_field,
+        lon_field,
+        center: (48.8606, 2.3376),
+        radius_km: 5f64,
+    };
+    let mut count_collector = CountCollector::default();
+    searcher.search(&near_louvre, &mut count_collector).unwrap();
+    assert_eq!(count_collector.count(), 3);
+    assert_eq!(near_louvre.count(&*searcher).unwrap(), 3);
+
+    // Museums within 5km of the Louvre.
+    let museums_near_louvre = BooleanQuery::from(vec![
+        (
+            Occur::Must,
+            Box::new(TermQuery::new(
+                Term::from_field_text(kind_field, "museum"),
+                IndexRecordOption::Basic,
+            )) as Box<Query>,
+        ),
+        (Occur::Must, near_louvre.box_clone()),
+    ]);
+    let mut top_collector = TopCollector::with_limit(10);
+    searcher
+        .search(&museums_near_louvre, &mut top_collector)
+        .unwrap();
+    let mut names: Vec<String> = top_collector.docs().into_iter().map(&name).collect();
+    names.sort();
+    assert_eq!(names, vec!["louvre".to_string(), "orsay".to_string()]);
+
+    // Places that are not within 5km of the Louvre.
+    let far_from_louvre = BooleanQuery::from(vec![
+        (Occur::Should, Box::new(AllQuery) as Box<Query>),
+        (Occur::MustNot, near_louvre.box_clone()),
+    ]);
+    let mut count_collector = CountCollector::default();
+    searcher
+        .search(&far_from_louvre, &mut count_collector)
+        .unwrap();
+    assert_eq!(count_collector.count(), 3);
+}